@@ -1,3 +1,66 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[path = "src/seed_sources.rs"]
+mod seed_sources;
+
 fn main() {
+  generate_migration_list();
+  check_seed_database();
   tauri_build::build()
 }
+
+/// Release builds embed seed_database.db, so refuse to build with one that is out of
+/// date with its sources (see seed_builder.rs); debug builds only warn
+fn check_seed_database() {
+  let root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+  println!("cargo:rerun-if-changed={}", root.join(seed_sources::SEED_DATABASE_PATH).display());
+  println!("cargo:rerun-if-changed={}", root.join(seed_sources::SEED_MANIFEST_PATH).display());
+  for (dir, _) in seed_sources::SEED_SOURCE_DIRS {
+    println!("cargo:rerun-if-changed={}", root.join(dir).display());
+  }
+  for file in seed_sources::seed_source_files(&root).unwrap_or_default() {
+    println!("cargo:rerun-if-changed={}", file.display());
+  }
+
+  if let Some(reason) = seed_sources::seed_staleness(&root) {
+    let message = format!(
+      "seed_database.db is stale: {}. Rebuild it with `cargo run --bin content-admin -- build-seed`",
+      reason
+    );
+    if env::var("PROFILE").as_deref() == Ok("release") {
+      panic!("{}", message);
+    }
+    println!("cargo:warning={}", message);
+  }
+}
+
+/// Embed every migrations/*.sql file in file name order, so a new migration only needs
+/// to be added to the directory (see migrations.rs)
+fn generate_migration_list() {
+  let migrations_dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("migrations");
+  println!("cargo:rerun-if-changed={}", migrations_dir.display());
+
+  let mut files: Vec<PathBuf> = fs::read_dir(&migrations_dir)
+    .expect("Failed to read migrations directory")
+    .map(|entry| entry.expect("Failed to read migrations directory").path())
+    .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("sql"))
+    .collect();
+  files.sort();
+
+  let mut out = String::from("pub const MIGRATIONS: &[Migration] = &[\n");
+  for path in &files {
+    let version = path.file_stem().unwrap().to_str().expect("Migration file names must be UTF-8");
+    println!("cargo:rerun-if-changed={}", path.display());
+    out.push_str(&format!(
+      "    Migration {{ version: {:?}, sql: include_str!({:?}) }},\n",
+      version,
+      path.display().to_string()
+    ));
+  }
+  out.push_str("];\n");
+
+  fs::write(Path::new(&env::var("OUT_DIR").unwrap()).join("migrations.rs"), out)
+    .expect("Failed to write migration list");
+}
@@ -0,0 +1,22 @@
+-- Encrypted AI provider credentials
+-- API keys are encrypted in the backend and never stored in web storage
+
+CREATE TABLE IF NOT EXISTS ai_provider_credentials (
+    provider TEXT PRIMARY KEY, -- e.g., "claude"
+
+    -- AES-256-GCM encrypted key material
+    ciphertext BLOB NOT NULL,
+    nonce BLOB NOT NULL,
+
+    -- Where the encryption key comes from
+    key_source TEXT NOT NULL CHECK(key_source IN ('machine', 'passphrase')),
+    salt BLOB, -- Argon2 salt, only set for passphrase-derived keys
+
+    key_hint TEXT, -- Last few characters of the API key, for display only
+
+    -- Metadata
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    rotated_at TEXT,
+    last_tested_at TEXT,
+    last_test_ok BOOLEAN
+);
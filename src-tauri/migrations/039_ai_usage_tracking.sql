@@ -0,0 +1,52 @@
+-- AI usage accounting and spending budgets
+-- Every AI call is recorded so rate limits and budgets survive restarts
+
+CREATE TABLE IF NOT EXISTS ai_usage_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER, -- NULL when the call is not tied to a user
+    provider TEXT NOT NULL, -- e.g., "claude"
+    model TEXT NOT NULL,
+
+    -- Token counts from the provider's usage block
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    estimated_cost_usd REAL NOT NULL DEFAULT 0,
+    latency_ms INTEGER NOT NULL DEFAULT 0,
+
+    -- What the call was for, e.g., "lesson:python-01", "puzzle:fizzbuzz", "playground"
+    context TEXT,
+
+    status TEXT NOT NULL CHECK(status IN ('success', 'error', 'rate_limited', 'budget_exceeded')),
+    error TEXT,
+
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_usage_user_date ON ai_usage_log(user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_ai_usage_provider_date ON ai_usage_log(provider, created_at);
+
+-- Per-user limits. user_id 0 holds the default applied to users without their own row.
+CREATE TABLE IF NOT EXISTS ai_usage_budgets (
+    user_id INTEGER PRIMARY KEY,
+    daily_token_limit INTEGER, -- NULL = unlimited
+    daily_cost_limit_usd REAL, -- NULL = unlimited
+    requests_per_minute INTEGER NOT NULL DEFAULT 10,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+INSERT OR IGNORE INTO ai_usage_budgets (user_id, daily_token_limit, daily_cost_limit_usd, requests_per_minute)
+VALUES (0, NULL, NULL, 10);
+
+-- Price list used to estimate spend (USD per million tokens)
+CREATE TABLE IF NOT EXISTS ai_model_pricing (
+    model TEXT PRIMARY KEY,
+    input_cost_per_mtok REAL NOT NULL,
+    output_cost_per_mtok REAL NOT NULL
+);
+
+INSERT OR IGNORE INTO ai_model_pricing (model, input_cost_per_mtok, output_cost_per_mtok) VALUES
+('claude-4-sonnet-20250514', 3.0, 15.0),
+('claude-sonnet-4-20250514', 3.0, 15.0),
+('claude-opus-4-20250514', 15.0, 75.0),
+('claude-3-5-sonnet-20241022', 3.0, 15.0),
+('claude-3-5-haiku-20241022', 0.8, 4.0);
@@ -0,0 +1,18 @@
+-- Solution leak guard events
+-- Records when the AI tutor's answer contained the reference solution, and whether
+-- it was redacted or the learner chose to reveal it (which counts as a solution view)
+
+CREATE TABLE IF NOT EXISTS tutor_solution_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER,
+    context_kind TEXT NOT NULL CHECK(context_kind IN ('lesson', 'puzzle')),
+    context_id TEXT NOT NULL, -- Lesson id or puzzle id
+    language TEXT NOT NULL,
+    action TEXT NOT NULL CHECK(action IN ('redacted', 'revealed')),
+    similarity REAL NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_tutor_solution_events_user ON tutor_solution_events(user_id, context_kind, context_id);
@@ -0,0 +1,36 @@
+-- Offline retrieval index for tutor grounding
+-- BM25 over lesson descriptions, docs, concept implementations and MCQ explanations
+
+-- One row per indexed source (a lesson file, a doc file, an MCQ question...)
+CREATE TABLE IF NOT EXISTS retrieval_sources (
+    source_key TEXT PRIMARY KEY, -- e.g., "lesson:python-05-function-forge.json", "doc:items.md", "mcq:py-var-001", "concept:loops:python"
+    source_type TEXT NOT NULL CHECK(source_type IN ('lesson', 'doc', 'concept', 'mcq')),
+    content_hash TEXT NOT NULL, -- SHA-256 of the source text, used for incremental rebuilds
+    indexed_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Searchable chunks of a source
+CREATE TABLE IF NOT EXISTS retrieval_passages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source_key TEXT NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    language TEXT, -- NULL for language-agnostic content
+    token_count INTEGER NOT NULL,
+
+    FOREIGN KEY (source_key) REFERENCES retrieval_sources(source_key) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_retrieval_passages_source ON retrieval_passages(source_key);
+
+-- Inverted index: term frequency per passage
+CREATE TABLE IF NOT EXISTS retrieval_terms (
+    term TEXT NOT NULL,
+    passage_id INTEGER NOT NULL,
+    tf INTEGER NOT NULL,
+
+    PRIMARY KEY (term, passage_id),
+    FOREIGN KEY (passage_id) REFERENCES retrieval_passages(id) ON DELETE CASCADE
+) WITHOUT ROWID;
+
+CREATE INDEX IF NOT EXISTS idx_retrieval_terms_passage ON retrieval_terms(passage_id);
@@ -0,0 +1,31 @@
+-- AI-generated MCQ drafts
+-- Generated questions wait here until an author approves them into mcq_questions or rejects them
+
+CREATE TABLE IF NOT EXISTS mcq_question_drafts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    batch_id TEXT NOT NULL, -- Groups the drafts produced by one generation request
+
+    -- Same shape as mcq_questions
+    question_text TEXT NOT NULL,
+    explanation TEXT NOT NULL,
+    options TEXT NOT NULL, -- JSON array of 4 strings
+    correct_answer_index INTEGER NOT NULL CHECK(correct_answer_index BETWEEN 0 AND 3),
+    difficulty TEXT NOT NULL CHECK(difficulty IN ('easy', 'medium', 'hard', 'expert')),
+    topic TEXT,
+    language TEXT NOT NULL,
+    tags TEXT, -- JSON array
+
+    -- Where the question came from
+    source_kind TEXT NOT NULL CHECK(source_kind IN ('lesson', 'topic')),
+    source_ref TEXT NOT NULL, -- e.g., "python:5" for a lesson, or the topic text
+    model TEXT NOT NULL,
+
+    -- Review
+    status TEXT NOT NULL DEFAULT 'pending' CHECK(status IN ('pending', 'approved', 'rejected')),
+    review_notes TEXT,
+    approved_question_id TEXT, -- mcq_questions.id once approved
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    reviewed_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_mcq_question_drafts_status ON mcq_question_drafts(status, created_at);
@@ -0,0 +1,8 @@
+-- AI settings
+-- App-wide AI options stored as key/value pairs, e.g. llm_backend = 'claude' | 'mock'
+
+CREATE TABLE IF NOT EXISTS ai_settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
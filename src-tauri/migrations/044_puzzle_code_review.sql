@@ -0,0 +1,6 @@
+-- AI code review of accepted puzzle solutions
+-- The review is stored as JSON (see code_review.rs) with its overall score for sorting
+
+ALTER TABLE user_puzzle_progress ADD COLUMN code_review TEXT;
+ALTER TABLE user_puzzle_progress ADD COLUMN code_review_score REAL;
+ALTER TABLE user_puzzle_progress ADD COLUMN code_reviewed_at TIMESTAMP;
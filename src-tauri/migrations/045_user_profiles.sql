@@ -0,0 +1,8 @@
+-- Profile details for the profile picker, previously kept only in the frontend's localStorage
+-- metadata is a JSON object for frontend-owned settings (active courses, theme, ...)
+
+ALTER TABLE users ADD COLUMN display_name TEXT;
+ALTER TABLE users ADD COLUMN avatar TEXT;
+ALTER TABLE users ADD COLUMN metadata TEXT NOT NULL DEFAULT '{}';
+ALTER TABLE users ADD COLUMN last_active_at TIMESTAMP;
+ALTER TABLE users ADD COLUMN archived_at TIMESTAMP;
@@ -0,0 +1,127 @@
+-- Full-text search over lessons, puzzles, MCQs, playground templates, snippets and projects
+-- One FTS5 table for every content type; triggers on the source tables keep it in sync
+
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    title,
+    body,
+    doc_type UNINDEXED, -- 'lesson', 'puzzle', 'mcq', 'template', 'snippet', 'project'
+    doc_id UNINDEXED, -- id in the source table; lesson passages use their retrieval source_key
+    owner_id UNINDEXED, -- user owning a snippet or project, NULL for shared content
+    language UNINDEXED,
+    tokenize = 'porter unicode61'
+);
+
+-- Puzzles
+CREATE TRIGGER IF NOT EXISTS search_puzzles_insert AFTER INSERT ON puzzles BEGIN
+    INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+    VALUES (new.title, new.description, 'puzzle', new.id, NULL, NULL);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_puzzles_update AFTER UPDATE OF title, description ON puzzles BEGIN
+    DELETE FROM search_index WHERE doc_type = 'puzzle' AND doc_id = old.id;
+    INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+    VALUES (new.title, new.description, 'puzzle', new.id, NULL, NULL);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_puzzles_delete AFTER DELETE ON puzzles BEGIN
+    DELETE FROM search_index WHERE doc_type = 'puzzle' AND doc_id = old.id;
+END;
+
+-- MCQ questions
+CREATE TRIGGER IF NOT EXISTS search_mcq_insert AFTER INSERT ON mcq_questions BEGIN
+    INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+    VALUES (new.question_text, COALESCE(new.explanation, ''), 'mcq', new.id, NULL, new.language);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_mcq_update AFTER UPDATE OF question_text, explanation, language ON mcq_questions BEGIN
+    DELETE FROM search_index WHERE doc_type = 'mcq' AND doc_id = old.id;
+    INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+    VALUES (new.question_text, COALESCE(new.explanation, ''), 'mcq', new.id, NULL, new.language);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_mcq_delete AFTER DELETE ON mcq_questions BEGIN
+    DELETE FROM search_index WHERE doc_type = 'mcq' AND doc_id = old.id;
+END;
+
+-- Playground templates
+CREATE TRIGGER IF NOT EXISTS search_templates_insert AFTER INSERT ON playground_templates BEGIN
+    INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+    VALUES (new.name, COALESCE(new.description, '') || char(10) || new.code, 'template', new.id, NULL, new.language_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_templates_update AFTER UPDATE OF name, description, code, language_id ON playground_templates BEGIN
+    DELETE FROM search_index WHERE doc_type = 'template' AND doc_id = old.id;
+    INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+    VALUES (new.name, COALESCE(new.description, '') || char(10) || new.code, 'template', new.id, NULL, new.language_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_templates_delete AFTER DELETE ON playground_templates BEGIN
+    DELETE FROM search_index WHERE doc_type = 'template' AND doc_id = old.id;
+END;
+
+-- Playground snippets
+CREATE TRIGGER IF NOT EXISTS search_snippets_insert AFTER INSERT ON playground_snippets BEGIN
+    INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+    VALUES (new.name, COALESCE(new.description, '') || char(10) || new.code, 'snippet', new.id, new.user_id, new.language_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_snippets_update AFTER UPDATE OF name, description, code, language_id, user_id ON playground_snippets BEGIN
+    DELETE FROM search_index WHERE doc_type = 'snippet' AND doc_id = old.id;
+    INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+    VALUES (new.name, COALESCE(new.description, '') || char(10) || new.code, 'snippet', new.id, new.user_id, new.language_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_snippets_delete AFTER DELETE ON playground_snippets BEGIN
+    DELETE FROM search_index WHERE doc_type = 'snippet' AND doc_id = old.id;
+END;
+
+-- Playground projects
+CREATE TRIGGER IF NOT EXISTS search_projects_insert AFTER INSERT ON playground_projects BEGIN
+    INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+    VALUES (new.name, COALESCE(new.description, '') || char(10) || new.code, 'project', new.id, new.user_id, new.language_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_projects_update AFTER UPDATE OF name, description, code, language_id, user_id ON playground_projects BEGIN
+    DELETE FROM search_index WHERE doc_type = 'project' AND doc_id = old.id;
+    INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+    VALUES (new.name, COALESCE(new.description, '') || char(10) || new.code, 'project', new.id, new.user_id, new.language_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_projects_delete AFTER DELETE ON playground_projects BEGIN
+    DELETE FROM search_index WHERE doc_type = 'project' AND doc_id = old.id;
+END;
+
+-- Lesson text arrives through the retrieval index (retrieval_commands.rs), which
+-- re-chunks lesson files when they change; passages are replaced, never updated.
+-- Each passage is keyed by its negated id: passage ids only grow (AUTOINCREMENT),
+-- so these never meet the positive rowids given to every other row
+CREATE TRIGGER IF NOT EXISTS search_lessons_insert AFTER INSERT ON retrieval_passages
+WHEN new.source_key LIKE 'lesson:%' BEGIN
+    INSERT INTO search_index (rowid, title, body, doc_type, doc_id, owner_id, language)
+    VALUES (-new.id, new.title, new.content, 'lesson', new.source_key, NULL, new.language);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_lessons_delete AFTER DELETE ON retrieval_passages
+WHEN old.source_key LIKE 'lesson:%' BEGIN
+    DELETE FROM search_index WHERE rowid = -old.id;
+END;
+
+-- Index what already exists
+INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+SELECT title, description, 'puzzle', id, NULL, NULL FROM puzzles;
+
+INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+SELECT question_text, COALESCE(explanation, ''), 'mcq', id, NULL, language FROM mcq_questions;
+
+INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+SELECT name, COALESCE(description, '') || char(10) || code, 'template', id, NULL, language_id FROM playground_templates;
+
+INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+SELECT name, COALESCE(description, '') || char(10) || code, 'snippet', id, user_id, language_id FROM playground_snippets;
+
+INSERT INTO search_index (title, body, doc_type, doc_id, owner_id, language)
+SELECT name, COALESCE(description, '') || char(10) || code, 'project', id, user_id, language_id FROM playground_projects;
+
+INSERT INTO search_index (rowid, title, body, doc_type, doc_id, owner_id, language)
+SELECT -id, title, content, 'lesson', source_key, NULL, language FROM retrieval_passages
+WHERE source_key LIKE 'lesson:%';
@@ -0,0 +1,36 @@
+-- Content versioning: where each shipped content row came from and whether the
+-- user has changed it, so later releases can update rows nobody has edited
+-- (see content_versions.rs)
+
+-- One row per release of shipped content this database has been synced to
+CREATE TABLE IF NOT EXISTS content_versions (
+    version INTEGER PRIMARY KEY AUTOINCREMENT,
+    fingerprint TEXT NOT NULL UNIQUE, -- hash of the migrations and seeds that shipped it
+    updated_rows INTEGER NOT NULL DEFAULT 0,
+    added_rows INTEGER NOT NULL DEFAULT 0,
+    conflicts INTEGER NOT NULL DEFAULT 0,
+    synced_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS content_rows (
+    table_name TEXT NOT NULL,
+    row_id TEXT NOT NULL,
+    origin TEXT NOT NULL CHECK(origin IN ('shipped', 'user')),
+    -- Release the shipped copy below came from; NULL for rows that were never shipped
+    content_version INTEGER REFERENCES content_versions(version),
+    -- Hash of the shipped copy last applied (or kept over); a live row with a different hash was edited
+    shipped_hash TEXT,
+    PRIMARY KEY (table_name, row_id)
+);
+
+-- Shipped changes that were not applied because the user had edited or deleted the row
+CREATE TABLE IF NOT EXISTS content_conflicts (
+    table_name TEXT NOT NULL,
+    row_id TEXT NOT NULL,
+    reason TEXT NOT NULL CHECK(reason IN ('modified', 'deleted')),
+    content_version INTEGER NOT NULL REFERENCES content_versions(version),
+    shipped_hash TEXT NOT NULL,
+    shipped_row TEXT NOT NULL, -- JSON object of the shipped columns
+    detected_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (table_name, row_id)
+);
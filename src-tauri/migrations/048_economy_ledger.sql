@@ -0,0 +1,52 @@
+-- Economy ledger: one row for every change to a gold, gem, dungeon gold or XP balance
+-- Balances stay where gameplay reads them; every change goes through game/ledger.rs,
+-- so each balance equals the sum of its account's entries.
+-- Replaces currency_transactions, which only saw add_currency and spend_currency.
+
+CREATE TABLE IF NOT EXISTS ledger_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    -- gold: user_currency.gold, gems: user_currency.gems,
+    -- dungeon_gold: character_stats.current_gold, xp: user_dungeon_progress.total_xp_earned
+    account TEXT NOT NULL CHECK(account IN ('gold', 'gems', 'dungeon_gold', 'xp')),
+    amount INTEGER NOT NULL, -- positive = earned, negative = spent or lost
+    balance_after INTEGER NOT NULL,
+    source_type TEXT NOT NULL, -- 'combat_victory', 'shop_purchase', 'opening_balance', ...
+    source_id TEXT, -- enemy, item, outcome, achievement... the entry came from
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_entries_user ON ledger_entries(user_id, account, id);
+
+-- Balances a row starts with are its opening entries, whichever code created it
+CREATE TRIGGER IF NOT EXISTS ledger_open_user_currency AFTER INSERT ON user_currency BEGIN
+    INSERT INTO ledger_entries (user_id, account, amount, balance_after, source_type)
+    SELECT new.user_id, 'gold', new.gold, new.gold, 'opening_balance' WHERE new.gold != 0;
+    INSERT INTO ledger_entries (user_id, account, amount, balance_after, source_type)
+    SELECT new.user_id, 'gems', new.gems, new.gems, 'opening_balance' WHERE new.gems != 0;
+END;
+
+CREATE TRIGGER IF NOT EXISTS ledger_open_character_stats AFTER INSERT ON character_stats
+WHEN new.current_gold != 0 BEGIN
+    INSERT INTO ledger_entries (user_id, account, amount, balance_after, source_type)
+    VALUES (new.user_id, 'dungeon_gold', new.current_gold, new.current_gold, 'opening_balance');
+END;
+
+CREATE TRIGGER IF NOT EXISTS ledger_open_dungeon_progress AFTER INSERT ON user_dungeon_progress
+WHEN new.total_xp_earned != 0 BEGIN
+    INSERT INTO ledger_entries (user_id, account, amount, balance_after, source_type)
+    VALUES (new.user_id, 'xp', new.total_xp_earned, new.total_xp_earned, 'opening_balance');
+END;
+
+-- Open the balances of existing players
+INSERT INTO ledger_entries (user_id, account, amount, balance_after, source_type)
+SELECT user_id, 'gold', gold, gold, 'opening_balance' FROM user_currency WHERE gold != 0;
+
+INSERT INTO ledger_entries (user_id, account, amount, balance_after, source_type)
+SELECT user_id, 'gems', gems, gems, 'opening_balance' FROM user_currency WHERE gems != 0;
+
+INSERT INTO ledger_entries (user_id, account, amount, balance_after, source_type)
+SELECT user_id, 'dungeon_gold', current_gold, current_gold, 'opening_balance' FROM character_stats WHERE current_gold != 0;
+
+INSERT INTO ledger_entries (user_id, account, amount, balance_after, source_type)
+SELECT user_id, 'xp', total_xp_earned, total_xp_earned, 'opening_balance' FROM user_dungeon_progress WHERE total_xp_earned != 0;
@@ -0,0 +1,11 @@
+-- Clock settings (see game/clock.rs): how instants are turned into calendar days,
+-- and a development-only offset for testing time-based rules.
+-- At most one row (id = 1); without it the defaults apply.
+
+CREATE TABLE IF NOT EXISTS clock_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    utc_offset_minutes INTEGER, -- NULL: follow the system timezone
+    day_rollover_hour INTEGER NOT NULL DEFAULT 0 CHECK (day_rollover_hour BETWEEN 0 AND 23),
+    time_travel_seconds INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,22 @@
+-- Server-held combat encounters (see game/combat.rs)
+-- The enemy a fight is against is recorded in the session when it starts, so
+-- turns and rewards are worked out from this row rather than from whatever the
+-- client sends. Fights in progress before this migration have no encounter and
+-- have to be started again.
+
+ALTER TABLE dungeon_session ADD COLUMN enemy_id TEXT;
+ALTER TABLE dungeon_session ADD COLUMN enemy_name TEXT;
+ALTER TABLE dungeon_session ADD COLUMN enemy_icon TEXT;
+ALTER TABLE dungeon_session ADD COLUMN is_boss BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE dungeon_session ADD COLUMN enemy_max_health INTEGER;
+ALTER TABLE dungeon_session ADD COLUMN enemy_damage INTEGER;
+ALTER TABLE dungeon_session ADD COLUMN enemy_defense INTEGER;
+
+-- Running totals for the combat log
+ALTER TABLE dungeon_session ADD COLUMN damage_dealt INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE dungeon_session ADD COLUMN damage_taken INTEGER NOT NULL DEFAULT 0;
+
+-- NULL: no fight; 'active': in progress; 'won': enemy down, rewards unclaimed;
+-- 'lost': player down
+ALTER TABLE dungeon_session ADD COLUMN combat_status TEXT
+    CHECK (combat_status IN ('active', 'won', 'lost'));
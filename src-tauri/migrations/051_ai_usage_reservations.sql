@@ -0,0 +1,40 @@
+-- AI calls reserve their rate limit slot before they are made (see ai_usage_commands.rs)
+-- A 'pending' row is written when a call is let through and updated with its
+-- tokens, cost and outcome once the provider answers, so calls made at the same
+-- time all count against the limit.
+
+-- SQLite can't change a CHECK constraint, so the table is recreated
+CREATE TABLE IF NOT EXISTS ai_usage_log_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER, -- NULL when the call is not tied to a user
+    provider TEXT NOT NULL, -- e.g., "claude"
+    model TEXT NOT NULL,
+
+    -- Token counts from the provider's usage block
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    estimated_cost_usd REAL NOT NULL DEFAULT 0,
+    latency_ms INTEGER NOT NULL DEFAULT 0,
+
+    -- What the call was for, e.g., "lesson:python-01", "puzzle:fizzbuzz", "playground"
+    context TEXT,
+
+    status TEXT NOT NULL CHECK(status IN ('pending', 'success', 'error', 'rate_limited', 'budget_exceeded')),
+    error TEXT,
+
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+INSERT INTO ai_usage_log_new
+    (id, user_id, provider, model, input_tokens, output_tokens, estimated_cost_usd, latency_ms,
+     context, status, error, created_at)
+SELECT id, user_id, provider, model, input_tokens, output_tokens, estimated_cost_usd, latency_ms,
+       context, status, error, created_at
+FROM ai_usage_log;
+
+DROP TABLE ai_usage_log;
+
+ALTER TABLE ai_usage_log_new RENAME TO ai_usage_log;
+
+CREATE INDEX IF NOT EXISTS idx_ai_usage_user_date ON ai_usage_log(user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_ai_usage_provider_date ON ai_usage_log(provider, created_at);
@@ -0,0 +1,17 @@
+-- Reckon game days in a named timezone (see game/clock.rs), so daylight saving
+-- changes are followed instead of a fixed offset applying all year.
+-- Whole-hour offsets carry over as the matching Etc/GMT zone (whose sign is
+-- inverted: UTC-5 is Etc/GMT+5); any other offset falls back to the system
+-- timezone.
+
+ALTER TABLE clock_settings ADD COLUMN timezone TEXT; -- IANA name; NULL: follow the system timezone
+
+UPDATE clock_settings
+SET timezone = CASE
+    WHEN utc_offset_minutes = 0 THEN 'UTC'
+    WHEN utc_offset_minutes % 60 = 0 AND utc_offset_minutes BETWEEN -12 * 60 AND 14 * 60 THEN
+        'Etc/GMT' || CASE WHEN utc_offset_minutes > 0 THEN '-' ELSE '+' END || (abs(utc_offset_minutes) / 60)
+END
+WHERE utc_offset_minutes IS NOT NULL;
+
+ALTER TABLE clock_settings DROP COLUMN utc_offset_minutes;
@@ -2,6 +2,8 @@ use rusqlite::{params, Connection, Result};
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 use crate::db;
+use crate::error::AppError;
+use crate::game::ledger::{self, Account, Source};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Achievement {
@@ -80,7 +82,7 @@ pub fn get_achievements(
     category_filter: Option<String>,
     tier_filter: Option<String>,
     completion_filter: Option<String>, // 'all', 'completed', 'locked'
-) -> Result<Vec<AchievementWithProgress>, String> {
+) -> Result<Vec<AchievementWithProgress>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut query = String::from(
@@ -107,7 +109,7 @@ pub fn get_achievements(
 
     query.push_str(" ORDER BY a.category_id, a.display_order");
 
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&query)?;
 
     let achievements = stmt
         .query_map([user_id], |row| {
@@ -152,9 +154,9 @@ pub fn get_achievements(
                 progress,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<Result<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     // Apply completion filter
     let filtered = match completion_filter.as_deref() {
@@ -177,7 +179,7 @@ pub fn get_achievements(
 pub fn get_achievement_stats(
     app: AppHandle,
     user_id: i32,
-) -> Result<AchievementStats, String> {
+) -> Result<AchievementStats, AppError> {
     let conn = db::get_connection(&app)?;
 
     // Total achievements
@@ -187,7 +189,7 @@ pub fn get_achievement_stats(
             [],
             |row| row.get(0),
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     // Completed achievements
     let completed_achievements: i32 = conn
@@ -197,7 +199,7 @@ pub fn get_achievement_stats(
             [user_id],
             |row| row.get(0),
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     // Unviewed achievements (completed but not viewed)
     let unviewed_achievements: i32 = conn
@@ -207,7 +209,7 @@ pub fn get_achievement_stats(
             [user_id],
             |row| row.get(0),
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let completion_percentage = if total_achievements > 0 {
         (completed_achievements as f32 / total_achievements as f32) * 100.0
@@ -228,7 +230,7 @@ pub fn get_achievement_stats(
             [user_id],
             |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     // By tier stats
     let mut stmt = conn
@@ -239,13 +241,13 @@ pub fn get_achievement_stats(
              WHERE p.user_id = ?1 AND p.completed = TRUE
              GROUP BY a.tier",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let tier_stats: Vec<(String, i32)> = stmt
         .query_map([user_id], |row| Ok((row.get(0)?, row.get(1)?)))
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<Result<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let mut by_tier = TierStats {
         bronze_completed: 0,
@@ -285,49 +287,62 @@ pub fn update_achievement_progress(
     user_id: i32,
     tracking_key: String,
     increment: i32,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, AppError> {
     let mut conn = db::get_connection(&app)?;
-    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let tx = conn.transaction()?;
+    let newly_completed = record_progress(&tx, user_id, &tracking_key, increment)?;
+    tx.commit()?;
 
+    Ok(newly_completed)
+}
+
+/// Add `increment` to a tracked stat and complete any count achievements it now
+/// satisfies. Returns the ids of achievements completed by this update.
+pub fn record_progress(
+    conn: &Connection,
+    user_id: i32,
+    tracking_key: &str,
+    increment: i32,
+) -> Result<Vec<String>, AppError> {
     // Update the user_achievement_stats table
     let update_query = format!(
         "UPDATE user_achievement_stats SET {} = {} + ?1, updated_at = CURRENT_TIMESTAMP WHERE user_id = ?2",
         tracking_key, tracking_key
     );
 
-    tx.execute(&update_query, params![increment, user_id])
-        .map_err(|e| e.to_string())?;
+    conn.execute(&update_query, params![increment, user_id])
+        ?;
 
     // Get the new value
-    let new_value: i32 = tx
+    let new_value: i32 = conn
         .query_row(
             &format!("SELECT {} FROM user_achievement_stats WHERE user_id = ?1", tracking_key),
             [user_id],
             |row| row.get(0),
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     // Find achievements that use this tracking key
-    let mut stmt = tx
+    let mut stmt = conn
         .prepare(
             "SELECT id, requirement_value FROM achievements
              WHERE tracking_key = ?1 AND requirement_type = 'count'",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let achievements: Vec<(String, i32)> = stmt
-        .query_map([&tracking_key], |row| Ok((row.get(0)?, row.get(1)?)))
-        .map_err(|e| e.to_string())?
+        .query_map([tracking_key], |row| Ok((row.get(0)?, row.get(1)?)))
+        ?
         .collect::<Result<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
-    drop(stmt); // Explicitly drop the statement before using tx again
+    drop(stmt); // Explicitly drop the statement before using conn again
 
     let mut newly_completed = Vec::new();
 
     for (achievement_id, requirement_value) in achievements {
         // Check if already completed
-        let already_completed: bool = tx
+        let already_completed: bool = conn
             .query_row(
                 "SELECT COALESCE(completed, FALSE) FROM user_achievement_progress
                  WHERE user_id = ?1 AND achievement_id = ?2",
@@ -338,7 +353,7 @@ pub fn update_achievement_progress(
 
         if !already_completed {
             // Insert or update progress
-            tx.execute(
+            conn.execute(
                 "INSERT INTO user_achievement_progress (user_id, achievement_id, current_progress, completed, last_updated_at)
                  VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)
                  ON CONFLICT (user_id, achievement_id) DO UPDATE SET
@@ -347,33 +362,31 @@ pub fn update_achievement_progress(
                  last_updated_at = CURRENT_TIMESTAMP",
                 params![user_id, &achievement_id, new_value, new_value >= requirement_value],
             )
-            .map_err(|e| e.to_string())?;
+            ?;
 
             // Check if just completed
             if new_value >= requirement_value {
                 // Mark as completed and set completion time
-                tx.execute(
+                conn.execute(
                     "UPDATE user_achievement_progress
                      SET completed = TRUE, completed_at = CURRENT_TIMESTAMP, times_completed = times_completed + 1
                      WHERE user_id = ?1 AND achievement_id = ?2",
                     params![user_id, &achievement_id],
                 )
-                .map_err(|e| e.to_string())?;
+                ?;
 
                 // Create notification
-                tx.execute(
+                conn.execute(
                     "INSERT INTO achievement_notifications (user_id, achievement_id) VALUES (?1, ?2)",
                     params![user_id, &achievement_id],
                 )
-                .map_err(|e| e.to_string())?;
+                ?;
 
                 newly_completed.push(achievement_id);
             }
         }
     }
 
-    tx.commit().map_err(|e| e.to_string())?;
-
     Ok(newly_completed)
 }
 
@@ -382,7 +395,7 @@ pub fn update_achievement_progress(
 pub fn get_pending_achievement_notifications(
     app: AppHandle,
     user_id: i32,
-) -> Result<Vec<AchievementNotification>, String> {
+) -> Result<Vec<AchievementNotification>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn
@@ -401,7 +414,7 @@ pub fn get_pending_achievement_notifications(
              ORDER BY n.created_at ASC
              LIMIT 5",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let notifications = stmt
         .query_map([user_id], |row| {
@@ -432,9 +445,9 @@ pub fn get_pending_achievement_notifications(
                 },
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<Result<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(notifications)
 }
@@ -444,14 +457,14 @@ pub fn get_pending_achievement_notifications(
 pub fn mark_achievement_notification_shown(
     app: AppHandle,
     notification_id: i32,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
         "UPDATE achievement_notifications SET shown = TRUE, shown_at = CURRENT_TIMESTAMP WHERE id = ?1",
         [notification_id],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(())
 }
@@ -462,9 +475,9 @@ pub fn claim_achievement_rewards(
     app: AppHandle,
     user_id: i32,
     achievement_id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut conn = db::get_connection(&app)?;
-    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let tx = conn.transaction()?;
 
     // Check if already claimed
     let already_claimed: bool = tx
@@ -473,10 +486,10 @@ pub fn claim_achievement_rewards(
             params![user_id, &achievement_id],
             |row| row.get(0),
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     if already_claimed {
-        return Err("Rewards already claimed".to_string());
+        return Err(AppError::Validation("Rewards already claimed".to_string()));
     }
 
     // Get achievement rewards
@@ -486,26 +499,19 @@ pub fn claim_achievement_rewards(
             [&achievement_id],
             |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     // Add XP (assuming there's a user stats table)
     // TODO: Add XP to user stats
 
     // Add currency
+    let source = Source::with_id("achievement_reward", &achievement_id);
     if rewards.1 > 0 {
-        tx.execute(
-            "UPDATE user_currency SET gold = gold + ?1, lifetime_gold_earned = lifetime_gold_earned + ?1 WHERE user_id = ?2",
-            params![rewards.1, user_id],
-        )
-        .map_err(|e| e.to_string())?;
+        ledger::credit(&tx, user_id.into(), Account::Gold, rewards.1.into(), source)?;
     }
 
     if rewards.2 > 0 {
-        tx.execute(
-            "UPDATE user_currency SET gems = gems + ?1, lifetime_gems_earned = lifetime_gems_earned + ?1 WHERE user_id = ?2",
-            params![rewards.2, user_id],
-        )
-        .map_err(|e| e.to_string())?;
+        ledger::credit(&tx, user_id.into(), Account::Gems, rewards.2.into(), source)?;
     }
 
     // Mark rewards as claimed
@@ -514,9 +520,9 @@ pub fn claim_achievement_rewards(
          VALUES (?1, ?2, ?3, ?4, ?5, FALSE)",
         params![user_id, &achievement_id, rewards.0, rewards.1, rewards.2],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
-    tx.commit().map_err(|e| e.to_string())?;
+    tx.commit()?;
 
     Ok(())
 }
@@ -527,32 +533,32 @@ pub fn mark_achievements_as_viewed(
     app: AppHandle,
     user_id: i32,
     achievement_ids: Option<Vec<String>>,
-) -> Result<(), String> {
-    let conn = db::get_connection(&app)?;
-
-    if let Some(ids) = achievement_ids {
-        // Mark specific achievements as viewed
-        for achievement_id in ids {
+) -> Result<(), AppError> {
+    db::unit_of_work(&app, |conn| {
+        if let Some(ids) = achievement_ids {
+            // Mark specific achievements as viewed
+            for achievement_id in ids {
+                conn.execute(
+                    "UPDATE user_achievement_progress
+                     SET viewed_at = CURRENT_TIMESTAMP
+                     WHERE user_id = ?1 AND achievement_id = ?2 AND completed = TRUE AND viewed_at IS NULL",
+                    params![user_id, &achievement_id],
+                )
+                ?;
+            }
+        } else {
+            // Mark all completed achievements as viewed
             conn.execute(
                 "UPDATE user_achievement_progress
                  SET viewed_at = CURRENT_TIMESTAMP
-                 WHERE user_id = ?1 AND achievement_id = ?2 AND completed = TRUE AND viewed_at IS NULL",
-                params![user_id, &achievement_id],
+                 WHERE user_id = ?1 AND completed = TRUE AND viewed_at IS NULL",
+                [user_id],
             )
-            .map_err(|e| e.to_string())?;
+            ?;
         }
-    } else {
-        // Mark all completed achievements as viewed
-        conn.execute(
-            "UPDATE user_achievement_progress
-             SET viewed_at = CURRENT_TIMESTAMP
-             WHERE user_id = ?1 AND completed = TRUE AND viewed_at IS NULL",
-            [user_id],
-        )
-        .map_err(|e| e.to_string())?;
-    }
 
-    Ok(())
+        Ok(())
+    })
 }
 
 /// Initialize achievement stats for a user
@@ -563,3 +569,49 @@ pub fn initialize_achievement_stats(conn: &Connection, user_id: i32) -> Result<(
     )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{scalar, seeded_db, TestUser};
+
+    #[test]
+    fn reaching_a_count_completes_the_achievement_once() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("solver").create(&conn) as i32;
+
+        let completed = record_progress(&conn, user_id, "puzzles_solved", 9).unwrap();
+        assert!(completed.is_empty());
+
+        let completed = record_progress(&conn, user_id, "puzzles_solved", 1).unwrap();
+        assert_eq!(completed, vec!["puzzles-bronze".to_string()]);
+
+        let completed = record_progress(&conn, user_id, "puzzles_solved", 1).unwrap();
+        assert!(completed.is_empty());
+
+        let notifications: i64 = scalar(
+            &conn,
+            "SELECT COUNT(*) FROM achievement_notifications WHERE user_id = ? AND achievement_id = 'puzzles-bronze'",
+            [user_id],
+        );
+        let progress: i64 = scalar(
+            &conn,
+            "SELECT current_progress FROM user_achievement_progress WHERE user_id = ? AND achievement_id = 'puzzles-silver'",
+            [user_id],
+        );
+        assert_eq!(notifications, 1);
+        assert_eq!(progress, 11);
+    }
+
+    #[test]
+    fn progress_is_tracked_per_user() {
+        let conn = seeded_db();
+        let first = TestUser::new("first").create(&conn) as i32;
+        let second = TestUser::new("second").create(&conn) as i32;
+
+        record_progress(&conn, first, "puzzles_solved", 10).unwrap();
+
+        let completed = record_progress(&conn, second, "puzzles_solved", 1).unwrap();
+        assert!(completed.is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+use reqwest::Client;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::credentials;
+use crate::db;
+use crate::error::{AppError, ResultExt};
+
+// ============================================================================
+// AI Provider Credential Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AiKeyStatus {
+    pub provider: String,
+    pub configured: bool,
+    pub key_source: Option<String>, // "machine" or "passphrase"
+    pub locked: bool,               // Passphrase-protected and not unlocked this session
+    pub key_hint: Option<String>,
+    pub created_at: Option<String>,
+    pub rotated_at: Option<String>,
+    pub last_tested_at: Option<String>,
+    pub last_test_ok: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AiKeyTestResult {
+    pub ok: bool,
+    pub message: String,
+}
+
+fn load_key_status(app: &AppHandle, provider: &str) -> Result<AiKeyStatus, AppError> {
+    credentials::validate_provider(provider)?;
+    let conn = db::get_connection(app)?;
+
+    let row = conn
+        .query_row(
+            "SELECT key_source, key_hint, created_at, rotated_at, last_tested_at, last_test_ok
+             FROM ai_provider_credentials WHERE provider = ?1",
+            params![provider],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<bool>>(5)?,
+                ))
+            },
+        )
+        .optional()
+        .context("Failed to load key status")?;
+
+    Ok(match row {
+        Some((key_source, key_hint, created_at, rotated_at, last_tested_at, last_test_ok)) => AiKeyStatus {
+            provider: provider.to_string(),
+            configured: true,
+            locked: key_source == credentials::KEY_SOURCE_PASSPHRASE && !credentials::is_unlocked(provider),
+            key_source: Some(key_source),
+            key_hint,
+            created_at: Some(created_at),
+            rotated_at,
+            last_tested_at,
+            last_test_ok,
+        },
+        None => AiKeyStatus {
+            provider: provider.to_string(),
+            configured: false,
+            key_source: None,
+            locked: false,
+            key_hint: None,
+            created_at: None,
+            rotated_at: None,
+            last_tested_at: None,
+            last_test_ok: None,
+        },
+    })
+}
+
+/// Treat blank passphrases from the UI as "no passphrase"
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.is_empty())
+}
+
+// ============================================================================
+// AI Provider Credential Commands
+// ============================================================================
+
+/// Get whether a provider key is configured, without revealing the key
+#[tauri::command]
+pub fn get_ai_provider_key_status(app: AppHandle, provider: String) -> Result<AiKeyStatus, AppError> {
+    load_key_status(&app, &provider)
+}
+
+/// Encrypt and store an API key. Without a passphrase the machine-local secret is used.
+#[tauri::command]
+pub fn set_ai_provider_key(
+    app: AppHandle,
+    provider: String,
+    api_key: String,
+    passphrase: Option<String>,
+) -> Result<AiKeyStatus, AppError> {
+    let conn = db::get_connection(&app)?;
+    let secret_dir = db::get_app_data_dir(&app)?;
+
+    credentials::store_api_key(
+        &conn,
+        &secret_dir,
+        &provider,
+        &api_key,
+        non_empty(passphrase).as_deref(),
+        false,
+    )?;
+
+    log::info!("Stored encrypted API key for provider {}", provider);
+    load_key_status(&app, &provider)
+}
+
+/// Unlock a passphrase-protected key for the rest of this session
+#[tauri::command]
+pub fn unlock_ai_provider_key(app: AppHandle, provider: String, passphrase: String) -> Result<AiKeyStatus, AppError> {
+    credentials::validate_provider(&provider)?;
+    let conn = db::get_connection(&app)?;
+    credentials::unlock_with_passphrase(&conn, &provider, &passphrase)?;
+    load_key_status(&app, &provider)
+}
+
+/// Check the stored key against the provider API and record the result
+#[tauri::command]
+pub async fn test_ai_provider_key(app: AppHandle, provider: String) -> Result<AiKeyTestResult, AppError> {
+    credentials::validate_provider(&provider)?;
+    let api_key = credentials::get_api_key(&app, &provider)?;
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .context("Failed to build HTTP client")?;
+
+    let result = match client
+        .get("https://api.anthropic.com/v1/models")
+        .header("x-api-key", api_key)
+        .header("anthropic-version", "2023-06-01")
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => AiKeyTestResult {
+            ok: true,
+            message: "API key is valid".to_string(),
+        },
+        Ok(response) if response.status().as_u16() == 401 || response.status().as_u16() == 403 => AiKeyTestResult {
+            ok: false,
+            message: "API key was rejected by the provider".to_string(),
+        },
+        Ok(response) => AiKeyTestResult {
+            ok: false,
+            message: format!("Provider returned status {}", response.status()),
+        },
+        Err(e) => {
+            // Network failures say nothing about the key itself
+            return Err(AppError::Ai(format!("Failed to reach provider: {}", e)));
+        }
+    };
+
+    let conn = db::get_connection(&app)?;
+    conn.execute(
+        "UPDATE ai_provider_credentials SET last_tested_at = datetime('now'), last_test_ok = ?1 WHERE provider = ?2",
+        params![result.ok, provider],
+    )
+    .context("Failed to record key test")?;
+
+    Ok(result)
+}
+
+/// Re-encrypt a provider key with fresh key material.
+/// Optionally replaces the API key itself and/or switches to a new passphrase.
+/// The current protection is kept unless a new passphrase is given or
+/// `remove_passphrase` asks to switch to the machine-local secret.
+#[tauri::command]
+pub fn rotate_ai_provider_key(
+    app: AppHandle,
+    provider: String,
+    new_api_key: Option<String>,
+    current_passphrase: Option<String>,
+    new_passphrase: Option<String>,
+    remove_passphrase: Option<bool>,
+) -> Result<AiKeyStatus, AppError> {
+    let conn = db::get_connection(&app)?;
+    let secret_dir = db::get_app_data_dir(&app)?;
+
+    let new_passphrase = non_empty(new_passphrase);
+    let protection = match (&new_passphrase, remove_passphrase.unwrap_or(false)) {
+        (Some(_), true) => {
+            return Err(AppError::Validation("Choose either a new passphrase or removing the passphrase".to_string()));
+        }
+        (Some(passphrase), false) => credentials::Protection::Passphrase(passphrase),
+        (None, true) => credentials::Protection::Machine,
+        (None, false) => credentials::Protection::Keep,
+    };
+
+    credentials::rotate_api_key(
+        &conn,
+        &secret_dir,
+        &provider,
+        non_empty(new_api_key).as_deref(),
+        non_empty(current_passphrase).as_deref(),
+        protection,
+    )?;
+
+    log::info!("Rotated encrypted API key for provider {}", provider);
+    load_key_status(&app, &provider)
+}
+
+/// Remove a stored provider key
+#[tauri::command]
+pub fn delete_ai_provider_key(app: AppHandle, provider: String) -> Result<(), AppError> {
+    credentials::validate_provider(&provider)?;
+    let conn = db::get_connection(&app)?;
+
+    conn.execute(
+        "DELETE FROM ai_provider_credentials WHERE provider = ?1",
+        params![provider],
+    )
+    .context("Failed to delete credential")?;
+
+    credentials::lock(&provider);
+    log::info!("Deleted API key for provider {}", provider);
+    Ok(())
+}
@@ -0,0 +1,639 @@
+use chrono::{Duration, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::error::{AppError, ResultExt};
+use crate::game::clock::{self, AppClock, Clock};
+
+// ============================================================================
+// AI Usage Types
+// ============================================================================
+
+/// A single AI call to be written to ai_usage_log
+pub struct AiUsageRecord<'a> {
+    pub user_id: Option<i32>,
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub latency_ms: i64,
+    pub context: Option<&'a str>,
+    pub status: &'a str,
+    pub error: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AiUsageBudget {
+    pub user_id: i32,
+    pub daily_token_limit: Option<i64>,
+    pub daily_cost_limit_usd: Option<f64>,
+    pub requests_per_minute: i64,
+    pub is_default: bool, // True when the user has no budget of their own
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AiUsageDay {
+    pub date: String,
+    pub provider: String,
+    pub model: String,
+    pub request_count: i64,
+    pub failed_count: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub estimated_cost_usd: f64,
+    pub avg_latency_ms: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AiUsageEntry {
+    pub id: i64,
+    pub user_id: Option<i32>,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub estimated_cost_usd: f64,
+    pub latency_ms: i64,
+    pub context: Option<String>,
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AiUsageReport {
+    pub user_id: Option<i32>,
+    pub days: Vec<AiUsageDay>,
+    pub total_requests: i64,
+    pub total_input_tokens: i64,
+    pub total_output_tokens: i64,
+    pub total_cost_usd: f64,
+    pub today_tokens: i64,
+    pub today_cost_usd: f64,
+    pub budget: Option<AiUsageBudget>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Load the budget for a user, falling back to the default row (user_id 0)
+pub fn get_effective_budget(conn: &Connection, user_id: i32) -> Result<AiUsageBudget, AppError> {
+    let budget = conn
+        .query_row(
+            "SELECT user_id, daily_token_limit, daily_cost_limit_usd, requests_per_minute
+             FROM ai_usage_budgets
+             WHERE user_id IN (?1, 0)
+             ORDER BY user_id DESC
+             LIMIT 1",
+            params![user_id],
+            |row| {
+                let budget_user_id: i32 = row.get(0)?;
+                Ok(AiUsageBudget {
+                    user_id,
+                    daily_token_limit: row.get(1)?,
+                    daily_cost_limit_usd: row.get(2)?,
+                    requests_per_minute: row.get(3)?,
+                    is_default: budget_user_id != user_id,
+                })
+            },
+        )
+        .optional()
+        .context("Failed to load AI budget")?;
+
+    Ok(budget.unwrap_or(AiUsageBudget {
+        user_id,
+        daily_token_limit: None,
+        daily_cost_limit_usd: None,
+        requests_per_minute: 10,
+        is_default: true,
+    }))
+}
+
+/// Tokens and estimated cost spent by a user on the current game day
+fn get_today_usage(conn: &Connection, user_id: Option<i32>) -> Result<(i64, f64), AppError> {
+    let (start, end) = AppClock::load(conn)?.today_range();
+    conn.query_row(
+        "SELECT COALESCE(SUM(input_tokens + output_tokens), 0), COALESCE(SUM(estimated_cost_usd), 0)
+         FROM ai_usage_log
+         WHERE user_id IS ?1
+           AND status IN ('success', 'error')
+           AND created_at >= ?2 AND created_at < ?3",
+        params![user_id, clock::to_sqlite(start), clock::to_sqlite(end)],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .context("Failed to load today's AI usage")
+}
+
+/// Estimated cost of a call from ai_model_pricing
+fn estimate_cost(conn: &Connection, record: &AiUsageRecord) -> Result<f64, AppError> {
+    let pricing: Option<(f64, f64)> = conn
+        .query_row(
+            "SELECT input_cost_per_mtok, output_cost_per_mtok FROM ai_model_pricing WHERE model = ?1",
+            params![record.model],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .context("Failed to load model pricing")?;
+
+    // Mock responses are free, whatever model they pretend to be
+    Ok(pricing
+        .filter(|_| record.provider != "mock")
+        .map(|(input_cost, output_cost)| {
+            (record.input_tokens as f64 * input_cost + record.output_tokens as f64 * output_cost) / 1_000_000.0
+        })
+        .unwrap_or(0.0))
+}
+
+/// Write one AI call to the usage log, estimating its cost from ai_model_pricing
+pub fn record_ai_usage(conn: &Connection, record: &AiUsageRecord) -> Result<(), AppError> {
+    let estimated_cost = estimate_cost(conn, record)?;
+
+    conn.execute(
+        "INSERT INTO ai_usage_log (user_id, provider, model, input_tokens, output_tokens, estimated_cost_usd, latency_ms, context, status, error)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            record.user_id,
+            record.provider,
+            record.model,
+            record.input_tokens,
+            record.output_tokens,
+            estimated_cost,
+            record.latency_ms,
+            record.context,
+            record.status,
+            record.error,
+        ],
+    )
+    .context("Failed to record AI usage")?;
+
+    Ok(())
+}
+
+/// Why an AI call would be refused right now: (status, message), or None to let it through
+fn check_limits(conn: &Connection, user_id: i32, provider: &str) -> Result<Option<(&'static str, String)>, AppError> {
+    let budget = get_effective_budget(conn, user_id)?;
+
+    // Reserved calls still waiting on the provider count too
+    let recent_requests: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM ai_usage_log
+             WHERE user_id = ?1
+               AND provider = ?2
+               AND status IN ('pending', 'success', 'error')
+               AND created_at >= datetime('now', '-60 seconds')",
+            params![user_id, provider],
+            |row| row.get(0),
+        )
+        .context("Failed to check AI rate limit")?;
+
+    if recent_requests >= budget.requests_per_minute {
+        return Ok(Some((
+            "rate_limited",
+            "Rate limit exceeded. Please wait before making more requests.".to_string(),
+        )));
+    }
+
+    let (tokens_today, cost_today) = get_today_usage(conn, Some(user_id))?;
+
+    Ok(match (budget.daily_token_limit, budget.daily_cost_limit_usd) {
+        (Some(limit), _) if tokens_today >= limit => Some((
+            "budget_exceeded",
+            format!("Daily AI token budget reached ({} of {} tokens). It resets tomorrow.", tokens_today, limit),
+        )),
+        (_, Some(limit)) if cost_today >= limit => Some((
+            "budget_exceeded",
+            format!("Daily AI spending budget reached (${:.2} of ${:.2}). It resets tomorrow.", cost_today, limit),
+        )),
+        _ => None,
+    })
+}
+
+/// Enforce the per-minute rate limit and daily budgets before an AI call, and
+/// reserve the call's slot with a 'pending' usage row. Checking and reserving
+/// happen in one transaction, so calls made at the same time can't all take the
+/// last slot. Rejected calls are recorded in the usage log as well. Every call is
+/// charged to the learner making it; those without a budget of their own get the
+/// default limits, counted against their own usage.
+/// Returns the reservation to pass to `complete_ai_call`.
+pub fn reserve_ai_call(
+    conn: &mut Connection,
+    user_id: i32,
+    provider: &str,
+    model: &str,
+    context: Option<&str>,
+) -> Result<i64, AppError> {
+    let outcome = db::run_unit_of_work(conn, |tx| {
+        let rejection = check_limits(tx, user_id, provider)?;
+        let (status, error) = match &rejection {
+            Some((status, message)) => (*status, Some(message.as_str())),
+            None => ("pending", None),
+        };
+
+        record_ai_usage(
+            tx,
+            &AiUsageRecord {
+                user_id: Some(user_id),
+                provider,
+                model,
+                input_tokens: 0,
+                output_tokens: 0,
+                latency_ms: 0,
+                context,
+                status,
+                error,
+            },
+        )?;
+
+        // The rejection is committed with the transaction, then reported
+        Ok(match rejection {
+            Some((_, message)) => Err(message),
+            None => Ok(tx.last_insert_rowid()),
+        })
+    })?;
+
+    outcome.map_err(AppError::Ai)
+}
+
+/// Fill in a reserved call's tokens, cost, latency and outcome
+pub fn complete_ai_call(conn: &Connection, reservation: i64, record: &AiUsageRecord) -> Result<(), AppError> {
+    let estimated_cost = estimate_cost(conn, record)?;
+
+    conn.execute(
+        "UPDATE ai_usage_log
+         SET input_tokens = ?1, output_tokens = ?2, estimated_cost_usd = ?3, latency_ms = ?4, status = ?5, error = ?6
+         WHERE id = ?7",
+        params![
+            record.input_tokens,
+            record.output_tokens,
+            estimated_cost,
+            record.latency_ms,
+            record.status,
+            record.error,
+            reservation,
+        ],
+    )
+    .context("Failed to record AI usage")?;
+
+    Ok(())
+}
+
+// ============================================================================
+// AI Usage Commands
+// ============================================================================
+
+/// Get the budget that applies to a user (user_id 0 is the default for everyone)
+#[tauri::command]
+pub fn get_ai_usage_budget(app: AppHandle, user_id: i32) -> Result<AiUsageBudget, AppError> {
+    let conn = db::get_connection(&app)?;
+    get_effective_budget(&conn, user_id)
+}
+
+/// Set a user's daily budget. Pass user_id 0 to change the default for all users.
+#[tauri::command]
+pub fn set_ai_usage_budget(
+    app: AppHandle,
+    user_id: i32,
+    daily_token_limit: Option<i64>,
+    daily_cost_limit_usd: Option<f64>,
+    requests_per_minute: Option<i64>,
+) -> Result<AiUsageBudget, AppError> {
+    if daily_token_limit.is_some_and(|limit| limit < 0) {
+        return Err(AppError::Validation("Daily token limit cannot be negative".to_string()));
+    }
+    if daily_cost_limit_usd.is_some_and(|limit| limit < 0.0) {
+        return Err(AppError::Validation("Daily cost limit cannot be negative".to_string()));
+    }
+    if requests_per_minute.is_some_and(|limit| limit < 1) {
+        return Err(AppError::Validation("Requests per minute must be at least 1".to_string()));
+    }
+
+    let conn = db::get_connection(&app)?;
+
+    conn.execute(
+        "INSERT INTO ai_usage_budgets (user_id, daily_token_limit, daily_cost_limit_usd, requests_per_minute, updated_at)
+         VALUES (?1, ?2, ?3, COALESCE(?4, 10), datetime('now'))
+         ON CONFLICT(user_id) DO UPDATE SET
+            daily_token_limit = excluded.daily_token_limit,
+            daily_cost_limit_usd = excluded.daily_cost_limit_usd,
+            requests_per_minute = COALESCE(?4, ai_usage_budgets.requests_per_minute),
+            updated_at = excluded.updated_at",
+        params![user_id, daily_token_limit, daily_cost_limit_usd, requests_per_minute],
+    )
+    .context("Failed to save AI budget")?;
+
+    get_effective_budget(&conn, user_id)
+}
+
+/// Remove a user's own budget so the default applies again
+#[tauri::command]
+pub fn clear_ai_usage_budget(app: AppHandle, user_id: i32) -> Result<(), AppError> {
+    if user_id == 0 {
+        return Err(AppError::Validation("The default budget cannot be removed".to_string()));
+    }
+
+    let conn = db::get_connection(&app)?;
+    conn.execute("DELETE FROM ai_usage_budgets WHERE user_id = ?1", params![user_id])
+        .context("Failed to clear AI budget")?;
+    Ok(())
+}
+
+/// Daily usage grouped by provider and model. Without a user_id, covers all users.
+#[tauri::command]
+pub fn get_ai_usage_report(app: AppHandle, user_id: Option<i32>, days: Option<i32>) -> Result<AiUsageReport, AppError> {
+    let conn = db::get_connection(&app)?;
+    usage_report(&conn, &AppClock::load(&conn)?, user_id, days.unwrap_or(30).max(1))
+}
+
+/// Usage over the last `days` game days, today included. Calls are grouped by the
+/// game day they fall in, which SQLite's date() can't work out for a named timezone.
+fn usage_report<C: Clock>(
+    conn: &Connection,
+    clock: &AppClock<C>,
+    user_id: Option<i32>,
+    days: i32,
+) -> Result<AiUsageReport, AppError> {
+    let today = clock.today();
+    let since = clock.settings().day_start(today - Duration::days(i64::from(days - 1)));
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT created_at, provider, model, status, input_tokens, output_tokens, estimated_cost_usd, latency_ms
+             FROM ai_usage_log
+             WHERE (?1 IS NULL OR user_id = ?1)
+               AND created_at >= ?2",
+        )
+        .context("Failed to prepare query")?;
+
+    let calls = stmt
+        .query_map(params![user_id, clock::to_sqlite(since)], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, i64>(5)?,
+                row.get::<_, f64>(6)?,
+                row.get::<_, i64>(7)?,
+            ))
+        })
+        .context("Failed to query AI usage")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to collect AI usage")?;
+
+    // (day, provider, model) -> totals plus the latency sum and count behind the average
+    let mut groups: BTreeMap<(NaiveDate, String, String), (AiUsageDay, i64, i64)> = BTreeMap::new();
+    for (created_at, provider, model, status, input_tokens, output_tokens, cost, latency_ms) in calls {
+        let Some(instant) = clock::from_sqlite(&created_at) else { continue };
+        let day = clock.settings().day_of(instant);
+
+        let (usage, latency_total, latency_count) = groups
+            .entry((day, provider.clone(), model.clone()))
+            .or_insert_with(|| {
+                let usage = AiUsageDay {
+                    date: day.to_string(),
+                    provider,
+                    model,
+                    request_count: 0,
+                    failed_count: 0,
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    estimated_cost_usd: 0.0,
+                    avg_latency_ms: 0.0,
+                };
+                (usage, 0, 0)
+            });
+        usage.request_count += 1;
+        if status != "success" && status != "pending" {
+            usage.failed_count += 1;
+        }
+        usage.input_tokens += input_tokens;
+        usage.output_tokens += output_tokens;
+        usage.estimated_cost_usd += cost;
+        if status == "success" || status == "error" {
+            *latency_total += latency_ms;
+            *latency_count += 1;
+        }
+    }
+
+    let mut today_tokens = 0;
+    let mut today_cost_usd = 0.0;
+    let mut usage_days = Vec::new();
+    for ((day, _, _), (mut usage, latency_total, latency_count)) in groups {
+        if day == today {
+            today_tokens += usage.input_tokens + usage.output_tokens;
+            today_cost_usd += usage.estimated_cost_usd;
+        }
+        if latency_count > 0 {
+            usage.avg_latency_ms = latency_total as f64 / latency_count as f64;
+        }
+        usage_days.push(usage);
+    }
+    // Newest day first, then provider and model
+    usage_days.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.provider.cmp(&b.provider)).then_with(|| a.model.cmp(&b.model)));
+
+    let budget = match user_id {
+        Some(id) => Some(get_effective_budget(conn, id)?),
+        None => None,
+    };
+
+    Ok(AiUsageReport {
+        user_id,
+        total_requests: usage_days.iter().map(|d| d.request_count).sum(),
+        total_input_tokens: usage_days.iter().map(|d| d.input_tokens).sum(),
+        total_output_tokens: usage_days.iter().map(|d| d.output_tokens).sum(),
+        total_cost_usd: usage_days.iter().map(|d| d.estimated_cost_usd).sum(),
+        today_tokens,
+        today_cost_usd,
+        budget,
+        days: usage_days,
+    })
+}
+
+/// Most recent AI calls, newest first
+#[tauri::command]
+pub fn get_recent_ai_calls(app: AppHandle, user_id: Option<i32>, limit: Option<i32>) -> Result<Vec<AiUsageEntry>, AppError> {
+    let conn = db::get_connection(&app)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, user_id, provider, model, input_tokens, output_tokens, estimated_cost_usd,
+                    latency_ms, context, status, error, created_at
+             FROM ai_usage_log
+             WHERE (?1 IS NULL OR user_id = ?1)
+             ORDER BY id DESC
+             LIMIT ?2",
+        )
+        .context("Failed to prepare query")?;
+
+    let entries = stmt
+        .query_map(params![user_id, limit.unwrap_or(50)], |row| {
+            Ok(AiUsageEntry {
+                id: row.get(0)?,
+                user_id: row.get(1)?,
+                provider: row.get(2)?,
+                model: row.get(3)?,
+                input_tokens: row.get(4)?,
+                output_tokens: row.get(5)?,
+                estimated_cost_usd: row.get(6)?,
+                latency_ms: row.get(7)?,
+                context: row.get(8)?,
+                status: row.get(9)?,
+                error: row.get(10)?,
+                created_at: row.get(11)?,
+            })
+        })
+        .context("Failed to query AI calls")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to collect AI calls")?;
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::clock::{FixedClock, TimeSettings};
+    use crate::test_support::{scalar, seeded_db};
+
+    fn complete(conn: &Connection, reservation: i64, tokens: i64) {
+        complete_ai_call(
+            conn,
+            reservation,
+            &AiUsageRecord {
+                user_id: Some(1),
+                provider: "claude",
+                model: "test-model",
+                input_tokens: tokens,
+                output_tokens: 0,
+                latency_ms: 5,
+                context: None,
+                status: "success",
+                error: None,
+            },
+        )
+        .unwrap();
+    }
+
+    fn logged(conn: &Connection, user_id: i32, status: &str) -> i64 {
+        scalar(
+            conn,
+            "SELECT COUNT(*) FROM ai_usage_log WHERE user_id = ?1 AND status = ?2",
+            params![user_id, status],
+        )
+    }
+
+    #[test]
+    fn rate_limit_counts_each_users_own_calls() {
+        let mut conn = seeded_db();
+        conn.execute("UPDATE ai_usage_budgets SET requests_per_minute = 2 WHERE user_id = 0", []).unwrap();
+
+        // Reserved calls count before they complete
+        reserve_ai_call(&mut conn, 1, "claude", "test-model", None).unwrap();
+        reserve_ai_call(&mut conn, 1, "claude", "test-model", None).unwrap();
+        let err = reserve_ai_call(&mut conn, 1, "claude", "test-model", Some("lesson:x")).unwrap_err();
+        assert!(matches!(err, AppError::Ai(_)));
+
+        assert_eq!(logged(&conn, 1, "pending"), 2);
+        assert_eq!(logged(&conn, 1, "rate_limited"), 1);
+
+        // Another learner has their own minute
+        reserve_ai_call(&mut conn, 2, "claude", "test-model", None).unwrap();
+    }
+
+    #[test]
+    fn daily_token_and_cost_budgets_reject_further_calls() {
+        let mut conn = seeded_db();
+        conn.execute_batch(
+            "INSERT INTO ai_model_pricing (model, input_cost_per_mtok, output_cost_per_mtok) VALUES ('test-model', 10.0, 10.0);
+             INSERT INTO ai_usage_budgets (user_id, daily_token_limit, daily_cost_limit_usd) VALUES (1, 100, NULL);",
+        )
+        .unwrap();
+
+        let first = reserve_ai_call(&mut conn, 1, "claude", "test-model", None).unwrap();
+        complete(&conn, first, 80);
+        let second = reserve_ai_call(&mut conn, 1, "claude", "test-model", None).unwrap();
+        complete(&conn, second, 30);
+
+        let err = reserve_ai_call(&mut conn, 1, "claude", "test-model", None).unwrap_err();
+        assert!(err.message().contains("token budget"));
+        assert_eq!(logged(&conn, 1, "budget_exceeded"), 1);
+
+        // 110 tokens at $10 per million is $0.0011
+        conn.execute(
+            "UPDATE ai_usage_budgets SET daily_token_limit = NULL, daily_cost_limit_usd = 0.001 WHERE user_id = 1",
+            [],
+        )
+        .unwrap();
+        let err = reserve_ai_call(&mut conn, 1, "claude", "test-model", None).unwrap_err();
+        assert!(err.message().contains("spending budget"));
+        assert_eq!(logged(&conn, 1, "budget_exceeded"), 2);
+        assert_eq!(logged(&conn, 1, "success"), 2);
+    }
+
+    #[test]
+    fn learners_without_a_budget_are_charged_the_default_on_their_own_usage() {
+        let mut conn = seeded_db();
+        conn.execute("UPDATE ai_usage_budgets SET daily_token_limit = 100 WHERE user_id = 0", []).unwrap();
+
+        let first = reserve_ai_call(&mut conn, 1, "claude", "test-model", None).unwrap();
+        complete(&conn, first, 120);
+        let err = reserve_ai_call(&mut conn, 1, "claude", "test-model", None).unwrap_err();
+        assert!(err.message().contains("token budget"));
+
+        // The default is a per-learner allowance, not a pool the first learner used up
+        reserve_ai_call(&mut conn, 2, "claude", "test-model", None).unwrap();
+        let unattributed: i64 = scalar(&conn, "SELECT COUNT(*) FROM ai_usage_log WHERE user_id IS NULL", []);
+        assert_eq!(unattributed, 0);
+    }
+
+    #[test]
+    fn report_groups_calls_by_game_day_in_the_configured_timezone() {
+        let conn = seeded_db();
+        let log = |created_at: &str, model: &str, tokens: i64, status: &str| {
+            conn.execute(
+                "INSERT INTO ai_usage_log (user_id, provider, model, input_tokens, output_tokens, estimated_cost_usd,
+                                           latency_ms, status, created_at)
+                 VALUES (1, 'claude', ?1, ?2, 0, 0.5, 100, ?3, ?4)",
+                params![model, tokens, status, created_at],
+            )
+            .unwrap();
+        };
+        // New York is UTC-4 in July: 03:30 UTC on the 15th is still the evening of the 14th there
+        log("2024-07-14 15:00:00", "b-model", 10, "success");
+        log("2024-07-15 03:30:00", "a-model", 20, "error");
+        log("2024-07-15 04:30:00", "a-model", 40, "success");
+        log("2024-07-15 18:00:00", "a-model", 0, "rate_limited");
+        log("2024-07-10 12:00:00", "a-model", 1000, "success");
+
+        let settings = TimeSettings { timezone: Some("America/New_York".to_string()), ..Default::default() };
+        let clock = AppClock::new(FixedClock(clock::from_sqlite("2024-07-15 20:00:00").unwrap()), settings);
+        let report = usage_report(&conn, &clock, Some(1), 2).unwrap();
+
+        let days: Vec<(&str, &str, i64, i64, i64)> = report
+            .days
+            .iter()
+            .map(|d| (d.date.as_str(), d.model.as_str(), d.request_count, d.failed_count, d.input_tokens))
+            .collect();
+        assert_eq!(
+            days,
+            vec![
+                ("2024-07-15", "a-model", 2, 1, 40),
+                ("2024-07-14", "a-model", 1, 1, 20),
+                ("2024-07-14", "b-model", 1, 0, 10),
+            ]
+        );
+        assert_eq!(report.days[0].avg_latency_ms, 100.0);
+        assert_eq!(report.today_tokens, 40);
+        assert_eq!(report.today_cost_usd, 1.0);
+        assert_eq!(report.total_requests, 4);
+    }
+}
@@ -0,0 +1,585 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::db::DbPool;
+use crate::error::{AppError, ResultExt};
+use crate::game::clock::{Clock, SystemClock};
+use crate::migrations;
+
+// ============================================================================
+// Database Backups
+// ============================================================================
+//
+// Copies of code-tutor.db are taken with SQLite's online backup API, so they
+// are consistent even while commands are writing. Each copy is a standalone
+// file in <app data>/backups named "<kind>-<UTC timestamp>.db", with a "-2",
+// "-3"... suffix on the timestamp if that name is already taken. Automatic
+// kinds are pruned to the newest N after each new copy; manual ones are kept
+// until the learner deletes them.
+//
+// Settings live next to the backups rather than in the database, so they are
+// readable before migrations run and aren't rolled back by a restore.
+
+const BACKUP_DIR: &str = "backups";
+const SETTINGS_FILE: &str = "settings.json";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+
+/// Pages copied per backup step; other connections can write between steps
+const PAGES_PER_STEP: i32 = 256;
+const STEP_PAUSE: Duration = Duration::from_millis(10);
+/// How often the scheduler checks whether a backup is due
+const SCHEDULER_TICK: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+    Scheduled,
+    PreMigration,
+    PreRestore,
+    Manual,
+}
+
+impl BackupKind {
+    const ALL: [BackupKind; 4] = [
+        BackupKind::Scheduled,
+        BackupKind::PreMigration,
+        BackupKind::PreRestore,
+        BackupKind::Manual,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BackupKind::Scheduled => "scheduled",
+            BackupKind::PreMigration => "pre_migration",
+            BackupKind::PreRestore => "pre_restore",
+            BackupKind::Manual => "manual",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Take scheduled backups while the app is running
+    pub scheduled_enabled: bool,
+    pub interval_hours: u32,
+    pub keep_scheduled: u32,
+    pub keep_pre_migration: u32,
+    pub keep_pre_restore: u32,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        BackupSettings {
+            scheduled_enabled: true,
+            interval_hours: 24,
+            keep_scheduled: 7,
+            keep_pre_migration: 5,
+            keep_pre_restore: 3,
+        }
+    }
+}
+
+impl BackupSettings {
+    /// How many backups of a kind to keep; None keeps all of them
+    fn retention(&self, kind: BackupKind) -> Option<usize> {
+        match kind {
+            BackupKind::Scheduled => Some(self.keep_scheduled as usize),
+            BackupKind::PreMigration => Some(self.keep_pre_migration as usize),
+            BackupKind::PreRestore => Some(self.keep_pre_restore as usize),
+            BackupKind::Manual => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        if self.interval_hours == 0 {
+            return Err(AppError::Validation("Backup interval must be at least one hour".to_string()));
+        }
+        if self.keep_scheduled == 0 || self.keep_pre_migration == 0 || self.keep_pre_restore == 0 {
+            return Err(AppError::Validation("Retention must keep at least one backup of each kind".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub kind: BackupKind,
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupVerification {
+    pub file_name: String,
+    pub ok: bool,
+    /// Rows reported by PRAGMA integrity_check, or why the file couldn't be read
+    pub problems: Vec<String>,
+    pub schema_version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub restored: BackupInfo,
+    /// Copy of the database as it was just before the restore
+    pub safety_copy: BackupInfo,
+    /// Migrations applied to bring an older backup up to date
+    pub migrations_applied: Vec<String>,
+}
+
+/// The backups folder inside the app data dir, creating it if needed
+pub fn backup_dir(app_data_dir: &Path) -> Result<PathBuf, AppError> {
+    let dir = app_data_dir.join(BACKUP_DIR);
+    fs::create_dir_all(&dir)
+        .context("Failed to create backup directory")?;
+    Ok(dir)
+}
+
+pub fn load_settings(dir: &Path) -> Result<BackupSettings, AppError> {
+    let path = dir.join(SETTINGS_FILE);
+    if !path.exists() {
+        return Ok(BackupSettings::default());
+    }
+    let json = fs::read_to_string(&path)
+        .context("Failed to read backup settings")?;
+    serde_json::from_str(&json)
+        .context("Failed to parse backup settings")
+}
+
+pub fn save_settings(dir: &Path, settings: &BackupSettings) -> Result<(), AppError> {
+    settings.validate()?;
+    let json = serde_json::to_string_pretty(settings)
+        .context("Failed to serialize backup settings")?;
+    fs::write(dir.join(SETTINGS_FILE), json)
+        .context("Failed to write backup settings")
+}
+
+/// Kind, creation time and collision counter encoded in a backup file name
+fn parse_file_name(file_name: &str) -> Option<(BackupKind, DateTime<Utc>, u32)> {
+    let stem = file_name.strip_suffix(".db")?;
+    let (kind, rest) = stem.split_once('-')?;
+    let (timestamp, sequence) = match rest.rsplit_once('-') {
+        Some((timestamp, n)) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => (timestamp, n.parse().ok()?),
+        _ => (rest, 1),
+    };
+    let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+    Some((BackupKind::parse(kind)?, created_at.and_utc(), sequence))
+}
+
+fn sequence(file_name: &str) -> u32 {
+    parse_file_name(file_name).map_or(1, |(_, _, sequence)| sequence)
+}
+
+fn backup_info(path: &Path) -> Option<BackupInfo> {
+    let file_name = path.file_name()?.to_str()?;
+    let (kind, created_at, _) = parse_file_name(file_name)?;
+    let size_bytes = fs::metadata(path).ok()?.len();
+    Some(BackupInfo {
+        file_name: file_name.to_string(),
+        kind,
+        created_at: created_at.to_rfc3339(),
+        size_bytes,
+    })
+}
+
+/// Backups in the folder, newest first. Files that don't follow the naming scheme are ignored.
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupInfo>, AppError> {
+    let mut backups: Vec<BackupInfo> = fs::read_dir(dir)
+        .context("Failed to read backup directory")?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| backup_info(&entry.path()))
+        .collect();
+
+    // UTC RFC 3339 timestamps sort correctly as text; the counter orders backups made in the same millisecond
+    backups.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| sequence(&b.file_name).cmp(&sequence(&a.file_name)))
+    });
+    Ok(backups)
+}
+
+/// Path of a listed backup. Only names produced by create_backup are accepted.
+fn resolve(dir: &Path, file_name: &str) -> Result<PathBuf, AppError> {
+    if parse_file_name(file_name).is_none() || file_name.contains(['/', '\\']) {
+        return Err(AppError::Validation(format!("'{}' is not a backup file name", file_name)));
+    }
+    let path = dir.join(file_name);
+    if !path.is_file() {
+        return Err(AppError::NotFound(format!("Backup {} not found", file_name)));
+    }
+    Ok(path)
+}
+
+pub(crate) fn copy_database(from: &Connection, to: &mut Connection) -> Result<(), AppError> {
+    Backup::new(from, to)
+        .context("Failed to start database backup")?
+        .run_to_completion(PAGES_PER_STEP, STEP_PAUSE, None)
+        .context("Failed to copy database")
+}
+
+/// Copy the database behind conn into a new backup file
+pub fn create_backup(conn: &Connection, dir: &Path, kind: BackupKind, clock: &dyn Clock) -> Result<BackupInfo, AppError> {
+    let timestamp = clock.now().format(TIMESTAMP_FORMAT).to_string();
+    // Backups taken within the same millisecond get a counter rather than replacing each other
+    let file_name = (1..)
+        .map(|n| match n {
+            1 => format!("{}-{}.db", kind.as_str(), timestamp),
+            n => format!("{}-{}-{}.db", kind.as_str(), timestamp, n),
+        })
+        .find(|name| !dir.join(name).exists() && !dir.join(format!("{}.partial", name)).exists())
+        .expect("backup names are unbounded");
+    let path = dir.join(&file_name);
+    // Written under a temporary name so an interrupted backup is never listed
+    let partial = dir.join(format!("{}.partial", file_name));
+
+    {
+        let mut dest = Connection::open(&partial)
+            .context("Failed to create backup file")?;
+        copy_database(conn, &mut dest)?;
+        // The live database is in WAL mode; a backup should be one self-contained file
+        dest.query_row("PRAGMA journal_mode = DELETE", [], |_| Ok(()))
+            .context("Failed to finalize backup file")?;
+    }
+
+    fs::rename(&partial, &path)
+        .context("Failed to save backup file")?;
+
+    log::info!("Created {} backup {}", kind.as_str(), file_name);
+    backup_info(&path)
+        .ok_or_else(|| AppError::Internal(format!("Backup {} was written but can't be read back", file_name)))
+}
+
+/// Delete the oldest backups of a kind beyond its retention, returning the names removed
+pub fn prune(dir: &Path, kind: BackupKind, settings: &BackupSettings) -> Result<Vec<String>, AppError> {
+    let Some(keep) = settings.retention(kind) else {
+        return Ok(Vec::new());
+    };
+
+    let mut removed = Vec::new();
+    for backup in list_backups(dir)?.into_iter().filter(|b| b.kind == kind).skip(keep) {
+        fs::remove_file(dir.join(&backup.file_name))
+            .with_context(|| format!("Failed to delete old backup {}", backup.file_name))?;
+        removed.push(backup.file_name);
+    }
+    if !removed.is_empty() {
+        log::info!("Pruned {} old {} backups", removed.len(), kind.as_str());
+    }
+    Ok(removed)
+}
+
+/// Back up and then apply retention for that kind
+pub fn create_and_prune(conn: &Connection, dir: &Path, kind: BackupKind, clock: &dyn Clock) -> Result<BackupInfo, AppError> {
+    let backup = create_backup(conn, dir, kind, clock)?;
+    prune(dir, kind, &load_settings(dir)?)?;
+    Ok(backup)
+}
+
+/// Run PRAGMA integrity_check against a backup without modifying it. FTS5's part of
+/// the check needs a writable connection, so it runs in a transaction that is rolled back.
+pub fn verify_backup(dir: &Path, file_name: &str) -> Result<BackupVerification, AppError> {
+    let path = resolve(dir, file_name)?;
+
+    let check = || -> Result<(Vec<String>, Option<String>), rusqlite::Error> {
+        let mut conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+        let tx = conn.transaction()?;
+        let rows = tx
+            .prepare("PRAGMA integrity_check")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let schema_version = migrations::current_version(&tx).ok().flatten();
+        tx.rollback()?;
+        Ok((rows, schema_version))
+    };
+
+    let (problems, schema_version) = match check() {
+        Ok((rows, _)) if rows.is_empty() => (vec!["Integrity check returned no result".to_string()], None),
+        Ok((rows, version)) if rows.len() == 1 && rows[0] == "ok" => (Vec::new(), version),
+        Ok((rows, version)) => (rows, version),
+        Err(e) => (vec![e.to_string()], None),
+    };
+
+    Ok(BackupVerification {
+        file_name: file_name.to_string(),
+        ok: problems.is_empty(),
+        problems,
+        schema_version,
+    })
+}
+
+/// Replace the live database with a backup. The backup is verified first and the
+/// current database is saved as a pre_restore backup; if the restored copy can't
+/// be migrated to this version of the app, that safety copy is put back.
+pub fn restore_backup(conn: &mut Connection, dir: &Path, file_name: &str, clock: &dyn Clock) -> Result<RestoreReport, AppError> {
+    let verification = verify_backup(dir, file_name)?;
+    if !verification.ok {
+        return Err(AppError::Validation(format!(
+            "Backup {} failed its integrity check: {}",
+            file_name,
+            verification.problems.join("; ")
+        )));
+    }
+
+    let path = resolve(dir, file_name)?;
+    let restored = backup_info(&path)
+        .ok_or_else(|| AppError::NotFound(format!("Backup {} not found", file_name)))?;
+    let safety_copy = create_and_prune(conn, dir, BackupKind::PreRestore, clock)?;
+
+    let source = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open backup {}", file_name))?;
+    copy_database(&source, conn)?;
+
+    let report = match migrations::run_migrations(conn) {
+        Ok(report) => report,
+        Err(e) => {
+            log::error!("Restored backup {} can't be migrated, rolling back: {}", file_name, e);
+            let previous = Connection::open_with_flags(dir.join(&safety_copy.file_name), OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .context("Failed to open safety copy")?;
+            copy_database(&previous, conn)
+                .context("Failed to roll back restore")?;
+            return Err(e);
+        }
+    };
+
+    log::info!("Restored database from {} (safety copy {})", file_name, safety_copy.file_name);
+    Ok(RestoreReport {
+        restored,
+        safety_copy,
+        migrations_applied: report.applied,
+    })
+}
+
+/// Take a scheduled backup if the newest one is older than the configured interval
+pub fn run_scheduled(conn: &Connection, dir: &Path, clock: &dyn Clock) -> Result<Option<BackupInfo>, AppError> {
+    let settings = load_settings(dir)?;
+    if !settings.scheduled_enabled {
+        return Ok(None);
+    }
+
+    let interval = chrono::Duration::hours(settings.interval_hours as i64);
+    let latest = list_backups(dir)?
+        .into_iter()
+        .find(|b| b.kind == BackupKind::Scheduled)
+        .and_then(|b| DateTime::parse_from_rfc3339(&b.created_at).ok());
+    if latest.is_some_and(|at| clock.now() - at.with_timezone(&Utc) < interval) {
+        return Ok(None);
+    }
+
+    create_and_prune(conn, dir, BackupKind::Scheduled, clock).map(Some)
+}
+
+/// Check for a due scheduled backup now and then periodically, for the life of the app
+pub fn start_scheduler(pool: DbPool, dir: PathBuf) {
+    std::thread::spawn(move || loop {
+        let result = pool
+            .get()
+            .context("Failed to get database connection")
+            .and_then(|conn| run_scheduled(&conn, &dir, &SystemClock));
+        if let Err(e) = result {
+            log::error!("Scheduled backup failed: {}", e);
+        }
+        std::thread::sleep(SCHEDULER_TICK);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::clock::FixedClock;
+    use crate::test_support::{scalar, TestUser};
+    use chrono::TimeZone;
+
+    /// A scratch folder with a migrated database file, removed on drop
+    struct Scratch {
+        root: PathBuf,
+    }
+
+    impl Scratch {
+        fn new() -> Self {
+            let root = std::env::temp_dir().join(format!("code-tutor-backup-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(&root).unwrap();
+            Scratch { root }
+        }
+
+        fn dir(&self) -> PathBuf {
+            backup_dir(&self.root).unwrap()
+        }
+
+        fn database(&self) -> Connection {
+            let mut conn = Connection::open(self.root.join("code-tutor.db")).unwrap();
+            conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(())).unwrap();
+            migrations::run_migrations(&mut conn).unwrap();
+            conn
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn at(hour: u32) -> FixedClock {
+        FixedClock(Utc.with_ymd_and_hms(2026, 3, 1, hour, 0, 0).unwrap())
+    }
+
+    fn user_count(conn: &Connection) -> i64 {
+        scalar(conn, "SELECT COUNT(*) FROM users", [])
+    }
+
+    #[test]
+    fn backup_is_listed_and_verifies() {
+        let scratch = Scratch::new();
+        let conn = scratch.database();
+        TestUser::new("hero").create(&conn);
+        let users = user_count(&conn);
+
+        let backup = create_backup(&conn, &scratch.dir(), BackupKind::Manual, &at(9)).unwrap();
+        assert_eq!(backup.file_name, "manual-20260301-090000.000.db");
+        assert_eq!(backup.created_at, "2026-03-01T09:00:00+00:00");
+
+        let listed = list_backups(&scratch.dir()).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].kind, BackupKind::Manual);
+
+        let verification = verify_backup(&scratch.dir(), &backup.file_name).unwrap();
+        assert!(verification.ok, "{:?}", verification.problems);
+        assert_eq!(verification.schema_version, migrations::current_version(&conn).unwrap());
+
+        let copy = Connection::open(scratch.dir().join(&backup.file_name)).unwrap();
+        assert_eq!(user_count(&copy), users);
+    }
+
+    #[test]
+    fn back_to_back_backups_keep_both_copies() {
+        let scratch = Scratch::new();
+        let conn = scratch.database();
+        let users = user_count(&conn);
+
+        let first = create_backup(&conn, &scratch.dir(), BackupKind::Manual, &at(9)).unwrap();
+        TestUser::new("hero").create(&conn);
+        let second = create_backup(&conn, &scratch.dir(), BackupKind::Manual, &at(9)).unwrap();
+        assert_eq!(first.file_name, "manual-20260301-090000.000.db");
+        assert_eq!(second.file_name, "manual-20260301-090000.000-2.db");
+
+        let listed: Vec<String> = list_backups(&scratch.dir()).unwrap().into_iter().map(|b| b.file_name).collect();
+        assert_eq!(listed, vec![second.file_name.clone(), first.file_name.clone()]);
+
+        let first_copy = Connection::open(scratch.dir().join(&first.file_name)).unwrap();
+        let second_copy = Connection::open(scratch.dir().join(&second.file_name)).unwrap();
+        assert_eq!(user_count(&first_copy), users);
+        assert_eq!(user_count(&second_copy), users + 1);
+        assert!(verify_backup(&scratch.dir(), &second.file_name).unwrap().ok);
+    }
+
+    #[test]
+    fn corrupt_backup_fails_verification() {
+        let scratch = Scratch::new();
+        let conn = scratch.database();
+        let backup = create_backup(&conn, &scratch.dir(), BackupKind::Manual, &at(9)).unwrap();
+
+        let path = scratch.dir().join(&backup.file_name);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[..16].copy_from_slice(b"not a database!!");
+        fs::write(&path, bytes).unwrap();
+
+        let verification = verify_backup(&scratch.dir(), &backup.file_name).unwrap();
+        assert!(!verification.ok);
+        assert!(!verification.problems.is_empty());
+    }
+
+    #[test]
+    fn retention_keeps_newest_of_each_kind() {
+        let scratch = Scratch::new();
+        let dir = scratch.dir();
+        let conn = scratch.database();
+        save_settings(&dir, &BackupSettings { keep_scheduled: 2, ..BackupSettings::default() }).unwrap();
+
+        create_and_prune(&conn, &dir, BackupKind::Manual, &at(1)).unwrap();
+        for hour in 2..6 {
+            create_and_prune(&conn, &dir, BackupKind::Scheduled, &at(hour)).unwrap();
+        }
+
+        let names: Vec<String> = list_backups(&dir).unwrap().into_iter().map(|b| b.file_name).collect();
+        assert_eq!(
+            names,
+            vec![
+                "scheduled-20260301-050000.000.db",
+                "scheduled-20260301-040000.000.db",
+                "manual-20260301-010000.000.db",
+            ]
+        );
+    }
+
+    #[test]
+    fn scheduled_backup_waits_for_interval() {
+        let scratch = Scratch::new();
+        let dir = scratch.dir();
+        let conn = scratch.database();
+
+        assert!(run_scheduled(&conn, &dir, &at(1)).unwrap().is_some());
+        assert!(run_scheduled(&conn, &dir, &at(12)).unwrap().is_none());
+        assert!(run_scheduled(&conn, &dir, &FixedClock(Utc.with_ymd_and_hms(2026, 3, 2, 1, 0, 0).unwrap())).unwrap().is_some());
+
+        save_settings(&dir, &BackupSettings { scheduled_enabled: false, ..BackupSettings::default() }).unwrap();
+        assert!(run_scheduled(&conn, &dir, &FixedClock(Utc.with_ymd_and_hms(2026, 3, 9, 1, 0, 0).unwrap())).unwrap().is_none());
+    }
+
+    #[test]
+    fn restore_replaces_database_and_keeps_safety_copy() {
+        let scratch = Scratch::new();
+        let dir = scratch.dir();
+        let mut conn = scratch.database();
+        TestUser::new("before").create(&conn);
+        let users = user_count(&conn);
+        let backup = create_backup(&conn, &dir, BackupKind::Manual, &at(9)).unwrap();
+
+        TestUser::new("after").create(&conn);
+
+        let report = restore_backup(&mut conn, &dir, &backup.file_name, &at(10)).unwrap();
+        assert_eq!(user_count(&conn), users);
+        assert_eq!(report.safety_copy.kind, BackupKind::PreRestore);
+        assert!(report.migrations_applied.is_empty());
+
+        // Other connections to the file see the restored data too
+        let other = Connection::open(scratch.root.join("code-tutor.db")).unwrap();
+        assert_eq!(user_count(&other), users);
+
+        let safety = Connection::open(dir.join(&report.safety_copy.file_name)).unwrap();
+        assert_eq!(user_count(&safety), users + 1);
+    }
+
+    #[test]
+    fn restore_refuses_corrupt_backup() {
+        let scratch = Scratch::new();
+        let dir = scratch.dir();
+        let mut conn = scratch.database();
+        TestUser::new("hero").create(&conn);
+        let users = user_count(&conn);
+        let backup = create_backup(&conn, &dir, BackupKind::Manual, &at(9)).unwrap();
+        fs::write(dir.join(&backup.file_name), b"garbage").unwrap();
+
+        let err = restore_backup(&mut conn, &dir, &backup.file_name, &at(10)).unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+        assert_eq!(user_count(&conn), users);
+        assert_eq!(list_backups(&dir).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn only_backup_names_resolve() {
+        let scratch = Scratch::new();
+        let dir = scratch.dir();
+        assert!(matches!(verify_backup(&dir, "../code-tutor.db"), Err(AppError::Validation(_))));
+        assert!(matches!(verify_backup(&dir, "manual-20260301-090000.000.db"), Err(AppError::NotFound(_))));
+    }
+}
@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::backup::{self, BackupInfo, BackupKind, BackupSettings, BackupVerification, RestoreReport};
+use crate::db;
+use crate::error::AppError;
+use crate::game::clock::SystemClock;
+
+// ============================================================================
+// Database Backup Commands
+// ============================================================================
+
+fn backup_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    backup::backup_dir(&db::get_app_data_dir(app)?)
+}
+
+#[tauri::command]
+pub fn get_backup_settings(app: AppHandle) -> Result<BackupSettings, AppError> {
+    backup::load_settings(&backup_dir(&app)?)
+}
+
+/// Save settings and apply the new retention to existing backups
+#[tauri::command]
+pub fn set_backup_settings(app: AppHandle, settings: BackupSettings) -> Result<BackupSettings, AppError> {
+    let dir = backup_dir(&app)?;
+    backup::save_settings(&dir, &settings)?;
+    for kind in [BackupKind::Scheduled, BackupKind::PreMigration, BackupKind::PreRestore] {
+        backup::prune(&dir, kind, &settings)?;
+    }
+    Ok(settings)
+}
+
+/// Backups on disk, newest first
+#[tauri::command]
+pub fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, AppError> {
+    backup::list_backups(&backup_dir(&app)?)
+}
+
+/// Take a manual backup now; these are never pruned
+#[tauri::command]
+pub fn create_backup(app: AppHandle) -> Result<BackupInfo, AppError> {
+    let conn = db::get_connection(&app)?;
+    backup::create_backup(&conn, &backup_dir(&app)?, BackupKind::Manual, &SystemClock)
+}
+
+#[tauri::command]
+pub fn verify_backup(app: AppHandle, file_name: String) -> Result<BackupVerification, AppError> {
+    backup::verify_backup(&backup_dir(&app)?, &file_name)
+}
+
+/// Replace the current database with a backup, saving a safety copy first
+#[tauri::command]
+pub fn restore_backup(app: AppHandle, file_name: String) -> Result<RestoreReport, AppError> {
+    let mut conn = db::get_connection(&app)?;
+    backup::restore_backup(&mut conn, &backup_dir(&app)?, &file_name, &SystemClock)
+}
@@ -0,0 +1,5 @@
+// Content tooling for the content pipeline; see content_cli.rs for commands
+
+fn main() {
+  std::process::exit(app_lib::content_cli::run(std::env::args().skip(1).collect()));
+}
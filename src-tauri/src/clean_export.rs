@@ -0,0 +1,116 @@
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::backup;
+use crate::error::{AppError, ResultExt};
+use crate::profiles;
+
+// ============================================================================
+// Clean Database Export
+// ============================================================================
+//
+// Produces a content-only copy of a database for shipping as seed_database.db:
+// everything authored stays, every player's data is removed.
+
+/// Tables cleared in the export besides the per-user tables: the users
+/// themselves, legacy tables, this install's shop rotation and clock settings
+const EXTRA_CLEARED_TABLES: &[&str] = &[
+    "users",
+    "user_challenge_history_new",
+    "equipment_inventory",
+    "shop_refresh_state",
+    "shop_active_inventory",
+    "clock_settings",
+];
+
+#[derive(Debug, Serialize)]
+pub struct CleanExport {
+    pub question_count: i64,
+    pub enemy_count: i64,
+    pub equipment_count: i64,
+    pub location_count: i64,
+    /// "table: N rows" for each table that had user data
+    pub cleared_tables: Vec<String>,
+}
+
+fn count(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+        .unwrap_or(0)
+}
+
+/// Delete every player's data, returning "table: N rows" for each table that had some
+pub fn clear_user_data(conn: &Connection) -> Result<Vec<String>, AppError> {
+    // Disable foreign keys temporarily to allow deletion
+    conn.execute("PRAGMA foreign_keys = OFF", [])
+        .context("Failed to disable foreign keys")?;
+
+    let mut cleared_tables = Vec::new();
+    for table in profiles::USER_TABLES.iter().chain(EXTRA_CLEARED_TABLES) {
+        // Tables missing from older databases are skipped
+        if let Ok(rows) = conn.execute(&format!("DELETE FROM {}", table), []) {
+            if rows > 0 {
+                cleared_tables.push(format!("{}: {} rows", table, rows));
+            }
+        }
+    }
+
+    conn.execute("PRAGMA foreign_keys = ON", [])
+        .context("Failed to re-enable foreign keys")?;
+    Ok(cleared_tables)
+}
+
+/// Copy the database behind source to export_path and strip user data from the copy
+pub fn export_clean_database(source: &Connection, export_path: &Path) -> Result<CleanExport, AppError> {
+    let mut export_conn = Connection::open(export_path)
+        .context("Failed to open export database")?;
+    backup::copy_database(source, &mut export_conn)?;
+
+    // A seed database should be one self-contained file
+    export_conn
+        .query_row("PRAGMA journal_mode = DELETE", [], |_| Ok(()))
+        .context("Failed to finalize export database")?;
+
+    let cleared_tables = clear_user_data(&export_conn)?;
+
+    // Vacuum to reclaim space
+    export_conn
+        .execute("VACUUM", [])
+        .context("Failed to vacuum database")?;
+
+    Ok(CleanExport {
+        question_count: count(&export_conn, "mcq_questions"),
+        enemy_count: count(&export_conn, "custom_enemies"),
+        equipment_count: count(&export_conn, "equipment_items"),
+        location_count: count(&export_conn, "narrative_locations"),
+        cleared_tables,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{scalar, seeded_db, TestUser};
+
+    #[test]
+    fn export_keeps_content_and_drops_users() {
+        let conn = seeded_db();
+        TestUser::new("exported").currency(500, 5).create(&conn);
+        let path = std::env::temp_dir().join(format!("code-tutor-clean-{}.db", uuid::Uuid::new_v4()));
+
+        let report = export_clean_database(&conn, &path).unwrap();
+        let exported = Connection::open(&path).unwrap();
+
+        assert_eq!(scalar::<i64>(&exported, "SELECT COUNT(*) FROM users", []), 0);
+        assert!(report.cleared_tables.iter().any(|t| t.starts_with("users:")));
+        assert_eq!(
+            scalar::<i64>(&exported, "SELECT COUNT(*) FROM equipment_items", []),
+            scalar::<i64>(&conn, "SELECT COUNT(*) FROM equipment_items", [])
+        );
+        // The source is untouched
+        assert!(scalar::<i64>(&conn, "SELECT COUNT(*) FROM users", []) > 0);
+
+        drop(exported);
+        let _ = std::fs::remove_file(&path);
+    }
+}
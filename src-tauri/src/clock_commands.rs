@@ -0,0 +1,51 @@
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::error::AppError;
+use crate::game::clock::{self, AppClock, Clock, TimeSettings};
+
+// ============================================================================
+// Clock Commands
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockStatus {
+    /// The app's current time (UTC, CURRENT_TIMESTAMP format), including any time travel
+    pub now: String,
+    /// The current game day, YYYY-MM-DD
+    pub today: String,
+    pub settings: TimeSettings,
+}
+
+pub(crate) fn clock_status(conn: &Connection) -> Result<ClockStatus, AppError> {
+    let clock = AppClock::load(conn)?;
+    Ok(ClockStatus {
+        now: clock::to_sqlite(clock.now()),
+        today: clock.today().to_string(),
+        settings: clock.settings().clone(),
+    })
+}
+
+#[tauri::command]
+pub fn get_clock_status(app: AppHandle) -> Result<ClockStatus, AppError> {
+    let conn = db::get_connection(&app)?;
+    clock_status(&conn)
+}
+
+/// Set the timezone days are reckoned in (an IANA name such as "Europe/Berlin", or
+/// None for the system timezone) and the hour a new day starts
+#[tauri::command]
+pub fn update_time_settings(
+    app: AppHandle,
+    timezone: Option<String>,
+    day_rollover_hour: u32,
+) -> Result<ClockStatus, AppError> {
+    db::unit_of_work(&app, |conn| {
+        let settings = TimeSettings { timezone, day_rollover_hour, ..clock::load_settings(conn)? };
+        clock::save_settings(conn, &settings)?;
+        clock_status(conn)
+    })
+}
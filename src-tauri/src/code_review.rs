@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::commands::ClaudeMessage;
+use crate::llm_provider::CompletionProvider;
+use crate::prompt_builder::render_template;
+use crate::error::AppError;
+
+// ============================================================================
+// Rubric-Based Code Review
+// ============================================================================
+//
+// Accepted solutions are reviewed against a per-language rubric with three
+// fixed categories. The model replies with JSON; scores are validated and
+// line comments that point outside the learner's code are dropped.
+
+/// Bump when the rubric or review prompt changes, so stored reviews can be told apart
+pub const REVIEW_RUBRIC_VERSION: u32 = 1;
+pub const REVIEW_CATEGORIES: [&str; 3] = ["naming", "complexity", "idioms"];
+
+const MAX_LINE_COMMENTS: usize = 8;
+const MAX_SOLUTION_CHARS: usize = 8000;
+/// Lesson descriptions can be long; the start is enough to explain the task
+const MAX_DESCRIPTION_CHARS: usize = 4000;
+
+const REVIEW_TEMPLATE: &str = include_str!("../prompts/review/review.md");
+const REVIEW_SYSTEM_PROMPT: &str =
+    "You are a friendly senior developer reviewing code written by someone learning to program. You always reply with valid JSON only.";
+
+fn rubric_for(language: &str) -> &'static str {
+    match language {
+        "python" => include_str!("../prompts/review/rubrics/python.md"),
+        "javascript" => include_str!("../prompts/review/rubrics/javascript.md"),
+        "gdscript" => include_str!("../prompts/review/rubrics/gdscript.md"),
+        "csharp" => include_str!("../prompts/review/rubrics/csharp.md"),
+        "ruby" => include_str!("../prompts/review/rubrics/ruby.md"),
+        "bash" => include_str!("../prompts/review/rubrics/bash.md"),
+        _ => include_str!("../prompts/review/rubrics/general.md"),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryScore {
+    pub category: String,
+    /// 1 (needs a lot of work) to 5 (excellent)
+    pub score: u8,
+    pub comment: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineComment {
+    /// 1-based line in the learner's solution
+    pub line: usize,
+    pub severity: String,
+    pub comment: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeReview {
+    pub rubric_version: u32,
+    pub model: String,
+    pub summary: String,
+    pub categories: Vec<CategoryScore>,
+    pub line_comments: Vec<LineComment>,
+    /// Mean of the category scores
+    pub overall_score: f64,
+}
+
+/// What gets reviewed
+pub struct ReviewRequest<'a> {
+    pub language: &'a str,
+    pub title: &'a str,
+    pub description: &'a str,
+    pub solution: &'a str,
+    pub reference_solution: Option<&'a str>,
+}
+
+/// The review as the model is asked to produce it
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawReview {
+    summary: String,
+    categories: Vec<CategoryScore>,
+    #[serde(default)]
+    line_comments: Vec<LineComment>,
+}
+
+fn numbered_lines(code: &str) -> String {
+    code.lines()
+        .enumerate()
+        .map(|(i, line)| format!("{:>3} | {}", i + 1, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn render_review_prompt(request: &ReviewRequest) -> Result<String, AppError> {
+    if request.solution.chars().count() > MAX_SOLUTION_CHARS {
+        return Err(AppError::Validation(format!("Solution is too long to review (over {} characters)", MAX_SOLUTION_CHARS)));
+    }
+
+    let vars = HashMap::from([
+        ("language", request.language.to_string()),
+        ("title", request.title.to_string()),
+        ("description", request.description.trim().chars().take(MAX_DESCRIPTION_CHARS).collect()),
+        ("rubric", rubric_for(request.language).trim().to_string()),
+        ("numbered_solution", numbered_lines(request.solution)),
+        ("reference_solution", request.reference_solution.unwrap_or_default().trim().to_string()),
+        ("max_line_comments", MAX_LINE_COMMENTS.to_string()),
+    ]);
+    render_template(REVIEW_TEMPLATE, &vars)
+}
+
+/// Parse and validate a review response. Every rubric category must be scored
+/// exactly once from 1 to 5; line comments outside the solution are dropped.
+pub fn parse_review(response: &str, solution: &str, model: &str) -> Result<CodeReview, AppError> {
+    let start = response.find('{').ok_or_else(|| AppError::Ai("Review did not contain a JSON object".to_string()))?;
+    let end = response.rfind('}').filter(|end| *end > start).ok_or_else(|| AppError::Ai("Review did not contain a JSON object".to_string()))?;
+
+    let raw: RawReview = serde_json::from_str(&response[start..=end])
+        .map_err(|e| AppError::Ai(format!("Review is not in the expected format: {}", e)))?;
+
+    let mut categories = Vec::new();
+    for name in REVIEW_CATEGORIES {
+        let mut matches = raw.categories.iter().filter(|c| c.category.eq_ignore_ascii_case(name));
+        let category = matches.next().ok_or_else(|| AppError::Ai(format!("Review is missing the '{}' category", name)))?;
+        if matches.next().is_some() {
+            return Err(AppError::Validation(format!("Review scores the '{}' category more than once", name)));
+        }
+        if !(1..=5).contains(&category.score) {
+            return Err(AppError::Validation(format!("Score for '{}' must be between 1 and 5, got {}", name, category.score)));
+        }
+        categories.push(CategoryScore {
+            category: name.to_string(),
+            score: category.score,
+            comment: category.comment.trim().to_string(),
+        });
+    }
+
+    let line_count = solution.lines().count();
+    let line_comments: Vec<LineComment> = raw
+        .line_comments
+        .into_iter()
+        .filter(|c| c.line >= 1 && c.line <= line_count && !c.comment.trim().is_empty())
+        .map(|c| LineComment {
+            severity: match c.severity.as_str() {
+                "praise" | "suggestion" | "issue" => c.severity,
+                _ => "suggestion".to_string(),
+            },
+            comment: c.comment.trim().to_string(),
+            ..c
+        })
+        .take(MAX_LINE_COMMENTS)
+        .collect();
+
+    let overall_score = categories.iter().map(|c| c.score as f64).sum::<f64>() / categories.len() as f64;
+
+    Ok(CodeReview {
+        rubric_version: REVIEW_RUBRIC_VERSION,
+        model: model.to_string(),
+        summary: raw.summary.trim().to_string(),
+        categories,
+        line_comments,
+        overall_score: (overall_score * 10.0).round() / 10.0,
+    })
+}
+
+/// Ask the provider to review a solution
+pub async fn review_code(provider: &dyn CompletionProvider, request: &ReviewRequest<'_>) -> Result<CodeReview, AppError> {
+    if request.solution.trim().is_empty() {
+        return Err(AppError::Validation("There is no solution to review".to_string()));
+    }
+
+    let messages = vec![ClaudeMessage {
+        role: "user".to_string(),
+        content: render_review_prompt(request)?,
+    }];
+
+    let response = provider.complete(REVIEW_SYSTEM_PROMPT, messages).await?;
+    parse_review(&response, request.solution, provider.model())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOLUTION: &str = "def add(a, b):\n    return a + b\n";
+
+    fn review_json(categories: &str, line_comments: &str) -> String {
+        format!(
+            r#"Here is my review:
+{{"summary": " Clear and correct. ", "categories": [{}], "lineComments": [{}]}}"#,
+            categories, line_comments
+        )
+    }
+
+    fn score(category: &str, score: i64) -> String {
+        format!(r#"{{"category": "{}", "score": {}, "comment": " ok "}}"#, category, score)
+    }
+
+    fn all_scored(naming: i64, complexity: i64, idioms: i64) -> String {
+        [score("naming", naming), score("complexity", complexity), score("idioms", idioms)].join(",")
+    }
+
+    #[test]
+    fn valid_review_is_parsed_in_rubric_order() {
+        let categories = [score("Idioms", 3), score("naming", 5), score("COMPLEXITY", 4)].join(",");
+        let review = parse_review(&review_json(&categories, ""), SOLUTION, "test-model").unwrap();
+
+        let scored: Vec<(&str, u8)> = review.categories.iter().map(|c| (c.category.as_str(), c.score)).collect();
+        assert_eq!(scored, vec![("naming", 5), ("complexity", 4), ("idioms", 3)]);
+        assert_eq!(review.overall_score, 4.0);
+        assert_eq!(review.summary, "Clear and correct.");
+        assert_eq!(review.categories[0].comment, "ok");
+        assert_eq!(review.model, "test-model");
+        assert_eq!(review.rubric_version, REVIEW_RUBRIC_VERSION);
+    }
+
+    #[test]
+    fn missing_or_repeated_categories_are_rejected() {
+        let missing = [score("naming", 4), score("complexity", 4)].join(",");
+        let err = parse_review(&review_json(&missing, ""), SOLUTION, "m").unwrap_err();
+        assert!(matches!(&err, AppError::Ai(msg) if msg.contains("'idioms'")), "{:?}", err);
+
+        let repeated = format!("{},{}", all_scored(4, 4, 4), score("naming", 2));
+        let err = parse_review(&review_json(&repeated, ""), SOLUTION, "m").unwrap_err();
+        assert!(matches!(&err, AppError::Validation(msg) if msg.contains("'naming'")), "{:?}", err);
+    }
+
+    #[test]
+    fn out_of_range_scores_are_rejected() {
+        for (scores, category) in [(all_scored(0, 3, 3), "naming"), (all_scored(3, 6, 3), "complexity")] {
+            let err = parse_review(&review_json(&scores, ""), SOLUTION, "m").unwrap_err();
+            assert!(matches!(&err, AppError::Validation(msg) if msg.contains(category)), "{:?}", err);
+        }
+
+        // Scores that don't fit a u8 never make it past deserialization
+        for scores in [all_scored(-1, 3, 3), all_scored(3, 3, 300)] {
+            assert!(matches!(parse_review(&review_json(&scores, ""), SOLUTION, "m"), Err(AppError::Ai(_))));
+        }
+    }
+
+    #[test]
+    fn malformed_responses_are_rejected() {
+        let responses = [
+            "I could not review this code.".to_string(),
+            "} backwards {".to_string(),
+            r#"{"summary": "Fine", "categories": "all good"}"#.to_string(),
+            r#"{"categories": []}"#.to_string(),
+            format!(r#"{{"summary": "Fine", "categories": [{{"category": "naming", "score": 4}}, {}]}}"#, all_scored(4, 4, 4)),
+            format!(r#"{{"summary": "Fine", "categories": [{}]"#, all_scored(4, 4, 4)),
+        ];
+        for response in responses {
+            assert!(matches!(parse_review(&response, SOLUTION, "m"), Err(AppError::Ai(_))), "{}", response);
+        }
+    }
+
+    #[test]
+    fn line_comments_are_kept_inside_the_solution() {
+        let comments = r#"
+            {"line": 0, "severity": "issue", "comment": "before the code"},
+            {"line": 1, "severity": "praise", "comment": " Good name "},
+            {"line": 2, "severity": "nitpick", "comment": "Unknown severity"},
+            {"line": 2, "severity": "issue", "comment": "   "},
+            {"line": 3, "severity": "issue", "comment": "past the end"}"#;
+        let review = parse_review(&review_json(&all_scored(4, 4, 5), comments), SOLUTION, "m").unwrap();
+
+        let kept: Vec<(usize, &str, &str)> = review
+            .line_comments
+            .iter()
+            .map(|c| (c.line, c.severity.as_str(), c.comment.as_str()))
+            .collect();
+        assert_eq!(kept, vec![(1, "praise", "Good name"), (2, "suggestion", "Unknown severity")]);
+        assert_eq!(review.overall_score, 4.3);
+    }
+
+    #[test]
+    fn line_comments_are_capped() {
+        let solution = "x = 1\n".repeat(20);
+        let comments: Vec<String> = (1..=20)
+            .map(|line| format!(r#"{{"line": {}, "severity": "issue", "comment": "c"}}"#, line))
+            .collect();
+        let review = parse_review(&review_json(&all_scored(3, 3, 3), &comments.join(",")), &solution, "m").unwrap();
+
+        assert_eq!(review.line_comments.len(), MAX_LINE_COMMENTS);
+    }
+
+    #[test]
+    fn oversized_solutions_are_not_sent_for_review() {
+        let solution = "x".repeat(MAX_SOLUTION_CHARS + 1);
+        let request = ReviewRequest {
+            language: "python",
+            title: "Add",
+            description: "Add two numbers",
+            solution: &solution,
+            reference_solution: None,
+        };
+
+        assert!(matches!(render_review_prompt(&request), Err(AppError::Validation(_))));
+    }
+}
@@ -0,0 +1,159 @@
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::code_review::{self, CodeReview, ReviewRequest};
+use crate::db;
+use crate::llm_provider::ClaudeProvider;
+use crate::puzzle_commands;
+use crate::tutor_commands;
+use crate::error::{AppError, ResultExt};
+
+/// A review stored on a user_puzzle_progress row
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredCodeReview {
+    pub review: CodeReview,
+    pub reviewed_solution: String,
+    pub reviewed_at: String,
+}
+
+// ============================================================================
+// Code Review Commands
+// ============================================================================
+
+/// Review the accepted solution of a solved puzzle and store the review on its progress row.
+/// The solution is read from the database rather than taken from the caller, so the
+/// review always matches what was accepted by `mark_puzzle_solved`.
+#[tauri::command]
+pub async fn review_puzzle_solution(
+    app: AppHandle,
+    user_id: i32,
+    puzzle_id: String,
+    language_id: String,
+    model: String,
+) -> Result<StoredCodeReview, AppError> {
+    let solution = {
+        let conn = db::get_connection(&app)?;
+        conn.query_row(
+            "SELECT user_solution FROM user_puzzle_progress
+             WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3 AND status IN ('solved', 'optimized')",
+            params![user_id, &puzzle_id, &language_id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()
+        .context("Failed to load puzzle progress")?
+        .flatten()
+        .ok_or_else(|| AppError::Validation("Only solved puzzles can be reviewed".to_string()))?
+    };
+
+    let puzzle = puzzle_commands::get_puzzle(app.clone(), puzzle_id.clone())?;
+    let reference = puzzle_commands::get_puzzle_implementation(app.clone(), puzzle_id.clone(), language_id.clone())
+        .ok()
+        .map(|implementation| implementation.solution_code);
+
+    let provider = ClaudeProvider {
+        app: app.clone(),
+        model,
+        user_id,
+        context: format!("review:puzzle:{}", puzzle_id),
+    };
+    let review = code_review::review_code(
+        &provider,
+        &ReviewRequest {
+            language: &language_id,
+            title: &puzzle.title,
+            description: &puzzle.description,
+            solution: &solution,
+            reference_solution: reference.as_deref(),
+        },
+    )
+    .await?;
+
+    let review_json = serde_json::to_string(&review)
+        .context("Failed to serialize review")?;
+
+    let conn = db::get_connection(&app)?;
+    conn.execute(
+        "UPDATE user_puzzle_progress
+         SET code_review = ?4, code_review_score = ?5, code_reviewed_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3",
+        params![user_id, &puzzle_id, &language_id, review_json, review.overall_score],
+    )
+    .context("Failed to save code review")?;
+
+    get_puzzle_code_review(app, user_id, puzzle_id, language_id)?
+        .ok_or_else(|| AppError::NotFound("Failed to load the saved code review".to_string()))
+}
+
+/// The stored review for a puzzle, if it has been reviewed
+#[tauri::command]
+pub fn get_puzzle_code_review(
+    app: AppHandle,
+    user_id: i32,
+    puzzle_id: String,
+    language_id: String,
+) -> Result<Option<StoredCodeReview>, AppError> {
+    let conn = db::get_connection(&app)?;
+
+    let row = conn
+        .query_row(
+            "SELECT code_review, user_solution, code_reviewed_at FROM user_puzzle_progress
+             WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3 AND code_review IS NOT NULL",
+            params![user_id, puzzle_id, language_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            },
+        )
+        .optional()
+        .context("Failed to load code review")?;
+
+    let Some((review_json, solution, reviewed_at)) = row else {
+        return Ok(None);
+    };
+
+    let review: CodeReview = serde_json::from_str(&review_json)
+        .context("Failed to parse stored code review")?;
+
+    Ok(Some(StoredCodeReview {
+        review,
+        reviewed_solution: solution.unwrap_or_default(),
+        reviewed_at,
+    }))
+}
+
+/// Review a completed lesson solution. Lesson progress is kept by the frontend,
+/// so the review is returned for it to store rather than saved here.
+#[tauri::command]
+pub async fn review_lesson_solution(
+    app: AppHandle,
+    language: String,
+    lesson_id: i64,
+    solution: String,
+    model: String,
+    user_id: i32,
+) -> Result<CodeReview, AppError> {
+    let lesson = tutor_commands::load_lesson(&app, &language, lesson_id)?;
+
+    let provider = ClaudeProvider {
+        app,
+        model,
+        user_id,
+        context: format!("review:lesson:{}", lesson_id),
+    };
+    code_review::review_code(
+        &provider,
+        &ReviewRequest {
+            language: &language,
+            title: &lesson.title,
+            description: &lesson.description,
+            solution: &solution,
+            reference_solution: Some(lesson.solution_code.as_str()).filter(|code| !code.trim().is_empty()),
+        },
+    )
+    .await
+}
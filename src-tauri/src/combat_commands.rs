@@ -1,12 +1,11 @@
-use rusqlite::params;
 use serde::{Deserialize, Serialize};
-use serde_json;
 use tauri::AppHandle;
-use rand::Rng;
 
-use crate::db::get_connection;
-use crate::rpg_commands::{get_character_stats, Ability};
-use crate::dungeon_commands::{EnemyType, BossEnemy};
+use crate::db::{get_connection, unit_of_work};
+use crate::game::character::{load_character_stats, Ability};
+use crate::game::enemies::{load_boss, load_enemy};
+use crate::error::AppError;
+use crate::game::combat::{self, CombatRewards, CombatTurnResult, DamageResult, Encounter, EscapeAttempt};
 
 // ============================================================================
 // COMBAT STATE
@@ -29,150 +28,62 @@ pub struct ActiveCombat {
     pub active_debuffs: Option<String>,     // JSON
 }
 
-#[tauri::command]
-pub fn start_combat(
-    app: AppHandle,
-    user_id: i64,
-    enemy: EnemyType,
-) -> Result<ActiveCombat, String> {
-    let conn = get_connection(&app)?;
-
-    // Update user dungeon progress - set in_combat
-    conn.execute(
-        "UPDATE user_dungeon_progress
-         SET in_combat = TRUE,
-             current_enemy_id = ?,
-             current_enemy_health = ?,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![enemy.id, enemy.base_health, user_id],
-    )
-    .map_err(|e| format!("Failed to start combat: {}", e))?;
-
-    // Initialize or update dungeon session
-    conn.execute(
-        "INSERT OR REPLACE INTO dungeon_session
-            (user_id, combat_turn, enemy_current_health, ability_cooldowns, active_buffs, active_debuffs)
-         VALUES (?, 0, ?, '{}', '[]', '[]')",
-        params![user_id, enemy.base_health],
-    )
-    .map_err(|e| format!("Failed to initialize combat session: {}", e))?;
-
-    Ok(ActiveCombat {
-        user_id,
-        enemy_id: enemy.id.clone(),
-        enemy_name: enemy.name,
-        enemy_current_health: enemy.base_health,
-        enemy_max_health: enemy.base_health,
-        enemy_damage: enemy.base_damage,
-        enemy_defense: enemy.base_defense,
-        is_boss: false,
-        icon: enemy.icon,
-        combat_turn: 0,
-        ability_cooldowns: Some("{}".to_string()),
-        active_buffs: Some("[]".to_string()),
-        active_debuffs: Some("[]".to_string()),
-    })
+impl ActiveCombat {
+    fn new(user_id: i64, encounter: Encounter) -> Self {
+        ActiveCombat {
+            user_id,
+            enemy_id: encounter.enemy_id,
+            enemy_name: encounter.enemy_name,
+            enemy_current_health: encounter.max_health,
+            enemy_max_health: encounter.max_health,
+            enemy_damage: encounter.damage,
+            enemy_defense: encounter.defense,
+            is_boss: encounter.is_boss,
+            icon: encounter.icon,
+            combat_turn: 0,
+            ability_cooldowns: Some("{}".to_string()),
+            active_buffs: Some("[]".to_string()),
+            active_debuffs: Some("[]".to_string()),
+        }
+    }
 }
 
 #[tauri::command]
-pub fn start_boss_combat(
-    app: AppHandle,
-    user_id: i64,
-    boss: BossEnemy,
-) -> Result<ActiveCombat, String> {
-    let conn = get_connection(&app)?;
-
-    // Update user dungeon progress - set in_combat
-    conn.execute(
-        "UPDATE user_dungeon_progress
-         SET in_combat = TRUE,
-             current_enemy_id = ?,
-             current_enemy_health = ?,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![boss.id, boss.health, user_id],
-    )
-    .map_err(|e| format!("Failed to start boss combat: {}", e))?;
-
-    // Initialize or update dungeon session
-    conn.execute(
-        "INSERT OR REPLACE INTO dungeon_session
-            (user_id, combat_turn, enemy_current_health, ability_cooldowns, active_buffs, active_debuffs)
-         VALUES (?, 0, ?, '{}', '[]', '[]')",
-        params![user_id, boss.health],
-    )
-    .map_err(|e| format!("Failed to initialize boss combat session: {}", e))?;
+pub fn start_combat(app: AppHandle, user_id: i64, enemy_id: String) -> Result<ActiveCombat, AppError> {
+    let encounter = unit_of_work(&app, |conn| {
+        let encounter = Encounter::enemy(&load_enemy(conn, &enemy_id)?);
+        combat::begin(conn, user_id, &encounter)?;
+        Ok(encounter)
+    })?;
+
+    Ok(ActiveCombat::new(user_id, encounter))
+}
 
-    Ok(ActiveCombat {
-        user_id,
-        enemy_id: boss.id.clone(),
-        enemy_name: boss.name,
-        enemy_current_health: boss.health,
-        enemy_max_health: boss.health,
-        enemy_damage: boss.damage,
-        enemy_defense: boss.defense,
-        is_boss: true,
-        icon: boss.icon,
-        combat_turn: 0,
-        ability_cooldowns: Some("{}".to_string()),
-        active_buffs: Some("[]".to_string()),
-        active_debuffs: Some("[]".to_string()),
-    })
+#[tauri::command]
+pub fn start_boss_combat(app: AppHandle, user_id: i64, boss_id: String) -> Result<ActiveCombat, AppError> {
+    let encounter = unit_of_work(&app, |conn| {
+        let encounter = Encounter::boss(&load_boss(conn, &boss_id)?);
+        combat::begin(conn, user_id, &encounter)?;
+        Ok(encounter)
+    })?;
+
+    Ok(ActiveCombat::new(user_id, encounter))
 }
 
 // ============================================================================
 // DAMAGE CALCULATION
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DamageResult {
-    pub damage: i64,
-    pub is_critical: bool,
-    pub is_dodged: bool,
-}
-
 #[tauri::command]
 pub fn calculate_player_damage(
     app: AppHandle,
     user_id: i64,
     ability: Ability,
     challenge_success: bool,
-) -> Result<DamageResult, String> {
-    let stats = get_character_stats(app, user_id)?;
-
-    // Base damage from ability
-    let mut damage = ability.base_value;
-
-    // Add stat scaling
-    let stat_value = match ability.scaling_stat.as_str() {
-        "strength" => stats.strength,
-        "intelligence" => stats.intelligence,
-        "dexterity" => stats.dexterity,
-        _ => 0,
-    };
-
-    damage += (stat_value as f64 * ability.scaling_ratio) as i64;
-
-    // Apply challenge success/failure modifier
-    if !challenge_success {
-        damage = (damage as f64 * 0.5) as i64; // 50% damage on failure
-    }
-
-    // Check for critical hit
-    let mut rng = rand::thread_rng();
-    let crit_roll: f64 = rng.gen();
-    let is_critical = crit_roll < stats.critical_chance;
-
-    if is_critical {
-        damage *= 2;
-    }
-
-    Ok(DamageResult {
-        damage,
-        is_critical,
-        is_dodged: false,
-    })
+) -> Result<DamageResult, AppError> {
+    let conn = get_connection(&app)?;
+    let stats = load_character_stats(&conn, user_id)?;
+    Ok(combat::roll_player_damage(&stats, &ability, challenge_success, &mut rand::thread_rng()))
 }
 
 #[tauri::command]
@@ -180,181 +91,25 @@ pub fn calculate_enemy_damage(
     app: AppHandle,
     user_id: i64,
     enemy_base_damage: i64,
-) -> Result<DamageResult, String> {
-    let stats = get_character_stats(app, user_id)?;
-
-    // Check for dodge
-    let mut rng = rand::thread_rng();
-    let dodge_roll: f64 = rng.gen();
-    let is_dodged = dodge_roll < stats.dodge_chance;
-
-    if is_dodged {
-        return Ok(DamageResult {
-            damage: 0,
-            is_critical: false,
-            is_dodged: true,
-        });
-    }
-
-    // Calculate damage after defense
-    let damage = (enemy_base_damage - stats.defense).max(1);
-
-    Ok(DamageResult {
-        damage,
-        is_critical: false,
-        is_dodged: false,
-    })
+) -> Result<DamageResult, AppError> {
+    let conn = get_connection(&app)?;
+    let stats = load_character_stats(&conn, user_id)?;
+    Ok(combat::roll_enemy_damage(&stats, enemy_base_damage, &mut rand::thread_rng()))
 }
 
 // ============================================================================
 // COMBAT ACTIONS
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CombatTurnResult {
-    pub player_damage_dealt: i64,
-    pub player_damage_taken: i64,
-    pub enemy_current_health: i64,
-    pub player_current_health: i64,
-    pub player_current_mana: i64,
-    pub enemy_defeated: bool,
-    pub player_defeated: bool,
-    pub is_critical: bool,
-    pub is_dodged: bool,
-    pub turn_number: i64,
-}
-
 #[tauri::command]
 pub fn execute_combat_turn(
     app: AppHandle,
     user_id: i64,
     ability_id: String,
     challenge_success: bool,
-) -> Result<CombatTurnResult, String> {
-    let conn = get_connection(&app)?;
-
-    // Get current combat state
-    let stats = get_character_stats(app.clone(), user_id)?;
-
-    // Get ability
-    let ability: Ability = conn
-        .query_row(
-            "SELECT id, name, description, type, required_level, mana_cost, cooldown_turns,
-                    base_value, scaling_stat, scaling_ratio, additional_effects, icon,
-                    animation_text, created_at
-             FROM abilities
-             WHERE id = ?",
-            params![ability_id],
-            |row| {
-                Ok(Ability {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    ability_type: row.get(3)?,
-                    required_level: row.get(4)?,
-                    mana_cost: row.get(5)?,
-                    cooldown_turns: row.get(6)?,
-                    base_value: row.get(7)?,
-                    scaling_stat: row.get(8)?,
-                    scaling_ratio: row.get(9)?,
-                    additional_effects: row.get(10)?,
-                    icon: row.get(11)?,
-                    animation_text: row.get(12)?,
-                    created_at: row.get(13)?,
-                })
-            },
-        )
-        .map_err(|e| format!("Failed to get ability: {}", e))?;
-
-    // Check if player has enough mana
-    if stats.current_mana < ability.mana_cost {
-        return Err("Not enough mana".to_string());
-    }
-
-    // Get enemy state from session
-    let (enemy_health, enemy_damage, turn): (i64, i64, i64) = conn
-        .query_row(
-            "SELECT enemy_current_health, combat_turn
-             FROM dungeon_session
-             WHERE user_id = ?",
-            params![user_id],
-            |row| Ok((row.get(0)?, 15i64, row.get(1)?)), // TODO: Get actual enemy damage
-        )
-        .map_err(|e| format!("Failed to get combat session: {}", e))?;
-
-    // Calculate player damage
-    let player_damage_result = calculate_player_damage(app.clone(), user_id, ability.clone(), challenge_success)?;
-    let mut player_damage_dealt = player_damage_result.damage;
-
-    // Handle heal ability
-    let mut new_player_health = stats.current_health;
-    if ability.ability_type == "heal" {
-        new_player_health = (stats.current_health + player_damage_dealt).min(stats.max_health);
-        player_damage_dealt = 0; // Don't damage enemy with heal
-    }
-
-    // Apply damage to enemy
-    let new_enemy_health = (enemy_health - player_damage_dealt).max(0);
-    let enemy_defeated = new_enemy_health <= 0;
-
-    // Deduct mana
-    let new_player_mana = stats.current_mana - ability.mana_cost;
-
-    // Update player stats
-    conn.execute(
-        "UPDATE character_stats
-         SET current_health = ?,
-             current_mana = ?,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![new_player_health, new_player_mana, user_id],
-    )
-    .map_err(|e| format!("Failed to update player stats: {}", e))?;
-
-    // Enemy turn (if not defeated)
-    let mut player_damage_taken = 0;
-    let mut player_defeated = false;
-
-    if !enemy_defeated {
-        let enemy_damage_result = calculate_enemy_damage(app.clone(), user_id, enemy_damage)?;
-        player_damage_taken = enemy_damage_result.damage;
-
-        new_player_health = (new_player_health - player_damage_taken).max(0);
-        player_defeated = new_player_health <= 0;
-
-        // Update player health after enemy attack
-        conn.execute(
-            "UPDATE character_stats
-             SET current_health = ?,
-                 updated_at = CURRENT_TIMESTAMP
-             WHERE user_id = ?",
-            params![new_player_health, user_id],
-        )
-        .map_err(|e| format!("Failed to update player health: {}", e))?;
-    }
-
-    // Update combat session
-    conn.execute(
-        "UPDATE dungeon_session
-         SET combat_turn = combat_turn + 1,
-             enemy_current_health = ?,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![new_enemy_health, user_id],
-    )
-    .map_err(|e| format!("Failed to update combat session: {}", e))?;
-
-    Ok(CombatTurnResult {
-        player_damage_dealt,
-        player_damage_taken,
-        enemy_current_health: new_enemy_health,
-        player_current_health: new_player_health,
-        player_current_mana: new_player_mana,
-        enemy_defeated,
-        player_defeated,
-        is_critical: player_damage_result.is_critical,
-        is_dodged: false,
-        turn_number: turn + 1,
+) -> Result<CombatTurnResult, AppError> {
+    unit_of_work(&app, |conn| {
+        combat::execute_turn(conn, &mut rand::thread_rng(), user_id, &ability_id, challenge_success)
     })
 }
 
@@ -362,327 +117,21 @@ pub fn execute_combat_turn(
 // COMBAT REWARDS
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CombatRewards {
-    pub xp_gained: i64,
-    pub gold_gained: i64,
-    pub items_looted: Vec<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct LootEntry {
-    item: String,
-    chance: f64,
-}
-
+/// Pay out the fight the user has just won; the rewards come from the enemy recorded at the start
 #[tauri::command]
-pub fn end_combat_victory(
-    app: AppHandle,
-    user_id: i64,
-    enemy: EnemyType,
-    turns_taken: i64,
-    damage_dealt: i64,
-    damage_taken: i64,
-) -> Result<CombatRewards, String> {
-    let conn = get_connection(&app)?;
-
-    // Calculate rewards
-    let mut rng = rand::thread_rng();
-    let gold_gained = rng.gen_range(enemy.gold_drop_min..=enemy.gold_drop_max);
-    let xp_gained = enemy.xp_reward;
-
-    // Award gold to DUNGEON currency (character_stats.current_gold) for dungeon shop
-    conn.execute(
-        "UPDATE character_stats
-         SET current_gold = current_gold + ?,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![gold_gained, user_id],
-    )
-    .map_err(|e| format!("Failed to award dungeon gold: {}", e))?;
-
-    // Also update gamification gold for general rewards tracking
-    conn.execute(
-        "UPDATE user_currency
-         SET gold = gold + ?,
-             lifetime_gold_earned = lifetime_gold_earned + ?
-         WHERE user_id = ?",
-        params![gold_gained, gold_gained, user_id],
-    )
-    .map_err(|e| format!("Failed to award gamification gold: {}", e))?;
-
-    // Apply XP and check for level up
-    let (current_level, current_xp): (i64, i64) = conn
-        .query_row(
-            "SELECT level, COALESCE((SELECT total_xp_earned FROM user_dungeon_progress WHERE user_id = ?), 0)
-             FROM character_stats WHERE user_id = ?",
-            params![user_id, user_id],
-            |row| Ok((row.get(0)?, row.get(1)?)),
-        )
-        .map_err(|e| format!("Failed to get current level: {}", e))?;
-
-    // XP required per level: 100 XP per level (level 1 needs 100 XP, level 2 needs 200 XP total, etc.)
-    let new_total_xp = current_xp + xp_gained;
-
-    // Calculate if we leveled up
-    let mut new_level = current_level;
-    let mut xp_remaining = new_total_xp;
-    let mut levels_gained = 0i64;
-
-    while xp_remaining >= new_level * 100 {
-        xp_remaining -= new_level * 100;
-        new_level += 1;
-        levels_gained += 1;
-    }
-
-    // Apply level up if we gained levels
-    if levels_gained > 0 {
-        // Each level grants stat points - player chooses how to spend them
-        let stat_points_gained = levels_gained;
-
-        conn.execute(
-            "UPDATE character_stats
-             SET level = ?,
-                 stat_points_available = stat_points_available + ?,
-                 updated_at = CURRENT_TIMESTAMP
-             WHERE user_id = ?",
-            params![new_level, stat_points_gained, user_id],
-        )
-        .map_err(|e| format!("Failed to apply level up: {}", e))?;
-
-        log::info!(
-            "User {} leveled up! {} -> {} (+{} stat points)",
-            user_id, current_level, new_level, stat_points_gained
-        );
-
-        // Check for new ability unlocks
-        conn.execute(
-            "INSERT OR IGNORE INTO user_abilities (user_id, ability_id, unlocked_at)
-             SELECT ?, id, CURRENT_TIMESTAMP
-             FROM abilities
-             WHERE required_level <= ?",
-            params![user_id, new_level],
-        )
-        .map_err(|e| format!("Failed to unlock abilities: {}", e))?;
-    }
-
-    // Update dungeon progress
-    conn.execute(
-        "UPDATE user_dungeon_progress
-         SET in_combat = FALSE,
-             current_enemy_id = NULL,
-             current_enemy_health = NULL,
-             total_enemies_defeated = total_enemies_defeated + 1,
-             total_gold_earned = total_gold_earned + ?,
-             total_xp_earned = total_xp_earned + ?,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![gold_gained, xp_gained, user_id],
-    )
-    .map_err(|e| format!("Failed to update dungeon progress: {}", e))?;
-
-    // Log combat
-    conn.execute(
-        "INSERT INTO dungeon_combat_log
-            (user_id, enemy_type, enemy_name, floor_number, is_boss, victory, turns_taken,
-             damage_dealt, damage_taken, xp_gained, gold_gained)
-         SELECT ?, ?, ?, current_floor, FALSE, TRUE, ?, ?, ?, ?, ?
-         FROM user_dungeon_progress
-         WHERE user_id = ?",
-        params![
-            user_id,
-            enemy.id,
-            enemy.name,
-            turns_taken,
-            damage_dealt,
-            damage_taken,
-            xp_gained,
-            gold_gained,
-            user_id
-        ],
-    )
-    .map_err(|e| format!("Failed to log combat: {}", e))?;
-
-    // Process loot drops from enemy loot_table
-    let mut items_looted: Vec<String> = vec![];
-
-    if let Some(loot_table_json) = &enemy.loot_table {
-        if !loot_table_json.is_empty() {
-            // Parse loot table JSON: [{"item": "health_potion_small", "chance": 0.15}, ...]
-            if let Ok(loot_entries) = serde_json::from_str::<Vec<LootEntry>>(loot_table_json) {
-                for entry in loot_entries {
-                    let roll: f64 = rng.gen();
-                    if roll < entry.chance {
-                        // Item dropped! Determine if it's equipment or consumable
-                        let item_id = &entry.item;
-
-                        // Try to add as consumable first
-                        let is_consumable: bool = conn
-                            .query_row(
-                                "SELECT EXISTS(SELECT 1 FROM consumable_items WHERE id = ?)",
-                                params![item_id],
-                                |row| row.get(0),
-                            )
-                            .unwrap_or(false);
-
-                        if is_consumable {
-                            // Add to consumable inventory (upsert)
-                            conn.execute(
-                                "INSERT INTO user_consumable_inventory (user_id, consumable_id, quantity, acquired_at)
-                                 VALUES (?, ?, 1, CURRENT_TIMESTAMP)
-                                 ON CONFLICT(user_id, consumable_id) DO UPDATE SET
-                                     quantity = quantity + 1",
-                                params![user_id, item_id],
-                            )
-                            .map_err(|e| format!("Failed to add consumable loot: {}", e))?;
-
-                            // Get item name for display
-                            let item_name: String = conn
-                                .query_row(
-                                    "SELECT name FROM consumable_items WHERE id = ?",
-                                    params![item_id],
-                                    |row| row.get(0),
-                                )
-                                .unwrap_or_else(|_| item_id.clone());
-
-                            items_looted.push(item_name);
-                            log::info!("User {} looted consumable: {}", user_id, item_id);
-                        } else {
-                            // Try as equipment
-                            let is_equipment: bool = conn
-                                .query_row(
-                                    "SELECT EXISTS(SELECT 1 FROM equipment_items WHERE id = ?)",
-                                    params![item_id],
-                                    |row| row.get(0),
-                                )
-                                .unwrap_or(false);
-
-                            if is_equipment {
-                                // Check if already in inventory
-                                let existing: Option<i64> = conn
-                                    .query_row(
-                                        "SELECT quantity FROM user_equipment_inventory
-                                         WHERE user_id = ? AND equipment_id = ?",
-                                        params![user_id, item_id],
-                                        |row| row.get(0),
-                                    )
-                                    .ok();
-
-                                if existing.is_some() {
-                                    conn.execute(
-                                        "UPDATE user_equipment_inventory
-                                         SET quantity = quantity + 1
-                                         WHERE user_id = ? AND equipment_id = ?",
-                                        params![user_id, item_id],
-                                    )
-                                    .map_err(|e| format!("Failed to update equipment quantity: {}", e))?;
-                                } else {
-                                    conn.execute(
-                                        "INSERT INTO user_equipment_inventory (user_id, equipment_id, quantity, acquired_at)
-                                         VALUES (?, ?, 1, CURRENT_TIMESTAMP)",
-                                        params![user_id, item_id],
-                                    )
-                                    .map_err(|e| format!("Failed to add equipment loot: {}", e))?;
-                                }
-
-                                // Get item name for display
-                                let item_name: String = conn
-                                    .query_row(
-                                        "SELECT name FROM equipment_items WHERE id = ?",
-                                        params![item_id],
-                                        |row| row.get(0),
-                                    )
-                                    .unwrap_or_else(|_| item_id.clone());
-
-                                items_looted.push(item_name);
-                                log::info!("User {} looted equipment: {}", user_id, item_id);
-                            } else {
-                                // Item ID not found in either table - log but don't fail
-                                log::warn!("Loot item '{}' not found in consumable or equipment tables", item_id);
-                            }
-                        }
-                    }
-                }
-            } else {
-                log::warn!("Failed to parse loot_table JSON for enemy {}: {}", enemy.id, loot_table_json);
-            }
-        }
-    }
-
-    Ok(CombatRewards {
-        xp_gained,
-        gold_gained,
-        items_looted,
-    })
+pub fn end_combat_victory(app: AppHandle, user_id: i64) -> Result<CombatRewards, AppError> {
+    unit_of_work(&app, |conn| combat::resolve_victory(conn, &mut rand::thread_rng(), user_id))
 }
 
 #[tauri::command]
-pub fn end_combat_defeat(app: AppHandle, user_id: i64) -> Result<(), String> {
-    let conn = get_connection(&app)?;
-
-    // Calculate gold penalty (10%)
-    let gold_lost: i64 = conn
-        .query_row(
-            "SELECT CAST(gold * 0.1 AS INTEGER) FROM user_currency WHERE user_id = ?",
-            params![user_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
-
-    // Apply penalty
-    conn.execute(
-        "UPDATE user_currency
-         SET gold = gold - ?
-         WHERE user_id = ?",
-        params![gold_lost, user_id],
-    )
-    .map_err(|e| format!("Failed to apply gold penalty: {}", e))?;
-
-    // Update dungeon progress
-    conn.execute(
-        "UPDATE user_dungeon_progress
-         SET in_combat = FALSE,
-             current_enemy_id = NULL,
-             current_enemy_health = NULL,
-             total_deaths = total_deaths + 1,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![user_id],
-    )
-    .map_err(|e| format!("Failed to update dungeon progress: {}", e))?;
-
-    // Restore health and mana (respawn with full HP/MP)
-    conn.execute(
-        "UPDATE character_stats
-         SET current_health = max_health,
-             current_mana = max_mana,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![user_id],
-    )
-    .map_err(|e| format!("Failed to restore health and mana: {}", e))?;
-
-    Ok(())
+pub fn end_combat_defeat(app: AppHandle, user_id: i64) -> Result<(), AppError> {
+    unit_of_work(&app, |conn| combat::resolve_defeat(conn, user_id))
 }
 
+/// Try to run from the fight; the roll is made here, against the recorded enemy
 #[tauri::command]
-pub fn end_combat_flee(app: AppHandle, user_id: i64) -> Result<(), String> {
-    let conn = get_connection(&app)?;
-
-    // Update dungeon progress - clear combat state
-    conn.execute(
-        "UPDATE user_dungeon_progress
-         SET in_combat = FALSE,
-             current_enemy_id = NULL,
-             current_enemy_health = NULL,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![user_id],
-    )
-    .map_err(|e| format!("Failed to update dungeon progress: {}", e))?;
-
-    // Note: Player keeps their current health and damage taken during flee
-    // This is handled in the frontend flee logic
-
-    Ok(())
+pub fn attempt_escape(app: AppHandle, user_id: i64, challenge_success: bool) -> Result<EscapeAttempt, AppError> {
+    unit_of_work(&app, |conn| {
+        combat::attempt_escape(conn, &mut rand::thread_rng(), user_id, challenge_success)
+    })
 }
@@ -5,6 +5,11 @@ use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::time::timeout;
 use reqwest::Client;
 
+use crate::ai_usage_commands;
+use crate::llm_provider::{self, ChunkSink, LlmBackend};
+use crate::mock_llm;
+use crate::error::{AppError, ResultExt};
+
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
@@ -18,6 +23,7 @@ pub struct ExecutionResult {
     pub stdout: String,
     pub stderr: String,
     pub exit_code: i32,
+    #[serde(default)]
     pub execution_time_ms: u64,
 }
 
@@ -44,7 +50,7 @@ enum ExecutionMode {
 }
 
 impl LanguageConfig {
-    fn get_config(language: &str) -> Result<Self, String> {
+    fn get_config(language: &str) -> Result<Self, AppError> {
         match language.to_lowercase().as_str() {
             "python" => Ok(LanguageConfig {
                 command: vec!["python".to_string(), "-c".to_string()],
@@ -82,7 +88,7 @@ impl LanguageConfig {
                 execution_mode: ExecutionMode::Inline,
                 extension: ".sh".to_string(),
             }),
-            _ => Err(format!("Unsupported language: {}", language)),
+            _ => Err(AppError::Validation(format!("Unsupported language: {}", language))),
         }
     }
 }
@@ -111,7 +117,7 @@ async fn execute_with_config(
     code: String,
     stdin_input: Option<String>,
     timeout_duration: Duration,
-) -> Result<ExecutionResult, String> {
+) -> Result<ExecutionResult, AppError> {
     let start = Instant::now();
 
     let output_future = match config.execution_mode {
@@ -158,7 +164,7 @@ async fn execute_with_config(
                 .join(format!("code_tutor_temp_{}{}", timestamp, config.extension));
 
             fs::write(&temp_file, &code)
-                .map_err(|e| format!("Failed to write temp file: {}", e))?;
+                .map_err(|e| AppError::Execution(format!("Failed to write temp file: {}", e)))?;
 
             let command_parts = config.command.clone();
             let temp_file_clone = temp_file.clone();
@@ -207,9 +213,9 @@ async fn execute_with_config(
 
     let output_result = timeout(timeout_duration, output_future)
         .await
-        .map_err(|_| format!("Execution timed out. Your code took too long to run (max {} seconds).", timeout_duration.as_secs()))?
-        .map_err(|e| format!("Failed to spawn execution task: {}", e))?
-        .map_err(|e| format!("Failed to execute code: {}. Is the runtime installed?", e))?;
+        .map_err(|_| AppError::Execution(format!("Execution timed out. Your code took too long to run (max {} seconds).", timeout_duration.as_secs())))?
+        .map_err(|e| AppError::Execution(format!("Failed to spawn execution task: {}", e)))?
+        .map_err(|e| AppError::Execution(format!("Failed to execute code: {}. Is the runtime installed?", e)))?;
 
     let execution_time_ms = start.elapsed().as_millis() as u64;
 
@@ -233,24 +239,24 @@ pub async fn execute_code(
     timeout_ms: Option<u64>,
     stdin: Option<String>,
     custom_executable_path: Option<String>,
-) -> Result<ExecutionResult, String> {
+) -> Result<ExecutionResult, AppError> {
     // Input validation
     const MAX_CODE_SIZE: usize = 100_000; // 100KB limit
     const MAX_LINES: usize = 2000; // Maximum lines of code
     const MAX_TIMEOUT_MS: u64 = 30_000; // 30 seconds max
 
     if code.is_empty() {
-        return Err("Code cannot be empty".to_string());
+        return Err(AppError::Validation("Code cannot be empty".to_string()));
     }
 
     if code.len() > MAX_CODE_SIZE {
-        return Err(format!("Code too large (max {} bytes)", MAX_CODE_SIZE));
+        return Err(AppError::Validation(format!("Code too large (max {} bytes)", MAX_CODE_SIZE)));
     }
 
     // Check line count
     let line_count = code.lines().count();
     if line_count > MAX_LINES {
-        return Err(format!("Code has too many lines (max {} lines)", MAX_LINES));
+        return Err(AppError::Validation(format!("Code has too many lines (max {} lines)", MAX_LINES)));
     }
 
     // Check for potentially dangerous patterns (basic heuristics)
@@ -312,7 +318,7 @@ pub async fn execute_code(
 
 /// Check if a language runtime is available
 #[tauri::command]
-pub async fn check_language_runtime(language: String) -> Result<bool, String> {
+pub async fn check_language_runtime(language: String) -> Result<bool, AppError> {
     let _config = LanguageConfig::get_config(&language)?;
 
     let check_command = match language.to_lowercase().as_str() {
@@ -322,7 +328,7 @@ pub async fn check_language_runtime(language: String) -> Result<bool, String> {
         "csharp" => vec!["dotnet", "--version"],
         "ruby" => vec!["ruby", "--version"],
         "bash" => vec!["bash", "--version"],
-        _ => return Err(format!("Unknown language: {}", language)),
+        _ => return Err(AppError::Validation(format!("Unknown language: {}", language))),
     };
 
     let output = tokio::task::spawn_blocking(move || {
@@ -341,20 +347,20 @@ pub async fn check_language_runtime(language: String) -> Result<bool, String> {
         cmd.output()
     })
     .await
-    .map_err(|e| format!("Failed to check runtime: {}", e))?
-    .map_err(|e| format!("Failed to execute check: {}", e))?;
+    .map_err(|e| AppError::Execution(format!("Failed to check runtime: {}", e)))?
+    .map_err(|e| AppError::Execution(format!("Failed to execute check: {}", e)))?;
 
     Ok(output.status.success())
 }
 
 /// Legacy Python execution command (kept for backward compatibility)
 #[tauri::command]
-pub async fn execute_python(code: String, timeout_ms: Option<u64>) -> Result<ExecutionResult, String> {
+pub async fn execute_python(code: String, timeout_ms: Option<u64>) -> Result<ExecutionResult, AppError> {
     execute_code("python".to_string(), code, timeout_ms, None, None).await
 }
 
 // Claude API types
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudeMessage {
     pub role: String,
     pub content: String,
@@ -375,88 +381,137 @@ pub struct ClaudeContent {
     pub content_type: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ClaudeUsage {
+    #[serde(default)]
+    pub input_tokens: i64,
+    #[serde(default)]
+    pub output_tokens: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ClaudeResponse {
     pub content: Vec<ClaudeContent>,
+    #[serde(default)]
+    pub usage: ClaudeUsage,
 }
 
-// Rate limiter for Claude API calls
-use std::sync::Mutex;
-use std::collections::HashMap;
-
-lazy_static::lazy_static! {
-    static ref CLAUDE_RATE_LIMITER: Mutex<HashMap<String, Vec<std::time::Instant>>> = Mutex::new(HashMap::new());
-}
-
-/// Proxy Claude API requests through Tauri backend to avoid CORS
-#[tauri::command]
-pub async fn call_claude_api(
-    api_key: String,
-    model: String,
-    system_prompt: String,
-    messages: Vec<ClaudeMessage>,
-) -> Result<String, String> {
-    // Rate limiting: 10 requests per minute
-    const RATE_LIMIT: usize = 10;
-    const RATE_WINDOW_SECS: u64 = 60;
-
-    let key_hash = format!("{:x}", md5::compute(&api_key));
-    let now = std::time::Instant::now();
-
-    {
-        let mut limiter = CLAUDE_RATE_LIMITER.lock()
-            .map_err(|e| format!("Rate limiter error: {}. Please restart the app.", e))?;
-        let requests = limiter.entry(key_hash.clone()).or_insert_with(Vec::new);
-
-        // Remove old requests outside the window
-        requests.retain(|&time| now.duration_since(time) < Duration::from_secs(RATE_WINDOW_SECS));
-
-        // Check if limit exceeded
-        if requests.len() >= RATE_LIMIT {
-            return Err("Rate limit exceeded. Please wait before making more requests.".to_string());
-        }
-
-        requests.push(now);
-    }
-
+/// Send a request to the Messages API and parse the response
+async fn send_claude_request(api_key: &str, request_body: &ClaudeRequest) -> Result<ClaudeResponse, AppError> {
     // Build client with timeout
     let client = Client::builder()
         .timeout(Duration::from_secs(30))
         .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
-
-    let request_body = ClaudeRequest {
-        model,
-        max_tokens: 2048,
-        system: system_prompt,
-        messages,
-    };
+        .context("Failed to build HTTP client")?;
 
     let response = client
         .post("https://api.anthropic.com/v1/messages")
         .header("x-api-key", api_key)
         .header("anthropic-version", "2023-06-01")
         .header("content-type", "application/json")
-        .json(&request_body)
+        .json(request_body)
         .send()
         .await
         .map_err(|e| {
             if e.is_timeout() {
-                "Request timed out. Please try again.".to_string()
+                AppError::Ai("Request timed out. Please try again.".to_string())
             } else {
-                format!("Failed to send request: {}", e)
+                AppError::Ai(format!("Failed to send request: {}", e))
             }
         })?;
 
     if !response.status().is_success() {
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Claude API error: {}", error_text));
+        return Err(AppError::Ai(format!("Claude API error: {}", error_text)));
     }
 
-    let claude_response: ClaudeResponse = response
+    response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")
+}
+
+/// Send a conversation to Claude using the stored API key.
+/// Every call is checked against the user's rate limit and daily budget, then recorded
+/// in the usage log along with its token counts and latency.
+pub async fn send_claude_message(
+    app: &tauri::AppHandle,
+    model: String,
+    system_prompt: String,
+    messages: Vec<ClaudeMessage>,
+    user_id: i32,
+    context: Option<String>,
+) -> Result<String, AppError> {
+    send_claude_message_with_chunks(app, model, system_prompt, messages, user_id, context, None).await
+}
+
+/// `send_claude_message`, passing the response text to `on_chunk` as it arrives.
+/// When the mock backend is selected, fixtures answer instead of the API and no key is needed.
+pub async fn send_claude_message_with_chunks(
+    app: &tauri::AppHandle,
+    model: String,
+    system_prompt: String,
+    messages: Vec<ClaudeMessage>,
+    user_id: i32,
+    context: Option<String>,
+    on_chunk: Option<&ChunkSink<'_>>,
+) -> Result<String, AppError> {
+    let backend = {
+        let conn = crate::db::get_connection(app)?;
+        llm_provider::get_llm_backend(&conn)?
+    };
+    let provider = backend.as_str();
+
+    let api_key = match backend {
+        LlmBackend::Claude => Some(crate::credentials::get_api_key(app, provider)?),
+        LlmBackend::Mock => None,
+    };
+
+    let reservation = {
+        let mut conn = crate::db::get_connection(app)?;
+        ai_usage_commands::reserve_ai_call(&mut conn, user_id, provider, &model, context.as_deref())?
+    };
+
+    let request_body = ClaudeRequest {
+        model,
+        max_tokens: 2048,
+        system: system_prompt,
+        messages,
+    };
+
+    let started = Instant::now();
+    let result = match &api_key {
+        Some(api_key) => send_claude_request(api_key, &request_body).await,
+        None => {
+            let mock = mock_llm::shared(crate::db::get_app_data_dir(app).ok())?;
+            mock.respond(&request_body, on_chunk).await
+        }
+    };
+    let latency_ms = started.elapsed().as_millis() as i64;
+
+    let (usage, error) = match &result {
+        Ok(response) => (&response.usage, None),
+        Err(e) => (&ClaudeUsage::default(), Some(e.message())),
+    };
+
+    let conn = crate::db::get_connection(app)?;
+    ai_usage_commands::complete_ai_call(
+        &conn,
+        reservation,
+        &ai_usage_commands::AiUsageRecord {
+            user_id: Some(user_id),
+            provider,
+            model: &request_body.model,
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            latency_ms,
+            context: context.as_deref(),
+            status: if error.is_some() { "error" } else { "success" },
+            error,
+        },
+    )?;
+
+    let claude_response = result?;
 
     // Extract the text from the first content block
     let text = claude_response
@@ -465,12 +520,17 @@ pub async fn call_claude_api(
         .map(|c| c.text.clone())
         .unwrap_or_else(|| "No response from Claude".to_string());
 
+    // The live API is not streamed yet, so it arrives as a single chunk
+    if let (Some(on_chunk), Some(_)) = (on_chunk, &api_key) {
+        on_chunk(&text);
+    }
+
     Ok(text)
 }
 
 /// Check if Ollama is available
 #[tauri::command]
-pub async fn check_ollama_available() -> Result<bool, String> {
+pub async fn check_ollama_available() -> Result<bool, AppError> {
     let client = Client::new();
 
     match client
@@ -486,7 +546,7 @@ pub async fn check_ollama_available() -> Result<bool, String> {
 
 /// Check if a specific executable path is valid for a language
 #[tauri::command]
-pub async fn check_runtime_path(language: String, executable_path: String) -> Result<bool, String> {
+pub async fn check_runtime_path(language: String, executable_path: String) -> Result<bool, AppError> {
     // Get the version check flag for this language
     let version_flag = match language.to_lowercase().as_str() {
         "python" => "--version",
@@ -495,7 +555,7 @@ pub async fn check_runtime_path(language: String, executable_path: String) -> Re
         "csharp" => "--version",
         "ruby" => "--version",
         "bash" => "--version",
-        _ => return Err(format!("Unknown language: {}", language)),
+        _ => return Err(AppError::Validation(format!("Unknown language: {}", language))),
     };
 
     let exec_path = executable_path.clone();
@@ -517,8 +577,8 @@ pub async fn check_runtime_path(language: String, executable_path: String) -> Re
         cmd.output()
     })
     .await
-    .map_err(|e| format!("Failed to check runtime path: {}", e))?
-    .map_err(|e| format!("Failed to execute check: {}", e))?;
+    .map_err(|e| AppError::Execution(format!("Failed to check runtime path: {}", e)))?
+    .map_err(|e| AppError::Execution(format!("Failed to execute check: {}", e)))?;
 
     Ok(output.status.success())
 }
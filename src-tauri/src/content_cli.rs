@@ -0,0 +1,407 @@
+use rusqlite::{Connection, OpenFlags};
+use std::fs;
+use std::path::Path;
+
+use crate::clean_export;
+use crate::content_integrity::{self, Severity};
+use crate::content_versions;
+use crate::db;
+use crate::dungeon_level_commands::{self, DungeonLevel};
+use crate::error::{AppError, ResultExt};
+use crate::mcq_commands::{self, McqQuestion};
+use crate::migrations;
+use crate::seed_builder;
+use crate::seed_sources::{SEED_DATABASE_PATH, SEED_MANIFEST_PATH};
+
+// ============================================================================
+// Content Admin CLI
+// ============================================================================
+//
+// Entry point for the content-admin binary (src/bin/content-admin.rs), which
+// runs content tooling against any code-tutor.db without starting the app.
+// Subcommands call the same functions as the Tauri commands.
+
+const USAGE: &str = "Usage: content-admin <command> [--db <path>] [options]
+
+Commands:
+  migrate                     Create or upgrade the database to the current schema and content
+  seed [--file <markdown>]    Migrate, then import dungeon challenges and an MCQ markdown bank as questions
+  export-clean --out <path>   Write a copy of the database without any user data
+  import-mcq --file <path>    Import MCQ questions from a JSON array or multiple-choice markdown
+  export-levels --out <path>  Write every dungeon level to a JSON file
+  import-levels --file <path> Insert or replace dungeon levels from a JSON file
+  validate [--json]           Check content references; exits 1 if there are errors
+  conflicts [--json]          List shipped content changes held back by local edits; exits 1 if there are any
+  build-seed                  Rebuild src-tauri/seed_database.db and its manifest from their sources
+  verify-seed                 Check seed_database.db is current; exits 1 if it is stale
+
+Every command except build-seed and verify-seed needs --db";
+
+/// Exit codes
+const EXIT_OK: i32 = 0;
+const EXIT_FINDINGS: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+
+struct Args {
+    command: String,
+    db: Option<String>,
+    file: Option<String>,
+    out: Option<String>,
+    json: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut iter = args.iter();
+    let command = iter.next().ok_or("Missing command")?.clone();
+    let mut parsed = Args { command, db: None, file: None, out: None, json: false };
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--db" => parsed.db = Some(iter.next().ok_or("--db needs a path")?.clone()),
+            "--file" => parsed.file = Some(iter.next().ok_or("--file needs a path")?.clone()),
+            "--out" => parsed.out = Some(iter.next().ok_or("--out needs a path")?.clone()),
+            "--json" => parsed.json = true,
+            other => return Err(format!("Unknown option {}", other)),
+        }
+    }
+    Ok(parsed)
+}
+
+fn require<'a>(value: &'a Option<String>, option: &str) -> Result<&'a str, AppError> {
+    value
+        .as_deref()
+        .ok_or_else(|| AppError::Validation(format!("{} is required for this command", option)))
+}
+
+fn open_read_only(path: &str) -> Result<Connection, AppError> {
+    if !Path::new(path).is_file() {
+        return Err(AppError::NotFound(format!("Database {} not found", path)));
+    }
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {}", path))
+}
+
+/// Open an existing database for writing; only migrate and seed may create one
+fn open_existing(path: &str) -> Result<Connection, AppError> {
+    if !Path::new(path).is_file() {
+        return Err(AppError::NotFound(format!("Database {} not found", path)));
+    }
+    db::open_database(Path::new(path))
+}
+
+fn read_file(path: &str) -> Result<String, AppError> {
+    fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))
+}
+
+fn write_file(path: &str, contents: &str) -> Result<(), AppError> {
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path))
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), AppError> {
+    let report = migrations::run_migrations(conn)?;
+    if report.baselined > 0 {
+        println!("Recorded {} legacy migrations as applied", report.baselined);
+    }
+    for version in &report.applied {
+        println!("Applied migration {}", version);
+    }
+    for name in &report.seeded {
+        println!("Applied seed {}", name);
+    }
+    println!(
+        "Schema at {}",
+        migrations::current_version(conn)?.unwrap_or_default()
+    );
+
+    let content = content_versions::sync_content(conn)?;
+    if let Some(version) = content.content_version {
+        println!(
+            "Content version {}: {} rows updated, {} added, {} conflicts",
+            version,
+            content.updated.len(),
+            content.added.len(),
+            content.conflicts.len()
+        );
+    }
+    Ok(())
+}
+
+/// Questions from a .md bank in the docs/multiple-choice.md format, otherwise a JSON array
+fn load_questions(path: &str) -> Result<Vec<McqQuestion>, AppError> {
+    let content = read_file(path)?;
+    if path.ends_with(".md") {
+        mcq_commands::parse_markdown_questions(&content)
+    } else {
+        serde_json::from_str(&content).with_context(|| format!("{} is not a JSON array of questions", path))
+    }
+}
+
+fn seed(db: &str, markdown: Option<&str>) -> Result<(), AppError> {
+    let mut conn = db::open_database(Path::new(db))?;
+    migrate(&mut conn)?;
+
+    let imported = mcq_commands::import_dungeon_challenges(&conn)?;
+    println!("Imported {} dungeon challenges as MCQ questions", imported);
+
+    if let Some(path) = markdown {
+        let imported = mcq_commands::import_questions(&conn, load_questions(path)?)?;
+        println!("Imported {} MCQ questions from {}", imported, path);
+    }
+    Ok(())
+}
+
+fn export_clean(db: &str, out: &str) -> Result<(), AppError> {
+    if Path::new(out).exists() {
+        return Err(AppError::Validation(format!("{} already exists", out)));
+    }
+    let export = clean_export::export_clean_database(&open_read_only(db)?, Path::new(out))?;
+
+    for table in &export.cleared_tables {
+        println!("Cleared {}", table);
+    }
+    println!(
+        "Exported {} MCQ questions, {} custom enemies, {} equipment items, {} narrative locations to {}",
+        export.question_count, export.enemy_count, export.equipment_count, export.location_count, out
+    );
+    Ok(())
+}
+
+fn import_mcq(db: &str, file: &str) -> Result<(), AppError> {
+    let questions = load_questions(file)?;
+    let total = questions.len();
+    let imported = mcq_commands::import_questions(&open_existing(db)?, questions)?;
+    println!("Imported {} of {} questions ({} already existed)", imported, total, total - imported);
+    Ok(())
+}
+
+fn export_levels(db: &str, out: &str) -> Result<(), AppError> {
+    let conn = open_read_only(db)?;
+    let mut ids: Vec<String> = dungeon_level_commands::list_levels(&conn)?
+        .into_iter()
+        .map(|level| level.id)
+        .collect();
+    // Stable order so exports diff cleanly in version control
+    ids.sort();
+
+    let levels = ids
+        .iter()
+        .map(|id| dungeon_level_commands::load_level(&conn, id))
+        .collect::<Result<Vec<_>, _>>()?;
+    write_file(out, &serde_json::to_string_pretty(&levels).context("Failed to serialize levels")?)?;
+    println!("Exported {} levels to {}", levels.len(), out);
+    Ok(())
+}
+
+fn import_levels(db: &str, file: &str) -> Result<(), AppError> {
+    let levels: Vec<DungeonLevel> = serde_json::from_str(&read_file(file)?)
+        .with_context(|| format!("{} is not a JSON array of levels", file))?;
+    let conn = open_existing(db)?;
+    for level in &levels {
+        dungeon_level_commands::save_level(&conn, level)
+            .with_context(|| format!("Failed to import level {}", level.metadata.id))?;
+    }
+    println!("Imported {} levels", levels.len());
+    Ok(())
+}
+
+fn validate(db: &str, json: bool) -> Result<i32, AppError> {
+    let conn = open_read_only(db)?;
+    let report = content_integrity::check_content(&conn)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).context("Failed to serialize report")?);
+    } else {
+        for issue in &report.issues {
+            let severity = match issue.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            let kind = serde_json::to_value(issue.kind).ok().and_then(|k| k.as_str().map(String::from)).unwrap_or_default();
+            println!("{:<7} {:<18} {}[{}].{}: {}", severity, kind, issue.table, issue.id, issue.field, issue.message);
+        }
+        println!("{} errors, {} warnings", report.errors, report.warnings);
+    }
+
+    Ok(if report.is_clean() { EXIT_OK } else { EXIT_FINDINGS })
+}
+
+fn conflicts(db: &str, json: bool) -> Result<i32, AppError> {
+    let conn = open_read_only(db)?;
+    let conflicts = content_versions::list_conflicts(&conn)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&conflicts).context("Failed to serialize conflicts")?);
+    } else {
+        for conflict in &conflicts {
+            println!("{:<8} {}[{}] (content version {})", conflict.reason, conflict.table_name, conflict.row_id, conflict.content_version);
+        }
+        println!("{} conflicts", conflicts.len());
+    }
+
+    Ok(if conflicts.is_empty() { EXIT_OK } else { EXIT_FINDINGS })
+}
+
+/// The src-tauri directory this binary was built from, where the seed sources live
+fn sources_root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn build_seed() -> Result<i32, AppError> {
+    let root = sources_root();
+    let manifest = seed_builder::build_seed_database(root, &root.join(SEED_DATABASE_PATH), &root.join(SEED_MANIFEST_PATH))?;
+    for (table, count) in &manifest.row_counts {
+        println!("{:<24} {}", table, count);
+    }
+    println!("Built {} ({})", SEED_DATABASE_PATH, manifest.database_sha256);
+    Ok(EXIT_OK)
+}
+
+fn verify_seed() -> Result<i32, AppError> {
+    let problems = seed_builder::verify_seed_database(sources_root())?;
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    if problems.is_empty() {
+        println!("{} is up to date", SEED_DATABASE_PATH);
+        Ok(EXIT_OK)
+    } else {
+        println!("Run `content-admin build-seed` to rebuild it");
+        Ok(EXIT_FINDINGS)
+    }
+}
+
+fn dispatch(args: &Args, db: &str) -> Result<i32, AppError> {
+    match args.command.as_str() {
+        "migrate" => migrate(&mut db::open_database(Path::new(db))?),
+        "seed" => seed(db, args.file.as_deref()),
+        "export-clean" => export_clean(db, require(&args.out, "--out")?),
+        "import-mcq" => import_mcq(db, require(&args.file, "--file")?),
+        "export-levels" => export_levels(db, require(&args.out, "--out")?),
+        "import-levels" => import_levels(db, require(&args.file, "--file")?),
+        "validate" => return validate(db, args.json),
+        "conflicts" => return conflicts(db, args.json),
+        other => return Err(AppError::Validation(format!("Unknown command {}", other))),
+    }
+    .map(|()| EXIT_OK)
+}
+
+/// Run with the arguments after the program name, returning the process exit code
+pub fn run(args: Vec<String>) -> i32 {
+    let args = match parse_args(&args) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+    let result = match args.command.as_str() {
+        "build-seed" => build_seed(),
+        "verify-seed" => verify_seed(),
+        _ => match args.db.as_deref() {
+            Some(db) => dispatch(&args, db),
+            None => {
+                eprintln!("--db is required\n\n{}", USAGE);
+                return EXIT_USAGE;
+            }
+        },
+    };
+
+    result.unwrap_or_else(|e| {
+        match e {
+            AppError::Validation(_) => eprintln!("{}\n\n{}", e, USAGE),
+            _ => eprintln!("error: {}", e),
+        }
+        EXIT_USAGE
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scalar;
+
+    /// A scratch folder for database and export files, removed on drop
+    struct Scratch {
+        root: std::path::PathBuf,
+    }
+
+    impl Scratch {
+        fn new() -> Self {
+            let root = std::env::temp_dir().join(format!("code-tutor-cli-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(&root).unwrap();
+            Scratch { root }
+        }
+
+        fn path(&self, name: &str) -> String {
+            self.root.join(name).to_string_lossy().into_owned()
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn cli(args: &[&str]) -> i32 {
+        run(args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    const LEVEL: &str = r#"[{
+        "metadata": {
+            "id": "cli-level", "name": "CLI Level", "description": "", "recommendedLevel": 1,
+            "difficulty": "easy", "estimatedDuration": 5, "isPublished": true, "version": 1,
+            "tags": ["test"], "createdAt": "2026-01-01T00:00:00Z", "updatedAt": "2026-01-01T00:00:00Z"
+        },
+        "nodes": [{"id": "n1", "type": "start", "data": {"label": "Start"}, "position": {"x": 0.0, "y": 0.0}}],
+        "edges": []
+    }]"#;
+
+    #[test]
+    fn levels_round_trip_between_databases() {
+        let scratch = Scratch::new();
+        let (source, target) = (scratch.path("source.db"), scratch.path("target.db"));
+        let (levels, exported) = (scratch.path("levels.json"), scratch.path("exported.json"));
+        fs::write(&levels, LEVEL).unwrap();
+
+        assert_eq!(cli(&["migrate", "--db", &source]), EXIT_OK);
+        assert_eq!(cli(&["migrate", "--db", &target]), EXIT_OK);
+        assert_eq!(cli(&["import-levels", "--db", &source, "--file", &levels]), EXIT_OK);
+        assert_eq!(cli(&["export-levels", "--db", &source, "--out", &exported]), EXIT_OK);
+        assert_eq!(cli(&["import-levels", "--db", &target, "--file", &exported]), EXIT_OK);
+
+        let conn = Connection::open(&target).unwrap();
+        let level = dungeon_level_commands::load_level(&conn, "cli-level").unwrap();
+        assert_eq!(level.metadata.name, "CLI Level");
+        assert_eq!(level.nodes.len(), 1);
+    }
+
+    #[test]
+    fn import_mcq_skips_existing_questions() {
+        let scratch = Scratch::new();
+        let db = scratch.path("code-tutor.db");
+        let questions = scratch.path("questions.json");
+        fs::write(
+            &questions,
+            r#"[{"id": "cli-q1", "questionText": "2 + 2?", "explanation": null, "options": "[\"3\",\"4\"]",
+                 "correctAnswerIndex": 1, "difficulty": "easy", "topic": null, "language": "python",
+                 "tags": null, "createdAt": "", "updatedAt": ""}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(cli(&["migrate", "--db", &db]), EXIT_OK);
+        assert_eq!(cli(&["import-mcq", "--db", &db, "--file", &questions]), EXIT_OK);
+        assert_eq!(cli(&["import-mcq", "--db", &db, "--file", &questions]), EXIT_OK);
+
+        let conn = Connection::open(&db).unwrap();
+        assert_eq!(scalar::<i64>(&conn, "SELECT COUNT(*) FROM mcq_questions WHERE id = 'cli-q1'", []), 1);
+    }
+
+    #[test]
+    fn usage_errors_exit_with_two() {
+        let scratch = Scratch::new();
+        assert_eq!(cli(&["validate"]), EXIT_USAGE);
+        assert_eq!(cli(&["frobnicate", "--db", &scratch.path("x.db")]), EXIT_USAGE);
+        assert_eq!(cli(&["import-levels", "--db", &scratch.path("missing.db"), "--file", "levels.json"]), EXIT_USAGE);
+    }
+}
@@ -0,0 +1,639 @@
+use rusqlite::{Connection, Params};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::error::{AppError, ResultExt};
+use crate::game::rewards::LootEntry;
+
+// ============================================================================
+// Content Integrity
+// ============================================================================
+//
+// Game content links to other content by plain string ids, often inside JSON
+// columns that no foreign key covers: loot tables, outcome rewards, story
+// flags, achievement tracking keys. check_content walks every one of those
+// links and reports what would only surface at runtime, usually as an item
+// that silently fails to drop or a choice that errors when picked.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Breaks or silently skips something at runtime
+    Error,
+    /// Content nobody can reach; harmless but probably a mistake
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// An id that doesn't exist in the table it should point at
+    DanglingReference,
+    /// A value of the wrong shape, e.g. malformed JSON or an unknown item type
+    TypeMismatch,
+    /// Something the game code expects to find but can't, e.g. a skill check without a failure outcome
+    MissingContent,
+    /// Content no player can ever reach
+    Unreachable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    pub table: String,
+    /// Id of the row with the problem
+    pub id: String,
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.errors == 0
+    }
+}
+
+#[derive(Default)]
+struct Checker {
+    issues: Vec<IntegrityIssue>,
+}
+
+impl Checker {
+    fn report(&mut self, severity: Severity, kind: IssueKind, table: &str, id: &str, field: &str, message: String) {
+        self.issues.push(IntegrityIssue {
+            severity,
+            kind,
+            table: table.to_string(),
+            id: id.to_string(),
+            field: field.to_string(),
+            message,
+        });
+    }
+
+    fn dangling(&mut self, table: &str, id: &str, field: &str, message: String) {
+        self.report(Severity::Error, IssueKind::DanglingReference, table, id, field, message);
+    }
+
+    fn mismatch(&mut self, table: &str, id: &str, field: &str, message: String) {
+        self.report(Severity::Error, IssueKind::TypeMismatch, table, id, field, message);
+    }
+
+    fn missing(&mut self, table: &str, id: &str, field: &str, message: String) {
+        self.report(Severity::Error, IssueKind::MissingContent, table, id, field, message);
+    }
+
+    fn unreachable(&mut self, table: &str, id: &str, field: &str, message: String) {
+        self.report(Severity::Warning, IssueKind::Unreachable, table, id, field, message);
+    }
+}
+
+/// Content ids the checks resolve against
+struct Catalog {
+    consumables: HashSet<String>,
+    equipment: HashSet<String>,
+    enemies: HashSet<String>,
+    locations: HashSet<String>,
+    shop_items: HashSet<String>,
+    cosmetics: HashSet<String>,
+    achievements: HashSet<String>,
+    /// Every item id that has a source: shop, loot, rewards or unlocks
+    obtainable: HashSet<String>,
+}
+
+impl Catalog {
+    /// Ids rewards::grant_item accepts
+    fn is_item(&self, id: &str) -> bool {
+        self.consumables.contains(id) || self.equipment.contains(id)
+    }
+}
+
+fn query_rows<T>(
+    conn: &Connection,
+    sql: &str,
+    params: impl Params,
+    map: impl FnMut(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> Result<Vec<T>, AppError> {
+    let mut stmt = conn
+        .prepare(sql)
+        .with_context(|| format!("Failed to prepare integrity query: {}", sql))?;
+    let rows = stmt
+        .query_map(params, map)
+        .context("Failed to run integrity query")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read integrity query")?;
+    Ok(rows)
+}
+
+fn ids(conn: &Connection, sql: &str) -> Result<HashSet<String>, AppError> {
+    Ok(query_rows(conn, sql, [], |row| row.get(0))?.into_iter().collect())
+}
+
+/// Parse a JSON column, reporting a type mismatch if it isn't valid JSON
+fn parse_json(c: &mut Checker, table: &str, id: &str, field: &str, json: &str) -> Option<serde_json::Value> {
+    match serde_json::from_str(json) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            c.mismatch(table, id, field, format!("Not valid JSON: {}", e));
+            None
+        }
+    }
+}
+
+fn check_loot(conn: &Connection, catalog: &mut Catalog, c: &mut Checker) -> Result<(), AppError> {
+    let enemies: Vec<(String, Option<String>)> =
+        query_rows(conn, "SELECT id, loot_table FROM enemy_types ORDER BY id", [], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+    for (enemy_id, loot_table) in enemies {
+        let Some(loot_table) = loot_table.filter(|t| !t.trim().is_empty()) else {
+            continue;
+        };
+        let entries: Vec<LootEntry> = match serde_json::from_str(&loot_table) {
+            Ok(entries) => entries,
+            Err(e) => {
+                c.mismatch("enemy_types", &enemy_id, "loot_table", format!("Expected [{{\"item\", \"chance\"}}]: {}", e));
+                continue;
+            }
+        };
+        for entry in entries {
+            if !(0.0..=1.0).contains(&entry.chance) {
+                c.mismatch("enemy_types", &enemy_id, "loot_table", format!("Drop chance {} for {} is outside 0..1", entry.chance, entry.item));
+            }
+            if !catalog.is_item(&entry.item) {
+                c.dangling("enemy_types", &enemy_id, "loot_table", format!("Loot item {} is not an equipment or consumable item", entry.item));
+            }
+            catalog.obtainable.insert(entry.item);
+        }
+    }
+
+    let bosses: Vec<(String, Option<String>)> =
+        query_rows(conn, "SELECT id, guaranteed_loot FROM boss_enemies ORDER BY id", [], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+    for (boss_id, loot) in bosses {
+        let Some(loot) = loot.filter(|t| !t.trim().is_empty()) else {
+            continue;
+        };
+        match serde_json::from_str::<Vec<String>>(&loot) {
+            Ok(items) => {
+                for item in items {
+                    if !catalog.is_item(&item) {
+                        c.dangling("boss_enemies", &boss_id, "guaranteed_loot", format!("Loot item {} is not an equipment or consumable item", item));
+                    }
+                    catalog.obtainable.insert(item);
+                }
+            }
+            Err(e) => c.mismatch("boss_enemies", &boss_id, "guaranteed_loot", format!("Expected an array of item ids: {}", e)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Outcome rewards JSON, as read by narrative::apply_rewards
+fn check_rewards(catalog: &mut Catalog, c: &mut Checker, outcome_id: &str, rewards: &serde_json::Value) {
+    let Some(rewards) = rewards.as_object() else {
+        c.mismatch("narrative_outcomes", outcome_id, "rewards", "Expected a JSON object".to_string());
+        return;
+    };
+    for (key, value) in rewards {
+        match key.as_str() {
+            "gold" | "xp" if !value.is_i64() => {
+                c.mismatch("narrative_outcomes", outcome_id, "rewards", format!("{} should be a whole number, found {}", key, value));
+            }
+            "gold" | "xp" => {}
+            "heal" if value != "full" && !value.is_i64() => {
+                c.mismatch("narrative_outcomes", outcome_id, "rewards", format!("heal should be \"full\" or a number, found {}", value));
+            }
+            "heal" => {}
+            "items" => match value.as_array() {
+                Some(items) => {
+                    for item in items {
+                        match item.as_str() {
+                            Some(item) => {
+                                if !catalog.is_item(item) {
+                                    c.dangling("narrative_outcomes", outcome_id, "rewards", format!("Reward item {} is not an equipment or consumable item", item));
+                                }
+                                catalog.obtainable.insert(item.to_string());
+                            }
+                            None => c.mismatch("narrative_outcomes", outcome_id, "rewards", format!("Reward item {} is not an id", item)),
+                        }
+                    }
+                }
+                None => c.mismatch("narrative_outcomes", outcome_id, "rewards", "items should be an array of item ids".to_string()),
+            },
+            _ => c.mismatch("narrative_outcomes", outcome_id, "rewards", format!("Unknown reward {}", key)),
+        }
+    }
+}
+
+/// Outcome penalties JSON, as read by narrative::apply_penalties
+fn check_penalties(c: &mut Checker, outcome_id: &str, penalties: &serde_json::Value) {
+    let Some(penalties) = penalties.as_object() else {
+        c.mismatch("narrative_outcomes", outcome_id, "penalties", "Expected a JSON object".to_string());
+        return;
+    };
+    for (key, value) in penalties {
+        match key.as_str() {
+            "damage" | "gold" if !value.is_i64() => {
+                c.mismatch("narrative_outcomes", outcome_id, "penalties", format!("{} should be a whole number, found {}", key, value));
+            }
+            "damage" | "gold" => {}
+            _ => c.mismatch("narrative_outcomes", outcome_id, "penalties", format!("Unknown penalty {}", key)),
+        }
+    }
+}
+
+struct Choice {
+    id: String,
+    location_id: String,
+    requires_skill_check: bool,
+    requires_flag: Option<String>,
+}
+
+struct Outcome {
+    id: String,
+    choice_id: String,
+    outcome_type: String,
+    next_location_id: Option<String>,
+    rewards: Option<String>,
+    penalties: Option<String>,
+    sets_flags: Option<String>,
+    triggers_combat: bool,
+    enemy_id: Option<String>,
+}
+
+fn check_narrative(conn: &Connection, catalog: &mut Catalog, c: &mut Checker) -> Result<(), AppError> {
+    let starts = ids(conn, "SELECT id FROM narrative_locations WHERE location_type = 'start'")?;
+    let choices = query_rows(
+        conn,
+        "SELECT id, location_id, COALESCE(requires_skill_check, FALSE), requires_flag FROM narrative_choices ORDER BY id",
+        [],
+        |row| {
+            Ok(Choice {
+                id: row.get(0)?,
+                location_id: row.get(1)?,
+                requires_skill_check: row.get(2)?,
+                requires_flag: row.get(3)?,
+            })
+        },
+    )?;
+    let outcomes = query_rows(
+        conn,
+        "SELECT id, choice_id, outcome_type, next_location_id, rewards, penalties, sets_flags,
+                COALESCE(triggers_combat, FALSE), enemy_id
+         FROM narrative_outcomes ORDER BY id",
+        [],
+        |row| {
+            Ok(Outcome {
+                id: row.get(0)?,
+                choice_id: row.get(1)?,
+                outcome_type: row.get(2)?,
+                next_location_id: row.get(3)?,
+                rewards: row.get(4)?,
+                penalties: row.get(5)?,
+                sets_flags: row.get(6)?,
+                triggers_combat: row.get(7)?,
+                enemy_id: row.get(8)?,
+            })
+        },
+    )?;
+
+    let choice_ids: HashSet<&str> = choices.iter().map(|ch| ch.id.as_str()).collect();
+    let mut outcome_types: HashMap<&str, HashSet<&str>> = HashMap::new();
+    let mut flags_set: HashSet<String> = HashSet::new();
+
+    for outcome in &outcomes {
+        if !choice_ids.contains(outcome.choice_id.as_str()) {
+            c.dangling("narrative_outcomes", &outcome.id, "choice_id", format!("Choice {} doesn't exist", outcome.choice_id));
+        }
+        outcome_types.entry(outcome.choice_id.as_str()).or_default().insert(outcome.outcome_type.as_str());
+
+        if let Some(next) = &outcome.next_location_id {
+            if !catalog.locations.contains(next) {
+                c.dangling("narrative_outcomes", &outcome.id, "next_location_id", format!("Location {} doesn't exist", next));
+            }
+        }
+
+        match &outcome.enemy_id {
+            // The frontend loads combat enemies with get_enemy_by_id, which only reads enemy_types
+            Some(enemy) if !catalog.enemies.contains(enemy) => {
+                c.dangling("narrative_outcomes", &outcome.id, "enemy_id", format!("Enemy {} is not in enemy_types", enemy));
+            }
+            None if outcome.triggers_combat => {
+                c.missing("narrative_outcomes", &outcome.id, "enemy_id", "Triggers combat but has no enemy".to_string());
+            }
+            _ => {}
+        }
+
+        if let Some(rewards) = outcome.rewards.as_deref().and_then(|json| parse_json(c, "narrative_outcomes", &outcome.id, "rewards", json)) {
+            check_rewards(catalog, c, &outcome.id, &rewards);
+        }
+        if let Some(penalties) = outcome.penalties.as_deref().and_then(|json| parse_json(c, "narrative_outcomes", &outcome.id, "penalties", json)) {
+            check_penalties(c, &outcome.id, &penalties);
+        }
+        if let Some(flags) = outcome.sets_flags.as_deref().and_then(|json| parse_json(c, "narrative_outcomes", &outcome.id, "sets_flags", json)) {
+            match flags.as_object() {
+                Some(flags) => flags_set.extend(flags.keys().cloned()),
+                None => c.mismatch("narrative_outcomes", &outcome.id, "sets_flags", "Expected a JSON object of flags".to_string()),
+            }
+        }
+    }
+
+    for choice in &choices {
+        if !catalog.locations.contains(&choice.location_id) {
+            c.dangling("narrative_choices", &choice.id, "location_id", format!("Location {} doesn't exist", choice.location_id));
+        }
+
+        // Skill checks fall back to success/failure; simple choices use 'default'
+        let types = outcome_types.get(choice.id.as_str());
+        let required: &[&str] = if choice.requires_skill_check { &["success", "failure"] } else { &["default"] };
+        for outcome_type in required {
+            if !types.is_some_and(|t| t.contains(outcome_type)) {
+                c.missing("narrative_choices", &choice.id, "outcomes", format!("No '{}' outcome", outcome_type));
+            }
+        }
+
+        if let Some(flags) = choice.requires_flag.as_deref().and_then(|json| parse_json(c, "narrative_choices", &choice.id, "requires_flag", json)) {
+            match flags.as_object() {
+                Some(flags) => {
+                    for flag in flags.keys().filter(|flag| !flags_set.contains(*flag)) {
+                        c.unreachable("narrative_choices", &choice.id, "requires_flag", format!("Flag {} is never set by any outcome", flag));
+                    }
+                }
+                None => c.mismatch("narrative_choices", &choice.id, "requires_flag", "Expected a JSON object of flags".to_string()),
+            }
+        }
+    }
+
+    // Walk from every start location through choices and their outcomes
+    let mut exits: HashMap<&str, Vec<&str>> = HashMap::new();
+    let choice_location: HashMap<&str, &str> = choices.iter().map(|ch| (ch.id.as_str(), ch.location_id.as_str())).collect();
+    for outcome in &outcomes {
+        if let (Some(from), Some(to)) = (choice_location.get(outcome.choice_id.as_str()), &outcome.next_location_id) {
+            exits.entry(from).or_default().push(to.as_str());
+        }
+    }
+
+    let mut reached: HashSet<&str> = starts.iter().map(String::as_str).collect();
+    let mut queue: VecDeque<&str> = reached.iter().copied().collect();
+    while let Some(location) = queue.pop_front() {
+        for next in exits.get(location).into_iter().flatten() {
+            if reached.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let mut locations: Vec<&String> = catalog.locations.iter().collect();
+    locations.sort();
+    for location in locations {
+        if !reached.contains(location.as_str()) {
+            c.unreachable("narrative_locations", location, "id", "No path leads here from a start location".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+struct Achievement {
+    id: String,
+    tracking_key: Option<String>,
+    required: Option<String>,
+    unlock_id: Option<String>,
+    unlock_type: Option<String>,
+}
+
+fn check_achievements(conn: &Connection, catalog: &mut Catalog, c: &mut Checker) -> Result<(), AppError> {
+    // tracking_key names the user_achievement_stats column that record_progress increments
+    let stat_columns = ids(conn, "SELECT name FROM pragma_table_info('user_achievement_stats')")?;
+
+    let achievements = query_rows(
+        conn,
+        "SELECT id, tracking_key, required_achievement_id, unlock_item_id, unlock_item_type FROM achievements ORDER BY id",
+        [],
+        |row| {
+            Ok(Achievement {
+                id: row.get(0)?,
+                tracking_key: row.get(1)?,
+                required: row.get(2)?,
+                unlock_id: row.get(3)?,
+                unlock_type: row.get(4)?,
+            })
+        },
+    )?;
+
+    for Achievement { id, tracking_key, required, unlock_id, unlock_type } in achievements {
+        if let Some(key) = tracking_key {
+            if key == "user_id" || !stat_columns.contains(&key) {
+                c.dangling("achievements", &id, "tracking_key", format!("{} is not a user_achievement_stats column", key));
+            }
+        }
+
+        if let Some(required) = required {
+            if !catalog.achievements.contains(&required) {
+                c.dangling("achievements", &id, "required_achievement_id", format!("Achievement {} doesn't exist", required));
+            }
+        }
+
+        let Some(unlock_id) = unlock_id else {
+            continue;
+        };
+        let known = match unlock_type.as_deref() {
+            Some("equipment") => catalog.equipment.contains(&unlock_id),
+            Some("consumable") => catalog.consumables.contains(&unlock_id),
+            Some("cosmetic") => catalog.cosmetics.contains(&unlock_id),
+            other => {
+                c.mismatch("achievements", &id, "unlock_item_type", format!("Unknown unlock type {:?}", other));
+                continue;
+            }
+        };
+        if !known {
+            c.dangling(
+                "achievements",
+                &id,
+                "unlock_item_id",
+                format!("{} {} doesn't exist", unlock_type.unwrap_or_default(), unlock_id),
+            );
+        }
+        catalog.obtainable.insert(unlock_id);
+    }
+
+    Ok(())
+}
+
+fn check_quests(conn: &Connection, catalog: &Catalog, c: &mut Checker) -> Result<(), AppError> {
+    let quests: Vec<(String, String)> = query_rows(
+        conn,
+        "SELECT id, reward_item_id FROM quests WHERE reward_item_id IS NOT NULL ORDER BY id",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    for (id, item) in quests {
+        if !catalog.shop_items.contains(&item) {
+            c.dangling("quests", &id, "reward_item_id", format!("Shop item {} doesn't exist", item));
+        }
+    }
+    Ok(())
+}
+
+fn check_shop(conn: &Connection, catalog: &mut Catalog, c: &mut Checker) -> Result<(), AppError> {
+    let stock: Vec<(i64, String, String)> = query_rows(
+        conn,
+        "SELECT id, item_type, item_id FROM shop_inventory ORDER BY id",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    for (id, item_type, item_id) in stock {
+        let id = id.to_string();
+        let known = match item_type.as_str() {
+            "equipment" => catalog.equipment.contains(&item_id),
+            "consumable" => catalog.consumables.contains(&item_id),
+            other => {
+                c.mismatch("shop_inventory", &id, "item_type", format!("Unknown item type {}", other));
+                continue;
+            }
+        };
+        if !known {
+            // A consumable listed as equipment (or the reverse) can't be bought
+            let other_table = if catalog.is_item(&item_id) { " (it exists as the other item type)" } else { "" };
+            c.dangling("shop_inventory", &id, "item_id", format!("{} {} doesn't exist{}", item_type, item_id, other_table));
+        }
+        catalog.obtainable.insert(item_id);
+    }
+    Ok(())
+}
+
+/// Check every cross-content reference and return what is wrong, errors first
+pub fn check_content(conn: &Connection) -> Result<IntegrityReport, AppError> {
+    let mut catalog = Catalog {
+        consumables: ids(conn, "SELECT id FROM consumable_items")?,
+        equipment: ids(conn, "SELECT id FROM equipment_items")?,
+        enemies: ids(conn, "SELECT id FROM enemy_types")?,
+        locations: ids(conn, "SELECT id FROM narrative_locations")?,
+        shop_items: ids(conn, "SELECT id FROM shop_items")?,
+        cosmetics: ids(conn, "SELECT id FROM shop_items WHERE category = 'cosmetic'")?,
+        achievements: ids(conn, "SELECT id FROM achievements")?,
+        obtainable: HashSet::new(),
+    };
+    let mut c = Checker::default();
+
+    check_loot(conn, &mut catalog, &mut c)?;
+    check_narrative(conn, &mut catalog, &mut c)?;
+    check_achievements(conn, &mut catalog, &mut c)?;
+    check_quests(conn, &catalog, &mut c)?;
+    check_shop(conn, &mut catalog, &mut c)?;
+
+    for (table, items) in [("equipment_items", &catalog.equipment), ("consumable_items", &catalog.consumables)] {
+        let mut unobtainable: Vec<&String> = items.difference(&catalog.obtainable).collect();
+        unobtainable.sort();
+        for item in unobtainable {
+            c.unreachable(table, item, "id", "Not sold, dropped or rewarded anywhere".to_string());
+        }
+    }
+
+    let mut issues = c.issues;
+    issues.sort_by_key(|issue| issue.severity != Severity::Error);
+    let errors = issues.iter().filter(|issue| issue.severity == Severity::Error).count();
+    Ok(IntegrityReport {
+        errors,
+        warnings: issues.len() - errors,
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::seeded_db;
+
+    fn issues_for<'a>(report: &'a IntegrityReport, table: &str, id: &str) -> Vec<&'a IntegrityIssue> {
+        report.issues.iter().filter(|i| i.table == table && i.id == id).collect()
+    }
+
+    #[test]
+    fn bad_loot_is_reported() {
+        let conn = seeded_db();
+        conn.execute(
+            "UPDATE enemy_types SET loot_table = '[{\"item\": \"no_such_item\", \"chance\": 1.5}]' WHERE id = 'rat'",
+            [],
+        )
+        .unwrap();
+        conn.execute("UPDATE enemy_types SET loot_table = '{\"item\": \"wooden_sword\"}' WHERE id = 'slime'", []).unwrap();
+
+        let report = check_content(&conn).unwrap();
+        let rat: Vec<IssueKind> = issues_for(&report, "enemy_types", "rat").iter().map(|i| i.kind).collect();
+        assert_eq!(rat, vec![IssueKind::TypeMismatch, IssueKind::DanglingReference]);
+        let slime = issues_for(&report, "enemy_types", "slime");
+        assert_eq!(slime.len(), 1);
+        assert_eq!(slime[0].kind, IssueKind::TypeMismatch);
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn narrative_links_are_checked() {
+        let conn = seeded_db();
+        // Content written by older tools or hand-edited databases skips foreign keys
+        conn.execute("PRAGMA foreign_keys = OFF", []).unwrap();
+        conn.execute("UPDATE narrative_outcomes SET next_location_id = 'nowhere', enemy_id = 'dragon_king' WHERE id = (SELECT MIN(id) FROM narrative_outcomes)", []).unwrap();
+        conn.execute("DELETE FROM narrative_outcomes WHERE choice_id = 'trap_disarm' AND outcome_type = 'failure'", []).unwrap();
+        conn.execute("UPDATE narrative_outcomes SET rewards = '{\"gold\": \"lots\"}' WHERE choice_id = 'courtyard_fight' AND outcome_type = 'success'", []).unwrap();
+
+        let report = check_content(&conn).unwrap();
+        let first: String = conn.query_row("SELECT MIN(id) FROM narrative_outcomes", [], |row| row.get(0)).unwrap();
+        let fields: Vec<&str> = issues_for(&report, "narrative_outcomes", &first).iter().map(|i| i.field.as_str()).collect();
+        assert!(fields.contains(&"next_location_id") && fields.contains(&"enemy_id"), "{:?}", fields);
+
+        let trap = issues_for(&report, "narrative_choices", "trap_disarm");
+        assert!(trap.iter().any(|i| i.kind == IssueKind::MissingContent && i.message.contains("failure")));
+
+        assert!(report.issues.iter().any(|i| i.field == "rewards" && i.message.contains("gold should be a whole number")));
+    }
+
+    #[test]
+    fn unreachable_location_is_a_warning() {
+        let conn = seeded_db();
+        conn.execute(
+            "INSERT INTO narrative_locations (id, floor_number, name, description, location_type) VALUES ('secret_room', 1, 'Secret', '', 'treasure')",
+            [],
+        )
+        .unwrap();
+
+        let report = check_content(&conn).unwrap();
+        let secret = issues_for(&report, "narrative_locations", "secret_room");
+        assert_eq!(secret.len(), 1);
+        assert_eq!((secret[0].severity, secret[0].kind), (Severity::Warning, IssueKind::Unreachable));
+    }
+
+    #[test]
+    fn tracking_key_must_be_a_stats_column() {
+        let conn = seeded_db();
+        conn.execute("UPDATE achievements SET tracking_key = 'puzzles_slovd' WHERE id = 'puzzles-bronze'", []).unwrap();
+
+        let report = check_content(&conn).unwrap();
+        let issues = issues_for(&report, "achievements", "puzzles-bronze");
+        assert!(issues.iter().any(|i| i.field == "tracking_key" && i.kind == IssueKind::DanglingReference));
+    }
+
+    #[test]
+    fn shop_item_of_wrong_type_is_dangling() {
+        let conn = seeded_db();
+        conn.execute(
+            "INSERT INTO shop_inventory (item_type, item_id, available, required_level) VALUES ('equipment', 'health_potion', TRUE, 1)",
+            [],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid().to_string();
+
+        let report = check_content(&conn).unwrap();
+        let issues = issues_for(&report, "shop_inventory", &id);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("other item type"));
+    }
+}
@@ -0,0 +1,17 @@
+use tauri::AppHandle;
+
+use crate::content_integrity::{self, IntegrityReport};
+use crate::db;
+use crate::error::AppError;
+
+// ============================================================================
+// Content Integrity Commands
+// ============================================================================
+
+/// Check cross-content references (loot, narrative links, achievements, quests,
+/// shop stock). The same check runs from the CLI: content-admin validate.
+#[tauri::command]
+pub fn check_content_integrity(app: AppHandle) -> Result<IntegrityReport, AppError> {
+    let conn = db::get_connection(&app)?;
+    content_integrity::check_content(&conn)
+}
@@ -0,0 +1,29 @@
+use tauri::AppHandle;
+
+use crate::content_versions::{self, ConflictResolution, ContentConflict};
+use crate::db;
+use crate::error::AppError;
+
+// ============================================================================
+// Content Version Commands
+// ============================================================================
+
+/// Shipped content changes that were held back because the row was edited or
+/// deleted locally. The same list is printed by: content-admin conflicts
+#[tauri::command]
+pub fn list_content_conflicts(app: AppHandle) -> Result<Vec<ContentConflict>, AppError> {
+    let conn = db::get_connection(&app)?;
+    content_versions::list_conflicts(&conn)
+}
+
+/// Keep the local row ("keep_mine") or replace it with the shipped one ("take_shipped")
+#[tauri::command]
+pub fn resolve_content_conflict(
+    app: AppHandle,
+    table_name: String,
+    row_id: String,
+    resolution: ConflictResolution,
+) -> Result<(), AppError> {
+    let conn = db::get_connection(&app)?;
+    content_versions::resolve_conflict(&conn, &table_name, &row_id, resolution)
+}
@@ -0,0 +1,592 @@
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::{AppError, ResultExt};
+use crate::migrations;
+
+// ============================================================================
+// Content Versioning
+// ============================================================================
+//
+// Seeds load shipped content with INSERT OR IGNORE, so a row a later release
+// fixes never reaches a database that already has it. After migrating, sync
+// builds the content this release ships in a scratch in-memory database and
+// compares it row by row with the live one. content_rows keeps the hash of the
+// shipped copy each row last took, so any difference from it is a user edit,
+// whether made in an editor (which also calls mark_user_modified) or by hand.
+// Unedited rows take the new shipped version; edited and deleted rows are left
+// alone and listed in content_conflicts until the user resolves them.
+
+struct VersionedTable {
+    name: &'static str,
+    /// Columns gameplay keeps updating, which are not content
+    volatile: &'static [&'static str],
+}
+
+/// Content tables keyed by a stable text id, parents before the rows that reference them
+const VERSIONED_TABLES: &[VersionedTable] = &[
+    VersionedTable { name: "puzzles", volatile: &["solve_count", "average_time"] },
+    VersionedTable { name: "playground_templates", volatile: &[] },
+    VersionedTable { name: "achievements", volatile: &[] },
+    VersionedTable { name: "abilities", volatile: &[] },
+    VersionedTable { name: "enemy_types", volatile: &[] },
+    VersionedTable { name: "boss_enemies", volatile: &[] },
+    VersionedTable { name: "custom_enemies", volatile: &[] },
+    VersionedTable { name: "equipment_items", volatile: &[] },
+    VersionedTable { name: "consumable_items", volatile: &[] },
+    VersionedTable { name: "shop_items", volatile: &[] },
+    VersionedTable { name: "quests", volatile: &[] },
+    VersionedTable { name: "narrative_locations", volatile: &[] },
+    VersionedTable { name: "narrative_choices", volatile: &[] },
+    VersionedTable { name: "narrative_outcomes", volatile: &[] },
+    VersionedTable { name: "dungeon_challenges", volatile: &["times_used", "success_rate"] },
+    VersionedTable { name: "mcq_questions", volatile: &[] },
+];
+
+/// Never compared: set when a row is written, not part of what it says
+const TIMESTAMP_COLUMNS: &[&str] = &["created_at", "updated_at"];
+
+/// Column values of one row, by column name
+type Row = BTreeMap<String, Value>;
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentSyncReport {
+    /// Content version recorded by this sync; None when already in sync
+    pub content_version: Option<i64>,
+    /// "table:id" of unedited rows updated to the shipped version
+    pub updated: Vec<String>,
+    /// "table:id" of shipped rows new to this database
+    pub added: Vec<String>,
+    /// "table:id (reason)" of shipped changes held back by user edits
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentConflict {
+    pub table_name: String,
+    pub row_id: String,
+    /// "modified" or "deleted"
+    pub reason: String,
+    pub content_version: i64,
+    /// The shipped row that was not applied
+    pub shipped_row: serde_json::Value,
+    pub detected_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Keep the user's row and stop reporting this shipped version
+    KeepMine,
+    /// Replace (or restore) the row with the shipped version
+    TakeShipped,
+}
+
+struct RowRecord {
+    origin: String,
+    shipped_hash: Option<String>,
+}
+
+fn versioned_table(name: &str) -> Result<&'static VersionedTable, AppError> {
+    VERSIONED_TABLES
+        .iter()
+        .find(|table| table.name == name)
+        .ok_or_else(|| AppError::Validation(format!("{} is not versioned content", name)))
+}
+
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>, AppError> {
+    conn.prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get(1))?
+        .collect::<rusqlite::Result<_>>()
+        .with_context(|| format!("Failed to read columns of {}", table))
+}
+
+/// The columns that make up a row's content
+fn compared_columns<'a>(table: &VersionedTable, columns: &'a [String]) -> Vec<&'a str> {
+    columns
+        .iter()
+        .map(String::as_str)
+        .filter(|column| !TIMESTAMP_COLUMNS.contains(column) && !table.volatile.contains(column))
+        .collect()
+}
+
+fn load_rows(conn: &Connection, table: &str, columns: &[String]) -> Result<HashMap<String, Row>, AppError> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM {}", columns.join(", "), table))?;
+    let rows = stmt
+        .query_map([], |sql_row| {
+            let mut row = Row::new();
+            for (index, column) in columns.iter().enumerate() {
+                row.insert(column.clone(), sql_row.get::<_, Value>(index)?);
+            }
+            Ok((sql_row.get::<_, String>("id")?, row))
+        })?
+        .collect::<rusqlite::Result<_>>()
+        .with_context(|| format!("Failed to read {}", table))?;
+    Ok(rows)
+}
+
+fn row_hash(row: &Row, compared: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for column in compared {
+        let value = match row.get(*column).unwrap_or(&Value::Null) {
+            Value::Null => "n".to_string(),
+            Value::Integer(i) => format!("i{}", i),
+            Value::Real(f) => format!("r{:?}", f),
+            Value::Text(s) => format!("t{}", s),
+            Value::Blob(b) => format!("b{:?}", b),
+        };
+        hasher.update(format!("{}={}\u{0}", column, value));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn row_to_json(row: &Row) -> serde_json::Value {
+    let object = row
+        .iter()
+        .map(|(column, value)| {
+            let value = match value {
+                Value::Null => serde_json::Value::Null,
+                Value::Integer(i) => (*i).into(),
+                Value::Real(f) => serde_json::Number::from_f64(*f).map_or(serde_json::Value::Null, Into::into),
+                Value::Text(s) => s.clone().into(),
+                Value::Blob(b) => b.clone().into(),
+            };
+            (column.clone(), value)
+        })
+        .collect();
+    serde_json::Value::Object(object)
+}
+
+fn row_from_json(json: &serde_json::Value) -> Result<Row, AppError> {
+    let object = json
+        .as_object()
+        .ok_or_else(|| AppError::Internal("Stored shipped row is not an object".to_string()))?;
+    object
+        .iter()
+        .map(|(column, value)| {
+            let value = match value {
+                serde_json::Value::Null => Value::Null,
+                serde_json::Value::Number(n) => match n.as_i64() {
+                    Some(i) => Value::Integer(i),
+                    None => Value::Real(n.as_f64().unwrap_or_default()),
+                },
+                serde_json::Value::String(s) => Value::Text(s.clone()),
+                serde_json::Value::Array(bytes) => {
+                    Value::Blob(bytes.iter().map(|b| b.as_u64().unwrap_or_default() as u8).collect())
+                }
+                other => return Err(AppError::Internal(format!("Unexpected value {} in stored shipped row", other))),
+            };
+            Ok((column.clone(), value))
+        })
+        .collect()
+}
+
+/// Overwrite the content columns of an existing row
+fn update_row(conn: &Connection, table: &str, id: &str, row: &Row, compared: &[&str]) -> Result<(), AppError> {
+    let columns: Vec<&str> = compared.iter().copied().filter(|column| *column != "id").collect();
+    let assignments: Vec<String> = columns.iter().enumerate().map(|(i, column)| format!("{} = ?{}", column, i + 2)).collect();
+    let values = std::iter::once(Value::Text(id.to_string()))
+        .chain(columns.iter().map(|column| row.get(*column).cloned().unwrap_or(Value::Null)));
+
+    conn.execute(&format!("UPDATE {} SET {} WHERE id = ?1", table, assignments.join(", ")), params_from_iter(values))
+        .with_context(|| format!("Failed to update {} {}", table, id))?;
+    Ok(())
+}
+
+fn insert_row(conn: &Connection, table: &str, row: &Row) -> Result<(), AppError> {
+    let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+
+    conn.execute(
+        &format!("INSERT INTO {} ({}) VALUES ({})", table, columns.join(", "), placeholders.join(", ")),
+        params_from_iter(row.values()),
+    )
+    .with_context(|| format!("Failed to insert {} row", table))?;
+    Ok(())
+}
+
+fn load_records(conn: &Connection, table: &str) -> Result<HashMap<String, RowRecord>, AppError> {
+    let mut stmt = conn.prepare("SELECT row_id, origin, shipped_hash FROM content_rows WHERE table_name = ?1")?;
+    let records = stmt
+        .query_map(params![table], |row| {
+            Ok((row.get(0)?, RowRecord { origin: row.get(1)?, shipped_hash: row.get(2)? }))
+        })?
+        .collect::<rusqlite::Result<_>>()
+        .context("Failed to read content rows")?;
+    Ok(records)
+}
+
+fn track(
+    conn: &Connection,
+    table: &str,
+    id: &str,
+    origin: &str,
+    version: i64,
+    shipped_hash: &str,
+) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO content_rows (table_name, row_id, origin, content_version, shipped_hash)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(table_name, row_id) DO UPDATE SET
+            origin = excluded.origin,
+            content_version = excluded.content_version,
+            shipped_hash = excluded.shipped_hash",
+        params![table, id, origin, version, shipped_hash],
+    )
+    .context("Failed to record content row")?;
+    conn.execute("DELETE FROM content_conflicts WHERE table_name = ?1 AND row_id = ?2", params![table, id])
+        .context("Failed to clear content conflict")?;
+    Ok(())
+}
+
+/// Record that the user changed (or created) a content row, so releases never overwrite it
+pub(crate) fn mark_user_modified(conn: &Connection, table: &str, id: &str) -> Result<(), AppError> {
+    versioned_table(table)?;
+    conn.execute(
+        "INSERT INTO content_rows (table_name, row_id, origin) VALUES (?1, ?2, 'user')
+         ON CONFLICT(table_name, row_id) DO UPDATE SET origin = 'user'",
+        params![table, id],
+    )
+    .context("Failed to mark content as edited")?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record_conflict(
+    conn: &Connection,
+    table: &str,
+    id: &str,
+    reason: &str,
+    version: i64,
+    shipped_hash: &str,
+    shipped_row: &Row,
+    report: &mut ContentSyncReport,
+) -> Result<(), AppError> {
+    mark_user_modified(conn, table, id)?;
+    conn.execute(
+        "INSERT INTO content_conflicts (table_name, row_id, reason, content_version, shipped_hash, shipped_row)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(table_name, row_id) DO UPDATE SET
+            reason = excluded.reason,
+            content_version = excluded.content_version,
+            shipped_hash = excluded.shipped_hash,
+            shipped_row = excluded.shipped_row,
+            detected_at = datetime('now')",
+        params![table, id, reason, version, shipped_hash, row_to_json(shipped_row).to_string()],
+    )
+    .context("Failed to record content conflict")?;
+    report.conflicts.push(format!("{}:{} ({})", table, id, reason));
+    Ok(())
+}
+
+/// Bring one table's unedited rows up to the shipped content
+fn sync_table(
+    conn: &Connection,
+    reference: &Connection,
+    table: &VersionedTable,
+    version: i64,
+    first_sync: bool,
+    report: &mut ContentSyncReport,
+) -> Result<(), AppError> {
+    let columns = table_columns(conn, table.name)?;
+    let compared = compared_columns(table, &columns);
+    let shipped_rows = load_rows(reference, table.name, &columns)?;
+    let live_rows = load_rows(conn, table.name, &columns)?;
+    let records = load_records(conn, table.name)?;
+
+    let mut ids: Vec<&String> = shipped_rows.keys().collect();
+    ids.sort();
+
+    for id in ids {
+        let shipped = &shipped_rows[id];
+        let shipped_hash = row_hash(shipped, &compared);
+        let live_hash = live_rows.get(id).map(|row| row_hash(row, &compared));
+        let label = format!("{}:{}", table.name, id);
+
+        match (records.get(id), live_hash) {
+            (Some(record), Some(live)) => {
+                let recorded = record.shipped_hash.as_deref();
+                if record.origin == "shipped" && live == shipped_hash {
+                    if recorded != Some(shipped_hash.as_str()) {
+                        track(conn, table.name, id, "shipped", version, &shipped_hash)?;
+                    }
+                } else if record.origin == "shipped" && recorded == Some(live.as_str()) {
+                    // Unedited since the last shipped copy
+                    update_row(conn, table.name, id, shipped, &compared)?;
+                    track(conn, table.name, id, "shipped", version, &shipped_hash)?;
+                    report.updated.push(label);
+                } else if live == shipped_hash {
+                    // The user's row already says what this release ships
+                    if recorded != Some(shipped_hash.as_str()) {
+                        track(conn, table.name, id, "user", version, &shipped_hash)?;
+                    }
+                } else if recorded == Some(shipped_hash.as_str()) {
+                    // Edited, but nothing new shipped for it
+                    if record.origin != "user" {
+                        mark_user_modified(conn, table.name, id)?;
+                    }
+                } else {
+                    record_conflict(conn, table.name, id, "modified", version, &shipped_hash, shipped, report)?;
+                }
+            }
+            (None, Some(live)) => {
+                if live == shipped_hash {
+                    track(conn, table.name, id, "shipped", version, &shipped_hash)?;
+                } else {
+                    // Created by the user under an id a release now uses
+                    record_conflict(conn, table.name, id, "modified", version, &shipped_hash, shipped, report)?;
+                }
+            }
+            // Deleted by the user
+            (Some(record), None) => {
+                if record.shipped_hash.as_deref() != Some(shipped_hash.as_str()) {
+                    record_conflict(conn, table.name, id, "deleted", version, &shipped_hash, shipped, report)?;
+                }
+            }
+            // Missing before anything was tracked: the seeds put every shipped row in, so it was deleted
+            (None, None) if first_sync => {
+                track(conn, table.name, id, "user", version, &shipped_hash)?;
+            }
+            (None, None) => {
+                insert_row(conn, table.name, shipped)?;
+                track(conn, table.name, id, "shipped", version, &shipped_hash)?;
+                report.added.push(label);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The content this build ships: every migration and seed applied to an empty database
+fn shipped_content() -> Result<Connection, AppError> {
+    let mut reference = Connection::open_in_memory()
+        .context("Failed to open shipped content database")?;
+    migrations::run_migrations(&mut reference)
+        .context("Failed to build shipped content")?;
+    Ok(reference)
+}
+
+/// Apply shipped content changes to unedited rows and report the rest. Call after
+/// run_migrations; does nothing when this content version was already synced.
+pub fn sync_content(conn: &mut Connection) -> Result<ContentSyncReport, AppError> {
+    let fingerprint = migrations::content_fingerprint();
+    let synced: bool = conn
+        .query_row("SELECT COUNT(*) FROM content_versions WHERE fingerprint = ?1", params![fingerprint], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+        .context("Failed to read content version")?;
+    if synced {
+        return Ok(ContentSyncReport::default());
+    }
+
+    let reference = shipped_content()?;
+    let tx = conn
+        .transaction()
+        .context("Failed to start content sync")?;
+    // Shipped rows are inserted parent first, but outcomes and locations point at each other
+    tx.execute_batch("PRAGMA defer_foreign_keys = ON;")
+        .context("Failed to defer foreign keys")?;
+
+    let first_sync: bool = tx
+        .query_row("SELECT COUNT(*) = 0 FROM content_versions", [], |row| row.get(0))
+        .context("Failed to read content versions")?;
+    tx.execute("INSERT INTO content_versions (fingerprint) VALUES (?1)", params![fingerprint])
+        .context("Failed to record content version")?;
+    let version = tx.last_insert_rowid();
+
+    let mut report = ContentSyncReport { content_version: Some(version), ..Default::default() };
+    for table in VERSIONED_TABLES {
+        sync_table(&tx, &reference, table, version, first_sync, &mut report)?;
+    }
+
+    tx.execute(
+        "UPDATE content_versions SET updated_rows = ?2, added_rows = ?3, conflicts = ?4 WHERE version = ?1",
+        params![version, report.updated.len() as i64, report.added.len() as i64, report.conflicts.len() as i64],
+    )
+    .context("Failed to record content version")?;
+    tx.commit()
+        .context("Failed to commit content sync")?;
+    Ok(report)
+}
+
+/// Shipped changes waiting on the user, oldest first
+pub fn list_conflicts(conn: &Connection) -> Result<Vec<ContentConflict>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT table_name, row_id, reason, content_version, shipped_row, detected_at
+         FROM content_conflicts ORDER BY detected_at, table_name, row_id",
+    )?;
+    let conflicts = stmt
+        .query_map([], |row| {
+            Ok(ContentConflict {
+                table_name: row.get(0)?,
+                row_id: row.get(1)?,
+                reason: row.get(2)?,
+                content_version: row.get(3)?,
+                shipped_row: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+                detected_at: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()
+        .context("Failed to read content conflicts")?;
+    Ok(conflicts)
+}
+
+/// Settle a conflict by keeping the user's row or taking the shipped one
+pub fn resolve_conflict(conn: &Connection, table_name: &str, row_id: &str, resolution: ConflictResolution) -> Result<(), AppError> {
+    let table = versioned_table(table_name)?;
+    let (version, shipped_hash, shipped_row): (i64, String, String) = conn
+        .query_row(
+            "SELECT content_version, shipped_hash, shipped_row FROM content_conflicts WHERE table_name = ?1 AND row_id = ?2",
+            params![table_name, row_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("No content conflict for {} {}", table_name, row_id)))?;
+
+    let tx = conn
+        .unchecked_transaction()
+        .context("Failed to start conflict resolution")?;
+    match resolution {
+        ConflictResolution::KeepMine => {
+            track(&tx, table_name, row_id, "user", version, &shipped_hash)?;
+        }
+        ConflictResolution::TakeShipped => {
+            let shipped = row_from_json(&serde_json::from_str(&shipped_row).context("Stored shipped row is not valid JSON")?)?;
+            let exists: bool = tx.query_row(
+                &format!("SELECT COUNT(*) FROM {} WHERE id = ?1", table_name),
+                params![row_id],
+                |row| row.get::<_, i64>(0),
+            )? > 0;
+
+            if exists {
+                let columns = table_columns(&tx, table_name)?;
+                update_row(&tx, table_name, row_id, &shipped, &compared_columns(table, &columns))?;
+            } else {
+                insert_row(&tx, table_name, &shipped)?;
+            }
+            track(&tx, table_name, row_id, "shipped", version, &shipped_hash)?;
+        }
+    }
+    tx.commit()
+        .context("Failed to commit conflict resolution")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{scalar, seeded_db};
+
+    /// A database tracking the content this build ships
+    fn synced_db() -> Connection {
+        let mut conn = seeded_db();
+        sync_content(&mut conn).unwrap();
+        conn
+    }
+
+    /// Sync again as if a new release had shipped, by forgetting the current version
+    fn next_release(conn: &mut Connection) -> ContentSyncReport {
+        conn.execute("UPDATE content_versions SET fingerprint = 'release ' || version", []).unwrap();
+        sync_content(conn).unwrap()
+    }
+
+    /// Make a row look like an older shipped copy: changed, with its record following the change
+    fn ship_old_copy(conn: &Connection, table: &str, id: &str, assignment: &str) {
+        conn.execute(&format!("UPDATE {} SET {} WHERE id = ?1", table, assignment), params![id]).unwrap();
+        let versioned = versioned_table(table).unwrap();
+        let columns = table_columns(conn, table).unwrap();
+        let row = load_rows(conn, table, &columns).unwrap().remove(id).unwrap();
+        let hash = row_hash(&row, &compared_columns(versioned, &columns));
+        conn.execute(
+            "UPDATE content_rows SET shipped_hash = ?3 WHERE table_name = ?1 AND row_id = ?2",
+            params![table, id, hash],
+        )
+        .unwrap();
+    }
+
+    fn first_id(conn: &Connection, table: &str) -> String {
+        scalar(conn, &format!("SELECT id FROM {} ORDER BY id LIMIT 1", table), [])
+    }
+
+    #[test]
+    fn first_sync_tracks_everything_and_changes_nothing() {
+        let mut conn = seeded_db();
+        let report = sync_content(&mut conn).unwrap();
+
+        assert_eq!(report.content_version, Some(1));
+        assert!(report.updated.is_empty() && report.added.is_empty() && report.conflicts.is_empty(), "{:?}", report);
+        assert!(scalar::<i64>(&conn, "SELECT COUNT(*) FROM content_rows WHERE origin = 'shipped'", []) > 100);
+
+        // Same content again is a no-op
+        assert_eq!(sync_content(&mut conn).unwrap().content_version, None);
+    }
+
+    #[test]
+    fn unedited_rows_take_shipped_fixes_and_edited_rows_are_reported() {
+        let mut conn = synced_db();
+        let fixed = first_id(&conn, "equipment_items");
+        let edited: String = scalar(&conn, "SELECT id FROM equipment_items ORDER BY id LIMIT 1 OFFSET 1", []);
+        let shipped_name: String = scalar(&conn, "SELECT name FROM equipment_items WHERE id = ?1", params![fixed]);
+        let shipped_damage: i64 = scalar(&conn, "SELECT damage_bonus FROM equipment_items WHERE id = ?1", params![edited]);
+
+        // Both rows shipped with a typo; the user then rebalanced one of them
+        ship_old_copy(&conn, "equipment_items", &fixed, "name = 'Swrod'");
+        ship_old_copy(&conn, "equipment_items", &edited, "name = 'Swrod'");
+        conn.execute("UPDATE equipment_items SET damage_bonus = 999 WHERE id = ?1", params![edited]).unwrap();
+
+        let report = next_release(&mut conn);
+        assert_eq!(report.updated, [format!("equipment_items:{}", fixed)]);
+        assert_eq!(report.conflicts, [format!("equipment_items:{} (modified)", edited)]);
+
+        assert_eq!(scalar::<String>(&conn, "SELECT name FROM equipment_items WHERE id = ?1", params![fixed]), shipped_name);
+        assert_eq!(scalar::<i64>(&conn, "SELECT damage_bonus FROM equipment_items WHERE id = ?1", params![edited]), 999);
+
+        let conflicts = list_conflicts(&conn).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].shipped_row["damage_bonus"], shipped_damage);
+
+        resolve_conflict(&conn, "equipment_items", &edited, ConflictResolution::TakeShipped).unwrap();
+        assert_eq!(scalar::<i64>(&conn, "SELECT damage_bonus FROM equipment_items WHERE id = ?1", params![edited]), shipped_damage);
+        assert!(list_conflicts(&conn).unwrap().is_empty());
+        assert!(next_release(&mut conn).conflicts.is_empty());
+    }
+
+    #[test]
+    fn marked_edits_are_kept_and_deleted_rows_stay_deleted() {
+        let mut conn = synced_db();
+        let question = first_id(&conn, "mcq_questions");
+        let template = first_id(&conn, "playground_templates");
+
+        // Saved unchanged from the Question Manager, which marks it edited
+        ship_old_copy(&conn, "mcq_questions", &question, "question_text = 'Old wording'");
+        mark_user_modified(&conn, "mcq_questions", &question).unwrap();
+        conn.execute("DELETE FROM playground_templates WHERE id = ?1", params![template]).unwrap();
+
+        let report = next_release(&mut conn);
+        assert_eq!(report.conflicts, [format!("mcq_questions:{} (modified)", question)]);
+        assert!(report.added.is_empty(), "{:?}", report);
+        assert_eq!(scalar::<String>(&conn, "SELECT question_text FROM mcq_questions WHERE id = ?1", params![question]), "Old wording");
+        assert_eq!(scalar::<i64>(&conn, "SELECT COUNT(*) FROM playground_templates WHERE id = ?1", params![template]), 0);
+
+        resolve_conflict(&conn, "mcq_questions", &question, ConflictResolution::KeepMine).unwrap();
+        assert!(next_release(&mut conn).conflicts.is_empty());
+
+        // A release that changes the deleted row asks before restoring it
+        conn.execute(
+            "UPDATE content_rows SET shipped_hash = 'older' WHERE table_name = 'playground_templates' AND row_id = ?1",
+            params![template],
+        )
+        .unwrap();
+        assert_eq!(next_release(&mut conn).conflicts, [format!("playground_templates:{} (deleted)", template)]);
+
+        resolve_conflict(&conn, "playground_templates", &template, ConflictResolution::TakeShipped).unwrap();
+        assert_eq!(scalar::<i64>(&conn, "SELECT COUNT(*) FROM playground_templates WHERE id = ?1", params![template]), 1);
+        assert_eq!(
+            scalar::<String>(&conn, "SELECT origin FROM content_rows WHERE table_name = 'mcq_questions' AND row_id = ?1", params![question]),
+            "user"
+        );
+    }
+}
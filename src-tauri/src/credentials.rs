@@ -0,0 +1,393 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::error::{AppError, ResultExt};
+
+// ============================================================================
+// At-rest encryption for AI provider API keys
+// ============================================================================
+//
+// Keys are encrypted with AES-256-GCM. The encryption key is either a random
+// machine-local secret stored next to the database, or derived from a user
+// passphrase with Argon2. Passphrase-derived keys are only held in memory
+// after the user unlocks them for the current session.
+
+pub const KEY_SOURCE_MACHINE: &str = "machine";
+pub const KEY_SOURCE_PASSPHRASE: &str = "passphrase";
+
+const MACHINE_SECRET_FILE: &str = "credentials.key";
+const SUPPORTED_PROVIDERS: &[&str] = &["claude"];
+
+lazy_static::lazy_static! {
+    /// Passphrase-derived keys unlocked for this session, keyed by provider
+    static ref UNLOCKED_KEYS: Mutex<HashMap<String, [u8; 32]>> = Mutex::new(HashMap::new());
+}
+
+/// Encrypted credential row as stored in ai_provider_credentials
+pub struct StoredCredential {
+    pub ciphertext: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub key_source: String,
+    pub salt: Option<Vec<u8>>,
+}
+
+pub fn validate_provider(provider: &str) -> Result<(), AppError> {
+    if SUPPORTED_PROVIDERS.contains(&provider) {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!("Unsupported AI provider: {}", provider)))
+    }
+}
+
+/// Short display hint for a key, e.g. "…a1b2"
+pub fn key_hint(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.chars().collect();
+    let tail: String = chars[chars.len().saturating_sub(4)..].iter().collect();
+    format!("…{}", tail)
+}
+
+/// Read the machine-local secret, creating it on first use
+fn load_machine_secret(dir: &Path) -> Result<[u8; 32], AppError> {
+    let path = dir.join(MACHINE_SECRET_FILE);
+
+    if path.exists() {
+        let bytes = std::fs::read(&path)
+            .context("Failed to read credentials secret")?;
+        return bytes
+            .try_into()
+            .map_err(|_| AppError::Internal("Credentials secret file is corrupted".to_string()));
+    }
+
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    std::fs::write(&path, secret)
+        .context("Failed to write credentials secret")?;
+
+    // Keep the secret readable by the current user only
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .context("Failed to secure credentials secret")?;
+    }
+
+    Ok(secret)
+}
+
+fn derive_passphrase_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], AppError> {
+    if passphrase.is_empty() {
+        return Err(AppError::Validation("Passphrase cannot be empty".to_string()));
+    }
+
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| AppError::Internal(format!("Failed to derive key from passphrase: {}", e)))?;
+    Ok(key)
+}
+
+fn encrypt(key: &[u8; 32], plaintext: &str) -> Result<(Vec<u8>, Vec<u8>), AppError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| AppError::Internal("Failed to encrypt API key".to_string()))?;
+    Ok((nonce.to_vec(), ciphertext))
+}
+
+fn decrypt(key: &[u8; 32], nonce: &[u8], ciphertext: &[u8]) -> Result<String, AppError> {
+    if nonce.len() != 12 {
+        return Err(AppError::Internal("Stored credential is corrupted".to_string()));
+    }
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| AppError::Validation("Failed to decrypt API key (wrong passphrase or corrupted data)".to_string()))?;
+    String::from_utf8(plaintext).map_err(|_| AppError::Internal("Stored credential is corrupted".to_string()))
+}
+
+pub fn get_stored_credential(conn: &Connection, provider: &str) -> Result<Option<StoredCredential>, AppError> {
+    conn.query_row(
+        "SELECT ciphertext, nonce, key_source, salt FROM ai_provider_credentials WHERE provider = ?1",
+        params![provider],
+        |row| {
+            Ok(StoredCredential {
+                ciphertext: row.get(0)?,
+                nonce: row.get(1)?,
+                key_source: row.get(2)?,
+                salt: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+    .context("Failed to load credential")
+}
+
+/// Whether a passphrase-protected credential has been unlocked this session
+pub fn is_unlocked(provider: &str) -> bool {
+    UNLOCKED_KEYS
+        .lock()
+        .map(|keys| keys.contains_key(provider))
+        .unwrap_or(false)
+}
+
+pub fn lock(provider: &str) {
+    if let Ok(mut keys) = UNLOCKED_KEYS.lock() {
+        keys.remove(provider);
+    }
+}
+
+fn remember_unlocked_key(provider: &str, key: [u8; 32]) -> Result<(), AppError> {
+    let mut keys = UNLOCKED_KEYS
+        .lock()
+        .map_err(|e| AppError::Internal(format!("Credential cache error: {}. Please restart the app.", e)))?;
+    keys.insert(provider.to_string(), key);
+    Ok(())
+}
+
+/// Encrypt and store an API key, replacing any existing key for the provider.
+/// With a passphrase the key is derived via Argon2, otherwise the machine secret is used.
+pub fn store_api_key(
+    conn: &Connection,
+    secret_dir: &Path,
+    provider: &str,
+    api_key: &str,
+    passphrase: Option<&str>,
+    is_rotation: bool,
+) -> Result<(), AppError> {
+    validate_provider(provider)?;
+
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Err(AppError::Validation("API key cannot be empty".to_string()));
+    }
+
+    let (key, key_source, salt) = match passphrase {
+        Some(passphrase) => {
+            let mut salt = [0u8; 16];
+            OsRng.fill_bytes(&mut salt);
+            let key = derive_passphrase_key(passphrase, &salt)?;
+            (key, KEY_SOURCE_PASSPHRASE, Some(salt.to_vec()))
+        }
+        None => (load_machine_secret(secret_dir)?, KEY_SOURCE_MACHINE, None),
+    };
+
+    let (nonce, ciphertext) = encrypt(&key, api_key)?;
+
+    conn.execute(
+        "INSERT INTO ai_provider_credentials (provider, ciphertext, nonce, key_source, salt, key_hint, rotated_at, last_tested_at, last_test_ok)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, CASE WHEN ?7 THEN datetime('now') END, NULL, NULL)
+         ON CONFLICT(provider) DO UPDATE SET
+            ciphertext = excluded.ciphertext,
+            nonce = excluded.nonce,
+            key_source = excluded.key_source,
+            salt = excluded.salt,
+            key_hint = excluded.key_hint,
+            rotated_at = excluded.rotated_at,
+            last_tested_at = NULL,
+            last_test_ok = NULL",
+        params![provider, ciphertext, nonce, key_source, salt, key_hint(api_key), is_rotation],
+    )
+    .context("Failed to store credential")?;
+
+    // A passphrase-protected key is usable straight away in the session that set it
+    lock(provider);
+    if key_source == KEY_SOURCE_PASSPHRASE {
+        remember_unlocked_key(provider, key)?;
+    }
+
+    Ok(())
+}
+
+/// Decrypt the stored API key for a provider
+pub fn load_api_key(conn: &Connection, secret_dir: &Path, provider: &str) -> Result<String, AppError> {
+    let credential = get_stored_credential(conn, provider)?.ok_or_else(|| {
+        AppError::NotFound(format!("No API key configured for {}. Add one in Settings.", provider))
+    })?;
+
+    let key = if credential.key_source == KEY_SOURCE_PASSPHRASE {
+        let keys = UNLOCKED_KEYS
+            .lock()
+            .map_err(|e| AppError::Internal(format!("Credential cache error: {}. Please restart the app.", e)))?;
+        *keys.get(provider).ok_or_else(|| {
+            AppError::Validation(format!("The {} API key is locked. Enter your passphrase to unlock it.", provider))
+        })?
+    } else {
+        load_machine_secret(secret_dir)?
+    };
+
+    decrypt(&key, &credential.nonce, &credential.ciphertext)
+}
+
+/// Verify a passphrase against the stored credential and keep it unlocked for the session
+pub fn unlock_with_passphrase(conn: &Connection, provider: &str, passphrase: &str) -> Result<String, AppError> {
+    let credential = get_stored_credential(conn, provider)?
+        .ok_or_else(|| AppError::NotFound(format!("No API key configured for {}", provider)))?;
+
+    if credential.key_source != KEY_SOURCE_PASSPHRASE {
+        return Err(AppError::Validation(format!("The {} API key is not protected by a passphrase", provider)));
+    }
+
+    let salt = credential
+        .salt
+        .ok_or_else(|| AppError::Internal("Stored credential is missing its salt".to_string()))?;
+    let key = derive_passphrase_key(passphrase, &salt)?;
+    let api_key = decrypt(&key, &credential.nonce, &credential.ciphertext)?;
+
+    remember_unlocked_key(provider, key)?;
+    Ok(api_key)
+}
+
+/// How a rotated key is protected afterwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protection<'a> {
+    /// Whatever protects it now; a passphrase-protected key needs the current passphrase
+    Keep,
+    Passphrase(&'a str),
+    /// Drop any passphrase and use the machine-local secret
+    Machine,
+}
+
+/// Re-encrypt a provider key with fresh key material, optionally replacing the key itself.
+/// Decrypting the current key first proves the caller can unlock it, even when it is being replaced.
+pub fn rotate_api_key(
+    conn: &Connection,
+    secret_dir: &Path,
+    provider: &str,
+    new_api_key: Option<&str>,
+    current_passphrase: Option<&str>,
+    protection: Protection,
+) -> Result<(), AppError> {
+    validate_provider(provider)?;
+    let credential = get_stored_credential(conn, provider)?
+        .ok_or_else(|| AppError::NotFound(format!("No API key configured for {}", provider)))?;
+    let protected = credential.key_source == KEY_SOURCE_PASSPHRASE;
+
+    let passphrase = match protection {
+        Protection::Passphrase(passphrase) => Some(passphrase),
+        Protection::Machine => None,
+        Protection::Keep if protected => Some(current_passphrase.ok_or_else(|| {
+            AppError::Validation(format!("Enter the current passphrase to rotate the {} API key", provider))
+        })?),
+        Protection::Keep => None,
+    };
+
+    let current_api_key = match current_passphrase {
+        Some(current) if protected => unlock_with_passphrase(conn, provider, current)?,
+        _ => load_api_key(conn, secret_dir, provider)?,
+    };
+
+    store_api_key(conn, secret_dir, provider, new_api_key.unwrap_or(&current_api_key), passphrase, true)
+}
+
+/// Convenience wrapper used by the AI commands
+pub fn get_api_key(app: &AppHandle, provider: &str) -> Result<String, AppError> {
+    let conn = db::get_connection(app)?;
+    let secret_dir = db::get_app_data_dir(app)?;
+    load_api_key(&conn, &secret_dir, provider)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::seeded_db;
+    use std::path::PathBuf;
+
+    // Only the machine-secret path of load_api_key is used here: the unlocked
+    // passphrase keys are process-wide and other tests store under the same provider.
+
+    /// A scratch folder for the machine secret, removed on drop
+    struct SecretDir(PathBuf);
+
+    impl SecretDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("code-tutor-credentials-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            SecretDir(dir)
+        }
+    }
+
+    impl Drop for SecretDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn stored(conn: &Connection) -> StoredCredential {
+        get_stored_credential(conn, "claude").unwrap().unwrap()
+    }
+
+    #[test]
+    fn keys_round_trip_under_either_secret() {
+        let conn = seeded_db();
+        let secrets = SecretDir::new();
+
+        store_api_key(&conn, &secrets.0, "claude", " sk-machine-1234 ", None, false).unwrap();
+        assert_eq!(stored(&conn).key_source, KEY_SOURCE_MACHINE);
+        assert_eq!(load_api_key(&conn, &secrets.0, "claude").unwrap(), "sk-machine-1234");
+
+        store_api_key(&conn, &secrets.0, "claude", "sk-phrase-5678", Some("hunter2"), false).unwrap();
+        let credential = stored(&conn);
+        assert_eq!(credential.key_source, KEY_SOURCE_PASSPHRASE);
+        assert!(!credential.ciphertext.windows(4).any(|w| w == b"5678"));
+        assert_eq!(unlock_with_passphrase(&conn, "claude", "hunter2").unwrap(), "sk-phrase-5678");
+    }
+
+    #[test]
+    fn wrong_passphrase_does_not_decrypt() {
+        let conn = seeded_db();
+        let secrets = SecretDir::new();
+        store_api_key(&conn, &secrets.0, "claude", "sk-phrase-5678", Some("hunter2"), false).unwrap();
+
+        let err = unlock_with_passphrase(&conn, "claude", "hunter3").unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+
+    #[test]
+    fn tampered_ciphertext_does_not_decrypt() {
+        let conn = seeded_db();
+        let secrets = SecretDir::new();
+        store_api_key(&conn, &secrets.0, "claude", "sk-machine-1234", None, false).unwrap();
+
+        let mut ciphertext = stored(&conn).ciphertext;
+        ciphertext[0] ^= 0x01;
+        conn.execute("UPDATE ai_provider_credentials SET ciphertext = ?1 WHERE provider = 'claude'", params![ciphertext])
+            .unwrap();
+
+        assert!(load_api_key(&conn, &secrets.0, "claude").is_err());
+    }
+
+    #[test]
+    fn rotation_keeps_the_passphrase_unless_told_otherwise() {
+        let conn = seeded_db();
+        let secrets = SecretDir::new();
+        store_api_key(&conn, &secrets.0, "claude", "sk-phrase-5678", Some("hunter2"), false).unwrap();
+        let before = stored(&conn);
+
+        let err = rotate_api_key(&conn, &secrets.0, "claude", None, None, Protection::Keep).unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+        assert_eq!(stored(&conn).ciphertext, before.ciphertext);
+
+        rotate_api_key(&conn, &secrets.0, "claude", None, Some("hunter2"), Protection::Keep).unwrap();
+        let rotated = stored(&conn);
+        assert_eq!(rotated.key_source, KEY_SOURCE_PASSPHRASE);
+        assert_ne!(rotated.salt, before.salt);
+        assert_eq!(unlock_with_passphrase(&conn, "claude", "hunter2").unwrap(), "sk-phrase-5678");
+
+        rotate_api_key(&conn, &secrets.0, "claude", Some("sk-new-9999"), Some("hunter2"), Protection::Machine).unwrap();
+        assert_eq!(stored(&conn).key_source, KEY_SOURCE_MACHINE);
+        assert_eq!(load_api_key(&conn, &secrets.0, "claude").unwrap(), "sk-new-9999");
+    }
+}
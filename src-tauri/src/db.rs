@@ -1,40 +1,88 @@
-use rusqlite::{Connection, Result};
-use std::path::PathBuf;
+use r2d2::{Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{Connection, Result, TransactionBehavior};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tauri::{AppHandle, Manager};
 
-/// Helper function to safely execute ALTER TABLE ADD COLUMN statements
-/// Only ignores "duplicate column" errors, returns error for any other failure
-fn safe_add_column(conn: &Connection, sql: &str) -> std::result::Result<(), String> {
-    match conn.execute(sql, []) {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            let error_msg = e.to_string().to_lowercase();
-            if error_msg.contains("duplicate column") || error_msg.contains("already exists") {
-                // Column already exists - this is fine
-                Ok(())
-            } else {
-                Err(format!("Migration failed: {}", e))
-            }
-        }
-    }
-}
+use crate::backup::{self, BackupKind};
+use crate::game::clock::SystemClock;
+use crate::content_versions;
+use crate::migrations;
+use crate::error::{AppError, ResultExt};
 
-/// Get the database file path
-fn get_db_path(app: &AppHandle) -> Result<PathBuf, String> {
+/// Shared connection pool, kept in Tauri managed state
+pub type DbPool = Pool<SqliteConnectionManager>;
+/// A connection borrowed from the pool; returned to it when dropped
+pub type DbConnection = PooledConnection<SqliteConnectionManager>;
+
+/// Enough for nested helpers inside concurrent commands; SQLite still serializes writes
+const POOL_MAX_SIZE: u32 = 8;
+/// How long a writer waits on a locked database before failing with SQLITE_BUSY
+const BUSY_TIMEOUT_MS: u32 = 5000;
+
+/// Get the app data directory, creating it if needed
+pub fn get_app_data_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
     let app_data_dir = app
         .path()
         .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+        .context("Failed to get app data directory")?;
 
     // Create the directory if it doesn't exist
     std::fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+        .context("Failed to create app data directory")?;
 
-    Ok(app_data_dir.join("code-tutor.db"))
+    Ok(app_data_dir)
 }
 
-/// Initialize the database with schema and seed data
-pub fn initialize_database(app: &AppHandle) -> Result<(), String> {
+/// Get the database file path
+fn get_db_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    Ok(get_app_data_dir(app)?.join("code-tutor.db"))
+}
+
+/// Per-connection settings, applied once when the pool opens a connection
+fn configure_connection(conn: &mut Connection) -> Result<()> {
+    conn.execute_batch(&format!(
+        "PRAGMA foreign_keys = ON;
+         PRAGMA busy_timeout = {};
+         PRAGMA synchronous = NORMAL;",
+        BUSY_TIMEOUT_MS
+    ))
+}
+
+/// Build a connection pool for a database file. WAL mode is stored in the file
+/// itself, so it only needs setting once rather than per connection.
+pub fn create_pool(db_path: &Path) -> Result<DbPool, AppError> {
+    let manager = SqliteConnectionManager::file(db_path).with_init(configure_connection);
+    let pool = Pool::builder()
+        .max_size(POOL_MAX_SIZE)
+        .connection_timeout(Duration::from_secs(10))
+        .build(manager)
+        .context("Failed to create database pool")?;
+
+    let conn = pool
+        .get()
+        .context("Failed to open database")?;
+    conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))
+        .context("Failed to enable WAL mode")?;
+
+    Ok(pool)
+}
+
+/// Open a database file on its own connection, outside the app's pool. Used by the
+/// content-admin CLI, which works on any database path.
+pub fn open_database(db_path: &Path) -> Result<Connection, AppError> {
+    let mut conn = Connection::open(db_path)
+        .with_context(|| format!("Failed to open {}", db_path.display()))?;
+    configure_connection(&mut conn)
+        .context("Failed to configure database connection")?;
+    conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))
+        .context("Failed to enable WAL mode")?;
+    Ok(conn)
+}
+
+/// Initialize the database with schema and seed data, returning the pool for managed state
+pub fn initialize_database(app: &AppHandle) -> Result<DbPool, AppError> {
     let db_path = get_db_path(app)?;
 
     // Check if this is the first launch (database doesn't exist)
@@ -44,7 +92,7 @@ pub fn initialize_database(app: &AppHandle) -> Result<(), String> {
         log::info!("First launch detected - checking for seed database...");
 
         // Try to copy bundled seed database if it exists
-        // The seed database should be exported from dev using export-database-for-production.ps1
+        // The seed database is built from its sources by `content-admin build-seed` (seed_builder.rs)
         #[cfg(not(debug_assertions))]
         {
             // In production builds, try to use bundled seed database
@@ -54,7 +102,7 @@ pub fn initialize_database(app: &AppHandle) -> Result<(), String> {
             if seed_db_bytes.len() > 1024 {
                 log::info!("Found bundled seed database ({} bytes), copying to app data...", seed_db_bytes.len());
                 std::fs::write(&db_path, seed_db_bytes)
-                    .map_err(|e| format!("Failed to write seed database: {}", e))?;
+                    .context("Failed to write seed database")?;
                 log::info!("✓ Seed database copied successfully!");
                 log::info!("  All questions, enemies, items, and content are now available!");
             } else {
@@ -68,353 +116,50 @@ pub fn initialize_database(app: &AppHandle) -> Result<(), String> {
         }
     }
 
-    let conn = Connection::open(&db_path)
-        .map_err(|e| format!("Failed to open database: {}", e))?;
-
-    // Enable foreign key constraints
-    conn.execute("PRAGMA foreign_keys = ON", [])
-        .map_err(|e| format!("Failed to enable foreign keys: {}", e))?;
-
-    // Execute languages migration first (required by other tables)
-    let languages_migration = include_str!("../migrations/001_languages.sql");
-    conn.execute_batch(languages_migration)
-        .map_err(|e| format!("Failed to execute languages migration: {}", e))?;
-
-    // Execute puzzle schema
-    let schema_sql = include_str!("../../course-framework-output/database/puzzles-schema.sql");
-    conn.execute_batch(schema_sql)
-        .map_err(|e| format!("Failed to execute puzzle schema: {}", e))?;
-
-    // Execute puzzle seed data
-    let seed_sql = include_str!("../../course-framework-output/database/puzzles-seed.sql");
-    conn.execute_batch(seed_sql)
-        .map_err(|e| format!("Failed to execute puzzle seed data: {}", e))?;
-
-    // Execute playground migration
-    let playground_migration = include_str!("../migrations/006_playground.sql");
-    conn.execute_batch(playground_migration)
-        .map_err(|e| format!("Failed to execute playground migration: {}", e))?;
-
-    // Execute playground seed data
-    let playground_seed = include_str!("../../course-framework-output/database/playground-seed.sql");
-    conn.execute_batch(playground_seed)
-        .map_err(|e| format!("Failed to execute playground seed data: {}", e))?;
-
-    // Execute gamification migration
-    let gamification_migration = include_str!("../migrations/007_gamification.sql");
-    conn.execute_batch(gamification_migration)
-        .map_err(|e| format!("Failed to execute gamification migration: {}", e))?;
-
-    // Execute gamification seed data
-    let gamification_seed = include_str!("../../course-framework-output/database/gamification-seed.sql");
-    conn.execute_batch(gamification_seed)
-        .map_err(|e| format!("Failed to execute gamification seed data: {}", e))?;
-
-    // Execute quest updates migration
-    let quest_updates_migration = include_str!("../migrations/008_update_quests.sql");
-    conn.execute_batch(quest_updates_migration)
-        .map_err(|e| format!("Failed to execute quest updates migration: {}", e))?;
-
-    // Execute RPG character system migration
-    let rpg_character_migration = include_str!("../migrations/009_rpg_character_system.sql");
-    conn.execute_batch(rpg_character_migration)
-        .map_err(|e| format!("Failed to execute RPG character system migration: {}", e))?;
-
-    // Execute RPG dungeon world migration
-    let rpg_dungeon_migration = include_str!("../migrations/010_rpg_dungeon_world.sql");
-    conn.execute_batch(rpg_dungeon_migration)
-        .map_err(|e| format!("Failed to execute RPG dungeon world migration: {}", e))?;
-
-    // Execute RPG progress and combat migration
-    let rpg_progress_migration = include_str!("../migrations/011_rpg_progress_combat.sql");
-    conn.execute_batch(rpg_progress_migration)
-        .map_err(|e| format!("Failed to execute RPG progress and combat migration: {}", e))?;
-
-    // Execute RPG achievements migration
-    let rpg_achievements_migration = include_str!("../migrations/012_rpg_achievements.sql");
-    conn.execute_batch(rpg_achievements_migration)
-        .map_err(|e| format!("Failed to execute RPG achievements migration: {}", e))?;
-
-    // Execute multiple choice challenges migration
-    let multiple_choice_migration = include_str!("../migrations/013_convert_challenges_to_multiple_choice.sql");
-    conn.execute_batch(multiple_choice_migration)
-        .map_err(|e| format!("Failed to execute multiple choice migration: {}", e))?;
-
-    // Add multiple choice columns (safe to run multiple times)
-    safe_add_column(&conn, "ALTER TABLE dungeon_challenges ADD COLUMN choices TEXT")?;
-    safe_add_column(&conn, "ALTER TABLE dungeon_challenges ADD COLUMN correct_answer TEXT")?;
-
-    // Execute narrative dungeon system migration
-    let narrative_migration = include_str!("../migrations/014_narrative_dungeon_system.sql");
-    conn.execute_batch(narrative_migration)
-        .map_err(|e| format!("Failed to execute narrative dungeon migration: {}", e))?;
-
-    // Execute expanded equipment system migration (main tables)
-    let equipment_expansion = include_str!("../migrations/015_expand_equipment_system.sql");
-    conn.execute_batch(equipment_expansion)
-        .map_err(|e| format!("Failed to execute equipment expansion migration: {}", e))?;
-
-    // Add new equipment columns (safe to run multiple times)
-    safe_add_column(&conn, "ALTER TABLE character_equipment ADD COLUMN shield_id TEXT REFERENCES equipment_items(id)")?;
-    safe_add_column(&conn, "ALTER TABLE character_equipment ADD COLUMN helmet_id TEXT REFERENCES equipment_items(id)")?;
-    safe_add_column(&conn, "ALTER TABLE character_equipment ADD COLUMN chest_id TEXT REFERENCES equipment_items(id)")?;
-    safe_add_column(&conn, "ALTER TABLE character_equipment ADD COLUMN boots_id TEXT REFERENCES equipment_items(id)")?;
-    safe_add_column(&conn, "ALTER TABLE user_abilities ADD COLUMN ability_level INTEGER DEFAULT 1")?;
-
-    // Execute equipment slot fix migration
-    let equipment_slot_fix = include_str!("../migrations/016_fix_equipment_slots.sql");
-    conn.execute_batch(equipment_slot_fix)
-        .map_err(|e| format!("Failed to execute equipment slot fix migration: {}", e))?;
-
-    // Add charisma column (safe to run multiple times)
-    safe_add_column(&conn, "ALTER TABLE character_stats ADD COLUMN charisma INTEGER DEFAULT 1")?;
-
-    // Execute charisma stat migration
-    let charisma_migration = include_str!("../migrations/017_add_charisma_stat.sql");
-    conn.execute_batch(charisma_migration)
-        .map_err(|e| format!("Failed to execute charisma stat migration: {}", e))?;
-
-    // Execute active abilities system migration
-    let active_abilities_migration = include_str!("../migrations/018_active_abilities_system.sql");
-    conn.execute_batch(active_abilities_migration)
-        .map_err(|e| format!("Failed to execute active abilities migration: {}", e))?;
-
-    // Add shop system columns (safe to run multiple times)
-    safe_add_column(&conn, "ALTER TABLE character_stats ADD COLUMN current_gold INTEGER DEFAULT 100")?;
-    safe_add_column(&conn, "ALTER TABLE user_dungeon_progress ADD COLUMN in_town BOOLEAN DEFAULT TRUE")?;
-
-    // Execute shop system migration
-    let shop_migration = include_str!("../migrations/019_shop_system.sql");
-    conn.execute_batch(shop_migration)
-        .map_err(|e| format!("Failed to execute shop system migration: {}", e))?;
-
-    // Execute RPG dungeon seed data
-    let rpg_dungeon_seed = include_str!("../../course-framework-output/database/rpg-dungeon-seed.sql");
-    conn.execute_batch(rpg_dungeon_seed)
-        .map_err(|e| format!("Failed to execute RPG dungeon seed data: {}", e))?;
-
-    // Add all equipment items to shop inventory (now that equipment_items are seeded)
-    let shop_equipment_seed = r#"
-        -- Add all equipment items to shop inventory with appropriate level requirements
-        INSERT OR IGNORE INTO shop_inventory (item_type, item_id, available, required_level, stock_quantity)
-        SELECT 'equipment', id, TRUE, required_level, NULL
-        FROM equipment_items;
-    "#;
-    conn.execute_batch(shop_equipment_seed)
-        .map_err(|e| format!("Failed to add equipment to shop: {}", e))?;
-
-    // Execute multiple choice challenges seed data
-    let multiple_choice_seed = include_str!("../../course-framework-output/database/rpg-challenges-multiple-choice.sql");
-    conn.execute_batch(multiple_choice_seed)
-        .map_err(|e| format!("Failed to execute multiple choice seed data: {}", e))?;
-
-    // Note: narrative-level1-seed.sql is deprecated - using migrations 020-023 instead
-
-    // Execute Level 1 enemies migration
-    log::info!("Loading Level 1 enemies migration...");
-    let level1_enemies_migration = include_str!("../migrations/020_level1_enemies.sql");
-    conn.execute_batch(level1_enemies_migration)
-        .map_err(|e| {
-            log::error!("Level 1 enemies migration failed: {}", e);
-            format!("Failed to execute Level 1 enemies migration: {}", e)
-        })?;
-    log::info!("Level 1 enemies migration completed successfully");
-
-    // Execute Level 1 locations migration
-    log::info!("Loading Level 1 locations migration...");
-    let level1_locations_migration = include_str!("../migrations/021_level1_locations.sql");
-    conn.execute_batch(level1_locations_migration)
-        .map_err(|e| {
-            log::error!("Level 1 locations migration failed: {}", e);
-            format!("Failed to execute Level 1 locations migration: {}", e)
-        })?;
-    log::info!("Level 1 locations migration completed successfully");
-
-    // Execute Level 1 choices migration
-    log::info!("Loading Level 1 choices migration...");
-    let level1_choices_migration = include_str!("../migrations/022_level1_choices.sql");
-    conn.execute_batch(level1_choices_migration)
-        .map_err(|e| {
-            log::error!("Level 1 choices migration failed: {}", e);
-            format!("Failed to execute Level 1 choices migration: {}", e)
-        })?;
-    log::info!("Level 1 choices migration completed successfully");
-
-    // Execute Level 1 outcomes migration
-    log::info!("Loading Level 1 outcomes migration...");
-    let level1_outcomes_migration = include_str!("../migrations/023_level1_outcomes.sql");
-    conn.execute_batch(level1_outcomes_migration)
-        .map_err(|e| {
-            log::error!("Level 1 outcomes migration failed: {}", e);
-            format!("Failed to execute Level 1 outcomes migration: {}", e)
-        })?;
-    log::info!("Level 1 outcomes migration completed successfully");
-
-    // Execute shop refresh system migration
-    log::info!("Loading shop refresh system migration...");
-    let shop_refresh_migration = include_str!("../migrations/025_shop_refresh_system.sql");
-    conn.execute_batch(shop_refresh_migration)
-        .map_err(|e| {
-            log::error!("Shop refresh system migration failed: {}", e);
-            format!("Failed to execute shop refresh system migration: {}", e)
-        })?;
-    log::info!("Shop refresh system migration completed successfully");
-
-    // Execute remove duplicate choices migration
-    log::info!("Loading remove duplicate choices migration...");
-    let remove_duplicate_choices_migration = include_str!("../migrations/026_remove_duplicate_choices.sql");
-    conn.execute_batch(remove_duplicate_choices_migration)
-        .map_err(|e| {
-            log::error!("Remove duplicate choices migration failed: {}", e);
-            format!("Failed to execute remove duplicate choices migration: {}", e)
-        })?;
-    log::info!("Remove duplicate choices migration completed successfully");
-
-    // Execute unified achievement system migration
-    log::info!("Loading unified achievement system migration...");
-    let unified_achievement_migration = include_str!("../migrations/027_unified_achievement_system.sql");
-    conn.execute_batch(unified_achievement_migration)
-        .map_err(|e| {
-            log::error!("Unified achievement system migration failed: {}", e);
-            format!("Failed to execute unified achievement system migration: {}", e)
-        })?;
-    log::info!("Unified achievement system migration completed successfully");
-
-    // Execute achievement seed data
-    log::info!("Loading achievement seed data...");
-    let achievement_seed_data = include_str!("../migrations/028_achievement_seed_data.sql");
-    conn.execute_batch(achievement_seed_data)
-        .map_err(|e| {
-            log::error!("Achievement seed data failed: {}", e);
-            format!("Failed to execute achievement seed data: {}", e)
-        })?;
-    log::info!("Achievement seed data completed successfully");
-
-    // Execute achievement viewed tracking migration (idempotent)
-    log::info!("Loading achievement viewed tracking migration...");
-
-    // Try to add the viewed_at column - ignore error if column already exists
-    // Add viewed_at column - ignore only duplicate column errors
-    match conn.execute(
-        "ALTER TABLE user_achievement_progress ADD COLUMN viewed_at TEXT",
-        [],
-    ) {
-        Ok(_) => log::info!("Added viewed_at column to user_achievement_progress"),
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("duplicate column") || error_msg.contains("already exists") {
-                log::info!("viewed_at column already exists, skipping");
-            } else {
-                log::error!("Failed to add viewed_at column: {}", e);
-                return Err(format!("Failed to add viewed_at column: {}", e));
-            }
-        }
-    }
-
-    // Create index (IF NOT EXISTS handles idempotency)
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_user_achievement_viewed ON user_achievement_progress(user_id, viewed_at)",
-        [],
-    ).map_err(|e| {
-        log::error!("Failed to create achievement viewed index: {}", e);
-        format!("Failed to create achievement viewed index: {}", e)
-    })?;
+    let pool = create_pool(&db_path)?;
+    let mut conn = pool
+        .get()
+        .context("Failed to open database")?;
 
-    log::info!("Achievement viewed tracking migration completed successfully");
-
-    // Execute solution_viewed migration (if columns don't exist yet)
-    let solution_viewed_migration = r#"
-        -- Add solution_viewed columns if they don't exist
-        ALTER TABLE user_puzzle_progress ADD COLUMN solution_viewed BOOLEAN DEFAULT FALSE;
-        ALTER TABLE user_puzzle_progress ADD COLUMN solution_viewed_at TIMESTAMP;
-    "#;
-
-    // Try to execute migration - only ignore "duplicate column" errors
-    match conn.execute_batch(solution_viewed_migration) {
-        Ok(_) => log::info!("Solution viewed migration completed successfully"),
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("duplicate column") || error_msg.contains("already exists") {
-                log::info!("Solution viewed columns already exist, skipping migration");
-            } else {
-                log::error!("Solution viewed migration failed: {}", e);
-                return Err(format!("Failed to execute solution viewed migration: {}", e));
-            }
-        }
+    // Keep a copy of existing progress in case a migration goes wrong
+    if !is_first_launch && migrations::has_pending(&conn)? {
+        let backup_dir = backup::backup_dir(&get_app_data_dir(app)?)?;
+        backup::create_and_prune(&conn, &backup_dir, BackupKind::PreMigration, &SystemClock)
+            .context("Failed to back up database before migrating")?;
     }
 
-    // Execute dungeon level editor migration
-    log::info!("Loading dungeon level editor migration...");
-    let dungeon_levels_migration = include_str!("../migrations/030_dungeon_levels.sql");
-    conn.execute_batch(dungeon_levels_migration)
-        .map_err(|e| {
-            log::error!("Dungeon levels migration failed: {}", e);
-            format!("Failed to execute dungeon levels migration: {}", e)
-        })?;
-    log::info!("Dungeon levels migration completed successfully");
-
-    // Execute level sequencing migration
-    log::info!("Loading level sequencing migration...");
-    let level_sequencing_migration = include_str!("../migrations/031_level_sequencing.sql");
-    match conn.execute_batch(level_sequencing_migration) {
-        Ok(_) => log::info!("Level sequencing migration completed successfully"),
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("duplicate column") || error_msg.contains("already exists") {
-                log::info!("Level sequencing columns already exist, skipping migration");
-            } else {
-                log::error!("Level sequencing migration failed: {}", e);
-                return Err(format!("Failed to execute level sequencing migration: {}", e));
-            }
-        }
-    }
-
-    // Execute custom enemies migration
-    log::info!("Loading custom enemies migration...");
-    let custom_enemies_migration = include_str!("../migrations/032_custom_enemies.sql");
-    match conn.execute_batch(custom_enemies_migration) {
-        Ok(_) => log::info!("Custom enemies migration completed successfully"),
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("already exists") || error_msg.contains("duplicate") {
-                log::info!("Custom enemies table already exists, skipping migration");
-            } else {
-                log::error!("Custom enemies migration failed: {}", e);
-                return Err(format!("Failed to execute custom enemies migration: {}", e));
-            }
-        }
+    // Apply pending migrations and seeds (see migrations.rs)
+    let report = migrations::run_migrations(&mut conn).map_err(|e| {
+        log::error!("Database migration failed: {}", e);
+        e
+    })?;
+    if report.baselined > 0 {
+        log::info!("Recorded {} existing migrations from before migration tracking", report.baselined);
     }
-
-    // Execute MCQ questions migration
-    log::info!("Loading MCQ questions migration...");
-    let mcq_migration = include_str!("../migrations/033_mcq_questions.sql");
-    match conn.execute_batch(mcq_migration) {
-        Ok(_) => log::info!("MCQ questions migration completed successfully"),
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("already exists") || error_msg.contains("duplicate") {
-                log::info!("MCQ questions table already exists, skipping migration");
-            } else {
-                log::error!("MCQ questions migration failed: {}", e);
-                return Err(format!("Failed to execute MCQ questions migration: {}", e));
+    log::info!(
+        "Applied {} migrations and {} seeds, schema at {}",
+        report.applied.len(),
+        report.seeded.len(),
+        migrations::current_version(&conn)?.unwrap_or_default()
+    );
+
+    // Bring unedited shipped content up to this release (see content_versions.rs).
+    // It runs in one transaction, so a failure leaves the content as it was
+    match content_versions::sync_content(&mut conn) {
+        Ok(content) => {
+            if let Some(version) = content.content_version {
+                log::info!(
+                    "Content version {}: {} rows updated, {} added",
+                    version,
+                    content.updated.len(),
+                    content.added.len()
+                );
             }
-        }
-    }
-
-    // Execute challenge history foreign key fix migration
-    log::info!("Loading challenge history FK fix migration...");
-    let challenge_history_fk_migration = include_str!("../migrations/034_fix_challenge_history_fk.sql");
-    match conn.execute_batch(challenge_history_fk_migration) {
-        Ok(_) => log::info!("Challenge history FK fix migration completed successfully"),
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("already exists") || error_msg.contains("duplicate") || error_msg.contains("no such table") {
-                log::info!("Challenge history FK fix already applied or not needed, skipping");
-            } else {
-                log::error!("Challenge history FK fix migration failed: {}", e);
-                return Err(format!("Failed to execute challenge history FK fix migration: {}", e));
+            for conflict in &content.conflicts {
+                log::warn!("Shipped content not applied over local changes: {}", conflict);
             }
         }
+        Err(e) => log::error!("Content sync failed, keeping existing content: {}", e),
     }
 
     // Auto-seed MCQ questions if none exist (for production builds)
@@ -435,18 +180,120 @@ pub fn initialize_database(app: &AppHandle) -> Result<(), String> {
     }
 
     log::info!("Database initialized successfully at {:?}", db_path);
-    Ok(())
+    Ok(pool)
 }
 
-/// Get a database connection
-pub fn get_connection(app: &AppHandle) -> Result<Connection, String> {
-    let db_path = get_db_path(app)?;
-    let conn = Connection::open(&db_path)
-        .map_err(|e| format!("Failed to open database connection: {}", e))?;
+/// Borrow a connection from the shared pool
+pub fn get_connection(app: &AppHandle) -> Result<DbConnection, AppError> {
+    let pool = app
+        .try_state::<DbPool>()
+        .ok_or_else(|| AppError::Database("Database has not been initialized".to_string()))?;
 
-    // Enable foreign key constraints
-    conn.execute("PRAGMA foreign_keys = ON", [])
-        .map_err(|e| format!("Failed to enable foreign keys: {}", e))?;
+    pool.get()
+        .context("Failed to get database connection")
+}
 
-    Ok(conn)
+// ============================================================================
+// Units of Work
+// ============================================================================
+
+/// Run `work` in one transaction: everything it writes is committed when it
+/// returns Ok and rolled back when it fails, so a multi-step gameplay command
+/// never leaves part of its changes behind. The write lock is taken up front,
+/// so no other command can change the rows `work` reads before it writes them.
+///
+/// `work` must do all its reads and writes through the connection it is given;
+/// another pooled connection would not see its uncommitted changes.
+pub fn run_unit_of_work<T>(
+    conn: &mut Connection,
+    work: impl FnOnce(&Connection) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .context("Failed to begin transaction")?;
+    // Dropping tx without committing rolls it back
+    let value = work(&tx)?;
+    tx.commit().context("Failed to commit transaction")?;
+    Ok(value)
+}
+
+/// Borrow a connection from the pool and run `work` on it as one unit of work
+pub fn unit_of_work<T>(
+    app: &AppHandle,
+    work: impl FnOnce(&Connection) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let mut conn = get_connection(app)?;
+    run_unit_of_work(&mut conn, work)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    const ITERATIONS: u32 = 500;
+    const QUERY: &str = "SELECT COUNT(*) FROM equipment_items WHERE required_level <= 5";
+
+    /// What get_connection did before the pool: open the file and configure it per call
+    fn open_unpooled(db_path: &Path) -> Connection {
+        let mut conn = Connection::open(db_path).unwrap();
+        configure_connection(&mut conn).unwrap();
+        conn
+    }
+
+    fn query(conn: &Connection) -> i64 {
+        conn.query_row(QUERY, [], |row| row.get(0)).unwrap()
+    }
+
+    fn time_per_call(mut f: impl FnMut()) -> f64 {
+        f();
+        let started = Instant::now();
+        for _ in 0..ITERATIONS {
+            f();
+        }
+        started.elapsed().as_secs_f64() * 1_000_000.0 / ITERATIONS as f64
+    }
+
+    /// Connection latency with and without the pool, for a single command and for a
+    /// command that calls two nested helpers which each fetch their own connection
+    /// (as execute_combat_turn used to). Both sides configure connections the same
+    /// way and acquire the same number of them; only where they come from differs.
+    /// Run with: cargo test --release pool_benchmark -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn pool_benchmark() {
+        let db_path = std::env::temp_dir().join(format!("code-tutor-bench-{}.db", uuid::Uuid::new_v4()));
+        let pool = create_pool(&db_path).unwrap();
+        migrations::run_migrations(&mut pool.get().unwrap()).unwrap();
+
+        let unpooled = time_per_call(|| {
+            query(&open_unpooled(&db_path));
+        });
+        let pooled = time_per_call(|| {
+            query(&pool.get().unwrap());
+        });
+        let unpooled_nested = time_per_call(|| {
+            let conn = open_unpooled(&db_path);
+            query(&conn);
+            query(&open_unpooled(&db_path));
+            query(&open_unpooled(&db_path));
+        });
+        let pooled_nested = time_per_call(|| {
+            let conn = pool.get().unwrap();
+            query(&conn);
+            query(&pool.get().unwrap());
+            query(&pool.get().unwrap());
+        });
+
+        println!("single query:   open per call {:>8.1} us, pooled {:>8.1} us ({:.1}x)", unpooled, pooled, unpooled / pooled);
+        println!("nested helpers: open per call {:>8.1} us, pooled {:>8.1} us ({:.1}x)", unpooled_nested, pooled_nested, unpooled_nested / pooled_nested);
+
+        drop(pool);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", db_path.display(), suffix));
+        }
+
+        assert!(pooled < unpooled);
+        assert!(pooled_nested < unpooled_nested);
+    }
 }
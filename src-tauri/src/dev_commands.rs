@@ -4,8 +4,14 @@
 use rusqlite::params;
 use tauri::{AppHandle, Manager};
 
-use crate::db::get_connection;
+use crate::clean_export;
+use crate::clock_commands::{clock_status, ClockStatus};
+use crate::db::{get_connection, unit_of_work};
+use crate::game::clock;
+use crate::game::ledger::{self, Account, Source};
+use crate::game::rewards;
 use crate::gamification_commands::add_currency;
+use crate::error::{AppError, ResultExt};
 
 // ============================================================================
 // CURRENCY COMMANDS
@@ -16,15 +22,16 @@ pub fn dev_add_gold(
     app: AppHandle,
     user_id: i64,
     amount: i64,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = get_connection(&app)?;
 
     // Update RPG dungeon gold only
-    conn.execute(
-        "UPDATE character_stats SET current_gold = current_gold + ? WHERE user_id = ?",
-        params![amount, user_id],
-    )
-    .map_err(|e| format!("Failed to add dungeon gold: {}", e))?;
+    let source = Source::new("dev_panel");
+    if amount >= 0 {
+        ledger::credit(&conn, user_id, Account::DungeonGold, amount, source)?;
+    } else {
+        ledger::debit_up_to(&conn, user_id, Account::DungeonGold, -amount, source)?;
+    }
 
     Ok(())
 }
@@ -34,7 +41,7 @@ pub fn dev_add_gems(
     app: AppHandle,
     user_id: i64,
     amount: i64,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     // Update gamification currency
     add_currency(
         app,
@@ -44,7 +51,7 @@ pub fn dev_add_gems(
         "dev_panel".to_string(),
         None,
     )
-    .map_err(|e| format!("Failed to add gems: {}", e))?;
+    .context("Failed to add gems")?;
 
     Ok(())
 }
@@ -54,14 +61,14 @@ pub fn dev_add_skill_points(
     app: AppHandle,
     user_id: i64,
     amount: i64,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = get_connection(&app)?;
 
     conn.execute(
         "UPDATE character_stats SET stat_points_available = stat_points_available + ? WHERE user_id = ?",
         params![amount, user_id],
     )
-    .map_err(|e| format!("Failed to add skill points: {}", e))?;
+    .context("Failed to add skill points")?;
 
     Ok(())
 }
@@ -75,7 +82,7 @@ pub fn dev_add_levels(
     app: AppHandle,
     user_id: i64,
     levels: i64,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = get_connection(&app)?;
 
     // Each level grants 1 stat point
@@ -88,7 +95,7 @@ pub fn dev_add_levels(
          WHERE user_id = ?",
         params![levels, stat_points, user_id],
     )
-    .map_err(|e| format!("Failed to add levels: {}", e))?;
+    .context("Failed to add levels")?;
 
     // Recalculate derived stats
     recalculate_stats_for_user(&conn, user_id)?;
@@ -101,32 +108,12 @@ pub fn dev_add_xp(
     app: AppHandle,
     user_id: i64,
     xp: i64,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = get_connection(&app)?;
 
-    // Get current level and XP
-    let (_current_level, _): (i64, i64) = conn.query_row(
-        "SELECT level, stat_points_available FROM character_stats WHERE user_id = ?",
-        params![user_id],
-        |row| Ok((row.get(0)?, row.get(1)?)),
-    )
-    .map_err(|e| format!("Failed to get current level: {}", e))?;
-
-    // Simple level calculation: 100 XP per level
-    let levels_gained = xp / 100;
-    let stat_points = levels_gained * 1;
-
-    if levels_gained > 0 {
-        conn.execute(
-            "UPDATE character_stats
-             SET level = level + ?,
-                 stat_points_available = stat_points_available + ?
-             WHERE user_id = ?",
-            params![levels_gained, stat_points, user_id],
-        )
-        .map_err(|e| format!("Failed to add levels: {}", e))?;
-
-        // Recalculate derived stats
+    // Level-ups follow the same rules as XP earned in play
+    let level_up = rewards::award_xp(&conn, user_id, xp, Source::new("dev_panel"))?;
+    if level_up.levels_gained > 0 {
         recalculate_stats_for_user(&conn, user_id)?;
     }
 
@@ -142,14 +129,14 @@ pub fn dev_add_charisma(
     app: AppHandle,
     user_id: i64,
     amount: i64,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = get_connection(&app)?;
 
     conn.execute(
         "UPDATE character_stats SET charisma = charisma + ? WHERE user_id = ?",
         params![amount, user_id],
     )
-    .map_err(|e| format!("Failed to add charisma: {}", e))?;
+    .context("Failed to add charisma")?;
 
     Ok(())
 }
@@ -164,20 +151,20 @@ pub fn dev_generate_loot(
     user_id: i64,
     tier: String,
     quantity: i64,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = get_connection(&app)?;
 
     // Get random equipment items of the specified tier
     let mut stmt = conn.prepare(
         "SELECT id FROM equipment_items WHERE tier = ? ORDER BY RANDOM() LIMIT ?"
     )
-    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    .context("Failed to prepare statement")?;
 
     let equipment_ids: Vec<String> = stmt
         .query_map(params![tier, quantity], |row| row.get(0))
-        .map_err(|e| format!("Failed to query equipment: {}", e))?
+        .context("Failed to query equipment")?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect equipment: {}", e))?;
+        .context("Failed to collect equipment")?;
 
     drop(stmt); // Drop statement before using conn again
 
@@ -189,7 +176,7 @@ pub fn dev_generate_loot(
             params![user_id, &equipment_id],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to check inventory: {}", e))?;
+        .context("Failed to check inventory")?;
 
         if exists {
             // Increment quantity
@@ -197,7 +184,7 @@ pub fn dev_generate_loot(
                 "UPDATE equipment_inventory SET quantity = quantity + 1 WHERE user_id = ? AND equipment_id = ?",
                 params![user_id, &equipment_id],
             )
-            .map_err(|e| format!("Failed to update inventory: {}", e))?;
+            .context("Failed to update inventory")?;
         } else {
             // Insert new item
             conn.execute(
@@ -205,7 +192,7 @@ pub fn dev_generate_loot(
                  VALUES (?, ?, 1, CURRENT_TIMESTAMP)",
                 params![user_id, &equipment_id],
             )
-            .map_err(|e| format!("Failed to insert inventory: {}", e))?;
+            .context("Failed to insert inventory")?;
         }
     }
 
@@ -220,7 +207,7 @@ pub fn dev_generate_loot(
 pub fn dev_reset_character(
     app: AppHandle,
     user_id: i64,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = get_connection(&app)?;
 
     // Reset character to level 1 with default stats
@@ -240,12 +227,12 @@ pub fn dev_reset_character(
              critical_chance = 0.05,
              dodge_chance = 0.05,
              stat_points_available = 0,
-             current_gold = 100,
              updated_at = CURRENT_TIMESTAMP
          WHERE user_id = ?",
         params![user_id],
     )
-    .map_err(|e| format!("Failed to reset character: {}", e))?;
+    .context("Failed to reset character")?;
+    ledger::set_balance(&conn, user_id, Account::DungeonGold, 100, Source::new("dev_reset"))?;
 
     Ok(())
 }
@@ -259,134 +246,32 @@ pub fn dev_reset_character(
 // ============================================================================
 
 #[tauri::command]
-pub fn dev_export_clean_database(app: AppHandle) -> Result<String, String> {
-    use std::fs;
-
+pub fn dev_export_clean_database(app: AppHandle) -> Result<String, AppError> {
     let conn = get_connection(&app)?;
 
-    // Tables that contain user-specific data (will be cleared)
-    let user_tables = vec![
-        "users",
-        "user_currency",
-        "currency_transactions",
-        "user_inventory",
-        "purchase_history",
-        "user_active_effects",
-        "user_quest_progress",
-        "user_dungeon_progress",
-        "dungeon_combat_log",
-        "dungeon_session",
-        "user_challenge_history",
-        "user_challenge_history_new",
-        "playground_projects",
-        "user_snippet_library",
-        "playground_likes",
-        "playground_comments",
-        "playground_sessions",
-        "user_playground_achievements",
-        "character_stats",
-        "character_equipment",
-        "user_abilities",
-        "user_boss_defeats",
-        "user_narrative_progress",
-        "skill_check_history",
-        "user_active_abilities",
-        "user_ability_cooldowns",
-        "user_dungeon_achievements",
-        "user_equipment_inventory",
-        "equipment_inventory",
-        "stat_point_history",
-        "user_consumable_inventory",
-        "shop_purchases",
-        "shop_refresh_state",
-        "shop_active_inventory",
-        "user_achievement_progress",
-        "user_achievement_stats",
-        "achievement_rewards_claimed",
-        "achievement_notifications",
-        "user_puzzle_progress",
-    ];
-
-    // Get current database path
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    let db_path = app_data_dir.join("code-tutor.db");
-
-    // Export path (in project src-tauri folder)
     // We'll use the user's Downloads folder for easier access
     let downloads_dir = app
         .path()
         .download_dir()
-        .map_err(|e| format!("Failed to get downloads directory: {}", e))?;
+        .context("Failed to get downloads directory")?;
     let export_path = downloads_dir.join("seed_database_clean.db");
 
-    // Copy the current database to export location
-    fs::copy(&db_path, &export_path)
-        .map_err(|e| format!("Failed to copy database: {}", e))?;
-
-    // Open the exported database and clean user data
-    let export_conn = rusqlite::Connection::open(&export_path)
-        .map_err(|e| format!("Failed to open export database: {}", e))?;
-
-    // Disable foreign keys temporarily to allow deletion
-    export_conn
-        .execute("PRAGMA foreign_keys = OFF", [])
-        .map_err(|e| format!("Failed to disable foreign keys: {}", e))?;
-
-    // Clear user data from each table
-    let mut cleared_tables = Vec::new();
-    for table in &user_tables {
-        match export_conn.execute(&format!("DELETE FROM {}", table), []) {
-            Ok(rows) => {
-                if rows > 0 {
-                    cleared_tables.push(format!("{}: {} rows", table, rows));
-                }
-            }
-            Err(_) => {
-                // Table might not exist, skip silently
-            }
-        }
-    }
-
-    // Re-enable foreign keys
-    export_conn
-        .execute("PRAGMA foreign_keys = ON", [])
-        .map_err(|e| format!("Failed to re-enable foreign keys: {}", e))?;
-
-    // Vacuum to reclaim space
-    export_conn
-        .execute("VACUUM", [])
-        .map_err(|e| format!("Failed to vacuum database: {}", e))?;
-
-    // Get content stats
-    let question_count: i64 = export_conn
-        .query_row("SELECT COUNT(*) FROM mcq_questions", [], |row| row.get(0))
-        .unwrap_or(0);
-    let enemy_count: i64 = export_conn
-        .query_row("SELECT COUNT(*) FROM custom_enemies", [], |row| row.get(0))
-        .unwrap_or(0);
-    let equipment_count: i64 = export_conn
-        .query_row("SELECT COUNT(*) FROM equipment_items", [], |row| row.get(0))
-        .unwrap_or(0);
-    let location_count: i64 = export_conn
-        .query_row("SELECT COUNT(*) FROM narrative_locations", [], |row| row.get(0))
-        .unwrap_or(0);
+    // The same export the content-admin CLI runs (see clean_export.rs)
+    let export = clean_export::export_clean_database(&conn, &export_path)?;
 
     Ok(format!(
-        "Clean database exported to: {}\n\nContent included:\n- MCQ Questions: {}\n- Custom Enemies: {}\n- Equipment Items: {}\n- Narrative Locations: {}\n\nCleared user data from {} tables.\n\nCopy this file to src-tauri/seed_database.db before building.",
+        "Clean database exported to: {}\n\nContent included:\n- MCQ Questions: {}\n- Custom Enemies: {}\n- Equipment Items: {}\n- Narrative Locations: {}\n\nCleared user data from {} tables.\n\nTo ship new levels, export them to src-tauri/content/levels.json and run content-admin build-seed.",
         export_path.display(),
-        question_count,
-        enemy_count,
-        equipment_count,
-        location_count,
-        cleared_tables.len()
+        export.question_count,
+        export.enemy_count,
+        export.equipment_count,
+        export.location_count,
+        export.cleared_tables.len()
     ))
 }
 
 #[tauri::command]
-pub fn dev_clear_inventory(app: AppHandle, user_id: i64) -> Result<(), String> {
+pub fn dev_clear_inventory(app: AppHandle, user_id: i64) -> Result<(), AppError> {
     let conn = get_connection(&app)?;
 
     // Clear equipment inventory
@@ -394,7 +279,7 @@ pub fn dev_clear_inventory(app: AppHandle, user_id: i64) -> Result<(), String> {
         "DELETE FROM user_equipment_inventory WHERE user_id = ?",
         params![user_id],
     )
-    .map_err(|e| format!("Failed to clear equipment inventory: {}", e))?;
+    .context("Failed to clear equipment inventory")?;
 
     // Also clear from equipment_inventory table if it exists
     let _ = conn.execute(
@@ -407,26 +292,26 @@ pub fn dev_clear_inventory(app: AppHandle, user_id: i64) -> Result<(), String> {
         "DELETE FROM user_consumable_inventory WHERE user_id = ?",
         params![user_id],
     )
-    .map_err(|e| format!("Failed to clear consumable inventory: {}", e))?;
+    .context("Failed to clear consumable inventory")?;
 
     // Clear equipped items
     conn.execute(
         "UPDATE character_equipment SET weapon_id = NULL, armor_id = NULL, accessory_id = NULL, shield_id = NULL, helmet_id = NULL, chest_id = NULL, boots_id = NULL WHERE user_id = ?",
         params![user_id],
     )
-    .map_err(|e| format!("Failed to clear equipped items: {}", e))?;
+    .context("Failed to clear equipped items")?;
 
     Ok(())
 }
 
-fn recalculate_stats_for_user(conn: &rusqlite::Connection, user_id: i64) -> Result<(), String> {
+fn recalculate_stats_for_user(conn: &rusqlite::Connection, user_id: i64) -> Result<(), AppError> {
     // Get current stats
     let (level, strength, intelligence, dexterity): (i64, i64, i64, i64) = conn.query_row(
         "SELECT level, strength, intelligence, dexterity FROM character_stats WHERE user_id = ?",
         params![user_id],
         |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
     )
-    .map_err(|e| format!("Failed to get stats: {}", e))?;
+    .context("Failed to get stats")?;
 
     // Calculate derived stats
     let max_health = 100 + (level * 10) + (strength * 5);
@@ -461,7 +346,34 @@ fn recalculate_stats_for_user(conn: &rusqlite::Connection, user_id: i64) -> Resu
             user_id
         ],
     )
-    .map_err(|e| format!("Failed to update derived stats: {}", e))?;
+    .context("Failed to update derived stats")?;
 
     Ok(())
 }
+
+// ============================================================================
+// TIME TRAVEL
+// ============================================================================
+
+fn set_time_travel(app: &AppHandle, travel: impl FnOnce(i64) -> i64) -> Result<ClockStatus, AppError> {
+    if !cfg!(debug_assertions) {
+        return Err(AppError::Validation("Time travel is only available in development builds".to_string()));
+    }
+    unit_of_work(app, |conn| {
+        let mut settings = clock::load_settings(conn)?;
+        settings.time_travel_seconds = travel(settings.time_travel_seconds);
+        clock::save_settings(conn, &settings)?;
+        clock_status(conn)
+    })
+}
+
+/// Move the app clock forwards (or, with negative hours, backwards) to test streaks and refreshes
+#[tauri::command]
+pub fn dev_time_travel(app: AppHandle, hours: i64) -> Result<ClockStatus, AppError> {
+    set_time_travel(&app, |seconds| seconds + hours * 3600)
+}
+
+#[tauri::command]
+pub fn dev_reset_time_travel(app: AppHandle) -> Result<ClockStatus, AppError> {
+    set_time_travel(&app, |_| 0)
+}
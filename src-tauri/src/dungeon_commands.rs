@@ -1,9 +1,12 @@
-use rusqlite::{params, Result as SqlResult, Row};
+use rusqlite::{params, Connection, Result as SqlResult, Row};
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 use rand::Rng;
 
-use crate::db::get_connection;
+use crate::db::{get_connection, unit_of_work};
+use crate::error::{AppError, ResultExt};
+use crate::game::enemies::load_enemy;
+pub use crate::game::enemies::{BossEnemy, EnemyType};
 
 // ============================================================================
 // DUNGEON FLOORS
@@ -43,7 +46,7 @@ impl DungeonFloor {
 }
 
 #[tauri::command]
-pub fn get_dungeon_floor(app: AppHandle, floor_number: i64) -> Result<DungeonFloor, String> {
+pub fn get_dungeon_floor(app: AppHandle, floor_number: i64) -> Result<DungeonFloor, AppError> {
     let conn = get_connection(&app)?;
 
     conn.query_row(
@@ -55,11 +58,11 @@ pub fn get_dungeon_floor(app: AppHandle, floor_number: i64) -> Result<DungeonFlo
         params![floor_number],
         DungeonFloor::from_row,
     )
-    .map_err(|e| format!("Failed to get dungeon floor: {}", e))
+    .context("Failed to get dungeon floor")
 }
 
 #[tauri::command]
-pub fn get_available_floors(app: AppHandle) -> Result<Vec<DungeonFloor>, String> {
+pub fn get_available_floors(app: AppHandle) -> Result<Vec<DungeonFloor>, AppError> {
     let conn = get_connection(&app)?;
 
     let mut stmt = conn
@@ -70,13 +73,13 @@ pub fn get_available_floors(app: AppHandle) -> Result<Vec<DungeonFloor>, String>
              FROM dungeon_floors
              ORDER BY floor_number",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let floors = stmt
         .query_map([], DungeonFloor::from_row)
-        .map_err(|e| format!("Failed to query floors: {}", e))?
+        .context("Failed to query floors")?
         .collect::<SqlResult<Vec<DungeonFloor>>>()
-        .map_err(|e| format!("Failed to collect floors: {}", e))?;
+        .context("Failed to collect floors")?;
 
     Ok(floors)
 }
@@ -131,9 +134,11 @@ impl UserDungeonProgress {
 }
 
 #[tauri::command]
-pub fn get_user_dungeon_progress(app: AppHandle, user_id: i64) -> Result<UserDungeonProgress, String> {
-    let conn = get_connection(&app)?;
+pub fn get_user_dungeon_progress(app: AppHandle, user_id: i64) -> Result<UserDungeonProgress, AppError> {
+    unit_of_work(&app, |conn| load_dungeon_progress(conn, user_id))
+}
 
+fn load_dungeon_progress(conn: &Connection, user_id: i64) -> Result<UserDungeonProgress, AppError> {
     // Initialize dungeon progress if doesn't exist (starting at floor 1)
     conn.execute(
         "INSERT OR IGNORE INTO user_dungeon_progress (
@@ -144,7 +149,7 @@ pub fn get_user_dungeon_progress(app: AppHandle, user_id: i64) -> Result<UserDun
         ) VALUES (?, 1, 1, 0, 'entrance', 0, 0, 0, 0, 0, 0, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
         params![user_id],
     )
-    .map_err(|e| format!("Failed to initialize dungeon progress: {}", e))?;
+    .context("Failed to initialize dungeon progress")?;
 
     conn.query_row(
         "SELECT user_id, current_floor, deepest_floor_reached, in_combat, current_enemy_id,
@@ -157,7 +162,7 @@ pub fn get_user_dungeon_progress(app: AppHandle, user_id: i64) -> Result<UserDun
         params![user_id],
         UserDungeonProgress::from_row,
     )
-    .map_err(|e| format!("Failed to get dungeon progress: {}", e))
+    .context("Failed to get dungeon progress")
 }
 
 #[tauri::command]
@@ -165,9 +170,11 @@ pub fn update_dungeon_floor(
     app: AppHandle,
     user_id: i64,
     floor_number: i64,
-) -> Result<UserDungeonProgress, String> {
-    let conn = get_connection(&app)?;
+) -> Result<UserDungeonProgress, AppError> {
+    unit_of_work(&app, |conn| move_to_floor(conn, user_id, floor_number))
+}
 
+fn move_to_floor(conn: &Connection, user_id: i64, floor_number: i64) -> Result<UserDungeonProgress, AppError> {
     conn.execute(
         "UPDATE user_dungeon_progress
          SET current_floor = ?,
@@ -176,97 +183,17 @@ pub fn update_dungeon_floor(
          WHERE user_id = ?",
         params![floor_number, floor_number, user_id],
     )
-    .map_err(|e| format!("Failed to update floor: {}", e))?;
+    .context("Failed to update floor")?;
 
-    get_user_dungeon_progress(app, user_id)
+    load_dungeon_progress(conn, user_id)
 }
 
 // ============================================================================
 // ENEMIES
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct EnemyType {
-    pub id: String,
-    pub name: String,
-    pub description: String,
-    pub base_health: i64,
-    pub base_damage: i64,
-    pub base_defense: i64,
-    pub behavior_type: String,
-    pub gold_drop_min: i64,
-    pub gold_drop_max: i64,
-    pub xp_reward: i64,
-    pub loot_table: Option<String>,
-    pub icon: String,
-    pub ascii_art: Option<String>,
-    pub created_at: String,
-}
-
-impl EnemyType {
-    fn from_row(row: &Row) -> SqlResult<Self> {
-        Ok(EnemyType {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            base_health: row.get(3)?,
-            base_damage: row.get(4)?,
-            base_defense: row.get(5)?,
-            behavior_type: row.get(6)?,
-            gold_drop_min: row.get(7)?,
-            gold_drop_max: row.get(8)?,
-            xp_reward: row.get(9)?,
-            loot_table: row.get(10)?,
-            icon: row.get(11)?,
-            ascii_art: row.get(12)?,
-            created_at: row.get(13)?,
-        })
-    }
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BossEnemy {
-    pub id: String,
-    pub name: String,
-    pub description: String,
-    pub floor_number: i64,
-    pub health: i64,
-    pub damage: i64,
-    pub defense: i64,
-    pub abilities: String,
-    pub phases: Option<String>,
-    pub gold_reward: i64,
-    pub xp_reward: i64,
-    pub guaranteed_loot: Option<String>,
-    pub icon: String,
-    pub ascii_art: Option<String>,
-    pub created_at: String,
-}
-
-impl BossEnemy {
-    fn from_row(row: &Row) -> SqlResult<Self> {
-        Ok(BossEnemy {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            floor_number: row.get(3)?,
-            health: row.get(4)?,
-            damage: row.get(5)?,
-            defense: row.get(6)?,
-            abilities: row.get(7)?,
-            phases: row.get(8)?,
-            gold_reward: row.get(9)?,
-            xp_reward: row.get(10)?,
-            guaranteed_loot: row.get(11)?,
-            icon: row.get(12)?,
-            ascii_art: row.get(13)?,
-            created_at: row.get(14)?,
-        })
-    }
-}
-
 #[tauri::command]
-pub fn get_random_enemy_for_floor(app: AppHandle, floor_number: i64) -> Result<EnemyType, String> {
+pub fn get_random_enemy_for_floor(app: AppHandle, floor_number: i64) -> Result<EnemyType, AppError> {
     let conn = get_connection(&app)?;
 
     // Get all enemies for this floor with their spawn weights
@@ -279,7 +206,7 @@ pub fn get_random_enemy_for_floor(app: AppHandle, floor_number: i64) -> Result<E
              INNER JOIN floor_enemies fe ON e.id = fe.enemy_id
              WHERE fe.floor_number = ?",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let enemies: Vec<(EnemyType, i64)> = stmt
         .query_map(params![floor_number], |row| {
@@ -303,12 +230,12 @@ pub fn get_random_enemy_for_floor(app: AppHandle, floor_number: i64) -> Result<E
                 row.get::<_, i64>(14)?, // spawn_weight
             ))
         })
-        .map_err(|e| format!("Failed to query enemies: {}", e))?
+        .context("Failed to query enemies")?
         .collect::<SqlResult<Vec<(EnemyType, i64)>>>()
-        .map_err(|e| format!("Failed to collect enemies: {}", e))?;
+        .context("Failed to collect enemies")?;
 
     if enemies.is_empty() {
-        return Err("No enemies found for this floor".to_string());
+        return Err(AppError::NotFound("No enemies found for this floor".to_string()));
     }
 
     // Calculate total weight
@@ -330,7 +257,7 @@ pub fn get_random_enemy_for_floor(app: AppHandle, floor_number: i64) -> Result<E
 }
 
 #[tauri::command]
-pub fn get_boss_for_floor(app: AppHandle, floor_number: i64) -> Result<BossEnemy, String> {
+pub fn get_boss_for_floor(app: AppHandle, floor_number: i64) -> Result<BossEnemy, AppError> {
     let conn = get_connection(&app)?;
 
     conn.query_row(
@@ -341,22 +268,13 @@ pub fn get_boss_for_floor(app: AppHandle, floor_number: i64) -> Result<BossEnemy
         params![floor_number],
         BossEnemy::from_row,
     )
-    .map_err(|e| format!("Failed to get boss: {}", e))
+    .context("Failed to get boss")
 }
 
 #[tauri::command]
-pub fn get_enemy_by_id(app: AppHandle, enemy_id: String) -> Result<EnemyType, String> {
+pub fn get_enemy_by_id(app: AppHandle, enemy_id: String) -> Result<EnemyType, AppError> {
     let conn = get_connection(&app)?;
-
-    conn.query_row(
-        "SELECT id, name, description, base_health, base_damage, base_defense, behavior_type,
-                gold_drop_min, gold_drop_max, xp_reward, loot_table, icon, ascii_art, created_at
-         FROM enemy_types
-         WHERE id = ?",
-        params![enemy_id],
-        EnemyType::from_row,
-    )
-    .map_err(|e| format!("Failed to get enemy by id: {}", e))
+    load_enemy(&conn, &enemy_id)
 }
 
 // ============================================================================
@@ -399,7 +317,7 @@ pub fn get_random_encounter(
     app: AppHandle,
     floor_number: i64,
     encounter_type: String,
-) -> Result<DungeonEncounter, String> {
+) -> Result<DungeonEncounter, AppError> {
     let conn = get_connection(&app)?;
 
     let mut stmt = conn
@@ -411,10 +329,10 @@ pub fn get_random_encounter(
              ORDER BY RANDOM()
              LIMIT 1",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     stmt.query_row(params![floor_number, encounter_type], DungeonEncounter::from_row)
-        .map_err(|e| format!("Failed to get encounter: {}", e))
+        .context("Failed to get encounter")
 }
 
 // ============================================================================
@@ -471,7 +389,7 @@ pub fn get_challenge_for_action(
     floor_number: i64,
     difficulty: Option<String>,
     user_id: Option<i64>, // Optional for backwards compatibility
-) -> Result<DungeonChallenge, String> {
+) -> Result<DungeonChallenge, AppError> {
     // Map floor number to difficulty if not specified
     let target_difficulty = difficulty.unwrap_or_else(|| {
         if floor_number <= 2 {
@@ -509,7 +427,7 @@ pub fn get_challenge_for_action(
     // If no questions found, try to import from dungeon_challenges first
     let mcq = match mcq_result {
         Ok(q) => q,
-        Err(e) if e.contains("No questions available") => {
+        Err(e @ AppError::NotFound(_)) => {
             eprintln!("No MCQ questions found, attempting to import from dungeon_challenges...");
             match crate::mcq_commands::import_dungeon_challenges_to_mcq(app.clone()) {
                 Ok(count) => {
@@ -533,7 +451,7 @@ pub fn get_challenge_for_action(
                     }
                 }
                 Err(import_err) => {
-                    return Err(format!("No questions available and import failed: {}. Please add questions using the Question Manager in the Dungeon Editor. Original error: {}", import_err, e));
+                    return Err(AppError::NotFound(format!("No questions available and import failed: {}. Please add questions using the Question Manager in the Dungeon Editor. Original error: {}", import_err, e)));
                 }
             }
         }
@@ -542,7 +460,7 @@ pub fn get_challenge_for_action(
 
     // Convert MCQ format to DungeonChallenge format for combat compatibility
     let options: Vec<String> = serde_json::from_str(&mcq.options)
-        .map_err(|e| format!("Failed to parse MCQ options: {}", e))?;
+        .context("Failed to parse MCQ options")?;
 
     // Convert options to the old format with letter prefixes: "A) text", "B) text", etc.
     let letters = ["A", "B", "C", "D"];
@@ -568,7 +486,7 @@ pub fn get_challenge_for_action(
         solution: Some(mcq.explanation.unwrap_or_default()),
         test_cases: Some(String::new()),
         choices: Some(serde_json::to_string(&formatted_choices)
-            .map_err(|e| format!("Failed to serialize choices: {}", e))?),
+            .context("Failed to serialize choices")?),
         correct_answer: Some(correct_letter),
         required_language: Some(mcq.language),
         min_floor: 1,
@@ -586,16 +504,24 @@ pub fn record_challenge_attempt(
     challenge_id: String,
     success: bool,
     time_taken_seconds: i64,
-) -> Result<(), String> {
-    let conn = get_connection(&app)?;
+) -> Result<(), AppError> {
+    unit_of_work(&app, |conn| record_attempt(conn, user_id, challenge_id, success, time_taken_seconds))
+}
 
+fn record_attempt(
+    conn: &Connection,
+    user_id: i64,
+    challenge_id: String,
+    success: bool,
+    time_taken_seconds: i64,
+) -> Result<(), AppError> {
     // Record in user challenge history
     conn.execute(
         "INSERT INTO user_challenge_history (user_id, challenge_id, success, time_taken_seconds)
          VALUES (?, ?, ?, ?)",
         params![user_id, challenge_id, success, time_taken_seconds],
     )
-    .map_err(|e| format!("Failed to record challenge attempt: {}", e))?;
+    .context("Failed to record challenge attempt")?;
 
     // Update challenge stats
     conn.execute(
@@ -604,7 +530,7 @@ pub fn record_challenge_attempt(
          WHERE id = ?",
         params![challenge_id],
     )
-    .map_err(|e| format!("Failed to update challenge stats: {}", e))?;
+    .context("Failed to update challenge stats")?;
 
     Ok(())
 }
@@ -2,6 +2,7 @@ use rusqlite::{params, Connection, Result as SqlResult};
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 use crate::db;
+use crate::error::AppError;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +18,9 @@ pub struct DungeonLevelMetadata {
     pub tags: Option<Vec<String>>,
     pub created_at: String,
     pub updated_at: String,
+    /// Position in the campaign; saving without one keeps the stored order
+    #[serde(default)]
+    pub sequence_order: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -69,12 +73,14 @@ pub struct LevelListItem {
 pub fn save_dungeon_level(
     app: AppHandle,
     level: DungeonLevel,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
+    save_level(&conn, &level)
+}
 
-    // Start transaction
-    conn.execute("BEGIN TRANSACTION", [])
-        .map_err(|e| e.to_string())?;
+/// Insert or replace a level with its nodes and edges in one transaction
+pub(crate) fn save_level(conn: &Connection, level: &DungeonLevel) -> Result<(), AppError> {
+    let tx = conn.unchecked_transaction()?;
 
     // Insert or update metadata
     let tags_json = level
@@ -83,11 +89,11 @@ pub fn save_dungeon_level(
         .as_ref()
         .map(|t| serde_json::to_string(t).unwrap_or_default());
 
-    let result = conn.execute(
+    tx.execute(
         "INSERT INTO dungeon_levels
         (id, name, description, recommended_level, difficulty, estimated_duration,
-         is_published, version, tags, created_at, updated_at)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+         is_published, version, tags, created_at, updated_at, sequence_order)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
         ON CONFLICT(id) DO UPDATE SET
             name = excluded.name,
             description = excluded.description,
@@ -97,7 +103,8 @@ pub fn save_dungeon_level(
             is_published = excluded.is_published,
             version = excluded.version,
             tags = excluded.tags,
-            updated_at = excluded.updated_at",
+            updated_at = excluded.updated_at,
+            sequence_order = COALESCE(excluded.sequence_order, sequence_order)",
         params![
             level.metadata.id,
             level.metadata.name,
@@ -110,41 +117,26 @@ pub fn save_dungeon_level(
             tags_json,
             level.metadata.created_at,
             level.metadata.updated_at,
+            level.metadata.sequence_order,
         ],
-    );
-
-    if let Err(e) = result {
-        conn.execute("ROLLBACK", []).ok();
-        return Err(e.to_string());
-    }
+    )?;
 
     // Delete existing nodes and edges
-    conn.execute(
+    tx.execute(
         "DELETE FROM dungeon_level_nodes WHERE level_id = ?1",
         params![level.metadata.id],
-    )
-    .map_err(|e| {
-        conn.execute("ROLLBACK", []).ok();
-        e.to_string()
-    })?;
+    )?;
 
-    conn.execute(
+    tx.execute(
         "DELETE FROM dungeon_level_edges WHERE level_id = ?1",
         params![level.metadata.id],
-    )
-    .map_err(|e| {
-        conn.execute("ROLLBACK", []).ok();
-        e.to_string()
-    })?;
+    )?;
 
     // Insert nodes
     for node in &level.nodes {
-        let node_data_json = serde_json::to_string(&node.data).map_err(|e| {
-            conn.execute("ROLLBACK", []).ok();
-            e.to_string()
-        })?;
+        let node_data_json = serde_json::to_string(&node.data)?;
 
-        conn.execute(
+        tx.execute(
             "INSERT INTO dungeon_level_nodes
             (id, level_id, node_data, position_x, position_y, node_type)
             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
@@ -156,16 +148,12 @@ pub fn save_dungeon_level(
                 node.position.y,
                 node.node_type,
             ],
-        )
-        .map_err(|e| {
-            conn.execute("ROLLBACK", []).ok();
-            e.to_string()
-        })?;
+        )?;
     }
 
     // Insert edges
     for edge in &level.edges {
-        conn.execute(
+        tx.execute(
             "INSERT INTO dungeon_level_edges
             (id, level_id, source_node_id, target_node_id, source_handle, target_handle)
             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
@@ -177,15 +165,10 @@ pub fn save_dungeon_level(
                 edge.source_handle,
                 edge.target_handle,
             ],
-        )
-        .map_err(|e| {
-            conn.execute("ROLLBACK", []).ok();
-            e.to_string()
-        })?;
+        )?;
     }
 
-    // Commit transaction
-    conn.execute("COMMIT", []).map_err(|e| e.to_string())?;
+    tx.commit()?;
 
     Ok(())
 }
@@ -195,17 +178,22 @@ pub fn save_dungeon_level(
 pub fn load_dungeon_level(
     app: AppHandle,
     level_id: String,
-) -> Result<DungeonLevel, String> {
+) -> Result<DungeonLevel, AppError> {
     let conn = db::get_connection(&app)?;
+    load_level(&conn, &level_id)
+}
+
+/// A level with its nodes and edges
+pub(crate) fn load_level(conn: &Connection, level_id: &str) -> Result<DungeonLevel, AppError> {
 
     // Load metadata
     let mut stmt = conn
         .prepare(
             "SELECT id, name, description, recommended_level, difficulty,
-             estimated_duration, is_published, version, tags, created_at, updated_at
+             estimated_duration, is_published, version, tags, created_at, updated_at, sequence_order
              FROM dungeon_levels WHERE id = ?1",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let metadata = stmt
         .query_row(params![level_id], |row| {
@@ -224,9 +212,10 @@ pub fn load_dungeon_level(
                 tags,
                 created_at: row.get(9)?,
                 updated_at: row.get(10)?,
+                sequence_order: row.get(11)?,
             })
         })
-        .map_err(|e| e.to_string())?;
+        ?;
 
     // Load nodes
     let mut stmt = conn
@@ -234,7 +223,7 @@ pub fn load_dungeon_level(
             "SELECT id, node_data, position_x, position_y, node_type
              FROM dungeon_level_nodes WHERE level_id = ?1",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let nodes = stmt
         .query_map(params![level_id], |row| {
@@ -252,9 +241,9 @@ pub fn load_dungeon_level(
                 },
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     // Load edges
     let mut stmt = conn
@@ -262,7 +251,7 @@ pub fn load_dungeon_level(
             "SELECT id, source_node_id, target_node_id, source_handle, target_handle
              FROM dungeon_level_edges WHERE level_id = ?1",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let edges = stmt
         .query_map(params![level_id], |row| {
@@ -274,9 +263,9 @@ pub fn load_dungeon_level(
                 target_handle: row.get(4)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(DungeonLevel {
         metadata,
@@ -287,8 +276,13 @@ pub fn load_dungeon_level(
 
 // Get list of all dungeon levels
 #[tauri::command]
-pub fn list_dungeon_levels(app: AppHandle) -> Result<Vec<LevelListItem>, String> {
+pub fn list_dungeon_levels(app: AppHandle) -> Result<Vec<LevelListItem>, AppError> {
     let conn = db::get_connection(&app)?;
+    list_levels(&conn)
+}
+
+/// Every level, most recently updated first
+pub(crate) fn list_levels(conn: &Connection) -> Result<Vec<LevelListItem>, AppError> {
 
     let mut stmt = conn
         .prepare(
@@ -296,7 +290,7 @@ pub fn list_dungeon_levels(app: AppHandle) -> Result<Vec<LevelListItem>, String>
              FROM dungeon_levels
              ORDER BY updated_at DESC",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let levels = stmt
         .query_map([], |row| {
@@ -310,9 +304,9 @@ pub fn list_dungeon_levels(app: AppHandle) -> Result<Vec<LevelListItem>, String>
                 updated_at: row.get(6)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(levels)
 }
@@ -322,14 +316,14 @@ pub fn list_dungeon_levels(app: AppHandle) -> Result<Vec<LevelListItem>, String>
 pub fn delete_dungeon_level(
     app: AppHandle,
     level_id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
         "DELETE FROM dungeon_levels WHERE id = ?1",
         params![level_id],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(())
 }
@@ -340,7 +334,7 @@ pub fn duplicate_dungeon_level(
     app: AppHandle,
     level_id: String,
     new_name: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     // Load existing level
     let existing_level = load_dungeon_level(app.clone(), level_id)?;
 
@@ -361,6 +355,7 @@ pub fn duplicate_dungeon_level(
             tags: existing_level.metadata.tags,
             created_at: now.clone(),
             updated_at: now,
+            sequence_order: None,
         },
         nodes: existing_level.nodes,
         edges: existing_level.edges,
@@ -377,7 +372,7 @@ pub fn duplicate_dungeon_level(
 pub fn update_level_sequence(
     app: AppHandle,
     level_sequences: Vec<(String, i32)>, // Vec of (level_id, sequence_order)
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     for (level_id, sequence_order) in level_sequences {
@@ -385,7 +380,7 @@ pub fn update_level_sequence(
             "UPDATE dungeon_levels SET sequence_order = ?1 WHERE id = ?2",
             params![sequence_order, level_id],
         )
-        .map_err(|e| e.to_string())?;
+        ?;
     }
 
     Ok(())
@@ -393,7 +388,7 @@ pub fn update_level_sequence(
 
 // Get levels in sequence order
 #[tauri::command]
-pub fn get_levels_in_sequence(app: AppHandle) -> Result<Vec<LevelListItem>, String> {
+pub fn get_levels_in_sequence(app: AppHandle) -> Result<Vec<LevelListItem>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn
@@ -403,7 +398,7 @@ pub fn get_levels_in_sequence(app: AppHandle) -> Result<Vec<LevelListItem>, Stri
              WHERE sequence_order IS NOT NULL
              ORDER BY sequence_order ASC",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let levels = stmt
         .query_map([], |row| {
@@ -417,9 +412,9 @@ pub fn get_levels_in_sequence(app: AppHandle) -> Result<Vec<LevelListItem>, Stri
                 updated_at: row.get(6)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(levels)
 }
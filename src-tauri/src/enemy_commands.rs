@@ -1,7 +1,9 @@
 use rusqlite::{params, Connection, Result as SqlResult};
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
+use crate::content_versions;
 use crate::db;
+use crate::error::AppError;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -34,7 +36,7 @@ pub struct EnemyListItem {
 
 // Save or update a custom enemy
 #[tauri::command]
-pub fn save_custom_enemy(app: AppHandle, enemy: CustomEnemy) -> Result<(), String> {
+pub fn save_custom_enemy(app: AppHandle, enemy: CustomEnemy) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     // Check if enemy exists
@@ -70,7 +72,7 @@ pub fn save_custom_enemy(app: AppHandle, enemy: CustomEnemy) -> Result<(), Strin
                 enemy.attacks,
             ],
         )
-        .map_err(|e| e.to_string())?;
+        ?;
     } else {
         // Insert new enemy
         conn.execute(
@@ -92,15 +94,17 @@ pub fn save_custom_enemy(app: AppHandle, enemy: CustomEnemy) -> Result<(), Strin
                 enemy.attacks,
             ],
         )
-        .map_err(|e| e.to_string())?;
+        ?;
     }
 
+    content_versions::mark_user_modified(&conn, "custom_enemies", &enemy.id)?;
+
     Ok(())
 }
 
 // Load a single custom enemy
 #[tauri::command]
-pub fn load_custom_enemy(app: AppHandle, enemy_id: String) -> Result<CustomEnemy, String> {
+pub fn load_custom_enemy(app: AppHandle, enemy_id: String) -> Result<CustomEnemy, AppError> {
     let conn = db::get_connection(&app)?;
 
     let enemy = conn
@@ -127,14 +131,14 @@ pub fn load_custom_enemy(app: AppHandle, enemy_id: String) -> Result<CustomEnemy
                 })
             },
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(enemy)
 }
 
 // List all custom enemies
 #[tauri::command]
-pub fn list_custom_enemies(app: AppHandle, enemy_type_filter: Option<String>) -> Result<Vec<EnemyListItem>, String> {
+pub fn list_custom_enemies(app: AppHandle, enemy_type_filter: Option<String>) -> Result<Vec<EnemyListItem>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let query = if let Some(filter) = enemy_type_filter {
@@ -151,7 +155,7 @@ pub fn list_custom_enemies(app: AppHandle, enemy_type_filter: Option<String>) ->
          ORDER BY level ASC, name ASC".to_string()
     };
 
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&query)?;
 
     let enemies = stmt
         .query_map([], |row| {
@@ -164,23 +168,23 @@ pub fn list_custom_enemies(app: AppHandle, enemy_type_filter: Option<String>) ->
                 image_path: row.get(5)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(enemies)
 }
 
 // Delete a custom enemy
 #[tauri::command]
-pub fn delete_custom_enemy(app: AppHandle, enemy_id: String) -> Result<(), String> {
+pub fn delete_custom_enemy(app: AppHandle, enemy_id: String) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
         "DELETE FROM custom_enemies WHERE id = ?1",
         params![enemy_id],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(())
 }
@@ -191,7 +195,7 @@ pub fn duplicate_custom_enemy(
     app: AppHandle,
     enemy_id: String,
     new_name: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let existing_enemy = load_custom_enemy(app.clone(), enemy_id)?;
 
     let new_id = uuid::Uuid::new_v4().to_string();
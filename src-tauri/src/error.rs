@@ -0,0 +1,138 @@
+use serde::Serialize;
+use std::fmt;
+
+// ============================================================================
+// Application Errors
+// ============================================================================
+//
+// Every command returns AppError. It reaches the frontend as
+// { "code": "INSUFFICIENT_MANA", "message": "Not enough mana" }, so the UI can
+// branch on a stable code instead of matching message text. The message is
+// meant to be shown to the user as-is.
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "code", content = "message", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AppError {
+    /// A requested record or resource doesn't exist
+    NotFound(String),
+    /// Not enough gold or gems for a purchase
+    InsufficientFunds(String),
+    /// Not enough mana to use an ability
+    InsufficientMana(String),
+    /// The request itself is invalid (bad input, wrong state, unmet requirement)
+    Validation(String),
+    /// SQLite or connection pool failure
+    Database(String),
+    /// Running user code failed (missing runtime, timeout, spawn error)
+    Execution(String),
+    /// An AI provider call failed or was refused
+    Ai(String),
+    /// Anything else: file IO, serialization, corrupted data
+    Internal(String),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::NotFound(message)
+            | AppError::InsufficientFunds(message)
+            | AppError::InsufficientMana(message)
+            | AppError::Validation(message)
+            | AppError::Database(message)
+            | AppError::Execution(message)
+            | AppError::Ai(message)
+            | AppError::Internal(message) => message,
+        }
+    }
+
+    /// Prefix the message with what was being attempted, keeping the code
+    pub fn context(self, context: impl fmt::Display) -> Self {
+        let message = format!("{}: {}", context, self.message());
+        self.with_message(message)
+    }
+
+    fn with_message(self, message: String) -> Self {
+        match self {
+            AppError::NotFound(_) => AppError::NotFound(message),
+            AppError::InsufficientFunds(_) => AppError::InsufficientFunds(message),
+            AppError::InsufficientMana(_) => AppError::InsufficientMana(message),
+            AppError::Validation(_) => AppError::Validation(message),
+            AppError::Database(_) => AppError::Database(message),
+            AppError::Execution(_) => AppError::Execution(message),
+            AppError::Ai(_) => AppError::Ai(message),
+            AppError::Internal(_) => AppError::Internal(message),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(e.to_string()),
+            _ => AppError::Database(e.to_string()),
+        }
+    }
+}
+
+impl From<r2d2::Error> for AppError {
+    fn from(e: r2d2::Error) -> Self {
+        AppError::Database(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        AppError::Ai(e.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+/// `.context("Failed to load stats")?` in place of
+/// `.map_err(|e| format!("Failed to load stats: {}", e))?`
+pub trait ResultExt<T> {
+    fn context(self, context: &str) -> AppResult<T>;
+    fn with_context<C: fmt::Display>(self, context: impl FnOnce() -> C) -> AppResult<T>;
+}
+
+impl<T, E: Into<AppError>> ResultExt<T> for Result<T, E> {
+    fn context(self, context: &str) -> AppResult<T> {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    fn with_context<C: fmt::Display>(self, context: impl FnOnce() -> C) -> AppResult<T> {
+        self.map_err(|e| e.into().context(context()))
+    }
+}
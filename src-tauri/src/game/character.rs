@@ -0,0 +1,137 @@
+use rusqlite::{params, Connection, Result as SqlResult, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ResultExt};
+
+// ============================================================================
+// CHARACTER STATS
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CharacterStats {
+    pub user_id: i64,
+    pub level: i64,
+    pub strength: i64,
+    pub intelligence: i64,
+    pub dexterity: i64,
+    pub charisma: i64,
+    pub max_health: i64,
+    pub current_health: i64,
+    pub max_mana: i64,
+    pub current_mana: i64,
+    pub base_damage: i64,
+    pub defense: i64,
+    pub critical_chance: f64,
+    pub dodge_chance: f64,
+    pub stat_points_available: i64,
+    pub current_gold: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl CharacterStats {
+    pub fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(CharacterStats {
+            user_id: row.get(0)?,
+            level: row.get(1)?,
+            strength: row.get(2)?,
+            intelligence: row.get(3)?,
+            dexterity: row.get(4)?,
+            charisma: row.get(5)?,
+            max_health: row.get(6)?,
+            current_health: row.get(7)?,
+            max_mana: row.get(8)?,
+            current_mana: row.get(9)?,
+            base_damage: row.get(10)?,
+            defense: row.get(11)?,
+            critical_chance: row.get(12)?,
+            dodge_chance: row.get(13)?,
+            stat_points_available: row.get(14)?,
+            current_gold: row.get(15)?,
+            created_at: row.get(16)?,
+            updated_at: row.get(17)?,
+        })
+    }
+}
+
+/// Load a character's stats on an existing connection, creating the character if needed
+pub fn load_character_stats(conn: &Connection, user_id: i64) -> Result<CharacterStats, AppError> {
+    log::info!("load_character_stats called with user_id={}", user_id);
+
+    // Check if user exists
+    let user_exists: Result<i64, _> = conn.query_row(
+        "SELECT id FROM users WHERE id = ?",
+        params![user_id],
+        |row| row.get(0),
+    );
+
+    if user_exists.is_err() {
+        return Err(AppError::NotFound(format!("User with id={} does not exist in users table", user_id)));
+    }
+
+    // Initialize character if doesn't exist (with default starting stats)
+    // New defaults: all abilities start at 1, 2 stat points available, health=50, mana=30, 100 gold
+    conn.execute(
+        "INSERT OR IGNORE INTO character_stats (
+            user_id, level, strength, intelligence, dexterity, charisma, max_health, current_health,
+            max_mana, current_mana, base_damage, defense, critical_chance, dodge_chance,
+            stat_points_available, current_gold, created_at, updated_at
+        ) VALUES (?, 1, 1, 1, 1, 1, 50, 50, 30, 30, 10, 5, 0.05, 0.05, 2, 100, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+        params![user_id],
+    )
+    .context("Failed to initialize character")?;
+
+    conn.query_row(
+        "SELECT user_id, level, strength, intelligence, dexterity, charisma, max_health, current_health,
+                max_mana, current_mana, base_damage, defense, critical_chance, dodge_chance,
+                stat_points_available, current_gold, created_at, updated_at
+         FROM character_stats
+         WHERE user_id = ?",
+        params![user_id],
+        CharacterStats::from_row,
+    )
+    .context("Failed to get character stats")
+}
+
+// ============================================================================
+// ABILITIES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ability {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub ability_type: String,
+    pub required_level: i64,
+    pub mana_cost: i64,
+    pub cooldown_turns: i64,
+    pub base_value: i64,
+    pub scaling_stat: String,
+    pub scaling_ratio: f64,
+    pub additional_effects: Option<String>,
+    pub icon: String,
+    pub animation_text: String,
+    pub created_at: String,
+}
+
+impl Ability {
+    pub fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(Ability {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            ability_type: row.get(3)?,
+            required_level: row.get(4)?,
+            mana_cost: row.get(5)?,
+            cooldown_turns: row.get(6)?,
+            base_value: row.get(7)?,
+            scaling_stat: row.get(8)?,
+            scaling_ratio: row.get(9)?,
+            additional_effects: row.get(10)?,
+            icon: row.get(11)?,
+            animation_text: row.get(12)?,
+            created_at: row.get(13)?,
+        })
+    }
+}
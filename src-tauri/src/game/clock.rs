@@ -0,0 +1,280 @@
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ResultExt};
+
+// ============================================================================
+// Clock & Time Policy
+// ============================================================================
+//
+// Instants are UTC everywhere and stored in CURRENT_TIMESTAMP format
+// (to_sqlite). Calendar days - the daily puzzle, streaks - are game days: the
+// date in the configured timezone, starting at the rollover hour rather than at
+// midnight. Rules read the time through AppClock, which development builds can
+// shift forwards or backwards to test streaks and refreshes.
+
+/// Source of the current time for rules that depend on it
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real wall clock
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that stays where it is put
+#[cfg(test)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// Format used by SQLite's CURRENT_TIMESTAMP, so stored times compare as text
+const SQLITE_TIMESTAMP: &str = "%Y-%m-%d %H:%M:%S";
+
+pub fn to_sqlite(time: DateTime<Utc>) -> String {
+    time.format(SQLITE_TIMESTAMP).to_string()
+}
+
+/// Parse a CURRENT_TIMESTAMP value; None for anything else
+pub fn from_sqlite(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, SQLITE_TIMESTAMP)
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// The first instant at or after a local time, moving past a daylight saving gap if
+/// the local time falls inside one
+fn first_instant_at<Z: TimeZone>(zone: &Z, local: NaiveDateTime) -> DateTime<Utc> {
+    (0..=4)
+        .find_map(|step| zone.from_local_datetime(&(local + Duration::minutes(30 * step))).earliest())
+        .map(|instant| instant.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeSettings {
+    /// IANA timezone name such as "Europe/Berlin"; None follows the system timezone
+    pub timezone: Option<String>,
+    /// Hour a game day starts at, so a session past midnight still counts for the day before
+    pub day_rollover_hour: u32,
+    /// How far the clock runs ahead of real time (behind if negative); development builds only
+    pub time_travel_seconds: i64,
+}
+
+impl TimeSettings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(name) = &self.timezone {
+            if name.parse::<Tz>().is_err() {
+                return Err(AppError::Validation(format!("Unknown timezone '{}'", name)));
+            }
+        }
+        if self.day_rollover_hour > 23 {
+            return Err(AppError::Validation(format!(
+                "Day rollover hour must be 0-23, got {}",
+                self.day_rollover_hour
+            )));
+        }
+        Ok(())
+    }
+
+    fn zone(&self) -> Option<Tz> {
+        self.timezone.as_deref().and_then(|name| name.parse().ok())
+    }
+
+    /// The UTC offset in effect at an instant, which changes with daylight saving time
+    fn offset_at(&self, instant: DateTime<Utc>) -> FixedOffset {
+        match self.zone() {
+            Some(zone) => zone.offset_from_utc_datetime(&instant.naive_utc()).fix(),
+            None => Local.offset_from_utc_datetime(&instant.naive_utc()).fix(),
+        }
+    }
+
+    /// The game day an instant falls in
+    pub fn day_of(&self, instant: DateTime<Utc>) -> NaiveDate {
+        let local = instant.with_timezone(&self.offset_at(instant)).naive_local();
+        (local - Duration::hours(self.day_rollover_hour.into())).date()
+    }
+
+    /// The first instant of a game day
+    pub fn day_start(&self, day: NaiveDate) -> DateTime<Utc> {
+        let local = day.and_hms_opt(self.day_rollover_hour, 0, 0).unwrap_or_default();
+        match self.zone() {
+            Some(zone) => first_instant_at(&zone, local),
+            None => first_instant_at(&Local, local),
+        }
+    }
+
+    /// The instants a game day covers, from its start up to the next day's start
+    pub fn day_range(&self, day: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        (self.day_start(day), self.day_start(day + Duration::days(1)))
+    }
+}
+
+pub fn load_settings(conn: &Connection) -> Result<TimeSettings, AppError> {
+    let settings = conn
+        .query_row(
+            "SELECT timezone, day_rollover_hour, time_travel_seconds FROM clock_settings WHERE id = 1",
+            [],
+            |row| {
+                Ok(TimeSettings {
+                    timezone: row.get(0)?,
+                    day_rollover_hour: row.get(1)?,
+                    time_travel_seconds: row.get(2)?,
+                })
+            },
+        )
+        .optional()
+        .context("Failed to load clock settings")?;
+    Ok(settings.unwrap_or_default())
+}
+
+pub fn save_settings(conn: &Connection, settings: &TimeSettings) -> Result<(), AppError> {
+    settings.validate()?;
+    conn.execute(
+        "INSERT INTO clock_settings (id, timezone, day_rollover_hour, time_travel_seconds)
+         VALUES (1, ?1, ?2, ?3)
+         ON CONFLICT(id) DO UPDATE SET
+             timezone = excluded.timezone,
+             day_rollover_hour = excluded.day_rollover_hour,
+             time_travel_seconds = excluded.time_travel_seconds,
+             updated_at = datetime('now')",
+        params![settings.timezone, settings.day_rollover_hour, settings.time_travel_seconds],
+    )
+    .context("Failed to save clock settings")?;
+    Ok(())
+}
+
+/// The app's clock: real time plus any time travel, with game days reckoned by the settings
+pub struct AppClock<C: Clock = SystemClock> {
+    base: C,
+    settings: TimeSettings,
+}
+
+impl AppClock {
+    /// The clock as configured in this database. Release builds never time travel.
+    pub fn load(conn: &Connection) -> Result<AppClock, AppError> {
+        let mut settings = load_settings(conn)?;
+        if !cfg!(debug_assertions) {
+            settings.time_travel_seconds = 0;
+        }
+        Ok(AppClock::new(SystemClock, settings))
+    }
+}
+
+impl<C: Clock> AppClock<C> {
+    pub fn new(base: C, settings: TimeSettings) -> Self {
+        AppClock { base, settings }
+    }
+
+    pub fn settings(&self) -> &TimeSettings {
+        &self.settings
+    }
+
+    /// The current game day
+    pub fn today(&self) -> NaiveDate {
+        self.settings.day_of(self.now())
+    }
+
+    /// The instants the current game day covers, for filtering stored timestamps
+    pub fn today_range(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        self.settings.day_range(self.today())
+    }
+}
+
+impl<C: Clock> Clock for AppClock<C> {
+    fn now(&self) -> DateTime<Utc> {
+        self.base.now() + Duration::seconds(self.settings.time_travel_seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::seeded_db;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        from_sqlite(value).unwrap()
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn zone(name: &str) -> Option<String> {
+        Some(name.to_string())
+    }
+
+    #[test]
+    fn game_days_follow_the_timezone_and_rollover_hour() {
+        let settings = TimeSettings { timezone: zone("America/New_York"), ..Default::default() };
+        // 03:00 UTC is still the evening before in New York
+        assert_eq!(settings.day_of(at("2024-03-10 03:00:00")), date("2024-03-09"));
+        assert_eq!(settings.day_of(at("2024-03-10 05:00:00")), date("2024-03-10"));
+
+        let night_owl = TimeSettings { timezone: zone("UTC"), day_rollover_hour: 4, ..Default::default() };
+        assert_eq!(night_owl.day_of(at("2024-03-10 03:59:59")), date("2024-03-09"));
+        assert_eq!(night_owl.day_of(at("2024-03-10 04:00:00")), date("2024-03-10"));
+    }
+
+    #[test]
+    fn offsets_follow_daylight_saving_time() {
+        let settings = TimeSettings { timezone: zone("America/New_York"), ..Default::default() };
+        // Midnight is 05:00 UTC in winter (UTC-5) but 04:00 UTC in summer (UTC-4)
+        assert_eq!(settings.day_of(at("2024-01-15 04:30:00")), date("2024-01-14"));
+        assert_eq!(settings.day_of(at("2024-07-15 04:30:00")), date("2024-07-15"));
+
+        assert_eq!(settings.day_range(date("2024-01-15")), (at("2024-01-15 05:00:00"), at("2024-01-16 05:00:00")));
+        // The day clocks go forward is an hour short
+        assert_eq!(settings.day_range(date("2024-03-10")), (at("2024-03-10 05:00:00"), at("2024-03-11 04:00:00")));
+    }
+
+    #[test]
+    fn a_rollover_inside_a_daylight_saving_gap_starts_the_day_after_it() {
+        // 02:00-03:00 local doesn't exist in New York on 2024-03-10
+        let settings = TimeSettings { timezone: zone("America/New_York"), day_rollover_hour: 2, ..Default::default() };
+        let (start, end) = settings.day_range(date("2024-03-10"));
+
+        assert_eq!(start, at("2024-03-10 07:00:00"));
+        assert_eq!(end, at("2024-03-11 06:00:00"));
+        assert_eq!(settings.day_of(start), date("2024-03-10"));
+        assert_eq!(settings.day_of(start - Duration::seconds(1)), date("2024-03-09"));
+    }
+
+    #[test]
+    fn time_travel_shifts_now_and_today() {
+        let settings = TimeSettings { timezone: zone("UTC"), day_rollover_hour: 0, time_travel_seconds: 86_400 };
+        let clock = AppClock::new(FixedClock(at("2024-03-10 12:00:00")), settings);
+        assert_eq!(clock.now(), at("2024-03-11 12:00:00"));
+        assert_eq!(clock.today(), date("2024-03-11"));
+        assert_eq!(clock.today_range(), (at("2024-03-11 00:00:00"), at("2024-03-12 00:00:00")));
+    }
+
+    #[test]
+    fn settings_round_trip_and_are_validated() {
+        let conn = seeded_db();
+        assert_eq!(load_settings(&conn).unwrap(), TimeSettings::default());
+
+        let settings = TimeSettings { timezone: zone("Asia/Kolkata"), day_rollover_hour: 3, time_travel_seconds: -3600 };
+        save_settings(&conn, &settings).unwrap();
+        assert_eq!(load_settings(&conn).unwrap(), settings);
+
+        let bad_hour = TimeSettings { day_rollover_hour: 24, ..settings.clone() };
+        assert!(matches!(save_settings(&conn, &bad_hour), Err(AppError::Validation(_))));
+        let bad_zone = TimeSettings { timezone: zone("Mars/Olympus_Mons"), ..settings.clone() };
+        assert!(matches!(save_settings(&conn, &bad_zone), Err(AppError::Validation(_))));
+        assert_eq!(load_settings(&conn).unwrap(), settings);
+    }
+}
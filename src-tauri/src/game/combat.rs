@@ -0,0 +1,1276 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ResultExt};
+use crate::game::character::{load_character_stats, Ability, CharacterStats};
+use crate::game::enemies::{load_boss, load_enemy, BossEnemy, EnemyType};
+use crate::game::ledger::{self, Account, Source};
+use crate::game::rewards;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DamageResult {
+    pub damage: i64,
+    pub is_critical: bool,
+    pub is_dodged: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CombatTurnResult {
+    pub player_damage_dealt: i64,
+    pub player_damage_taken: i64,
+    pub enemy_current_health: i64,
+    pub player_current_health: i64,
+    pub player_current_mana: i64,
+    pub enemy_defeated: bool,
+    pub player_defeated: bool,
+    pub is_critical: bool,
+    pub is_dodged: bool,
+    pub turn_number: i64,
+}
+
+/// How an attempt to run from a fight went
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EscapeAttempt {
+    pub roll: i64,
+    pub modifier: i64,
+    pub dc: i64,
+    pub escaped: bool,
+    /// The enemy's free attack when the escape fails
+    pub damage: i64,
+    pub player_current_health: i64,
+    pub player_defeated: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CombatRewards {
+    pub xp_gained: i64,
+    pub gold_gained: i64,
+    pub items_looted: Vec<String>,
+}
+
+// ============================================================================
+// ENCOUNTER STATE
+// ============================================================================
+//
+// A fight is recorded in dungeon_session when it starts: which enemy, its
+// stats and whether it is a boss. Turns play out against that row and the
+// rewards are paid from it, so nothing the client sends about the enemy, the
+// damage done or the turns taken is trusted. combat_status moves from 'active'
+// to 'won' or 'lost' as the fight ends, and back to NULL once it is settled.
+
+/// The enemy a fight is against, as recorded when it started
+#[derive(Debug, Clone, PartialEq)]
+pub struct Encounter {
+    pub enemy_id: String,
+    pub enemy_name: String,
+    pub icon: String,
+    pub is_boss: bool,
+    pub max_health: i64,
+    pub damage: i64,
+    pub defense: i64,
+}
+
+impl Encounter {
+    pub fn enemy(enemy: &EnemyType) -> Self {
+        Encounter {
+            enemy_id: enemy.id.clone(),
+            enemy_name: enemy.name.clone(),
+            icon: enemy.icon.clone(),
+            is_boss: false,
+            max_health: enemy.base_health,
+            damage: enemy.base_damage,
+            defense: enemy.base_defense,
+        }
+    }
+
+    pub fn boss(boss: &BossEnemy) -> Self {
+        Encounter {
+            enemy_id: boss.id.clone(),
+            enemy_name: boss.name.clone(),
+            icon: boss.icon.clone(),
+            is_boss: true,
+            max_health: boss.health,
+            damage: boss.damage,
+            defense: boss.defense,
+        }
+    }
+}
+
+/// A user's current fight
+#[derive(Debug)]
+pub struct CombatSession {
+    pub encounter: Encounter,
+    pub enemy_health: i64,
+    pub turn: i64,
+    pub damage_dealt: i64,
+    pub damage_taken: i64,
+    /// The turn each ability used this fight is ready again on
+    pub cooldowns: BTreeMap<String, i64>,
+    /// 'active', 'won' or 'lost'
+    pub status: String,
+}
+
+/// The user's fight, or None when they are not in one
+pub fn load_session(conn: &Connection, user_id: i64) -> Result<Option<CombatSession>, AppError> {
+    let session = conn
+        .query_row(
+            "SELECT enemy_id, enemy_name, enemy_icon, is_boss, enemy_max_health, enemy_damage, enemy_defense,
+                    enemy_current_health, combat_turn, damage_dealt, damage_taken, combat_status, ability_cooldowns
+             FROM dungeon_session
+             WHERE user_id = ? AND combat_status IS NOT NULL",
+            params![user_id],
+            |row| {
+                let session = CombatSession {
+                    encounter: Encounter {
+                        enemy_id: row.get(0)?,
+                        enemy_name: row.get(1)?,
+                        icon: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                        is_boss: row.get(3)?,
+                        max_health: row.get(4)?,
+                        damage: row.get(5)?,
+                        defense: row.get(6)?,
+                    },
+                    enemy_health: row.get(7)?,
+                    turn: row.get(8)?,
+                    damage_dealt: row.get(9)?,
+                    damage_taken: row.get(10)?,
+                    cooldowns: BTreeMap::new(),
+                    status: row.get(11)?,
+                };
+                Ok((session, row.get::<_, Option<String>>(12)?))
+            },
+        )
+        .optional()
+        .context("Failed to get combat session")?;
+
+    let Some((mut session, cooldowns)) = session else {
+        return Ok(None);
+    };
+    if let Some(cooldowns) = cooldowns {
+        session.cooldowns = serde_json::from_str(&cooldowns).context("Failed to read ability cooldowns")?;
+    }
+    Ok(Some(session))
+}
+
+/// The user's fight, which must be in the given state
+fn session_in(conn: &Connection, user_id: i64, status: &str) -> Result<CombatSession, AppError> {
+    let session = load_session(conn, user_id)?
+        .ok_or_else(|| AppError::Validation("Not in combat".to_string()))?;
+
+    if session.status != status {
+        let message = match session.status.as_str() {
+            "won" => "The enemy has already been defeated",
+            "lost" => "You have been defeated",
+            _ => "The enemy hasn't been defeated yet",
+        };
+        return Err(AppError::Validation(message.to_string()));
+    }
+    Ok(session)
+}
+
+/// Refuse while a fight is under way, or lost and not yet resolved. Until it's
+/// settled the player's health, mana and rewards only change through combat.
+pub fn ensure_out_of_combat(conn: &Connection, user_id: i64) -> Result<(), AppError> {
+    match load_session(conn, user_id)?.map(|session| session.status) {
+        Some(status) if status == "active" => Err(AppError::Validation("Already in combat".to_string())),
+        Some(status) if status == "lost" => Err(AppError::Validation("You have been defeated".to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Whether the encounter belongs on the user's current floor: a boss of that
+/// floor, an enemy that spawns there, or one a story choice there sends at them
+fn on_current_floor(conn: &Connection, user_id: i64, encounter: &Encounter) -> Result<bool, AppError> {
+    let sql = if encounter.is_boss {
+        "SELECT EXISTS(
+            SELECT 1 FROM boss_enemies b
+            JOIN user_dungeon_progress p ON p.current_floor = b.floor_number
+            WHERE p.user_id = ?1 AND b.id = ?2)"
+    } else {
+        "SELECT EXISTS(
+            SELECT 1 FROM floor_enemies fe
+            JOIN user_dungeon_progress p ON p.current_floor = fe.floor_number
+            WHERE p.user_id = ?1 AND fe.enemy_id = ?2
+            UNION ALL
+            SELECT 1 FROM narrative_outcomes o
+            JOIN narrative_choices c ON c.id = o.choice_id
+            JOIN narrative_locations l ON l.id = c.location_id
+            JOIN user_dungeon_progress p ON p.current_floor = l.floor_number
+            WHERE p.user_id = ?1 AND o.triggers_combat AND o.enemy_id = ?2)"
+    };
+
+    conn.query_row(sql, params![user_id, encounter.enemy_id], |row| row.get(0))
+        .context("Failed to check the enemy's floor")
+}
+
+/// Put the user in combat against an enemy and reset the combat session. A fight
+/// still under way, or lost and not yet resolved, has to be finished first.
+pub fn begin(conn: &Connection, user_id: i64, encounter: &Encounter) -> Result<(), AppError> {
+    ensure_out_of_combat(conn, user_id)?;
+
+    if !on_current_floor(conn, user_id, encounter)? {
+        return Err(AppError::Validation(format!(
+            "{} isn't found on this floor",
+            encounter.enemy_name
+        )));
+    }
+
+    conn.execute(
+        "UPDATE user_dungeon_progress
+         SET in_combat = TRUE,
+             current_enemy_id = ?,
+             current_enemy_health = ?,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![encounter.enemy_id, encounter.max_health, user_id],
+    )
+    .context("Failed to start combat")?;
+
+    conn.execute(
+        "INSERT OR REPLACE INTO dungeon_session
+            (user_id, combat_turn, enemy_current_health, ability_cooldowns, active_buffs, active_debuffs,
+             enemy_id, enemy_name, enemy_icon, is_boss, enemy_max_health, enemy_damage, enemy_defense,
+             damage_dealt, damage_taken, combat_status)
+         VALUES (?1, 0, ?2, '{}', '[]', '[]', ?3, ?4, ?5, ?6, ?2, ?7, ?8, 0, 0, 'active')",
+        params![
+            user_id,
+            encounter.max_health,
+            encounter.enemy_id,
+            encounter.enemy_name,
+            encounter.icon,
+            encounter.is_boss,
+            encounter.damage,
+            encounter.defense
+        ],
+    )
+    .context("Failed to initialize combat session")?;
+
+    Ok(())
+}
+
+/// Settle the fight: the user is out of combat and the session holds no encounter
+fn end_encounter(conn: &Connection, user_id: i64) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE dungeon_session
+         SET combat_status = NULL,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![user_id],
+    )
+    .context("Failed to end combat session")?;
+
+    Ok(())
+}
+
+// ============================================================================
+// DAMAGE CALCULATION
+// ============================================================================
+
+pub fn roll_player_damage(
+    stats: &CharacterStats,
+    ability: &Ability,
+    challenge_success: bool,
+    rng: &mut impl Rng,
+) -> DamageResult {
+    // Base damage from ability
+    let mut damage = ability.base_value;
+
+    // Add stat scaling
+    let stat_value = match ability.scaling_stat.as_str() {
+        "strength" => stats.strength,
+        "intelligence" => stats.intelligence,
+        "dexterity" => stats.dexterity,
+        _ => 0,
+    };
+
+    damage += (stat_value as f64 * ability.scaling_ratio) as i64;
+
+    // Apply challenge success/failure modifier
+    if !challenge_success {
+        damage = (damage as f64 * 0.5) as i64; // 50% damage on failure
+    }
+
+    // Check for critical hit
+    let crit_roll: f64 = rng.gen();
+    let is_critical = crit_roll < stats.critical_chance;
+
+    if is_critical {
+        damage *= 2;
+    }
+
+    DamageResult {
+        damage,
+        is_critical,
+        is_dodged: false,
+    }
+}
+
+pub fn roll_enemy_damage(stats: &CharacterStats, enemy_base_damage: i64, rng: &mut impl Rng) -> DamageResult {
+    // Check for dodge
+    let dodge_roll: f64 = rng.gen();
+    let is_dodged = dodge_roll < stats.dodge_chance;
+
+    if is_dodged {
+        return DamageResult {
+            damage: 0,
+            is_critical: false,
+            is_dodged: true,
+        };
+    }
+
+    // Calculate damage after defense
+    let damage = (enemy_base_damage - stats.defense).max(1);
+
+    DamageResult {
+        damage,
+        is_critical: false,
+        is_dodged: false,
+    }
+}
+
+// ============================================================================
+// COMBAT ACTIONS
+// ============================================================================
+
+/// Play one round of the user's fight: the player's ability, then the enemy's counterattack
+pub fn execute_turn(
+    conn: &Connection,
+    rng: &mut impl Rng,
+    user_id: i64,
+    ability_id: &str,
+    challenge_success: bool,
+) -> Result<CombatTurnResult, AppError> {
+    let session = session_in(conn, user_id, "active")?;
+    let stats = load_character_stats(conn, user_id)?;
+
+    let ability: Ability = conn
+        .query_row(
+            "SELECT id, name, description, type, required_level, mana_cost, cooldown_turns,
+                    base_value, scaling_stat, scaling_ratio, additional_effects, icon,
+                    animation_text, created_at
+             FROM abilities
+             WHERE id = ?",
+            params![ability_id],
+            Ability::from_row,
+        )
+        .context("Failed to get ability")?;
+
+    let unlocked: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM user_abilities WHERE user_id = ? AND ability_id = ?)",
+            params![user_id, ability_id],
+            |row| row.get(0),
+        )
+        .context("Failed to check ability")?;
+    if !unlocked {
+        return Err(AppError::Validation(format!("{} hasn't been unlocked", ability.name)));
+    }
+
+    let turn_number = session.turn + 1;
+    if let Some(&ready) = session.cooldowns.get(ability_id).filter(|&&ready| ready > turn_number) {
+        let turns = ready - turn_number;
+        return Err(AppError::Validation(format!(
+            "{} is recharging for {} more turn{}",
+            ability.name,
+            turns,
+            if turns == 1 { "" } else { "s" }
+        )));
+    }
+
+    // Check if player has enough mana
+    if stats.current_mana < ability.mana_cost {
+        return Err(AppError::InsufficientMana("Not enough mana".to_string()));
+    }
+
+    let player_damage_result = roll_player_damage(&stats, &ability, challenge_success, rng);
+    let mut player_damage_dealt = player_damage_result.damage;
+
+    // Handle heal ability
+    let mut new_player_health = stats.current_health;
+    if ability.ability_type == "heal" {
+        new_player_health = (stats.current_health + player_damage_dealt).min(stats.max_health);
+        player_damage_dealt = 0; // Don't damage enemy with heal
+    } else {
+        // Enemy armour soaks part of the hit, the same way the player's defense does
+        player_damage_dealt = (player_damage_dealt - session.encounter.defense).max(1);
+    }
+
+    // Apply damage to enemy
+    let new_enemy_health = (session.enemy_health - player_damage_dealt).max(0);
+    let enemy_defeated = new_enemy_health <= 0;
+
+    // Deduct mana
+    let new_player_mana = stats.current_mana - ability.mana_cost;
+
+    conn.execute(
+        "UPDATE character_stats
+         SET current_health = ?,
+             current_mana = ?,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![new_player_health, new_player_mana, user_id],
+    )
+    .context("Failed to update player stats")?;
+
+    // Enemy turn (if not defeated)
+    let mut player_damage_taken = 0;
+    let mut player_defeated = false;
+    let mut is_dodged = false;
+
+    if !enemy_defeated {
+        let enemy_damage_result = roll_enemy_damage(&stats, session.encounter.damage, rng);
+        player_damage_taken = enemy_damage_result.damage;
+        is_dodged = enemy_damage_result.is_dodged;
+
+        new_player_health = (new_player_health - player_damage_taken).max(0);
+        player_defeated = new_player_health <= 0;
+
+        conn.execute(
+            "UPDATE character_stats
+             SET current_health = ?,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE user_id = ?",
+            params![new_player_health, user_id],
+        )
+        .context("Failed to update player health")?;
+    }
+
+    let mut cooldowns = session.cooldowns;
+    if ability.cooldown_turns > 0 {
+        cooldowns.insert(ability.id.clone(), turn_number + ability.cooldown_turns + 1);
+    }
+    let cooldowns = serde_json::to_string(&cooldowns).context("Failed to save ability cooldowns")?;
+
+    let status = if enemy_defeated {
+        "won"
+    } else if player_defeated {
+        "lost"
+    } else {
+        "active"
+    };
+
+    conn.execute(
+        "UPDATE dungeon_session
+         SET combat_turn = combat_turn + 1,
+             enemy_current_health = ?,
+             damage_dealt = damage_dealt + ?,
+             damage_taken = damage_taken + ?,
+             ability_cooldowns = ?,
+             combat_status = ?,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![new_enemy_health, player_damage_dealt, player_damage_taken, cooldowns, status, user_id],
+    )
+    .context("Failed to update combat session")?;
+
+    Ok(CombatTurnResult {
+        player_damage_dealt,
+        player_damage_taken,
+        enemy_current_health: new_enemy_health,
+        player_current_health: new_player_health,
+        player_current_mana: new_player_mana,
+        enemy_defeated,
+        player_defeated,
+        is_critical: player_damage_result.is_critical,
+        is_dodged,
+        turn_number,
+    })
+}
+
+// ============================================================================
+// COMBAT OUTCOMES
+// ============================================================================
+
+/// Gold, XP and item ids an encounter pays out, read from the enemy's own row
+fn roll_bounty(
+    conn: &Connection,
+    rng: &mut impl Rng,
+    encounter: &Encounter,
+) -> Result<(i64, i64, Vec<String>), AppError> {
+    if encounter.is_boss {
+        let boss = load_boss(conn, &encounter.enemy_id)?;
+        let loot = boss.guaranteed_loot.as_deref().map(rewards::guaranteed_loot).unwrap_or_default();
+        return Ok((boss.gold_reward, boss.xp_reward, loot));
+    }
+
+    let enemy = load_enemy(conn, &encounter.enemy_id)?;
+    let gold = rng.gen_range(enemy.gold_drop_min..=enemy.gold_drop_max);
+    let loot = enemy.loot_table.as_deref().map(|table| rewards::roll_loot(table, rng)).unwrap_or_default();
+    Ok((gold, enemy.xp_reward, loot))
+}
+
+/// Grant gold, XP and loot for the enemy the user has just defeated, and log the fight.
+/// Only a won fight pays out, and only once.
+pub fn resolve_victory(conn: &Connection, rng: &mut impl Rng, user_id: i64) -> Result<CombatRewards, AppError> {
+    let session = session_in(conn, user_id, "won")?;
+    let encounter = &session.encounter;
+    let (gold_gained, xp_gained, loot) = roll_bounty(conn, rng, encounter)?;
+
+    let source = Source::with_id("combat_victory", &encounter.enemy_id);
+    rewards::award_gold(conn, user_id, gold_gained, source)?;
+    rewards::award_xp(conn, user_id, xp_gained, source)?;
+
+    conn.execute(
+        "UPDATE user_dungeon_progress
+         SET in_combat = FALSE,
+             current_enemy_id = NULL,
+             current_enemy_health = NULL,
+             total_enemies_defeated = total_enemies_defeated + 1,
+             total_bosses_defeated = total_bosses_defeated + ?,
+             total_gold_earned = total_gold_earned + ?,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![encounter.is_boss as i64, gold_gained, user_id],
+    )
+    .context("Failed to update dungeon progress")?;
+
+    conn.execute(
+        "INSERT INTO dungeon_combat_log
+            (user_id, enemy_type, enemy_name, floor_number, is_boss, victory, turns_taken,
+             damage_dealt, damage_taken, xp_gained, gold_gained)
+         SELECT ?, ?, ?, current_floor, ?, TRUE, ?, ?, ?, ?, ?
+         FROM user_dungeon_progress
+         WHERE user_id = ?",
+        params![
+            user_id,
+            encounter.enemy_id,
+            encounter.enemy_name,
+            encounter.is_boss,
+            session.turn,
+            session.damage_dealt,
+            session.damage_taken,
+            xp_gained,
+            gold_gained,
+            user_id
+        ],
+    )
+    .context("Failed to log combat")?;
+
+    let mut items_looted = Vec::new();
+    for item_id in loot {
+        if let Some(name) = rewards::grant_item(conn, user_id, &item_id)? {
+            items_looted.push(name);
+        }
+    }
+
+    end_encounter(conn, user_id)?;
+
+    Ok(CombatRewards {
+        xp_gained,
+        gold_gained,
+        items_looted,
+    })
+}
+
+/// Lose 10% of gold, count the death and respawn at full health and mana. Only a
+/// fight the player has lost can be resolved this way.
+pub fn resolve_defeat(conn: &Connection, user_id: i64) -> Result<(), AppError> {
+    session_in(conn, user_id, "lost")?;
+
+    let gold_lost = ledger::balance(conn, user_id, Account::Gold)? / 10;
+    ledger::debit(conn, user_id, Account::Gold, gold_lost, Source::new("combat_defeat"))?;
+
+    conn.execute(
+        "UPDATE user_dungeon_progress
+         SET in_combat = FALSE,
+             current_enemy_id = NULL,
+             current_enemy_health = NULL,
+             total_deaths = total_deaths + 1,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![user_id],
+    )
+    .context("Failed to update dungeon progress")?;
+
+    conn.execute(
+        "UPDATE character_stats
+         SET current_health = max_health,
+             current_mana = max_mana,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![user_id],
+    )
+    .context("Failed to restore health and mana")?;
+
+    end_encounter(conn, user_id)
+}
+
+/// Try to run from the fight: a d20 plus the player's dexterity, when they
+/// answered the challenge, against 10 + half the enemy's damage. Getting away
+/// ends the fight with whatever health the player has left. Failing gives the
+/// enemy a free attack against half the player's defense, and a hit that knocks
+/// them out loses the fight.
+pub fn attempt_escape(
+    conn: &Connection,
+    rng: &mut impl Rng,
+    user_id: i64,
+    challenge_success: bool,
+) -> Result<EscapeAttempt, AppError> {
+    let session = session_in(conn, user_id, "active")?;
+    let stats = load_character_stats(conn, user_id)?;
+
+    let roll = rng.gen_range(1..=20);
+    let modifier = if challenge_success { stats.dexterity } else { 0 };
+    let dc = 10 + session.encounter.damage / 2;
+
+    if roll + modifier >= dc {
+        conn.execute(
+            "UPDATE user_dungeon_progress
+             SET in_combat = FALSE,
+                 current_enemy_id = NULL,
+                 current_enemy_health = NULL,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE user_id = ?",
+            params![user_id],
+        )
+        .context("Failed to update dungeon progress")?;
+        end_encounter(conn, user_id)?;
+
+        return Ok(EscapeAttempt {
+            roll,
+            modifier,
+            dc,
+            escaped: true,
+            damage: 0,
+            player_current_health: stats.current_health,
+            player_defeated: false,
+        });
+    }
+
+    let damage = (session.encounter.damage - stats.defense / 2).max(1);
+    let health = (stats.current_health - damage).max(0);
+    let player_defeated = health == 0;
+
+    conn.execute(
+        "UPDATE character_stats
+         SET current_health = ?,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![health, user_id],
+    )
+    .context("Failed to update player health")?;
+
+    conn.execute(
+        "UPDATE dungeon_session
+         SET damage_taken = damage_taken + ?,
+             combat_status = ?,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![damage, if player_defeated { "lost" } else { "active" }, user_id],
+    )
+    .context("Failed to update combat session")?;
+
+    Ok(EscapeAttempt {
+        roll,
+        modifier,
+        dc,
+        escaped: false,
+        damage,
+        player_current_health: health,
+        player_defeated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::test_support::{
+        consumable_quantity, equipment_quantity, fail_inserts_into, scalar, seeded_db, unlock_ability, TestUser,
+    };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn stats(critical_chance: f64, dodge_chance: f64) -> CharacterStats {
+        CharacterStats {
+            user_id: 1,
+            level: 1,
+            strength: 10,
+            intelligence: 10,
+            dexterity: 10,
+            charisma: 10,
+            max_health: 100,
+            current_health: 100,
+            max_mana: 50,
+            current_mana: 50,
+            base_damage: 10,
+            defense: 5,
+            critical_chance,
+            dodge_chance,
+            stat_points_available: 0,
+            current_gold: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn strike() -> Ability {
+        Ability {
+            id: "strike".to_string(),
+            name: "Strike".to_string(),
+            description: String::new(),
+            ability_type: "damage".to_string(),
+            required_level: 1,
+            mana_cost: 0,
+            cooldown_turns: 0,
+            base_value: 10,
+            scaling_stat: "strength".to_string(),
+            scaling_ratio: 0.5,
+            additional_effects: None,
+            icon: String::new(),
+            animation_text: String::new(),
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn failed_challenge_halves_damage() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let stats = stats(0.0, 0.0);
+
+        assert_eq!(roll_player_damage(&stats, &strike(), true, &mut rng).damage, 15);
+        assert_eq!(roll_player_damage(&stats, &strike(), false, &mut rng).damage, 7);
+    }
+
+    #[test]
+    fn critical_hit_doubles_damage() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let result = roll_player_damage(&stats(1.0, 0.0), &strike(), true, &mut rng);
+
+        assert!(result.is_critical);
+        assert_eq!(result.damage, 30);
+    }
+
+    #[test]
+    fn enemy_damage_is_reduced_by_defense_but_never_zero() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert_eq!(roll_enemy_damage(&stats(0.0, 0.0), 15, &mut rng).damage, 10);
+        assert_eq!(roll_enemy_damage(&stats(0.0, 0.0), 2, &mut rng).damage, 1);
+
+        let dodged = roll_enemy_damage(&stats(0.0, 1.0), 15, &mut rng);
+        assert!(dodged.is_dodged);
+        assert_eq!(dodged.damage, 0);
+    }
+
+    /// An enemy that goes down to any hit, with fixed rewards
+    fn dummy(conn: &Connection, xp_reward: i64, gold: i64, loot_table: &str) -> Encounter {
+        conn.execute(
+            "INSERT OR REPLACE INTO enemy_types
+                (id, name, description, base_health, base_damage, base_defense, behavior_type,
+                 gold_drop_min, gold_drop_max, xp_reward, loot_table, icon)
+             VALUES ('training_dummy', 'Training Dummy', '', 1, 6, 0, 'defensive', ?1, ?1, ?2, ?3, '')",
+            params![gold, xp_reward, loot_table],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO floor_enemies (floor_number, enemy_id) VALUES (1, 'training_dummy')",
+            [],
+        )
+        .unwrap();
+        Encounter::enemy(&load_enemy(conn, "training_dummy").unwrap())
+    }
+
+    fn seeded_enemy(conn: &Connection, enemy_id: &str) -> Encounter {
+        Encounter::enemy(&load_enemy(conn, enemy_id).unwrap())
+    }
+
+    /// Start a fight and play basic attacks until the enemy is down
+    fn win(conn: &Connection, rng: &mut StdRng, user_id: i64, encounter: &Encounter) {
+        begin(conn, user_id, encounter).unwrap();
+        while !execute_turn(conn, rng, user_id, "basic_attack", true).unwrap().enemy_defeated {}
+    }
+
+    #[test]
+    fn victory_awards_gold_xp_levels_and_loot() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("victor").dungeon_gold(100).currency(5, 0).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let dummy = dummy(
+            &conn,
+            120,
+            20,
+            r#"[{"item": "wooden_sword", "chance": 1.0}, {"item": "minor_health_potion", "chance": 1.0}]"#,
+        );
+        win(&conn, &mut rng, user_id, &dummy);
+        let rewards = resolve_victory(&conn, &mut rng, user_id).unwrap();
+
+        assert_eq!(rewards.gold_gained, 20);
+        assert_eq!(rewards.xp_gained, 120);
+        assert_eq!(rewards.items_looted.len(), 2);
+
+        // Gold goes to both the dungeon purse and the gamification currency
+        let dungeon_gold: i64 = scalar(&conn, "SELECT current_gold FROM character_stats WHERE user_id = ?", [user_id]);
+        let (gold, lifetime): (i64, i64) = conn
+            .query_row(
+                "SELECT gold, lifetime_gold_earned FROM user_currency WHERE user_id = ?",
+                [user_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(dungeon_gold, 120);
+        assert_eq!((gold, lifetime), (25, 20));
+
+        // 120 XP from level 1 is one level: a stat point and the level 2 abilities
+        let (level, stat_points): (i64, i64) = conn
+            .query_row(
+                "SELECT level, stat_points_available FROM character_stats WHERE user_id = ?",
+                [user_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((level, stat_points), (2, 3));
+        let unlocked: i64 = scalar(
+            &conn,
+            "SELECT COUNT(*) FROM user_abilities WHERE user_id = ? AND ability_id = 'defend'",
+            [user_id],
+        );
+        assert_eq!(unlocked, 1);
+
+        let (in_combat, defeated, total_xp): (bool, i64, i64) = conn
+            .query_row(
+                "SELECT in_combat, total_enemies_defeated, total_xp_earned FROM user_dungeon_progress WHERE user_id = ?",
+                [user_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert!(!in_combat);
+        assert_eq!((defeated, total_xp), (1, 120));
+
+        assert_eq!(equipment_quantity(&conn, user_id, "wooden_sword"), 1);
+        assert_eq!(consumable_quantity(&conn, user_id, "minor_health_potion"), 1);
+
+        let logged: i64 = scalar(
+            &conn,
+            "SELECT COUNT(*) FROM dungeon_combat_log
+             WHERE user_id = ? AND enemy_type = 'training_dummy' AND victory = TRUE AND is_boss = FALSE
+               AND turns_taken = 1 AND damage_taken = 0 AND gold_gained = 20",
+            [user_id],
+        );
+        assert_eq!(logged, 1);
+
+        // Every balance change is in the ledger under the enemy that paid it
+        let entries = ledger::statement(&conn, user_id, None, 10).unwrap();
+        assert!(entries
+            .iter()
+            .filter(|e| e.source_type == "combat_victory")
+            .all(|e| e.source_id.as_deref() == Some("training_dummy")));
+        assert_eq!(entries.iter().filter(|e| e.source_type == "combat_victory").count(), 3);
+        assert!(ledger::reconcile(&conn, Some(user_id)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn interrupted_victory_applies_none_of_its_rewards() {
+        let mut conn = seeded_db();
+        let user_id = TestUser::new("interrupted").create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        let dummy = dummy(&conn, 120, 20, "[]");
+        win(&conn, &mut rng, user_id, &dummy);
+
+        let state = |conn: &Connection| -> (i64, i64, bool, i64, i64) {
+            conn.query_row(
+                "SELECT cs.current_gold, cs.level, p.in_combat, p.total_xp_earned,
+                        (SELECT COUNT(*) FROM ledger_entries WHERE user_id = cs.user_id)
+                 FROM character_stats cs JOIN user_dungeon_progress p ON p.user_id = cs.user_id
+                 WHERE cs.user_id = ?",
+                [user_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )
+            .unwrap()
+        };
+        let (_, _, _, _, entries) = state(&conn);
+
+        // The combat log is written after the gold, XP, level-up and progress
+        fail_inserts_into(&conn, "dungeon_combat_log");
+        let result = db::run_unit_of_work(&mut conn, |tx| resolve_victory(tx, &mut rng, user_id));
+        assert!(result.is_err());
+        assert_eq!(state(&conn), (100, 1, true, 0, entries));
+        assert!(ledger::reconcile(&conn, Some(user_id)).unwrap().is_empty());
+
+        conn.execute_batch("DROP TRIGGER fail_dungeon_combat_log").unwrap();
+        db::run_unit_of_work(&mut conn, |tx| resolve_victory(tx, &mut rng, user_id)).unwrap();
+        assert_eq!(state(&conn), (120, 2, false, 120, entries + 3));
+    }
+
+    #[test]
+    fn repeated_loot_stacks_instead_of_duplicating() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("looter").create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        let dummy = dummy(&conn, 10, 5, r#"[{"item": "wooden_sword", "chance": 1.0}]"#);
+
+        for _ in 0..2 {
+            win(&conn, &mut rng, user_id, &dummy);
+            resolve_victory(&conn, &mut rng, user_id).unwrap();
+        }
+
+        assert_eq!(equipment_quantity(&conn, user_id, "wooden_sword"), 2);
+    }
+
+    #[test]
+    fn turn_trades_blows_and_advances_the_session() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("fighter").stats(10, 1, 1).luck(0.0, 0.0).defense(5).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        begin(&conn, user_id, &seeded_enemy(&conn, "wolf")).unwrap();
+
+        // basic_attack: 10 + 10 strength * 0.3 - the wolf's 1 defense; it bites for 8 damage - 5 defense
+        let turn = execute_turn(&conn, &mut rng, user_id, "basic_attack", true).unwrap();
+        assert_eq!(turn.player_damage_dealt, 12);
+        assert_eq!(turn.enemy_current_health, 33);
+        assert_eq!(turn.player_damage_taken, 3);
+        assert_eq!(turn.player_current_health, 47);
+        assert_eq!(turn.turn_number, 1);
+
+        let session = load_session(&conn, user_id).unwrap().unwrap();
+        assert_eq!(session.encounter.enemy_id, "wolf");
+        assert_eq!(session.status, "active");
+        assert_eq!((session.enemy_health, session.turn), (33, 1));
+        assert_eq!((session.damage_dealt, session.damage_taken), (12, 3));
+    }
+
+    #[test]
+    fn turn_without_enough_mana_is_rejected() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("drained").mana(2, 30).create(&conn);
+        unlock_ability(&conn, user_id, "power_strike");
+        let mut rng = StdRng::seed_from_u64(3);
+        begin(&conn, user_id, &seeded_enemy(&conn, "goblin")).unwrap();
+
+        let err = execute_turn(&conn, &mut rng, user_id, "power_strike", true).unwrap_err();
+        assert!(matches!(err, AppError::InsufficientMana(_)));
+
+        let turn: i64 = scalar(&conn, "SELECT combat_turn FROM dungeon_session WHERE user_id = ?", [user_id]);
+        assert_eq!(turn, 0);
+    }
+
+    #[test]
+    fn victory_is_paid_once_and_only_for_a_won_fight() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("claimant").create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        let dummy = dummy(&conn, 10, 5, "[]");
+
+        let not_in_combat = resolve_victory(&conn, &mut rng, user_id).unwrap_err();
+        assert!(matches!(not_in_combat, AppError::Validation(_)));
+
+        begin(&conn, user_id, &dummy).unwrap();
+        let still_standing = resolve_victory(&conn, &mut rng, user_id).unwrap_err();
+        assert!(matches!(still_standing, AppError::Validation(_)));
+
+        execute_turn(&conn, &mut rng, user_id, "basic_attack", true).unwrap();
+        let finished = execute_turn(&conn, &mut rng, user_id, "basic_attack", true).unwrap_err();
+        assert!(matches!(finished, AppError::Validation(_)));
+
+        resolve_victory(&conn, &mut rng, user_id).unwrap();
+        let claimed = resolve_victory(&conn, &mut rng, user_id).unwrap_err();
+        assert!(matches!(claimed, AppError::Validation(_)));
+
+        assert!(load_session(&conn, user_id).unwrap().is_none());
+        let paid: i64 = scalar(&conn, "SELECT COUNT(*) FROM dungeon_combat_log WHERE user_id = ?", [user_id]);
+        assert_eq!(paid, 1);
+    }
+
+    #[test]
+    fn boss_victory_pays_the_boss_rewards() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("kingslayer").stats(200, 1, 1).luck(0.0, 0.0).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        let rat_king = Encounter::boss(&load_boss(&conn, "giant_rat_king").unwrap());
+
+        // 70 - 5 defense a turn against 150 health; the king hits back for 15 - 5 defense
+        win(&conn, &mut rng, user_id, &rat_king);
+        let rewards = resolve_victory(&conn, &mut rng, user_id).unwrap();
+
+        assert_eq!((rewards.gold_gained, rewards.xp_gained), (200, 500));
+        assert_eq!(equipment_quantity(&conn, user_id, "iron_sword"), 1);
+
+        let (enemies, bosses): (i64, i64) = conn
+            .query_row(
+                "SELECT total_enemies_defeated, total_bosses_defeated FROM user_dungeon_progress WHERE user_id = ?",
+                [user_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((enemies, bosses), (1, 1));
+
+        let logged: i64 = scalar(
+            &conn,
+            "SELECT COUNT(*) FROM dungeon_combat_log
+             WHERE user_id = ? AND is_boss = TRUE AND turns_taken = 3 AND damage_dealt = 195 AND damage_taken = 20",
+            [user_id],
+        );
+        assert_eq!(logged, 1);
+    }
+
+    #[test]
+    fn lost_fight_takes_no_more_turns_and_pays_nothing() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("outmatched").health(2, 50).luck(0.0, 0.0).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        begin(&conn, user_id, &seeded_enemy(&conn, "wolf")).unwrap();
+
+        assert!(execute_turn(&conn, &mut rng, user_id, "basic_attack", true).unwrap().player_defeated);
+        assert_eq!(load_session(&conn, user_id).unwrap().unwrap().status, "lost");
+
+        let another_turn = execute_turn(&conn, &mut rng, user_id, "basic_attack", true).unwrap_err();
+        assert!(matches!(another_turn, AppError::Validation(_)));
+        let claim = resolve_victory(&conn, &mut rng, user_id).unwrap_err();
+        assert!(matches!(claim, AppError::Validation(_)));
+
+        resolve_defeat(&conn, user_id).unwrap();
+        assert!(load_session(&conn, user_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn armour_soaks_hits_but_never_all_of_them() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("tinker").stats(1, 1, 1).luck(0.0, 0.0).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        conn.execute("UPDATE enemy_types SET base_defense = 100 WHERE id = 'wolf'", []).unwrap();
+        begin(&conn, user_id, &seeded_enemy(&conn, "wolf")).unwrap();
+
+        let turn = execute_turn(&conn, &mut rng, user_id, "basic_attack", true).unwrap();
+        assert_eq!(turn.player_damage_dealt, 1);
+        assert_eq!(turn.enemy_current_health, 44);
+    }
+
+    /// Start a fight the player is sure to lose on the first turn
+    fn lose(conn: &Connection, user_id: i64) {
+        let mut rng = StdRng::seed_from_u64(3);
+        begin(conn, user_id, &seeded_enemy(conn, "wolf")).unwrap();
+        assert!(execute_turn(conn, &mut rng, user_id, "basic_attack", true).unwrap().player_defeated);
+    }
+
+    #[test]
+    fn defeat_costs_a_tenth_of_gold_and_restores_the_character() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("fallen").health(2, 50).mana(3, 30).luck(0.0, 0.0).currency(250, 0).create(&conn);
+        lose(&conn, user_id);
+
+        resolve_defeat(&conn, user_id).unwrap();
+
+        let gold: i64 = scalar(&conn, "SELECT gold FROM user_currency WHERE user_id = ?", [user_id]);
+        let (health, mana): (i64, i64) = conn
+            .query_row(
+                "SELECT current_health, current_mana FROM character_stats WHERE user_id = ?",
+                [user_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        let deaths: i64 = scalar(&conn, "SELECT total_deaths FROM user_dungeon_progress WHERE user_id = ?", [user_id]);
+
+        assert_eq!(gold, 225);
+        assert_eq!((health, mana), (50, 30));
+        assert_eq!(deaths, 1);
+        assert!(ledger::reconcile(&conn, Some(user_id)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn defeat_is_only_accepted_for_a_lost_fight() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("shammer").health(30, 50).currency(250, 0).create(&conn);
+
+        let not_in_combat = resolve_defeat(&conn, user_id).unwrap_err();
+        assert!(matches!(not_in_combat, AppError::Validation(_)));
+
+        begin(&conn, user_id, &seeded_enemy(&conn, "wolf")).unwrap();
+        let still_fighting = resolve_defeat(&conn, user_id).unwrap_err();
+        assert!(matches!(still_fighting, AppError::Validation(_)));
+
+        // Nothing was paid or restored
+        let gold: i64 = scalar(&conn, "SELECT gold FROM user_currency WHERE user_id = ?", [user_id]);
+        let health: i64 = scalar(&conn, "SELECT current_health FROM character_stats WHERE user_id = ?", [user_id]);
+        let deaths: i64 = scalar(&conn, "SELECT total_deaths FROM user_dungeon_progress WHERE user_id = ?", [user_id]);
+        assert_eq!((gold, health, deaths), (250, 30, 0));
+        assert_eq!(load_session(&conn, user_id).unwrap().unwrap().status, "active");
+    }
+
+    #[test]
+    fn a_quick_enough_escape_ends_the_fight() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("sprinter").stats(1, 1, 30).health(40, 50).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        begin(&conn, user_id, &seeded_enemy(&conn, "wolf")).unwrap();
+
+        // The wolf's 8 damage sets the DC at 14, which 30 dexterity always clears
+        let attempt = attempt_escape(&conn, &mut rng, user_id, true).unwrap();
+        assert!(attempt.escaped);
+        assert_eq!((attempt.modifier, attempt.dc, attempt.damage), (30, 14, 0));
+        assert!((1..=20).contains(&attempt.roll));
+        assert_eq!(attempt.player_current_health, 40);
+
+        assert!(load_session(&conn, user_id).unwrap().is_none());
+        let in_combat: bool = scalar(&conn, "SELECT in_combat FROM user_dungeon_progress WHERE user_id = ?", [user_id]);
+        assert!(!in_combat);
+    }
+
+    #[test]
+    fn a_failed_escape_can_lose_the_fight() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("runner").stats(1, 1, 30).health(50, 50).defense(5).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        conn.execute("UPDATE enemy_types SET base_damage = 40 WHERE id = 'wolf'", []).unwrap();
+        begin(&conn, user_id, &seeded_enemy(&conn, "wolf")).unwrap();
+
+        // Without the challenge there's no dexterity bonus, and a d20 can't reach DC 30.
+        // The free attack is the wolf's 40 damage against half of 5 defense.
+        let attempt = attempt_escape(&conn, &mut rng, user_id, false).unwrap();
+        assert!(!attempt.escaped);
+        assert_eq!((attempt.modifier, attempt.dc), (0, 30));
+        assert_eq!((attempt.damage, attempt.player_current_health, attempt.player_defeated), (38, 12, false));
+        assert_eq!(load_session(&conn, user_id).unwrap().unwrap().status, "active");
+
+        let attempt = attempt_escape(&conn, &mut rng, user_id, false).unwrap();
+        assert_eq!((attempt.player_current_health, attempt.player_defeated), (0, true));
+        assert_eq!(load_session(&conn, user_id).unwrap().unwrap().status, "lost");
+
+        // Running off doesn't dodge the defeat
+        let too_late = attempt_escape(&conn, &mut rng, user_id, true).unwrap_err();
+        assert!(matches!(too_late, AppError::Validation(_)));
+        resolve_defeat(&conn, user_id).unwrap();
+        assert!(load_session(&conn, user_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn a_fight_in_progress_or_lost_cant_be_restarted() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("reroller").health(2, 50).luck(0.0, 0.0).currency(250, 0).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        let wolf = seeded_enemy(&conn, "wolf");
+
+        begin(&conn, user_id, &wolf).unwrap();
+        let reroll = begin(&conn, user_id, &seeded_enemy(&conn, "rat")).unwrap_err();
+        assert!(matches!(reroll, AppError::Validation(_)));
+        assert_eq!(load_session(&conn, user_id).unwrap().unwrap().encounter.enemy_id, "wolf");
+
+        assert!(execute_turn(&conn, &mut rng, user_id, "basic_attack", true).unwrap().player_defeated);
+        let skip_defeat = begin(&conn, user_id, &wolf).unwrap_err();
+        assert!(matches!(skip_defeat, AppError::Validation(_)));
+        assert_eq!(load_session(&conn, user_id).unwrap().unwrap().status, "lost");
+
+        // Once the defeat is paid for the next fight can start
+        resolve_defeat(&conn, user_id).unwrap();
+        begin(&conn, user_id, &wolf).unwrap();
+    }
+
+    #[test]
+    fn only_enemies_of_the_current_floor_can_be_fought() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("tourist").stats(1, 1, 30).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut run = |conn: &Connection| assert!(attempt_escape(conn, &mut rng, user_id, true).unwrap().escaped);
+        conn.execute(
+            "INSERT OR IGNORE INTO dungeon_floors (floor_number, name) VALUES (2, 'The Deep')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO enemy_types
+                (id, name, description, base_health, base_damage, behavior_type, gold_drop_min, gold_drop_max, xp_reward, icon)
+             VALUES ('deep_horror', 'Deep Horror', '', 500, 50, 'aggressive', 100, 200, 1000, '')",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO floor_enemies (floor_number, enemy_id) VALUES (2, 'deep_horror')", [])
+            .unwrap();
+        let horror = seeded_enemy(&conn, "deep_horror");
+
+        let too_deep = begin(&conn, user_id, &horror).unwrap_err();
+        assert!(matches!(too_deep, AppError::Validation(_)));
+        assert!(load_session(&conn, user_id).unwrap().is_none());
+
+        // A floor 1 boss and an enemy a floor 1 story choice sends are both fair game
+        let rat_king = Encounter::boss(&load_boss(&conn, "giant_rat_king").unwrap());
+        begin(&conn, user_id, &rat_king).unwrap();
+        run(&conn);
+        let story_enemy: String = scalar(
+            &conn,
+            "SELECT o.enemy_id FROM narrative_outcomes o
+             JOIN narrative_choices c ON c.id = o.choice_id
+             JOIN narrative_locations l ON l.id = c.location_id
+             WHERE o.triggers_combat AND l.floor_number = 1
+               AND o.enemy_id NOT IN (SELECT enemy_id FROM floor_enemies)
+               AND o.enemy_id IN (SELECT id FROM enemy_types)
+             LIMIT 1",
+            [],
+        );
+        begin(&conn, user_id, &seeded_enemy(&conn, &story_enemy)).unwrap();
+        run(&conn);
+
+        conn.execute("UPDATE user_dungeon_progress SET current_floor = 2 WHERE user_id = ?", [user_id])
+            .unwrap();
+        begin(&conn, user_id, &horror).unwrap();
+        run(&conn);
+        let left_behind = begin(&conn, user_id, &rat_king).unwrap_err();
+        assert!(matches!(left_behind, AppError::Validation(_)));
+    }
+
+    #[test]
+    fn only_unlocked_abilities_can_be_used() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("novice").create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        begin(&conn, user_id, &seeded_enemy(&conn, "wolf")).unwrap();
+
+        let locked = execute_turn(&conn, &mut rng, user_id, "whirlwind", true).unwrap_err();
+        assert!(matches!(locked, AppError::Validation(_)));
+        assert_eq!(load_session(&conn, user_id).unwrap().unwrap().turn, 0);
+    }
+
+    #[test]
+    fn abilities_recharge_for_their_cooldown_before_reuse() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("spinner").health(100, 100).mana(50, 50).create(&conn);
+        unlock_ability(&conn, user_id, "whirlwind");
+        let mut rng = StdRng::seed_from_u64(3);
+        begin(&conn, user_id, &seeded_enemy(&conn, "wolf")).unwrap();
+
+        // Whirlwind has a two turn cooldown: used on turn 1, ready again on turn 4
+        execute_turn(&conn, &mut rng, user_id, "whirlwind", true).unwrap();
+        for _ in 0..2 {
+            let recharging = execute_turn(&conn, &mut rng, user_id, "whirlwind", true).unwrap_err();
+            assert!(matches!(recharging, AppError::Validation(_)));
+            execute_turn(&conn, &mut rng, user_id, "basic_attack", true).unwrap();
+        }
+        let turn = execute_turn(&conn, &mut rng, user_id, "whirlwind", true).unwrap();
+        assert_eq!(turn.turn_number, 4);
+
+        let session = load_session(&conn, user_id).unwrap().unwrap();
+        assert_eq!(session.cooldowns.get("whirlwind"), Some(&7));
+        assert!(!session.cooldowns.contains_key("basic_attack"));
+    }
+
+    #[test]
+    fn a_dodged_counterattack_is_reported() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("nimble").luck(0.0, 1.0).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        begin(&conn, user_id, &seeded_enemy(&conn, "wolf")).unwrap();
+
+        let turn = execute_turn(&conn, &mut rng, user_id, "basic_attack", true).unwrap();
+        assert!(turn.is_dodged);
+        assert_eq!(turn.player_damage_taken, 0);
+    }
+
+    #[test]
+    fn the_character_is_locked_until_the_fight_is_settled() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("patient").health(2, 50).luck(0.0, 0.0).create(&conn);
+        let mut rng = StdRng::seed_from_u64(3);
+        ensure_out_of_combat(&conn, user_id).unwrap();
+
+        begin(&conn, user_id, &seeded_enemy(&conn, "wolf")).unwrap();
+        assert!(matches!(ensure_out_of_combat(&conn, user_id), Err(AppError::Validation(_))));
+
+        assert!(execute_turn(&conn, &mut rng, user_id, "basic_attack", true).unwrap().player_defeated);
+        assert!(matches!(ensure_out_of_combat(&conn, user_id), Err(AppError::Validation(_))));
+
+        resolve_defeat(&conn, user_id).unwrap();
+        ensure_out_of_combat(&conn, user_id).unwrap();
+    }
+}
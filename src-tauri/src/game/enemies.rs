@@ -0,0 +1,114 @@
+use rusqlite::{params, Connection, Result as SqlResult, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ResultExt};
+
+// ============================================================================
+// ENEMIES
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EnemyType {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub base_health: i64,
+    pub base_damage: i64,
+    pub base_defense: i64,
+    pub behavior_type: String,
+    pub gold_drop_min: i64,
+    pub gold_drop_max: i64,
+    pub xp_reward: i64,
+    pub loot_table: Option<String>,
+    pub icon: String,
+    pub ascii_art: Option<String>,
+    pub created_at: String,
+}
+
+impl EnemyType {
+    pub fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(EnemyType {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            base_health: row.get(3)?,
+            base_damage: row.get(4)?,
+            base_defense: row.get(5)?,
+            behavior_type: row.get(6)?,
+            gold_drop_min: row.get(7)?,
+            gold_drop_max: row.get(8)?,
+            xp_reward: row.get(9)?,
+            loot_table: row.get(10)?,
+            icon: row.get(11)?,
+            ascii_art: row.get(12)?,
+            created_at: row.get(13)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BossEnemy {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub floor_number: i64,
+    pub health: i64,
+    pub damage: i64,
+    pub defense: i64,
+    pub abilities: String,
+    pub phases: Option<String>,
+    pub gold_reward: i64,
+    pub xp_reward: i64,
+    pub guaranteed_loot: Option<String>,
+    pub icon: String,
+    pub ascii_art: Option<String>,
+    pub created_at: String,
+}
+
+impl BossEnemy {
+    pub fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(BossEnemy {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            floor_number: row.get(3)?,
+            health: row.get(4)?,
+            damage: row.get(5)?,
+            defense: row.get(6)?,
+            abilities: row.get(7)?,
+            phases: row.get(8)?,
+            gold_reward: row.get(9)?,
+            xp_reward: row.get(10)?,
+            guaranteed_loot: row.get(11)?,
+            icon: row.get(12)?,
+            ascii_art: row.get(13)?,
+            created_at: row.get(14)?,
+        })
+    }
+}
+
+/// A boss by id, for starting and paying out boss fights
+pub fn load_boss(conn: &Connection, boss_id: &str) -> Result<BossEnemy, AppError> {
+    conn.query_row(
+        "SELECT id, name, description, floor_number, health, damage, defense, abilities,
+                phases, gold_reward, xp_reward, guaranteed_loot, icon, ascii_art, created_at
+         FROM boss_enemies
+         WHERE id = ?",
+        params![boss_id],
+        BossEnemy::from_row,
+    )
+    .context("Failed to get boss")
+}
+
+/// An enemy type by id, for starting and paying out fights
+pub fn load_enemy(conn: &Connection, enemy_id: &str) -> Result<EnemyType, AppError> {
+    conn.query_row(
+        "SELECT id, name, description, base_health, base_damage, base_defense, behavior_type,
+                gold_drop_min, gold_drop_max, xp_reward, loot_table, icon, ascii_art, created_at
+         FROM enemy_types
+         WHERE id = ?",
+        params![enemy_id],
+        EnemyType::from_row,
+    )
+    .context("Failed to get enemy by id")
+}
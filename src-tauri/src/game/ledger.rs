@@ -0,0 +1,275 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ResultExt};
+
+// ============================================================================
+// Economy Ledger
+// ============================================================================
+//
+// Every change to a gold, gem, dungeon gold or XP balance goes through post(),
+// which updates the balance and appends a ledger_entries row recording where the
+// change came from and the balance after it. Balances a row is created with are
+// recorded by triggers (migrations/048_economy_ledger.sql), so a balance always
+// equals the sum of its entries; reconcile() lists any that don't.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Account {
+    /// user_currency.gold, earned across the app
+    Gold,
+    /// user_currency.gems
+    Gems,
+    /// character_stats.current_gold, spent in the dungeon shop
+    DungeonGold,
+    /// user_dungeon_progress.total_xp_earned, which character levels are computed from
+    Xp,
+}
+
+impl Account {
+    pub const ALL: [Account; 4] = [Account::Gold, Account::Gems, Account::DungeonGold, Account::Xp];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Account::Gold => "gold",
+            Account::Gems => "gems",
+            Account::DungeonGold => "dungeon_gold",
+            Account::Xp => "xp",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Account, AppError> {
+        Account::ALL
+            .into_iter()
+            .find(|account| account.as_str() == name)
+            .ok_or_else(|| AppError::Validation(format!("Unknown account '{}'", name)))
+    }
+
+    /// (table, balance column, lifetime earned column)
+    fn columns(self) -> (&'static str, &'static str, Option<&'static str>) {
+        match self {
+            Account::Gold => ("user_currency", "gold", Some("lifetime_gold_earned")),
+            Account::Gems => ("user_currency", "gems", Some("lifetime_gems_earned")),
+            Account::DungeonGold => ("character_stats", "current_gold", None),
+            Account::Xp => ("user_dungeon_progress", "total_xp_earned", None),
+        }
+    }
+}
+
+impl FromSql for Account {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        Account::parse(value.as_str()?).map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+/// What caused a change: a kind such as "combat_victory" and the enemy, item,
+/// outcome or achievement involved
+#[derive(Debug, Clone, Copy)]
+pub struct Source<'a> {
+    pub kind: &'a str,
+    pub id: Option<&'a str>,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(kind: &'a str) -> Self {
+        Source { kind, id: None }
+    }
+
+    pub fn with_id(kind: &'a str, id: &'a str) -> Self {
+        Source { kind, id: Some(id) }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    pub id: i64,
+    pub account: Account,
+    pub amount: i64,
+    pub balance_after: i64,
+    pub source_type: String,
+    pub source_id: Option<String>,
+    pub created_at: String,
+}
+
+/// A balance that differs from the sum of its ledger entries
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Discrepancy {
+    pub user_id: i64,
+    pub account: Account,
+    pub balance: i64,
+    pub ledger_total: i64,
+}
+
+pub fn balance(conn: &Connection, user_id: i64, account: Account) -> Result<i64, AppError> {
+    let (table, column, _) = account.columns();
+    conn.query_row(&format!("SELECT {} FROM {} WHERE user_id = ?", column, table), params![user_id], |row| row.get(0))
+        .optional()
+        .with_context(|| format!("Failed to read {} balance", account.as_str()))?
+        .ok_or_else(|| AppError::NotFound(format!("User {} has no {} balance", user_id, account.as_str())))
+}
+
+/// Apply a signed change and record it, returning the new balance. Balances never go negative.
+fn post(conn: &Connection, user_id: i64, account: Account, amount: i64, source: Source) -> Result<i64, AppError> {
+    let current = balance(conn, user_id, account)?;
+    let balance_after = current + amount;
+    if balance_after < 0 {
+        return Err(AppError::InsufficientFunds(format!(
+            "Insufficient {} (have: {}, need: {})",
+            account.as_str(),
+            current,
+            -amount
+        )));
+    }
+
+    let (table, column, lifetime) = account.columns();
+    let lifetime = lifetime.map(|lifetime| format!(", {lifetime} = {lifetime} + {}", amount.max(0))).unwrap_or_default();
+    conn.execute(
+        &format!("UPDATE {table} SET {column} = ?{lifetime}, updated_at = CURRENT_TIMESTAMP WHERE user_id = ?"),
+        params![balance_after, user_id],
+    )
+    .with_context(|| format!("Failed to update {} balance", account.as_str()))?;
+
+    conn.execute(
+        "INSERT INTO ledger_entries (user_id, account, amount, balance_after, source_type, source_id)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![user_id, account.as_str(), amount, balance_after, source.kind, source.id],
+    )
+    .context("Failed to record ledger entry")?;
+
+    Ok(balance_after)
+}
+
+fn check_amount(amount: i64) -> Result<(), AppError> {
+    if amount < 0 {
+        return Err(AppError::Validation(format!("Amount must not be negative, got {}", amount)));
+    }
+    Ok(())
+}
+
+/// Add to a balance, returning the new balance
+pub fn credit(conn: &Connection, user_id: i64, account: Account, amount: i64, source: Source) -> Result<i64, AppError> {
+    check_amount(amount)?;
+    post(conn, user_id, account, amount, source)
+}
+
+/// Take from a balance, failing with InsufficientFunds if it doesn't cover the amount
+pub fn debit(conn: &Connection, user_id: i64, account: Account, amount: i64, source: Source) -> Result<i64, AppError> {
+    check_amount(amount)?;
+    post(conn, user_id, account, -amount, source)
+}
+
+/// Take as much of the amount as the balance holds, returning what was taken
+pub fn debit_up_to(conn: &Connection, user_id: i64, account: Account, amount: i64, source: Source) -> Result<i64, AppError> {
+    check_amount(amount)?;
+    let taken = amount.min(balance(conn, user_id, account)?);
+    if taken > 0 {
+        post(conn, user_id, account, -taken, source)?;
+    }
+    Ok(taken)
+}
+
+/// Move a balance to an exact value, recording the difference
+pub fn set_balance(conn: &Connection, user_id: i64, account: Account, target: i64, source: Source) -> Result<i64, AppError> {
+    check_amount(target)?;
+    let difference = target - balance(conn, user_id, account)?;
+    if difference != 0 {
+        post(conn, user_id, account, difference, source)?;
+    }
+    Ok(target)
+}
+
+/// A user's entries, newest first, optionally for one account
+pub fn statement(conn: &Connection, user_id: i64, account: Option<Account>, limit: i64) -> Result<Vec<LedgerEntry>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, account, amount, balance_after, source_type, source_id, created_at
+         FROM ledger_entries
+         WHERE user_id = ?1 AND (?2 IS NULL OR account = ?2)
+         ORDER BY id DESC
+         LIMIT ?3",
+    )?;
+    let entries = stmt
+        .query_map(params![user_id, account.map(Account::as_str), limit], |row| {
+            Ok(LedgerEntry {
+                id: row.get(0)?,
+                account: row.get(1)?,
+                amount: row.get(2)?,
+                balance_after: row.get(3)?,
+                source_type: row.get(4)?,
+                source_id: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()
+        .context("Failed to read ledger")?;
+    Ok(entries)
+}
+
+/// Every balance, for one user or all, that doesn't equal the sum of its ledger entries
+pub fn reconcile(conn: &Connection, user_id: Option<i64>) -> Result<Vec<Discrepancy>, AppError> {
+    let mut discrepancies = Vec::new();
+    for account in Account::ALL {
+        let (table, column, _) = account.columns();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT b.user_id, b.{column},
+                    COALESCE((SELECT SUM(amount) FROM ledger_entries l WHERE l.user_id = b.user_id AND l.account = ?1), 0)
+             FROM {table} b
+             WHERE ?2 IS NULL OR b.user_id = ?2
+             ORDER BY b.user_id"
+        ))?;
+        let rows = stmt
+            .query_map(params![account.as_str(), user_id], |row| {
+                Ok(Discrepancy { user_id: row.get(0)?, account, balance: row.get(1)?, ledger_total: row.get(2)? })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to reconcile ledger")?;
+        discrepancies.extend(rows.into_iter().filter(|row| row.balance != row.ledger_total));
+    }
+    Ok(discrepancies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{scalar, seeded_db, TestUser};
+
+    #[test]
+    fn postings_update_balances_and_reconcile() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("saver").currency(50, 5).create(&conn);
+        assert!(reconcile(&conn, Some(user_id)).unwrap().is_empty());
+
+        assert_eq!(credit(&conn, user_id, Account::Gold, 30, Source::with_id("quest", "q1")).unwrap(), 80);
+        assert_eq!(debit(&conn, user_id, Account::Gems, 2, Source::new("shop_purchase")).unwrap(), 3);
+        assert_eq!(debit_up_to(&conn, user_id, Account::DungeonGold, 1_000, Source::new("penalty")).unwrap(), 100);
+        assert_eq!(set_balance(&conn, user_id, Account::DungeonGold, 40, Source::new("dev_reset")).unwrap(), 40);
+
+        assert_eq!(scalar::<i64>(&conn, "SELECT lifetime_gold_earned FROM user_currency WHERE user_id = ?", [user_id]), 30);
+        assert!(reconcile(&conn, None).unwrap().is_empty());
+
+        let entries = statement(&conn, user_id, Some(Account::Gold), 10).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].amount, entries[0].balance_after), (30, 80));
+        assert_eq!((entries[0].source_type.as_str(), entries[0].source_id.as_deref()), ("quest", Some("q1")));
+        assert_eq!(entries[1].source_type, "opening_balance");
+    }
+
+    #[test]
+    fn overdrafts_are_refused_and_raw_updates_are_caught() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("spender").currency(10, 0).create(&conn);
+
+        let err = debit(&conn, user_id, Account::Gold, 11, Source::new("shop_purchase")).unwrap_err();
+        assert!(matches!(err, AppError::InsufficientFunds(_)));
+        assert!(matches!(credit(&conn, user_id, Account::Gold, -5, Source::new("x")), Err(AppError::Validation(_))));
+        assert_eq!(balance(&conn, user_id, Account::Gold).unwrap(), 10);
+
+        conn.execute("UPDATE user_currency SET gold = gold + 7 WHERE user_id = ?", [user_id]).unwrap();
+        assert_eq!(
+            reconcile(&conn, Some(user_id)).unwrap(),
+            [Discrepancy { user_id, account: Account::Gold, balance: 17, ledger_total: 10 }]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use rusqlite::{params, Connection};
+
+use crate::error::{AppError, ResultExt};
+
+/// XP needed to advance from a level is the level times this
+pub const XP_PER_LEVEL: i64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelUp {
+    pub old_level: i64,
+    pub new_level: i64,
+    pub levels_gained: i64,
+}
+
+/// Level reached from `level` with `total_xp` to spend. Level 1 needs 100 XP,
+/// level 2 another 200, and so on.
+pub fn level_for_xp(level: i64, total_xp: i64) -> i64 {
+    let mut new_level = level;
+    let mut xp_remaining = total_xp;
+
+    while xp_remaining >= new_level * XP_PER_LEVEL {
+        xp_remaining -= new_level * XP_PER_LEVEL;
+        new_level += 1;
+    }
+
+    new_level
+}
+
+/// Apply the level-ups earned by `xp_gained`: one stat point per level, plus any
+/// abilities the new level unlocks. Reads XP from user_dungeon_progress before the
+/// caller records the gain there.
+pub fn apply_xp(conn: &Connection, user_id: i64, xp_gained: i64) -> Result<LevelUp, AppError> {
+    let (current_level, current_xp): (i64, i64) = conn
+        .query_row(
+            "SELECT level, COALESCE((SELECT total_xp_earned FROM user_dungeon_progress WHERE user_id = ?), 0)
+             FROM character_stats WHERE user_id = ?",
+            params![user_id, user_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .context("Failed to get current level")?;
+
+    let new_level = level_for_xp(current_level, current_xp + xp_gained);
+    let levels_gained = new_level - current_level;
+
+    if levels_gained > 0 {
+        // Each level grants stat points - player chooses how to spend them
+        conn.execute(
+            "UPDATE character_stats
+             SET level = ?,
+                 stat_points_available = stat_points_available + ?,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE user_id = ?",
+            params![new_level, levels_gained, user_id],
+        )
+        .context("Failed to apply level up")?;
+
+        conn.execute(
+            "INSERT OR IGNORE INTO user_abilities (user_id, ability_id, unlocked_at)
+             SELECT ?, id, CURRENT_TIMESTAMP
+             FROM abilities
+             WHERE required_level <= ?",
+            params![user_id, new_level],
+        )
+        .context("Failed to unlock abilities")?;
+
+        log::info!(
+            "User {} leveled up! {} -> {} (+{} stat points)",
+            user_id, current_level, new_level, levels_gained
+        );
+    }
+
+    Ok(LevelUp {
+        old_level: current_level,
+        new_level,
+        levels_gained,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_need_increasing_xp() {
+        assert_eq!(level_for_xp(1, 99), 1);
+        assert_eq!(level_for_xp(1, 100), 2);
+        assert_eq!(level_for_xp(1, 299), 2);
+        assert_eq!(level_for_xp(1, 300), 3);
+        assert_eq!(level_for_xp(3, 250), 3);
+    }
+}
@@ -0,0 +1,20 @@
+// ============================================================================
+// Game Rules
+// ============================================================================
+//
+// Characters, enemies, combat, leveling, rewards, narrative outcomes and the
+// shop, independent of Tauri. Services take a rusqlite Connection plus an
+// explicit RNG and clock, so the same rules run from commands, tools and tests
+// against in-memory SQLite.
+// The #[tauri::command] functions are thin wrappers that borrow a pooled
+// connection and pass in thread_rng() and the system clock.
+
+pub mod character;
+pub mod clock;
+pub mod combat;
+pub mod enemies;
+pub mod ledger;
+pub mod leveling;
+pub mod narrative;
+pub mod rewards;
+pub mod shop;
@@ -0,0 +1,608 @@
+use rand::Rng;
+use rusqlite::{params, Connection, Result as SqlResult, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ResultExt};
+use crate::game::ledger::{self, Account, Source};
+use crate::game::rewards;
+
+// ============================================================================
+// NARRATIVE LOCATIONS
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NarrativeLocation {
+    pub id: String,
+    pub floor_number: i64,
+    pub name: String,
+    pub description: String,
+    pub location_type: String,
+    pub is_repeatable: bool,
+    pub icon: String,
+    pub created_at: String,
+}
+
+impl NarrativeLocation {
+    pub fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(NarrativeLocation {
+            id: row.get(0)?,
+            floor_number: row.get(1)?,
+            name: row.get(2)?,
+            description: row.get(3)?,
+            location_type: row.get(4)?,
+            is_repeatable: row.get(5)?,
+            icon: row.get(6)?,
+            created_at: row.get(7)?,
+        })
+    }
+}
+
+// ============================================================================
+// NARRATIVE CHOICES
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NarrativeChoice {
+    pub id: String,
+    pub location_id: String,
+    pub choice_text: String,
+    pub requires_skill_check: bool,
+    pub skill_type: Option<String>,
+    pub skill_dc: Option<i64>,
+    pub challenge_action_type: Option<String>,
+    pub display_order: i64,
+    pub icon: Option<String>,
+    pub requires_flag: Option<String>,
+    pub created_at: String,
+}
+
+impl NarrativeChoice {
+    pub fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(NarrativeChoice {
+            id: row.get(0)?,
+            location_id: row.get(1)?,
+            choice_text: row.get(2)?,
+            requires_skill_check: row.get(3)?,
+            skill_type: row.get(4)?,
+            skill_dc: row.get(5)?,
+            challenge_action_type: row.get(6)?,
+            display_order: row.get(7)?,
+            icon: row.get(8)?,
+            requires_flag: row.get(9)?,
+            created_at: row.get(10)?,
+        })
+    }
+}
+
+// ============================================================================
+// NARRATIVE OUTCOMES
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NarrativeOutcome {
+    pub id: String,
+    pub choice_id: String,
+    pub outcome_type: String,
+    pub description: String,
+    pub next_location_id: Option<String>,
+    pub rewards: Option<String>,
+    pub penalties: Option<String>,
+    pub sets_flags: Option<String>,
+    pub triggers_combat: bool,
+    pub enemy_id: Option<String>,
+    pub enemy_count: i64,
+    pub created_at: String,
+}
+
+impl NarrativeOutcome {
+    pub fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(NarrativeOutcome {
+            id: row.get(0)?,
+            choice_id: row.get(1)?,
+            outcome_type: row.get(2)?,
+            description: row.get(3)?,
+            next_location_id: row.get(4)?,
+            rewards: row.get(5)?,
+            penalties: row.get(6)?,
+            sets_flags: row.get(7)?,
+            triggers_combat: row.get(8)?,
+            enemy_id: row.get(9)?,
+            enemy_count: row.get(10)?,
+            created_at: row.get(11)?,
+        })
+    }
+}
+
+// ============================================================================
+// USER NARRATIVE PROGRESS
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserNarrativeProgress {
+    pub user_id: i64,
+    pub floor_number: i64,
+    pub current_location_id: Option<String>,
+    pub visited_locations: Option<String>,
+    pub completed_choices: Option<String>,
+    pub story_flags: Option<String>,
+    pub last_roll: Option<i64>,
+    pub last_skill_type: Option<String>,
+    pub last_skill_dc: Option<i64>,
+    pub last_modifier: Option<i64>,
+    pub last_challenge_success: Option<bool>,
+    pub total_skill_checks: i64,
+    pub successful_skill_checks: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl UserNarrativeProgress {
+    pub fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(UserNarrativeProgress {
+            user_id: row.get(0)?,
+            floor_number: row.get(1)?,
+            current_location_id: row.get(2)?,
+            visited_locations: row.get(3)?,
+            completed_choices: row.get(4)?,
+            story_flags: row.get(5)?,
+            last_roll: row.get(6)?,
+            last_skill_type: row.get(7)?,
+            last_skill_dc: row.get(8)?,
+            last_modifier: row.get(9)?,
+            last_challenge_success: row.get(10)?,
+            total_skill_checks: row.get(11)?,
+            successful_skill_checks: row.get(12)?,
+            created_at: row.get(13)?,
+            updated_at: row.get(14)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillCheckResult {
+    pub dice_roll: i64,
+    pub stat_modifier: i64,
+    pub challenge_success: bool,
+    pub applied_modifier: i64,  // 0 if challenge failed, stat_modifier if succeeded
+    pub total_roll: i64,  // dice_roll + applied_modifier
+    pub dc: i64,
+    pub check_passed: bool,
+    pub outcome_type: String,  // 'critical_success', 'success', 'failure', 'critical_failure'
+    pub outcome: NarrativeOutcome,
+}
+
+pub fn roll_d20(rng: &mut impl Rng) -> i64 {
+    rng.gen_range(1..=20)
+}
+
+/// A natural 20 or 1 decides the outcome on its own; otherwise the total against the DC does
+pub fn outcome_type_for_roll(dice_roll: i64, check_passed: bool) -> &'static str {
+    if dice_roll == 20 {
+        "critical_success"
+    } else if dice_roll == 1 {
+        "critical_failure"
+    } else if check_passed {
+        "success"
+    } else {
+        "failure"
+    }
+}
+
+/// The user's narrative progress, created at floor 1 on first access
+pub fn load_progress(conn: &Connection, user_id: i64) -> Result<UserNarrativeProgress, AppError> {
+    conn.execute(
+        "INSERT OR IGNORE INTO user_narrative_progress (user_id, floor_number)
+         VALUES (?, 1)",
+        params![user_id],
+    )
+    .context("Failed to initialize narrative progress")?;
+
+    conn.query_row(
+        "SELECT user_id, floor_number, current_location_id, visited_locations, completed_choices,
+                story_flags, last_roll, last_skill_type, last_skill_dc, last_modifier,
+                last_challenge_success, total_skill_checks, successful_skill_checks,
+                created_at, updated_at
+         FROM user_narrative_progress
+         WHERE user_id = ?",
+        params![user_id],
+        UserNarrativeProgress::from_row,
+    )
+    .context("Failed to get narrative progress")
+}
+
+fn find_outcome(conn: &Connection, choice_id: &str, outcome_type: &str) -> rusqlite::Result<NarrativeOutcome> {
+    conn.query_row(
+        "SELECT id, choice_id, outcome_type, description, next_location_id, rewards, penalties,
+                sets_flags, triggers_combat, enemy_id, enemy_count, created_at
+         FROM narrative_outcomes
+         WHERE choice_id = ? AND outcome_type = ?
+         LIMIT 1",
+        params![choice_id, outcome_type],
+        NarrativeOutcome::from_row,
+    )
+}
+
+/// Judge a roll against the choice's DC, record it and apply the matching outcome.
+/// The stat modifier only counts if the player passed the coding challenge.
+pub fn resolve_skill_check(
+    conn: &Connection,
+    user_id: i64,
+    choice_id: &str,
+    dice_roll: i64,
+    stat_modifier: i64,
+    challenge_success: bool,
+) -> Result<SkillCheckResult, AppError> {
+    let choice: NarrativeChoice = conn
+        .query_row(
+            "SELECT id, location_id, choice_text, requires_skill_check, skill_type, skill_dc,
+                    challenge_action_type, display_order, icon, requires_flag, created_at
+             FROM narrative_choices
+             WHERE id = ?",
+            params![choice_id],
+            NarrativeChoice::from_row,
+        )
+        .context("Failed to get choice")?;
+
+    let dc = choice.skill_dc.unwrap_or(10);
+    let applied_modifier = if challenge_success { stat_modifier } else { 0 };
+    let total_roll = dice_roll + applied_modifier;
+    let check_passed = total_roll >= dc;
+    let outcome_type = outcome_type_for_roll(dice_roll, check_passed);
+
+    // Fall back to basic success/failure if there is no critical outcome for this choice
+    let outcome = find_outcome(conn, choice_id, outcome_type)
+        .or_else(|_| find_outcome(conn, choice_id, if check_passed { "success" } else { "failure" }))
+        .context("Failed to get outcome")?;
+
+    conn.execute(
+        "UPDATE user_narrative_progress
+         SET last_roll = ?,
+             last_skill_type = ?,
+             last_skill_dc = ?,
+             last_modifier = ?,
+             last_challenge_success = ?,
+             total_skill_checks = total_skill_checks + 1,
+             successful_skill_checks = successful_skill_checks + ?,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![
+            dice_roll,
+            choice.skill_type,
+            dc,
+            applied_modifier,
+            challenge_success,
+            if check_passed { 1 } else { 0 },
+            user_id
+        ],
+    )
+    .context("Failed to update progress")?;
+
+    conn.execute(
+        "INSERT INTO skill_check_history (user_id, choice_id, skill_type, skill_dc, dice_roll,
+                                          stat_modifier, challenge_success, total_roll, check_passed, outcome_type)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            user_id,
+            choice_id,
+            choice.skill_type.unwrap_or_default(),
+            dc,
+            dice_roll,
+            stat_modifier,
+            challenge_success,
+            total_roll,
+            check_passed,
+            outcome_type
+        ],
+    )
+    .context("Failed to record skill check")?;
+
+    log::info!("Applying skill check outcome for user {}", user_id);
+    apply_outcome(conn, user_id, &outcome)?;
+
+    Ok(SkillCheckResult {
+        dice_roll,
+        stat_modifier,
+        challenge_success,
+        applied_modifier,
+        total_roll,
+        dc,
+        check_passed,
+        outcome_type: outcome_type.to_string(),
+        outcome,
+    })
+}
+
+/// Move the user along the story and apply an outcome's flags, rewards and penalties.
+/// Rewards are granted only the first time an outcome is reached, so revisiting a
+/// location can't be farmed; penalties apply every time.
+pub fn apply_outcome(
+    conn: &Connection,
+    user_id: i64,
+    outcome: &NarrativeOutcome,
+) -> Result<UserNarrativeProgress, AppError> {
+    let progress = load_progress(conn, user_id)?;
+
+    let mut completed_choices: Vec<String> = progress
+        .completed_choices
+        .as_deref()
+        .and_then(|completed| serde_json::from_str(completed).ok())
+        .unwrap_or_default();
+
+    // Track completion per outcome rather than per choice
+    let outcome_key = format!("{}:{}", outcome.choice_id, outcome.outcome_type);
+    let already_completed = completed_choices.contains(&outcome_key);
+    if !already_completed {
+        completed_choices.push(outcome_key.clone());
+    }
+    let completed_json = serde_json::to_string(&completed_choices).unwrap_or_else(|_| "[]".to_string());
+
+    if let Some(ref next_location_id) = outcome.next_location_id {
+        let mut visited: Vec<String> = progress
+            .visited_locations
+            .as_deref()
+            .and_then(|visited| serde_json::from_str(visited).ok())
+            .unwrap_or_default();
+
+        if !visited.contains(next_location_id) {
+            visited.push(next_location_id.clone());
+        }
+
+        let mut flags: serde_json::Value = progress
+            .story_flags
+            .as_deref()
+            .and_then(|flags| serde_json::from_str(flags).ok())
+            .unwrap_or(serde_json::json!({}));
+
+        let new_flags = outcome
+            .sets_flags
+            .as_deref()
+            .and_then(|flags| serde_json::from_str::<serde_json::Value>(flags).ok());
+
+        if let (Some(new_flags), Some(flags_obj)) = (new_flags, flags.as_object_mut()) {
+            if let Some(new_flags_obj) = new_flags.as_object() {
+                for (key, value) in new_flags_obj {
+                    flags_obj.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        let visited_json = serde_json::to_string(&visited).unwrap_or_else(|_| "[]".to_string());
+        let flags_json = serde_json::to_string(&flags).unwrap_or_else(|_| "{}".to_string());
+
+        conn.execute(
+            "UPDATE user_narrative_progress
+             SET current_location_id = ?,
+                 visited_locations = ?,
+                 completed_choices = ?,
+                 story_flags = ?,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE user_id = ?",
+            params![next_location_id, visited_json, completed_json, flags_json, user_id],
+        )
+        .context("Failed to update location")?;
+    } else if !already_completed {
+        conn.execute(
+            "UPDATE user_narrative_progress
+             SET completed_choices = ?,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE user_id = ?",
+            params![completed_json, user_id],
+        )
+        .context("Failed to update completed choices")?;
+    }
+
+    if already_completed {
+        log::info!("Outcome {} already completed by user {}, skipping rewards", outcome_key, user_id);
+    } else if let Some(ref rewards_str) = outcome.rewards {
+        log::info!("Applying rewards (first time): {}", rewards_str);
+        if let Ok(rewards) = serde_json::from_str::<serde_json::Value>(rewards_str) {
+            apply_rewards(conn, user_id, &outcome.id, &rewards)?;
+        }
+    }
+
+    if let Some(ref penalties_str) = outcome.penalties {
+        log::info!("Applying penalties: {}", penalties_str);
+        if let Ok(penalties) = serde_json::from_str::<serde_json::Value>(penalties_str) {
+            apply_penalties(conn, user_id, &outcome.id, &penalties)?;
+        }
+    }
+
+    load_progress(conn, user_id)
+}
+
+/// Rewards JSON: `{"gold": 25, "xp": 50, "heal": "full" | 20, "items": ["health_potion_small"]}`
+fn apply_rewards(conn: &Connection, user_id: i64, outcome_id: &str, rewards: &serde_json::Value) -> Result<(), AppError> {
+    let source = Source::with_id("narrative_outcome", outcome_id);
+
+    if let Some(gold) = rewards.get("gold").and_then(|v| v.as_i64()) {
+        log::info!("Awarding {} gold to user {}", gold, user_id);
+        rewards::award_gold(conn, user_id, gold, source)?;
+    }
+
+    if let Some(xp) = rewards.get("xp").and_then(|v| v.as_i64()) {
+        log::info!("Awarding {} XP to user {}", xp, user_id);
+        rewards::award_xp(conn, user_id, xp, source)?;
+    }
+
+    if let Some(heal) = rewards.get("heal") {
+        if heal == "full" {
+            conn.execute(
+                "UPDATE character_stats
+                 SET current_health = max_health
+                 WHERE user_id = ?",
+                params![user_id],
+            )
+            .context("Failed to heal")?;
+        } else if let Some(heal_amount) = heal.as_i64() {
+            conn.execute(
+                "UPDATE character_stats
+                 SET current_health = MIN(current_health + ?, max_health)
+                 WHERE user_id = ?",
+                params![heal_amount, user_id],
+            )
+            .context("Failed to heal")?;
+        }
+    }
+
+    if let Some(items) = rewards.get("items").and_then(|v| v.as_array()) {
+        for item_id in items.iter().filter_map(|item| item.as_str()) {
+            rewards::grant_item(conn, user_id, item_id)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Penalties JSON: `{"damage": 10, "gold": 5}`
+fn apply_penalties(conn: &Connection, user_id: i64, outcome_id: &str, penalties: &serde_json::Value) -> Result<(), AppError> {
+    if let Some(damage) = penalties.get("damage").and_then(|v| v.as_i64()) {
+        log::info!("Applying {} damage to user {}", damage, user_id);
+        let rows_affected = conn
+            .execute(
+                "UPDATE character_stats
+                 SET current_health = MAX(0, current_health - ?)
+                 WHERE user_id = ?",
+                params![damage, user_id],
+            )
+            .context("Failed to apply damage")?;
+        if rows_affected == 0 {
+            log::warn!("No character_stats row found for user {}! Damage was not applied.", user_id);
+        }
+    }
+
+    if let Some(gold_lost) = penalties.get("gold").and_then(|v| v.as_i64()) {
+        log::info!("Removing {} gold from user {}", gold_lost, user_id);
+        let source = Source::with_id("narrative_penalty", outcome_id);
+        ledger::debit_up_to(conn, user_id, Account::DungeonGold, gold_lost, source)?;
+        ledger::debit_up_to(conn, user_id, Account::Gold, gold_lost, source)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::test_support::{equipment_quantity, fail_inserts_into, narrative_progress, scalar, seeded_db, TestUser};
+
+    #[test]
+    fn natural_rolls_override_the_dc() {
+        assert_eq!(outcome_type_for_roll(20, false), "critical_success");
+        assert_eq!(outcome_type_for_roll(1, true), "critical_failure");
+        assert_eq!(outcome_type_for_roll(12, true), "success");
+        assert_eq!(outcome_type_for_roll(12, false), "failure");
+    }
+
+    fn gold(conn: &Connection, user_id: i64) -> (i64, i64) {
+        conn.query_row(
+            "SELECT cs.current_gold, uc.gold
+             FROM character_stats cs JOIN user_currency uc ON uc.user_id = cs.user_id
+             WHERE cs.user_id = ?",
+            [user_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn passed_check_applies_rewards_flags_and_location_once() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("lifter").dungeon_gold(100).create(&conn);
+        narrative_progress(&conn, user_id, "path_a_barracks_cleared", &[]);
+
+        // 12 + 3 meets DC 13, but only because the challenge was passed
+        let result = resolve_skill_check(&conn, user_id, "barracks_move_beam", 12, 3, true).unwrap();
+        assert!(result.check_passed);
+        assert_eq!(result.outcome.id, "barracks_beam_success");
+
+        let progress = load_progress(&conn, user_id).unwrap();
+        assert_eq!(progress.current_location_id.as_deref(), Some("path_a_barracks_cleared"));
+        assert!(progress.story_flags.unwrap().contains("locker_opened"));
+        assert_eq!(gold(&conn, user_id), (110, 10));
+        assert_eq!(equipment_quantity(&conn, user_id, "leather_armor"), 1);
+
+        // Reaching the same outcome again grants nothing
+        resolve_skill_check(&conn, user_id, "barracks_move_beam", 15, 0, false).unwrap();
+        assert_eq!(gold(&conn, user_id), (110, 10));
+        assert_eq!(equipment_quantity(&conn, user_id, "leather_armor"), 1);
+
+        let checks: i64 = scalar(&conn, "SELECT COUNT(*) FROM skill_check_history WHERE user_id = ?", [user_id]);
+        assert_eq!(checks, 2);
+    }
+
+    #[test]
+    fn interrupted_outcome_is_rolled_back_whole() {
+        let mut conn = seeded_db();
+        let user_id = TestUser::new("jammed").dungeon_gold(100).create(&conn);
+        narrative_progress(&conn, user_id, "path_a_barracks_cleared", &[]);
+
+        // Flags, completion and gold are applied before the armor is granted
+        fail_inserts_into(&conn, "user_equipment_inventory");
+        let result = db::run_unit_of_work(&mut conn, |tx| {
+            resolve_skill_check(tx, user_id, "barracks_move_beam", 12, 3, true)
+        });
+        assert!(result.is_err());
+
+        let progress = load_progress(&conn, user_id).unwrap();
+        assert_eq!(progress.story_flags.as_deref(), Some("{}"));
+        assert_eq!(progress.completed_choices.as_deref(), Some("[]"));
+        assert_eq!(gold(&conn, user_id), (100, 0));
+        let checks: i64 = scalar(&conn, "SELECT COUNT(*) FROM skill_check_history WHERE user_id = ?", [user_id]);
+        assert_eq!(checks, 0);
+        assert!(ledger::reconcile(&conn, Some(user_id)).unwrap().is_empty());
+
+        // With nothing recorded, the same outcome still pays out in full
+        conn.execute_batch("DROP TRIGGER fail_user_equipment_inventory").unwrap();
+        db::run_unit_of_work(&mut conn, |tx| {
+            resolve_skill_check(tx, user_id, "barracks_move_beam", 12, 3, true)
+        })
+        .unwrap();
+        assert_eq!(gold(&conn, user_id), (110, 10));
+        assert_eq!(equipment_quantity(&conn, user_id, "leather_armor"), 1);
+    }
+
+    #[test]
+    fn failed_challenge_drops_the_modifier() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("fumbler").create(&conn);
+        narrative_progress(&conn, user_id, "path_a_barracks_cleared", &[]);
+
+        let result = resolve_skill_check(&conn, user_id, "barracks_move_beam", 12, 3, false).unwrap();
+        assert_eq!(result.applied_modifier, 0);
+        assert!(!result.check_passed);
+        assert_eq!(result.outcome.id, "barracks_beam_fail");
+    }
+
+    #[test]
+    fn penalties_apply_every_time() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("trapped").health(50, 50).create(&conn);
+        narrative_progress(&conn, user_id, "path_b_trapped_hall", &[]);
+
+        // A natural 1 has no outcome of its own here and falls back to failure
+        let result = resolve_skill_check(&conn, user_id, "trap_disarm", 1, 5, true).unwrap();
+        assert_eq!(result.outcome_type, "critical_failure");
+        assert_eq!(result.outcome.id, "trap_disarm_fail");
+
+        resolve_skill_check(&conn, user_id, "trap_disarm", 4, 0, false).unwrap();
+
+        let health: i64 = scalar(&conn, "SELECT current_health FROM character_stats WHERE user_id = ?", [user_id]);
+        assert_eq!(health, 20);
+    }
+
+    #[test]
+    fn xp_rewards_level_up_the_character() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("reader").total_xp(80).create(&conn);
+        narrative_progress(&conn, user_id, "path_a_courtyard", &[]);
+
+        let outcome = find_outcome(&conn, "courtyard_fight", "success").unwrap();
+        apply_outcome(&conn, user_id, &outcome).unwrap();
+
+        let level: i64 = scalar(&conn, "SELECT level FROM character_stats WHERE user_id = ?", [user_id]);
+        let total_xp: i64 = scalar(&conn, "SELECT total_xp_earned FROM user_dungeon_progress WHERE user_id = ?", [user_id]);
+        assert_eq!(level, 2);
+        assert_eq!(total_xp, 125);
+    }
+}
@@ -0,0 +1,146 @@
+use rand::Rng;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ResultExt};
+use crate::game::ledger::{self, Account, Source};
+use crate::game::leveling::{self, LevelUp};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LootEntry {
+    pub item: String,
+    pub chance: f64,
+}
+
+/// Award gold to both the dungeon purse (character_stats.current_gold, spent in
+/// the dungeon shop) and the gamification currency used for tracking
+pub fn award_gold(conn: &Connection, user_id: i64, gold: i64, source: Source) -> Result<(), AppError> {
+    ledger::credit(conn, user_id, Account::DungeonGold, gold, source)?;
+    ledger::credit(conn, user_id, Account::Gold, gold, source)?;
+    Ok(())
+}
+
+/// Award XP, applying any level-ups it earns
+pub fn award_xp(conn: &Connection, user_id: i64, xp: i64, source: Source) -> Result<LevelUp, AppError> {
+    // Level-ups read total_xp_earned, so apply them before recording the gain
+    let level_up = leveling::apply_xp(conn, user_id, xp)?;
+    ledger::credit(conn, user_id, Account::Xp, xp, source)?;
+    Ok(level_up)
+}
+
+/// Item ids that drop from a loot table JSON such as
+/// `[{"item": "health_potion_small", "chance": 0.15}]`. A malformed table drops nothing.
+pub fn roll_loot(loot_table: &str, rng: &mut impl Rng) -> Vec<String> {
+    if loot_table.is_empty() {
+        return Vec::new();
+    }
+
+    match serde_json::from_str::<Vec<LootEntry>>(loot_table) {
+        Ok(entries) => entries
+            .into_iter()
+            .filter(|entry| rng.gen::<f64>() < entry.chance)
+            .map(|entry| entry.item)
+            .collect(),
+        Err(_) => {
+            log::warn!("Failed to parse loot table: {}", loot_table);
+            Vec::new()
+        }
+    }
+}
+
+/// Item ids from a guaranteed loot list such as `["iron_sword"]`. A malformed list drops nothing.
+pub fn guaranteed_loot(loot: &str) -> Vec<String> {
+    if loot.is_empty() {
+        return Vec::new();
+    }
+
+    serde_json::from_str(loot).unwrap_or_else(|_| {
+        log::warn!("Failed to parse guaranteed loot: {}", loot);
+        Vec::new()
+    })
+}
+
+/// Add one of an item to the user's inventory, whichever table it belongs to.
+/// Returns the item's display name, or None if the id is not a known item.
+pub fn grant_item(conn: &Connection, user_id: i64, item_id: &str) -> Result<Option<String>, AppError> {
+    let consumable_name: Option<String> = conn
+        .query_row(
+            "SELECT name FROM consumable_items WHERE id = ?",
+            params![item_id],
+            |row| row.get(0),
+        )
+        .ok();
+
+    if let Some(name) = consumable_name {
+        conn.execute(
+            "INSERT INTO user_consumable_inventory (user_id, consumable_id, quantity, acquired_at)
+             VALUES (?, ?, 1, CURRENT_TIMESTAMP)
+             ON CONFLICT(user_id, consumable_id) DO UPDATE SET
+                 quantity = quantity + 1",
+            params![user_id, item_id],
+        )
+        .context("Failed to add consumable")?;
+
+        log::info!("User {} received consumable: {}", user_id, item_id);
+        return Ok(Some(name));
+    }
+
+    let equipment_name: Option<String> = conn
+        .query_row(
+            "SELECT name FROM equipment_items WHERE id = ?",
+            params![item_id],
+            |row| row.get(0),
+        )
+        .ok();
+
+    let Some(name) = equipment_name else {
+        log::warn!("Item '{}' not found in consumable or equipment tables", item_id);
+        return Ok(None);
+    };
+
+    // user_equipment_inventory has no unique key on the item, so update before inserting
+    let updated = conn
+        .execute(
+            "UPDATE user_equipment_inventory
+             SET quantity = quantity + 1
+             WHERE user_id = ? AND equipment_id = ?",
+            params![user_id, item_id],
+        )
+        .context("Failed to update equipment quantity")?;
+
+    if updated == 0 {
+        conn.execute(
+            "INSERT INTO user_equipment_inventory (user_id, equipment_id, quantity, acquired_at)
+             VALUES (?, ?, 1, CURRENT_TIMESTAMP)",
+            params![user_id, item_id],
+        )
+        .context("Failed to add equipment")?;
+    }
+
+    log::info!("User {} received equipment: {}", user_id, item_id);
+    Ok(Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn loot_chance_bounds() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let table = r#"[{"item": "always", "chance": 1.0}, {"item": "never", "chance": 0.0}]"#;
+
+        for _ in 0..50 {
+            assert_eq!(roll_loot(table, &mut rng), vec!["always".to_string()]);
+        }
+    }
+
+    #[test]
+    fn malformed_loot_table_drops_nothing() {
+        let mut rng = StdRng::seed_from_u64(7);
+        assert!(roll_loot("not json", &mut rng).is_empty());
+        assert!(roll_loot("", &mut rng).is_empty());
+    }
+}
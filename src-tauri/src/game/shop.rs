@@ -0,0 +1,618 @@
+use chrono::Duration;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rusqlite::{params, Connection, Result as SqlResult, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ResultExt};
+use crate::game::clock::{self, Clock};
+use crate::game::ledger::{self, Account, Source};
+use crate::game::character::{load_character_stats, CharacterStats};
+
+/// How long a rolled shop inventory stays on the shelves
+pub const REFRESH_INTERVAL_HOURS: i64 = 2;
+
+#[derive(Debug)]
+struct RarityWeight {
+    tier: String,
+    weight: f64,
+    max_items: i64,
+}
+
+// ============================================================================
+// CONSUMABLE ITEMS
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConsumableItem {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub health_restore: i64,
+    pub mana_restore: i64,
+    pub buff_type: Option<String>,
+    pub buff_value: i64,
+    pub buff_duration_turns: i64,
+    pub buy_price: i64,
+    pub sell_price: i64,
+    pub icon: String,
+    pub tier: String,
+    pub stack_size: i64,
+    pub created_at: String,
+}
+
+impl ConsumableItem {
+    pub fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(ConsumableItem {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            item_type: row.get(3)?,
+            health_restore: row.get(4)?,
+            mana_restore: row.get(5)?,
+            buff_type: row.get(6)?,
+            buff_value: row.get(7)?,
+            buff_duration_turns: row.get(8)?,
+            buy_price: row.get(9)?,
+            sell_price: row.get(10)?,
+            icon: row.get(11)?,
+            tier: row.get(12)?,
+            stack_size: row.get(13)?,
+            created_at: row.get(14)?,
+        })
+    }
+}
+
+// ============================================================================
+// SHOP REFRESH
+// ============================================================================
+
+// Shop refresh state
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShopRefreshState {
+    pub last_refresh_time: String,
+    pub next_refresh_time: String,
+    pub refresh_count: i64,
+}
+
+
+fn ensure_refresh_state(conn: &Connection, clock: &dyn Clock) -> Result<(), AppError> {
+    let now = clock.now();
+    conn.execute(
+        "INSERT OR IGNORE INTO shop_refresh_state (id, last_refresh_time, next_refresh_time, refresh_count)
+         VALUES (1, ?, ?, 0)",
+        params![clock::to_sqlite(now), clock::to_sqlite(now)],
+    )
+    .context("Failed to initialize refresh state")?;
+    Ok(())
+}
+
+/// Restock the shop if its refresh time has passed. Returns whether it restocked.
+pub fn refresh_if_due(conn: &Connection, rng: &mut impl Rng, clock: &dyn Clock) -> Result<bool, AppError> {
+    ensure_refresh_state(conn, clock)?;
+
+    let (last_refresh, next_refresh): (String, String) = conn
+        .query_row(
+            "SELECT last_refresh_time, next_refresh_time FROM shop_refresh_state WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .context("Failed to get refresh state")?;
+
+    // An unreadable refresh time counts as due
+    let due = clock::from_sqlite(&next_refresh).map_or(true, |next| clock.now() >= next);
+
+    if due {
+        log::info!("Shop refresh triggered. Last refresh: {}, Next: {}", last_refresh, next_refresh);
+        refresh_inventory(conn, rng, clock)?;
+    }
+
+    Ok(due)
+}
+
+/// Replace the shop's stock with a random pick from each rarity tier
+pub fn refresh_inventory(conn: &Connection, rng: &mut impl Rng, clock: &dyn Clock) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT OR IGNORE INTO shop_rarity_weights (tier, weight, max_items_per_refresh) VALUES
+         ('common', 0.60, 8),
+         ('uncommon', 0.25, 5),
+         ('rare', 0.10, 3),
+         ('epic', 0.04, 2),
+         ('legendary', 0.01, 1)",
+        [],
+    )
+    .context("Failed to initialize rarity weights")?;
+
+    let mut stmt = conn
+        .prepare("SELECT tier, weight, max_items_per_refresh FROM shop_rarity_weights ORDER BY weight DESC")
+        .context("Failed to get rarity weights")?;
+
+    let weights: Vec<RarityWeight> = stmt
+        .query_map([], |row| {
+            Ok(RarityWeight {
+                tier: row.get(0)?,
+                weight: row.get(1)?,
+                max_items: row.get(2)?,
+            })
+        })
+        .context("Failed to query weights")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to collect weights")?;
+
+    if weights.is_empty() {
+        return Err(AppError::Internal("No rarity weights configured".to_string()));
+    }
+
+    conn.execute("DELETE FROM shop_active_inventory", [])
+        .context("Failed to clear active inventory")?;
+
+    let mut display_order = 0;
+
+    for rarity in &weights {
+        log::debug!("Rolling {} tier (weight {})", rarity.tier, rarity.weight);
+
+        let mut all_items: Vec<(String, String, i64)> = Vec::new();
+
+        let mut eq_stmt = conn
+            .prepare("SELECT id, value FROM equipment_items WHERE tier = ?")
+            .context("Failed to prepare equipment query")?;
+
+        let eq_results = eq_stmt
+            .query_map(params![rarity.tier], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })
+            .context("Failed to query equipment")?;
+
+        for item in eq_results {
+            let (id, price) = item.context("Failed to get equipment item")?;
+            all_items.push(("equipment".to_string(), id, price));
+        }
+
+        let mut cons_stmt = conn
+            .prepare("SELECT id, buy_price FROM consumable_items WHERE tier = ?")
+            .context("Failed to prepare consumable query")?;
+
+        let cons_results = cons_stmt
+            .query_map(params![rarity.tier], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })
+            .context("Failed to query consumables")?;
+
+        for item in cons_results {
+            let (id, price) = item.context("Failed to get consumable item")?;
+            all_items.push(("consumable".to_string(), id, price));
+        }
+
+        // Shuffle and take up to max_items
+        let num_to_select = (rarity.max_items as usize).min(all_items.len());
+        all_items.shuffle(rng);
+
+        for (item_type, item_id, price) in all_items.iter().take(num_to_select) {
+            conn.execute(
+                "INSERT INTO shop_active_inventory (item_type, item_id, display_order, price, stock_quantity)
+                 VALUES (?, ?, ?, ?, NULL)",
+                params![item_type, item_id, display_order, price],
+            )
+            .context("Failed to insert active inventory item")?;
+
+            display_order += 1;
+        }
+
+        log::info!("Selected {} {} items for shop", num_to_select, rarity.tier);
+    }
+
+    let now = clock.now();
+    conn.execute(
+        "UPDATE shop_refresh_state
+         SET last_refresh_time = ?,
+             next_refresh_time = ?,
+             refresh_count = refresh_count + 1,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        params![
+            clock::to_sqlite(now),
+            clock::to_sqlite(now + Duration::hours(REFRESH_INTERVAL_HOURS))
+        ],
+    )
+    .context("Failed to update refresh state")?;
+
+    log::info!("Shop inventory refreshed successfully. {} items added.", display_order);
+
+    Ok(())
+}
+
+pub fn refresh_state(conn: &Connection, clock: &dyn Clock) -> Result<ShopRefreshState, AppError> {
+    let now = clock.now();
+    conn.execute(
+        "INSERT OR IGNORE INTO shop_refresh_state (id, last_refresh_time, next_refresh_time, refresh_count)
+         VALUES (1, ?, ?, 0)",
+        params![
+            clock::to_sqlite(now),
+            clock::to_sqlite(now + Duration::hours(REFRESH_INTERVAL_HOURS))
+        ],
+    )
+    .context("Failed to initialize refresh state")?;
+
+    conn.query_row(
+        "SELECT last_refresh_time, next_refresh_time, refresh_count
+         FROM shop_refresh_state
+         WHERE id = 1",
+        [],
+        |row| {
+            Ok(ShopRefreshState {
+                last_refresh_time: row.get(0)?,
+                next_refresh_time: row.get(1)?,
+                refresh_count: row.get(2)?,
+            })
+        },
+    )
+    .context("Failed to get refresh state")
+}
+
+// ============================================================================
+// TRANSACTIONS
+// ============================================================================
+
+/// Buy `quantity` of a shop_inventory listing with dungeon gold
+pub fn purchase(
+    conn: &Connection,
+    user_id: i64,
+    shop_id: i64,
+    item_type: &str,
+    item_id: &str,
+    quantity: i64,
+) -> Result<CharacterStats, AppError> {
+    let (price, stock, required_level): (Option<i64>, Option<i64>, i64) = conn
+        .query_row(
+            "SELECT price_override, stock_quantity, required_level
+             FROM shop_inventory
+             WHERE id = ? AND available = TRUE",
+            params![shop_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .context("Shop item not found or unavailable")?;
+
+    if let Some(current_stock) = stock {
+        if current_stock < quantity {
+            return Err(AppError::Validation("Insufficient stock".to_string()));
+        }
+    }
+
+    let stats = load_character_stats(conn, user_id)?;
+
+    if stats.level < required_level {
+        return Err(AppError::Validation(format!(
+            "Requires level {}. You are level {}.",
+            required_level, stats.level
+        )));
+    }
+
+    // Listing price, or the item's own price when the listing doesn't override it
+    let base_price_query = if item_type == "equipment" {
+        "SELECT value FROM equipment_items WHERE id = ?"
+    } else {
+        "SELECT buy_price FROM consumable_items WHERE id = ?"
+    };
+    let actual_price = price.unwrap_or_else(|| {
+        conn.query_row(base_price_query, params![item_id], |row| row.get::<_, i64>(0))
+            .unwrap_or(0)
+    });
+
+    let total_cost = actual_price * quantity;
+
+    if stats.current_gold < total_cost {
+        return Err(AppError::InsufficientFunds(format!(
+            "Insufficient gold. Need {} but have {}.",
+            total_cost, stats.current_gold
+        )));
+    }
+
+    ledger::debit(conn, user_id, Account::DungeonGold, total_cost, Source::with_id("dungeon_shop_purchase", item_id))?;
+
+    if item_type == "equipment" {
+        let updated = conn
+            .execute(
+                "UPDATE user_equipment_inventory
+                 SET quantity = quantity + ?
+                 WHERE user_id = ? AND equipment_id = ?",
+                params![quantity, user_id, item_id],
+            )
+            .context("Failed to update equipment quantity")?;
+
+        if updated == 0 {
+            conn.execute(
+                "INSERT INTO user_equipment_inventory (user_id, equipment_id, quantity, acquired_at)
+                 VALUES (?, ?, ?, CURRENT_TIMESTAMP)",
+                params![user_id, item_id, quantity],
+            )
+            .context("Failed to add equipment to inventory")?;
+        }
+    } else {
+        conn.execute(
+            "INSERT INTO user_consumable_inventory (user_id, consumable_id, quantity, acquired_at)
+             VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+             ON CONFLICT(user_id, consumable_id) DO UPDATE SET
+                 quantity = quantity + excluded.quantity",
+            params![user_id, item_id, quantity],
+        )
+        .context("Failed to add consumable to inventory")?;
+    }
+
+    if stock.is_some() {
+        conn.execute(
+            "UPDATE shop_inventory
+             SET stock_quantity = stock_quantity - ?
+             WHERE id = ?",
+            params![quantity, shop_id],
+        )
+        .context("Failed to update stock")?;
+    }
+
+    conn.execute(
+        "INSERT INTO shop_purchases (user_id, item_type, item_id, quantity, price_paid, user_level_at_purchase, purchased_at)
+         VALUES (?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)",
+        params![user_id, item_type, item_id, quantity, total_cost, stats.level],
+    )
+    .context("Failed to record purchase")?;
+
+    log::info!(
+        "User {} purchased {} x{} for {} gold",
+        user_id,
+        item_id,
+        quantity,
+        total_cost
+    );
+
+    load_character_stats(conn, user_id)
+}
+
+/// Sell an owned equipment item back for half its value
+pub fn sell_equipment(conn: &Connection, user_id: i64, equipment_id: &str) -> Result<CharacterStats, AppError> {
+    let inventory_id: i64 = conn
+        .query_row(
+            "SELECT id FROM user_equipment_inventory
+             WHERE user_id = ? AND equipment_id = ?",
+            params![user_id, equipment_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| AppError::NotFound("You don't own this item".to_string()))?;
+
+    let value: i64 = conn
+        .query_row(
+            "SELECT value FROM equipment_items WHERE id = ?",
+            params![equipment_id],
+            |row| row.get(0),
+        )
+        .context("Failed to get item value")?;
+
+    let sell_price = value / 2;
+
+    conn.execute(
+        "DELETE FROM user_equipment_inventory WHERE id = ?",
+        params![inventory_id],
+    )
+    .context("Failed to remove item")?;
+
+    ledger::credit(conn, user_id, Account::DungeonGold, sell_price, Source::with_id("dungeon_shop_sale", equipment_id))?;
+
+    log::info!(
+        "User {} sold {} for {} gold",
+        user_id,
+        equipment_id,
+        sell_price
+    );
+
+    load_character_stats(conn, user_id)
+}
+
+/// Restore value at which a consumable fully restores health or mana
+const FULL_RESTORE: i64 = 999;
+
+/// Drink one of an owned consumable, restoring health and mana up to their maximums
+pub fn use_consumable(conn: &Connection, user_id: i64, consumable_id: &str) -> Result<CharacterStats, AppError> {
+    let (inventory_id, quantity): (i64, i64) = conn
+        .query_row(
+            "SELECT id, quantity FROM user_consumable_inventory
+             WHERE user_id = ? AND consumable_id = ?",
+            params![user_id, consumable_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| AppError::NotFound("You don't own this item".to_string()))?;
+
+    if quantity <= 0 {
+        return Err(AppError::Validation("No items to use".to_string()));
+    }
+
+    let consumable: ConsumableItem = conn
+        .query_row(
+            "SELECT id, name, description, type, health_restore, mana_restore,
+                    buff_type, buff_value, buff_duration_turns, buy_price, sell_price,
+                    icon, tier, stack_size, created_at
+             FROM consumable_items
+             WHERE id = ?",
+            params![consumable_id],
+            ConsumableItem::from_row,
+        )
+        .context("Failed to get consumable")?;
+
+    let stats = load_character_stats(conn, user_id)?;
+
+    let new_health = if consumable.health_restore == FULL_RESTORE {
+        stats.max_health
+    } else {
+        (stats.current_health + consumable.health_restore).min(stats.max_health)
+    };
+
+    let new_mana = if consumable.mana_restore == FULL_RESTORE {
+        stats.max_mana
+    } else {
+        (stats.current_mana + consumable.mana_restore).min(stats.max_mana)
+    };
+
+    conn.execute(
+        "UPDATE character_stats
+         SET current_health = ?, current_mana = ?, updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?",
+        params![new_health, new_mana, user_id],
+    )
+    .context("Failed to update stats")?;
+
+    // Decrease quantity or remove if last one
+    if quantity == 1 {
+        conn.execute(
+            "DELETE FROM user_consumable_inventory WHERE id = ?",
+            params![inventory_id],
+        )
+        .context("Failed to remove consumable")?;
+    } else {
+        conn.execute(
+            "UPDATE user_consumable_inventory
+             SET quantity = quantity - 1
+             WHERE id = ?",
+            params![inventory_id],
+        )
+        .context("Failed to decrease quantity")?;
+    }
+
+    log::info!(
+        "User {} used {}. Health: {} -> {}, Mana: {} -> {}",
+        user_id,
+        consumable.name,
+        stats.current_health,
+        new_health,
+        stats.current_mana,
+        new_mana
+    );
+
+    load_character_stats(conn, user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::clock::FixedClock;
+    use crate::test_support::{
+        consumable_quantity, equipment_quantity, give_consumable, give_equipment, scalar, seeded_db, TestUser,
+    };
+    use chrono::{TimeZone, Utc};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn shop_is_not_restocked_before_refresh_time() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE shop_refresh_state (
+                 id INTEGER PRIMARY KEY CHECK (id = 1),
+                 last_refresh_time TIMESTAMP NOT NULL,
+                 next_refresh_time TIMESTAMP NOT NULL,
+                 refresh_count INTEGER DEFAULT 0,
+                 updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+             );",
+        )
+        .unwrap();
+
+        let opened = Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap();
+        let state = refresh_state(&conn, &FixedClock(opened)).unwrap();
+        assert_eq!(state.next_refresh_time, "2025-03-01 14:00:00");
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let an_hour_later = FixedClock(opened + Duration::hours(1));
+        assert!(!refresh_if_due(&conn, &mut rng, &an_hour_later).unwrap());
+    }
+
+    /// shop_inventory id of a seeded listing
+    fn listing(conn: &Connection, item_id: &str) -> i64 {
+        scalar(conn, "SELECT id FROM shop_inventory WHERE item_id = ?", [item_id])
+    }
+
+    #[test]
+    fn purchase_deducts_gold_and_stacks_inventory() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("shopper").dungeon_gold(100).create(&conn);
+        let shop_id = listing(&conn, "health_potion");
+
+        let stats = purchase(&conn, user_id, shop_id, "consumable", "health_potion", 2).unwrap();
+        assert_eq!(stats.current_gold, 50);
+
+        purchase(&conn, user_id, shop_id, "consumable", "health_potion", 1).unwrap();
+        assert_eq!(consumable_quantity(&conn, user_id, "health_potion"), 3);
+
+        let spent: i64 = scalar(
+            &conn,
+            "SELECT SUM(price_paid) FROM shop_purchases WHERE user_id = ?",
+            [user_id],
+        );
+        assert_eq!(spent, 75);
+    }
+
+    #[test]
+    fn purchase_without_enough_gold_changes_nothing() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("broke").dungeon_gold(20).create(&conn);
+        let shop_id = listing(&conn, "health_potion");
+
+        let err = purchase(&conn, user_id, shop_id, "consumable", "health_potion", 1).unwrap_err();
+        assert!(matches!(err, AppError::InsufficientFunds(_)));
+
+        let gold: i64 = scalar(&conn, "SELECT current_gold FROM character_stats WHERE user_id = ?", [user_id]);
+        assert_eq!(gold, 20);
+        assert_eq!(consumable_quantity(&conn, user_id, "health_potion"), 0);
+    }
+
+    #[test]
+    fn purchase_enforces_level_requirement() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("novice").dungeon_gold(10_000).create(&conn);
+        let shop_id = listing(&conn, "iron_sword");
+
+        let err = purchase(&conn, user_id, shop_id, "equipment", "iron_sword", 1).unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+
+        conn.execute("UPDATE character_stats SET level = 3 WHERE user_id = ?", [user_id]).unwrap();
+        purchase(&conn, user_id, shop_id, "equipment", "iron_sword", 1).unwrap();
+        assert_eq!(equipment_quantity(&conn, user_id, "iron_sword"), 1);
+    }
+
+    #[test]
+    fn equipment_sells_for_half_its_value() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("seller").level(3).dungeon_gold(0).create(&conn);
+        give_equipment(&conn, user_id, "wooden_sword", 1);
+
+        let stats = sell_equipment(&conn, user_id, "wooden_sword").unwrap();
+        assert_eq!(stats.current_gold, 10);
+        assert_eq!(equipment_quantity(&conn, user_id, "wooden_sword"), 0);
+    }
+
+    #[test]
+    fn consumables_restore_up_to_max_and_are_used_up() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("wounded").health(40, 50).create(&conn);
+        give_consumable(&conn, user_id, "minor_health_potion", 1);
+
+        let stats = use_consumable(&conn, user_id, "minor_health_potion").unwrap();
+        assert_eq!(stats.current_health, 50);
+        assert_eq!(consumable_quantity(&conn, user_id, "minor_health_potion"), 0);
+
+        let err = use_consumable(&conn, user_id, "minor_health_potion").unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+    }
+
+    #[test]
+    fn due_refresh_restocks_and_schedules_the_next() {
+        let conn = seeded_db();
+        let mut rng = StdRng::seed_from_u64(1);
+        // The seed schedules the first refresh for the moment the database was built
+        let opened = Utc.with_ymd_and_hms(2099, 3, 1, 12, 0, 0).unwrap();
+
+        assert!(refresh_if_due(&conn, &mut rng, &FixedClock(opened)).unwrap());
+
+        let stocked: i64 = scalar(&conn, "SELECT COUNT(*) FROM shop_active_inventory", []);
+        let state = refresh_state(&conn, &FixedClock(opened)).unwrap();
+        assert!(stocked > 0);
+        assert_eq!(state.next_refresh_time, "2099-03-01 14:00:00");
+    }
+}
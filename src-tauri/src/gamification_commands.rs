@@ -1,7 +1,12 @@
-use rusqlite::{params, Result as SqliteResult};
+use rusqlite::{params, Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 use crate::db;
+use crate::error::{AppError, ResultExt};
+use crate::game::clock::{self, Clock, SystemClock};
+use crate::game::combat::ensure_out_of_combat;
+use crate::game::ledger::{self, Account, Source};
+use crate::profiles;
 
 // ============================================================================
 // TYPES - Currency
@@ -143,7 +148,7 @@ pub struct LevelReward {
 // ============================================================================
 
 #[tauri::command]
-pub fn get_or_create_user(app: AppHandle, username: String) -> Result<i64, String> {
+pub fn get_or_create_user(app: AppHandle, username: String) -> Result<i64, AppError> {
     let conn = db::get_connection(&app)?;
 
     // Try to get existing user
@@ -154,51 +159,22 @@ pub fn get_or_create_user(app: AppHandle, username: String) -> Result<i64, Strin
     );
 
     if let Ok(user_id) = existing_user {
+        profiles::touch(&conn, user_id, &SystemClock)?;
         return Ok(user_id);
     }
 
     // Create new user
     conn.execute(
-        "INSERT INTO users (username) VALUES (?)",
-        params![username]
-    ).map_err(|e| e.to_string())?;
+        "INSERT INTO users (username, last_active_at) VALUES (?, ?)",
+        params![username, clock::to_sqlite(SystemClock.now())]
+    )?;
 
     let user_id = conn.last_insert_rowid();
 
     log::info!("Created new user with id={} and username={}", user_id, username);
 
     // Initialize all user-related tables for new user
-
-    // Currency
-    conn.execute(
-        "INSERT OR IGNORE INTO user_currency (user_id, gold, gems) VALUES (?, 0, 0)",
-        params![user_id]
-    ).map_err(|e| format!("Failed to initialize currency: {}", e))?;
-
-    // Character stats (from migration 009, updated in migration 017)
-    // New defaults: all abilities start at 1, 2 stat points available
-    conn.execute(
-        "INSERT OR IGNORE INTO character_stats (user_id, level, strength, intelligence, dexterity, charisma, stat_points_available) VALUES (?, 1, 1, 1, 1, 1, 2)",
-        params![user_id]
-    ).map_err(|e| format!("Failed to initialize character stats: {}", e))?;
-
-    // Character equipment (from migration 009)
-    conn.execute(
-        "INSERT OR IGNORE INTO character_equipment (user_id) VALUES (?)",
-        params![user_id]
-    ).map_err(|e| format!("Failed to initialize character equipment: {}", e))?;
-
-    // Dungeon progress (from migration 011)
-    conn.execute(
-        "INSERT OR IGNORE INTO user_dungeon_progress (user_id, current_floor, deepest_floor_reached) VALUES (?, 1, 1)",
-        params![user_id]
-    ).map_err(|e| format!("Failed to initialize dungeon progress: {}", e))?;
-
-    // Unlock basic_attack ability (from rpg-dungeon-seed.sql)
-    conn.execute(
-        "INSERT OR IGNORE INTO user_abilities (user_id, ability_id) VALUES (?, 'basic_attack')",
-        params![user_id]
-    ).map_err(|e| format!("Failed to initialize starting ability: {}", e))?;
+    profiles::initialize_user(&conn, user_id)?;
 
     log::info!("New user fully initialized with all required data");
 
@@ -210,14 +186,17 @@ pub fn get_or_create_user(app: AppHandle, username: String) -> Result<i64, Strin
 // ============================================================================
 
 #[tauri::command]
-pub fn get_user_currency(app: AppHandle, user_id: i64) -> Result<UserCurrency, String> {
+pub fn get_user_currency(app: AppHandle, user_id: i64) -> Result<UserCurrency, AppError> {
     let conn = db::get_connection(&app)?;
+    load_currency(&conn, user_id)
+}
 
+fn load_currency(conn: &Connection, user_id: i64) -> Result<UserCurrency, AppError> {
     let mut stmt = conn.prepare(
         "SELECT user_id, gold, gems, lifetime_gold_earned, lifetime_gems_earned
          FROM user_currency
          WHERE user_id = ?"
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     let currency = stmt.query_row(params![user_id], |row| {
         Ok(UserCurrency {
@@ -227,11 +206,21 @@ pub fn get_user_currency(app: AppHandle, user_id: i64) -> Result<UserCurrency, S
             lifetime_gold_earned: row.get(3)?,
             lifetime_gems_earned: row.get(4)?,
         })
-    }).map_err(|e| e.to_string())?;
+    })?;
 
     Ok(currency)
 }
 
+/// Account for a currency_type of "gold" or "gems"
+fn currency_account(currency_type: &str) -> Result<Account, AppError> {
+    match currency_type {
+        "gold" => Ok(Account::Gold),
+        "gems" => Ok(Account::Gems),
+        _ => Err(AppError::Validation("Invalid currency type".to_string())),
+    }
+}
+
+/// Add (or, with a negative amount, take away) currency, recorded in the ledger
 #[tauri::command]
 pub fn add_currency(
     app: AppHandle,
@@ -240,43 +229,31 @@ pub fn add_currency(
     amount: i64,
     reason: String,
     reference_id: Option<String>
-) -> Result<UserCurrency, String> {
-    let conn = db::get_connection(&app)?;
-
-    // Validate currency type
-    if currency_type != "gold" && currency_type != "gems" {
-        return Err("Invalid currency type".to_string());
-    }
-
-    // Get current balance
-    let current_balance: i64 = conn.query_row(
-        &format!("SELECT {} FROM user_currency WHERE user_id = ?", currency_type),
-        params![user_id],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
+) -> Result<UserCurrency, AppError> {
+    db::unit_of_work(&app, |conn| {
+        adjust_currency(conn, user_id, currency_type, amount, reason, reference_id)
+    })
+}
 
-    let new_balance = current_balance + amount;
+fn adjust_currency(
+    conn: &Connection,
+    user_id: i64,
+    currency_type: String,
+    amount: i64,
+    reason: String,
+    reference_id: Option<String>,
+) -> Result<UserCurrency, AppError> {
+    let account = currency_account(&currency_type)?;
+    let source = Source { kind: &reason, id: reference_id.as_deref() };
 
-    // Update balance and lifetime earned
-    conn.execute(
-        &format!(
-            "UPDATE user_currency
-             SET {} = {}, lifetime_{}_earned = lifetime_{}_earned + ?, updated_at = CURRENT_TIMESTAMP
-             WHERE user_id = ?",
-            currency_type, new_balance, currency_type, currency_type
-        ),
-        params![amount.max(0), user_id]
-    ).map_err(|e| e.to_string())?;
-
-    // Record transaction
-    conn.execute(
-        "INSERT INTO currency_transactions (user_id, currency_type, amount, reason, reference_id, balance_after)
-         VALUES (?, ?, ?, ?, ?, ?)",
-        params![user_id, currency_type, amount, reason, reference_id, new_balance]
-    ).map_err(|e| e.to_string())?;
+    if amount >= 0 {
+        ledger::credit(conn, user_id, account, amount, source)?;
+    } else {
+        ledger::debit(conn, user_id, account, -amount, source)?;
+    }
 
     // Return updated currency
-    get_user_currency(app, user_id)
+    load_currency(conn, user_id)
 }
 
 #[tauri::command]
@@ -287,47 +264,24 @@ pub fn spend_currency(
     amount: i64,
     reason: String,
     reference_id: Option<String>
-) -> Result<UserCurrency, String> {
-    let conn = db::get_connection(&app)?;
-
-    // Validate currency type
-    if currency_type != "gold" && currency_type != "gems" {
-        return Err("Invalid currency type".to_string());
-    }
-
-    // Get current balance
-    let current_balance: i64 = conn.query_row(
-        &format!("SELECT {} FROM user_currency WHERE user_id = ?", currency_type),
-        params![user_id],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-
-    if current_balance < amount {
-        return Err(format!("Insufficient {} (have: {}, need: {})", currency_type, current_balance, amount));
-    }
-
-    let new_balance = current_balance - amount;
+) -> Result<UserCurrency, AppError> {
+    db::unit_of_work(&app, |conn| spend(conn, user_id, currency_type, amount, reason, reference_id))
+}
 
-    // Update balance
-    conn.execute(
-        &format!(
-            "UPDATE user_currency
-             SET {} = {}, updated_at = CURRENT_TIMESTAMP
-             WHERE user_id = ?",
-            currency_type, new_balance
-        ),
-        params![user_id]
-    ).map_err(|e| e.to_string())?;
+fn spend(
+    conn: &Connection,
+    user_id: i64,
+    currency_type: String,
+    amount: i64,
+    reason: String,
+    reference_id: Option<String>,
+) -> Result<UserCurrency, AppError> {
+    let account = currency_account(&currency_type)?;
 
-    // Record transaction (negative amount)
-    conn.execute(
-        "INSERT INTO currency_transactions (user_id, currency_type, amount, reason, reference_id, balance_after)
-         VALUES (?, ?, ?, ?, ?, ?)",
-        params![user_id, currency_type, -amount, reason, reference_id, new_balance]
-    ).map_err(|e| e.to_string())?;
+    ledger::debit(conn, user_id, account, amount, Source { kind: &reason, id: reference_id.as_deref() })?;
 
     // Return updated currency
-    get_user_currency(app.clone(), user_id)
+    load_currency(conn, user_id)
 }
 
 // ============================================================================
@@ -335,7 +289,7 @@ pub fn spend_currency(
 // ============================================================================
 
 #[tauri::command]
-pub fn get_shop_items(app: AppHandle, category: Option<String>, user_level: i64) -> Result<Vec<ShopItem>, String> {
+pub fn get_shop_items(app: AppHandle, category: Option<String>, user_level: i64) -> Result<Vec<ShopItem>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let query = if let Some(ref _cat) = category {
@@ -358,18 +312,18 @@ pub fn get_shop_items(app: AppHandle, category: Option<String>, user_level: i64)
         )
     };
 
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&query)?;
 
     let items = if let Some(cat) = category {
         stmt.query_map(params![cat, user_level], map_shop_item)
-            .map_err(|e| e.to_string())?
+            ?
             .collect::<SqliteResult<Vec<_>>>()
-            .map_err(|e| e.to_string())?
+            ?
     } else {
         stmt.query_map(params![user_level], map_shop_item)
-            .map_err(|e| e.to_string())?
+            ?
             .collect::<SqliteResult<Vec<_>>>()
-            .map_err(|e| e.to_string())?
+            ?
     };
 
     Ok(items)
@@ -402,9 +356,16 @@ pub fn purchase_item(
     user_id: i64,
     item_id: String,
     quantity: i64
-) -> Result<InventoryItem, String> {
-    let conn = db::get_connection(&app)?;
+) -> Result<InventoryItem, AppError> {
+    db::unit_of_work(&app, |conn| purchase(conn, user_id, item_id, quantity))
+}
 
+fn purchase(
+    conn: &Connection,
+    user_id: i64,
+    item_id: String,
+    quantity: i64,
+) -> Result<InventoryItem, AppError> {
     // Get item details
     let item = conn.query_row(
         "SELECT id, name, description, category, type, cost_gold, cost_gems, required_level,
@@ -413,7 +374,7 @@ pub fn purchase_item(
          WHERE id = ?",
         params![item_id],
         map_shop_item
-    ).map_err(|e| format!("Item not found: {}", e))?;
+    ).context("Item not found")?;
 
     // Calculate total cost
     let total_gold = item.cost_gold * quantity;
@@ -427,28 +388,29 @@ pub fn purchase_item(
     ).unwrap_or(1); // Default to level 1 if not found
 
     if user_level < item.required_level {
-        return Err(format!(
+        return Err(AppError::Validation(format!(
             "Level requirement not met: Requires level {} (you are level {})",
             item.required_level,
             user_level
-        ));
+        )));
     }
 
     // Check if user has enough currency
-    let currency = get_user_currency(app.clone(), user_id)?;
+    let currency = load_currency(conn, user_id)?;
     if currency.gold < total_gold {
-        return Err(format!("Insufficient gold (have: {}, need: {})", currency.gold, total_gold));
+        return Err(AppError::InsufficientFunds(format!("Insufficient gold (have: {}, need: {})", currency.gold, total_gold)));
     }
     if currency.gems < total_gems {
-        return Err(format!("Insufficient gems (have: {}, need: {})", currency.gems, total_gems));
+        return Err(AppError::InsufficientFunds(format!("Insufficient gems (have: {}, need: {})", currency.gems, total_gems)));
     }
 
     // Spend currency
+    let source = Source::with_id("shop_purchase", &item_id);
     if total_gold > 0 {
-        spend_currency(app.clone(), user_id, "gold".to_string(), total_gold, "shop_purchase".to_string(), Some(item_id.clone()))?;
+        ledger::debit(conn, user_id, Account::Gold, total_gold, source)?;
     }
     if total_gems > 0 {
-        spend_currency(app.clone(), user_id, "gems".to_string(), total_gems, "shop_purchase".to_string(), Some(item_id.clone()))?;
+        ledger::debit(conn, user_id, Account::Gems, total_gems, source)?;
     }
 
     // Add to inventory (or update quantity)
@@ -458,17 +420,17 @@ pub fn purchase_item(
          ON CONFLICT(user_id, item_id)
          DO UPDATE SET quantity = quantity + ?",
         params![user_id, item_id, quantity, quantity]
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     // Record purchase
     conn.execute(
         "INSERT INTO purchase_history (user_id, item_id, quantity, cost_gold, cost_gems)
          VALUES (?, ?, ?, ?, ?)",
         params![user_id, item_id, quantity, total_gold, total_gems]
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     // Return inventory item
-    get_inventory_item(&app, user_id, &item_id)
+    get_inventory_item(conn, user_id, &item_id)
 }
 
 // ============================================================================
@@ -476,7 +438,7 @@ pub fn purchase_item(
 // ============================================================================
 
 #[tauri::command]
-pub fn get_user_inventory(app: AppHandle, user_id: i64) -> Result<Vec<InventoryItem>, String> {
+pub fn get_user_inventory(app: AppHandle, user_id: i64) -> Result<Vec<InventoryItem>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn.prepare(
@@ -488,7 +450,7 @@ pub fn get_user_inventory(app: AppHandle, user_id: i64) -> Result<Vec<InventoryI
          JOIN shop_items si ON ui.item_id = si.id
          WHERE ui.user_id = ?
          ORDER BY si.category, si.rarity"
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     let items = stmt.query_map(params![user_id], |row| {
         Ok(InventoryItem {
@@ -517,16 +479,14 @@ pub fn get_user_inventory(app: AppHandle, user_id: i64) -> Result<Vec<InventoryI
             },
         })
     })
-    .map_err(|e| e.to_string())?
+    ?
     .collect::<SqliteResult<Vec<_>>>()
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(items)
 }
 
-fn get_inventory_item(app: &AppHandle, user_id: i64, item_id: &str) -> Result<InventoryItem, String> {
-    let conn = db::get_connection(app)?;
-
+fn get_inventory_item(conn: &Connection, user_id: i64, item_id: &str) -> Result<InventoryItem, AppError> {
     conn.query_row(
         "SELECT ui.id, ui.user_id, ui.item_id, ui.quantity, ui.acquired_at,
                 si.id, si.name, si.description, si.category, si.type, si.cost_gold, si.cost_gems,
@@ -563,22 +523,24 @@ fn get_inventory_item(app: &AppHandle, user_id: i64, item_id: &str) -> Result<In
                 },
             })
         }
-    ).map_err(|e| e.to_string())
+    ).map_err(AppError::from)
 }
 
 #[tauri::command]
-pub fn use_inventory_item(app: AppHandle, user_id: i64, item_id: String) -> Result<bool, String> {
-    let conn = db::get_connection(&app)?;
+pub fn use_inventory_item(app: AppHandle, user_id: i64, item_id: String) -> Result<bool, AppError> {
+    db::unit_of_work(&app, |conn| use_item(conn, user_id, item_id))
+}
 
+fn use_item(conn: &Connection, user_id: i64, item_id: String) -> Result<bool, AppError> {
     // Get item from inventory
-    let inv_item = get_inventory_item(&app, user_id, &item_id)?;
+    let inv_item = get_inventory_item(conn, user_id, &item_id)?;
 
     if inv_item.quantity < 1 {
-        return Err("Item not in inventory".to_string());
+        return Err(AppError::NotFound("Item not in inventory".to_string()));
     }
 
     if !inv_item.item.is_consumable {
-        return Err("Item is not consumable".to_string());
+        return Err(AppError::Validation("Item is not consumable".to_string()));
     }
 
     // Parse effects and apply them
@@ -587,7 +549,7 @@ pub fn use_inventory_item(app: AppHandle, user_id: i64, item_id: String) -> Resu
         "INSERT INTO user_active_effects (user_id, effect_type, effect_value, source_item_id, metadata)
          VALUES (?, 'item_effect', 1.0, ?, ?)",
         params![user_id, item_id, inv_item.item.effects]
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     // Decrease quantity
     conn.execute(
@@ -595,14 +557,14 @@ pub fn use_inventory_item(app: AppHandle, user_id: i64, item_id: String) -> Resu
          SET quantity = quantity - 1
          WHERE user_id = ? AND item_id = ?",
         params![user_id, item_id]
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     // Delete if quantity is 0
     conn.execute(
         "DELETE FROM user_inventory
          WHERE user_id = ? AND item_id = ? AND quantity <= 0",
         params![user_id, item_id]
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     Ok(true)
 }
@@ -641,42 +603,19 @@ fn default_quantity() -> i64 {
     1
 }
 
+/// Add the items found in a dungeon loot room. Gold and XP aren't taken from the
+/// client; they're paid by the fights and outcomes that earn them.
 #[tauri::command]
 pub fn add_dungeon_loot_to_inventory(
     app: AppHandle,
     user_id: i64,
     loot_items: Vec<DungeonLootItem>,
-    gold: Option<i64>,
-    xp: Option<i64>,
-) -> Result<(), String> {
-    let conn = db::get_connection(&app)?;
-
-    // Add gold if provided
-    if let Some(gold_amount) = gold {
-        if gold_amount > 0 {
-            // Add to dungeon gold (character_stats.current_gold)
-            conn.execute(
-                "UPDATE character_stats SET current_gold = current_gold + ? WHERE user_id = ?",
-                params![gold_amount, user_id]
-            ).map_err(|e| format!("Failed to add dungeon gold: {}", e))?;
-
-            // Also add to gamification gold
-            conn.execute(
-                "UPDATE user_currency SET gold = gold + ?, lifetime_gold_earned = lifetime_gold_earned + ? WHERE user_id = ?",
-                params![gold_amount, gold_amount, user_id]
-            ).map_err(|e| format!("Failed to add gamification gold: {}", e))?;
-        }
-    }
+) -> Result<(), AppError> {
+    db::unit_of_work(&app, |conn| add_loot(conn, user_id, loot_items))
+}
 
-    // Add XP if provided
-    if let Some(xp_amount) = xp {
-        if xp_amount > 0 {
-            conn.execute(
-                "UPDATE user_dungeon_progress SET total_xp_earned = total_xp_earned + ? WHERE user_id = ?",
-                params![xp_amount, user_id]
-            ).map_err(|e| format!("Failed to add XP: {}", e))?;
-        }
-    }
+fn add_loot(conn: &Connection, user_id: i64, loot_items: Vec<DungeonLootItem>) -> Result<(), AppError> {
+    ensure_out_of_combat(conn, user_id)?;
 
     for item in loot_items {
         // Process database items
@@ -722,13 +661,13 @@ pub fn add_dungeon_loot_to_inventory(
                                 conn.execute(
                                     "UPDATE user_equipment_inventory SET quantity = quantity + ? WHERE user_id = ? AND equipment_id = ?",
                                     params![item.quantity, user_id, &item_id]
-                                ).map_err(|e| format!("Failed to update equipment quantity: {}", e))?;
+                                ).context("Failed to update equipment quantity")?;
                             } else {
                                 conn.execute(
                                     "INSERT INTO user_equipment_inventory (user_id, equipment_id, quantity, acquired_at)
                                      VALUES (?, ?, ?, CURRENT_TIMESTAMP)",
                                     params![user_id, &item_id, item.quantity]
-                                ).map_err(|e| format!("Failed to add equipment to inventory: {}", e))?;
+                                ).context("Failed to add equipment to inventory")?;
                             }
                             log::info!("Added equipment {} x{} to user {}'s inventory", item_id, item.quantity, user_id);
                         },
@@ -739,7 +678,7 @@ pub fn add_dungeon_loot_to_inventory(
                                  VALUES (?, ?, ?, CURRENT_TIMESTAMP)
                                  ON CONFLICT(user_id, consumable_id) DO UPDATE SET quantity = quantity + ?",
                                 params![user_id, &item_id, item.quantity, item.quantity]
-                            ).map_err(|e| format!("Failed to add consumable to inventory: {}", e))?;
+                            ).context("Failed to add consumable to inventory")?;
                             log::info!("Added consumable {} x{} to user {}'s inventory", item_id, item.quantity, user_id);
                         },
                         _ => {
@@ -764,7 +703,7 @@ pub fn add_dungeon_loot_to_inventory(
 // ============================================================================
 
 #[tauri::command]
-pub fn get_quests(app: AppHandle, quest_type: String) -> Result<Vec<Quest>, String> {
+pub fn get_quests(app: AppHandle, quest_type: String) -> Result<Vec<Quest>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn.prepare(
@@ -773,7 +712,7 @@ pub fn get_quests(app: AppHandle, quest_type: String) -> Result<Vec<Quest>, Stri
          FROM quests
          WHERE type = ?
          ORDER BY order_index"
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     let quests = stmt.query_map(params![quest_type], |row| {
         Ok(Quest {
@@ -791,15 +730,15 @@ pub fn get_quests(app: AppHandle, quest_type: String) -> Result<Vec<Quest>, Stri
             order_index: row.get(11)?,
         })
     })
-    .map_err(|e| e.to_string())?
+    ?
     .collect::<SqliteResult<Vec<_>>>()
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(quests)
 }
 
 #[tauri::command]
-pub fn get_user_quest_progress(app: AppHandle, user_id: i64, quest_type: String) -> Result<Vec<UserQuestProgress>, String> {
+pub fn get_user_quest_progress(app: AppHandle, user_id: i64, quest_type: String) -> Result<Vec<UserQuestProgress>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn.prepare(
@@ -810,7 +749,7 @@ pub fn get_user_quest_progress(app: AppHandle, user_id: i64, quest_type: String)
          JOIN quests q ON uqp.quest_id = q.id
          WHERE uqp.user_id = ? AND q.type = ?
          ORDER BY q.order_index"
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     let progress = stmt.query_map(params![user_id, quest_type], |row| {
         Ok(UserQuestProgress {
@@ -836,9 +775,9 @@ pub fn get_user_quest_progress(app: AppHandle, user_id: i64, quest_type: String)
             },
         })
     })
-    .map_err(|e| e.to_string())?
+    ?
     .collect::<SqliteResult<Vec<_>>>()
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(progress)
 }
@@ -849,9 +788,16 @@ pub fn update_quest_progress(
     user_id: i64,
     quest_id: String,
     progress: i64
-) -> Result<UserQuestProgress, String> {
-    let conn = db::get_connection(&app)?;
+) -> Result<UserQuestProgress, AppError> {
+    db::unit_of_work(&app, |conn| record_quest_progress(conn, user_id, quest_id, progress))
+}
 
+fn record_quest_progress(
+    conn: &Connection,
+    user_id: i64,
+    quest_id: String,
+    progress: i64,
+) -> Result<UserQuestProgress, AppError> {
     // Get quest to check if completed
     let quest: Quest = conn.query_row(
         "SELECT id, type, title, description, objective_type, objective_target,
@@ -874,7 +820,7 @@ pub fn update_quest_progress(
                 order_index: row.get(11)?,
             })
         }
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     let completed = progress >= quest.objective_target;
 
@@ -895,7 +841,7 @@ pub fn update_quest_progress(
             completed = ?,
             completed_at = CASE WHEN ? AND completed = 0 THEN CURRENT_TIMESTAMP ELSE completed_at END",
         params![user_id, quest_id, progress, completed, completed, progress, completed, completed]
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     // If just completed (and wasn't completed before), award rewards
     if completed && !was_already_completed {
@@ -904,10 +850,10 @@ pub fn update_quest_progress(
             // Award XP (you'd need to implement this in your user system)
         }
         if quest.reward_gold > 0 {
-            add_currency(app.clone(), user_id, "gold".to_string(), quest.reward_gold, "quest_reward".to_string(), Some(quest_id.clone()))?;
+            ledger::credit(conn, user_id, Account::Gold, quest.reward_gold, Source::with_id("quest_reward", &quest_id))?;
         }
         if quest.reward_gems > 0 {
-            add_currency(app.clone(), user_id, "gems".to_string(), quest.reward_gems, "quest_reward".to_string(), Some(quest_id.clone()))?;
+            ledger::credit(conn, user_id, Account::Gems, quest.reward_gems, Source::with_id("quest_reward", &quest_id))?;
         }
     }
 
@@ -928,7 +874,7 @@ pub fn update_quest_progress(
                 quest: quest.clone(),
             })
         }
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     Ok(progress_record)
 }
@@ -938,7 +884,7 @@ pub fn update_quest_progress(
 // ============================================================================
 
 #[tauri::command]
-pub fn get_active_effects(app: AppHandle, user_id: i64) -> Result<Vec<ActiveEffect>, String> {
+pub fn get_active_effects(app: AppHandle, user_id: i64) -> Result<Vec<ActiveEffect>, AppError> {
     let conn = db::get_connection(&app)?;
 
     // Clean up expired effects first
@@ -946,14 +892,14 @@ pub fn get_active_effects(app: AppHandle, user_id: i64) -> Result<Vec<ActiveEffe
         "DELETE FROM user_active_effects
          WHERE user_id = ? AND expires_at IS NOT NULL AND expires_at < datetime('now')",
         params![user_id]
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     let mut stmt = conn.prepare(
         "SELECT id, user_id, effect_type, effect_value, started_at, expires_at, source_item_id, metadata
          FROM user_active_effects
          WHERE user_id = ?
          AND (expires_at IS NULL OR expires_at > datetime('now'))"
-    ).map_err(|e| e.to_string())?;
+    )?;
 
     let effects = stmt.query_map(params![user_id], |row| {
         Ok(ActiveEffect {
@@ -967,9 +913,9 @@ pub fn get_active_effects(app: AppHandle, user_id: i64) -> Result<Vec<ActiveEffe
             metadata: row.get(7)?,
         })
     })
-    .map_err(|e| e.to_string())?
+    ?
     .collect::<SqliteResult<Vec<_>>>()
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(effects)
 }
@@ -979,9 +925,12 @@ pub fn get_active_effects(app: AppHandle, user_id: i64) -> Result<Vec<ActiveEffe
 // ============================================================================
 
 #[tauri::command]
-pub fn get_level_rewards(app: AppHandle, level: i64) -> Result<Option<LevelReward>, String> {
+pub fn get_level_rewards(app: AppHandle, level: i64) -> Result<Option<LevelReward>, AppError> {
     let conn = db::get_connection(&app)?;
+    load_level_reward(&conn, level)
+}
 
+fn load_level_reward(conn: &Connection, level: i64) -> Result<Option<LevelReward>, AppError> {
     let reward = conn.query_row(
         "SELECT level, xp_required, reward_gold, reward_gems, reward_items,
                 unlocks_feature, unlocks_category, title, description, icon
@@ -1007,24 +956,67 @@ pub fn get_level_rewards(app: AppHandle, level: i64) -> Result<Option<LevelRewar
     match reward {
         Ok(r) => Ok(Some(r)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(e.into()),
     }
 }
 
 #[tauri::command]
-pub fn claim_level_rewards(app: AppHandle, user_id: i64, level: i64) -> Result<LevelReward, String> {
-    let reward = get_level_rewards(app.clone(), level)?
-        .ok_or_else(|| format!("No rewards for level {}", level))?;
+pub fn claim_level_rewards(app: AppHandle, user_id: i64, level: i64) -> Result<LevelReward, AppError> {
+    db::unit_of_work(&app, |conn| claim_level_reward(conn, user_id, level))
+}
+
+fn claim_level_reward(conn: &Connection, user_id: i64, level: i64) -> Result<LevelReward, AppError> {
+    let reward = load_level_reward(conn, level)?
+        .ok_or_else(|| AppError::NotFound(format!("No rewards for level {}", level)))?;
 
     // Award currency
+    let level_id = level.to_string();
+    let source = Source::with_id("level_reward", &level_id);
     if reward.reward_gold > 0 {
-        add_currency(app.clone(), user_id, "gold".to_string(), reward.reward_gold, "level_reward".to_string(), Some(level.to_string()))?;
+        ledger::credit(conn, user_id, Account::Gold, reward.reward_gold, source)?;
     }
     if reward.reward_gems > 0 {
-        add_currency(app, user_id, "gems".to_string(), reward.reward_gems, "level_reward".to_string(), Some(level.to_string()))?;
+        ledger::credit(conn, user_id, Account::Gems, reward.reward_gems, source)?;
     }
 
     // TODO: Award items if reward_items is not null (parse JSON and add to inventory)
 
     Ok(reward)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::combat;
+    use crate::game::enemies::load_enemy;
+    use crate::test_support::{consumable_quantity, seeded_db, TestUser};
+
+    fn potion() -> DungeonLootItem {
+        DungeonLootItem {
+            item_source: "database".to_string(),
+            item_id: Some("minor_health_potion".to_string()),
+            item_category: None,
+            item_type: None,
+            name: "Minor Health Potion".to_string(),
+            quantity: 2,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn loot_waits_until_the_fight_is_over() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("magpie").stats(1, 1, 30).create(&conn);
+        let wolf = combat::Encounter::enemy(&load_enemy(&conn, "wolf").unwrap());
+        combat::begin(&conn, user_id, &wolf).unwrap();
+
+        let mid_fight = add_loot(&conn, user_id, vec![potion()]).unwrap_err();
+        assert!(matches!(mid_fight, AppError::Validation(_)));
+        assert_eq!(consumable_quantity(&conn, user_id, "minor_health_potion"), 0);
+
+        let mut rng = rand::thread_rng();
+        assert!(combat::attempt_escape(&conn, &mut rng, user_id, true).unwrap().escaped);
+        add_loot(&conn, user_id, vec![potion()]).unwrap();
+        assert_eq!(consumable_quantity(&conn, user_id, "minor_health_potion"), 2);
+    }
+}
@@ -1,7 +1,9 @@
+use crate::content_versions;
 use crate::db;
 use rusqlite::{params, Result as SqlResult};
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
+use crate::error::AppError;
 
 // Equipment Item
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -53,7 +55,7 @@ pub struct LootItemOption {
 
 // List all equipment items
 #[tauri::command]
-pub fn list_equipment_items(app: AppHandle) -> Result<Vec<EquipmentItem>, String> {
+pub fn list_equipment_items(app: AppHandle) -> Result<Vec<EquipmentItem>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn
@@ -63,7 +65,7 @@ pub fn list_equipment_items(app: AppHandle) -> Result<Vec<EquipmentItem>, String
              FROM equipment_items
              ORDER BY tier, required_level, name",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let items = stmt
         .query_map([], |row| {
@@ -82,16 +84,16 @@ pub fn list_equipment_items(app: AppHandle) -> Result<Vec<EquipmentItem>, String
                 value: row.get(11)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(items)
 }
 
 // List all consumable items
 #[tauri::command]
-pub fn list_consumable_items(app: AppHandle) -> Result<Vec<ConsumableItem>, String> {
+pub fn list_consumable_items(app: AppHandle) -> Result<Vec<ConsumableItem>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn
@@ -101,7 +103,7 @@ pub fn list_consumable_items(app: AppHandle) -> Result<Vec<ConsumableItem>, Stri
              FROM consumable_items
              ORDER BY tier, buy_price, name",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let items = stmt
         .query_map([], |row| {
@@ -121,16 +123,16 @@ pub fn list_consumable_items(app: AppHandle) -> Result<Vec<ConsumableItem>, Stri
                 tier: row.get(12)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(items)
 }
 
 // List all items suitable for loot (combined equipment and consumables)
 #[tauri::command]
-pub fn list_all_loot_items(app: AppHandle) -> Result<Vec<LootItemOption>, String> {
+pub fn list_all_loot_items(app: AppHandle) -> Result<Vec<LootItemOption>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut items = Vec::new();
@@ -142,7 +144,7 @@ pub fn list_all_loot_items(app: AppHandle) -> Result<Vec<LootItemOption>, String
              FROM equipment_items
              ORDER BY tier, name",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let equipment_items = stmt
         .query_map([], |row| {
@@ -156,9 +158,9 @@ pub fn list_all_loot_items(app: AppHandle) -> Result<Vec<LootItemOption>, String
                 icon: row.get(5)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     items.extend(equipment_items);
 
@@ -169,7 +171,7 @@ pub fn list_all_loot_items(app: AppHandle) -> Result<Vec<LootItemOption>, String
              FROM consumable_items
              ORDER BY tier, name",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let consumable_items = stmt
         .query_map([], |row| {
@@ -183,9 +185,9 @@ pub fn list_all_loot_items(app: AppHandle) -> Result<Vec<LootItemOption>, String
                 icon: row.get(5)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     items.extend(consumable_items);
 
@@ -197,7 +199,7 @@ pub fn list_all_loot_items(app: AppHandle) -> Result<Vec<LootItemOption>, String
 pub fn save_custom_consumable(
     app: AppHandle,
     item: ConsumableItem,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
@@ -221,7 +223,9 @@ pub fn save_custom_consumable(
             item.tier,
         ],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
+
+    content_versions::mark_user_modified(&conn, "consumable_items", &item.id)?;
 
     Ok(())
 }
@@ -231,7 +235,7 @@ pub fn save_custom_consumable(
 pub fn save_custom_equipment(
     app: AppHandle,
     item: EquipmentItem,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
@@ -254,14 +258,16 @@ pub fn save_custom_equipment(
             item.value,
         ],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
+
+    content_versions::mark_user_modified(&conn, "equipment_items", &item.id)?;
 
     Ok(())
 }
 
 // Get a single equipment item
 #[tauri::command]
-pub fn get_equipment_item(app: AppHandle, item_id: String) -> Result<EquipmentItem, String> {
+pub fn get_equipment_item(app: AppHandle, item_id: String) -> Result<EquipmentItem, AppError> {
     let conn = db::get_connection(&app)?;
 
     let item = conn.query_row(
@@ -286,14 +292,14 @@ pub fn get_equipment_item(app: AppHandle, item_id: String) -> Result<EquipmentIt
             })
         },
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(item)
 }
 
 // Get a single consumable item
 #[tauri::command]
-pub fn get_consumable_item(app: AppHandle, item_id: String) -> Result<ConsumableItem, String> {
+pub fn get_consumable_item(app: AppHandle, item_id: String) -> Result<ConsumableItem, AppError> {
     let conn = db::get_connection(&app)?;
 
     let item = conn.query_row(
@@ -319,35 +325,35 @@ pub fn get_consumable_item(app: AppHandle, item_id: String) -> Result<Consumable
             })
         },
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(item)
 }
 
 // Delete an equipment item
 #[tauri::command]
-pub fn delete_equipment_item(app: AppHandle, item_id: String) -> Result<(), String> {
+pub fn delete_equipment_item(app: AppHandle, item_id: String) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
         "DELETE FROM equipment_items WHERE id = ?1",
         params![item_id],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(())
 }
 
 // Delete a consumable item
 #[tauri::command]
-pub fn delete_consumable_item(app: AppHandle, item_id: String) -> Result<(), String> {
+pub fn delete_consumable_item(app: AppHandle, item_id: String) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
         "DELETE FROM consumable_items WHERE id = ?1",
         params![item_id],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(())
 }
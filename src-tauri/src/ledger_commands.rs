@@ -0,0 +1,33 @@
+use tauri::AppHandle;
+
+use crate::db;
+use crate::error::AppError;
+use crate::game::ledger::{self, Account, Discrepancy, LedgerEntry};
+
+// ============================================================================
+// Ledger Commands
+// ============================================================================
+
+/// Entries shown when no limit is given
+const DEFAULT_STATEMENT_LIMIT: i64 = 100;
+
+/// A user's gold, gem, dungeon gold and XP changes, newest first. account is
+/// "gold", "gems", "dungeon_gold" or "xp"; all accounts when omitted.
+#[tauri::command]
+pub fn get_ledger_statement(
+    app: AppHandle,
+    user_id: i64,
+    account: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<LedgerEntry>, AppError> {
+    let account = account.as_deref().map(Account::parse).transpose()?;
+    let conn = db::get_connection(&app)?;
+    ledger::statement(&conn, user_id, account, limit.unwrap_or(DEFAULT_STATEMENT_LIMIT))
+}
+
+/// Balances (for one user, or everyone) that don't match their ledger; empty when all reconcile
+#[tauri::command]
+pub fn reconcile_ledger(app: AppHandle, user_id: Option<i64>) -> Result<Vec<Discrepancy>, AppError> {
+    let conn = db::get_connection(&app)?;
+    ledger::reconcile(&conn, user_id)
+}
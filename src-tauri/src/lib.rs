@@ -1,5 +1,11 @@
 mod commands;
 mod db;
+mod backup;
+mod backup_commands;
+mod clean_export;
+mod error;
+mod game;
+mod migrations;
 mod puzzle_commands;
 mod playground_commands;
 mod gamification_commands;
@@ -14,6 +20,40 @@ mod dungeon_level_commands;
 mod enemy_commands;
 mod mcq_commands;
 mod item_commands;
+mod credentials;
+mod ai_credential_commands;
+mod ai_usage_commands;
+mod prompt_builder;
+mod tutor_commands;
+mod solution_guard;
+mod retrieval;
+mod retrieval_commands;
+mod search;
+mod search_commands;
+mod llm_provider;
+mod mcq_generation;
+mod mcq_generation_commands;
+mod code_review;
+mod code_review_commands;
+mod mock_llm;
+mod llm_settings_commands;
+mod profile_archive;
+mod profiles;
+mod profile_commands;
+mod content_integrity;
+mod seed_sources;
+mod seed_builder;
+mod content_integrity_commands;
+mod content_versions;
+mod content_version_commands;
+mod ledger_commands;
+mod clock_commands;
+pub mod content_cli;
+mod profile_archive_commands;
+#[cfg(test)]
+mod test_support;
+
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -29,12 +69,14 @@ pub fn run() {
         )?;
       }
 
-      // Initialize the database
-      db::initialize_database(&app.handle())
+      // Initialize the database and share its connection pool with commands
+      let pool = db::initialize_database(app.handle())
         .map_err(|e| {
           log::error!("Failed to initialize database: {}", e);
           e
         })?;
+      backup::start_scheduler(pool.clone(), backup::backup_dir(&db::get_app_data_dir(app.handle())?)?);
+      app.manage(pool);
 
       Ok(())
     })
@@ -42,9 +84,72 @@ pub fn run() {
       commands::execute_python,
       commands::execute_code,
       commands::check_language_runtime,
-      commands::call_claude_api,
       commands::check_ollama_available,
       commands::check_runtime_path,
+      // Database backup commands
+      backup_commands::get_backup_settings,
+      backup_commands::set_backup_settings,
+      backup_commands::list_backups,
+      backup_commands::create_backup,
+      backup_commands::verify_backup,
+      backup_commands::restore_backup,
+      // AI provider credential commands
+      ai_credential_commands::get_ai_provider_key_status,
+      ai_credential_commands::set_ai_provider_key,
+      ai_credential_commands::unlock_ai_provider_key,
+      ai_credential_commands::test_ai_provider_key,
+      ai_credential_commands::rotate_ai_provider_key,
+      ai_credential_commands::delete_ai_provider_key,
+      // AI usage and budget commands
+      ai_usage_commands::get_ai_usage_budget,
+      ai_usage_commands::set_ai_usage_budget,
+      ai_usage_commands::clear_ai_usage_budget,
+      ai_usage_commands::get_ai_usage_report,
+      ai_usage_commands::get_recent_ai_calls,
+      // Tutor commands
+      tutor_commands::build_tutor_prompt,
+      tutor_commands::ask_tutor,
+      // Retrieval commands
+      retrieval_commands::rebuild_retrieval_index,
+      retrieval_commands::search_knowledge,
+      retrieval_commands::get_retrieval_index_status,
+      search_commands::search,
+      // MCQ generation commands
+      mcq_generation_commands::generate_mcq_drafts,
+      mcq_generation_commands::list_mcq_drafts,
+      mcq_generation_commands::update_mcq_draft,
+      mcq_generation_commands::approve_mcq_draft,
+      mcq_generation_commands::reject_mcq_draft,
+      // Code review commands
+      code_review_commands::review_puzzle_solution,
+      code_review_commands::get_puzzle_code_review,
+      code_review_commands::review_lesson_solution,
+      // LLM backend settings commands
+      llm_settings_commands::get_llm_backend,
+      llm_settings_commands::set_llm_backend,
+      llm_settings_commands::list_mock_llm_fixtures,
+      llm_settings_commands::reload_mock_llm_fixtures,
+      // Content integrity commands
+      content_integrity_commands::check_content_integrity,
+      content_version_commands::list_content_conflicts,
+      content_version_commands::resolve_content_conflict,
+      ledger_commands::get_ledger_statement,
+      ledger_commands::reconcile_ledger,
+      clock_commands::get_clock_status,
+      clock_commands::update_time_settings,
+      // Profile commands
+      profile_commands::list_profiles,
+      profile_commands::get_profile,
+      profile_commands::create_profile,
+      profile_commands::rename_profile,
+      profile_commands::set_profile_avatar,
+      profile_commands::set_profile_metadata,
+      profile_commands::touch_profile,
+      profile_commands::set_profile_archived,
+      profile_commands::delete_profile,
+      // Profile export/import commands
+      profile_archive_commands::export_profile,
+      profile_archive_commands::import_profile,
       // Puzzle commands
       puzzle_commands::get_puzzle_categories,
       puzzle_commands::get_puzzles_by_category,
@@ -152,7 +257,7 @@ pub fn run() {
       combat_commands::execute_combat_turn,
       combat_commands::end_combat_victory,
       combat_commands::end_combat_defeat,
-      combat_commands::end_combat_flee,
+      combat_commands::attempt_escape,
       // Narrative dungeon commands
       narrative_commands::roll_d20,
       narrative_commands::get_user_narrative_progress,
@@ -184,6 +289,8 @@ pub fn run() {
       dev_commands::dev_reset_character,
       dev_commands::dev_export_clean_database,
       dev_commands::dev_clear_inventory,
+      dev_commands::dev_time_travel,
+      dev_commands::dev_reset_time_travel,
       // Achievement commands
       achievement_commands::get_achievements,
       achievement_commands::get_achievement_stats,
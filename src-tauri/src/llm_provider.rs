@@ -0,0 +1,111 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use tauri::AppHandle;
+
+use crate::commands::{self, ClaudeMessage};
+use crate::error::{AppError, ResultExt};
+
+// ============================================================================
+// LLM Completion Providers
+// ============================================================================
+//
+// Features that generate content (MCQ drafts, reviews...) talk to the model
+// through this trait instead of calling Claude directly, so their parsing and
+// validation can be exercised against scripted responses.
+
+/// Receives response text as it arrives
+pub type ChunkSink<'a> = dyn Fn(&str) + Send + Sync + 'a;
+
+pub type CompletionFuture<'a> = Pin<Box<dyn Future<Output = Result<String, AppError>> + Send + 'a>>;
+
+pub trait CompletionProvider: Send + Sync {
+    /// Send a system prompt and conversation, returning the model's text reply
+    fn complete<'a>(&'a self, system_prompt: &'a str, messages: Vec<ClaudeMessage>) -> CompletionFuture<'a>;
+
+    /// Model name recorded alongside generated content
+    fn model(&self) -> &str;
+}
+
+/// Claude through `send_claude_message`, so usage is logged and budgets apply
+pub struct ClaudeProvider {
+    pub app: AppHandle,
+    pub model: String,
+    pub user_id: i32,
+    /// Usage-log context label, e.g. "mcq_generation"
+    pub context: String,
+}
+
+impl CompletionProvider for ClaudeProvider {
+    fn complete<'a>(&'a self, system_prompt: &'a str, messages: Vec<ClaudeMessage>) -> CompletionFuture<'a> {
+        Box::pin(commands::send_claude_message(
+            &self.app,
+            self.model.clone(),
+            system_prompt.to_string(),
+            messages,
+            self.user_id,
+            Some(self.context.clone()),
+        ))
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+// ============================================================================
+// Backend Selection
+// ============================================================================
+
+/// Overrides the saved setting, e.g. CODE_TUTOR_LLM_BACKEND=mock in CI
+pub const BACKEND_ENV: &str = "CODE_TUTOR_LLM_BACKEND";
+
+/// Where Claude calls are answered: the real API or scripted fixtures
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmBackend {
+    Claude,
+    Mock,
+}
+
+impl LlmBackend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LlmBackend::Claude => "claude",
+            LlmBackend::Mock => "mock",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, AppError> {
+        match value.trim().to_lowercase().as_str() {
+            "claude" => Ok(LlmBackend::Claude),
+            "mock" => Ok(LlmBackend::Mock),
+            other => Err(AppError::Validation(format!("Unknown LLM backend: {}", other))),
+        }
+    }
+}
+
+/// The backend in use: the environment override if set, then the saved setting
+pub fn get_llm_backend(conn: &Connection) -> Result<LlmBackend, AppError> {
+    if let Ok(value) = std::env::var(BACKEND_ENV) {
+        return LlmBackend::parse(&value);
+    }
+
+    let saved: Option<String> = conn
+        .query_row("SELECT value FROM ai_settings WHERE key = 'llm_backend'", [], |row| row.get(0))
+        .optional()
+        .context("Failed to load LLM backend setting")?;
+
+    saved.map_or(Ok(LlmBackend::Claude), |value| LlmBackend::parse(&value))
+}
+
+pub fn set_llm_backend(conn: &Connection, backend: LlmBackend) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO ai_settings (key, value, updated_at) VALUES ('llm_backend', ?1, datetime('now'))
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        params![backend.as_str()],
+    )
+    .context("Failed to save LLM backend setting")?;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use tauri::AppHandle;
+
+use crate::db;
+use crate::llm_provider::{self, LlmBackend};
+use crate::mock_llm::{self, MockFixtureInfo};
+use crate::error::AppError;
+
+// ============================================================================
+// LLM Backend Settings Commands
+// ============================================================================
+
+/// The backend answering AI calls ("claude" or "mock")
+#[tauri::command]
+pub fn get_llm_backend(app: AppHandle) -> Result<LlmBackend, AppError> {
+    let conn = db::get_connection(&app)?;
+    llm_provider::get_llm_backend(&conn)
+}
+
+/// Save the backend choice. CODE_TUTOR_LLM_BACKEND still takes precedence when set.
+#[tauri::command]
+pub fn set_llm_backend(app: AppHandle, backend: LlmBackend) -> Result<LlmBackend, AppError> {
+    let conn = db::get_connection(&app)?;
+    llm_provider::set_llm_backend(&conn, backend)?;
+    llm_provider::get_llm_backend(&conn)
+}
+
+/// Fixtures the mock backend answers from, in match order
+#[tauri::command]
+pub fn list_mock_llm_fixtures(app: AppHandle) -> Result<Vec<MockFixtureInfo>, AppError> {
+    Ok(mock_llm::shared(db::get_app_data_dir(&app).ok())?.fixtures())
+}
+
+/// Re-read fixture files after they have been edited
+#[tauri::command]
+pub fn reload_mock_llm_fixtures(app: AppHandle) -> Result<Vec<MockFixtureInfo>, AppError> {
+    Ok(mock_llm::reload(db::get_app_data_dir(&app).ok())?.fixtures())
+}
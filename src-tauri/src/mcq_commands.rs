@@ -1,7 +1,10 @@
 use rusqlite::{params, Connection, Result as SqlResult};
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
+use crate::content_versions;
 use crate::db;
+use crate::error::{AppError, ResultExt};
+use crate::game::clock::{self, AppClock, Clock};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -31,7 +34,7 @@ pub struct QuestionListItem {
 
 // Save or update a question
 #[tauri::command]
-pub fn save_mcq_question(app: AppHandle, question: McqQuestion) -> Result<(), String> {
+pub fn save_mcq_question(app: AppHandle, question: McqQuestion) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     // Check if question exists
@@ -63,7 +66,7 @@ pub fn save_mcq_question(app: AppHandle, question: McqQuestion) -> Result<(), St
                 question.tags,
             ],
         )
-        .map_err(|e| e.to_string())?;
+        ?;
     } else {
         // Insert new question
         conn.execute(
@@ -83,15 +86,17 @@ pub fn save_mcq_question(app: AppHandle, question: McqQuestion) -> Result<(), St
                 question.tags,
             ],
         )
-        .map_err(|e| e.to_string())?;
+        ?;
     }
 
+    content_versions::mark_user_modified(&conn, "mcq_questions", &question.id)?;
+
     Ok(())
 }
 
 // Load a single question
 #[tauri::command]
-pub fn load_mcq_question(app: AppHandle, question_id: String) -> Result<McqQuestion, String> {
+pub fn load_mcq_question(app: AppHandle, question_id: String) -> Result<McqQuestion, AppError> {
     let conn = db::get_connection(&app)?;
 
     let question = conn
@@ -116,7 +121,7 @@ pub fn load_mcq_question(app: AppHandle, question_id: String) -> Result<McqQuest
                 })
             },
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(question)
 }
@@ -128,7 +133,7 @@ pub fn list_mcq_questions(
     difficulty_filter: Option<String>,
     language_filter: Option<String>,
     topic_filter: Option<String>,
-) -> Result<Vec<QuestionListItem>, String> {
+) -> Result<Vec<QuestionListItem>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut query = "SELECT id, question_text, difficulty, topic, language FROM mcq_questions WHERE question_text IS NOT NULL AND question_text != ''".to_string();
@@ -151,7 +156,7 @@ pub fn list_mcq_questions(
 
     query.push_str(" ORDER BY difficulty, language, topic");
 
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&query)?;
 
     let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec
         .iter()
@@ -168,23 +173,23 @@ pub fn list_mcq_questions(
                 language: row.get(4)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(questions)
 }
 
 // Delete a question
 #[tauri::command]
-pub fn delete_mcq_question(app: AppHandle, question_id: String) -> Result<(), String> {
+pub fn delete_mcq_question(app: AppHandle, question_id: String) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
         "DELETE FROM mcq_questions WHERE id = ?1",
         params![question_id],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(())
 }
@@ -194,7 +199,7 @@ pub fn delete_mcq_question(app: AppHandle, question_id: String) -> Result<(), St
 pub fn duplicate_mcq_question(
     app: AppHandle,
     question_id: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let existing_question = load_mcq_question(app.clone(), question_id)?;
 
     let new_id = uuid::Uuid::new_v4().to_string();
@@ -226,11 +231,11 @@ pub fn get_random_mcq_question(
     difficulty: Option<String>,
     language: Option<String>,
     topic: Option<String>,
-) -> Result<McqQuestion, String> {
+) -> Result<McqQuestion, AppError> {
     let questions = list_mcq_questions(app.clone(), difficulty, language, topic)?;
 
     if questions.is_empty() {
-        return Err("No questions match the criteria".to_string());
+        return Err(AppError::NotFound("No questions match the criteria".to_string()));
     }
 
     // Get random index
@@ -251,7 +256,7 @@ pub fn get_smart_mcq_question(
     difficulty: Option<String>,
     language: Option<String>,
     topic: Option<String>,
-) -> Result<McqQuestion, String> {
+) -> Result<McqQuestion, AppError> {
     let conn = db::get_connection(&app)?;
 
     // First try with the specified difficulty
@@ -264,8 +269,8 @@ pub fn get_smart_mcq_question(
     }
 
     if questions.is_empty() {
-        return Err(format!("No questions available in mcq_questions table (difficulty: {:?}, language: {:?}, topic: {:?}). Please add questions using the Question Manager.",
-            difficulty, language, topic));
+        return Err(AppError::NotFound(format!("No questions available in mcq_questions table (difficulty: {:?}, language: {:?}, topic: {:?}). Please add questions using the Question Manager.",
+            difficulty, language, topic)));
     }
 
     // Get user's question history (questions they've seen and when)
@@ -277,7 +282,7 @@ pub fn get_smart_mcq_question(
              WHERE user_id = ? AND challenge_id IN (SELECT id FROM mcq_questions)
              GROUP BY challenge_id"
         )
-        .map_err(|e| format!("Failed to query history: {}", e))?;
+        .context("Failed to query history")?;
 
     let history = stmt
         .query_map([user_id], |row| {
@@ -287,7 +292,7 @@ pub fn get_smart_mcq_question(
                 row.get::<_, String>(2)?,   // last_seen timestamp
             ))
         })
-        .map_err(|e| format!("Failed to map history: {}", e))?;
+        .context("Failed to map history")?;
 
     for result in history {
         if let Ok((id, success, last_seen)) = result {
@@ -301,12 +306,12 @@ pub fn get_smart_mcq_question(
     let mut seen_long_ago = Vec::new();
     let mut recently_seen = Vec::new();
 
-    let now = chrono::Utc::now();
+    let now = AppClock::load(&conn)?.now();
 
     for q in &questions {
         if let Some((last_success, last_seen_str)) = seen_questions.get(&q.id) {
-            // Parse the timestamp
-            if let Ok(last_seen_time) = chrono::DateTime::parse_from_rfc3339(&last_seen_str) {
+            // completed_at is a CURRENT_TIMESTAMP value
+            if let Some(last_seen_time) = clock::from_sqlite(last_seen_str) {
                 let hours_ago = now.signed_duration_since(last_seen_time).num_hours();
 
                 if !last_success {
@@ -339,7 +344,7 @@ pub fn get_smart_mcq_question(
     } else if !recently_seen.is_empty() {
         &recently_seen[rng.gen_range(0..recently_seen.len())]
     } else {
-        return Err("No suitable questions found".to_string());
+        return Err(AppError::NotFound("No suitable questions found".to_string()));
     };
 
     load_mcq_question(app, selected.id.clone())
@@ -347,8 +352,13 @@ pub fn get_smart_mcq_question(
 
 // Import questions from legacy dungeon_challenges table
 #[tauri::command]
-pub fn import_dungeon_challenges_to_mcq(app: AppHandle) -> Result<usize, String> {
+pub fn import_dungeon_challenges_to_mcq(app: AppHandle) -> Result<usize, AppError> {
     let conn = db::get_connection(&app)?;
+    import_dungeon_challenges(&conn)
+}
+
+/// Copy multiple-choice dungeon_challenges into mcq_questions, skipping ones already imported
+pub(crate) fn import_dungeon_challenges(conn: &Connection) -> Result<usize, AppError> {
 
     // Get all challenges with MCQ format (have choices and correct_answer)
     let mut stmt = conn
@@ -357,7 +367,7 @@ pub fn import_dungeon_challenges_to_mcq(app: AppHandle) -> Result<usize, String>
              FROM dungeon_challenges
              WHERE choices IS NOT NULL AND correct_answer IS NOT NULL"
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let challenges = stmt
         .query_map([], |row| {
@@ -370,16 +380,16 @@ pub fn import_dungeon_challenges_to_mcq(app: AppHandle) -> Result<usize, String>
                 row.get::<_, String>(5)?, // difficulty
             ))
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<SqlResult<Vec<_>>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let mut imported_count = 0;
 
     for (old_id, title, description, choices_json, correct_letter, difficulty) in challenges {
         // Parse the choices JSON array
         let choices: Vec<String> = serde_json::from_str(&choices_json)
-            .map_err(|e| format!("Failed to parse choices for {}: {}", old_id, e))?;
+            .with_context(|| format!("Failed to parse choices for {}", old_id))?;
 
         // Strip the letter prefixes (e.g., "A) text" -> "text")
         let options: Vec<String> = choices
@@ -400,12 +410,12 @@ pub fn import_dungeon_challenges_to_mcq(app: AppHandle) -> Result<usize, String>
             "B" => 1,
             "C" => 2,
             "D" => 3,
-            _ => return Err(format!("Invalid correct_answer letter: {}", correct_letter)),
+            _ => return Err(AppError::Validation(format!("Invalid correct_answer letter: {}", correct_letter))),
         };
 
         // Create new MCQ question
         let new_id = format!("imported-{}", old_id);
-        let options_json = serde_json::to_string(&options).map_err(|e| e.to_string())?;
+        let options_json = serde_json::to_string(&options)?;
 
         // Determine topic from the old_id prefix (e.g., "str_basic_1" -> "algorithms")
         let topic = if old_id.starts_with("str_") {
@@ -422,7 +432,7 @@ pub fn import_dungeon_challenges_to_mcq(app: AppHandle) -> Result<usize, String>
 
         // Create tags array
         let tags = vec![topic.to_string(), "dungeon".to_string()];
-        let tags_json = serde_json::to_string(&tags).map_err(|e| e.to_string())?;
+        let tags_json = serde_json::to_string(&tags)?;
 
         // Check if already imported
         let exists: bool = conn
@@ -453,7 +463,7 @@ pub fn import_dungeon_challenges_to_mcq(app: AppHandle) -> Result<usize, String>
                     tags_json,
                 ],
             )
-            .map_err(|e| e.to_string())?;
+            ?;
 
             imported_count += 1;
         }
@@ -467,8 +477,13 @@ pub fn import_dungeon_challenges_to_mcq(app: AppHandle) -> Result<usize, String>
 pub fn bulk_import_mcq_questions(
     app: AppHandle,
     questions: Vec<McqQuestion>,
-) -> Result<usize, String> {
+) -> Result<usize, AppError> {
     let conn = db::get_connection(&app)?;
+    import_questions(&conn, questions)
+}
+
+/// Insert questions whose id isn't taken yet, returning how many were added
+pub(crate) fn import_questions(conn: &Connection, questions: Vec<McqQuestion>) -> Result<usize, AppError> {
     let mut imported_count = 0;
     let mut skipped_count = 0;
 
@@ -502,7 +517,7 @@ pub fn bulk_import_mcq_questions(
                     question.tags,
                 ],
             )
-            .map_err(|e| format!("Failed to insert question {}: {}", question.id, e))?;
+            .with_context(|| format!("Failed to insert question {}", question.id))?;
 
             imported_count += 1;
         } else {
@@ -517,7 +532,7 @@ pub fn bulk_import_mcq_questions(
 
 // Import questions from markdown file (docs/multiple-choice.md)
 #[tauri::command]
-pub fn import_markdown_mcq_questions(app: AppHandle) -> Result<usize, String> {
+pub fn import_markdown_mcq_questions(app: AppHandle) -> Result<usize, AppError> {
     use std::fs;
     use std::path::PathBuf;
 
@@ -532,13 +547,13 @@ pub fn import_markdown_mcq_questions(app: AppHandle) -> Result<usize, String> {
     for path in possible_paths {
         if path.exists() {
             markdown_content = Some(fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read file {:?}: {}", path, e))?);
+                .with_context(|| format!("Failed to read file {:?}", path))?);
             break;
         }
     }
 
     let content = markdown_content
-        .ok_or_else(|| "Could not find docs/multiple-choice.md file".to_string())?;
+        .ok_or_else(|| AppError::NotFound("Could not find docs/multiple-choice.md file".to_string()))?;
 
     // Parse the markdown file
     let questions = parse_markdown_questions(&content)?;
@@ -548,7 +563,7 @@ pub fn import_markdown_mcq_questions(app: AppHandle) -> Result<usize, String> {
 }
 
 /// Parse markdown questions from the multiple-choice.md format
-fn parse_markdown_questions(content: &str) -> Result<Vec<McqQuestion>, String> {
+pub(crate) fn parse_markdown_questions(content: &str) -> Result<Vec<McqQuestion>, AppError> {
     let mut questions = Vec::new();
 
     // Split by difficulty sections
@@ -584,17 +599,17 @@ fn extract_section(content: &str, start_marker: &str, end_marker: &str) -> Optio
 }
 
 /// Parse a single difficulty section
-fn parse_section(section: &str, difficulty: &str, id_prefix: &str) -> Result<Vec<McqQuestion>, String> {
+fn parse_section(section: &str, difficulty: &str, id_prefix: &str) -> Result<Vec<McqQuestion>, AppError> {
     let mut questions = Vec::new();
 
     // Use regex to match question patterns
     // Pattern: number. Question\n   a) option\n   b) option\n   c) option\n   d) option\n   **Answer: X**
     let re = regex::Regex::new(
         r"(?m)^(\d+)\.\s+(.+?)\n\s+a\)\s+(.+?)\n\s+b\)\s+(.+?)\n\s+c\)\s+(.+?)\n\s+d\)\s+(.+?)\n\s+\*\*Answer:\s+([a-d])\*\*"
-    ).map_err(|e| e.to_string())?;
+    ).map_err(|e| AppError::Internal(e.to_string()))?;
 
     for cap in re.captures_iter(section) {
-        let number: i32 = cap[1].parse().map_err(|e| format!("Failed to parse question number: {}", e))?;
+        let number: i32 = cap[1].parse().map_err(|e| AppError::Validation(format!("Failed to parse question number: {}", e)))?;
         let question_text = cap[2].trim();
         let option_a = cap[3].trim();
         let option_b = cap[4].trim();
@@ -608,7 +623,7 @@ fn parse_section(section: &str, difficulty: &str, id_prefix: &str) -> Result<Vec
             "b" | "B" => 1,
             "c" | "C" => 2,
             "d" | "D" => 3,
-            _ => return Err(format!("Invalid answer: {}", answer)),
+            _ => return Err(AppError::Validation(format!("Invalid answer: {}", answer))),
         };
 
         // Generate ID
@@ -624,10 +639,10 @@ fn parse_section(section: &str, difficulty: &str, id_prefix: &str) -> Result<Vec
             option_c.to_string(),
             option_d.to_string(),
         ];
-        let options_json = serde_json::to_string(&options).map_err(|e| e.to_string())?;
+        let options_json = serde_json::to_string(&options)?;
 
         let tags = vec![topic.to_string(), "imported".to_string(), "bulk-import".to_string()];
-        let tags_json = serde_json::to_string(&tags).map_err(|e| e.to_string())?;
+        let tags_json = serde_json::to_string(&tags)?;
 
         let now = chrono::Utc::now().to_rfc3339();
 
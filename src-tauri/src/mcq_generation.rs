@@ -0,0 +1,382 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::commands::ClaudeMessage;
+use crate::llm_provider::CompletionProvider;
+use crate::prompt_builder::render_template;
+use crate::error::AppError;
+
+// ============================================================================
+// AI-Assisted MCQ Generation
+// ============================================================================
+//
+// The model is asked for a JSON object of questions. Each question is parsed
+// and validated on its own, so one malformed question doesn't discard the
+// rest of the batch; rejected questions are reported with the reason.
+
+pub const MAX_QUESTIONS_PER_REQUEST: u32 = 10;
+pub const OPTION_COUNT: usize = 4;
+
+/// Existing questions listed in the prompt so the model avoids repeating them
+const MAX_EXISTING_IN_PROMPT: usize = 20;
+
+const GENERATION_TEMPLATE: &str = include_str!("../prompts/mcq/generate.md");
+const GENERATION_SYSTEM_PROMPT: &str =
+    "You are an experienced programming instructor who writes precise, unambiguous quiz questions. You always reply with valid JSON only.";
+
+/// One question as the model is asked to produce it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GeneratedQuestion {
+    pub question: String,
+    pub options: Vec<String>,
+    pub correct_index: i64,
+    pub explanation: String,
+    #[serde(default)]
+    pub topic: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedQuestion {
+    /// Position in the model's response (0-based)
+    pub index: usize,
+    pub question: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Default)]
+pub struct GenerationOutcome {
+    pub accepted: Vec<GeneratedQuestion>,
+    pub rejected: Vec<RejectedQuestion>,
+}
+
+/// Everything the generation prompt needs
+pub struct GenerationPrompt {
+    pub count: u32,
+    pub language: String,
+    pub difficulty: String,
+    pub source_material: String,
+    /// Question texts already in the bank for this language, used for duplicate checks
+    pub existing_questions: Vec<String>,
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Check the structure of one question: four distinct options, one correct index
+/// pointing at an option, and a non-empty question and explanation
+pub fn validate_question(question: &GeneratedQuestion) -> Result<(), AppError> {
+    if question.question.trim().is_empty() {
+        return Err(AppError::Validation("Question text is empty".to_string()));
+    }
+
+    if question.options.len() != OPTION_COUNT {
+        return Err(AppError::Validation(format!("Expected {} options, got {}", OPTION_COUNT, question.options.len())));
+    }
+
+    if question.options.iter().any(|option| option.trim().is_empty()) {
+        return Err(AppError::Validation("Options must not be empty".to_string()));
+    }
+
+    let distinct: HashSet<String> = question.options.iter().map(|option| normalize(option)).collect();
+    if distinct.len() != question.options.len() {
+        return Err(AppError::Validation("Options must all be different".to_string()));
+    }
+
+    if question.correct_index < 0 || question.correct_index >= OPTION_COUNT as i64 {
+        return Err(AppError::Validation(format!(
+            "Correct index {} is out of range (0-{})",
+            question.correct_index,
+            OPTION_COUNT - 1
+        )));
+    }
+
+    if question.explanation.trim().is_empty() {
+        return Err(AppError::Validation("Explanation is missing".to_string()));
+    }
+
+    Ok(())
+}
+
+/// The JSON object in a response, ignoring code fences or stray text around it
+fn extract_json_object(response: &str) -> Option<&str> {
+    let start = response.find('{')?;
+    let end = response.rfind('}')?;
+    (start < end).then(|| &response[start..=end])
+}
+
+/// Parse and validate a model response. Fails only when the response as a whole
+/// isn't the expected JSON object; individual bad questions are rejected.
+pub fn parse_generation_response(response: &str, existing_questions: &[String]) -> Result<GenerationOutcome, AppError> {
+    let json = extract_json_object(response).ok_or_else(|| AppError::Ai("Response did not contain a JSON object".to_string()))?;
+
+    let mut root: HashMap<String, serde_json::Value> =
+        serde_json::from_str(json).map_err(|e| AppError::Ai(format!("Response is not valid JSON: {}", e)))?;
+    let items = match root.remove("questions") {
+        Some(serde_json::Value::Array(items)) => items,
+        _ => return Err(AppError::Ai("Response is missing the \"questions\" array".to_string())),
+    };
+
+    let mut seen: HashSet<String> = existing_questions.iter().map(|q| normalize(q)).collect();
+    let mut outcome = GenerationOutcome::default();
+
+    for (index, item) in items.into_iter().enumerate() {
+        let question_text = item.get("question").and_then(|q| q.as_str()).map(|q| q.to_string());
+
+        let question = match serde_json::from_value::<GeneratedQuestion>(item) {
+            Ok(question) => question,
+            Err(e) => {
+                outcome.rejected.push(RejectedQuestion {
+                    index,
+                    question: question_text,
+                    reason: format!("Invalid structure: {}", e),
+                });
+                continue;
+            }
+        };
+
+        let result = validate_question(&question).and_then(|_| {
+            if seen.insert(normalize(&question.question)) {
+                Ok(())
+            } else {
+                Err(AppError::Validation("Duplicate of an existing question".to_string()))
+            }
+        });
+
+        match result {
+            Ok(()) => outcome.accepted.push(GeneratedQuestion {
+                question: question.question.trim().to_string(),
+                options: question.options.iter().map(|o| o.trim().to_string()).collect(),
+                explanation: question.explanation.trim().to_string(),
+                ..question
+            }),
+            Err(reason) => outcome.rejected.push(RejectedQuestion {
+                index,
+                question: question_text,
+                reason: reason.to_string(),
+            }),
+        }
+    }
+
+    Ok(outcome)
+}
+
+pub fn render_generation_prompt(prompt: &GenerationPrompt) -> Result<String, AppError> {
+    let existing = prompt
+        .existing_questions
+        .iter()
+        .take(MAX_EXISTING_IN_PROMPT)
+        .map(|q| format!("- {}", q))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let vars = HashMap::from([
+        ("count", prompt.count.to_string()),
+        ("language", prompt.language.clone()),
+        ("difficulty", prompt.difficulty.clone()),
+        ("existing_questions", existing),
+        ("source_material", prompt.source_material.trim().to_string()),
+    ]);
+    render_template(GENERATION_TEMPLATE, &vars)
+}
+
+/// Ask the provider for questions and validate what comes back
+pub async fn generate_questions(
+    provider: &dyn CompletionProvider,
+    prompt: &GenerationPrompt,
+) -> Result<GenerationOutcome, AppError> {
+    if prompt.count == 0 || prompt.count > MAX_QUESTIONS_PER_REQUEST {
+        return Err(AppError::Validation(format!("Question count must be between 1 and {}", MAX_QUESTIONS_PER_REQUEST)));
+    }
+
+    let messages = vec![ClaudeMessage {
+        role: "user".to_string(),
+        content: render_generation_prompt(prompt)?,
+    }];
+
+    let response = provider.complete(GENERATION_SYSTEM_PROMPT, messages).await?;
+    let mut outcome = parse_generation_response(&response, &prompt.existing_questions)?;
+
+    // Extra questions beyond the requested count are dropped rather than stored
+    let extra = outcome.accepted.split_off((prompt.count as usize).min(outcome.accepted.len()));
+    for (offset, question) in extra.into_iter().enumerate() {
+        outcome.rejected.push(RejectedQuestion {
+            index: prompt.count as usize + offset,
+            question: Some(question.question),
+            reason: "More questions than requested".to_string(),
+        });
+    }
+
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm_provider::CompletionFuture;
+    use std::sync::Mutex;
+
+    /// Returns a scripted response and remembers the prompt it was sent
+    struct MockProvider {
+        response: Result<String, AppError>,
+        last_prompt: Mutex<Option<String>>,
+    }
+
+    impl MockProvider {
+        fn new(response: &str) -> Self {
+            MockProvider {
+                response: Ok(response.to_string()),
+                last_prompt: Mutex::new(None),
+            }
+        }
+    }
+
+    impl CompletionProvider for MockProvider {
+        fn complete<'a>(&'a self, _system_prompt: &'a str, messages: Vec<ClaudeMessage>) -> CompletionFuture<'a> {
+            *self.last_prompt.lock().unwrap() = messages.last().map(|m| m.content.clone());
+            let response = self.response.clone();
+            Box::pin(async move { response })
+        }
+
+        fn model(&self) -> &str {
+            "mock"
+        }
+    }
+
+    fn prompt(count: u32) -> GenerationPrompt {
+        GenerationPrompt {
+            count,
+            language: "python".to_string(),
+            difficulty: "easy".to_string(),
+            source_material: "Lists hold ordered items. Use append() to add to the end.".to_string(),
+            existing_questions: vec!["What does len() return?".to_string()],
+        }
+    }
+
+    fn question_json(question: &str, options: &[&str], correct_index: i64, explanation: &str) -> String {
+        serde_json::json!({
+            "question": question,
+            "options": options,
+            "correctIndex": correct_index,
+            "explanation": explanation,
+            "topic": "lists",
+            "tags": ["lists"],
+        })
+        .to_string()
+    }
+
+    fn run<T>(future: impl std::future::Future<Output = T>) -> T {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn accepts_valid_questions_from_mock_provider() {
+        let response = format!(
+            "{{\"questions\": [{}, {}]}}",
+            question_json("Which method adds to the end of a list?", &["append()", "add()", "push()", "insert()"], 0, "append() adds one item to the end."),
+            question_json("What is [1, 2][0]?", &["1", "2", "0", "IndexError"], 0, "Indexes start at 0."),
+        );
+        let provider = MockProvider::new(&response);
+
+        let outcome = run(generate_questions(&provider, &prompt(2))).unwrap();
+
+        assert_eq!(outcome.accepted.len(), 2);
+        assert!(outcome.rejected.is_empty());
+        assert_eq!(outcome.accepted[0].options[0], "append()");
+
+        let sent = provider.last_prompt.lock().unwrap().clone().unwrap();
+        assert!(sent.contains("exactly 2 multiple-choice questions"));
+        assert!(sent.contains("Use append() to add to the end."));
+        assert!(sent.contains("- What does len() return?"));
+    }
+
+    #[test]
+    fn tolerates_code_fences_around_json() {
+        let response = format!(
+            "Here you go:\n```json\n{{\"questions\": [{}]}}\n```",
+            question_json("Which method adds to the end of a list?", &["append()", "add()", "push()", "insert()"], 0, "append() adds one item to the end."),
+        );
+
+        let outcome = parse_generation_response(&response, &[]).unwrap();
+        assert_eq!(outcome.accepted.len(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_questions_individually() {
+        let response = format!(
+            "{{\"questions\": [{}, {}, {}, {}, {}, {}]}}",
+            question_json("Three options?", &["a", "b", "c"], 0, "Only three."),
+            question_json("Repeated options?", &["a", "b", "A", "c"], 0, "Case-insensitive repeat."),
+            question_json("Index out of range?", &["a", "b", "c", "d"], 4, "Bad index."),
+            question_json("No explanation?", &["a", "b", "c", "d"], 1, "  "),
+            r#"{"question": "Two answers?", "options": ["a", "b", "c", "d"], "correctIndex": [0, 1], "explanation": "Multiple."}"#,
+            question_json("What does len() return?", &["a", "b", "c", "d"], 2, "Already in the bank."),
+        );
+
+        let outcome = parse_generation_response(&response, &prompt(1).existing_questions).unwrap();
+
+        assert!(outcome.accepted.is_empty());
+        let reasons: Vec<&str> = outcome.rejected.iter().map(|r| r.reason.as_str()).collect();
+        assert!(reasons[0].contains("Expected 4 options"));
+        assert!(reasons[1].contains("different"));
+        assert!(reasons[2].contains("out of range"));
+        assert!(reasons[3].contains("Explanation"));
+        assert!(reasons[4].contains("Invalid structure"));
+        assert!(reasons[5].contains("Duplicate"));
+        assert_eq!(outcome.rejected[4].question.as_deref(), Some("Two answers?"));
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let response = r#"{"questions": [{"question": "Q?", "options": ["a", "b", "c", "d"], "correctIndex": 0, "explanation": "E", "answer": "a"}]}"#;
+        let outcome = parse_generation_response(response, &[]).unwrap();
+        assert_eq!(outcome.rejected.len(), 1);
+    }
+
+    #[test]
+    fn fails_when_response_is_not_json() {
+        let provider = MockProvider::new("Sorry, I can't help with that.");
+        assert!(run(generate_questions(&provider, &prompt(1))).is_err());
+
+        assert!(parse_generation_response(r#"{"items": []}"#, &[]).is_err());
+    }
+
+    #[test]
+    fn drops_questions_beyond_requested_count() {
+        let response = format!(
+            "{{\"questions\": [{}, {}]}}",
+            question_json("First?", &["a", "b", "c", "d"], 0, "Because."),
+            question_json("Second?", &["a", "b", "c", "d"], 1, "Because."),
+        );
+        let provider = MockProvider::new(&response);
+
+        let outcome = run(generate_questions(&provider, &prompt(1))).unwrap();
+
+        assert_eq!(outcome.accepted.len(), 1);
+        assert_eq!(outcome.rejected[0].reason, "More questions than requested");
+    }
+
+    #[test]
+    fn provider_errors_are_returned() {
+        let provider = MockProvider {
+            response: Err(AppError::Ai("Daily token budget exceeded".to_string())),
+            last_prompt: Mutex::new(None),
+        };
+        let err = run(generate_questions(&provider, &prompt(1))).unwrap_err();
+        assert!(err.message().contains("budget"));
+    }
+
+    #[test]
+    fn rejects_out_of_range_counts() {
+        let provider = MockProvider::new("{}");
+        assert!(run(generate_questions(&provider, &prompt(0))).is_err());
+        assert!(run(generate_questions(&provider, &prompt(MAX_QUESTIONS_PER_REQUEST + 1))).is_err());
+    }
+}
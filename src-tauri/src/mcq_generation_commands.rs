@@ -0,0 +1,419 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::db;
+use crate::llm_provider::{ClaudeProvider, CompletionProvider};
+use crate::mcq_generation::{self, GeneratedQuestion, GenerationPrompt, RejectedQuestion};
+use crate::retrieval_commands;
+use crate::tutor_commands;
+use crate::error::{AppError, ResultExt};
+
+const DEFAULT_QUESTION_COUNT: u32 = 5;
+const TOPIC_PASSAGE_LIMIT: usize = 4;
+
+/// What to generate questions from: a lesson (language + lessonId) or a free-form topic
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McqGenerationRequest {
+    pub language: String,
+    pub lesson_id: Option<i64>,
+    pub topic: Option<String>,
+    pub difficulty: String,
+    pub count: Option<u32>,
+    pub model: String,
+    pub user_id: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McqDraft {
+    pub id: i64,
+    pub batch_id: String,
+    pub question_text: String,
+    pub explanation: String,
+    pub options: String, // JSON array of strings
+    pub correct_answer_index: i32,
+    pub difficulty: String,
+    pub topic: Option<String>,
+    pub language: String,
+    pub tags: Option<String>, // JSON array
+    pub source_kind: String,
+    pub source_ref: String,
+    pub model: String,
+    pub status: String,
+    pub review_notes: Option<String>,
+    pub approved_question_id: Option<String>,
+    pub created_at: String,
+    pub reviewed_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McqGenerationResult {
+    pub batch_id: String,
+    pub drafts: Vec<McqDraft>,
+    pub rejected: Vec<RejectedQuestion>,
+}
+
+const DRAFT_COLUMNS: &str = "id, batch_id, question_text, explanation, options, correct_answer_index, difficulty,
+    topic, language, tags, source_kind, source_ref, model, status, review_notes, approved_question_id,
+    created_at, reviewed_at";
+
+fn row_to_draft(row: &rusqlite::Row) -> rusqlite::Result<McqDraft> {
+    Ok(McqDraft {
+        id: row.get(0)?,
+        batch_id: row.get(1)?,
+        question_text: row.get(2)?,
+        explanation: row.get(3)?,
+        options: row.get(4)?,
+        correct_answer_index: row.get(5)?,
+        difficulty: row.get(6)?,
+        topic: row.get(7)?,
+        language: row.get(8)?,
+        tags: row.get(9)?,
+        source_kind: row.get(10)?,
+        source_ref: row.get(11)?,
+        model: row.get(12)?,
+        status: row.get(13)?,
+        review_notes: row.get(14)?,
+        approved_question_id: row.get(15)?,
+        created_at: row.get(16)?,
+        reviewed_at: row.get(17)?,
+    })
+}
+
+fn get_draft(conn: &Connection, draft_id: i64) -> Result<McqDraft, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM mcq_question_drafts WHERE id = ?1", DRAFT_COLUMNS),
+        params![draft_id],
+        row_to_draft,
+    )
+    .optional()
+    .context("Failed to load draft")?
+    .ok_or_else(|| AppError::NotFound(format!("Draft {} not found", draft_id)))
+}
+
+fn get_pending_draft(conn: &Connection, draft_id: i64) -> Result<McqDraft, AppError> {
+    let draft = get_draft(conn, draft_id)?;
+    if draft.status != "pending" {
+        return Err(AppError::Validation(format!("Draft {} has already been {}", draft_id, draft.status)));
+    }
+    Ok(draft)
+}
+
+// ============================================================================
+// Generation
+// ============================================================================
+
+/// Source material for the prompt, plus the (source_kind, source_ref) stored on each draft
+fn gather_source_material(app: &AppHandle, request: &McqGenerationRequest) -> Result<(String, String, String), AppError> {
+    if let Some(lesson_id) = request.lesson_id {
+        let lesson = tutor_commands::load_lesson(app, &request.language, lesson_id)?;
+
+        let mut material = format!("Lesson: {}\n\n{}", lesson.title, lesson.description.trim());
+        if !lesson.learning_objectives.is_empty() {
+            material.push_str("\n\nLearning objectives:\n");
+            material.push_str(
+                &lesson
+                    .learning_objectives
+                    .iter()
+                    .map(|objective| format!("- {}", objective))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+        if !lesson.common_mistakes.is_empty() {
+            material.push_str("\n\nCommon mistakes:\n");
+            material.push_str(
+                &lesson
+                    .common_mistakes
+                    .iter()
+                    .map(|m| format!("- {}: {}", m.mistake, m.explanation))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+
+        return Ok((material, "lesson".to_string(), format!("{}:{}", request.language, lesson_id)));
+    }
+
+    let topic = request
+        .topic
+        .as_deref()
+        .map(str::trim)
+        .filter(|topic| !topic.is_empty())
+        .ok_or_else(|| AppError::Validation("Provide either a lessonId or a topic".to_string()))?;
+
+    // Ground topic questions in the course content when the retrieval index has something
+    let passages = retrieval_commands::ensure_index_fresh(app)
+        .and_then(|_| {
+            let conn = db::get_connection(app)?;
+            retrieval_commands::search_index(&conn, topic, Some(&request.language), TOPIC_PASSAGE_LIMIT)
+        })
+        .unwrap_or_else(|e| {
+            log::warn!("Retrieval failed for MCQ topic '{}': {}", topic, e);
+            Vec::new()
+        });
+
+    let mut material = format!("Topic: {}", topic);
+    for passage in passages {
+        material.push_str(&format!("\n\n[{}]\n{}", passage.title, passage.content.trim()));
+    }
+
+    Ok((material, "topic".to_string(), topic.to_string()))
+}
+
+fn existing_question_texts(conn: &Connection, language: &str) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT question_text FROM mcq_questions WHERE language = ?1
+             UNION ALL
+             SELECT question_text FROM mcq_question_drafts WHERE language = ?1 AND status = 'pending'",
+        )
+        .context("Failed to prepare question query")?;
+
+    let texts = stmt
+        .query_map(params![language], |row| row.get(0))
+        .context("Failed to query existing questions")?
+        .collect::<Result<Vec<String>, _>>()
+        .context("Failed to read existing questions")?;
+
+    Ok(texts)
+}
+
+fn insert_drafts(
+    conn: &mut Connection,
+    batch_id: &str,
+    questions: &[GeneratedQuestion],
+    request: &McqGenerationRequest,
+    source_kind: &str,
+    source_ref: &str,
+    model: &str,
+) -> Result<Vec<McqDraft>, AppError> {
+    let tx = conn
+        .transaction()
+        .context("Failed to start transaction")?;
+
+    let mut ids = Vec::new();
+    for question in questions {
+        let options = serde_json::to_string(&question.options)
+            .context("Failed to serialize options")?;
+        let tags = if question.tags.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&question.tags).context("Failed to serialize tags")?)
+        };
+        let topic = question.topic.clone().or_else(|| request.topic.clone());
+
+        tx.execute(
+            "INSERT INTO mcq_question_drafts
+             (batch_id, question_text, explanation, options, correct_answer_index, difficulty,
+              topic, language, tags, source_kind, source_ref, model)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                batch_id,
+                question.question,
+                question.explanation,
+                options,
+                question.correct_index,
+                request.difficulty,
+                topic,
+                request.language,
+                tags,
+                source_kind,
+                source_ref,
+                model,
+            ],
+        )
+        .context("Failed to save draft")?;
+        ids.push(tx.last_insert_rowid());
+    }
+
+    tx.commit()
+        .context("Failed to commit drafts")?;
+
+    ids.into_iter().map(|id| get_draft(conn, id)).collect()
+}
+
+/// Generate questions with a provider and queue the valid ones as pending drafts
+pub async fn generate_drafts_with(
+    app: &AppHandle,
+    provider: &dyn CompletionProvider,
+    request: &McqGenerationRequest,
+) -> Result<McqGenerationResult, AppError> {
+    if !matches!(request.difficulty.as_str(), "easy" | "medium" | "hard" | "expert") {
+        return Err(AppError::Validation(format!("Invalid difficulty: {}", request.difficulty)));
+    }
+
+    let (source_material, source_kind, source_ref) = gather_source_material(app, request)?;
+    let existing_questions = {
+        let conn = db::get_connection(app)?;
+        existing_question_texts(&conn, &request.language)?
+    };
+
+    let prompt = GenerationPrompt {
+        count: request.count.unwrap_or(DEFAULT_QUESTION_COUNT),
+        language: request.language.clone(),
+        difficulty: request.difficulty.clone(),
+        source_material,
+        existing_questions,
+    };
+    let outcome = mcq_generation::generate_questions(provider, &prompt).await?;
+
+    for rejected in &outcome.rejected {
+        log::warn!("Rejected generated question #{}: {}", rejected.index, rejected.reason);
+    }
+
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let mut conn = db::get_connection(app)?;
+    let drafts = insert_drafts(
+        &mut conn,
+        &batch_id,
+        &outcome.accepted,
+        request,
+        &source_kind,
+        &source_ref,
+        provider.model(),
+    )?;
+
+    Ok(McqGenerationResult {
+        batch_id,
+        drafts,
+        rejected: outcome.rejected,
+    })
+}
+
+// ============================================================================
+// Draft Review Commands
+// ============================================================================
+
+/// Ask the configured model for questions about a lesson or topic; valid ones become pending drafts
+#[tauri::command]
+pub async fn generate_mcq_drafts(app: AppHandle, request: McqGenerationRequest) -> Result<McqGenerationResult, AppError> {
+    let provider = ClaudeProvider {
+        app: app.clone(),
+        model: request.model.clone(),
+        user_id: request.user_id,
+        context: "mcq_generation".to_string(),
+    };
+    generate_drafts_with(&app, &provider, &request).await
+}
+
+#[tauri::command]
+pub fn list_mcq_drafts(app: AppHandle, status: Option<String>) -> Result<Vec<McqDraft>, AppError> {
+    let conn = db::get_connection(&app)?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM mcq_question_drafts WHERE ?1 IS NULL OR status = ?1 ORDER BY created_at DESC, id",
+            DRAFT_COLUMNS
+        ))
+        .context("Failed to prepare draft query")?;
+
+    let drafts = stmt
+        .query_map(params![status], row_to_draft)
+        .context("Failed to query drafts")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read drafts")?;
+
+    Ok(drafts)
+}
+
+/// Save an author's edits to a pending draft. The edited question is validated again.
+#[tauri::command]
+pub fn update_mcq_draft(app: AppHandle, draft: McqDraft) -> Result<McqDraft, AppError> {
+    let conn = db::get_connection(&app)?;
+    get_pending_draft(&conn, draft.id)?;
+
+    let options: Vec<String> =
+        serde_json::from_str(&draft.options).context("Options must be a JSON array of strings")?;
+    mcq_generation::validate_question(&GeneratedQuestion {
+        question: draft.question_text.clone(),
+        options,
+        correct_index: draft.correct_answer_index as i64,
+        explanation: draft.explanation.clone(),
+        topic: draft.topic.clone(),
+        tags: Vec::new(),
+    })?;
+
+    conn.execute(
+        "UPDATE mcq_question_drafts
+         SET question_text = ?2, explanation = ?3, options = ?4, correct_answer_index = ?5,
+             difficulty = ?6, topic = ?7, tags = ?8
+         WHERE id = ?1",
+        params![
+            draft.id,
+            draft.question_text,
+            draft.explanation,
+            draft.options,
+            draft.correct_answer_index,
+            draft.difficulty,
+            draft.topic,
+            draft.tags,
+        ],
+    )
+    .context("Failed to update draft")?;
+
+    get_draft(&conn, draft.id)
+}
+
+/// Publish a pending draft into mcq_questions. Returns the new question id.
+#[tauri::command]
+pub fn approve_mcq_draft(app: AppHandle, draft_id: i64, question_id: Option<String>) -> Result<String, AppError> {
+    let mut conn = db::get_connection(&app)?;
+    let draft = get_pending_draft(&conn, draft_id)?;
+    let question_id = question_id.unwrap_or_else(|| format!("ai-{}-{}", draft.language, draft.id));
+
+    let tx = conn
+        .transaction()
+        .context("Failed to start transaction")?;
+
+    tx.execute(
+        "INSERT INTO mcq_questions
+         (id, question_text, explanation, options, correct_answer_index, difficulty,
+          topic, language, tags, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, datetime('now'), datetime('now'))",
+        params![
+            question_id,
+            draft.question_text,
+            draft.explanation,
+            draft.options,
+            draft.correct_answer_index,
+            draft.difficulty,
+            draft.topic,
+            draft.language,
+            draft.tags,
+        ],
+    )
+    .with_context(|| format!("Failed to insert question {}", question_id))?;
+
+    tx.execute(
+        "UPDATE mcq_question_drafts
+         SET status = 'approved', approved_question_id = ?2, reviewed_at = datetime('now')
+         WHERE id = ?1",
+        params![draft_id, question_id],
+    )
+    .context("Failed to update draft")?;
+
+    tx.commit()
+        .context("Failed to commit approval")?;
+
+    Ok(question_id)
+}
+
+#[tauri::command]
+pub fn reject_mcq_draft(app: AppHandle, draft_id: i64, notes: Option<String>) -> Result<(), AppError> {
+    let conn = db::get_connection(&app)?;
+    get_pending_draft(&conn, draft_id)?;
+
+    conn.execute(
+        "UPDATE mcq_question_drafts
+         SET status = 'rejected', review_notes = ?2, reviewed_at = datetime('now')
+         WHERE id = ?1",
+        params![draft_id, notes],
+    )
+    .context("Failed to reject draft")?;
+
+    Ok(())
+}
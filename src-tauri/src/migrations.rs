@@ -0,0 +1,493 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Instant;
+use crate::error::{AppError, ResultExt};
+
+// ============================================================================
+// Schema Migrations
+// ============================================================================
+//
+// Every migrations/*.sql file is embedded at build time (see build.rs) and
+// applied in file name order, exactly once, inside its own transaction. The
+// version (file name without .sql) and a checksum of the file are recorded in
+// schema_migrations. If an applied file has since changed, or the database has
+// migrations this build doesn't know about, startup is refused rather than run
+// against a schema the code doesn't match.
+//
+// A few early migrations expect columns that the old startup code added with
+// ALTER TABLE around them rather than in the file. Those column steps run in
+// the migration's own transaction, ahead of its SQL, so the files stay exactly
+// as they were applied to existing databases.
+//
+// Seed steps load bundled content (INSERT OR IGNORE) right after the migration
+// they depend on. Unlike migrations they are re-applied whenever their source
+// changes, so new content rows reach existing installs.
+
+pub struct Migration {
+    pub version: &'static str,
+    pub sql: &'static str,
+}
+
+include!(concat!(env!("OUT_DIR"), "/migrations.rs"));
+
+struct SeedStep {
+    name: &'static str,
+    /// Migration this seed runs after
+    after: &'static str,
+    sql: &'static str,
+}
+
+const SEED_STEPS: &[SeedStep] = &[
+    SeedStep {
+        name: "puzzles-schema",
+        after: "001_languages",
+        sql: include_str!("../../course-framework-output/database/puzzles-schema.sql"),
+    },
+    SeedStep {
+        name: "puzzles-seed",
+        after: "001_languages",
+        sql: include_str!("../../course-framework-output/database/puzzles-seed.sql"),
+    },
+    SeedStep {
+        name: "playground-seed",
+        after: "006_playground",
+        sql: include_str!("../../course-framework-output/database/playground-seed.sql"),
+    },
+    SeedStep {
+        name: "gamification-seed",
+        after: "007_gamification",
+        sql: include_str!("../../course-framework-output/database/gamification-seed.sql"),
+    },
+    SeedStep {
+        name: "rpg-dungeon-seed",
+        after: "019_shop_system",
+        sql: include_str!("../../course-framework-output/database/rpg-dungeon-seed.sql"),
+    },
+    SeedStep {
+        name: "shop-equipment",
+        after: "019_shop_system",
+        // shop_inventory has no unique key on the item, so skip items already stocked
+        sql: "INSERT INTO shop_inventory (item_type, item_id, available, required_level, stock_quantity)
+              SELECT 'equipment', e.id, TRUE, e.required_level, NULL FROM equipment_items e
+              WHERE NOT EXISTS (
+                  SELECT 1 FROM shop_inventory s WHERE s.item_type = 'equipment' AND s.item_id = e.id
+              );",
+    },
+    SeedStep {
+        name: "rpg-challenges-multiple-choice",
+        after: "019_shop_system",
+        sql: include_str!("../../course-framework-output/database/rpg-challenges-multiple-choice.sql"),
+    },
+];
+
+struct ColumnStep {
+    /// Migration these columns are added ahead of
+    before: &'static str,
+    sql: &'static str,
+}
+
+const COLUMN_STEPS: &[ColumnStep] = &[
+    ColumnStep {
+        before: "014_narrative_dungeon_system",
+        sql: "ALTER TABLE dungeon_challenges ADD COLUMN choices TEXT;
+              ALTER TABLE dungeon_challenges ADD COLUMN correct_answer TEXT;",
+    },
+    ColumnStep {
+        before: "016_fix_equipment_slots",
+        sql: "ALTER TABLE character_equipment ADD COLUMN shield_id TEXT REFERENCES equipment_items(id);
+              ALTER TABLE character_equipment ADD COLUMN helmet_id TEXT REFERENCES equipment_items(id);
+              ALTER TABLE character_equipment ADD COLUMN chest_id TEXT REFERENCES equipment_items(id);
+              ALTER TABLE character_equipment ADD COLUMN boots_id TEXT REFERENCES equipment_items(id);
+              ALTER TABLE user_abilities ADD COLUMN ability_level INTEGER DEFAULT 1;",
+    },
+    ColumnStep {
+        before: "017_add_charisma_stat",
+        sql: "ALTER TABLE character_stats ADD COLUMN charisma INTEGER DEFAULT 1;",
+    },
+    ColumnStep {
+        before: "019_shop_system",
+        sql: "ALTER TABLE character_stats ADD COLUMN current_gold INTEGER DEFAULT 100;
+              ALTER TABLE user_dungeon_progress ADD COLUMN in_town BOOLEAN DEFAULT TRUE;",
+    },
+    ColumnStep {
+        before: "029_achievement_viewed_tracking",
+        sql: "ALTER TABLE user_achievement_progress ADD COLUMN viewed_at TEXT;",
+    },
+];
+
+/// Column steps to run ahead of a migration, as one batch
+fn column_steps(version: &str) -> String {
+    COLUMN_STEPS
+        .iter()
+        .filter(|step| step.before == version)
+        .map(|step| step.sql)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Databases created before schema_migrations existed ran every migration up to
+/// this one on each startup, so they are recorded as applied without re-running
+const LEGACY_BASELINE: &str = "034_seed_mcq_questions";
+/// Files up to the baseline that the old startup code never ran
+const LEGACY_UNAPPLIED: &[&str] = &["024_level1_challenges"];
+
+const CREATE_SCHEMA_MIGRATIONS: &str = "
+    CREATE TABLE IF NOT EXISTS schema_migrations (
+        version TEXT PRIMARY KEY,
+        kind TEXT NOT NULL CHECK(kind IN ('migration', 'seed')),
+        checksum TEXT NOT NULL,
+        baseline BOOLEAN NOT NULL DEFAULT FALSE,
+        execution_ms INTEGER NOT NULL DEFAULT 0,
+        applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+    );";
+
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    pub applied: Vec<String>,
+    pub seeded: Vec<String>,
+    /// Legacy migrations recorded without running
+    pub baselined: usize,
+}
+
+/// SHA-256 of the file with line endings normalized, so Windows checkouts match
+fn checksum(sql: &str) -> String {
+    format!("{:x}", Sha256::digest(sql.replace("\r\n", "\n").as_bytes()))
+}
+
+fn seed_version(name: &str) -> String {
+    format!("seed:{}", name)
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool, AppError> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![table],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .context("Failed to inspect database schema")
+}
+
+/// Checksums of everything already applied, keyed by version
+fn load_applied(conn: &Connection) -> Result<HashMap<String, (String, String)>, AppError> {
+    let mut stmt = conn
+        .prepare("SELECT version, kind, checksum FROM schema_migrations")
+        .context("Failed to load applied migrations")?;
+
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, (row.get::<_, String>(1)?, row.get::<_, String>(2)?))))
+        .context("Failed to load applied migrations")?;
+
+    rows.collect::<Result<HashMap<_, _>, _>>()
+        .context("Failed to load applied migrations")
+}
+
+/// Refuse to continue if an applied migration was edited or is unknown to this build
+fn check_drift(migrations: &[Migration], applied: &HashMap<String, (String, String)>) -> Result<(), AppError> {
+    for (version, (kind, recorded)) in applied {
+        if kind != "migration" {
+            continue;
+        }
+        match migrations.iter().find(|m| m.version == version) {
+            Some(migration) if checksum(migration.sql) != *recorded => {
+                return Err(AppError::Internal(format!(
+                    "Migration {} has changed since it was applied to this database (checksum mismatch). \
+                     Add a new migration instead of editing an applied one.",
+                    version
+                )));
+            }
+            Some(_) => {}
+            None => {
+                return Err(AppError::Internal(format!(
+                    "Database has migration {} which this version of the app doesn't know about. \
+                     It was probably opened by a newer version.",
+                    version
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Record the legacy migrations of a pre-existing database as applied
+fn baseline_legacy(conn: &mut Connection, migrations: &[Migration]) -> Result<usize, AppError> {
+    let tx = conn
+        .transaction()
+        .context("Failed to start baseline transaction")?;
+
+    let mut count = 0;
+    for migration in migrations
+        .iter()
+        .filter(|m| m.version <= LEGACY_BASELINE && !LEGACY_UNAPPLIED.contains(&m.version))
+    {
+        tx.execute(
+            "INSERT INTO schema_migrations (version, kind, checksum, baseline) VALUES (?1, 'migration', ?2, TRUE)",
+            params![migration.version, checksum(migration.sql)],
+        )
+        .with_context(|| format!("Failed to record baseline migration {}", migration.version))?;
+        count += 1;
+    }
+
+    tx.commit()
+        .context("Failed to commit baseline")?;
+    Ok(count)
+}
+
+/// Run one step in a transaction and record it. `setup` runs first in the same
+/// transaction but is not part of the recorded checksum.
+fn apply(conn: &mut Connection, version: &str, kind: &str, setup: &str, sql: &str) -> Result<(), AppError> {
+    let started = Instant::now();
+    let tx = conn
+        .transaction()
+        .with_context(|| format!("Failed to start transaction for {}", version))?;
+
+    tx.execute_batch(setup)
+        .with_context(|| format!("Failed to prepare {} {}", kind, version))?;
+    tx.execute_batch(sql)
+        .with_context(|| format!("Failed to apply {} {}", kind, version))?;
+
+    tx.execute(
+        "INSERT INTO schema_migrations (version, kind, checksum, execution_ms) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(version) DO UPDATE SET
+            checksum = excluded.checksum,
+            execution_ms = excluded.execution_ms,
+            applied_at = datetime('now')",
+        params![version, kind, checksum(sql), started.elapsed().as_millis() as i64],
+    )
+    .with_context(|| format!("Failed to record {} {}", kind, version))?;
+
+    tx.commit()
+        .with_context(|| format!("Failed to commit {} {}", kind, version))
+}
+
+/// Bring the database up to date with the embedded migrations and seeds
+pub fn run_migrations(conn: &mut Connection) -> Result<MigrationReport, AppError> {
+    let migrations = MIGRATIONS;
+
+    for seed in SEED_STEPS {
+        if !migrations.iter().any(|m| m.version == seed.after) {
+            return Err(AppError::Internal(format!("Seed step {} runs after unknown migration {}", seed.name, seed.after)));
+        }
+    }
+    for step in COLUMN_STEPS {
+        if !migrations.iter().any(|m| m.version == step.before) {
+            return Err(AppError::Internal(format!("Column step runs before unknown migration {}", step.before)));
+        }
+    }
+
+    let mut report = MigrationReport::default();
+
+    // A database with tables but no schema_migrations predates the runner
+    let is_legacy = !table_exists(conn, "schema_migrations")? && table_exists(conn, "languages")?;
+
+    conn.execute_batch(CREATE_SCHEMA_MIGRATIONS)
+        .context("Failed to create schema_migrations table")?;
+
+    if is_legacy {
+        log::info!("Existing database without migration history, recording legacy migrations up to {}", LEGACY_BASELINE);
+        report.baselined = baseline_legacy(conn, migrations)?;
+    }
+
+    let applied = load_applied(conn)?;
+    check_drift(migrations, &applied)?;
+
+    for migration in migrations {
+        if !applied.contains_key(migration.version) {
+            log::info!("Applying migration {}", migration.version);
+            apply(conn, migration.version, "migration", &column_steps(migration.version), migration.sql)?;
+            report.applied.push(migration.version.to_string());
+        }
+
+        for seed in SEED_STEPS.iter().filter(|s| s.after == migration.version) {
+            let version = seed_version(seed.name);
+            let current = applied.get(&version).map(|(_, recorded)| recorded.as_str());
+            if current != Some(checksum(seed.sql).as_str()) {
+                log::info!("Applying seed {}", seed.name);
+                apply(conn, &version, "seed", "", seed.sql)?;
+                report.seeded.push(seed.name.to_string());
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Whether run_migrations has anything to apply, without applying it
+pub fn has_pending(conn: &Connection) -> Result<bool, AppError> {
+    if !table_exists(conn, "schema_migrations")? {
+        return Ok(true);
+    }
+
+    let applied = load_applied(conn)?;
+    let migration_pending = MIGRATIONS.iter().any(|m| !applied.contains_key(m.version));
+    let seed_pending = SEED_STEPS.iter().any(|seed| {
+        applied.get(&seed_version(seed.name)).map(|(_, recorded)| recorded.as_str()) != Some(checksum(seed.sql).as_str())
+    });
+    Ok(migration_pending || seed_pending)
+}
+
+/// Hash identifying the content these migrations and seeds ship; changes whenever any of them does
+pub fn content_fingerprint() -> String {
+    let mut hasher = Sha256::new();
+    for migration in MIGRATIONS {
+        hasher.update(format!("{}:{}\n", migration.version, checksum(migration.sql)));
+    }
+    for seed in SEED_STEPS {
+        hasher.update(format!("{}:{}\n", seed_version(seed.name), checksum(seed.sql)));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Version of the last migration applied to this database
+pub fn current_version(conn: &Connection) -> Result<Option<String>, AppError> {
+    conn.query_row(
+        "SELECT MAX(version) FROM schema_migrations WHERE kind = 'migration'",
+        [],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+    .context("Failed to read schema version")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scalar;
+
+    fn empty_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+        conn
+    }
+
+    fn has_column(conn: &Connection, table: &str, column: &str) -> bool {
+        scalar::<i64>(
+            conn,
+            "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+            params![table, column],
+        ) > 0
+    }
+
+    /// A database as the old startup code left it: everything up to the baseline
+    /// except the migrations it never ran, and no migration history
+    fn legacy_db() -> Connection {
+        let conn = empty_db();
+        for migration in MIGRATIONS
+            .iter()
+            .filter(|m| m.version <= LEGACY_BASELINE && !LEGACY_UNAPPLIED.contains(&m.version))
+        {
+            conn.execute_batch(&column_steps(migration.version)).unwrap();
+            conn.execute_batch(migration.sql).unwrap();
+            for seed in SEED_STEPS.iter().filter(|s| s.after == migration.version) {
+                conn.execute_batch(seed.sql).unwrap();
+            }
+        }
+        conn
+    }
+
+    #[test]
+    fn fresh_database_applies_every_migration_and_seed() {
+        let mut conn = empty_db();
+        let report = run_migrations(&mut conn).unwrap();
+
+        assert_eq!(report.applied, MIGRATIONS.iter().map(|m| m.version.to_string()).collect::<Vec<_>>());
+        assert_eq!(report.seeded.len(), SEED_STEPS.len());
+        assert_eq!(report.baselined, 0);
+        assert_eq!(current_version(&conn).unwrap().as_deref(), MIGRATIONS.last().map(|m| m.version));
+        assert!(!has_pending(&conn).unwrap());
+
+        // Columns the migration files rely on but don't add themselves
+        assert!(has_column(&conn, "dungeon_challenges", "correct_answer"));
+        assert!(has_column(&conn, "character_equipment", "boots_id"));
+        assert!(has_column(&conn, "character_stats", "charisma"));
+        assert!(has_column(&conn, "user_dungeon_progress", "in_town"));
+        assert!(has_column(&conn, "user_achievement_progress", "viewed_at"));
+    }
+
+    #[test]
+    fn second_run_is_a_no_op() {
+        let mut conn = empty_db();
+        run_migrations(&mut conn).unwrap();
+        let recorded: i64 = scalar(&conn, "SELECT COUNT(*) FROM schema_migrations", []);
+
+        let report = run_migrations(&mut conn).unwrap();
+        assert!(report.applied.is_empty());
+        assert!(report.seeded.is_empty());
+        assert_eq!(scalar::<i64>(&conn, "SELECT COUNT(*) FROM schema_migrations", []), recorded);
+    }
+
+    #[test]
+    fn applied_files_are_recorded_with_their_own_checksum() {
+        let mut conn = empty_db();
+        run_migrations(&mut conn).unwrap();
+
+        let migration = MIGRATIONS.iter().find(|m| m.version == "017_add_charisma_stat").unwrap();
+        let recorded: String = scalar(
+            &conn,
+            "SELECT checksum FROM schema_migrations WHERE version = ?1",
+            [migration.version],
+        );
+        assert_eq!(recorded, checksum(migration.sql));
+    }
+
+    #[test]
+    fn edited_migration_is_refused() {
+        let mut conn = empty_db();
+        run_migrations(&mut conn).unwrap();
+        conn.execute(
+            "UPDATE schema_migrations SET checksum = 'edited' WHERE version = '019_shop_system'",
+            [],
+        )
+        .unwrap();
+
+        let err = run_migrations(&mut conn).unwrap_err();
+        assert!(matches!(&err, AppError::Internal(msg) if msg.contains("019_shop_system") && msg.contains("checksum")), "{:?}", err);
+    }
+
+    #[test]
+    fn unknown_applied_migration_is_refused() {
+        let mut conn = empty_db();
+        run_migrations(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO schema_migrations (version, kind, checksum) VALUES ('999_from_the_future', 'migration', 'x')",
+            [],
+        )
+        .unwrap();
+
+        let err = run_migrations(&mut conn).unwrap_err();
+        assert!(matches!(&err, AppError::Internal(msg) if msg.contains("999_from_the_future")), "{:?}", err);
+    }
+
+    #[test]
+    fn legacy_database_is_baselined_then_brought_up_to_date() {
+        let mut conn = legacy_db();
+        assert!(!table_exists(&conn, "schema_migrations").unwrap());
+        let level1_challenges = "SELECT COUNT(*) FROM dungeon_challenges WHERE id = 'str_basic_1'";
+        assert_eq!(scalar::<i64>(&conn, level1_challenges, []), 0);
+
+        let report = run_migrations(&mut conn).unwrap();
+
+        let legacy = MIGRATIONS.iter().filter(|m| m.version <= LEGACY_BASELINE);
+        assert_eq!(report.baselined, legacy.count() - LEGACY_UNAPPLIED.len());
+
+        let expected: Vec<String> = MIGRATIONS
+            .iter()
+            .filter(|m| m.version > LEGACY_BASELINE || LEGACY_UNAPPLIED.contains(&m.version))
+            .map(|m| m.version.to_string())
+            .collect();
+        assert_eq!(report.applied, expected);
+        assert_eq!(report.applied[0], "024_level1_challenges");
+        assert_eq!(scalar::<i64>(&conn, level1_challenges, []), 1);
+
+        let baseline: i64 = scalar(
+            &conn,
+            "SELECT COUNT(*) FROM schema_migrations WHERE baseline AND version <= ?1",
+            [LEGACY_BASELINE],
+        );
+        assert_eq!(baseline as usize, report.baselined);
+        assert!(!has_pending(&conn).unwrap());
+        assert!(run_migrations(&mut conn).unwrap().applied.is_empty());
+    }
+}
@@ -0,0 +1,507 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::commands::{ClaudeContent, ClaudeRequest, ClaudeResponse, ClaudeUsage};
+use crate::llm_provider::ChunkSink;
+use crate::error::{AppError, ResultExt};
+
+// ============================================================================
+// Mock LLM Provider
+// ============================================================================
+//
+// Serves scripted responses from fixture files so AI features can run offline,
+// in tests and in demos. A fixture matches on regexes over the system prompt,
+// the last user message and the model; the first match wins. Fixtures without
+// patterns are fallbacks and are only used when nothing else matches.
+//
+// Fixture files hold {"fixtures": [...]} and are read from, in order:
+//   1. the directory in MOCK_LLM_FIXTURES_DIR
+//   2. <app data>/llm-fixtures
+//   3. the fixtures built into the app (src-tauri/fixtures/llm)
+
+pub const FIXTURES_DIR_ENV: &str = "MOCK_LLM_FIXTURES_DIR";
+
+const BUILTIN_FIXTURES: &[(&str, &str)] = &[
+    ("errors.json", include_str!("../fixtures/llm/errors.json")),
+    ("mcq_generation.json", include_str!("../fixtures/llm/mcq_generation.json")),
+    ("code_review.json", include_str!("../fixtures/llm/code_review.json")),
+    ("tutor.json", include_str!("../fixtures/llm/tutor.json")),
+];
+
+const DEFAULT_CHUNK_SIZE: usize = 24;
+
+lazy_static::lazy_static! {
+    static ref SHARED_MOCK: Mutex<Option<Arc<MockLlm>>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MockError {
+    RateLimit,
+    Timeout,
+    ServerError,
+}
+
+impl MockError {
+    /// Same messages the live Claude path produces, so callers handle both alike
+    fn message(self) -> String {
+        match self {
+            MockError::RateLimit => r#"Claude API error: {"type":"error","error":{"type":"rate_limit_error","message":"Number of requests has exceeded your rate limit (mock)"}}"#.to_string(),
+            MockError::Timeout => "Request timed out. Please try again.".to_string(),
+            MockError::ServerError => r#"Claude API error: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded (mock)"}}"#.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FixtureMatch {
+    pub system: Option<String>,
+    pub message: Option<String>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockFixture {
+    pub name: String,
+    #[serde(default, rename = "match")]
+    pub matcher: FixtureMatch,
+    #[serde(default)]
+    pub response: String,
+    /// Delay before the response (or error) is returned
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Characters per streamed chunk
+    #[serde(default)]
+    pub chunk_size: Option<usize>,
+    /// Delay between streamed chunks
+    #[serde(default)]
+    pub chunk_delay_ms: u64,
+    #[serde(default)]
+    pub error: Option<MockError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FixtureFile {
+    fixtures: Vec<MockFixture>,
+}
+
+/// A fixture with its patterns compiled
+struct LoadedFixture {
+    fixture: MockFixture,
+    source: String,
+    system: Option<Regex>,
+    message: Option<Regex>,
+    model: Option<Regex>,
+}
+
+impl LoadedFixture {
+    fn is_fallback(&self) -> bool {
+        self.system.is_none() && self.message.is_none() && self.model.is_none()
+    }
+
+    fn matches(&self, request: &ClaudeRequest) -> bool {
+        let last_user_message = request
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.role == "user")
+            .map(|m| m.content.as_str())
+            .unwrap_or_default();
+
+        self.system.as_ref().map_or(true, |re| re.is_match(&request.system))
+            && self.message.as_ref().map_or(true, |re| re.is_match(last_user_message))
+            && self.model.as_ref().map_or(true, |re| re.is_match(&request.model))
+    }
+}
+
+/// Summary of a loaded fixture, for the settings screen
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockFixtureInfo {
+    pub name: String,
+    pub source: String,
+    pub fallback: bool,
+    pub error: Option<MockError>,
+    pub latency_ms: u64,
+}
+
+pub struct MockLlm {
+    fixtures: Vec<LoadedFixture>,
+}
+
+fn compile_pattern(pattern: &Option<String>, fixture: &str, source: &str) -> Result<Option<Regex>, AppError> {
+    pattern
+        .as_deref()
+        .map(|pattern| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| AppError::Validation(format!("Invalid pattern in mock fixture '{}' ({}): {}", fixture, source, e)))
+        })
+        .transpose()
+}
+
+/// Rough token estimate (about four characters per token), stable across runs
+fn estimate_tokens(text: &str) -> i64 {
+    text.chars().count().div_ceil(4) as i64
+}
+
+impl MockLlm {
+    pub fn from_sources(sources: &[(String, String)]) -> Result<Self, AppError> {
+        let mut fixtures = Vec::new();
+        for (source, json) in sources {
+            let file: FixtureFile = serde_json::from_str(json)
+                .with_context(|| format!("Invalid mock fixture file {}", source))?;
+
+            for fixture in file.fixtures {
+                fixtures.push(LoadedFixture {
+                    system: compile_pattern(&fixture.matcher.system, &fixture.name, source)?,
+                    message: compile_pattern(&fixture.matcher.message, &fixture.name, source)?,
+                    model: compile_pattern(&fixture.matcher.model, &fixture.name, source)?,
+                    source: source.clone(),
+                    fixture,
+                });
+            }
+        }
+
+        // Fallbacks go last; the sort is stable so file order is kept otherwise
+        fixtures.sort_by_key(|fixture| fixture.is_fallback());
+        Ok(MockLlm { fixtures })
+    }
+
+    /// Fixtures from the given directories (sorted by file name), then the built-in ones
+    pub fn load(dirs: &[PathBuf]) -> Result<Self, AppError> {
+        let mut sources = Vec::new();
+        for dir in dirs {
+            sources.extend(read_fixture_dir(dir)?);
+        }
+        sources.extend(
+            BUILTIN_FIXTURES
+                .iter()
+                .map(|(name, json)| (format!("builtin:{}", name), json.to_string())),
+        );
+        Self::from_sources(&sources)
+    }
+
+    pub fn fixtures(&self) -> Vec<MockFixtureInfo> {
+        self.fixtures
+            .iter()
+            .map(|loaded| MockFixtureInfo {
+                name: loaded.fixture.name.clone(),
+                source: loaded.source.clone(),
+                fallback: loaded.is_fallback(),
+                error: loaded.fixture.error,
+                latency_ms: loaded.fixture.latency_ms,
+            })
+            .collect()
+    }
+
+    pub fn find(&self, request: &ClaudeRequest) -> Option<&MockFixture> {
+        self.fixtures
+            .iter()
+            .find(|loaded| loaded.matches(request))
+            .map(|loaded| &loaded.fixture)
+    }
+
+    /// Answer a request the way the Messages API would, including latency, streamed
+    /// chunks and simulated errors
+    pub async fn respond(&self, request: &ClaudeRequest, on_chunk: Option<&ChunkSink<'_>>) -> Result<ClaudeResponse, AppError> {
+        let fixture = self
+            .find(request)
+            .ok_or_else(|| AppError::NotFound("No mock fixture matches this request".to_string()))?;
+        log::debug!("Mock LLM answering with fixture '{}'", fixture.name);
+
+        tokio::time::sleep(Duration::from_millis(fixture.latency_ms)).await;
+
+        if let Some(error) = fixture.error {
+            return Err(AppError::Ai(error.message()));
+        }
+
+        if let Some(on_chunk) = on_chunk {
+            let chars: Vec<char> = fixture.response.chars().collect();
+            for (i, chunk) in chars.chunks(fixture.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE).max(1)).enumerate() {
+                if i > 0 && fixture.chunk_delay_ms > 0 {
+                    tokio::time::sleep(Duration::from_millis(fixture.chunk_delay_ms)).await;
+                }
+                on_chunk(&chunk.iter().collect::<String>());
+            }
+        }
+
+        let prompt_text: String = std::iter::once(request.system.as_str())
+            .chain(request.messages.iter().map(|m| m.content.as_str()))
+            .collect();
+
+        Ok(ClaudeResponse {
+            content: vec![ClaudeContent {
+                text: fixture.response.clone(),
+                content_type: "text".to_string(),
+            }],
+            usage: ClaudeUsage {
+                input_tokens: estimate_tokens(&prompt_text),
+                output_tokens: estimate_tokens(&fixture.response),
+            },
+        })
+    }
+}
+
+fn read_fixture_dir(dir: &Path) -> Result<Vec<(String, String)>, AppError> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read mock fixture directory {}", dir.display()))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            fs::read_to_string(&path)
+                .map(|json| (path.display().to_string(), json))
+                .with_context(|| format!("Failed to read mock fixture {}", path.display()))
+        })
+        .collect()
+}
+
+/// Directories searched for fixture files before the built-in ones
+pub fn fixture_dirs(app_data_dir: Option<PathBuf>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(dir) = std::env::var(FIXTURES_DIR_ENV) {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(app_data_dir) = app_data_dir {
+        dirs.push(app_data_dir.join("llm-fixtures"));
+    }
+    dirs
+}
+
+/// The mock used by the app, loaded on first use
+pub fn shared(app_data_dir: Option<PathBuf>) -> Result<Arc<MockLlm>, AppError> {
+    let mut shared = SHARED_MOCK
+        .lock()
+        .context("Failed to lock mock LLM")?;
+
+    if let Some(mock) = shared.as_ref() {
+        return Ok(mock.clone());
+    }
+
+    let mock = Arc::new(MockLlm::load(&fixture_dirs(app_data_dir))?);
+    *shared = Some(mock.clone());
+    Ok(mock)
+}
+
+/// Drop the loaded fixtures so edited files are picked up on the next call
+pub fn reload(app_data_dir: Option<PathBuf>) -> Result<Arc<MockLlm>, AppError> {
+    if let Ok(mut shared) = SHARED_MOCK.lock() {
+        *shared = None;
+    }
+    shared(app_data_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::ClaudeMessage;
+    use crate::llm_provider::{CompletionFuture, CompletionProvider};
+    use crate::code_review::{self, ReviewRequest};
+    use crate::mcq_generation::{self, GenerationPrompt};
+
+    /// CompletionProvider backed directly by a mock, without the usage log or budgets
+    struct MockProvider {
+        mock: Arc<MockLlm>,
+        model: String,
+    }
+
+    impl CompletionProvider for MockProvider {
+        fn complete<'a>(&'a self, system_prompt: &'a str, messages: Vec<ClaudeMessage>) -> CompletionFuture<'a> {
+            Box::pin(async move {
+                let request = ClaudeRequest {
+                    model: self.model.clone(),
+                    max_tokens: 2048,
+                    system: system_prompt.to_string(),
+                    messages,
+                };
+                let response = self.mock.respond(&request, None).await?;
+                Ok(response.content.first().map(|c| c.text.clone()).unwrap_or_default())
+            })
+        }
+
+        fn model(&self) -> &str {
+            &self.model
+        }
+    }
+
+    fn run<T>(future: impl std::future::Future<Output = T>) -> T {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .start_paused(true)
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn request(system: &str, message: &str) -> ClaudeRequest {
+        ClaudeRequest {
+            model: "claude-4-sonnet-20250514".to_string(),
+            max_tokens: 2048,
+            system: system.to_string(),
+            messages: vec![ClaudeMessage {
+                role: "user".to_string(),
+                content: message.to_string(),
+            }],
+        }
+    }
+
+    fn provider() -> MockProvider {
+        MockProvider {
+            mock: Arc::new(MockLlm::load(&[]).unwrap()),
+            model: "claude-4-sonnet-20250514".to_string(),
+        }
+    }
+
+    #[test]
+    fn builtin_fixtures_load() {
+        let mock = MockLlm::load(&[]).unwrap();
+        let fixtures = mock.fixtures();
+        assert!(!fixtures.is_empty());
+        // Fallbacks are sorted after every patterned fixture
+        let first_fallback = fixtures.iter().position(|f| f.fallback).unwrap();
+        assert!(fixtures[first_fallback..].iter().all(|f| f.fallback));
+    }
+
+    #[test]
+    fn matches_patterns_and_falls_back() {
+        let mock = MockLlm::load(&[]).unwrap();
+
+        let tutor = mock.find(&request("You are an AI coding tutor.", "I get a Traceback")).unwrap();
+        assert_eq!(tutor.name, "tutor-error-help");
+
+        let general = mock.find(&request("You are an AI coding tutor.", "What is a loop?")).unwrap();
+        assert_eq!(general.name, "tutor-default");
+
+        let fallback = mock.find(&request("Something else", "Hello")).unwrap();
+        assert_eq!(fallback.name, "fallback");
+    }
+
+    #[test]
+    fn responses_are_deterministic() {
+        let mock = MockLlm::load(&[]).unwrap();
+        let req = request("You are an AI coding tutor.", "What is a loop?");
+
+        let first = run(mock.respond(&req, None)).unwrap();
+        let second = run(mock.respond(&req, None)).unwrap();
+
+        assert_eq!(first.content[0].text, second.content[0].text);
+        assert_eq!(first.usage.input_tokens, second.usage.input_tokens);
+        assert!(first.usage.output_tokens > 0);
+    }
+
+    #[test]
+    fn simulates_rate_limits_and_timeouts() {
+        let mock = MockLlm::load(&[]).unwrap();
+
+        let rate_limited = run(mock.respond(&request("Any", "Help [mock:rate_limit]"), None)).unwrap_err();
+        assert!(rate_limited.message().contains("rate_limit_error"));
+
+        let timed_out = run(mock.respond(&request("Any", "Help [mock:timeout]"), None)).unwrap_err();
+        assert!(timed_out.message().contains("timed out"));
+
+        let overloaded = run(mock.respond(&request("Any", "Help [mock:server_error]"), None)).unwrap_err();
+        assert!(overloaded.message().contains("overloaded_error"));
+    }
+
+    #[test]
+    fn latency_is_simulated() {
+        let mock = MockLlm::from_sources(&[(
+            "test".to_string(),
+            r#"{"fixtures": [{"name": "slow", "latencyMs": 5000, "response": "done"}]}"#.to_string(),
+        )])
+        .unwrap();
+
+        // Paused time auto-advances, so this checks the simulated delay without waiting for it
+        let elapsed = run(async {
+            let started = tokio::time::Instant::now();
+            mock.respond(&request("Any", "Hi"), None).await.unwrap();
+            started.elapsed()
+        });
+        assert!(elapsed >= Duration::from_millis(5000));
+    }
+
+    #[test]
+    fn streams_response_in_chunks() {
+        let mock = MockLlm::from_sources(&[(
+            "test".to_string(),
+            r#"{"fixtures": [{"name": "stream", "response": "abcdefghij", "chunkSize": 4, "chunkDelayMs": 10}]}"#.to_string(),
+        )])
+        .unwrap();
+
+        let chunks = Mutex::new(Vec::new());
+        let sink = |chunk: &str| chunks.lock().unwrap().push(chunk.to_string());
+        let response = run(mock.respond(&request("Any", "Hi"), Some(&sink))).unwrap();
+
+        assert_eq!(*chunks.lock().unwrap(), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(response.content[0].text, "abcdefghij");
+    }
+
+    #[test]
+    fn earlier_sources_take_priority() {
+        let custom = r#"{"fixtures": [{"name": "custom-tutor", "match": {"system": "coding tutor"}, "response": "custom"}]}"#;
+        let mut sources = vec![("custom.json".to_string(), custom.to_string())];
+        sources.extend(BUILTIN_FIXTURES.iter().map(|(name, json)| (name.to_string(), json.to_string())));
+        let mock = MockLlm::from_sources(&sources).unwrap();
+
+        assert_eq!(mock.find(&request("You are an AI coding tutor.", "Hi")).unwrap().name, "custom-tutor");
+    }
+
+    #[test]
+    fn invalid_fixtures_are_reported() {
+        let bad_pattern = r#"{"fixtures": [{"name": "bad", "match": {"system": "("}, "response": ""}]}"#;
+        let err = MockLlm::from_sources(&[("bad.json".to_string(), bad_pattern.to_string())]).err().unwrap();
+        assert!(err.message().contains("bad.json"));
+
+        assert!(MockLlm::from_sources(&[("broken.json".to_string(), "{".to_string())]).is_err());
+    }
+
+    #[test]
+    fn mcq_generation_runs_against_mock() {
+        let prompt = GenerationPrompt {
+            count: 3,
+            language: "python".to_string(),
+            difficulty: "easy".to_string(),
+            source_material: "Lists, loops and functions.".to_string(),
+            existing_questions: Vec::new(),
+        };
+
+        let outcome = run(mcq_generation::generate_questions(&provider(), &prompt)).unwrap();
+        assert_eq!(outcome.accepted.len(), 3);
+        assert!(outcome.rejected.is_empty());
+    }
+
+    #[test]
+    fn code_review_runs_against_mock() {
+        let review = run(code_review::review_code(
+            &provider(),
+            &ReviewRequest {
+                language: "python",
+                title: "FizzBuzz",
+                description: "Print numbers from 1 to 15.",
+                solution: "for i in range(1, 16):\n    print(i)",
+                reference_solution: None,
+            },
+        ))
+        .unwrap();
+
+        assert_eq!(review.categories.len(), code_review::REVIEW_CATEGORIES.len());
+        assert_eq!(review.overall_score, 3.7);
+        assert_eq!(review.line_comments.len(), 1);
+    }
+}
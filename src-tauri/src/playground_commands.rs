@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
 use crate::db;
+use crate::error::AppError;
 
 // ============================================================================
 // TYPES
@@ -73,7 +74,7 @@ pub struct PlaygroundSession {
 pub fn get_playground_projects(
     app: AppHandle,
     user_id: i32,
-) -> Result<Vec<PlaygroundProject>, String> {
+) -> Result<Vec<PlaygroundProject>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn
@@ -85,7 +86,7 @@ pub fn get_playground_projects(
              WHERE user_id = ?1
              ORDER BY updated_at DESC",
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     let projects = stmt
         .query_map([user_id], |row| {
@@ -108,15 +109,15 @@ pub fn get_playground_projects(
                 last_run_at: row.get(15)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(projects)
 }
 
 #[tauri::command]
-pub fn get_playground_project(app: AppHandle, project_id: String) -> Result<PlaygroundProject, String> {
+pub fn get_playground_project(app: AppHandle, project_id: String) -> Result<PlaygroundProject, AppError> {
     let conn = db::get_connection(&app)?;
 
     let project = conn
@@ -148,7 +149,7 @@ pub fn get_playground_project(app: AppHandle, project_id: String) -> Result<Play
                 })
             },
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(project)
 }
@@ -162,7 +163,7 @@ pub fn create_playground_project(
     language_id: String,
     code: String,
     tags: Option<String>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let conn = db::get_connection(&app)?;
 
     // Generate unique ID
@@ -173,7 +174,7 @@ pub fn create_playground_project(
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         params![project_id, user_id, name, description, language_id, code, tags],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(project_id)
 }
@@ -188,7 +189,7 @@ pub fn update_playground_project(
     tags: Option<String>,
     is_public: Option<bool>,
     is_favorite: Option<bool>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     // Build dynamic update query
@@ -237,30 +238,30 @@ pub fn update_playground_project(
     let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
 
     conn.execute(&query, params_refs.as_slice())
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_playground_project(app: AppHandle, project_id: String) -> Result<(), String> {
+pub fn delete_playground_project(app: AppHandle, project_id: String) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute("DELETE FROM playground_projects WHERE id = ?1", [&project_id])
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn update_project_last_run(app: AppHandle, project_id: String) -> Result<(), String> {
+pub fn update_project_last_run(app: AppHandle, project_id: String) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
         "UPDATE playground_projects SET last_run_at = CURRENT_TIMESTAMP WHERE id = ?1",
         [&project_id],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(())
 }
@@ -274,7 +275,7 @@ pub fn get_playground_templates(
     app: AppHandle,
     language_id: Option<String>,
     category: Option<String>,
-) -> Result<Vec<PlaygroundTemplate>, String> {
+) -> Result<Vec<PlaygroundTemplate>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut query = String::from(
@@ -297,7 +298,7 @@ pub fn get_playground_templates(
 
     query.push_str(" ORDER BY order_index, name");
 
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&query)?;
 
     let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec
         .iter()
@@ -320,15 +321,15 @@ pub fn get_playground_templates(
                 order_index: row.get(10)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(templates)
 }
 
 #[tauri::command]
-pub fn get_playground_template(app: AppHandle, template_id: String) -> Result<PlaygroundTemplate, String> {
+pub fn get_playground_template(app: AppHandle, template_id: String) -> Result<PlaygroundTemplate, AppError> {
     let conn = db::get_connection(&app)?;
 
     let template = conn
@@ -353,7 +354,7 @@ pub fn get_playground_template(app: AppHandle, template_id: String) -> Result<Pl
                 })
             },
         )
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(template)
 }
@@ -369,7 +370,7 @@ pub fn get_playground_snippets(
     category: Option<String>,
     user_only: bool,
     user_id: i32,
-) -> Result<Vec<PlaygroundSnippet>, String> {
+) -> Result<Vec<PlaygroundSnippet>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut query = String::from(
@@ -397,7 +398,7 @@ pub fn get_playground_snippets(
 
     query.push_str(" ORDER BY use_count DESC, name");
 
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&query)?;
 
     let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
 
@@ -415,9 +416,9 @@ pub fn get_playground_snippets(
                 created_at: row.get(8)?,
             })
         })
-        .map_err(|e| e.to_string())?
+        ?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        ?;
 
     Ok(snippets)
 }
@@ -431,7 +432,7 @@ pub fn create_playground_snippet(
     language_id: String,
     category: Option<String>,
     code: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let conn = db::get_connection(&app)?;
 
     // Generate unique ID
@@ -442,20 +443,20 @@ pub fn create_playground_snippet(
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         params![snippet_id, user_id, name, description, language_id, category, code],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(snippet_id)
 }
 
 #[tauri::command]
-pub fn increment_snippet_use_count(app: AppHandle, snippet_id: String) -> Result<(), String> {
+pub fn increment_snippet_use_count(app: AppHandle, snippet_id: String) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
         "UPDATE playground_snippets SET use_count = use_count + 1 WHERE id = ?1",
         [&snippet_id],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(())
 }
@@ -469,7 +470,7 @@ pub fn get_playground_session(
     app: AppHandle,
     user_id: i32,
     language_id: String,
-) -> Result<Option<PlaygroundSession>, String> {
+) -> Result<Option<PlaygroundSession>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let result = conn.query_row(
@@ -491,7 +492,7 @@ pub fn get_playground_session(
     match result {
         Ok(session) => Ok(Some(session)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(e.into()),
     }
 }
 
@@ -501,7 +502,7 @@ pub fn save_playground_session(
     user_id: i32,
     language_id: String,
     code: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
@@ -512,7 +513,7 @@ pub fn save_playground_session(
             last_saved_at = CURRENT_TIMESTAMP",
         params![user_id, language_id, code],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(())
 }
@@ -527,7 +528,7 @@ pub fn fork_playground_project(
     user_id: i32,
     original_project_id: String,
     new_name: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let conn = db::get_connection(&app)?;
 
     // Get original project
@@ -550,14 +551,14 @@ pub fn fork_playground_project(
             original.tags
         ],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     // Increment fork count on original
     conn.execute(
         "UPDATE playground_projects SET fork_count = fork_count + 1 WHERE id = ?1",
         [&original_project_id],
     )
-    .map_err(|e| e.to_string())?;
+    ?;
 
     Ok(new_id)
 }
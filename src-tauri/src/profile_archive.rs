@@ -0,0 +1,496 @@
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::error::{AppError, ResultExt};
+use crate::game::clock::Clock;
+use crate::migrations;
+
+// ============================================================================
+// Profile Archives
+// ============================================================================
+//
+// A learner's progress exported as JSON so it can move to another install.
+// Rows are copied column by column from the tables in PROFILE_TABLES rather
+// than through typed structs, so columns added by later migrations travel
+// without touching this file. On import every row gets the new user's id and
+// fresh surrogate keys; references to content (items, puzzles, quests...) are
+// checked against the importing database before anything is written.
+
+pub const ARCHIVE_FORMAT: &str = "code-tutor-profile";
+/// Bump when the archive layout changes in a way older importers can't read
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Most problems listed in a rejected import's error message
+const MAX_REPORTED_PROBLEMS: usize = 20;
+
+struct ProfileTable {
+    name: &'static str,
+    /// Column holding the owning user's id
+    owner: &'static str,
+    /// Autoincrement key dropped on import so SQLite assigns a new one
+    row_id: Option<&'static str>,
+    /// (column, content table) pairs that must exist in the importing database
+    references: &'static [(&'static str, &'static str)],
+}
+
+/// Exported tables in insert order; users comes first so the new id is known
+const PROFILE_TABLES: &[ProfileTable] = &[
+    ProfileTable { name: "users", owner: "id", row_id: Some("id"), references: &[] },
+    ProfileTable { name: "user_currency", owner: "user_id", row_id: None, references: &[] },
+    ProfileTable { name: "character_stats", owner: "user_id", row_id: None, references: &[] },
+    ProfileTable {
+        name: "character_equipment",
+        owner: "user_id",
+        row_id: None,
+        references: &[
+            ("weapon_id", "equipment_items"),
+            ("armor_id", "equipment_items"),
+            ("accessory_id", "equipment_items"),
+            ("shield_id", "equipment_items"),
+            ("helmet_id", "equipment_items"),
+            ("chest_id", "equipment_items"),
+            ("boots_id", "equipment_items"),
+        ],
+    },
+    ProfileTable {
+        name: "user_equipment_inventory",
+        owner: "user_id",
+        row_id: Some("id"),
+        references: &[("equipment_id", "equipment_items")],
+    },
+    ProfileTable {
+        name: "user_consumable_inventory",
+        owner: "user_id",
+        row_id: Some("id"),
+        references: &[("consumable_id", "consumable_items")],
+    },
+    ProfileTable {
+        name: "user_abilities",
+        owner: "user_id",
+        row_id: Some("id"),
+        references: &[("ability_id", "abilities")],
+    },
+    ProfileTable {
+        name: "user_active_abilities",
+        owner: "user_id",
+        row_id: Some("id"),
+        references: &[("ability_id", "abilities")],
+    },
+    ProfileTable { name: "user_dungeon_progress", owner: "user_id", row_id: None, references: &[] },
+    ProfileTable {
+        name: "user_puzzle_progress",
+        owner: "user_id",
+        row_id: Some("id"),
+        references: &[("puzzle_id", "puzzles"), ("language_id", "languages")],
+    },
+    ProfileTable {
+        name: "user_quest_progress",
+        owner: "user_id",
+        row_id: Some("id"),
+        references: &[("quest_id", "quests")],
+    },
+    ProfileTable { name: "user_achievement_stats", owner: "user_id", row_id: None, references: &[] },
+    ProfileTable {
+        name: "user_achievement_progress",
+        owner: "user_id",
+        row_id: Some("id"),
+        references: &[("achievement_id", "achievements")],
+    },
+    ProfileTable {
+        name: "achievement_rewards_claimed",
+        owner: "user_id",
+        row_id: Some("id"),
+        references: &[("achievement_id", "achievements")],
+    },
+    ProfileTable {
+        name: "user_narrative_progress",
+        owner: "user_id",
+        row_id: None,
+        references: &[("current_location_id", "narrative_locations")],
+    },
+    ProfileTable {
+        name: "playground_projects",
+        owner: "user_id",
+        row_id: None,
+        references: &[("language_id", "languages")],
+    },
+];
+
+/// One row, column name to value
+pub type ArchiveRow = BTreeMap<String, serde_json::Value>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileArchive {
+    pub format: String,
+    pub archive_version: u32,
+    /// Last migration applied to the exporting database
+    pub schema_version: String,
+    pub exported_at: String,
+    pub username: String,
+    /// SHA-256 of the serialized tables
+    pub checksum: String,
+    pub tables: BTreeMap<String, Vec<ArchiveRow>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportedProfile {
+    pub user_id: i64,
+    pub username: String,
+    pub rows_imported: BTreeMap<String, usize>,
+}
+
+fn table_checksum(tables: &BTreeMap<String, Vec<ArchiveRow>>) -> Result<String, AppError> {
+    let json = serde_json::to_string(tables)
+        .context("Failed to serialize profile tables")?;
+    Ok(format!("{:x}", Sha256::digest(json.as_bytes())))
+}
+
+fn to_json(value: ValueRef) -> Result<serde_json::Value, AppError> {
+    Ok(match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned().into(),
+        ValueRef::Blob(_) => return Err(AppError::Internal("Profile tables aren't expected to hold binary data".to_string())),
+    })
+}
+
+fn to_sql(value: &serde_json::Value) -> Option<Value> {
+    match value {
+        serde_json::Value::Null => Some(Value::Null),
+        serde_json::Value::Bool(b) => Some(Value::Integer(*b as i64)),
+        serde_json::Value::Number(n) => n.as_i64().map(Value::Integer).or_else(|| n.as_f64().map(Value::Real)),
+        serde_json::Value::String(s) => Some(Value::Text(s.clone())),
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => None,
+    }
+}
+
+fn read_rows(conn: &Connection, table: &ProfileTable, user_id: i64) -> Result<Vec<ArchiveRow>, AppError> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {} WHERE {} = ?", table.name, table.owner))
+        .with_context(|| format!("Failed to read {}", table.name))?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+
+    let mut rows = stmt
+        .query(params![user_id])
+        .with_context(|| format!("Failed to read {}", table.name))?;
+
+    let mut exported = Vec::new();
+    while let Some(row) = rows.next().with_context(|| format!("Failed to read {}", table.name))? {
+        let mut values = ArchiveRow::new();
+        for (i, column) in columns.iter().enumerate() {
+            values.insert(column.clone(), to_json(row.get_ref(i)?)?);
+        }
+        exported.push(values);
+    }
+    Ok(exported)
+}
+
+/// Collect everything belonging to a user into an archive
+pub fn export_profile(conn: &Connection, user_id: i64, clock: &dyn Clock) -> Result<ProfileArchive, AppError> {
+    let username: String = conn
+        .query_row("SELECT username FROM users WHERE id = ?", params![user_id], |row| row.get(0))
+        .optional()
+        .context("Failed to load user")?
+        .ok_or_else(|| AppError::NotFound(format!("User {} not found", user_id)))?;
+
+    let mut tables = BTreeMap::new();
+    for table in PROFILE_TABLES {
+        tables.insert(table.name.to_string(), read_rows(conn, table, user_id)?);
+    }
+
+    Ok(ProfileArchive {
+        format: ARCHIVE_FORMAT.to_string(),
+        archive_version: ARCHIVE_VERSION,
+        schema_version: migrations::current_version(conn)?.unwrap_or_default(),
+        exported_at: clock.now().to_rfc3339(),
+        username,
+        checksum: table_checksum(&tables)?,
+        tables,
+    })
+}
+
+fn table_columns(conn: &Connection, table: &str) -> Result<HashSet<String>, AppError> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", table))
+        .with_context(|| format!("Failed to inspect {}", table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .with_context(|| format!("Failed to inspect {}", table))?
+        .collect::<Result<HashSet<_>, _>>()
+        .with_context(|| format!("Failed to inspect {}", table))?;
+    Ok(columns)
+}
+
+fn content_exists(conn: &Connection, table: &str, id: &Value) -> Result<bool, AppError> {
+    conn.query_row(&format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?)", table), params![id], |row| row.get(0))
+        .with_context(|| format!("Failed to look up {}", table))
+}
+
+/// Everything wrong with an archive for this database; empty when it can be imported
+fn validate(conn: &Connection, archive: &ProfileArchive, username: &str) -> Result<Vec<String>, AppError> {
+    if archive.format != ARCHIVE_FORMAT {
+        return Ok(vec![format!("Not a profile archive (format '{}')", archive.format)]);
+    }
+    if archive.archive_version > ARCHIVE_VERSION {
+        return Ok(vec![format!(
+            "Archive version {} is newer than this app supports ({}); update the app first",
+            archive.archive_version, ARCHIVE_VERSION
+        )]);
+    }
+    if table_checksum(&archive.tables)? != archive.checksum {
+        return Ok(vec!["Checksum mismatch; the archive is damaged or was edited".to_string()]);
+    }
+
+    let mut problems = Vec::new();
+    let current = migrations::current_version(conn)?.unwrap_or_default();
+    if archive.schema_version > current {
+        problems.push(format!(
+            "Archive was exported from a newer database ({}, this one is at {})",
+            archive.schema_version, current
+        ));
+    }
+
+    if archive.tables.get("users").map_or(0, Vec::len) != 1 {
+        problems.push("Archive must contain exactly one user".to_string());
+    }
+    let taken: bool = conn
+        .query_row("SELECT EXISTS(SELECT 1 FROM users WHERE username = ?)", params![username], |row| row.get(0))
+        .context("Failed to check username")?;
+    if taken {
+        problems.push(format!("A profile named '{}' already exists", username));
+    }
+
+    for name in archive.tables.keys() {
+        if !PROFILE_TABLES.iter().any(|t| t.name == name) {
+            problems.push(format!("Unknown table {}", name));
+        }
+    }
+
+    for table in PROFILE_TABLES {
+        let Some(rows) = archive.tables.get(table.name) else {
+            continue;
+        };
+        let columns = table_columns(conn, table.name)?;
+        let mut missing: HashSet<(String, String)> = HashSet::new();
+
+        for row in rows {
+            for (column, value) in row {
+                if !columns.contains(column) {
+                    missing.insert((column.clone(), "unknown column".to_string()));
+                } else if to_sql(value).is_none() {
+                    problems.push(format!("{}.{} holds a nested value", table.name, column));
+                }
+            }
+            for (column, content) in table.references {
+                let Some(value) = row.get(*column).and_then(to_sql) else {
+                    continue;
+                };
+                if value != Value::Null && !content_exists(conn, content, &value)? {
+                    missing.insert((column.to_string(), format!("{} {} doesn't exist here", content, row[*column])));
+                }
+            }
+        }
+
+        let mut missing: Vec<_> = missing.into_iter().collect();
+        missing.sort();
+        problems.extend(missing.into_iter().map(|(column, problem)| format!("{}.{}: {}", table.name, column, problem)));
+    }
+
+    Ok(problems)
+}
+
+fn insert_row(conn: &Connection, table: &str, row: &ArchiveRow) -> Result<i64, AppError> {
+    let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+    let values: Vec<Value> = row.values().filter_map(to_sql).collect();
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table,
+        columns.join(", "),
+        vec!["?"; columns.len()].join(", ")
+    );
+    conn.execute(&sql, params_from_iter(values))
+        .with_context(|| format!("Failed to import {}", table))?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Add the archived profile as a new user, optionally under a different name.
+/// Nothing is written unless the whole archive imports cleanly.
+pub fn import_profile(conn: &mut Connection, archive: &ProfileArchive, username: Option<&str>) -> Result<ImportedProfile, AppError> {
+    let username = username.unwrap_or(&archive.username).trim().to_string();
+    if username.is_empty() {
+        return Err(AppError::Validation("Username can't be empty".to_string()));
+    }
+
+    let problems = validate(conn, archive, &username)?;
+    if !problems.is_empty() {
+        let shown = problems.iter().take(MAX_REPORTED_PROBLEMS).cloned().collect::<Vec<_>>().join("; ");
+        let more = problems.len().saturating_sub(MAX_REPORTED_PROBLEMS);
+        return Err(AppError::Validation(if more > 0 {
+            format!("Profile can't be imported: {} (and {} more)", shown, more)
+        } else {
+            format!("Profile can't be imported: {}", shown)
+        }));
+    }
+
+    let tx = conn
+        .transaction()
+        .context("Failed to start import transaction")?;
+    // Forks may be listed before the project they came from
+    tx.execute_batch("PRAGMA defer_foreign_keys = ON")
+        .context("Failed to defer foreign keys")?;
+
+    // New ids for playground projects, which use text keys shared across users
+    let project_ids: HashMap<String, String> = archive
+        .tables
+        .get("playground_projects")
+        .into_iter()
+        .flatten()
+        .filter_map(|row| row.get("id").and_then(|id| id.as_str()))
+        .map(|id| (id.to_string(), format!("proj_{}", uuid::Uuid::new_v4().simple())))
+        .collect();
+
+    let mut user_id = 0;
+    let mut rows_imported = BTreeMap::new();
+    for table in PROFILE_TABLES {
+        let rows = archive.tables.get(table.name).map(Vec::as_slice).unwrap_or_default();
+        for row in rows {
+            let mut row = row.clone();
+            if let Some(row_id) = table.row_id {
+                row.remove(row_id);
+            }
+
+            if table.name == "users" {
+                row.insert("username".to_string(), username.clone().into());
+                user_id = insert_row(&tx, table.name, &row)?;
+                continue;
+            }
+
+            row.insert(table.owner.to_string(), user_id.into());
+            if table.name == "playground_projects" {
+                for column in ["id", "forked_from_id"] {
+                    // A fork of someone else's project keeps its content but not the link
+                    let remapped = row.get(column).and_then(|id| id.as_str()).map(|id| project_ids.get(id).cloned());
+                    if let Some(remapped) = remapped {
+                        row.insert(column.to_string(), remapped.map_or(serde_json::Value::Null, Into::into));
+                    }
+                }
+            }
+            insert_row(&tx, table.name, &row)?;
+        }
+        rows_imported.insert(table.name.to_string(), rows.len());
+    }
+
+    tx.commit()
+        .context("Failed to commit imported profile")?;
+
+    log::info!("Imported profile '{}' as user {}", username, user_id);
+    Ok(ImportedProfile { user_id, username, rows_imported })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::clock::SystemClock;
+    use crate::test_support::{consumable_quantity, give_consumable, give_equipment, narrative_progress, scalar, seeded_db, TestUser};
+
+    fn hero(conn: &Connection) -> i64 {
+        let user_id = TestUser::new("hero").level(4).currency(250, 3).create(conn);
+        give_consumable(conn, user_id, "health_potion", 2);
+        give_equipment(conn, user_id, "wooden_sword", 1);
+        narrative_progress(conn, user_id, "path_b_trapped_hall", &["trap_disarm:failure"]);
+        conn.execute(
+            "INSERT INTO playground_projects (id, user_id, name, language_id, code) VALUES ('proj_a', ?, 'Original', 'python', 'print(1)')",
+            params![user_id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO playground_projects (id, user_id, name, language_id, code, forked_from_id) VALUES ('proj_b', ?, 'Fork', 'python', 'print(2)', 'proj_a')",
+            params![user_id],
+        )
+        .unwrap();
+        user_id
+    }
+
+    #[test]
+    fn round_trip_to_another_install() {
+        let source = seeded_db();
+        let user_id = hero(&source);
+        let archive = export_profile(&source, user_id, &SystemClock).unwrap();
+        assert_eq!(archive.username, "hero");
+        assert_eq!(archive.tables["user_consumable_inventory"].len(), 1);
+
+        // Through JSON, as it travels between machines
+        let archive: ProfileArchive = serde_json::from_str(&serde_json::to_string_pretty(&archive).unwrap()).unwrap();
+
+        let mut target = seeded_db();
+        TestUser::new("someone_else").create(&target);
+        let imported = import_profile(&mut target, &archive, None).unwrap();
+        assert_eq!(imported.username, "hero");
+        assert_eq!(imported.rows_imported["playground_projects"], 2);
+
+        let id = imported.user_id;
+        assert_eq!(scalar::<i64>(&target, "SELECT level FROM character_stats WHERE user_id = ?", [id]), 4);
+        assert_eq!(scalar::<i64>(&target, "SELECT gold FROM user_currency WHERE user_id = ?", [id]), 250);
+        assert_eq!(consumable_quantity(&target, id, "health_potion"), 2);
+        assert_eq!(
+            scalar::<String>(&target, "SELECT current_location_id FROM user_narrative_progress WHERE user_id = ?", [id]),
+            "path_b_trapped_hall"
+        );
+
+        // The fork still points at the original under its new id
+        let (fork_parent, original): (String, String) = (
+            scalar(&target, "SELECT forked_from_id FROM playground_projects WHERE user_id = ? AND name = 'Fork'", [id]),
+            scalar(&target, "SELECT id FROM playground_projects WHERE user_id = ? AND name = 'Original'", [id]),
+        );
+        assert_eq!(fork_parent, original);
+        assert_ne!(original, "proj_a");
+    }
+
+    #[test]
+    fn import_alongside_original_needs_new_name() {
+        let mut conn = seeded_db();
+        let user_id = hero(&conn);
+        let archive = export_profile(&conn, user_id, &SystemClock).unwrap();
+
+        let err = import_profile(&mut conn, &archive, None).unwrap_err();
+        assert!(err.message().contains("already exists"), "{}", err);
+
+        let copy = import_profile(&mut conn, &archive, Some("hero_copy")).unwrap();
+        assert_ne!(copy.user_id, user_id);
+        assert_eq!(consumable_quantity(&conn, copy.user_id, "health_potion"), 2);
+        assert_eq!(consumable_quantity(&conn, user_id, "health_potion"), 2);
+    }
+
+    #[test]
+    fn edited_archive_is_rejected() {
+        let conn = seeded_db();
+        let user_id = hero(&conn);
+        let mut archive = export_profile(&conn, user_id, &SystemClock).unwrap();
+        archive.tables.get_mut("user_currency").unwrap()[0].insert("gold".to_string(), 1_000_000.into());
+
+        let mut target = seeded_db();
+        let err = import_profile(&mut target, &archive, None).unwrap_err();
+        assert!(err.message().contains("Checksum mismatch"), "{}", err);
+    }
+
+    #[test]
+    fn missing_content_is_reported_before_writing() {
+        let conn = seeded_db();
+        let user_id = hero(&conn);
+        let mut archive = export_profile(&conn, user_id, &SystemClock).unwrap();
+        archive.tables.get_mut("user_consumable_inventory").unwrap()[0]
+            .insert("consumable_id".to_string(), "elixir_of_nowhere".into());
+        archive.checksum = table_checksum(&archive.tables).unwrap();
+
+        let mut target = seeded_db();
+        let users_before: i64 = scalar(&target, "SELECT COUNT(*) FROM users", []);
+        let err = import_profile(&mut target, &archive, None).unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+        assert!(err.message().contains("consumable_items \"elixir_of_nowhere\""), "{}", err);
+        assert_eq!(scalar::<i64>(&target, "SELECT COUNT(*) FROM users", []), users_before);
+    }
+}
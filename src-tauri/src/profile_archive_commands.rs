@@ -0,0 +1,40 @@
+use std::fs;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::error::{AppError, ResultExt};
+use crate::game::clock::SystemClock;
+use crate::profile_archive::{self, ImportedProfile, ProfileArchive};
+
+// ============================================================================
+// Profile Export / Import Commands
+// ============================================================================
+
+/// Write a user's progress to a JSON archive at path, returning the row count
+#[tauri::command]
+pub fn export_profile(app: AppHandle, user_id: i64, path: String) -> Result<usize, AppError> {
+    let conn = db::get_connection(&app)?;
+    let archive = profile_archive::export_profile(&conn, user_id, &SystemClock)?;
+
+    let json = serde_json::to_string_pretty(&archive)
+        .context("Failed to serialize profile")?;
+    fs::write(&path, json)
+        .with_context(|| format!("Failed to write {}", path))?;
+
+    let rows = archive.tables.values().map(Vec::len).sum();
+    log::info!("Exported profile '{}' ({} rows) to {}", archive.username, rows, path);
+    Ok(rows)
+}
+
+/// Add the profile in an archive as a new user. Pass username to import under a
+/// different name, e.g. when the original is still on this install.
+#[tauri::command]
+pub fn import_profile(app: AppHandle, path: String, username: Option<String>) -> Result<ImportedProfile, AppError> {
+    let json = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path))?;
+    let archive: ProfileArchive = serde_json::from_str(&json)
+        .map_err(|e| AppError::Validation(format!("Not a valid profile archive: {}", e)))?;
+
+    let mut conn = db::get_connection(&app)?;
+    profile_archive::import_profile(&mut conn, &archive, username.as_deref())
+}
@@ -0,0 +1,74 @@
+use tauri::AppHandle;
+
+use crate::db;
+use crate::error::AppError;
+use crate::game::clock::SystemClock;
+use crate::profiles::{self, DeletedProfile, UserProfile};
+
+// ============================================================================
+// Profile Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn list_profiles(app: AppHandle, include_archived: Option<bool>) -> Result<Vec<UserProfile>, AppError> {
+    let conn = db::get_connection(&app)?;
+    profiles::list_profiles(&conn, include_archived.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn get_profile(app: AppHandle, user_id: i64) -> Result<UserProfile, AppError> {
+    let conn = db::get_connection(&app)?;
+    profiles::get_profile(&conn, user_id)
+}
+
+/// Create a profile with the same starting rows as get_or_create_user. The
+/// username defaults to a generated key when the frontend doesn't supply one.
+#[tauri::command]
+pub fn create_profile(
+    app: AppHandle,
+    display_name: String,
+    avatar: Option<String>,
+    username: Option<String>,
+) -> Result<UserProfile, AppError> {
+    let mut conn = db::get_connection(&app)?;
+    let username = username.unwrap_or_else(|| format!("profile_{}", uuid::Uuid::new_v4().simple()));
+    profiles::create_profile(&mut conn, &username, &display_name, avatar.as_deref(), &SystemClock)
+}
+
+#[tauri::command]
+pub fn rename_profile(app: AppHandle, user_id: i64, display_name: String) -> Result<UserProfile, AppError> {
+    let conn = db::get_connection(&app)?;
+    profiles::rename_profile(&conn, user_id, &display_name)
+}
+
+#[tauri::command]
+pub fn set_profile_avatar(app: AppHandle, user_id: i64, avatar: Option<String>) -> Result<UserProfile, AppError> {
+    let conn = db::get_connection(&app)?;
+    profiles::set_avatar(&conn, user_id, avatar.as_deref())
+}
+
+#[tauri::command]
+pub fn set_profile_metadata(app: AppHandle, user_id: i64, metadata: serde_json::Value) -> Result<UserProfile, AppError> {
+    let conn = db::get_connection(&app)?;
+    profiles::set_metadata(&conn, user_id, &metadata)
+}
+
+/// Mark the profile as just used, e.g. when it is selected
+#[tauri::command]
+pub fn touch_profile(app: AppHandle, user_id: i64) -> Result<UserProfile, AppError> {
+    let conn = db::get_connection(&app)?;
+    profiles::touch(&conn, user_id, &SystemClock)
+}
+
+#[tauri::command]
+pub fn set_profile_archived(app: AppHandle, user_id: i64, archived: bool) -> Result<UserProfile, AppError> {
+    let conn = db::get_connection(&app)?;
+    profiles::set_archived(&conn, user_id, archived, &SystemClock)
+}
+
+/// Permanently delete a profile and all of its progress
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, user_id: i64) -> Result<DeletedProfile, AppError> {
+    let mut conn = db::get_connection(&app)?;
+    profiles::delete_profile(&mut conn, user_id)
+}
@@ -0,0 +1,447 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::{AppError, ResultExt};
+use crate::game::clock::{self, Clock};
+
+// ============================================================================
+// Profiles
+// ============================================================================
+//
+// One install is shared by a family or classroom, one users row per learner.
+// users.username is the frontend's stable profile key; display_name, avatar and
+// metadata are what the profile picker shows and edits.
+
+/// Every table holding per-user rows, keyed by a user_id column. Deleting a
+/// profile clears all of them; a test checks new migrations don't add one
+/// that is missing here.
+pub const USER_TABLES: &[&str] = &[
+    "user_currency",
+    "currency_transactions",
+    "ledger_entries",
+    "user_inventory",
+    "purchase_history",
+    "user_active_effects",
+    "user_quest_progress",
+    "user_dungeon_progress",
+    "user_challenge_history",
+    "dungeon_combat_log",
+    "dungeon_session",
+    "playground_projects",
+    "playground_snippets",
+    "user_snippet_library",
+    "playground_likes",
+    "playground_comments",
+    "playground_sessions",
+    "user_playground_achievements",
+    "character_stats",
+    "character_equipment",
+    "user_abilities",
+    "user_boss_defeats",
+    "user_narrative_progress",
+    "skill_check_history",
+    "user_active_abilities",
+    "user_ability_cooldowns",
+    "user_dungeon_achievements",
+    "user_equipment_inventory",
+    "stat_point_history",
+    "user_consumable_inventory",
+    "shop_purchases",
+    "user_achievement_progress",
+    "user_achievement_stats",
+    "achievement_rewards_claimed",
+    "achievement_notifications",
+    "user_puzzle_progress",
+    "user_puzzle_achievements",
+    "puzzle_leaderboard",
+    "ai_usage_budgets",
+    "ai_usage_log",
+    "tutor_solution_events",
+];
+
+/// Longest display name accepted, in characters
+const MAX_DISPLAY_NAME: usize = 40;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserProfile {
+    pub id: i64,
+    pub username: String,
+    /// Falls back to the username for users created before profiles had names
+    pub display_name: String,
+    pub avatar: Option<String>,
+    pub metadata: serde_json::Value,
+    pub created_at: String,
+    pub last_active_at: Option<String>,
+    pub archived_at: Option<String>,
+}
+
+const PROFILE_COLUMNS: &str = "id, username, COALESCE(display_name, username), avatar, metadata, created_at, last_active_at, archived_at";
+
+impl UserProfile {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let metadata: String = row.get(4)?;
+        Ok(UserProfile {
+            id: row.get(0)?,
+            username: row.get(1)?,
+            display_name: row.get(2)?,
+            avatar: row.get(3)?,
+            // Metadata is only ever written by set_metadata, which stores an object
+            metadata: serde_json::from_str(&metadata).unwrap_or_else(|_| serde_json::json!({})),
+            created_at: row.get(5)?,
+            last_active_at: row.get(6)?,
+            archived_at: row.get(7)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeletedProfile {
+    pub user_id: i64,
+    pub username: String,
+    /// Rows removed per table, omitting tables the user had nothing in
+    pub rows_deleted: BTreeMap<String, usize>,
+}
+
+fn validate_display_name(display_name: &str) -> Result<String, AppError> {
+    let trimmed = display_name.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::Validation("Profile name can't be empty".to_string()));
+    }
+    if trimmed.chars().count() > MAX_DISPLAY_NAME {
+        return Err(AppError::Validation(format!("Profile name can be at most {} characters", MAX_DISPLAY_NAME)));
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Starting rows every new user needs before gameplay reads them
+pub fn initialize_user(conn: &Connection, user_id: i64) -> Result<(), AppError> {
+    // Currency
+    conn.execute(
+        "INSERT OR IGNORE INTO user_currency (user_id, gold, gems) VALUES (?, 0, 0)",
+        params![user_id]
+    ).context("Failed to initialize currency")?;
+
+    // Character stats (from migration 009, updated in migration 017)
+    // New defaults: all abilities start at 1, 2 stat points available
+    conn.execute(
+        "INSERT OR IGNORE INTO character_stats (user_id, level, strength, intelligence, dexterity, charisma, stat_points_available) VALUES (?, 1, 1, 1, 1, 1, 2)",
+        params![user_id]
+    ).context("Failed to initialize character stats")?;
+
+    // Character equipment (from migration 009)
+    conn.execute(
+        "INSERT OR IGNORE INTO character_equipment (user_id) VALUES (?)",
+        params![user_id]
+    ).context("Failed to initialize character equipment")?;
+
+    // Dungeon progress (from migration 011)
+    conn.execute(
+        "INSERT OR IGNORE INTO user_dungeon_progress (user_id, current_floor, deepest_floor_reached) VALUES (?, 1, 1)",
+        params![user_id]
+    ).context("Failed to initialize dungeon progress")?;
+
+    // Unlock basic_attack ability (from rpg-dungeon-seed.sql)
+    conn.execute(
+        "INSERT OR IGNORE INTO user_abilities (user_id, ability_id) VALUES (?, 'basic_attack')",
+        params![user_id]
+    ).context("Failed to initialize starting ability")?;
+
+    Ok(())
+}
+
+pub fn get_profile(conn: &Connection, user_id: i64) -> Result<UserProfile, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM users WHERE id = ?", PROFILE_COLUMNS),
+        params![user_id],
+        UserProfile::from_row,
+    )
+    .optional()
+    .context("Failed to load profile")?
+    .ok_or_else(|| AppError::NotFound(format!("Profile {} not found", user_id)))
+}
+
+/// Profiles ordered by most recently active, then oldest first
+pub fn list_profiles(conn: &Connection, include_archived: bool) -> Result<Vec<UserProfile>, AppError> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM users
+             WHERE ?1 OR archived_at IS NULL
+             ORDER BY last_active_at IS NULL, last_active_at DESC, created_at ASC, id ASC",
+            PROFILE_COLUMNS
+        ))
+        .context("Failed to prepare profile query")?;
+
+    let profiles = stmt
+        .query_map(params![include_archived], UserProfile::from_row)
+        .context("Failed to query profiles")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to collect profiles")?;
+
+    Ok(profiles)
+}
+
+/// Create a user with its starting rows. username is the frontend's key for the profile.
+pub fn create_profile(
+    conn: &mut Connection,
+    username: &str,
+    display_name: &str,
+    avatar: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<UserProfile, AppError> {
+    let display_name = validate_display_name(display_name)?;
+
+    let taken: bool = conn
+        .query_row("SELECT EXISTS(SELECT 1 FROM users WHERE username = ?)", params![username], |row| row.get(0))
+        .context("Failed to check username")?;
+    if taken {
+        return Err(AppError::Validation(format!("A profile with key '{}' already exists", username)));
+    }
+
+    let tx = conn
+        .transaction()
+        .context("Failed to start transaction")?;
+    tx.execute(
+        "INSERT INTO users (username, display_name, avatar, last_active_at) VALUES (?, ?, ?, ?)",
+        params![username, display_name, avatar, clock::to_sqlite(clock.now())],
+    )
+    .context("Failed to create profile")?;
+    let user_id = tx.last_insert_rowid();
+    initialize_user(&tx, user_id)?;
+    tx.commit()
+        .context("Failed to commit new profile")?;
+
+    log::info!("Created profile {} ('{}')", user_id, display_name);
+    get_profile(conn, user_id)
+}
+
+/// Run an UPDATE against one user, failing with NotFound if there is no such user
+fn update_user(conn: &Connection, user_id: i64, sql: &str, value: impl rusqlite::ToSql) -> Result<UserProfile, AppError> {
+    let updated = conn
+        .execute(sql, params![value, user_id])
+        .context("Failed to update profile")?;
+    if updated == 0 {
+        return Err(AppError::NotFound(format!("Profile {} not found", user_id)));
+    }
+    get_profile(conn, user_id)
+}
+
+pub fn rename_profile(conn: &Connection, user_id: i64, display_name: &str) -> Result<UserProfile, AppError> {
+    let display_name = validate_display_name(display_name)?;
+    update_user(conn, user_id, "UPDATE users SET display_name = ? WHERE id = ?", display_name)
+}
+
+pub fn set_avatar(conn: &Connection, user_id: i64, avatar: Option<&str>) -> Result<UserProfile, AppError> {
+    update_user(conn, user_id, "UPDATE users SET avatar = ? WHERE id = ?", avatar)
+}
+
+/// Replace the frontend-owned metadata object
+pub fn set_metadata(conn: &Connection, user_id: i64, metadata: &serde_json::Value) -> Result<UserProfile, AppError> {
+    if !metadata.is_object() {
+        return Err(AppError::Validation("Profile metadata must be a JSON object".to_string()));
+    }
+    let json = serde_json::to_string(metadata)
+        .context("Failed to serialize profile metadata")?;
+    update_user(conn, user_id, "UPDATE users SET metadata = ? WHERE id = ?", json)
+}
+
+/// Record that the profile was just used
+pub fn touch(conn: &Connection, user_id: i64, clock: &dyn Clock) -> Result<UserProfile, AppError> {
+    update_user(conn, user_id, "UPDATE users SET last_active_at = ? WHERE id = ?", clock::to_sqlite(clock.now()))
+}
+
+/// Hide a profile from the picker without deleting anything; false restores it
+pub fn set_archived(conn: &Connection, user_id: i64, archived: bool, clock: &dyn Clock) -> Result<UserProfile, AppError> {
+    let archived_at = archived.then(|| clock::to_sqlite(clock.now()));
+    update_user(conn, user_id, "UPDATE users SET archived_at = ? WHERE id = ?", archived_at)
+}
+
+/// Delete a user and everything they own in one transaction. Other users' rows
+/// that point at this user's projects, comments or snippets are detached or
+/// removed first, and foreign keys are checked before committing.
+pub fn delete_profile(conn: &mut Connection, user_id: i64) -> Result<DeletedProfile, AppError> {
+    let profile = get_profile(conn, user_id)?;
+
+    let tx = conn
+        .transaction()
+        .context("Failed to start transaction")?;
+    tx.execute_batch("PRAGMA defer_foreign_keys = ON")
+        .context("Failed to defer foreign keys")?;
+
+    // Rows of other users that reference what is about to go
+    tx.execute(
+        "UPDATE playground_projects SET forked_from_id = NULL
+         WHERE user_id != ?1 AND forked_from_id IN (SELECT id FROM playground_projects WHERE user_id = ?1)",
+        params![user_id],
+    )
+    .context("Failed to detach forks")?;
+    tx.execute(
+        "DELETE FROM playground_likes WHERE project_id IN (SELECT id FROM playground_projects WHERE user_id = ?1)",
+        params![user_id],
+    )
+    .context("Failed to delete likes")?;
+    tx.execute(
+        "DELETE FROM playground_comments WHERE project_id IN (SELECT id FROM playground_projects WHERE user_id = ?1)",
+        params![user_id],
+    )
+    .context("Failed to delete comments")?;
+    tx.execute(
+        "UPDATE playground_comments SET parent_comment_id = NULL
+         WHERE parent_comment_id IN (SELECT id FROM playground_comments WHERE user_id = ?1)",
+        params![user_id],
+    )
+    .context("Failed to detach comment replies")?;
+    tx.execute(
+        "DELETE FROM user_snippet_library WHERE snippet_id IN (SELECT id FROM playground_snippets WHERE user_id = ?1)",
+        params![user_id],
+    )
+    .context("Failed to delete saved snippets")?;
+
+    let mut rows_deleted = BTreeMap::new();
+    for table in USER_TABLES {
+        let count = tx
+            .execute(&format!("DELETE FROM {} WHERE user_id = ?", table), params![user_id])
+            .with_context(|| format!("Failed to delete from {}", table))?;
+        if count > 0 {
+            rows_deleted.insert(table.to_string(), count);
+        }
+    }
+    tx.execute("DELETE FROM users WHERE id = ?", params![user_id])
+        .context("Failed to delete user")?;
+
+    // Deferred foreign keys are enforced here; anything still pointing at the user rolls back
+    tx.commit()
+        .context("Failed to delete profile")?;
+
+    log::info!(
+        "Deleted profile {} ('{}'), {} rows",
+        user_id,
+        profile.display_name,
+        rows_deleted.values().sum::<usize>()
+    );
+    Ok(DeletedProfile {
+        user_id,
+        username: profile.username,
+        rows_deleted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::clock::FixedClock;
+    use crate::test_support::{give_consumable, scalar, seeded_db, TestUser};
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashSet;
+
+    fn at(day: u32) -> FixedClock {
+        FixedClock(Utc.with_ymd_and_hms(2026, 5, day, 12, 0, 0).unwrap())
+    }
+
+    fn rows_for(conn: &Connection, table: &str, user_id: i64) -> i64 {
+        scalar(conn, &format!("SELECT COUNT(*) FROM {} WHERE user_id = ?", table), [user_id])
+    }
+
+    #[test]
+    fn user_tables_cover_schema() {
+        let conn = seeded_db();
+        let mut stmt = conn
+            .prepare(
+                "SELECT m.name FROM sqlite_master m JOIN pragma_table_info(m.name) c
+                 WHERE m.type = 'table' AND c.name = 'user_id'",
+            )
+            .unwrap();
+        let in_schema: HashSet<String> = stmt.query_map([], |row| row.get(0)).unwrap().map(Result::unwrap).collect();
+        let listed: HashSet<String> = USER_TABLES.iter().map(|t| t.to_string()).collect();
+
+        let missing: Vec<_> = in_schema.difference(&listed).collect();
+        assert!(missing.is_empty(), "tables with user_id missing from USER_TABLES: {:?}", missing);
+        let stale: Vec<_> = listed.difference(&in_schema).collect();
+        assert!(stale.is_empty(), "USER_TABLES lists tables without user_id: {:?}", stale);
+    }
+
+    #[test]
+    fn created_profile_has_starting_rows() {
+        let mut conn = seeded_db();
+        let profile = create_profile(&mut conn, "profile_abc", "  Ada ", Some("/avatars/wizzard.png"), &at(1)).unwrap();
+
+        assert_eq!(profile.display_name, "Ada");
+        assert_eq!(profile.last_active_at.as_deref(), Some("2026-05-01 12:00:00"));
+        for table in ["user_currency", "character_stats", "character_equipment", "user_dungeon_progress", "user_abilities"] {
+            assert_eq!(rows_for(&conn, table, profile.id), 1, "{}", table);
+        }
+
+        let err = create_profile(&mut conn, "profile_abc", "Ada again", None, &at(1)).unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+        assert!(matches!(create_profile(&mut conn, "profile_def", "   ", None, &at(1)), Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn list_orders_by_activity_and_hides_archived() {
+        let mut conn = seeded_db();
+        let ada = create_profile(&mut conn, "p_ada", "Ada", None, &at(1)).unwrap().id;
+        let bo = create_profile(&mut conn, "p_bo", "Bo", None, &at(2)).unwrap().id;
+        let cy = create_profile(&mut conn, "p_cy", "Cy", None, &at(3)).unwrap().id;
+
+        touch(&conn, ada, &at(4)).unwrap();
+        set_archived(&conn, cy, true, &at(5)).unwrap();
+
+        let ids: Vec<i64> = list_profiles(&conn, false).unwrap().iter().map(|p| p.id).filter(|id| [ada, bo, cy].contains(id)).collect();
+        assert_eq!(ids, vec![ada, bo]);
+        let ids: Vec<i64> = list_profiles(&conn, true).unwrap().iter().map(|p| p.id).filter(|id| [ada, bo, cy].contains(id)).collect();
+        assert_eq!(ids, vec![ada, cy, bo]);
+
+        assert!(set_archived(&conn, cy, false, &at(6)).unwrap().archived_at.is_none());
+    }
+
+    #[test]
+    fn edits_update_the_profile() {
+        let mut conn = seeded_db();
+        let id = create_profile(&mut conn, "p_ada", "Ada", None, &at(1)).unwrap().id;
+
+        assert_eq!(rename_profile(&conn, id, "Ada L.").unwrap().display_name, "Ada L.");
+        assert_eq!(set_avatar(&conn, id, Some("/avatars/dragon-born.png")).unwrap().avatar.as_deref(), Some("/avatars/dragon-born.png"));
+
+        let metadata = serde_json::json!({ "activeCourses": [1, 3] });
+        assert_eq!(set_metadata(&conn, id, &metadata).unwrap().metadata, metadata);
+        assert!(matches!(set_metadata(&conn, id, &serde_json::json!([1])), Err(AppError::Validation(_))));
+
+        assert!(matches!(rename_profile(&conn, 9999, "Nobody"), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn delete_cascades_and_leaves_others_intact() {
+        let mut conn = seeded_db();
+        let doomed = TestUser::new("doomed").create(&conn);
+        let keeper = TestUser::new("keeper").create(&conn);
+        give_consumable(&conn, doomed, "health_potion", 2);
+        give_consumable(&conn, keeper, "health_potion", 1);
+
+        conn.execute(
+            "INSERT INTO playground_projects (id, user_id, name, language_id, code) VALUES ('proj_doomed', ?, 'Mine', 'python', '')",
+            [doomed],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO playground_projects (id, user_id, name, language_id, code, forked_from_id) VALUES ('proj_fork', ?, 'Fork', 'python', '', 'proj_doomed')",
+            [keeper],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO playground_likes (project_id, user_id) VALUES ('proj_doomed', ?)", [keeper]).unwrap();
+
+        let deleted = delete_profile(&mut conn, doomed).unwrap();
+        assert_eq!(deleted.rows_deleted["user_consumable_inventory"], 1);
+        assert_eq!(deleted.rows_deleted["playground_projects"], 1);
+
+        for table in USER_TABLES {
+            assert_eq!(rows_for(&conn, table, doomed), 0, "{}", table);
+        }
+        assert!(matches!(get_profile(&conn, doomed), Err(AppError::NotFound(_))));
+
+        // The other learner keeps their rows; their fork is detached and the like is gone
+        assert_eq!(rows_for(&conn, "user_consumable_inventory", keeper), 1);
+        assert_eq!(rows_for(&conn, "playground_likes", keeper), 0);
+        let fork_parent: Option<String> = scalar(&conn, "SELECT forked_from_id FROM playground_projects WHERE id = 'proj_fork'", []);
+        assert_eq!(fork_parent, None);
+    }
+}
@@ -0,0 +1,534 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::commands::{ClaudeMessage, ExecutionResult};
+use crate::retrieval::RetrievedPassage;
+use crate::error::AppError;
+
+// ============================================================================
+// Tutor Prompt Templates
+// ============================================================================
+//
+// Templates live in src-tauri/prompts/v<N>/ and use two constructs:
+//   {{name}}                  - replaced with the variable's value
+//   {{#name}}...{{/name}}     - kept only when the variable is non-empty
+// A new template version is added alongside the old one so prompts recorded
+// with an older version can still be reproduced.
+
+pub const CURRENT_TEMPLATE_VERSION: u32 = 2;
+
+const MAX_CODE_CHARS: usize = 6000;
+const MAX_OUTPUT_CHARS: usize = 2000;
+const MAX_PASSAGE_CHARS: usize = 1500;
+const MAX_HISTORY_MESSAGES: usize = 10;
+
+pub struct TemplateSet {
+    pub version: u32,
+    pub system: &'static str,
+    pub lesson_context: &'static str,
+    pub puzzle_context: &'static str,
+    pub playground_context: &'static str,
+    pub user_message: &'static str,
+}
+
+const TEMPLATE_SETS: &[TemplateSet] = &[
+    TemplateSet {
+        version: 1,
+        system: include_str!("../prompts/v1/system.md"),
+        lesson_context: include_str!("../prompts/v1/lesson_context.md"),
+        puzzle_context: include_str!("../prompts/v1/puzzle_context.md"),
+        playground_context: include_str!("../prompts/v1/playground_context.md"),
+        user_message: include_str!("../prompts/v1/user_message.md"),
+    },
+    // v2 adds retrieved course material to the system prompt
+    TemplateSet {
+        version: 2,
+        system: include_str!("../prompts/v2/system.md"),
+        lesson_context: include_str!("../prompts/v1/lesson_context.md"),
+        puzzle_context: include_str!("../prompts/v1/puzzle_context.md"),
+        playground_context: include_str!("../prompts/v1/playground_context.md"),
+        user_message: include_str!("../prompts/v1/user_message.md"),
+    },
+];
+
+lazy_static::lazy_static! {
+    static ref SECTION_RE: Regex = Regex::new(r"(?s)\{\{#(\w+)\}\}\n?(.*?)\{\{/(\w+)\}\}\n?").unwrap();
+    static ref PLACEHOLDER_RE: Regex = Regex::new(r"\{\{(\w+)\}\}").unwrap();
+    static ref EXTRA_BLANK_LINES_RE: Regex = Regex::new(r"\n{3,}").unwrap();
+}
+
+// ============================================================================
+// Prompt Context Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TutorContextKind {
+    Lesson,
+    Puzzle,
+    Playground,
+}
+
+/// What the student is working on, as sent by the frontend
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TutorContextRef {
+    pub kind: TutorContextKind,
+    pub language: String,
+    pub lesson_id: Option<i64>,
+    pub puzzle_id: Option<String>,
+    pub user_code: Option<String>,
+    pub last_result: Option<ExecutionResult>,
+    #[serde(default)]
+    pub hints_used: u32,
+    pub template_version: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CommonMistake {
+    pub mistake: String,
+    pub explanation: String,
+}
+
+/// The parts of a lesson JSON file the tutor uses
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LessonContent {
+    pub id: i64,
+    pub language: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub difficulty: i64,
+    pub description: String,
+    pub hints: Vec<String>,
+    pub learning_objectives: Vec<String>,
+    pub common_mistakes: Vec<CommonMistake>,
+    // Used by the solution leak guard, never sent to the model
+    pub starter_code: String,
+    pub solution_code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PuzzleContent {
+    pub title: String,
+    pub description: String,
+    pub difficulty: String,
+    pub concepts: Vec<String>,
+    pub hints: Vec<String>,
+    // Used by the solution leak guard, never sent to the model
+    pub starter_code: String,
+    pub solution_code: String,
+}
+
+pub enum TutorContent {
+    Lesson(LessonContent),
+    Puzzle(PuzzleContent),
+    Playground,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TutorPrompt {
+    pub template_version: u32,
+    pub system_prompt: String,
+    pub messages: Vec<ClaudeMessage>,
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+pub fn get_template_set(version: Option<u32>) -> Result<&'static TemplateSet, AppError> {
+    let version = version.unwrap_or(CURRENT_TEMPLATE_VERSION);
+    TEMPLATE_SETS
+        .iter()
+        .find(|set| set.version == version)
+        .ok_or_else(|| AppError::NotFound(format!("Unknown prompt template version: {}", version)))
+}
+
+/// Render a template, failing if it references a variable that was not provided
+pub fn render_template(template: &str, vars: &HashMap<&str, String>) -> Result<String, AppError> {
+    let mut section_error = None;
+
+    let with_sections = SECTION_RE.replace_all(template, |caps: &regex::Captures| {
+        if caps[1] != caps[3] {
+            section_error = Some(format!("Mismatched template section: {{{{#{}}}}} closed by {{{{/{}}}}}", &caps[1], &caps[3]));
+        }
+        match vars.get(&caps[1]) {
+            Some(value) if !value.trim().is_empty() => caps[2].to_string(),
+            _ => String::new(),
+        }
+    });
+
+    if let Some(e) = section_error {
+        return Err(AppError::Internal(e));
+    }
+
+    let mut missing: Vec<String> = PLACEHOLDER_RE
+        .captures_iter(&with_sections)
+        .filter(|caps| !vars.contains_key(&caps[1]))
+        .map(|caps| caps[1].to_string())
+        .collect();
+    if !missing.is_empty() {
+        missing.dedup();
+        return Err(AppError::Internal(format!("Template references unknown variables: {}", missing.join(", "))));
+    }
+
+    let rendered = PLACEHOLDER_RE.replace_all(&with_sections, |caps: &regex::Captures| vars[&caps[1]].clone());
+    Ok(EXTRA_BLANK_LINES_RE.replace_all(rendered.trim(), "\n\n").to_string())
+}
+
+/// Keep the start of long text (code, stdout)
+fn truncate_head(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let head: String = text.chars().take(max_chars).collect();
+    format!("{}\n... (truncated)", head)
+}
+
+/// Keep the end of long text (stderr, where the actual error usually is)
+fn truncate_tail(text: &str, max_chars: usize) -> String {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text.to_string();
+    }
+    let tail: String = text.chars().skip(count - max_chars).collect();
+    format!("(truncated) ...\n{}", tail)
+}
+
+fn bullet_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("  - {}", item))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn numbered_list(items: &[String]) -> String {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| format!("{}. {}", i + 1, item))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Assemble the system prompt and message list for a tutor request
+pub fn build_prompt(
+    content: &TutorContent,
+    context: &TutorContextRef,
+    question: &str,
+    history: &[ClaudeMessage],
+    passages: &[RetrievedPassage],
+) -> Result<TutorPrompt, AppError> {
+    let templates = get_template_set(context.template_version)?;
+
+    let mut vars: HashMap<&str, String> = HashMap::new();
+    vars.insert("language", context.language.clone());
+    vars.insert("hints_used", context.hints_used.to_string());
+
+    let hints: &[String] = match content {
+        TutorContent::Lesson(lesson) => &lesson.hints,
+        TutorContent::Puzzle(puzzle) => &puzzle.hints,
+        TutorContent::Playground => &[],
+    };
+    let revealed = (context.hints_used as usize).min(hints.len());
+    vars.insert("hints_revealed", numbered_list(&hints[..revealed]));
+    vars.insert("next_hint", hints.get(revealed).cloned().unwrap_or_default());
+
+    let context_block = match content {
+        TutorContent::Lesson(lesson) => {
+            let mut lesson_vars = vars.clone();
+            lesson_vars.insert("lesson_title", lesson.title.clone());
+            lesson_vars.insert("lesson_subtitle", lesson.subtitle.clone().unwrap_or_default());
+            lesson_vars.insert("difficulty", lesson.difficulty.to_string());
+            lesson_vars.insert("learning_objectives", bullet_list(&lesson.learning_objectives));
+            lesson_vars.insert("lesson_description", lesson.description.trim().to_string());
+            render_template(templates.lesson_context, &lesson_vars)?
+        }
+        TutorContent::Puzzle(puzzle) => {
+            let mut puzzle_vars = vars.clone();
+            puzzle_vars.insert("puzzle_title", puzzle.title.clone());
+            puzzle_vars.insert("difficulty", puzzle.difficulty.clone());
+            puzzle_vars.insert("concepts", puzzle.concepts.join(", "));
+            puzzle_vars.insert("puzzle_description", puzzle.description.trim().to_string());
+            render_template(templates.puzzle_context, &puzzle_vars)?
+        }
+        TutorContent::Playground => render_template(templates.playground_context, &vars)?,
+    };
+    vars.insert("context_block", context_block);
+
+    let common_mistakes = match content {
+        TutorContent::Lesson(lesson) => lesson
+            .common_mistakes
+            .iter()
+            .map(|m| format!("- {}: {}", m.mistake, m.explanation))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    };
+    vars.insert("common_mistakes", common_mistakes);
+
+    let reference_passages = passages
+        .iter()
+        .map(|p| format!("[{}]\n{}", p.title, truncate_head(p.content.trim(), MAX_PASSAGE_CHARS)))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    vars.insert("reference_passages", reference_passages);
+
+    let system_prompt = render_template(templates.system, &vars)?;
+
+    // The user turn carries the question plus the current code and last run
+    let mut message_vars = vars.clone();
+    message_vars.insert("question", question.trim().to_string());
+    message_vars.insert(
+        "user_code",
+        truncate_head(context.user_code.as_deref().unwrap_or("").trim_end(), MAX_CODE_CHARS),
+    );
+    match &context.last_result {
+        Some(result) => {
+            let mut summary = format!("exit code {}", result.exit_code);
+            if result.execution_time_ms > 0 {
+                summary.push_str(&format!(", {}ms", result.execution_time_ms));
+            }
+            message_vars.insert("run_summary", summary);
+            message_vars.insert("stdout", truncate_head(result.stdout.trim_end(), MAX_OUTPUT_CHARS));
+            message_vars.insert("stderr", truncate_tail(result.stderr.trim_end(), MAX_OUTPUT_CHARS));
+        }
+        None => {
+            message_vars.insert("run_summary", String::new());
+            message_vars.insert("stdout", String::new());
+            message_vars.insert("stderr", String::new());
+        }
+    }
+    let user_message = render_template(templates.user_message, &message_vars)?;
+
+    let mut messages: Vec<ClaudeMessage> = history
+        .iter()
+        .filter(|m| m.role == "user" || m.role == "assistant")
+        .cloned()
+        .collect();
+    if messages.len() > MAX_HISTORY_MESSAGES {
+        messages.drain(..messages.len() - MAX_HISTORY_MESSAGES);
+    }
+    // The Messages API requires the conversation to start with a user turn
+    while messages.first().is_some_and(|m| m.role != "user") {
+        messages.remove(0);
+    }
+    messages.push(ClaudeMessage {
+        role: "user".to_string(),
+        content: user_message,
+    });
+
+    Ok(TutorPrompt {
+        template_version: templates.version,
+        system_prompt,
+        messages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_lesson() -> LessonContent {
+        LessonContent {
+            id: 5,
+            language: "python".to_string(),
+            title: "The Function Forge".to_string(),
+            subtitle: Some("Creating Reusable Spells".to_string()),
+            difficulty: 3,
+            description: "Learn to define functions with def.".to_string(),
+            hints: vec![
+                "Use def to define a function".to_string(),
+                "Parameters are variable names".to_string(),
+                "Use return to send a value back".to_string(),
+            ],
+            learning_objectives: vec!["Define functions with def".to_string()],
+            common_mistakes: vec![CommonMistake {
+                mistake: "Forgetting parentheses when calling".to_string(),
+                explanation: "Call functions with greet() not greet".to_string(),
+            }],
+            starter_code: String::new(),
+            solution_code: "def heal(amount):\n    return amount".to_string(),
+        }
+    }
+
+    fn context(kind: TutorContextKind) -> TutorContextRef {
+        TutorContextRef {
+            kind,
+            language: "python".to_string(),
+            lesson_id: Some(5),
+            puzzle_id: None,
+            user_code: Some("def heal(amount):\n    print(amount)".to_string()),
+            last_result: None,
+            hints_used: 0,
+            template_version: None,
+        }
+    }
+
+    fn message(role: &str, content: &str) -> ClaudeMessage {
+        ClaudeMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn render_template_fills_variables_and_sections() {
+        let vars = HashMap::from([("name", "Aria".to_string()), ("title", String::new())]);
+        let rendered = render_template("Hello {{name}}!\n{{#title}}Title: {{title}}\n{{/title}}Bye", &vars).unwrap();
+        assert_eq!(rendered, "Hello Aria!\nBye");
+    }
+
+    #[test]
+    fn render_template_rejects_unknown_variables() {
+        let err = render_template("Hello {{missing}}", &HashMap::new()).unwrap_err();
+        assert!(err.message().contains("missing"));
+    }
+
+    #[test]
+    fn render_template_rejects_mismatched_sections() {
+        let vars = HashMap::from([("a", "x".to_string()), ("b", "y".to_string())]);
+        assert!(render_template("{{#a}}text{{/b}}", &vars).is_err());
+    }
+
+    #[test]
+    fn render_template_does_not_expand_placeholders_inside_values() {
+        let vars = HashMap::from([("code", "print('{{not_a_var}}')".to_string())]);
+        assert_eq!(render_template("{{code}}", &vars).unwrap(), "print('{{not_a_var}}')");
+    }
+
+    #[test]
+    fn every_template_version_renders_all_context_kinds() {
+        let contents = [
+            TutorContent::Lesson(sample_lesson()),
+            TutorContent::Puzzle(PuzzleContent {
+                title: "FizzBuzz".to_string(),
+                description: "Print numbers with Fizz and Buzz".to_string(),
+                difficulty: "easy".to_string(),
+                concepts: vec!["loops".to_string()],
+                hints: vec!["Use the modulo operator".to_string()],
+                ..Default::default()
+            }),
+            TutorContent::Playground,
+        ];
+        let kinds = [TutorContextKind::Lesson, TutorContextKind::Puzzle, TutorContextKind::Playground];
+
+        for set in TEMPLATE_SETS {
+            for (content, kind) in contents.iter().zip(kinds) {
+                let mut ctx = context(kind);
+                ctx.template_version = Some(set.version);
+                ctx.hints_used = 1;
+                ctx.last_result = Some(ExecutionResult {
+                    stdout: "ok".to_string(),
+                    stderr: "Traceback".to_string(),
+                    exit_code: 1,
+                    execution_time_ms: 12,
+                });
+
+                let prompt = build_prompt(content, &ctx, "Why does this fail?", &[], &[]).unwrap();
+                assert_eq!(prompt.template_version, set.version);
+                assert!(!prompt.system_prompt.contains("{{"), "unrendered system prompt: {}", prompt.system_prompt);
+                assert!(!prompt.messages[0].content.contains("{{"));
+            }
+        }
+    }
+
+    #[test]
+    fn lesson_prompt_includes_lesson_data_and_progressive_hints() {
+        let mut ctx = context(TutorContextKind::Lesson);
+        ctx.hints_used = 1;
+
+        let prompt = build_prompt(&TutorContent::Lesson(sample_lesson()), &ctx, "Help", &[], &[]).unwrap();
+
+        assert!(prompt.system_prompt.contains("The Function Forge"));
+        assert!(prompt.system_prompt.contains("Define functions with def"));
+        assert!(prompt.system_prompt.contains("1. Use def to define a function"));
+        assert!(prompt.system_prompt.contains("Parameters are variable names"));
+        assert!(!prompt.system_prompt.contains("Use return to send a value back"));
+        assert!(prompt.system_prompt.contains("Forgetting parentheses when calling"));
+        assert!(!prompt.system_prompt.contains("return amount"));
+    }
+
+    #[test]
+    fn empty_sections_are_omitted() {
+        let mut lesson = sample_lesson();
+        lesson.hints.clear();
+        lesson.common_mistakes.clear();
+        let mut ctx = context(TutorContextKind::Lesson);
+        ctx.user_code = None;
+
+        let prompt = build_prompt(&TutorContent::Lesson(lesson), &ctx, "What is a function?", &[], &[]).unwrap();
+
+        assert!(!prompt.system_prompt.contains("HINTS ALREADY GIVEN"));
+        assert!(!prompt.system_prompt.contains("NEXT HINT"));
+        assert!(!prompt.system_prompt.contains("COMMON MISTAKES"));
+        assert_eq!(prompt.messages[0].content, "What is a function?");
+    }
+
+    #[test]
+    fn user_message_includes_code_and_last_run() {
+        let mut ctx = context(TutorContextKind::Lesson);
+        ctx.last_result = Some(ExecutionResult {
+            stdout: "25".to_string(),
+            stderr: format!("{}NameError: name 'x' is not defined", "x".repeat(MAX_OUTPUT_CHARS)),
+            exit_code: 1,
+            execution_time_ms: 40,
+        });
+
+        let prompt = build_prompt(&TutorContent::Lesson(sample_lesson()), &ctx, "Why?", &[], &[]).unwrap();
+        let content = &prompt.messages[0].content;
+
+        assert!(content.starts_with("Why?"));
+        assert!(content.contains("def heal(amount):"));
+        assert!(content.contains("LAST RUN (exit code 1, 40ms)"));
+        assert!(content.contains("NameError: name 'x' is not defined"));
+        assert!(content.contains("(truncated)"));
+    }
+
+    #[test]
+    fn history_is_trimmed_and_starts_with_user_turn() {
+        let mut history = vec![message("system", "ignored"), message("assistant", "Welcome!")];
+        for i in 0..12 {
+            history.push(message(if i % 2 == 0 { "user" } else { "assistant" }, &format!("turn {}", i)));
+        }
+
+        let prompt = build_prompt(&TutorContent::Playground, &context(TutorContextKind::Playground), "Next", &history, &[]).unwrap();
+
+        assert!(prompt.messages.len() <= MAX_HISTORY_MESSAGES + 1);
+        assert_eq!(prompt.messages[0].role, "user");
+        assert!(prompt.messages.iter().all(|m| m.role != "system"));
+        assert_eq!(prompt.messages.last().unwrap().role, "user");
+    }
+
+    #[test]
+    fn unknown_template_version_is_an_error() {
+        let mut ctx = context(TutorContextKind::Playground);
+        ctx.template_version = Some(999);
+        assert!(build_prompt(&TutorContent::Playground, &ctx, "Hi", &[], &[]).is_err());
+    }
+
+    #[test]
+    fn build_prompt_includes_reference_passages() {
+        let passage = RetrievedPassage {
+            passage_id: 1,
+            source_key: "doc:items.md".to_string(),
+            title: "Items › Potions".to_string(),
+            content: "Potions restore health when used.".to_string(),
+            language: None,
+            score: 2.5,
+        };
+        let ctx = context(TutorContextKind::Playground);
+
+        let prompt = build_prompt(&TutorContent::Playground, &ctx, "What do potions do?", &[], &[passage]).unwrap();
+        assert!(prompt.system_prompt.contains("REFERENCE MATERIAL"));
+        assert!(prompt.system_prompt.contains("[Items › Potions]\nPotions restore health when used."));
+
+        let without = build_prompt(&TutorContent::Playground, &ctx, "What do potions do?", &[], &[]).unwrap();
+        assert!(!without.system_prompt.contains("REFERENCE MATERIAL"));
+    }
+}
@@ -1,8 +1,10 @@
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
 use crate::db;
+use crate::error::{AppError, ResultExt};
+use crate::game::clock::AppClock;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PuzzleCategory {
@@ -45,7 +47,7 @@ pub struct PuzzleImplementation {
 
 /// Get all puzzle categories
 #[tauri::command]
-pub fn get_puzzle_categories(app: AppHandle) -> Result<Vec<PuzzleCategory>, String> {
+pub fn get_puzzle_categories(app: AppHandle) -> Result<Vec<PuzzleCategory>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn
@@ -54,7 +56,7 @@ pub fn get_puzzle_categories(app: AppHandle) -> Result<Vec<PuzzleCategory>, Stri
              FROM puzzle_categories
              ORDER BY order_index",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let categories = stmt
         .query_map([], |row| {
@@ -66,9 +68,9 @@ pub fn get_puzzle_categories(app: AppHandle) -> Result<Vec<PuzzleCategory>, Stri
                 order_index: row.get(4)?,
             })
         })
-        .map_err(|e| format!("Failed to query categories: {}", e))?
+        .context("Failed to query categories")?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect categories: {}", e))?;
+        .context("Failed to collect categories")?;
 
     Ok(categories)
 }
@@ -78,7 +80,7 @@ pub fn get_puzzle_categories(app: AppHandle) -> Result<Vec<PuzzleCategory>, Stri
 pub fn get_puzzles_by_category(
     app: AppHandle,
     category_id: String,
-) -> Result<Vec<Puzzle>, String> {
+) -> Result<Vec<Puzzle>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn
@@ -91,7 +93,7 @@ pub fn get_puzzles_by_category(
              WHERE category_id = ?1
              ORDER BY difficulty, title",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let puzzles = stmt
         .query_map(params![category_id], |row| {
@@ -112,16 +114,16 @@ pub fn get_puzzles_by_category(
                 optimal_lines_of_code: row.get(13)?,
             })
         })
-        .map_err(|e| format!("Failed to query puzzles: {}", e))?
+        .context("Failed to query puzzles")?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect puzzles: {}", e))?;
+        .context("Failed to collect puzzles")?;
 
     Ok(puzzles)
 }
 
 /// Get a puzzle by ID
 #[tauri::command]
-pub fn get_puzzle(app: AppHandle, puzzle_id: String) -> Result<Puzzle, String> {
+pub fn get_puzzle(app: AppHandle, puzzle_id: String) -> Result<Puzzle, AppError> {
     let conn = db::get_connection(&app)?;
 
     let puzzle = conn
@@ -152,7 +154,7 @@ pub fn get_puzzle(app: AppHandle, puzzle_id: String) -> Result<Puzzle, String> {
                 })
             },
         )
-        .map_err(|e| format!("Failed to get puzzle: {}", e))?;
+        .context("Failed to get puzzle")?;
 
     Ok(puzzle)
 }
@@ -163,7 +165,7 @@ pub fn get_puzzle_implementation(
     app: AppHandle,
     puzzle_id: String,
     language_id: String,
-) -> Result<PuzzleImplementation, String> {
+) -> Result<PuzzleImplementation, AppError> {
     let conn = db::get_connection(&app)?;
 
     let implementation = conn
@@ -186,7 +188,7 @@ pub fn get_puzzle_implementation(
                 })
             },
         )
-        .map_err(|e| format!("Failed to get puzzle implementation: {}", e))?;
+        .context("Failed to get puzzle implementation")?;
 
     Ok(implementation)
 }
@@ -197,7 +199,7 @@ pub fn has_puzzle_implementation(
     app: AppHandle,
     puzzle_id: String,
     language_id: String,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let conn = db::get_connection(&app)?;
 
     let count: i32 = conn
@@ -207,7 +209,7 @@ pub fn has_puzzle_implementation(
             params![puzzle_id, language_id],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to check implementation: {}", e))?;
+        .context("Failed to check implementation")?;
 
     Ok(count > 0)
 }
@@ -243,7 +245,7 @@ pub fn get_puzzle_progress(
     user_id: i32,
     puzzle_id: String,
     language_id: String,
-) -> Result<Option<UserPuzzleProgress>, String> {
+) -> Result<Option<UserPuzzleProgress>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let result = conn.query_row(
@@ -279,13 +281,13 @@ pub fn get_puzzle_progress(
     match result {
         Ok(progress) => Ok(Some(progress)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(format!("Failed to get puzzle progress: {}", e)),
+        Err(e) => Err(AppError::Database(format!("Failed to get puzzle progress: {}", e))),
     }
 }
 
 /// Get all puzzle progress for a user (for batch loading)
 #[tauri::command]
-pub fn get_all_puzzle_progress(app: AppHandle, user_id: i32) -> Result<Vec<UserPuzzleProgress>, String> {
+pub fn get_all_puzzle_progress(app: AppHandle, user_id: i32) -> Result<Vec<UserPuzzleProgress>, AppError> {
     let conn = db::get_connection(&app)?;
 
     let mut stmt = conn
@@ -297,7 +299,7 @@ pub fn get_all_puzzle_progress(app: AppHandle, user_id: i32) -> Result<Vec<UserP
              FROM user_puzzle_progress
              WHERE user_id = ?1",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let progress_iter = stmt
         .query_map(params![user_id], |row| {
@@ -320,11 +322,11 @@ pub fn get_all_puzzle_progress(app: AppHandle, user_id: i32) -> Result<Vec<UserP
                 solution_viewed_at: row.get(15)?,
             })
         })
-        .map_err(|e| format!("Failed to query progress: {}", e))?;
+        .context("Failed to query progress")?;
 
     let mut results = Vec::new();
     for progress in progress_iter {
-        results.push(progress.map_err(|e| format!("Failed to read progress: {}", e))?);
+        results.push(progress.context("Failed to read progress")?);
     }
 
     Ok(results)
@@ -338,44 +340,44 @@ pub fn record_puzzle_attempt(
     puzzle_id: String,
     language_id: String,
     user_solution: String,
-) -> Result<(), String> {
-    let conn = db::get_connection(&app)?;
-
-    // Check if progress record exists
-    let exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) > 0 FROM user_puzzle_progress
-             WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3",
-            params![user_id, &puzzle_id, &language_id],
-            |row| row.get(0),
-        )
-        .map_err(|e| format!("Failed to check progress: {}", e))?;
-
-    if exists {
-        // Update existing record
-        conn.execute(
-            "UPDATE user_puzzle_progress
-             SET attempts = attempts + 1,
-                 status = CASE WHEN status = 'not_started' THEN 'attempted' ELSE status END,
-                 user_solution = ?4,
-                 last_attempt_at = CURRENT_TIMESTAMP
-             WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3",
-            params![user_id, &puzzle_id, &language_id, &user_solution],
-        )
-        .map_err(|e| format!("Failed to update attempt: {}", e))?;
-    } else {
-        // Create new record
-        conn.execute(
-            "INSERT INTO user_puzzle_progress
-             (user_id, puzzle_id, language_id, status, attempts, user_solution,
-              first_attempt_at, last_attempt_at)
-             VALUES (?1, ?2, ?3, 'attempted', 1, ?4, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
-            params![user_id, &puzzle_id, &language_id, &user_solution],
-        )
-        .map_err(|e| format!("Failed to insert attempt: {}", e))?;
-    }
+) -> Result<(), AppError> {
+    db::unit_of_work(&app, |conn| {
+        // Check if progress record exists
+        let exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM user_puzzle_progress
+                 WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3",
+                params![user_id, &puzzle_id, &language_id],
+                |row| row.get(0),
+            )
+            .context("Failed to check progress")?;
+
+        if exists {
+            // Update existing record
+            conn.execute(
+                "UPDATE user_puzzle_progress
+                 SET attempts = attempts + 1,
+                     status = CASE WHEN status = 'not_started' THEN 'attempted' ELSE status END,
+                     user_solution = ?4,
+                     last_attempt_at = CURRENT_TIMESTAMP
+                 WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3",
+                params![user_id, &puzzle_id, &language_id, &user_solution],
+            )
+            .context("Failed to update attempt")?;
+        } else {
+            // Create new record
+            conn.execute(
+                "INSERT INTO user_puzzle_progress
+                 (user_id, puzzle_id, language_id, status, attempts, user_solution,
+                  first_attempt_at, last_attempt_at)
+                 VALUES (?1, ?2, ?3, 'attempted', 1, ?4, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+                params![user_id, &puzzle_id, &language_id, &user_solution],
+            )
+            .context("Failed to insert attempt")?;
+        }
 
-    Ok(())
+        Ok(())
+    })
 }
 
 /// Record hint usage
@@ -385,7 +387,7 @@ pub fn record_hint_used(
     user_id: i32,
     puzzle_id: String,
     language_id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
@@ -396,7 +398,7 @@ pub fn record_hint_used(
          DO UPDATE SET hints_used = hints_used + 1",
         params![user_id, puzzle_id, language_id],
     )
-    .map_err(|e| format!("Failed to record hint: {}", e))?;
+    .context("Failed to record hint")?;
 
     Ok(())
 }
@@ -408,7 +410,7 @@ pub fn record_solution_viewed(
     user_id: i32,
     puzzle_id: String,
     language_id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let conn = db::get_connection(&app)?;
 
     conn.execute(
@@ -419,7 +421,7 @@ pub fn record_solution_viewed(
          DO UPDATE SET solution_viewed = 1, solution_viewed_at = CURRENT_TIMESTAMP",
         params![user_id, puzzle_id, language_id],
     )
-    .map_err(|e| format!("Failed to record solution viewed: {}", e))?;
+    .context("Failed to record solution viewed")?;
 
     Ok(())
 }
@@ -433,82 +435,82 @@ pub fn mark_puzzle_solved(
     language_id: String,
     user_solution: String,
     solve_time_seconds: i32,
-) -> Result<i32, String> {
-    let conn = db::get_connection(&app)?;
-
-    // Check if already solved or solution was viewed
-    let (already_solved, solution_viewed): (bool, bool) = conn
-        .query_row(
-            "SELECT COALESCE(status = 'solved', 0), COALESCE(solution_viewed, 0)
-             FROM user_puzzle_progress
-             WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3",
-            params![user_id, &puzzle_id, &language_id],
-            |row| Ok((row.get(0)?, row.get(1)?)),
-        )
-        .unwrap_or((false, false));
-
-    // Get puzzle points (0 if already solved or solution was viewed)
-    let points: i32 = if already_solved || solution_viewed {
-        0
-    } else {
-        conn.query_row(
-            "SELECT points FROM puzzles WHERE id = ?1",
-            params![&puzzle_id],
-            |row| row.get(0),
-        )
-        .map_err(|e| format!("Failed to get puzzle points: {}", e))?
-    };
+) -> Result<i32, AppError> {
+    db::unit_of_work(&app, |conn| {
+        // Check if already solved or solution was viewed
+        let (already_solved, solution_viewed): (bool, bool) = conn
+            .query_row(
+                "SELECT COALESCE(status = 'solved', 0), COALESCE(solution_viewed, 0)
+                 FROM user_puzzle_progress
+                 WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3",
+                params![user_id, &puzzle_id, &language_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap_or((false, false));
+
+        // Get puzzle points (0 if already solved or solution was viewed)
+        let points: i32 = if already_solved || solution_viewed {
+            0
+        } else {
+            conn.query_row(
+                "SELECT points FROM puzzles WHERE id = ?1",
+                params![&puzzle_id],
+                |row| row.get(0),
+            )
+            .context("Failed to get puzzle points")?
+        };
 
-    // Count lines of code
-    let solution_lines = user_solution.lines().filter(|l| !l.trim().is_empty()).count() as i32;
+        // Count lines of code
+        let solution_lines = user_solution.lines().filter(|l| !l.trim().is_empty()).count() as i32;
 
-    // Update or insert progress
-    let exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) > 0 FROM user_puzzle_progress
-             WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3",
-            params![user_id, &puzzle_id, &language_id],
-            |row| row.get(0),
-        )
-        .map_err(|e| format!("Failed to check progress: {}", e))?;
-
-    if exists {
-        // Only update if not already solved
-        conn.execute(
-            "UPDATE user_puzzle_progress
-             SET status = 'solved',
-                 user_solution = ?4,
-                 solve_time = ?5,
-                 solution_lines = ?6,
-                 solved_at = CURRENT_TIMESTAMP,
-                 last_attempt_at = CURRENT_TIMESTAMP
-             WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3
-               AND status != 'solved'",
-            params![user_id, &puzzle_id, &language_id, &user_solution, solve_time_seconds, solution_lines],
-        )
-        .map_err(|e| format!("Failed to update solved status: {}", e))?;
-    } else {
-        conn.execute(
-            "INSERT INTO user_puzzle_progress
-             (user_id, puzzle_id, language_id, status, attempts, user_solution,
-              solve_time, solution_lines, first_attempt_at, solved_at, last_attempt_at)
-             VALUES (?1, ?2, ?3, 'solved', 1, ?4, ?5, ?6,
-                     CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
-            params![user_id, &puzzle_id, &language_id, &user_solution, solve_time_seconds, solution_lines],
-        )
-        .map_err(|e| format!("Failed to insert solved record: {}", e))?;
-    }
+        // Update or insert progress
+        let exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM user_puzzle_progress
+                 WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3",
+                params![user_id, &puzzle_id, &language_id],
+                |row| row.get(0),
+            )
+            .context("Failed to check progress")?;
+
+        if exists {
+            // Only update if not already solved
+            conn.execute(
+                "UPDATE user_puzzle_progress
+                 SET status = 'solved',
+                     user_solution = ?4,
+                     solve_time = ?5,
+                     solution_lines = ?6,
+                     solved_at = CURRENT_TIMESTAMP,
+                     last_attempt_at = CURRENT_TIMESTAMP
+                 WHERE user_id = ?1 AND puzzle_id = ?2 AND language_id = ?3
+                   AND status != 'solved'",
+                params![user_id, &puzzle_id, &language_id, &user_solution, solve_time_seconds, solution_lines],
+            )
+            .context("Failed to update solved status")?;
+        } else {
+            conn.execute(
+                "INSERT INTO user_puzzle_progress
+                 (user_id, puzzle_id, language_id, status, attempts, user_solution,
+                  solve_time, solution_lines, first_attempt_at, solved_at, last_attempt_at)
+                 VALUES (?1, ?2, ?3, 'solved', 1, ?4, ?5, ?6,
+                         CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+                params![user_id, &puzzle_id, &language_id, &user_solution, solve_time_seconds, solution_lines],
+            )
+            .context("Failed to insert solved record")?;
+        }
 
-    // Update puzzle solve count (only if points were awarded)
-    if points > 0 {
-        conn.execute(
-            "UPDATE puzzles SET solve_count = solve_count + 1 WHERE id = ?1",
-            params![&puzzle_id],
-        )
-        .map_err(|e| format!("Failed to update solve count: {}", e))?;
-    }
+        // Update puzzle solve count (only if points were awarded)
+        if points > 0 {
+            conn.execute(
+                "UPDATE puzzles SET solve_count = solve_count + 1 WHERE id = ?1",
+                params![&puzzle_id],
+            )
+            .context("Failed to update solve count")?;
+        }
 
-    Ok(points)
+        Ok(points)
+    })
 }
 
 // ============================================================================
@@ -535,7 +537,7 @@ pub struct DailyPuzzleStreak {
 }
 
 /// Helper: Select appropriate puzzle for user based on their progress
-fn select_daily_puzzle_for_user(conn: &rusqlite::Connection, user_id: i32) -> Result<String, String> {
+fn select_daily_puzzle_for_user(conn: &rusqlite::Connection, user_id: i32) -> Result<String, AppError> {
     // Count incomplete puzzles by difficulty
     let easy_remaining: i32 = conn
         .query_row(
@@ -610,7 +612,7 @@ fn select_daily_puzzle_for_user(conn: &rusqlite::Connection, user_id: i32) -> Re
             [],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to select random puzzle: {}", e))?
+        .context("Failed to select random puzzle")?
     } else {
         conn.query_row(
             "SELECT id FROM puzzles
@@ -625,7 +627,7 @@ fn select_daily_puzzle_for_user(conn: &rusqlite::Connection, user_id: i32) -> Re
             params![difficulty, user_id],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to select {} puzzle: {}", difficulty, e))?
+        .with_context(|| format!("Failed to select {} puzzle", difficulty))?
     };
 
     Ok(puzzle_id)
@@ -633,12 +635,12 @@ fn select_daily_puzzle_for_user(conn: &rusqlite::Connection, user_id: i32) -> Re
 
 /// Helper: Update daily puzzle streak
 fn update_daily_puzzle_streak(
-    tx: &rusqlite::Transaction,
+    conn: &Connection,
     user_id: i32,
     today: &str,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     // Get current streak data
-    let (current_streak, last_date): (i32, Option<String>) = tx
+    let (current_streak, last_date): (i32, Option<String>) = conn
         .query_row(
             "SELECT daily_puzzle_streak, daily_puzzle_last_completion_date
              FROM user_achievement_stats
@@ -653,9 +655,9 @@ fn update_daily_puzzle_streak(
         Some(last) => {
             // Parse dates
             let today_date = chrono::NaiveDate::parse_from_str(today, "%Y-%m-%d")
-                .map_err(|e| format!("Invalid date format: {}", e))?;
+                .map_err(|e| AppError::Internal(format!("Invalid date format: {}", e)))?;
             let last_date = chrono::NaiveDate::parse_from_str(&last, "%Y-%m-%d")
-                .map_err(|e| format!("Invalid last date format: {}", e))?;
+                .map_err(|e| AppError::Internal(format!("Invalid last date format: {}", e)))?;
 
             let days_diff = today_date.signed_duration_since(last_date).num_days();
 
@@ -668,29 +670,29 @@ fn update_daily_puzzle_streak(
     };
 
     // Update streak
-    tx.execute(
+    conn.execute(
         "UPDATE user_achievement_stats
          SET daily_puzzle_streak = ?2,
              longest_daily_puzzle_streak = MAX(longest_daily_puzzle_streak, ?2)
          WHERE user_id = ?1",
         params![user_id, new_streak],
     )
-    .map_err(|e| format!("Failed to update streak: {}", e))?;
+    .context("Failed to update streak")?;
 
     Ok(())
 }
 
 /// Get today's daily puzzle challenge
 #[tauri::command]
-pub fn get_daily_puzzle(app: AppHandle, user_id: i32) -> Result<DailyPuzzleChallenge, String> {
+pub fn get_daily_puzzle(app: AppHandle, user_id: i32) -> Result<DailyPuzzleChallenge, AppError> {
     let mut conn = db::get_connection(&app)?;
 
-    // Get today's date (local)
-    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    // Today's game day, in the configured timezone
+    let today = AppClock::load(&conn)?.today().to_string();
 
     // Use a transaction to prevent race conditions when creating daily puzzles
     let tx = conn.transaction()
-        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        .context("Failed to start transaction")?;
 
     // Check if daily puzzle exists for today (within transaction for consistency)
     let existing_puzzle: Option<(i32, String, i32)> = tx
@@ -712,7 +714,7 @@ pub fn get_daily_puzzle(app: AppHandle, user_id: i32) -> Result<DailyPuzzleChall
                  VALUES (?1, ?2, 50)",
                 params![&selected_puzzle_id, &today],
             )
-            .map_err(|e| format!("Failed to insert daily puzzle: {}", e))?;
+            .context("Failed to insert daily puzzle")?;
 
             let daily_id = tx.last_insert_rowid() as i32;
             (daily_id, selected_puzzle_id, 50)
@@ -721,7 +723,7 @@ pub fn get_daily_puzzle(app: AppHandle, user_id: i32) -> Result<DailyPuzzleChall
 
     // Commit the transaction
     tx.commit()
-        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+        .context("Failed to commit transaction")?;
 
     // Re-acquire connection for remaining read-only queries
     let conn = db::get_connection(&app)?;
@@ -730,19 +732,7 @@ pub fn get_daily_puzzle(app: AppHandle, user_id: i32) -> Result<DailyPuzzleChall
     let puzzle = get_puzzle(app.clone(), puzzle_id.clone())?;
 
     // Check if user completed today
-    let completed_today: bool = conn
-        .query_row(
-            "SELECT EXISTS(
-                 SELECT 1 FROM user_puzzle_progress
-                 WHERE user_id = ?1
-                 AND puzzle_id = ?2
-                 AND DATE(solved_at) = ?3
-                 AND status = 'solved'
-             )",
-            params![user_id, &puzzle_id, &today],
-            |row| row.get(0),
-        )
-        .unwrap_or(false);
+    let completed_today = last_daily_completion(&conn, user_id)?.as_deref() == Some(today.as_str());
 
     // Get languages completed in
     let mut stmt = conn
@@ -750,13 +740,13 @@ pub fn get_daily_puzzle(app: AppHandle, user_id: i32) -> Result<DailyPuzzleChall
             "SELECT language_id FROM user_puzzle_progress
              WHERE user_id = ?1 AND puzzle_id = ?2 AND status = 'solved'",
         )
-        .map_err(|e| format!("Failed to query completed languages: {}", e))?;
+        .context("Failed to query completed languages")?;
 
     let completed_languages: Vec<String> = stmt
         .query_map(params![user_id, &puzzle_id], |row| row.get(0))
-        .map_err(|e| format!("Failed to map languages: {}", e))?
+        .context("Failed to map languages")?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect languages: {}", e))?;
+        .context("Failed to collect languages")?;
 
     Ok(DailyPuzzleChallenge {
         id: daily_id,
@@ -776,84 +766,93 @@ pub fn complete_daily_puzzle(
     user_id: i32,
     puzzle_id: String,
     language_id: String,
-) -> Result<i32, String> {
+) -> Result<i32, AppError> {
     let mut conn = db::get_connection(&app)?;
+    let today = AppClock::load(&conn)?.today().to_string();
+
+    let bonus = db::run_unit_of_work(&mut conn, |conn| record_daily_completion(conn, user_id, &puzzle_id, &today))?;
+    drop(conn);
+
+    let Some(bonus_awarded) = bonus else {
+        return Ok(0); // No bonus for additional language completions on same day
+    };
+
+    // Trigger achievement checks (use existing achievement system)
+    if let Ok(achievements) = crate::achievement_commands::update_achievement_progress(
+        app.clone(),
+        user_id,
+        "daily_puzzles_completed".to_string(),
+        1,
+    ) {
+        log::info!("Unlocked achievements: {:?}", achievements);
+    }
+
+    if let Ok(achievements) = crate::achievement_commands::update_achievement_progress(
+        app,
+        user_id,
+        "daily_puzzle_streak".to_string(),
+        0, // Increment of 0, just triggers check
+    ) {
+        log::info!("Streak achievements: {:?}", achievements);
+    }
 
-    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    Ok(bonus_awarded)
+}
 
-    // Check if this is the first completion today for this puzzle
-    let first_completion_today: bool = conn
+/// Game day of the user's last daily puzzle completion
+fn last_daily_completion(conn: &Connection, user_id: i32) -> Result<Option<String>, AppError> {
+    let last_date = conn
         .query_row(
-            "SELECT NOT EXISTS(
-                 SELECT 1 FROM user_puzzle_progress
-                 WHERE user_id = ?1
-                 AND puzzle_id = ?2
-                 AND DATE(solved_at) = ?3
-                 AND status = 'solved'
-             )",
-            params![user_id, &puzzle_id, &today],
+            "SELECT daily_puzzle_last_completion_date FROM user_achievement_stats WHERE user_id = ?1",
+            params![user_id],
             |row| row.get(0),
         )
-        .unwrap_or(false);
-
-    let bonus_awarded = if first_completion_today {
-        let tx = conn.transaction().map_err(|e| e.to_string())?;
-
-        // Get bonus points
-        let bonus: i32 = tx
-            .query_row(
-                "SELECT bonus_points FROM daily_puzzles WHERE date = ?1 AND puzzle_id = ?2",
-                params![&today, &puzzle_id],
-                |row| row.get(0),
-            )
-            .unwrap_or(50);
+        .optional()
+        .context("Failed to get last daily completion")?;
+    Ok(last_date.flatten())
+}
 
-        // Update streak tracking
-        update_daily_puzzle_streak(&tx, user_id, &today)?;
+/// Count a daily puzzle completion on game day `today` and extend the streak.
+/// Returns the bonus, or None if the daily puzzle was already completed today.
+fn record_daily_completion(
+    conn: &Connection,
+    user_id: i32,
+    puzzle_id: &str,
+    today: &str,
+) -> Result<Option<i32>, AppError> {
+    if last_daily_completion(conn, user_id)?.as_deref() == Some(today) {
+        return Ok(None);
+    }
 
-        // Update achievement stats
-        tx.execute(
-            "UPDATE user_achievement_stats
-             SET daily_puzzles_completed = daily_puzzles_completed + 1,
-                 daily_puzzle_last_completion_date = ?2,
-                 updated_at = CURRENT_TIMESTAMP
-             WHERE user_id = ?1",
-            params![user_id, &today],
+    // Get bonus points
+    let bonus: i32 = conn
+        .query_row(
+            "SELECT bonus_points FROM daily_puzzles WHERE date = ?1 AND puzzle_id = ?2",
+            params![today, puzzle_id],
+            |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to update stats: {}", e))?;
-
-        tx.commit().map_err(|e| e.to_string())?;
-
-        // Trigger achievement checks (use existing achievement system)
-        if let Ok(achievements) = crate::achievement_commands::update_achievement_progress(
-            app.clone(),
-            user_id,
-            "daily_puzzles_completed".to_string(),
-            1,
-        ) {
-            log::info!("Unlocked achievements: {:?}", achievements);
-        }
+        .unwrap_or(50);
 
-        if let Ok(achievements) = crate::achievement_commands::update_achievement_progress(
-            app,
-            user_id,
-            "daily_puzzle_streak".to_string(),
-            0, // Increment of 0, just triggers check
-        ) {
-            log::info!("Streak achievements: {:?}", achievements);
-        }
+    // Update streak tracking
+    update_daily_puzzle_streak(conn, user_id, today)?;
 
-        bonus
-    } else {
-        0 // No bonus for additional language completions on same day
-    };
+    // Update achievement stats
+    conn.execute(
+        "UPDATE user_achievement_stats
+         SET daily_puzzles_completed = daily_puzzles_completed + 1,
+             daily_puzzle_last_completion_date = ?2,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE user_id = ?1",
+        params![user_id, today],
+    )
+    .context("Failed to update stats")?;
 
-    Ok(bonus_awarded)
+    Ok(Some(bonus))
 }
 
 /// Get user's daily puzzle streak information
 #[tauri::command]
-pub fn get_daily_puzzle_streak(app: AppHandle, user_id: i32) -> Result<DailyPuzzleStreak, String> {
+pub fn get_daily_puzzle_streak(app: AppHandle, user_id: i32) -> Result<DailyPuzzleStreak, AppError> {
     let conn = db::get_connection(&app)?;
 
     let (current_streak, longest_streak, total_completed, last_date): (i32, i32, i32, Option<String>) = conn
@@ -874,3 +873,43 @@ pub fn get_daily_puzzle_streak(app: AppHandle, user_id: i32) -> Result<DailyPuzz
         last_completion_date: last_date,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::clock::{self, FixedClock, TimeSettings};
+    use crate::test_support::{scalar, seeded_db, TestUser};
+
+    #[test]
+    fn daily_streaks_count_game_days() {
+        let conn = seeded_db();
+        let user_id = TestUser::new("daily").create(&conn) as i32;
+        // Days roll over at 04:00 UTC
+        let settings = TimeSettings { timezone: Some("UTC".to_string()), day_rollover_hour: 4, time_travel_seconds: 0 };
+        let complete_at = |time: &str| {
+            let today = AppClock::new(FixedClock(clock::from_sqlite(time).unwrap()), settings.clone()).today();
+            record_daily_completion(&conn, user_id, "daily", &today.to_string()).unwrap()
+        };
+        let streaks = || -> (i32, i32) {
+            conn.query_row(
+                "SELECT daily_puzzle_streak, longest_daily_puzzle_streak FROM user_achievement_stats WHERE user_id = ?",
+                [user_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(complete_at("2024-03-10 20:00:00"), Some(50));
+        // Two in the morning is still the same game day
+        assert_eq!(complete_at("2024-03-11 02:00:00"), None);
+        assert_eq!(streaks(), (1, 1));
+
+        assert_eq!(complete_at("2024-03-11 21:00:00"), Some(50));
+        assert_eq!(streaks(), (2, 2));
+
+        // Skipping a day starts over
+        assert_eq!(complete_at("2024-03-13 12:00:00"), Some(50));
+        assert_eq!(streaks(), (1, 2));
+        assert_eq!(scalar::<i32>(&conn, "SELECT daily_puzzles_completed FROM user_achievement_stats WHERE user_id = ?", [user_id]), 3);
+    }
+}
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// ============================================================================
+// Text Processing for the Retrieval Index
+// ============================================================================
+
+/// Okapi BM25 parameters
+pub const BM25_K1: f64 = 1.2;
+pub const BM25_B: f64 = 0.75;
+
+/// Passages longer than this are split further at paragraph breaks
+const MAX_PASSAGE_CHARS: usize = 1200;
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "can", "do", "does", "for", "from", "has", "have",
+    "how", "i", "if", "in", "into", "is", "it", "its", "me", "my", "of", "on", "or", "so", "that", "the",
+    "their", "then", "there", "these", "this", "to", "use", "using", "was", "we", "what", "when", "where",
+    "which", "why", "will", "with", "you", "your",
+];
+
+/// A chunk of source text ready to be indexed
+#[derive(Debug, Clone)]
+pub struct PassageDraft {
+    pub title: String,
+    pub content: String,
+    pub language: Option<String>,
+}
+
+/// A passage returned from a search, best match first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrievedPassage {
+    pub passage_id: i64,
+    pub source_key: String,
+    pub title: String,
+    pub content: String,
+    pub language: Option<String>,
+    pub score: f64,
+}
+
+/// Lowercase, split on non-word characters, drop stopwords and fold simple plurals
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| word.len() > 1)
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .map(|word| {
+            if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+                word[..word.len() - 1].to_string()
+            } else {
+                word
+            }
+        })
+        .collect()
+}
+
+pub fn term_frequencies(tokens: &[String]) -> HashMap<&str, i64> {
+    let mut frequencies = HashMap::new();
+    for token in tokens {
+        *frequencies.entry(token.as_str()).or_insert(0) += 1;
+    }
+    frequencies
+}
+
+/// BM25 contribution of one term to one passage
+pub fn bm25_term_score(tf: f64, doc_len: f64, avg_doc_len: f64, doc_freq: f64, doc_count: f64) -> f64 {
+    let idf = (1.0 + (doc_count - doc_freq + 0.5) / (doc_freq + 0.5)).ln();
+    let norm = 1.0 - BM25_B + BM25_B * doc_len / avg_doc_len.max(1.0);
+    idf * (tf * (BM25_K1 + 1.0)) / (tf + BM25_K1 * norm)
+}
+
+/// Split long text at paragraph breaks so each piece stays under MAX_PASSAGE_CHARS
+fn split_long(text: &str) -> Vec<String> {
+    if text.len() <= MAX_PASSAGE_CHARS {
+        return vec![text.to_string()];
+    }
+
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n") {
+        if !current.is_empty() && current.len() + paragraph.len() > MAX_PASSAGE_CHARS {
+            pieces.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(paragraph);
+    }
+    if !current.trim().is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Chunk markdown into passages at headings, titled "<doc title> › <heading>"
+pub fn chunk_markdown(doc_title: &str, markdown: &str, language: Option<&str>) -> Vec<PassageDraft> {
+    let mut sections: Vec<(String, String)> = Vec::new();
+    let mut heading = doc_title.to_string();
+    let mut body = String::new();
+    let mut in_code_block = false;
+
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+
+        if !in_code_block && line.starts_with('#') {
+            sections.push((heading, std::mem::take(&mut body)));
+            let text = line.trim_start_matches('#').trim().trim_matches('*').trim();
+            heading = if text.is_empty() || text == doc_title {
+                doc_title.to_string()
+            } else {
+                format!("{} › {}", doc_title, text)
+            };
+        } else {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    sections.push((heading, body));
+
+    sections
+        .into_iter()
+        .filter(|(_, body)| !body.trim().is_empty())
+        .flat_map(|(title, body)| {
+            split_long(body.trim())
+                .into_iter()
+                .map(move |content| PassageDraft {
+                    title: title.clone(),
+                    content,
+                    language: language.map(|l| l.to_string()),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_lowercased_without_stopwords_or_plurals() {
+        assert_eq!(
+            tokenize("How do Loops work with the Lists, classes and x?"),
+            vec!["loop", "work", "list", "classe"]
+        );
+        assert_eq!(tokenize("print_line(class)"), vec!["print_line", "class"]);
+    }
+
+    #[test]
+    fn bm25_rewards_frequent_rare_terms_in_short_passages() {
+        let base = bm25_term_score(2.0, 50.0, 50.0, 1.0, 10.0);
+
+        assert!(bm25_term_score(4.0, 50.0, 50.0, 1.0, 10.0) > base);
+        assert!(bm25_term_score(2.0, 50.0, 50.0, 8.0, 10.0) < base);
+        assert!(bm25_term_score(2.0, 200.0, 50.0, 1.0, 10.0) < base);
+
+        // Term frequency saturates: doubling it far from zero adds less than the first doubling
+        let gain_low = bm25_term_score(2.0, 50.0, 50.0, 1.0, 10.0) - bm25_term_score(1.0, 50.0, 50.0, 1.0, 10.0);
+        let gain_high = bm25_term_score(40.0, 50.0, 50.0, 1.0, 10.0) - bm25_term_score(20.0, 50.0, 50.0, 1.0, 10.0);
+        assert!(gain_high < gain_low);
+    }
+
+    #[test]
+    fn markdown_is_chunked_at_headings_outside_code() {
+        let markdown = "Intro text\n\n## Loops\nUse a loop.\n```python\n# not a heading\nfor x in y: pass\n```\n## Empty\n\n# Guide\nBack at the top.\n";
+        let passages = chunk_markdown("Guide", markdown, Some("python"));
+
+        let titles: Vec<&str> = passages.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, vec!["Guide", "Guide › Loops", "Guide"]);
+        assert!(passages[1].content.contains("# not a heading"));
+        assert!(passages.iter().all(|p| p.language.as_deref() == Some("python")));
+    }
+
+    #[test]
+    fn long_sections_split_at_paragraphs() {
+        let paragraph = "word ".repeat(100);
+        let markdown = [paragraph.trim(); 6].join("\n\n");
+        let passages = chunk_markdown("Long", &markdown, None);
+
+        assert!(passages.len() > 1);
+        assert!(passages.iter().all(|p| p.content.len() <= MAX_PASSAGE_CHARS));
+        assert_eq!(passages.iter().map(|p| p.content.matches("word").count()).sum::<usize>(), 600);
+    }
+}
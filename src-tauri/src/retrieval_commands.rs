@@ -0,0 +1,624 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+use crate::prompt_builder::LessonContent;
+use crate::retrieval::{self, PassageDraft, RetrievedPassage};
+use crate::error::{AppError, ResultExt};
+
+/// Content is re-checked for changes at most this often during tutor calls
+const AUTO_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_SEARCH_LIMIT: usize = 5;
+
+lazy_static::lazy_static! {
+    static ref LAST_REFRESH: Mutex<Option<Instant>> = Mutex::new(None);
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexRefreshSummary {
+    pub added: i64,
+    pub updated: i64,
+    pub removed: i64,
+    pub unchanged: i64,
+    pub passages: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrievalIndexStatus {
+    pub sources: i64,
+    pub passages: i64,
+    pub terms: i64,
+    pub last_indexed_at: Option<String>,
+}
+
+/// Everything indexed from one lesson file, doc or database row
+struct IndexSource {
+    key: String,
+    source_type: &'static str,
+    content_hash: String,
+    passages: Vec<PassageDraft>,
+}
+
+impl IndexSource {
+    fn new(key: String, source_type: &'static str, raw: &str, passages: Vec<PassageDraft>) -> Self {
+        IndexSource {
+            key,
+            source_type,
+            content_hash: format!("{:x}", Sha256::digest(raw.as_bytes())),
+            passages,
+        }
+    }
+}
+
+// ============================================================================
+// Source Collection
+// ============================================================================
+
+/// Find the docs directory (lesson JSON files and markdown docs). Installed apps
+/// ship it as a bundled resource (see tauri.conf.json); development builds fall
+/// back to the repository's docs folder, wherever the app was started from.
+pub fn find_docs_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let bundled = app.path().resource_dir().ok().map(|dir| dir.join("docs"));
+    let repository = cfg!(debug_assertions).then(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("../docs"));
+    first_docs_dir(bundled.into_iter().chain(repository))
+}
+
+fn first_docs_dir(candidates: impl IntoIterator<Item = PathBuf>) -> Result<PathBuf, AppError> {
+    candidates
+        .into_iter()
+        .find(|path| path.join("lessons").is_dir())
+        .ok_or_else(|| AppError::NotFound("Could not find docs directory".to_string()))
+}
+
+/// Lesson descriptions, objectives and common mistakes. Hints and solutions are left out
+/// so retrieved passages can never hand the learner an answer.
+fn collect_lessons(docs_dir: &Path) -> Vec<IndexSource> {
+    let Ok(entries) = fs::read_dir(docs_dir.join("lessons")) else {
+        return Vec::new();
+    };
+
+    let mut sources = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+
+        let Ok(raw) = fs::read_to_string(&path) else { continue };
+        let Ok(lesson) = serde_json::from_str::<LessonContent>(&raw) else {
+            log::warn!("Skipping unreadable lesson file {}", path.display());
+            continue;
+        };
+
+        let mut passages = retrieval::chunk_markdown(&lesson.title, &lesson.description, Some(&lesson.language));
+
+        let mut summary = String::new();
+        if !lesson.learning_objectives.is_empty() {
+            summary.push_str("Learning objectives:\n");
+            for objective in &lesson.learning_objectives {
+                summary.push_str(&format!("- {}\n", objective));
+            }
+        }
+        if !lesson.common_mistakes.is_empty() {
+            summary.push_str("Common mistakes:\n");
+            for mistake in &lesson.common_mistakes {
+                summary.push_str(&format!("- {}: {}\n", mistake.mistake, mistake.explanation));
+            }
+        }
+        if !summary.is_empty() {
+            passages.push(PassageDraft {
+                title: format!("{} › Objectives and common mistakes", lesson.title),
+                content: summary.trim().to_string(),
+                language: Some(lesson.language.clone()),
+            });
+        }
+
+        // Keyed by file name: a few lesson files share an id within the same language
+        let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        sources.push(IndexSource::new(format!("lesson:{}", file_name), "lesson", &raw, passages));
+    }
+    sources
+}
+
+/// Top-level markdown docs (docs/*.md)
+fn collect_docs(docs_dir: &Path) -> Vec<IndexSource> {
+    let Ok(entries) = fs::read_dir(docs_dir) else {
+        return Vec::new();
+    };
+
+    let mut sources = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+            continue;
+        }
+
+        let Ok(raw) = fs::read_to_string(&path) else { continue };
+        let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string();
+
+        // Use the first heading as the title, falling back to the file name
+        let title = raw
+            .lines()
+            .find(|line| line.starts_with("# "))
+            .map(|line| line.trim_start_matches('#').trim().to_string())
+            .unwrap_or_else(|| file_name.trim_end_matches(".md").replace(['-', '_'], " "));
+
+        let passages = retrieval::chunk_markdown(&title, &raw, None);
+        sources.push(IndexSource::new(format!("doc:{}", file_name), "doc", &raw, passages));
+    }
+    sources
+}
+
+/// MCQ questions with their explanations
+fn collect_mcq_explanations(conn: &Connection) -> Result<Vec<IndexSource>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, question_text, explanation, topic, language FROM mcq_questions
+             WHERE explanation IS NOT NULL AND explanation != ''",
+        )
+        .context("Failed to prepare MCQ query")?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })
+        .context("Failed to query MCQ questions")?;
+
+    let mut sources = Vec::new();
+    for row in rows {
+        let (id, question, explanation, topic, language) =
+            row.context("Failed to read MCQ question")?;
+
+        // "general" questions apply to every language
+        let language = language.filter(|language| language != "general");
+        let content = format!("Q: {}\n{}", question, explanation);
+        let title = match topic {
+            Some(topic) => format!("Quiz explanation › {}", topic),
+            None => "Quiz explanation".to_string(),
+        };
+
+        sources.push(IndexSource::new(
+            format!("mcq:{}", id),
+            "mcq",
+            &content,
+            vec![PassageDraft { title, content: content.clone(), language }],
+        ));
+    }
+    Ok(sources)
+}
+
+/// Concept implementations, when the course framework tables are present
+fn collect_concepts(conn: &Connection) -> Result<Vec<IndexSource>, AppError> {
+    let has_table: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'concept_implementations'",
+            [],
+            |row| row.get(0),
+        )
+        .context("Failed to check for concept table")?;
+    if !has_table {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT concept_id, language_id, explanation, code_example, syntax_notes, common_mistakes
+             FROM concept_implementations",
+        )
+        .context("Failed to prepare concept query")?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })
+        .context("Failed to query concept implementations")?;
+
+    let mut sources = Vec::new();
+    for row in rows {
+        let (concept_id, language, explanation, code_example, syntax_notes, common_mistakes) =
+            row.context("Failed to read concept implementation")?;
+
+        let common_mistakes = common_mistakes
+            .as_deref()
+            .and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+            .map(|mistakes| mistakes.iter().map(|m| format!("- {}", m)).collect::<Vec<_>>().join("\n"));
+
+        let mut content = String::new();
+        for (label, part) in [
+            ("", explanation),
+            ("Example:\n", code_example),
+            ("Syntax notes:\n", syntax_notes),
+            ("Common mistakes:\n", common_mistakes),
+        ] {
+            if let Some(part) = part.filter(|part| !part.trim().is_empty()) {
+                content.push_str(label);
+                content.push_str(part.trim());
+                content.push_str("\n\n");
+            }
+        }
+        if content.is_empty() {
+            continue;
+        }
+
+        let title = format!("Concept › {}", concept_id.replace('_', " "));
+        let key = format!("concept:{}:{}", concept_id, language);
+        let passages = retrieval::chunk_markdown(&title, &content, Some(&language));
+        sources.push(IndexSource::new(key, "concept", &content, passages));
+    }
+    Ok(sources)
+}
+
+// ============================================================================
+// Index Maintenance and Search
+// ============================================================================
+
+/// Bring the index in line with the current content. Only sources whose content hash
+/// changed are re-indexed; sources that no longer exist are removed.
+pub fn refresh_index(conn: &mut Connection, docs_dir: Option<&Path>, force: bool) -> Result<IndexRefreshSummary, AppError> {
+    let mut sources = Vec::new();
+    if let Some(docs_dir) = docs_dir {
+        sources.extend(collect_lessons(docs_dir));
+        sources.extend(collect_docs(docs_dir));
+    }
+    sources.extend(collect_mcq_explanations(conn)?);
+    sources.extend(collect_concepts(conn)?);
+
+    let existing: HashMap<String, String> = {
+        let mut stmt = conn
+            .prepare("SELECT source_key, content_hash FROM retrieval_sources")
+            .context("Failed to prepare source query")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .context("Failed to query indexed sources")?;
+        rows.collect::<Result<_, _>>()
+            .context("Failed to read indexed sources")?
+    };
+
+    let tx = conn
+        .transaction()
+        .context("Failed to start transaction")?;
+    let mut summary = IndexRefreshSummary::default();
+
+    let current_keys: HashSet<&str> = sources.iter().map(|source| source.key.as_str()).collect();
+    for key in existing.keys().filter(|key| !current_keys.contains(key.as_str())) {
+        tx.execute("DELETE FROM retrieval_sources WHERE source_key = ?1", params![key])
+            .with_context(|| format!("Failed to remove source {}", key))?;
+        summary.removed += 1;
+    }
+
+    for source in &sources {
+        match existing.get(&source.key) {
+            Some(hash) if *hash == source.content_hash && !force => {
+                summary.unchanged += 1;
+                continue;
+            }
+            Some(_) => summary.updated += 1,
+            None => summary.added += 1,
+        }
+
+        // Passages and terms go with the source through ON DELETE CASCADE
+        tx.execute("DELETE FROM retrieval_sources WHERE source_key = ?1", params![source.key])
+            .with_context(|| format!("Failed to clear source {}", source.key))?;
+        tx.execute(
+            "INSERT INTO retrieval_sources (source_key, source_type, content_hash) VALUES (?1, ?2, ?3)",
+            params![source.key, source.source_type, source.content_hash],
+        )
+        .with_context(|| format!("Failed to index source {}", source.key))?;
+
+        for passage in &source.passages {
+            let tokens = retrieval::tokenize(&format!("{}\n{}", passage.title, passage.content));
+            if tokens.is_empty() {
+                continue;
+            }
+
+            tx.execute(
+                "INSERT INTO retrieval_passages (source_key, title, content, language, token_count)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![source.key, passage.title, passage.content, passage.language, tokens.len() as i64],
+            )
+            .context("Failed to index passage")?;
+            let passage_id = tx.last_insert_rowid();
+
+            for (term, tf) in retrieval::term_frequencies(&tokens) {
+                tx.execute(
+                    "INSERT INTO retrieval_terms (term, passage_id, tf) VALUES (?1, ?2, ?3)",
+                    params![term, passage_id, tf],
+                )
+                .context("Failed to index term")?;
+            }
+            summary.passages += 1;
+        }
+    }
+
+    tx.commit()
+        .context("Failed to commit retrieval index")?;
+
+    Ok(summary)
+}
+
+/// Rank passages for a query with BM25. Passages without a language match any language.
+pub fn search_index(
+    conn: &Connection,
+    query: &str,
+    language: Option<&str>,
+    limit: usize,
+) -> Result<Vec<RetrievedPassage>, AppError> {
+    let mut terms = retrieval::tokenize(query);
+    terms.sort();
+    terms.dedup();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (doc_count, avg_doc_len): (i64, f64) = conn
+        .query_row(
+            "SELECT COUNT(*), COALESCE(AVG(token_count), 0) FROM retrieval_passages
+             WHERE ?1 IS NULL OR language IS NULL OR language = ?1",
+            params![language],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .context("Failed to read index statistics")?;
+    if doc_count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.passage_id, t.tf, p.token_count
+             FROM retrieval_terms t
+             JOIN retrieval_passages p ON p.id = t.passage_id
+             WHERE t.term = ?1 AND (?2 IS NULL OR p.language IS NULL OR p.language = ?2)",
+        )
+        .context("Failed to prepare term query")?;
+
+    let mut scores: HashMap<i64, f64> = HashMap::new();
+    for term in &terms {
+        let postings: Vec<(i64, i64, i64)> = stmt
+            .query_map(params![term, language], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .context("Failed to query term")?
+            .collect::<Result<_, _>>()
+            .context("Failed to read term postings")?;
+
+        let doc_freq = postings.len() as f64;
+        for (passage_id, tf, doc_len) in postings {
+            *scores.entry(passage_id).or_insert(0.0) +=
+                retrieval::bm25_term_score(tf as f64, doc_len as f64, avg_doc_len, doc_freq, doc_count as f64);
+        }
+    }
+
+    let mut ranked: Vec<(i64, f64)> = scores.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut results = Vec::new();
+    for (passage_id, score) in ranked.into_iter().take(limit) {
+        let passage = conn
+            .query_row(
+                "SELECT source_key, title, content, language FROM retrieval_passages WHERE id = ?1",
+                params![passage_id],
+                |row| {
+                    Ok(RetrievedPassage {
+                        passage_id,
+                        source_key: row.get(0)?,
+                        title: row.get(1)?,
+                        content: row.get(2)?,
+                        language: row.get(3)?,
+                        score,
+                    })
+                },
+            )
+            .optional()
+            .context("Failed to load passage")?;
+
+        results.extend(passage);
+    }
+
+    Ok(results)
+}
+
+/// Refresh the index if it hasn't been checked recently. Used before tutor retrieval so
+/// edited lessons and newly seeded questions are picked up without a manual rebuild.
+pub fn ensure_index_fresh(app: &AppHandle) -> Result<(), AppError> {
+    let mut last_refresh = LAST_REFRESH
+        .lock()
+        .context("Failed to lock retrieval refresh state")?;
+
+    if last_refresh.is_some_and(|at| at.elapsed() < AUTO_REFRESH_INTERVAL) {
+        return Ok(());
+    }
+
+    let mut conn = db::get_connection(app)?;
+    let summary = refresh_index(&mut conn, find_docs_dir(app).ok().as_deref(), false)?;
+    if summary.added + summary.updated + summary.removed > 0 {
+        log::info!(
+            "Retrieval index refreshed: {} added, {} updated, {} removed",
+            summary.added,
+            summary.updated,
+            summary.removed
+        );
+    }
+
+    *last_refresh = Some(Instant::now());
+    Ok(())
+}
+
+// ============================================================================
+// Retrieval Commands
+// ============================================================================
+
+/// Re-index changed content (or everything when `force` is set)
+#[tauri::command]
+pub fn rebuild_retrieval_index(app: AppHandle, force: Option<bool>) -> Result<IndexRefreshSummary, AppError> {
+    let mut conn = db::get_connection(&app)?;
+    let docs_dir = find_docs_dir(&app).ok();
+    if docs_dir.is_none() {
+        log::warn!("Docs directory not found; only database content will be indexed");
+    }
+
+    let summary = refresh_index(&mut conn, docs_dir.as_deref(), force.unwrap_or(false))?;
+
+    if let Ok(mut last_refresh) = LAST_REFRESH.lock() {
+        *last_refresh = Some(Instant::now());
+    }
+
+    Ok(summary)
+}
+
+/// Search lessons, docs, concepts and quiz explanations
+#[tauri::command]
+pub fn search_knowledge(
+    app: AppHandle,
+    query: String,
+    language: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<RetrievedPassage>, AppError> {
+    ensure_index_fresh(&app)?;
+    let conn = db::get_connection(&app)?;
+    search_index(&conn, &query, language.as_deref(), limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+}
+
+#[tauri::command]
+pub fn get_retrieval_index_status(app: AppHandle) -> Result<RetrievalIndexStatus, AppError> {
+    let conn = db::get_connection(&app)?;
+
+    conn.query_row(
+        "SELECT
+            (SELECT COUNT(*) FROM retrieval_sources),
+            (SELECT COUNT(*) FROM retrieval_passages),
+            (SELECT COUNT(DISTINCT term) FROM retrieval_terms),
+            (SELECT MAX(indexed_at) FROM retrieval_sources)",
+        [],
+        |row| {
+            Ok(RetrievalIndexStatus {
+                sources: row.get(0)?,
+                passages: row.get(1)?,
+                terms: row.get(2)?,
+                last_indexed_at: row.get(3)?,
+            })
+        },
+    )
+    .context("Failed to get retrieval index status")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::seeded_db;
+
+    /// A scratch docs folder, removed on drop
+    struct Docs(PathBuf);
+
+    impl Docs {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("code-tutor-docs-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(dir.join("lessons")).unwrap();
+            Docs(dir)
+        }
+
+        fn write(&self, name: &str, content: &str) {
+            fs::write(self.0.join(name), content).unwrap();
+        }
+    }
+
+    impl Drop for Docs {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn counts(summary: &IndexRefreshSummary) -> (i64, i64, i64) {
+        (summary.added, summary.updated, summary.removed)
+    }
+
+    #[test]
+    fn docs_dir_is_the_first_candidate_with_lessons() {
+        let docs = Docs::new();
+        let missing = docs.0.join("missing");
+
+        assert_eq!(first_docs_dir([missing.clone(), docs.0.join("lessons"), docs.0.clone()]).unwrap(), docs.0);
+        assert!(matches!(first_docs_dir([missing]), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn indexed_docs_ship_with_the_app() {
+        let config: serde_json::Value = serde_json::from_str(include_str!("../tauri.conf.json")).unwrap();
+        let resources = &config["bundle"]["resources"];
+
+        assert_eq!(resources["../docs/lessons/*.json"], "docs/lessons/");
+        assert_eq!(resources["../docs/*.md"], "docs/");
+    }
+
+    #[test]
+    fn refresh_only_touches_changed_sources() {
+        let mut conn = seeded_db();
+        let docs = Docs::new();
+        docs.write("loops.md", "# Loops\nA for loop repeats a block once per item.\n");
+        docs.write("zebra.md", "# Zebras\nStripes are unrelated to programming.\n");
+        docs.write(
+            "lessons/loops.json",
+            r#"{"id": 1, "language": "python", "title": "Loop Lesson", "description": "Iterate with for.",
+                "solutionCode": "for x in y: print(x)", "learningObjectives": ["Write a loop"]}"#,
+        );
+
+        let first = refresh_index(&mut conn, Some(&docs.0), false).unwrap();
+        assert!(first.added >= 3);
+        assert_eq!(counts(&refresh_index(&mut conn, Some(&docs.0), false).unwrap()), (0, 0, 0));
+
+        docs.write("loops.md", "# Loops\nA while loop repeats until its condition is false.\n");
+        fs::remove_file(docs.0.join("zebra.md")).unwrap();
+        assert_eq!(counts(&refresh_index(&mut conn, Some(&docs.0), false).unwrap()), (0, 1, 1));
+
+        let forced = refresh_index(&mut conn, Some(&docs.0), true).unwrap();
+        assert_eq!(forced.updated, first.added - 1);
+
+        // Solutions never reach the index
+        let solutions: i64 = crate::test_support::scalar(
+            &conn,
+            "SELECT COUNT(*) FROM retrieval_passages WHERE content LIKE '%print(x)%'",
+            [],
+        );
+        assert_eq!(solutions, 0);
+    }
+
+    #[test]
+    fn search_ranks_the_best_match_first_within_the_language() {
+        let mut conn = seeded_db();
+        let docs = Docs::new();
+        docs.write("recursion.md", "# Recursion\nRecursion means a function calls itself. Recursion needs a base case.\n");
+        docs.write("loops.md", "# Loops\nLoops can replace recursion in many cases.\n");
+        docs.write(
+            "lessons/rust.json",
+            r#"{"id": 7, "language": "rust", "title": "Borrowing", "description": "Recursion with borrowed slices."}"#,
+        );
+        refresh_index(&mut conn, Some(&docs.0), false).unwrap();
+
+        let results = search_index(&conn, "recursion base case", Some("python"), 10).unwrap();
+        assert_eq!(results[0].source_key, "doc:recursion.md");
+        assert!(results.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert!(results.iter().all(|r| r.language.is_none() || r.language.as_deref() == Some("python")));
+        assert!(results.iter().all(|r| r.source_key != "lesson:rust.json"));
+
+        assert!(search_index(&conn, "the and of", None, 10).unwrap().is_empty());
+    }
+}
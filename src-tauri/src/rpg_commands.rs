@@ -1,99 +1,20 @@
-use rusqlite::{params, Result as SqlResult, Row};
+use rusqlite::{params, Connection, Result as SqlResult, Row};
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
-use crate::db::get_connection;
+use crate::db::{get_connection, unit_of_work};
+use crate::error::{AppError, ResultExt};
+pub use crate::game::character::{load_character_stats, Ability, CharacterStats};
+use crate::game::combat::ensure_out_of_combat;
 
 // ============================================================================
 // CHARACTER STATS
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CharacterStats {
-    pub user_id: i64,
-    pub level: i64,
-    pub strength: i64,
-    pub intelligence: i64,
-    pub dexterity: i64,
-    pub charisma: i64,
-    pub max_health: i64,
-    pub current_health: i64,
-    pub max_mana: i64,
-    pub current_mana: i64,
-    pub base_damage: i64,
-    pub defense: i64,
-    pub critical_chance: f64,
-    pub dodge_chance: f64,
-    pub stat_points_available: i64,
-    pub current_gold: i64,
-    pub created_at: String,
-    pub updated_at: String,
-}
-
-impl CharacterStats {
-    pub fn from_row(row: &Row) -> SqlResult<Self> {
-        Ok(CharacterStats {
-            user_id: row.get(0)?,
-            level: row.get(1)?,
-            strength: row.get(2)?,
-            intelligence: row.get(3)?,
-            dexterity: row.get(4)?,
-            charisma: row.get(5)?,
-            max_health: row.get(6)?,
-            current_health: row.get(7)?,
-            max_mana: row.get(8)?,
-            current_mana: row.get(9)?,
-            base_damage: row.get(10)?,
-            defense: row.get(11)?,
-            critical_chance: row.get(12)?,
-            dodge_chance: row.get(13)?,
-            stat_points_available: row.get(14)?,
-            current_gold: row.get(15)?,
-            created_at: row.get(16)?,
-            updated_at: row.get(17)?,
-        })
-    }
-}
-
 #[tauri::command]
-pub fn get_character_stats(app: AppHandle, user_id: i64) -> Result<CharacterStats, String> {
+pub fn get_character_stats(app: AppHandle, user_id: i64) -> Result<CharacterStats, AppError> {
     let conn = get_connection(&app)?;
-
-    log::info!("get_character_stats called with user_id={}", user_id);
-
-    // Check if user exists
-    let user_exists: Result<i64, _> = conn.query_row(
-        "SELECT id FROM users WHERE id = ?",
-        params![user_id],
-        |row| row.get(0),
-    );
-
-    if user_exists.is_err() {
-        return Err(format!("User with id={} does not exist in users table", user_id));
-    }
-
-    // Initialize character if doesn't exist (with default starting stats)
-    // New defaults: all abilities start at 1, 2 stat points available, health=50, mana=30, 100 gold
-    conn.execute(
-        "INSERT OR IGNORE INTO character_stats (
-            user_id, level, strength, intelligence, dexterity, charisma, max_health, current_health,
-            max_mana, current_mana, base_damage, defense, critical_chance, dodge_chance,
-            stat_points_available, current_gold, created_at, updated_at
-        ) VALUES (?, 1, 1, 1, 1, 1, 50, 50, 30, 30, 10, 5, 0.05, 0.05, 2, 100, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
-        params![user_id],
-    )
-    .map_err(|e| format!("Failed to initialize character: {}", e))?;
-
-    conn.query_row(
-        "SELECT user_id, level, strength, intelligence, dexterity, charisma, max_health, current_health,
-                max_mana, current_mana, base_damage, defense, critical_chance, dodge_chance,
-                stat_points_available, current_gold, created_at, updated_at
-         FROM character_stats
-         WHERE user_id = ?",
-        params![user_id],
-        CharacterStats::from_row,
-    )
-    .map_err(|e| format!("Failed to get character stats: {}", e))
+    load_character_stats(&conn, user_id)
 }
 
 #[tauri::command]
@@ -101,18 +22,20 @@ pub fn update_character_health(
     app: AppHandle,
     user_id: i64,
     current_health: i64,
-) -> Result<CharacterStats, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterStats, AppError> {
+    unit_of_work(&app, |conn| {
+        ensure_out_of_combat(conn, user_id)?;
 
-    conn.execute(
-        "UPDATE character_stats
-         SET current_health = ?, updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![current_health, user_id],
-    )
-    .map_err(|e| format!("Failed to update health: {}", e))?;
+        conn.execute(
+            "UPDATE character_stats
+             SET current_health = ?, updated_at = CURRENT_TIMESTAMP
+             WHERE user_id = ?",
+            params![current_health, user_id],
+        )
+        .context("Failed to update health")?;
 
-    get_character_stats(app, user_id)
+        load_character_stats(conn, user_id)
+    })
 }
 
 #[tauri::command]
@@ -120,18 +43,20 @@ pub fn update_character_mana(
     app: AppHandle,
     user_id: i64,
     current_mana: i64,
-) -> Result<CharacterStats, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterStats, AppError> {
+    unit_of_work(&app, |conn| {
+        ensure_out_of_combat(conn, user_id)?;
 
-    conn.execute(
-        "UPDATE character_stats
-         SET current_mana = ?, updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![current_mana, user_id],
-    )
-    .map_err(|e| format!("Failed to update mana: {}", e))?;
+        conn.execute(
+            "UPDATE character_stats
+             SET current_mana = ?, updated_at = CURRENT_TIMESTAMP
+             WHERE user_id = ?",
+            params![current_mana, user_id],
+        )
+        .context("Failed to update mana")?;
 
-    get_character_stats(app, user_id)
+        load_character_stats(conn, user_id)
+    })
 }
 
 #[tauri::command]
@@ -141,16 +66,26 @@ pub fn distribute_stat_points(
     strength_increase: i64,
     intelligence_increase: i64,
     dexterity_increase: i64,
-) -> Result<CharacterStats, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterStats, AppError> {
+    unit_of_work(&app, |conn| {
+        distribute_points(conn, user_id, strength_increase, intelligence_increase, dexterity_increase)
+    })
+}
 
+fn distribute_points(
+    conn: &Connection,
+    user_id: i64,
+    strength_increase: i64,
+    intelligence_increase: i64,
+    dexterity_increase: i64,
+) -> Result<CharacterStats, AppError> {
     let total_increase = strength_increase + intelligence_increase + dexterity_increase;
 
     // Get current stats
-    let stats = get_character_stats(app.clone(), user_id)?;
+    let stats = load_character_stats(conn, user_id)?;
 
     if stats.stat_points_available < total_increase {
-        return Err("Not enough stat points available".to_string());
+        return Err(AppError::Validation("Not enough stat points available".to_string()));
     }
 
     // Update stats
@@ -170,23 +105,25 @@ pub fn distribute_stat_points(
             user_id
         ],
     )
-    .map_err(|e| format!("Failed to distribute stat points: {}", e))?;
+    .context("Failed to distribute stat points")?;
 
     // Recalculate derived stats
-    recalculate_derived_stats(app.clone(), user_id)?;
+    recalculate_stats(conn, user_id)?;
 
-    get_character_stats(app, user_id)
+    load_character_stats(conn, user_id)
 }
 
 #[tauri::command]
-pub fn recalculate_derived_stats(app: AppHandle, user_id: i64) -> Result<(), String> {
-    let conn = get_connection(&app)?;
+pub fn recalculate_derived_stats(app: AppHandle, user_id: i64) -> Result<(), AppError> {
+    unit_of_work(&app, |conn| recalculate_stats(conn, user_id))
+}
 
+pub(crate) fn recalculate_stats(conn: &Connection, user_id: i64) -> Result<(), AppError> {
     // Get current stats
-    let stats = get_character_stats(app.clone(), user_id)?;
+    let stats = load_character_stats(conn, user_id)?;
 
     // Get equipped items to calculate bonuses
-    let equipment = get_character_equipment(app.clone(), user_id)?;
+    let equipment = load_character_equipment(conn, user_id)?;
 
     // Helper function to get equipment bonuses
     let get_equipment_bonuses = |item_id: &Option<String>| -> (i64, i64, i64, i64, i64, i64, i64, f64, f64) {
@@ -293,26 +230,28 @@ pub fn recalculate_derived_stats(app: AppHandle, user_id: i64) -> Result<(), Str
             user_id
         ],
     )
-    .map_err(|e| format!("Failed to recalculate derived stats: {}", e))?;
+    .context("Failed to recalculate derived stats")?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn restore_health_and_mana(app: AppHandle, user_id: i64) -> Result<CharacterStats, String> {
-    let conn = get_connection(&app)?;
+pub fn restore_health_and_mana(app: AppHandle, user_id: i64) -> Result<CharacterStats, AppError> {
+    unit_of_work(&app, |conn| {
+        ensure_out_of_combat(conn, user_id)?;
 
-    conn.execute(
-        "UPDATE character_stats
-         SET current_health = max_health,
-             current_mana = max_mana,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE user_id = ?",
-        params![user_id],
-    )
-    .map_err(|e| format!("Failed to restore health and mana: {}", e))?;
+        conn.execute(
+            "UPDATE character_stats
+             SET current_health = max_health,
+                 current_mana = max_mana,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE user_id = ?",
+            params![user_id],
+        )
+        .context("Failed to restore health and mana")?;
 
-    get_character_stats(app, user_id)
+        load_character_stats(conn, user_id)
+    })
 }
 
 // ============================================================================
@@ -388,7 +327,7 @@ pub struct CharacterEquipment {
 }
 
 #[tauri::command]
-pub fn get_equipment_items(app: AppHandle) -> Result<Vec<EquipmentItem>, String> {
+pub fn get_equipment_items(app: AppHandle) -> Result<Vec<EquipmentItem>, AppError> {
     let conn = get_connection(&app)?;
 
     let mut stmt = conn
@@ -401,13 +340,13 @@ pub fn get_equipment_items(app: AppHandle) -> Result<Vec<EquipmentItem>, String>
              FROM equipment_items
              ORDER BY tier, required_level",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let items = stmt
         .query_map([], EquipmentItem::from_row)
-        .map_err(|e| format!("Failed to query equipment items: {}", e))?
+        .context("Failed to query equipment items")?
         .collect::<SqlResult<Vec<EquipmentItem>>>()
-        .map_err(|e| format!("Failed to collect equipment items: {}", e))?;
+        .context("Failed to collect equipment items")?;
 
     Ok(items)
 }
@@ -433,9 +372,15 @@ pub struct CharacterEquipmentWithDetails {
 }
 
 #[tauri::command]
-pub fn get_character_equipment(app: AppHandle, user_id: i64) -> Result<CharacterEquipment, String> {
+pub fn get_character_equipment(app: AppHandle, user_id: i64) -> Result<CharacterEquipment, AppError> {
     let conn = get_connection(&app)?;
+    load_character_equipment(&conn, user_id)
+}
 
+pub(crate) fn load_character_equipment(
+    conn: &Connection,
+    user_id: i64,
+) -> Result<CharacterEquipment, AppError> {
     conn.query_row(
         "SELECT user_id, weapon_id, armor_id, accessory_id, shield_id, helmet_id, chest_id, boots_id, updated_at
          FROM character_equipment
@@ -455,18 +400,18 @@ pub fn get_character_equipment(app: AppHandle, user_id: i64) -> Result<Character
             })
         },
     )
-    .map_err(|e| format!("Failed to get character equipment: {}", e))
+    .context("Failed to get character equipment")
 }
 
 #[tauri::command]
-pub fn get_character_equipment_with_details(app: AppHandle, user_id: i64) -> Result<CharacterEquipmentWithDetails, String> {
+pub fn get_character_equipment_with_details(app: AppHandle, user_id: i64) -> Result<CharacterEquipmentWithDetails, AppError> {
     let conn = get_connection(&app)?;
 
     // Get equipped item IDs
-    let equipment = get_character_equipment(app.clone(), user_id)?;
+    let equipment = load_character_equipment(&conn, user_id)?;
 
     // Helper function to get equipment details by ID
-    let get_item_details = |item_id: &Option<String>| -> Result<Option<EquipmentItem>, String> {
+    let get_item_details = |item_id: &Option<String>| -> Result<Option<EquipmentItem>, AppError> {
         match item_id {
             Some(id) => {
                 let item = conn
@@ -514,9 +459,16 @@ pub fn equip_item(
     user_id: i64,
     item_id: String,
     slot: String,
-) -> Result<CharacterEquipment, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterEquipment, AppError> {
+    unit_of_work(&app, |conn| equip(conn, user_id, item_id, slot))
+}
 
+fn equip(
+    conn: &Connection,
+    user_id: i64,
+    item_id: String,
+    slot: String,
+) -> Result<CharacterEquipment, AppError> {
     // Validate that the item exists and is the correct slot
     let item: EquipmentItem = conn
         .query_row(
@@ -530,41 +482,41 @@ pub fn equip_item(
             params![item_id],
             EquipmentItem::from_row,
         )
-        .map_err(|e| format!("Item not found: {}", e))?;
+        .context("Item not found")?;
 
     if item.slot != slot {
-        return Err("Item slot mismatch".to_string());
+        return Err(AppError::Validation("Item slot mismatch".to_string()));
     }
 
     // Check requirements
-    let stats = get_character_stats(app.clone(), user_id)?;
+    let stats = load_character_stats(conn, user_id)?;
 
     if stats.level < item.required_level {
-        return Err(format!(
+        return Err(AppError::Validation(format!(
             "Level too low. Required: {}",
             item.required_level
-        ));
+        )));
     }
 
     if stats.strength < item.required_strength {
-        return Err(format!(
+        return Err(AppError::Validation(format!(
             "Strength too low. Required: {}",
             item.required_strength
-        ));
+        )));
     }
 
     if stats.intelligence < item.required_intelligence {
-        return Err(format!(
+        return Err(AppError::Validation(format!(
             "Intelligence too low. Required: {}",
             item.required_intelligence
-        ));
+        )));
     }
 
     if stats.dexterity < item.required_dexterity {
-        return Err(format!(
+        return Err(AppError::Validation(format!(
             "Dexterity too low. Required: {}",
             item.required_dexterity
-        ));
+        )));
     }
 
     // Equip the item
@@ -572,7 +524,7 @@ pub fn equip_item(
         "weapon" => "weapon_id",
         "armor" => "armor_id",
         "accessory" => "accessory_id",
-        _ => return Err("Invalid slot".to_string()),
+        _ => return Err(AppError::Validation("Invalid slot".to_string())),
     };
 
     conn.execute(
@@ -584,9 +536,9 @@ pub fn equip_item(
         ),
         params![item_id, user_id],
     )
-    .map_err(|e| format!("Failed to equip item: {}", e))?;
+    .context("Failed to equip item")?;
 
-    get_character_equipment(app, user_id)
+    load_character_equipment(conn, user_id)
 }
 
 #[tauri::command]
@@ -594,14 +546,16 @@ pub fn unequip_item(
     app: AppHandle,
     user_id: i64,
     slot: String,
-) -> Result<CharacterEquipment, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterEquipment, AppError> {
+    unit_of_work(&app, |conn| unequip(conn, user_id, slot))
+}
 
+fn unequip(conn: &Connection, user_id: i64, slot: String) -> Result<CharacterEquipment, AppError> {
     let column = match slot.as_str() {
         "weapon" => "weapon_id",
         "armor" => "armor_id",
         "accessory" => "accessory_id",
-        _ => return Err("Invalid slot".to_string()),
+        _ => return Err(AppError::Validation("Invalid slot".to_string())),
     };
 
     conn.execute(
@@ -613,54 +567,15 @@ pub fn unequip_item(
         ),
         params![user_id],
     )
-    .map_err(|e| format!("Failed to unequip item: {}", e))?;
+    .context("Failed to unequip item")?;
 
-    get_character_equipment(app, user_id)
+    load_character_equipment(conn, user_id)
 }
 
 // ============================================================================
 // ABILITIES
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Ability {
-    pub id: String,
-    pub name: String,
-    pub description: String,
-    pub ability_type: String,
-    pub required_level: i64,
-    pub mana_cost: i64,
-    pub cooldown_turns: i64,
-    pub base_value: i64,
-    pub scaling_stat: String,
-    pub scaling_ratio: f64,
-    pub additional_effects: Option<String>,
-    pub icon: String,
-    pub animation_text: String,
-    pub created_at: String,
-}
-
-impl Ability {
-    fn from_row(row: &Row) -> SqlResult<Self> {
-        Ok(Ability {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            ability_type: row.get(3)?,
-            required_level: row.get(4)?,
-            mana_cost: row.get(5)?,
-            cooldown_turns: row.get(6)?,
-            base_value: row.get(7)?,
-            scaling_stat: row.get(8)?,
-            scaling_ratio: row.get(9)?,
-            additional_effects: row.get(10)?,
-            icon: row.get(11)?,
-            animation_text: row.get(12)?,
-            created_at: row.get(13)?,
-        })
-    }
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserAbility {
     pub id: i64,
@@ -671,7 +586,7 @@ pub struct UserAbility {
 }
 
 #[tauri::command]
-pub fn get_user_abilities(app: AppHandle, user_id: i64) -> Result<Vec<Ability>, String> {
+pub fn get_user_abilities(app: AppHandle, user_id: i64) -> Result<Vec<Ability>, AppError> {
     let conn = get_connection(&app)?;
 
     let mut stmt = conn
@@ -684,37 +599,41 @@ pub fn get_user_abilities(app: AppHandle, user_id: i64) -> Result<Vec<Ability>,
              WHERE ua.user_id = ?
              ORDER BY a.required_level",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let abilities = stmt
         .query_map(params![user_id], Ability::from_row)
-        .map_err(|e| format!("Failed to query abilities: {}", e))?
+        .context("Failed to query abilities")?
         .collect::<SqlResult<Vec<Ability>>>()
-        .map_err(|e| format!("Failed to collect abilities: {}", e))?;
+        .context("Failed to collect abilities")?;
 
     Ok(abilities)
 }
 
 #[tauri::command]
-pub fn unlock_ability(app: AppHandle, user_id: i64, ability_id: String) -> Result<(), String> {
-    let conn = get_connection(&app)?;
+pub fn unlock_ability(app: AppHandle, user_id: i64, ability_id: String) -> Result<(), AppError> {
+    unit_of_work(&app, |conn| grant_ability(conn, user_id, ability_id))
+}
 
+fn grant_ability(conn: &Connection, user_id: i64, ability_id: String) -> Result<(), AppError> {
     conn.execute(
         "INSERT OR IGNORE INTO user_abilities (user_id, ability_id)
          VALUES (?, ?)",
         params![user_id, ability_id],
     )
-    .map_err(|e| format!("Failed to unlock ability: {}", e))?;
+    .context("Failed to unlock ability")?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn check_ability_unlocks(app: AppHandle, user_id: i64) -> Result<Vec<Ability>, String> {
-    let conn = get_connection(&app)?;
+pub fn check_ability_unlocks(app: AppHandle, user_id: i64) -> Result<Vec<Ability>, AppError> {
+    unit_of_work(&app, |conn| unlock_earned_abilities(conn, user_id))
+}
 
+fn unlock_earned_abilities(conn: &Connection, user_id: i64) -> Result<Vec<Ability>, AppError> {
     // Get character level
-    let stats = get_character_stats(app.clone(), user_id)?;
+    let stats = load_character_stats(conn, user_id)?;
 
     // Get abilities that should be unlocked but aren't yet
     let mut stmt = conn
@@ -730,17 +649,17 @@ pub fn check_ability_unlocks(app: AppHandle, user_id: i64) -> Result<Vec<Ability
                )
              ORDER BY a.required_level",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let new_abilities = stmt
         .query_map(params![stats.level, user_id], Ability::from_row)
-        .map_err(|e| format!("Failed to query new abilities: {}", e))?
+        .context("Failed to query new abilities")?
         .collect::<SqlResult<Vec<Ability>>>()
-        .map_err(|e| format!("Failed to collect new abilities: {}", e))?;
+        .context("Failed to collect new abilities")?;
 
     // Auto-unlock these abilities
     for ability in &new_abilities {
-        unlock_ability(app.clone(), user_id, ability.id.clone())?;
+        grant_ability(conn, user_id, ability.id.clone())?;
     }
 
     Ok(new_abilities)
@@ -761,7 +680,7 @@ pub struct EquipmentInventoryItem {
 }
 
 #[tauri::command]
-pub fn get_equipment_inventory(app: AppHandle, user_id: i64) -> Result<Vec<EquipmentInventoryItem>, String> {
+pub fn get_equipment_inventory(app: AppHandle, user_id: i64) -> Result<Vec<EquipmentInventoryItem>, AppError> {
     let conn = get_connection(&app)?;
 
     let mut stmt = conn
@@ -778,7 +697,7 @@ pub fn get_equipment_inventory(app: AppHandle, user_id: i64) -> Result<Vec<Equip
              WHERE i.user_id = ?
              ORDER BY e.tier DESC, e.required_level DESC",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let items = stmt
         .query_map(params![user_id], |row| {
@@ -814,9 +733,9 @@ pub fn get_equipment_inventory(app: AppHandle, user_id: i64) -> Result<Vec<Equip
                 },
             })
         })
-        .map_err(|e| format!("Failed to query inventory: {}", e))?
+        .context("Failed to query inventory")?
         .collect::<SqlResult<Vec<EquipmentInventoryItem>>>()
-        .map_err(|e| format!("Failed to collect inventory: {}", e))?;
+        .context("Failed to collect inventory")?;
 
     Ok(items)
 }
@@ -827,9 +746,16 @@ pub fn equip_item_to_slot(
     user_id: i64,
     equipment_id: String,
     slot: String, // "weapon", "shield", "helmet", "chest", "boots"
-) -> Result<CharacterEquipment, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterEquipment, AppError> {
+    unit_of_work(&app, |conn| equip_from_inventory(conn, user_id, equipment_id, slot))
+}
 
+fn equip_from_inventory(
+    conn: &Connection,
+    user_id: i64,
+    equipment_id: String,
+    slot: String,
+) -> Result<CharacterEquipment, AppError> {
     // Check if item exists in user's inventory
     let has_item: bool = conn
         .query_row(
@@ -837,10 +763,10 @@ pub fn equip_item_to_slot(
             params![user_id, equipment_id],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to check inventory: {}", e))?;
+        .context("Failed to check inventory")?;
 
     if !has_item {
-        return Err("Item not in inventory".to_string());
+        return Err(AppError::NotFound("Item not in inventory".to_string()));
     }
 
     // Determine which column to update based on slot
@@ -852,7 +778,7 @@ pub fn equip_item_to_slot(
         "boots" => "boots_id",
         "armor" => "armor_id", // legacy support
         "accessory" => "accessory_id", // legacy support
-        _ => return Err(format!("Invalid slot: {}", slot)),
+        _ => return Err(AppError::Validation(format!("Invalid slot: {}", slot))),
     };
 
     // Update the equipment slot
@@ -860,20 +786,20 @@ pub fn equip_item_to_slot(
         &format!("UPDATE character_equipment SET {} = ?, updated_at = CURRENT_TIMESTAMP WHERE user_id = ?", column),
         params![equipment_id, user_id],
     )
-    .map_err(|e| format!("Failed to equip item: {}", e))?;
+    .context("Failed to equip item")?;
 
     // Remove from inventory (or decrement quantity)
     conn.execute(
         "DELETE FROM user_equipment_inventory WHERE user_id = ? AND equipment_id = ?",
         params![user_id, equipment_id],
     )
-    .map_err(|e| format!("Failed to remove from inventory: {}", e))?;
+    .context("Failed to remove from inventory")?;
 
     // Recalculate stats
-    recalculate_derived_stats(app.clone(), user_id)?;
+    recalculate_stats(conn, user_id)?;
 
     // Return updated equipment
-    get_character_equipment(app, user_id)
+    load_character_equipment(conn, user_id)
 }
 
 #[tauri::command]
@@ -881,9 +807,15 @@ pub fn unequip_item_from_slot(
     app: AppHandle,
     user_id: i64,
     slot: String,
-) -> Result<CharacterEquipment, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterEquipment, AppError> {
+    unit_of_work(&app, |conn| unequip_to_inventory(conn, user_id, slot))
+}
 
+fn unequip_to_inventory(
+    conn: &Connection,
+    user_id: i64,
+    slot: String,
+) -> Result<CharacterEquipment, AppError> {
     // Determine which column to read from
     let column = match slot.as_str() {
         "weapon" => "weapon_id",
@@ -893,7 +825,7 @@ pub fn unequip_item_from_slot(
         "boots" => "boots_id",
         "armor" => "armor_id",
         "accessory" => "accessory_id",
-        _ => return Err(format!("Invalid slot: {}", slot)),
+        _ => return Err(AppError::Validation(format!("Invalid slot: {}", slot))),
     };
 
     // Get the currently equipped item
@@ -903,7 +835,7 @@ pub fn unequip_item_from_slot(
             params![user_id],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to get equipped item: {}", e))?;
+        .context("Failed to get equipped item")?;
 
     if let Some(equipment_id) = equipped_id {
         // Add back to inventory
@@ -913,33 +845,35 @@ pub fn unequip_item_from_slot(
              ON CONFLICT(user_id, equipment_id) DO UPDATE SET quantity = quantity + 1",
             params![user_id, equipment_id],
         )
-        .map_err(|e| format!("Failed to add to inventory: {}", e))?;
+        .context("Failed to add to inventory")?;
 
         // Clear the equipment slot
         conn.execute(
             &format!("UPDATE character_equipment SET {} = NULL, updated_at = CURRENT_TIMESTAMP WHERE user_id = ?", column),
             params![user_id],
         )
-        .map_err(|e| format!("Failed to unequip item: {}", e))?;
+        .context("Failed to unequip item")?;
 
         // Recalculate stats
-        recalculate_derived_stats(app.clone(), user_id)?;
+        recalculate_stats(conn, user_id)?;
     }
 
-    get_character_equipment(app, user_id)
+    load_character_equipment(conn, user_id)
 }
 
 #[tauri::command]
 pub fn spend_stat_point_on_health(
     app: AppHandle,
     user_id: i64,
-) -> Result<CharacterStats, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterStats, AppError> {
+    unit_of_work(&app, |conn| spend_point_on_health(conn, user_id))
+}
 
+fn spend_point_on_health(conn: &Connection, user_id: i64) -> Result<CharacterStats, AppError> {
     // Check if user has stat points available
-    let stats = get_character_stats(app.clone(), user_id)?;
+    let stats = load_character_stats(conn, user_id)?;
     if stats.stat_points_available <= 0 {
-        return Err("No stat points available".to_string());
+        return Err(AppError::Validation("No stat points available".to_string()));
     }
 
     let old_max_health = stats.max_health;
@@ -956,7 +890,7 @@ pub fn spend_stat_point_on_health(
          WHERE user_id = ?",
         params![new_max_health, health_increase, new_max_health, user_id],
     )
-    .map_err(|e| format!("Failed to update health: {}", e))?;
+    .context("Failed to update health")?;
 
     // Log the stat point spend
     conn.execute(
@@ -964,22 +898,24 @@ pub fn spend_stat_point_on_health(
          VALUES (?, 'health', 1, ?, ?)",
         params![user_id, old_max_health, new_max_health],
     )
-    .map_err(|e| format!("Failed to log stat history: {}", e))?;
+    .context("Failed to log stat history")?;
 
-    get_character_stats(app, user_id)
+    load_character_stats(conn, user_id)
 }
 
 #[tauri::command]
 pub fn spend_stat_point_on_mana(
     app: AppHandle,
     user_id: i64,
-) -> Result<CharacterStats, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterStats, AppError> {
+    unit_of_work(&app, |conn| spend_point_on_mana(conn, user_id))
+}
 
+fn spend_point_on_mana(conn: &Connection, user_id: i64) -> Result<CharacterStats, AppError> {
     // Check if user has stat points available
-    let stats = get_character_stats(app.clone(), user_id)?;
+    let stats = load_character_stats(conn, user_id)?;
     if stats.stat_points_available <= 0 {
-        return Err("No stat points available".to_string());
+        return Err(AppError::Validation("No stat points available".to_string()));
     }
 
     let old_max_mana = stats.max_mana;
@@ -996,7 +932,7 @@ pub fn spend_stat_point_on_mana(
          WHERE user_id = ?",
         params![new_max_mana, mana_increase, new_max_mana, user_id],
     )
-    .map_err(|e| format!("Failed to update mana: {}", e))?;
+    .context("Failed to update mana")?;
 
     // Log the stat point spend
     conn.execute(
@@ -1004,9 +940,9 @@ pub fn spend_stat_point_on_mana(
          VALUES (?, 'mana', 1, ?, ?)",
         params![user_id, old_max_mana, new_max_mana],
     )
-    .map_err(|e| format!("Failed to log stat history: {}", e))?;
+    .context("Failed to log stat history")?;
 
-    get_character_stats(app, user_id)
+    load_character_stats(conn, user_id)
 }
 
 #[tauri::command]
@@ -1014,19 +950,25 @@ pub fn spend_stat_point_on_stat(
     app: AppHandle,
     user_id: i64,
     stat_name: String, // "strength", "intelligence", "dexterity", or "charisma"
-) -> Result<CharacterStats, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterStats, AppError> {
+    unit_of_work(&app, |conn| spend_point_on_stat(conn, user_id, stat_name))
+}
 
+fn spend_point_on_stat(
+    conn: &Connection,
+    user_id: i64,
+    stat_name: String,
+) -> Result<CharacterStats, AppError> {
     // Validate stat name
     let valid_stats = ["strength", "intelligence", "dexterity", "charisma"];
     if !valid_stats.contains(&stat_name.as_str()) {
-        return Err(format!("Invalid stat name: {}", stat_name));
+        return Err(AppError::Validation(format!("Invalid stat name: {}", stat_name)));
     }
 
     // Check if user has stat points available
-    let stats = get_character_stats(app.clone(), user_id)?;
+    let stats = load_character_stats(conn, user_id)?;
     if stats.stat_points_available <= 0 {
-        return Err("No stat points available".to_string());
+        return Err(AppError::Validation("No stat points available".to_string()));
     }
 
     // Get current value
@@ -1035,7 +977,7 @@ pub fn spend_stat_point_on_stat(
         "intelligence" => stats.intelligence,
         "dexterity" => stats.dexterity,
         "charisma" => stats.charisma,
-        _ => return Err("Invalid stat".to_string()),
+        _ => return Err(AppError::Validation("Invalid stat".to_string())),
     };
 
     let new_value = old_value + 1;
@@ -1052,7 +994,7 @@ pub fn spend_stat_point_on_stat(
         ),
         params![new_value, user_id],
     )
-    .map_err(|e| format!("Failed to update {}: {}", stat_name, e))?;
+    .with_context(|| format!("Failed to update {}", stat_name))?;
 
     // Log the stat point spend
     conn.execute(
@@ -1060,12 +1002,12 @@ pub fn spend_stat_point_on_stat(
          VALUES (?, ?, 1, ?, ?)",
         params![user_id, stat_name, old_value, new_value],
     )
-    .map_err(|e| format!("Failed to log stat history: {}", e))?;
+    .context("Failed to log stat history")?;
 
     // Recalculate derived stats (damage, defense, etc)
-    recalculate_derived_stats(app.clone(), user_id)?;
+    recalculate_stats(conn, user_id)?;
 
-    get_character_stats(app, user_id)
+    load_character_stats(conn, user_id)
 }
 
 #[tauri::command]
@@ -1073,13 +1015,19 @@ pub fn spend_stat_point_on_ability(
     app: AppHandle,
     user_id: i64,
     ability_id: String,
-) -> Result<CharacterStats, String> {
-    let conn = get_connection(&app)?;
+) -> Result<CharacterStats, AppError> {
+    unit_of_work(&app, |conn| spend_point_on_ability(conn, user_id, ability_id))
+}
 
+fn spend_point_on_ability(
+    conn: &Connection,
+    user_id: i64,
+    ability_id: String,
+) -> Result<CharacterStats, AppError> {
     // Check if user has stat points available
-    let stats = get_character_stats(app.clone(), user_id)?;
+    let stats = load_character_stats(conn, user_id)?;
     if stats.stat_points_available <= 0 {
-        return Err("No stat points available".to_string());
+        return Err(AppError::Validation("No stat points available".to_string()));
     }
 
     // Check if user has this ability unlocked
@@ -1089,10 +1037,10 @@ pub fn spend_stat_point_on_ability(
             params![user_id, ability_id],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to check ability: {}", e))?;
+        .context("Failed to check ability")?;
 
     if !has_ability {
-        return Err("Ability not unlocked".to_string());
+        return Err(AppError::Validation("Ability not unlocked".to_string()));
     }
 
     // Level up the ability
@@ -1102,7 +1050,7 @@ pub fn spend_stat_point_on_ability(
          WHERE user_id = ? AND ability_id = ?",
         params![user_id, ability_id],
     )
-    .map_err(|e| format!("Failed to level up ability: {}", e))?;
+    .context("Failed to level up ability")?;
 
     // Spend the stat point
     conn.execute(
@@ -1112,9 +1060,9 @@ pub fn spend_stat_point_on_ability(
          WHERE user_id = ?",
         params![user_id],
     )
-    .map_err(|e| format!("Failed to spend stat point: {}", e))?;
+    .context("Failed to spend stat point")?;
 
-    get_character_stats(app, user_id)
+    load_character_stats(conn, user_id)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -1130,7 +1078,7 @@ pub struct UserAbilityWithLevel {
 pub fn get_user_abilities_with_levels(
     app: AppHandle,
     user_id: i64,
-) -> Result<Vec<UserAbilityWithLevel>, String> {
+) -> Result<Vec<UserAbilityWithLevel>, AppError> {
     let conn = get_connection(&app)?;
 
     let mut stmt = conn
@@ -1144,7 +1092,7 @@ pub fn get_user_abilities_with_levels(
              WHERE ua.user_id = ?
              ORDER BY a.required_level, a.name",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let abilities = stmt
         .query_map(params![user_id], |row| {
@@ -1171,9 +1119,9 @@ pub fn get_user_abilities_with_levels(
                 },
             })
         })
-        .map_err(|e| format!("Failed to query abilities: {}", e))?
+        .context("Failed to query abilities")?
         .collect::<SqlResult<Vec<UserAbilityWithLevel>>>()
-        .map_err(|e| format!("Failed to collect abilities: {}", e))?;
+        .context("Failed to collect abilities")?;
 
     Ok(abilities)
 }
@@ -1192,7 +1140,7 @@ pub struct AbilityWithUnlockStatus {
 pub fn get_all_abilities_with_status(
     app: AppHandle,
     user_id: i64,
-) -> Result<Vec<AbilityWithUnlockStatus>, String> {
+) -> Result<Vec<AbilityWithUnlockStatus>, AppError> {
     let conn = get_connection(&app)?;
 
     let mut stmt = conn
@@ -1208,7 +1156,7 @@ pub fn get_all_abilities_with_status(
              LEFT JOIN user_active_abilities uaa ON a.id = uaa.ability_id AND uaa.user_id = ?
              ORDER BY a.required_level, a.name",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let abilities = stmt
         .query_map(params![user_id, user_id], |row| {
@@ -1234,9 +1182,9 @@ pub fn get_all_abilities_with_status(
                 active_slot: row.get(16).ok(),
             })
         })
-        .map_err(|e| format!("Failed to query abilities: {}", e))?
+        .context("Failed to query abilities")?
         .collect::<SqlResult<Vec<AbilityWithUnlockStatus>>>()
-        .map_err(|e| format!("Failed to collect abilities: {}", e))?;
+        .context("Failed to collect abilities")?;
 
     Ok(abilities)
 }
@@ -1248,13 +1196,20 @@ pub fn set_active_ability(
     user_id: i64,
     ability_id: String,
     slot_number: i64,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     if slot_number < 1 || slot_number > 3 {
-        return Err("Slot number must be between 1 and 3".to_string());
+        return Err(AppError::Validation("Slot number must be between 1 and 3".to_string()));
     }
 
-    let conn = get_connection(&app)?;
+    unit_of_work(&app, |conn| activate_ability(conn, user_id, ability_id, slot_number))
+}
 
+fn activate_ability(
+    conn: &Connection,
+    user_id: i64,
+    ability_id: String,
+    slot_number: i64,
+) -> Result<(), AppError> {
     // Check if ability is unlocked
     let is_unlocked: bool = conn
         .query_row(
@@ -1262,10 +1217,10 @@ pub fn set_active_ability(
             params![user_id, &ability_id],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to check if ability is unlocked: {}", e))?;
+        .context("Failed to check if ability is unlocked")?;
 
     if !is_unlocked {
-        return Err("Ability is not unlocked".to_string());
+        return Err(AppError::Validation("Ability is not unlocked".to_string()));
     }
 
     // Insert or replace the ability in the slot
@@ -1275,16 +1230,16 @@ pub fn set_active_ability(
          ON CONFLICT(user_id, slot_number) DO UPDATE SET ability_id = excluded.ability_id",
         params![user_id, slot_number, &ability_id],
     )
-    .map_err(|e| format!("Failed to set active ability: {}", e))?;
+    .context("Failed to set active ability")?;
 
     Ok(())
 }
 
 // Remove an ability from an active slot
 #[tauri::command]
-pub fn remove_active_ability(app: AppHandle, user_id: i64, slot_number: i64) -> Result<(), String> {
+pub fn remove_active_ability(app: AppHandle, user_id: i64, slot_number: i64) -> Result<(), AppError> {
     if slot_number < 1 || slot_number > 3 {
-        return Err("Slot number must be between 1 and 3".to_string());
+        return Err(AppError::Validation("Slot number must be between 1 and 3".to_string()));
     }
 
     let conn = get_connection(&app)?;
@@ -1293,7 +1248,7 @@ pub fn remove_active_ability(app: AppHandle, user_id: i64, slot_number: i64) ->
         "DELETE FROM user_active_abilities WHERE user_id = ? AND slot_number = ?",
         params![user_id, slot_number],
     )
-    .map_err(|e| format!("Failed to remove active ability: {}", e))?;
+    .context("Failed to remove active ability")?;
 
     Ok(())
 }
@@ -1301,7 +1256,7 @@ pub fn remove_active_ability(app: AppHandle, user_id: i64, slot_number: i64) ->
 // Get only the abilities that are equipped in active slots (for combat)
 // Always includes basic_attack as it's always available
 #[tauri::command]
-pub fn get_active_abilities_for_combat(app: AppHandle, user_id: i64) -> Result<Vec<Ability>, String> {
+pub fn get_active_abilities_for_combat(app: AppHandle, user_id: i64) -> Result<Vec<Ability>, AppError> {
     let conn = get_connection(&app)?;
 
     // Get basic_attack ability (always available)
@@ -1328,13 +1283,13 @@ pub fn get_active_abilities_for_combat(app: AppHandle, user_id: i64) -> Result<V
              WHERE uaa.user_id = ?
              ORDER BY uaa.slot_number",
         )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
 
     let active_abilities: Vec<Ability> = stmt
         .query_map(params![user_id], Ability::from_row)
-        .map_err(|e| format!("Failed to query active abilities: {}", e))?
+        .context("Failed to query active abilities")?
         .collect::<SqlResult<Vec<Ability>>>()
-        .map_err(|e| format!("Failed to collect active abilities: {}", e))?;
+        .context("Failed to collect active abilities")?;
 
     // Combine: basic_attack first, then active abilities (avoiding duplicates)
     let mut result: Vec<Ability> = Vec::new();
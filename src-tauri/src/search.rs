@@ -0,0 +1,265 @@
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::{AppError, ResultExt};
+
+// ============================================================================
+// Full-Text Search
+// ============================================================================
+//
+// Queries the search_index FTS5 table (migrations/046_search_index.sql), which
+// triggers on the content tables keep current as rows are saved and deleted.
+
+/// Wrapped around matched terms in titles and snippets; the rest of the text is
+/// returned as stored, so callers must escape it before rendering as HTML
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+pub const DOC_TYPES: &[&str] = &["lesson", "puzzle", "mcq", "template", "snippet", "project"];
+
+/// Title matches count this many times more than body matches
+const TITLE_WEIGHT: f64 = 5.0;
+/// Tokens of body text around the match in a snippet
+const SNIPPET_TOKENS: i64 = 16;
+/// Longest query accepted, in terms
+const MAX_QUERY_TERMS: usize = 12;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub doc_id: String,
+    /// Title with matches highlighted
+    pub title: String,
+    /// Best-matching stretch of the body with matches highlighted
+    pub snippet: String,
+    pub language: Option<String>,
+    /// Higher is better; only comparable within one search
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchGroup {
+    pub doc_type: String,
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    pub query: String,
+    pub total: usize,
+    /// Best group first
+    pub groups: Vec<SearchGroup>,
+}
+
+#[derive(Debug, Default)]
+pub struct SearchOptions<'a> {
+    /// Limit to these document types; all when empty
+    pub doc_types: &'a [String],
+    /// Content in this language or language-agnostic content only
+    pub language: Option<&'a str>,
+    pub limit_per_type: usize,
+}
+
+/// Turn user input into an FTS5 query: every word must match, the last one as a
+/// prefix so results appear while typing. Operators and quotes are not passed through.
+fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<&str> = query
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|term| !term.is_empty())
+        .take(MAX_QUERY_TERMS)
+        .collect();
+    let (last, rest) = terms.split_last()?;
+
+    let mut parts: Vec<String> = rest.iter().map(|term| format!("\"{}\"", term)).collect();
+    parts.push(format!("\"{}\"*", last));
+    Some(parts.join(" "))
+}
+
+/// Ranked hits for user_id grouped by document type. Snippets and projects are only
+/// searched among the user's own (and, for snippets, the shared built-in ones).
+pub fn search(conn: &Connection, user_id: i64, query: &str, options: &SearchOptions) -> Result<SearchResults, AppError> {
+    for doc_type in options.doc_types {
+        if !DOC_TYPES.contains(&doc_type.as_str()) {
+            return Err(AppError::Validation(format!("Unknown search type '{}'", doc_type)));
+        }
+    }
+
+    let mut results = SearchResults { query: query.to_string(), total: 0, groups: Vec::new() };
+    let Some(expression) = match_expression(query) else {
+        return Ok(results);
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT doc_type, doc_id, language,
+                    highlight(search_index, 0, '{start}', '{end}'),
+                    snippet(search_index, 1, '{start}', '{end}', '…', {tokens}),
+                    bm25(search_index, {weight}, 1.0)
+             FROM search_index
+             WHERE search_index MATCH ?1
+               AND (owner_id IS NULL OR owner_id = ?2)
+               AND (doc_type != 'project' OR owner_id = ?2)
+               AND (?3 IS NULL OR language IS NULL OR language = ?3)
+             ORDER BY bm25(search_index, {weight}, 1.0)",
+            start = HIGHLIGHT_START,
+            end = HIGHLIGHT_END,
+            tokens = SNIPPET_TOKENS,
+            weight = TITLE_WEIGHT,
+        ))
+        .context("Failed to prepare search")?;
+
+    let rows = stmt
+        .query_map(params![expression, user_id, options.language], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                SearchHit {
+                    doc_id: row.get(1)?,
+                    language: row.get(2)?,
+                    title: row.get(3)?,
+                    snippet: row.get(4)?,
+                    // bm25() is lower-is-better; flip it so scores read naturally
+                    score: -row.get::<_, f64>(5)?,
+                },
+            ))
+        })
+        .context("Failed to run search")?;
+
+    for row in rows {
+        let (doc_type, hit) = row.context("Failed to read search hit")?;
+        if !options.doc_types.is_empty() && !options.doc_types.contains(&doc_type) {
+            continue;
+        }
+
+        // Rows arrive best first, so the first group for a type is its best
+        let group = match results.groups.iter_mut().position(|g| g.doc_type == doc_type) {
+            Some(index) => &mut results.groups[index],
+            None => {
+                results.groups.push(SearchGroup { doc_type, hits: Vec::new() });
+                results.groups.last_mut().unwrap()
+            }
+        };
+        // A lesson is indexed as several passages; keep its best one
+        if group.hits.len() >= options.limit_per_type || group.hits.iter().any(|h| h.doc_id == hit.doc_id) {
+            continue;
+        }
+        group.hits.push(hit);
+        results.total += 1;
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{seeded_db, TestUser};
+
+    fn options(limit_per_type: usize) -> SearchOptions<'static> {
+        SearchOptions { limit_per_type, ..Default::default() }
+    }
+
+    fn hits(results: &SearchResults, doc_type: &str) -> Vec<String> {
+        results
+            .groups
+            .iter()
+            .filter(|g| g.doc_type == doc_type)
+            .flat_map(|g| g.hits.iter().map(|h| h.doc_id.clone()))
+            .collect()
+    }
+
+    fn insert_question(conn: &Connection, id: &str, text: &str) {
+        conn.execute(
+            "INSERT INTO mcq_questions (id, question_text, options, correct_answer_index, difficulty, language)
+             VALUES (?1, ?2, '[\"a\",\"b\"]', 0, 'easy', 'python')",
+            params![id, text],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn seeded_content_is_searchable_and_highlighted() {
+        let conn = seeded_db();
+        let results = search(&conn, 1, "loop", &options(5)).unwrap();
+
+        assert!(results.total > 0);
+        let best = &results.groups[0].hits[0];
+        assert!(
+            best.title.contains(HIGHLIGHT_START) || best.snippet.contains(HIGHLIGHT_START),
+            "{:?}",
+            best
+        );
+        assert!(results.groups.iter().all(|g| g.hits.len() <= 5));
+    }
+
+    #[test]
+    fn index_follows_saves_and_deletes() {
+        let conn = seeded_db();
+        insert_question(&conn, "search-q1", "What does a zygomorphic iterator yield?");
+        assert_eq!(hits(&search(&conn, 1, "zygomorphic", &options(10)).unwrap(), "mcq"), ["search-q1"]);
+
+        conn.execute("UPDATE mcq_questions SET question_text = 'Renamed question' WHERE id = 'search-q1'", []).unwrap();
+        assert_eq!(search(&conn, 1, "zygomorphic", &options(10)).unwrap().total, 0);
+        assert_eq!(hits(&search(&conn, 1, "renamed", &options(10)).unwrap(), "mcq"), ["search-q1"]);
+
+        conn.execute("DELETE FROM mcq_questions WHERE id = 'search-q1'", []).unwrap();
+        assert_eq!(search(&conn, 1, "renamed", &options(10)).unwrap().total, 0);
+    }
+
+    #[test]
+    fn lesson_passages_follow_the_retrieval_index() {
+        let conn = seeded_db();
+        conn.execute(
+            "INSERT INTO retrieval_sources (source_key, source_type, content_hash) VALUES ('lesson:x.json', 'lesson', 'h')",
+            [],
+        )
+        .unwrap();
+        for title in ["Axolotl basics", "Axolotl advanced"] {
+            conn.execute(
+                "INSERT INTO retrieval_passages (source_key, title, content, language, token_count)
+                 VALUES ('lesson:x.json', ?1, 'Loops over axolotl lists', 'python', 4)",
+                params![title],
+            )
+            .unwrap();
+        }
+
+        // Both passages match, but the lesson is listed once
+        assert_eq!(hits(&search(&conn, 1, "axolotl", &options(10)).unwrap(), "lesson"), ["lesson:x.json"]);
+
+        conn.execute("DELETE FROM retrieval_sources WHERE source_key = 'lesson:x.json'", []).unwrap();
+        assert_eq!(search(&conn, 1, "axolotl", &options(10)).unwrap().total, 0);
+    }
+
+    #[test]
+    fn projects_are_private_to_their_owner() {
+        let conn = seeded_db();
+        let owner = TestUser::new("search_owner").create(&conn);
+        let other = TestUser::new("search_other").create(&conn);
+        conn.execute(
+            "INSERT INTO playground_projects (id, user_id, name, language_id, code)
+             VALUES ('proj_search', ?1, 'Quokka tracker', 'python', 'print(1)')",
+            params![owner],
+        )
+        .unwrap();
+
+        assert_eq!(hits(&search(&conn, owner, "quokka", &options(10)).unwrap(), "project"), ["proj_search"]);
+        assert_eq!(search(&conn, other, "quokka", &options(10)).unwrap().total, 0);
+    }
+
+    #[test]
+    fn query_syntax_is_not_passed_through() {
+        let conn = seeded_db();
+        for query in ["\"unbalanced", "title:loop OR", "NEAR(", "*", "   "] {
+            assert!(search(&conn, 1, query, &options(5)).is_ok(), "{}", query);
+        }
+
+        let types = vec!["mcq".to_string()];
+        let results = search(&conn, 1, "loop", &SearchOptions { doc_types: &types, language: None, limit_per_type: 3 }).unwrap();
+        assert!(results.groups.iter().all(|g| g.doc_type == "mcq"));
+
+        let bad = vec!["lessons".to_string()];
+        let err = search(&conn, 1, "loop", &SearchOptions { doc_types: &bad, language: None, limit_per_type: 3 }).unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+}
@@ -0,0 +1,35 @@
+use tauri::AppHandle;
+
+use crate::db;
+use crate::error::AppError;
+use crate::retrieval_commands;
+use crate::search::{self, SearchOptions, SearchResults};
+
+// ============================================================================
+// Search Commands
+// ============================================================================
+
+const DEFAULT_LIMIT_PER_TYPE: usize = 5;
+
+/// Search lessons, puzzles, MCQs, templates, snippets and the user's projects
+#[tauri::command]
+pub fn search(
+    app: AppHandle,
+    user_id: i64,
+    query: String,
+    types: Option<Vec<String>>,
+    language: Option<String>,
+    limit_per_type: Option<usize>,
+) -> Result<SearchResults, AppError> {
+    // Lesson text reaches the search index through the retrieval index
+    retrieval_commands::ensure_index_fresh(&app)?;
+
+    let conn = db::get_connection(&app)?;
+    let types = types.unwrap_or_default();
+    let options = SearchOptions {
+        doc_types: &types,
+        language: language.as_deref(),
+        limit_per_type: limit_per_type.unwrap_or(DEFAULT_LIMIT_PER_TYPE),
+    };
+    search::search(&conn, user_id, &query, &options)
+}
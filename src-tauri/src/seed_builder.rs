@@ -0,0 +1,260 @@
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::clean_export;
+use crate::content_versions;
+use crate::dungeon_level_commands::{self, DungeonLevel};
+use crate::error::{AppError, ResultExt};
+use crate::mcq_commands;
+use crate::migrations;
+use crate::seed_sources::{
+    self, LEVELS_PATH, MCQ_BANK_PATH, SEED_BUILDER_VERSION, SEED_DATABASE_PATH, SEED_MANIFEST_PATH,
+};
+
+// ============================================================================
+// Seed Database Builder
+// ============================================================================
+//
+// Builds seed_database.db from its sources (see seed_sources.rs) into a fresh
+// file: migrations and seed steps, the MCQ bank, imported dungeon challenges and
+// authored levels, with the content version of every shipped row recorded.
+// Timestamps are pinned so the same sources give the same bytes, and a manifest
+// next to the database lets build.rs reject a stale seed.
+
+/// Written over every timestamp the build itself produced, so builds are reproducible
+const SEED_TIMESTAMP: &str = "2024-01-01 00:00:00";
+
+/// Content tables a shipped seed must have rows in
+const CONTENT_TABLES: &[&str] = &[
+    "languages",
+    "puzzles",
+    "puzzle_implementations",
+    "playground_templates",
+    "achievements",
+    "abilities",
+    "enemy_types",
+    "boss_enemies",
+    "custom_enemies",
+    "equipment_items",
+    "consumable_items",
+    "shop_items",
+    "shop_inventory",
+    "quests",
+    "narrative_locations",
+    "narrative_choices",
+    "narrative_outcomes",
+    "dungeon_challenges",
+    "mcq_questions",
+    "dungeon_levels",
+];
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SeedManifest {
+    pub builder_version: u32,
+    pub sources_hash: String,
+    pub database_sha256: String,
+    pub row_counts: BTreeMap<String, i64>,
+}
+
+fn read_source(root: &Path, path: &str) -> Result<String, AppError> {
+    fs::read_to_string(root.join(path))
+        .with_context(|| format!("Failed to read seed source {}", path))
+}
+
+fn row_counts(conn: &Connection) -> Result<BTreeMap<String, i64>, AppError> {
+    CONTENT_TABLES
+        .iter()
+        .map(|table| {
+            let count = conn
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+                .with_context(|| format!("Failed to count {}", table))?;
+            Ok((table.to_string(), count))
+        })
+        .collect()
+}
+
+/// Replace every timestamp at or after started (column defaults, datetime('now') in
+/// imports) with SEED_TIMESTAMP; dates authored in the sources are older and kept
+fn pin_timestamps(conn: &Connection, started: &str) -> Result<(), AppError> {
+    let tables: Vec<String> = conn
+        // Plain tables only; the search index and its shadow tables follow through triggers
+        .prepare("SELECT name FROM pragma_table_list WHERE schema = 'main' AND type = 'table' AND name NOT LIKE 'sqlite_%'")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    for table in tables {
+        let columns: Vec<String> = conn
+            .prepare(&format!("PRAGMA table_info({})", table))?
+            .query_map([], |row| row.get(1))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        for column in columns {
+            // Text that isn't a date makes datetime() NULL, so only timestamps match
+            conn.execute(
+                &format!(
+                    "UPDATE {table} SET {column} = ?1 WHERE typeof({column}) = 'text' AND datetime({column}) >= datetime(?2)"
+                ),
+                params![SEED_TIMESTAMP, started],
+            )
+            .with_context(|| format!("Failed to pin {}.{}", table, column))?;
+        }
+    }
+
+    conn.execute("UPDATE schema_migrations SET execution_ms = 0", [])
+        .context("Failed to pin migration timings")?;
+    Ok(())
+}
+
+/// Fill a freshly migrated database with the non-SQL sources
+fn import_content(conn: &Connection, root: &Path) -> Result<(), AppError> {
+    mcq_commands::import_dungeon_challenges(conn)?;
+
+    let questions = mcq_commands::parse_markdown_questions(&read_source(root, MCQ_BANK_PATH)?)?;
+    let question_count = questions.len();
+    let imported = mcq_commands::import_questions(conn, questions)?;
+    if imported != question_count {
+        return Err(AppError::Validation(format!(
+            "{} has {} questions but only {} were imported; question ids must be unique",
+            MCQ_BANK_PATH, question_count, imported
+        )));
+    }
+
+    let levels: Vec<DungeonLevel> = serde_json::from_str(&read_source(root, LEVELS_PATH)?)
+        .with_context(|| format!("{} is not a JSON array of levels", LEVELS_PATH))?;
+    for level in &levels {
+        dungeon_level_commands::save_level(conn, level)
+            .with_context(|| format!("Failed to import level {}", level.metadata.id))?;
+    }
+    Ok(())
+}
+
+/// Build the seed database from the sources under root (src-tauri) into out,
+/// writing its manifest to manifest_path
+pub fn build_seed_database(root: &Path, out: &Path, manifest_path: &Path) -> Result<SeedManifest, AppError> {
+    let sources_hash = seed_sources::seed_sources_hash(root)
+        .context("Failed to read seed sources")?;
+
+    // Built under a temporary name so a failed build never replaces a good seed
+    let partial = out.with_extension("db.partial");
+    let _ = fs::remove_file(&partial);
+
+    let row_counts = {
+        let mut conn = Connection::open(&partial)
+            .context("Failed to create seed database")?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")
+            .context("Failed to enable foreign keys")?;
+        let started: String = conn.query_row("SELECT datetime('now')", [], |row| row.get(0))?;
+
+        migrations::run_migrations(&mut conn)?;
+        import_content(&conn, root)?;
+        // Installs start tracking from the shipped content they were seeded with
+        content_versions::sync_content(&mut conn)?;
+        clean_export::clear_user_data(&conn)?;
+        pin_timestamps(&conn, &started)?;
+
+        let row_counts = row_counts(&conn)?;
+        let empty: Vec<&str> = row_counts.iter().filter(|(_, count)| **count == 0).map(|(table, _)| table.as_str()).collect();
+        if !empty.is_empty() {
+            return Err(AppError::Validation(format!("Seed database has no rows in {}", empty.join(", "))));
+        }
+
+        conn.execute("VACUUM", [])
+            .context("Failed to vacuum seed database")?;
+        row_counts
+    };
+
+    fs::rename(&partial, out)
+        .context("Failed to save seed database")?;
+
+    let manifest = SeedManifest {
+        builder_version: SEED_BUILDER_VERSION,
+        sources_hash,
+        database_sha256: seed_sources::file_sha256(out).context("Failed to hash seed database")?,
+        row_counts,
+    };
+    let json = serde_json::to_string_pretty(&manifest).context("Failed to serialize seed manifest")?;
+    fs::write(manifest_path, json + "\n")
+        .context("Failed to write seed manifest")?;
+
+    Ok(manifest)
+}
+
+/// Check the committed seed against its manifest and the current sources, returning every problem found
+pub fn verify_seed_database(root: &Path) -> Result<Vec<String>, AppError> {
+    let mut problems: Vec<String> = seed_sources::seed_staleness(root).into_iter().collect();
+
+    let manifest: SeedManifest = serde_json::from_str(&read_source(root, SEED_MANIFEST_PATH)?)
+        .context("Seed manifest is not valid")?;
+    let db = Connection::open_with_flags(root.join(SEED_DATABASE_PATH), OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .context("Failed to open seed database")?;
+
+    for (table, count) in row_counts(&db)? {
+        match manifest.row_counts.get(&table) {
+            Some(expected) if *expected != count => {
+                problems.push(format!("{} has {} rows, manifest says {}", table, count, expected))
+            }
+            Some(_) => {}
+            None => problems.push(format!("{} is missing from the manifest", table)),
+        }
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scalar;
+
+    /// A scratch folder for seed builds, removed on drop
+    struct Scratch {
+        root: std::path::PathBuf,
+    }
+
+    impl Scratch {
+        fn new() -> Self {
+            let root = std::env::temp_dir().join(format!("code-tutor-seed-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(&root).unwrap();
+            Scratch { root }
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn sources_root() -> &'static Path {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn builds_are_byte_identical_and_content_only() {
+        let scratch = Scratch::new();
+        let build = |name: &str| {
+            let out = scratch.root.join(format!("{}.db", name));
+            let manifest = build_seed_database(sources_root(), &out, &scratch.root.join(format!("{}.json", name))).unwrap();
+            (fs::read(&out).unwrap(), manifest, out)
+        };
+
+        let (first, first_manifest, out) = build("first");
+        let (second, second_manifest, _) = build("second");
+        assert!(first == second, "two builds from the same sources differ");
+        assert_eq!(first_manifest, second_manifest);
+
+        let conn = Connection::open(&out).unwrap();
+        assert_eq!(scalar::<i64>(&conn, "SELECT COUNT(*) FROM users", []), 0);
+        assert_eq!(scalar::<String>(&conn, "PRAGMA journal_mode", []), "delete");
+        assert!(!migrations::has_pending(&conn).unwrap());
+    }
+
+    /// Fails when a source changed without `content-admin build-seed` being rerun
+    #[test]
+    fn committed_seed_is_current() {
+        let problems = verify_seed_database(sources_root()).unwrap();
+        assert!(problems.is_empty(), "seed_database.db is stale: {:?}", problems);
+    }
+}
@@ -0,0 +1,88 @@
+// Inputs of the bundled seed_database.db and its staleness check. Compiled into
+// both the app (seed_builder.rs) and build.rs, so it only uses std, sha2 and
+// serde_json.
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Bump when the builder writes something different for the same sources
+pub const SEED_BUILDER_VERSION: u32 = 1;
+
+/// Paths below are relative to src-tauri
+pub const SEED_DATABASE_PATH: &str = "seed_database.db";
+pub const SEED_MANIFEST_PATH: &str = "seed_database.manifest.json";
+pub const MCQ_BANK_PATH: &str = "../docs/multiple-choice.md";
+pub const LEVELS_PATH: &str = "content/levels.json";
+
+/// Directories whose files with the given extension are sources
+pub const SEED_SOURCE_DIRS: &[(&str, &str)] = &[
+    ("migrations", "sql"),
+    ("../course-framework-output/database", "sql"),
+];
+
+/// Individual source files; migrations.rs holds the seed step order and inline seeds
+pub const SEED_SOURCE_FILES: &[&str] = &["src/migrations.rs", MCQ_BANK_PATH, LEVELS_PATH];
+
+/// Every source file, in hashing order
+pub fn seed_source_files(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for (dir, extension) in SEED_SOURCE_DIRS {
+        let mut in_dir: Vec<PathBuf> = fs::read_dir(root.join(dir))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some(*extension))
+            .collect();
+        in_dir.sort();
+        files.extend(in_dir);
+    }
+    files.extend(SEED_SOURCE_FILES.iter().map(|file| root.join(file)));
+    Ok(files)
+}
+
+/// SHA-256 over the builder version and every source's path and contents, with
+/// line endings normalized so Windows checkouts hash the same
+pub fn seed_sources_hash(root: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(SEED_BUILDER_VERSION.to_le_bytes());
+    for path in seed_source_files(root)? {
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        let contents = fs::read_to_string(&path)?.replace("\r\n", "\n");
+        hasher.update((relative.len() as u64).to_le_bytes());
+        hasher.update(relative.as_bytes());
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(contents.as_bytes());
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+pub fn file_sha256(path: &Path) -> io::Result<String> {
+    Ok(format!("{:x}", Sha256::digest(fs::read(path)?)))
+}
+
+/// Why the seed database no longer matches its manifest and sources, if it doesn't
+pub fn seed_staleness(root: &Path) -> Option<String> {
+    let manifest: serde_json::Value = match fs::read_to_string(root.join(SEED_MANIFEST_PATH)) {
+        Ok(text) => match serde_json::from_str(&text) {
+            Ok(manifest) => manifest,
+            Err(e) => return Some(format!("{} is not valid JSON: {}", SEED_MANIFEST_PATH, e)),
+        },
+        Err(_) => return Some(format!("{} is missing", SEED_MANIFEST_PATH)),
+    };
+
+    let sources_hash = match seed_sources_hash(root) {
+        Ok(hash) => hash,
+        Err(e) => return Some(format!("Failed to read seed sources: {}", e)),
+    };
+    if manifest["sources_hash"].as_str() != Some(sources_hash.as_str()) {
+        return Some("its sources have changed since it was built".to_string());
+    }
+
+    match file_sha256(&root.join(SEED_DATABASE_PATH)) {
+        Ok(hash) if manifest["database_sha256"].as_str() == Some(hash.as_str()) => None,
+        Ok(_) => Some(format!("{} was modified after it was built", SEED_DATABASE_PATH)),
+        Err(_) => Some(format!("{} is missing", SEED_DATABASE_PATH)),
+    }
+}
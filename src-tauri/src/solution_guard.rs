@@ -0,0 +1,374 @@
+use regex::Regex;
+use rusqlite::{params, Connection};
+use std::collections::HashSet;
+
+use crate::error::{AppError, ResultExt};
+use crate::prompt_builder::{TutorContent, TutorContextKind, TutorContextRef};
+
+// ============================================================================
+// Solution Leak Guard
+// ============================================================================
+//
+// Compares code in AI tutor responses against the reference solution using
+// token shingles: both sides are tokenized (comments and whitespace dropped),
+// split into overlapping runs of SHINGLE_SIZE tokens, and we measure how much
+// of the solution appears in the response. Runs that already appear in the
+// starter code are ignored, since the learner has those anyway.
+
+const SHINGLE_SIZE: usize = 4;
+const LEAK_THRESHOLD: f64 = 0.6;
+/// Solutions with fewer distinctive shingles than this are too short to judge
+const MIN_SOLUTION_SHINGLES: usize = 8;
+
+lazy_static::lazy_static! {
+    static ref FENCED_BLOCK_RE: Regex = Regex::new(r"(?s)```[^\n]*\n(.*?)```").unwrap();
+    static ref HASH_COMMENT_RE: Regex = Regex::new(r"(?m)#.*$").unwrap();
+    static ref SLASH_COMMENT_RE: Regex = Regex::new(r"(?m)//.*$").unwrap();
+    static ref TOKEN_RE: Regex = Regex::new(
+        r#""(?:[^"\\\n]|\\.)*"|'(?:[^'\\\n]|\\.)*'|[A-Za-z_][A-Za-z0-9_]*|\d+(?:\.\d+)?|[^\sA-Za-z0-9_]"#
+    ).unwrap();
+}
+
+/// A code block found in an AI response (byte range covers the whole fenced block)
+pub struct CodeBlock {
+    pub start: usize,
+    pub end: usize,
+    pub code: String,
+}
+
+pub struct LeakCheck {
+    pub leaked: bool,
+    pub similarity: f64,
+    pub leaked_blocks: Vec<CodeBlock>,
+}
+
+/// Languages whose line comments start with '#' (elsewhere '#' is code, e.g. C# directives)
+fn uses_hash_comments(language: &str) -> bool {
+    matches!(language, "python" | "gdscript" | "ruby")
+}
+
+pub fn tokenize(code: &str, language: &str) -> Vec<String> {
+    let comment_re = if uses_hash_comments(language) { &*HASH_COMMENT_RE } else { &*SLASH_COMMENT_RE };
+    let without_comments = comment_re.replace_all(code, "");
+    TOKEN_RE
+        .find_iter(&without_comments)
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+fn shingles(code: &str, language: &str) -> HashSet<Vec<String>> {
+    tokenize(code, language)
+        .windows(SHINGLE_SIZE)
+        .map(|window| window.to_vec())
+        .collect()
+}
+
+/// Fraction of the solution's distinctive shingles that appear in the candidate (0.0 - 1.0)
+pub fn similarity(candidate: &str, solution: &str, starter: &str, language: &str) -> f64 {
+    let starter_shingles = shingles(starter, language);
+    let solution_shingles: HashSet<Vec<String>> = shingles(solution, language)
+        .into_iter()
+        .filter(|s| !starter_shingles.contains(s))
+        .collect();
+
+    if solution_shingles.len() < MIN_SOLUTION_SHINGLES {
+        return 0.0;
+    }
+
+    let candidate_shingles = shingles(candidate, language);
+    let shared = solution_shingles
+        .iter()
+        .filter(|s| candidate_shingles.contains(*s))
+        .count();
+
+    shared as f64 / solution_shingles.len() as f64
+}
+
+pub fn find_code_blocks(response: &str) -> Vec<CodeBlock> {
+    FENCED_BLOCK_RE
+        .captures_iter(response)
+        .map(|caps| {
+            let whole = caps.get(0).unwrap();
+            CodeBlock {
+                start: whole.start(),
+                end: whole.end(),
+                code: caps[1].to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Check a response for near-complete copies of the reference solution.
+/// Fenced code blocks are checked one by one; a response without fences is checked as a whole.
+pub fn check_response(response: &str, solution: &str, starter: &str, language: &str) -> LeakCheck {
+    let mut blocks = find_code_blocks(response);
+    if blocks.is_empty() {
+        blocks.push(CodeBlock {
+            start: 0,
+            end: response.len(),
+            code: response.to_string(),
+        });
+    }
+
+    let scores: Vec<f64> = blocks
+        .iter()
+        .map(|block| similarity(&block.code, solution, starter, language))
+        .collect();
+    let mut best = scores.iter().cloned().fold(0.0, f64::max);
+
+    let mut leaked_blocks: Vec<CodeBlock> = blocks
+        .iter()
+        .zip(&scores)
+        .filter(|(_, score)| **score >= LEAK_THRESHOLD)
+        .map(|(block, _)| CodeBlock {
+            start: block.start,
+            end: block.end,
+            code: block.code.clone(),
+        })
+        .collect();
+
+    // A solution can also be handed out piece by piece across several blocks
+    if leaked_blocks.is_empty() && blocks.len() > 1 {
+        let combined: Vec<&str> = blocks.iter().map(|block| block.code.as_str()).collect();
+        let combined_score = similarity(&combined.join("\n"), solution, starter, language);
+        best = best.max(combined_score);
+        if combined_score >= LEAK_THRESHOLD {
+            leaked_blocks = blocks;
+        }
+    }
+
+    LeakCheck {
+        leaked: !leaked_blocks.is_empty(),
+        similarity: best,
+        leaked_blocks,
+    }
+}
+
+/// Replace the leaking blocks with `replacement`
+pub fn redact(response: &str, check: &LeakCheck, replacement: &str) -> String {
+    let mut redacted = String::with_capacity(response.len());
+    let mut cursor = 0;
+
+    for block in &check.leaked_blocks {
+        redacted.push_str(&response[cursor..block.start]);
+        redacted.push_str(replacement);
+        cursor = block.end;
+    }
+    redacted.push_str(&response[cursor..]);
+
+    redacted.trim().to_string()
+}
+
+// ============================================================================
+// Tutor Responses
+// ============================================================================
+
+/// A tutor response after the leak check
+pub struct GuardedResponse {
+    pub content: String,
+    /// A near-complete solution was replaced with a hint
+    pub redacted: bool,
+    /// The learner asked to see the solution and the response contains it
+    pub revealed: bool,
+}
+
+fn record_solution_event(
+    conn: &Connection,
+    user_id: Option<i32>,
+    context: &TutorContextRef,
+    action: &str,
+    similarity: f64,
+) -> Result<(), AppError> {
+    let (kind, context_id) = match context.kind {
+        TutorContextKind::Lesson => ("lesson", context.lesson_id.unwrap_or_default().to_string()),
+        TutorContextKind::Puzzle => ("puzzle", context.puzzle_id.clone().unwrap_or_default()),
+        TutorContextKind::Playground => return Ok(()),
+    };
+
+    conn.execute(
+        "INSERT INTO tutor_solution_events (user_id, context_kind, context_id, language, action, similarity)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![user_id, kind, context_id, context.language, action, similarity],
+    )
+    .context("Failed to record solution event")?;
+
+    Ok(())
+}
+
+/// Check a tutor response against the solution of the lesson or puzzle it is about.
+/// Leaks are replaced with a hint unless the learner chose to reveal the solution;
+/// either way the event is recorded in tutor_solution_events.
+pub fn guard_response(
+    conn: &Connection,
+    content: &TutorContent,
+    context: &TutorContextRef,
+    response: String,
+    user_id: Option<i32>,
+    reveal_solution: bool,
+) -> Result<GuardedResponse, AppError> {
+    let (starter, solution, hints) = match content {
+        TutorContent::Lesson(lesson) => (&lesson.starter_code, &lesson.solution_code, &lesson.hints),
+        TutorContent::Puzzle(puzzle) => (&puzzle.starter_code, &puzzle.solution_code, &puzzle.hints),
+        TutorContent::Playground => return Ok(GuardedResponse { content: response, redacted: false, revealed: false }),
+    };
+
+    let check = check_response(&response, solution, starter, &context.language);
+    if !check.leaked {
+        return Ok(GuardedResponse { content: response, redacted: false, revealed: false });
+    }
+
+    if reveal_solution {
+        record_solution_event(conn, user_id, context, "revealed", check.similarity)?;
+        return Ok(GuardedResponse { content: response, redacted: false, revealed: true });
+    }
+
+    record_solution_event(conn, user_id, context, "redacted", check.similarity)?;
+
+    let hint = hints
+        .get(context.hints_used as usize)
+        .map(|hint| format!("Here's a hint instead: {}", hint))
+        .unwrap_or_else(|| "Try breaking the problem into smaller steps and tackle them one at a time.".to_string());
+    let replacement = format!(
+        "> 🔒 I hid some code here because it was basically the full solution, and you'll learn more by writing it yourself! {}",
+        hint
+    );
+
+    Ok(GuardedResponse {
+        content: redact(&response, &check, &replacement),
+        redacted: true,
+        revealed: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_builder::PuzzleContent;
+    use crate::test_support::{scalar, seeded_db};
+
+    const STARTER: &str = "def fizzbuzz(n):\n    # Your code here\n    pass\n";
+
+    const SOLUTION: &str = "def fizzbuzz(n):
+    result = []
+    for i in range(1, n + 1):
+        if i % 15 == 0:
+            result.append(\"FizzBuzz\")
+        elif i % 3 == 0:
+            result.append(\"Fizz\")
+        elif i % 5 == 0:
+            result.append(\"Buzz\")
+        else:
+            result.append(str(i))
+    return result
+";
+
+    fn puzzle() -> TutorContent {
+        TutorContent::Puzzle(PuzzleContent {
+            title: "FizzBuzz".to_string(),
+            hints: vec!["Check divisibility by 15 first.".to_string()],
+            starter_code: STARTER.to_string(),
+            solution_code: SOLUTION.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn context() -> TutorContextRef {
+        TutorContextRef {
+            kind: TutorContextKind::Puzzle,
+            language: "python".to_string(),
+            lesson_id: None,
+            puzzle_id: Some("fizzbuzz".to_string()),
+            user_code: None,
+            last_result: None,
+            hints_used: 0,
+            template_version: None,
+        }
+    }
+
+    #[test]
+    fn verbatim_solution_is_redacted() {
+        let response = format!("Here you go:\n\n```python\n{}```\n\nGood luck!", SOLUTION);
+        let check = check_response(&response, SOLUTION, STARTER, "python");
+        assert!(check.leaked);
+        assert!(check.similarity > 0.99);
+
+        let redacted = redact(&response, &check, "[hidden]");
+        assert_eq!(redacted, "Here you go:\n\n[hidden]\n\nGood luck!");
+    }
+
+    #[test]
+    fn reformatted_or_renamed_solution_is_still_caught() {
+        let reformatted = "def fizzbuzz(n):
+    result = []  # collect answers
+    for i in range(1, n+1):
+        if i%15==0: result.append(\"FizzBuzz\")
+        elif i%3==0: result.append(\"Fizz\")
+        elif i % 5 == 0:
+            # multiples of five
+            result.append(\"Buzz\")
+        else: result.append(str(i))
+    return result";
+        assert!(check_response(reformatted, SOLUTION, STARTER, "python").leaked);
+
+        // Renaming breaks only the shingles the name appears in
+        let renamed = SOLUTION.replace("fizzbuzz", "solve").replace("for i in", "for k in").replace("(i)", "(k)");
+        assert!(check_response(&renamed, SOLUTION, STARTER, "python").leaked);
+    }
+
+    #[test]
+    fn solution_split_across_blocks_is_caught() {
+        let (first, second) = SOLUTION.split_at(SOLUTION.find("        elif i % 3").unwrap());
+        let response = format!("Start with:\n```python\n{}```\nthen:\n```python\n{}```", first, second);
+        let check = check_response(&response, SOLUTION, STARTER, "python");
+        assert!(check.leaked);
+        assert_eq!(check.leaked_blocks.len(), 2);
+    }
+
+    #[test]
+    fn starter_code_and_short_snippets_are_not_flagged() {
+        let starter = format!("Your function starts like this:\n```python\n{}```", STARTER);
+        assert!(!check_response(&starter, SOLUTION, STARTER, "python").leaked);
+
+        let snippet = "Remember you can test divisibility with `i % 3 == 0`.\n```python\nif i % 3 == 0:\n    pass\n```";
+        assert!(!check_response(snippet, SOLUTION, STARTER, "python").leaked);
+
+        // A solution too short to judge never counts as leaked
+        assert_eq!(similarity("print(1)", "print(1)", "", "python"), 0.0);
+    }
+
+    #[test]
+    fn hash_is_code_outside_hash_comment_languages() {
+        assert_eq!(tokenize("x = 1 # note", "python"), vec!["x", "=", "1"]);
+        assert_eq!(tokenize("#region Main // note", "csharp"), vec!["#", "region", "Main"]);
+    }
+
+    #[test]
+    fn guarded_responses_record_redactions_and_reveals() {
+        let conn = seeded_db();
+        let leak = format!("```python\n{}```", SOLUTION);
+        let events = |action: &str| -> i64 {
+            scalar(
+                &conn,
+                "SELECT COUNT(*) FROM tutor_solution_events
+                 WHERE context_kind = 'puzzle' AND context_id = 'fizzbuzz' AND language = 'python'
+                   AND action = ?1 AND similarity > 0.99",
+                params![action],
+            )
+        };
+
+        let clean = guard_response(&conn, &puzzle(), &context(), "Think about `%`.".to_string(), None, false).unwrap();
+        assert!(!clean.redacted && !clean.revealed);
+        assert_eq!(events("redacted") + events("revealed"), 0);
+
+        let redacted = guard_response(&conn, &puzzle(), &context(), leak.clone(), None, false).unwrap();
+        assert!(redacted.redacted);
+        assert!(redacted.content.contains("Check divisibility by 15 first."));
+        assert!(!redacted.content.contains("append"));
+        assert_eq!(events("redacted"), 1);
+
+        let revealed = guard_response(&conn, &puzzle(), &context(), leak.clone(), None, true).unwrap();
+        assert!(revealed.revealed);
+        assert_eq!(revealed.content, leak);
+        assert_eq!(events("revealed"), 1);
+    }
+}
@@ -0,0 +1,246 @@
+// ============================================================================
+// Test Fixtures
+// ============================================================================
+//
+// An in-memory database with every migration and seed applied, the same way
+// initialize_database builds the real one, plus builders for the per-user rows
+// that gameplay reads. Scenario tests call the game services against it:
+//
+//     let conn = seeded_db();
+//     let user_id = TestUser::new("hero").level(3).dungeon_gold(500).create(&conn);
+
+use rusqlite::{params, Connection, Params};
+
+use crate::achievement_commands::initialize_achievement_stats;
+use crate::migrations;
+
+/// A fresh in-memory database with the full schema and seed content
+pub fn seeded_db() -> Connection {
+    let mut conn = Connection::open_in_memory().expect("open in-memory database");
+    conn.execute("PRAGMA foreign_keys = ON", []).expect("enable foreign keys");
+    migrations::run_migrations(&mut conn).expect("migrations apply cleanly");
+    conn
+}
+
+/// Read a single value, panicking if the query fails
+pub fn scalar<T: rusqlite::types::FromSql>(conn: &Connection, sql: &str, params: impl Params) -> T {
+    conn.query_row(sql, params, |row| row.get(0))
+        .unwrap_or_else(|e| panic!("{}: {}", sql, e))
+}
+
+/// A user with a character, currency and dungeon progress. Defaults match a
+/// brand new player as created by get_or_create_user and get_character_stats.
+pub struct TestUser {
+    username: String,
+    level: i64,
+    strength: i64,
+    intelligence: i64,
+    dexterity: i64,
+    health: (i64, i64),
+    mana: (i64, i64),
+    defense: i64,
+    critical_chance: f64,
+    dodge_chance: f64,
+    dungeon_gold: i64,
+    gold: i64,
+    gems: i64,
+    total_xp: i64,
+}
+
+impl TestUser {
+    pub fn new(username: &str) -> Self {
+        TestUser {
+            username: username.to_string(),
+            level: 1,
+            strength: 1,
+            intelligence: 1,
+            dexterity: 1,
+            health: (50, 50),
+            mana: (30, 30),
+            defense: 5,
+            critical_chance: 0.05,
+            dodge_chance: 0.05,
+            dungeon_gold: 100,
+            gold: 0,
+            gems: 0,
+            total_xp: 0,
+        }
+    }
+
+    pub fn level(mut self, level: i64) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn stats(mut self, strength: i64, intelligence: i64, dexterity: i64) -> Self {
+        self.strength = strength;
+        self.intelligence = intelligence;
+        self.dexterity = dexterity;
+        self
+    }
+
+    /// Current and maximum health
+    pub fn health(mut self, current: i64, max: i64) -> Self {
+        self.health = (current, max);
+        self
+    }
+
+    /// Current and maximum mana
+    pub fn mana(mut self, current: i64, max: i64) -> Self {
+        self.mana = (current, max);
+        self
+    }
+
+    pub fn defense(mut self, defense: i64) -> Self {
+        self.defense = defense;
+        self
+    }
+
+    /// Critical and dodge chance, 0.0 to 1.0
+    pub fn luck(mut self, critical_chance: f64, dodge_chance: f64) -> Self {
+        self.critical_chance = critical_chance;
+        self.dodge_chance = dodge_chance;
+        self
+    }
+
+    /// Gold spendable in the dungeon shop (character_stats.current_gold)
+    pub fn dungeon_gold(mut self, gold: i64) -> Self {
+        self.dungeon_gold = gold;
+        self
+    }
+
+    /// Gamification currency (user_currency)
+    pub fn currency(mut self, gold: i64, gems: i64) -> Self {
+        self.gold = gold;
+        self.gems = gems;
+        self
+    }
+
+    /// XP already earned in the dungeon
+    pub fn total_xp(mut self, xp: i64) -> Self {
+        self.total_xp = xp;
+        self
+    }
+
+    pub fn create(self, conn: &Connection) -> i64 {
+        conn.execute("INSERT INTO users (username) VALUES (?)", params![self.username])
+            .expect("insert user");
+        let user_id = conn.last_insert_rowid();
+
+        conn.execute(
+            "INSERT INTO user_currency (user_id, gold, gems) VALUES (?, ?, ?)",
+            params![user_id, self.gold, self.gems],
+        )
+        .expect("insert currency");
+
+        conn.execute(
+            "INSERT INTO character_stats (
+                user_id, level, strength, intelligence, dexterity, charisma, max_health, current_health,
+                max_mana, current_mana, base_damage, defense, critical_chance, dodge_chance,
+                stat_points_available, current_gold
+            ) VALUES (?, ?, ?, ?, ?, 1, ?, ?, ?, ?, 10, ?, ?, ?, 2, ?)",
+            params![
+                user_id,
+                self.level,
+                self.strength,
+                self.intelligence,
+                self.dexterity,
+                self.health.1,
+                self.health.0,
+                self.mana.1,
+                self.mana.0,
+                self.defense,
+                self.critical_chance,
+                self.dodge_chance,
+                self.dungeon_gold
+            ],
+        )
+        .expect("insert character stats");
+
+        conn.execute("INSERT INTO character_equipment (user_id) VALUES (?)", params![user_id])
+            .expect("insert character equipment");
+
+        conn.execute(
+            "INSERT INTO user_dungeon_progress (user_id, current_floor, deepest_floor_reached, total_xp_earned)
+             VALUES (?, 1, 1, ?)",
+            params![user_id, self.total_xp],
+        )
+        .expect("insert dungeon progress");
+
+        conn.execute(
+            "INSERT INTO user_abilities (user_id, ability_id) VALUES (?, 'basic_attack')",
+            params![user_id],
+        )
+        .expect("unlock basic_attack");
+
+        initialize_achievement_stats(conn, user_id as i32).expect("insert achievement stats");
+
+        user_id
+    }
+}
+
+pub fn unlock_ability(conn: &Connection, user_id: i64, ability_id: &str) {
+    conn.execute(
+        "INSERT INTO user_abilities (user_id, ability_id) VALUES (?, ?)",
+        params![user_id, ability_id],
+    )
+    .expect("unlock ability");
+}
+
+pub fn give_consumable(conn: &Connection, user_id: i64, consumable_id: &str, quantity: i64) {
+    conn.execute(
+        "INSERT INTO user_consumable_inventory (user_id, consumable_id, quantity) VALUES (?, ?, ?)",
+        params![user_id, consumable_id, quantity],
+    )
+    .expect("insert consumable");
+}
+
+pub fn give_equipment(conn: &Connection, user_id: i64, equipment_id: &str, quantity: i64) {
+    conn.execute(
+        "INSERT INTO user_equipment_inventory (user_id, equipment_id, quantity) VALUES (?, ?, ?)",
+        params![user_id, equipment_id, quantity],
+    )
+    .expect("insert equipment");
+}
+
+pub fn consumable_quantity(conn: &Connection, user_id: i64, consumable_id: &str) -> i64 {
+    scalar(
+        conn,
+        "SELECT COALESCE(SUM(quantity), 0) FROM user_consumable_inventory WHERE user_id = ? AND consumable_id = ?",
+        params![user_id, consumable_id],
+    )
+}
+
+pub fn equipment_quantity(conn: &Connection, user_id: i64, equipment_id: &str) -> i64 {
+    scalar(
+        conn,
+        "SELECT COALESCE(SUM(quantity), 0) FROM user_equipment_inventory WHERE user_id = ? AND equipment_id = ?",
+        params![user_id, equipment_id],
+    )
+}
+
+/// Place the user at a narrative location with the given completed outcome keys
+/// ("choice_id:outcome_type")
+pub fn narrative_progress(conn: &Connection, user_id: i64, location_id: &str, completed: &[&str]) {
+    conn.execute(
+        "INSERT INTO user_narrative_progress (user_id, floor_number, current_location_id, visited_locations, completed_choices, story_flags)
+         VALUES (?, 1, ?, ?, ?, '{}')",
+        params![
+            user_id,
+            location_id,
+            serde_json::to_string(&[location_id]).unwrap(),
+            serde_json::to_string(completed).unwrap()
+        ],
+    )
+    .expect("insert narrative progress");
+}
+
+/// Make every insert into a table fail, to interrupt a command partway through.
+/// Drop the `fail_<table>` trigger to let inserts through again.
+pub fn fail_inserts_into(conn: &Connection, table: &str) {
+    conn.execute_batch(&format!(
+        "CREATE TEMP TRIGGER fail_{table} BEFORE INSERT ON {table}
+         BEGIN SELECT RAISE(ABORT, 'injected failure'); END;"
+    ))
+    .expect("create failing trigger");
+}
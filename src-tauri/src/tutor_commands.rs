@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::commands::{self, ClaudeMessage};
+use crate::db;
+use crate::prompt_builder::{self, LessonContent, PuzzleContent, TutorContent, TutorContextKind, TutorContextRef, TutorPrompt};
+use crate::puzzle_commands;
+use crate::retrieval::RetrievedPassage;
+use crate::retrieval_commands;
+use crate::solution_guard;
+use crate::error::{AppError, ResultExt};
+
+/// Reference passages added to each tutor prompt
+const TUTOR_PASSAGE_LIMIT: usize = 3;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TutorResponse {
+    pub content: String,
+    pub template_version: u32,
+    /// A near-complete solution was removed from the response
+    pub solution_redacted: bool,
+    /// The learner asked to see the solution and the response contains it
+    pub solution_revealed: bool,
+}
+
+// ============================================================================
+// Tutor Context Resolution
+// ============================================================================
+
+/// Load a lesson by language and id from the app's docs
+pub fn load_lesson(app: &AppHandle, language: &str, lesson_id: i64) -> Result<LessonContent, AppError> {
+    read_lesson(&retrieval_commands::find_docs_dir(app)?, language, lesson_id)
+}
+
+/// Find a lesson in a docs directory. Lesson ids are only unique within a language.
+pub fn read_lesson(docs_dir: &Path, language: &str, lesson_id: i64) -> Result<LessonContent, AppError> {
+    let entries = fs::read_dir(docs_dir.join("lessons"))
+        .context("Failed to read lessons directory")?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+
+        let Ok(content) = fs::read_to_string(&path) else { continue };
+        let Ok(lesson) = serde_json::from_str::<LessonContent>(&content) else { continue };
+
+        if lesson.id == lesson_id && lesson.language == language {
+            return Ok(lesson);
+        }
+    }
+
+    Err(AppError::NotFound(format!("Lesson {} not found for language {}", lesson_id, language)))
+}
+
+/// Load the puzzle description and hints for the student's language
+fn load_puzzle(app: &AppHandle, puzzle_id: &str, language: &str) -> Result<PuzzleContent, AppError> {
+    let puzzle = puzzle_commands::get_puzzle(app.clone(), puzzle_id.to_string())?;
+
+    // Not every puzzle has an implementation in every language; hints are optional
+    let implementation =
+        puzzle_commands::get_puzzle_implementation(app.clone(), puzzle_id.to_string(), language.to_string()).ok();
+
+    let hints = implementation
+        .as_ref()
+        .and_then(|implementation| implementation.hints.as_deref())
+        .and_then(|hints| serde_json::from_str::<Vec<String>>(hints).ok())
+        .unwrap_or_default();
+
+    let concepts = puzzle
+        .concepts
+        .as_deref()
+        .and_then(|concepts| serde_json::from_str::<Vec<String>>(concepts).ok())
+        .unwrap_or_default();
+
+    Ok(PuzzleContent {
+        title: puzzle.title,
+        description: puzzle.description,
+        difficulty: puzzle.difficulty,
+        concepts,
+        hints,
+        starter_code: implementation.as_ref().map(|i| i.starter_code.clone()).unwrap_or_default(),
+        solution_code: implementation.map(|i| i.solution_code).unwrap_or_default(),
+    })
+}
+
+pub fn resolve_tutor_content(app: &AppHandle, context: &TutorContextRef) -> Result<TutorContent, AppError> {
+    match context.kind {
+        TutorContextKind::Lesson => {
+            let lesson_id = context
+                .lesson_id
+                .ok_or_else(|| AppError::Validation("Lesson context requires a lessonId".to_string()))?;
+            Ok(TutorContent::Lesson(load_lesson(app, &context.language, lesson_id)?))
+        }
+        TutorContextKind::Puzzle => {
+            let puzzle_id = context
+                .puzzle_id
+                .as_deref()
+                .ok_or_else(|| AppError::Validation("Puzzle context requires a puzzleId".to_string()))?;
+            Ok(TutorContent::Puzzle(load_puzzle(app, puzzle_id, &context.language)?))
+        }
+        TutorContextKind::Playground => Ok(TutorContent::Playground),
+    }
+}
+
+/// Course passages relevant to the question, best effort: the tutor still works without them
+fn retrieve_passages(app: &AppHandle, content: &TutorContent, context: &TutorContextRef, question: &str) -> Vec<RetrievedPassage> {
+    let topic = match content {
+        TutorContent::Lesson(lesson) => lesson.title.as_str(),
+        TutorContent::Puzzle(puzzle) => puzzle.title.as_str(),
+        TutorContent::Playground => "",
+    };
+    let query = format!("{} {}", question, topic);
+
+    let result = retrieval_commands::ensure_index_fresh(app).and_then(|_| {
+        let conn = db::get_connection(app)?;
+        // Fetch extra so there are enough left after dropping the current lesson
+        retrieval_commands::search_index(&conn, &query, Some(&context.language), TUTOR_PASSAGE_LIMIT * 2)
+    });
+
+    let mut passages = result.unwrap_or_else(|e| {
+        log::warn!("Tutor retrieval failed, continuing without reference passages: {}", e);
+        Vec::new()
+    });
+
+    // The current lesson is already in the prompt
+    if let TutorContent::Lesson(lesson) = content {
+        passages.retain(|p| !(p.source_key.starts_with("lesson:") && p.title.starts_with(lesson.title.as_str())));
+    }
+    passages.truncate(TUTOR_PASSAGE_LIMIT);
+    passages
+}
+
+// ============================================================================
+// Tutor Commands
+// ============================================================================
+
+/// Build the tutor system prompt and messages from a context reference
+#[tauri::command]
+pub fn build_tutor_prompt(
+    app: AppHandle,
+    context: TutorContextRef,
+    question: String,
+    history: Vec<ClaudeMessage>,
+) -> Result<TutorPrompt, AppError> {
+    let content = resolve_tutor_content(&app, &context)?;
+    let passages = retrieve_passages(&app, &content, &context, &question);
+    prompt_builder::build_prompt(&content, &context, &question, &history, &passages)
+}
+
+/// Usage-log context label, e.g. "lesson:5" or "puzzle:fizzbuzz"
+fn usage_context(context: &TutorContextRef) -> String {
+    match context.kind {
+        TutorContextKind::Lesson => format!("lesson:{}", context.lesson_id.unwrap_or_default()),
+        TutorContextKind::Puzzle => format!("puzzle:{}", context.puzzle_id.as_deref().unwrap_or_default()),
+        TutorContextKind::Playground => "playground".to_string(),
+    }
+}
+
+/// Ask the tutor a question. The prompt is built from the context reference, and
+/// the response is checked for near-complete copies of the reference solution.
+/// Leaks are replaced with a hint unless the learner explicitly chose to reveal
+/// the solution, in which case it is recorded as a solution view.
+#[tauri::command]
+pub async fn ask_tutor(
+    app: AppHandle,
+    context: TutorContextRef,
+    question: String,
+    history: Vec<ClaudeMessage>,
+    model: String,
+    user_id: i32,
+    reveal_solution: Option<bool>,
+) -> Result<TutorResponse, AppError> {
+    let content = resolve_tutor_content(&app, &context)?;
+    let passages = retrieve_passages(&app, &content, &context, &question);
+    let prompt = prompt_builder::build_prompt(&content, &context, &question, &history, &passages)?;
+
+    let response = commands::send_claude_message(
+        &app,
+        model,
+        prompt.system_prompt,
+        prompt.messages,
+        user_id,
+        Some(usage_context(&context)),
+    )
+    .await?;
+
+    let guarded = {
+        let conn = db::get_connection(&app)?;
+        solution_guard::guard_response(&conn, &content, &context, response, Some(user_id), reveal_solution.unwrap_or(false))?
+    };
+
+    // Puzzles already track solution views and award no points afterwards
+    if let (true, TutorContextKind::Puzzle, Some(puzzle_id)) = (guarded.revealed, context.kind, context.puzzle_id.clone())
+    {
+        puzzle_commands::record_solution_viewed(app.clone(), user_id, puzzle_id, context.language.clone())?;
+    }
+
+    Ok(TutorResponse {
+        content: guarded.content,
+        template_version: prompt.template_version,
+        solution_redacted: guarded.redacted,
+        solution_revealed: guarded.revealed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lessons_are_read_from_a_docs_dir_not_the_working_directory() {
+        let docs_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../docs");
+
+        let lesson = read_lesson(&docs_dir, "csharp", 16).unwrap();
+        assert_eq!(lesson.title, "The Console Awakens");
+
+        // Ids repeat across languages, so the language has to match too
+        assert!(matches!(read_lesson(&docs_dir, "klingon", 16), Err(AppError::NotFound(_))));
+    }
+}
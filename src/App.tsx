@@ -229,10 +229,13 @@ function App() {
   // Initialize and re-initialize AI provider when settings change
   useEffect(() => {
     const initAI = async () => {
+      await aiService.migrateLegacyApiKey()
+
       if (settings.aiProvider !== 'none') {
         try {
           await aiService.setProvider(settings.aiProvider, {
             claudeApiKey: settings.claudeApiKey,
+            useMockAI: settings.useMockAI,
           })
         } catch (error) {
           console.error('Failed to initialize AI provider:', error)
@@ -1,6 +1,7 @@
 import { useState, useRef, useEffect } from 'react'
 import { useAppStore } from '@/lib/store'
-import { aiService } from '@/lib/ai'
+import { aiService, askTutor } from '@/lib/ai'
+import type { TutorContextRef } from '@/lib/ai'
 import type { AIProviderType } from '@/types/ai'
 import { MarkdownRenderer } from './MarkdownRenderer'
 import { buildPromptContext } from '@/lib/ai/promptContext'
@@ -25,9 +26,9 @@ export function ChatPanel() {
   const [input, setInput] = useState('')
   const [isSending, setIsSending] = useState(false)
   const [showSettings, setShowSettings] = useState(false)
-  const [claudeApiKey, setClaudeApiKey] = useState(
-    localStorage.getItem('claudeApiKey') || ''
-  )
+  const [claudeApiKey, setClaudeApiKey] = useState('')
+  // Question whose answer had the solution hidden, so the learner can choose to reveal it
+  const [redactedQuestion, setRedactedQuestion] = useState<string | null>(null)
   const messagesEndRef = useRef<HTMLDivElement>(null)
 
   const scrollToBottom = () => {
@@ -38,6 +39,33 @@ export function ChatPanel() {
     scrollToBottom()
   }, [chatMessages])
 
+  const buildTutorContext = (): TutorContextRef | null => {
+    if (!currentLesson) return null
+
+    const lastOutput = consoleMessages
+      .filter((msg) => msg.type === 'stdout')
+      .slice(-3)
+      .map((msg) => msg.content)
+      .join('\n')
+    const lastError = consoleMessages
+      .filter((msg) => msg.type === 'error')
+      .slice(-1)
+      .map((msg) => msg.content)
+      .join('\n')
+
+    return {
+      kind: 'lesson',
+      language: currentLesson.language,
+      lessonId: currentLesson.id,
+      userCode: code,
+      lastResult:
+        lastOutput || lastError
+          ? { stdout: lastOutput, stderr: lastError, exitCode: lastError ? 1 : 0 }
+          : null,
+      hintsUsed: hintsRevealed,
+    }
+  }
+
   const handleSend = async () => {
     if (!input.trim() || isSending || !currentLesson) return
 
@@ -93,12 +121,25 @@ export function ChatPanel() {
         throw new Error('No AI provider configured. Please select one in settings.')
       }
 
+      // Claude goes through the backend tutor, which guards against solution leaks
+      const tutorContext = buildTutorContext()
+      if (provider.type === 'claude' && tutorContext) {
+        const result = await askTutor(tutorContext, userMessage, chatMessages, {
+          userId: useAppStore.getState().currentUserId,
+        })
+        addChatMessage({ role: 'assistant', content: result.content })
+        setRedactedQuestion(result.solutionRedacted ? userMessage : null)
+        return
+      }
+
       // Send message with enhanced context and dynamic system prompt
       const legacyContext = {
         lessonTitle: currentLesson.title,
         lessonDescription: currentLesson.description,
         userCode: code,
         chatHistory: chatMessages,
+        userId: useAppStore.getState().currentUserId,
+        usageContext: `lesson:${currentLesson.id}`,
       }
 
       const response = await aiService.sendMessage(
@@ -125,8 +166,36 @@ export function ChatPanel() {
     }
   }
 
+  const handleRevealSolution = async () => {
+    const tutorContext = buildTutorContext()
+    if (!redactedQuestion || !tutorContext) return
+    if (!window.confirm('Show the full solution? This counts as viewing the solution.')) return
+
+    setIsSending(true)
+    try {
+      const result = await askTutor(tutorContext, redactedQuestion, chatMessages, {
+        userId: useAppStore.getState().currentUserId,
+        revealSolution: true,
+      })
+      addChatMessage({ role: 'assistant', content: result.content })
+      setRedactedQuestion(null)
+    } catch (error) {
+      console.error('AI Error:', error)
+      addChatMessage({
+        role: 'assistant',
+        content: `Sorry, I couldn't show the solution: ${error}`,
+      })
+    } finally {
+      setIsSending(false)
+    }
+  }
+
   const handleProviderChange = async (provider: AIProviderType) => {
-    if (provider === 'claude' && !claudeApiKey) {
+    if (
+      provider === 'claude' &&
+      !claudeApiKey &&
+      !(await aiService.checkProviderAvailability('claude'))
+    ) {
       setShowSettings(true)
       return
     }
@@ -140,9 +209,9 @@ export function ChatPanel() {
     }
   }
 
-  const handleSaveApiKey = () => {
-    localStorage.setItem('claudeApiKey', claudeApiKey)
-    handleProviderChange('claude')
+  const handleSaveApiKey = async () => {
+    await handleProviderChange('claude')
+    setClaudeApiKey('')
     setShowSettings(false)
   }
 
@@ -352,6 +421,15 @@ export function ChatPanel() {
 
       {/* Input */}
       <div className="p-4 border-t border-navy-700">
+        {redactedQuestion && (
+          <button
+            onClick={handleRevealSolution}
+            disabled={isSending}
+            className="w-full mb-2 px-3 py-1.5 text-xs text-gray-300 border border-navy-600 rounded hover:bg-navy-700 transition-colors disabled:opacity-50"
+          >
+            🔓 Show the full solution (counts as viewing it)
+          </button>
+        )}
         <div className="flex gap-2">
           <input
             type="text"
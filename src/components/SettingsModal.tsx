@@ -6,6 +6,7 @@ export interface UserSettings {
   theme: 'dark' | 'light'
   aiProvider: AIProviderType
   claudeApiKey: string
+  useMockAI: boolean
   fontSize: number
   editorTabSize: number
   autoSave: boolean
@@ -148,12 +149,34 @@ export function SettingsModal({
                     onChange={(e) =>
                       setSettings({ ...settings, claudeApiKey: e.target.value })
                     }
-                    placeholder="sk-ant-..."
+                    placeholder="sk-ant-... (leave blank to keep the saved key)"
                     className="w-full px-4 py-2 bg-navy-700 border border-navy-600 rounded-lg text-white placeholder-gray-500 focus:border-accent-500 focus:outline-none"
                   />
                   <p className="text-xs text-gray-500 mt-1">
-                    Your API key is stored locally and never sent to our servers
+                    Your API key is stored encrypted on this device and never sent to our servers
                   </p>
+                  <div className="flex items-center justify-between mt-3">
+                    <div>
+                      <span className="text-sm text-gray-400">Use mock AI</span>
+                      <p className="text-xs text-gray-500">
+                        Scripted offline responses for testing, no API key needed
+                      </p>
+                    </div>
+                    <button
+                      onClick={() =>
+                        setSettings({ ...settings, useMockAI: !settings.useMockAI })
+                      }
+                      className={`relative w-12 h-6 rounded-full transition-colors ${
+                        settings.useMockAI ? 'bg-accent-500' : 'bg-navy-600'
+                      }`}
+                    >
+                      <div
+                        className={`absolute top-1 left-1 w-4 h-4 bg-white rounded-full transition-transform ${
+                          settings.useMockAI ? 'translate-x-6' : 'translate-x-0'
+                        }`}
+                      />
+                    </button>
+                  </div>
                 </div>
               )}
 
@@ -23,7 +23,7 @@ export const LevelManager: React.FC<LevelManagerProps> = ({ onLoadLevel, onClose
       const levelList = await invoke<LevelListItem[]>('list_dungeon_levels');
       setLevels(levelList);
     } catch (err) {
-      setError(err as string);
+      setError(String(err));
       console.error('Failed to load levels:', err);
     } finally {
       setLoading(false);
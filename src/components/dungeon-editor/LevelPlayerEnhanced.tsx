@@ -635,14 +635,12 @@ export const LevelPlayerEnhanced: React.FC<LevelPlayerEnhancedProps> = ({
       label: 'Take Loot',
       action: async () => {
         try {
-          // Add items, gold, and XP to actual inventory in database
+          // Add the items to the actual inventory in the database; gold and XP are only paid by combat
           await invoke('add_dungeon_loot_to_inventory', {
             userId,
             lootItems: data.items,
-            gold: data.gold || 0,
-            xp: data.xp || 0
           });
-          if (data.items.length > 0 || data.gold > 0 || data.xp > 0) {
+          if (data.items.length > 0) {
             addNarrative('✅ Loot added to your inventory!');
           }
         } catch (error) {
@@ -1,6 +1,6 @@
 import { useState, useRef, useEffect } from 'react'
 import { useAppStore } from '@/lib/store'
-import { aiService } from '@/lib/ai'
+import { aiService, askTutor } from '@/lib/ai'
 import type { AIProviderType, PromptType } from '@/types/ai'
 import { MarkdownRenderer } from '../MarkdownRenderer'
 import { buildPrompt } from '@/lib/ai/prompts'
@@ -32,9 +32,7 @@ export function PlaygroundChatPanel({ isOpen, onToggle }: PlaygroundChatPanelPro
   const [input, setInput] = useState('')
   const [isSending, setIsSending] = useState(false)
   const [showSettings, setShowSettings] = useState(false)
-  const [claudeApiKey, setClaudeApiKey] = useState(
-    localStorage.getItem('claudeApiKey') || ''
-  )
+  const [claudeApiKey, setClaudeApiKey] = useState('')
   const messagesEndRef = useRef<HTMLDivElement>(null)
 
   const scrollToBottom = () => {
@@ -226,17 +224,33 @@ export function PlaygroundChatPanel({ isOpen, onToggle }: PlaygroundChatPanelPro
         throw new Error('No AI provider configured. Please select one in settings.')
       }
 
+      const history = messages.map((m) => ({
+        id: m.id,
+        role: m.role,
+        content: m.content,
+        timestamp: m.timestamp,
+      }))
+
+      // Claude goes through the backend tutor, like the lesson and puzzle chats
+      if (provider.type === 'claude') {
+        const result = await askTutor(
+          { kind: 'playground', language: playgroundLanguage, userCode: playgroundCode },
+          userMessage,
+          history,
+          { userId: useAppStore.getState().currentUserId }
+        )
+        addMessage('assistant', result.content)
+        return
+      }
+
       // Send message
       const legacyContext = {
         lessonTitle: 'Playground',
         lessonDescription: 'Free-form coding practice',
         userCode: playgroundCode,
-        chatHistory: messages.map((m) => ({
-          id: m.id,
-          role: m.role,
-          content: m.content,
-          timestamp: m.timestamp,
-        })),
+        chatHistory: history,
+        userId: useAppStore.getState().currentUserId,
+        usageContext: 'playground',
       }
 
       const response = await aiService.sendMessage(
@@ -278,7 +292,11 @@ export function PlaygroundChatPanel({ isOpen, onToggle }: PlaygroundChatPanelPro
   }
 
   const handleProviderChange = async (provider: AIProviderType) => {
-    if (provider === 'claude' && !claudeApiKey) {
+    if (
+      provider === 'claude' &&
+      !claudeApiKey &&
+      !(await aiService.checkProviderAvailability('claude'))
+    ) {
       setShowSettings(true)
       return
     }
@@ -292,9 +310,9 @@ export function PlaygroundChatPanel({ isOpen, onToggle }: PlaygroundChatPanelPro
     }
   }
 
-  const handleSaveApiKey = () => {
-    localStorage.setItem('claudeApiKey', claudeApiKey)
-    handleProviderChange('claude')
+  const handleSaveApiKey = async () => {
+    await handleProviderChange('claude')
+    setClaudeApiKey('')
     setShowSettings(false)
   }
 
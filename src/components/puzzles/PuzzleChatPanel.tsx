@@ -1,6 +1,7 @@
 import { useState, useRef, useEffect } from 'react'
 import { useAppStore } from '@/lib/store'
-import { aiService } from '@/lib/ai'
+import { aiService, askTutor } from '@/lib/ai'
+import type { TutorContextRef } from '@/lib/ai'
 import type { AIProviderType } from '@/types/ai'
 import { MarkdownRenderer } from '../MarkdownRenderer'
 import { buildPrompt } from '@/lib/ai/prompts'
@@ -33,9 +34,9 @@ export function PuzzleChatPanel({
   const [input, setInput] = useState('')
   const [isSending, setIsSending] = useState(false)
   const [showSettings, setShowSettings] = useState(false)
-  const [claudeApiKey, setClaudeApiKey] = useState(
-    localStorage.getItem('claudeApiKey') || ''
-  )
+  const [claudeApiKey, setClaudeApiKey] = useState('')
+  // Question whose answer had the solution hidden, so the learner can choose to reveal it
+  const [redactedQuestion, setRedactedQuestion] = useState<string | null>(null)
   const messagesEndRef = useRef<HTMLDivElement>(null)
 
   const scrollToBottom = () => {
@@ -46,6 +47,14 @@ export function PuzzleChatPanel({
     scrollToBottom()
   }, [chatMessages])
 
+  const tutorContext: TutorContextRef = {
+    kind: 'puzzle',
+    language: implementation.languageId,
+    puzzleId: puzzle.id,
+    userCode,
+    hintsUsed: hintsRevealed,
+  }
+
   const handleSend = async () => {
     if (!input.trim() || isSending) return
 
@@ -117,12 +126,24 @@ export function PuzzleChatPanel({
         throw new Error('No AI provider configured. Please select one in settings.')
       }
 
+      // Claude goes through the backend tutor, which guards against solution leaks
+      if (provider.type === 'claude') {
+        const result = await askTutor(tutorContext, userMessage, chatMessages, {
+          userId: useAppStore.getState().currentUserId,
+        })
+        addChatMessage({ role: 'assistant', content: result.content })
+        setRedactedQuestion(result.solutionRedacted ? userMessage : null)
+        return
+      }
+
       // Send message
       const legacyContext = {
         lessonTitle: puzzle.title,
         lessonDescription: puzzle.description,
         userCode,
         chatHistory: chatMessages,
+        userId: useAppStore.getState().currentUserId,
+        usageContext: `puzzle:${puzzle.id}`,
       }
 
       const response = await aiService.sendMessage(
@@ -149,8 +170,35 @@ export function PuzzleChatPanel({
     }
   }
 
+  const handleRevealSolution = async () => {
+    if (!redactedQuestion) return
+    if (!window.confirm('Show the full solution? This counts as viewing the solution and no points will be awarded.')) return
+
+    setIsSending(true)
+    try {
+      const result = await askTutor(tutorContext, redactedQuestion, chatMessages, {
+        userId: useAppStore.getState().currentUserId,
+        revealSolution: true,
+      })
+      addChatMessage({ role: 'assistant', content: result.content })
+      setRedactedQuestion(null)
+    } catch (error) {
+      console.error('AI Error:', error)
+      addChatMessage({
+        role: 'assistant',
+        content: `Sorry, I couldn't show the solution: ${error}`,
+      })
+    } finally {
+      setIsSending(false)
+    }
+  }
+
   const handleProviderChange = async (provider: AIProviderType) => {
-    if (provider === 'claude' && !claudeApiKey) {
+    if (
+      provider === 'claude' &&
+      !claudeApiKey &&
+      !(await aiService.checkProviderAvailability('claude'))
+    ) {
       setShowSettings(true)
       return
     }
@@ -164,9 +212,9 @@ export function PuzzleChatPanel({
     }
   }
 
-  const handleSaveApiKey = () => {
-    localStorage.setItem('claudeApiKey', claudeApiKey)
-    handleProviderChange('claude')
+  const handleSaveApiKey = async () => {
+    await handleProviderChange('claude')
+    setClaudeApiKey('')
     setShowSettings(false)
   }
 
@@ -375,6 +423,15 @@ export function PuzzleChatPanel({
 
       {/* Input */}
       <div className="p-4 border-t border-navy-700">
+        {redactedQuestion && (
+          <button
+            onClick={handleRevealSolution}
+            disabled={isSending}
+            className="w-full mb-2 px-3 py-1.5 text-xs text-gray-300 border border-navy-600 rounded hover:bg-navy-700 transition-colors disabled:opacity-50"
+          >
+            🔓 Show the full solution (counts as viewing it)
+          </button>
+        )}
         <div className="flex gap-2">
           <input
             type="text"
@@ -5,13 +5,16 @@ import {
   executeCombatTurn,
   endCombatVictory,
   endCombatDefeat,
+  attemptEscape,
   getActiveAbilitiesForCombat,
   getChallengeForAction,
   recordChallengeAttempt,
   type ActiveCombat,
   type CombatTurnResult,
   type CombatRewards,
+  type EscapeAttempt,
 } from '../../lib/rpg';
+import { isCommandError } from '../../lib/tauri';
 import type { Ability, EnemyType, BossEnemy, DungeonChallenge } from '../../types/rpg';
 import { CharacterStatsDisplay } from './CharacterStats';
 import type { CharacterStats } from '../../types/rpg';
@@ -65,8 +68,7 @@ export function CombatModal({
   const [phase, setPhase] = useState<CombatPhase>('ability-select');
   const [combatLog, setCombatLog] = useState<CombatLogEntry[]>([]);
   const [loading, setLoading] = useState(true);
-  const [fleeRoll, setFleeRoll] = useState<number | null>(null);
-  const [fleeDamage, setFleeDamage] = useState<number>(0);
+  const [escape, setEscape] = useState<EscapeAttempt | null>(null);
 
   // Animation state
   const [showPlayerAnimation, setShowPlayerAnimation] = useState(false);
@@ -80,7 +82,7 @@ export function CombatModal({
     try {
       setLoading(true);
       const [combatState, activeAbilities] = await Promise.all([
-        isBoss ? startBossCombat(userId, enemy as BossEnemy) : startCombat(userId, enemy as EnemyType),
+        isBoss ? startBossCombat(userId, enemy.id) : startCombat(userId, enemy.id),
         getActiveAbilitiesForCombat(userId),
       ]);
       setCombat(combatState);
@@ -124,7 +126,11 @@ export function CombatModal({
       setPhase('challenge');
     } catch (err) {
       console.error('Failed to load challenge:', err);
-      addLogEntry(combat.combatTurn, 'Error loading challenge!', 'status');
+      addLogEntry(
+        combat.combatTurn,
+        isCommandError(err, 'NOT_FOUND') ? 'No questions available for this challenge!' : 'Error loading challenge!',
+        'status'
+      );
     }
   }
 
@@ -188,7 +194,8 @@ export function CombatModal({
 
       // Determine if attacks happened
       const hasPlayerAttack = result.playerDamageDealt > 0;
-      const hasEnemyAttack = result.playerDamageTaken > 0;
+      // A dodged attack still plays out, it just lands for nothing
+      const hasEnemyAttack = result.playerDamageTaken > 0 || result.isDodged;
 
       // Play animations sequentially and sync damage/logs with animation timing
       if (hasPlayerAttack) {
@@ -222,13 +229,7 @@ export function CombatModal({
           if (result.enemyDefeated) {
             setTimeout(async () => {
               setPhase('victory');
-              const rewards = await endCombatVictory(
-                userId,
-                enemy as EnemyType,
-                result.turnNumber,
-                result.playerDamageDealt,
-                result.playerDamageTaken
-              );
+              const rewards = await endCombatVictory(userId);
               addLogEntry(result.turnNumber, `Victory! ${combat.enemyName} defeated!`, 'status');
               setTimeout(() => onVictory(rewards), 2000);
             }, 500);
@@ -242,11 +243,12 @@ export function CombatModal({
 
               // Show damage log and apply damage in middle of animation (1250ms)
               setTimeout(() => {
-                const dodgeText = result.isDodged ? ' You dodged!' : '';
                 addLogEntry(
                   result.turnNumber,
-                  `${combat.enemyName} dealt ${result.playerDamageTaken} damage!${dodgeText}`,
-                  'damage'
+                  result.isDodged
+                    ? `${combat.enemyName} attacks, but you dodge!`
+                    : `${combat.enemyName} dealt ${result.playerDamageTaken} damage!`,
+                  result.isDodged ? 'status' : 'damage'
                 );
 
                 // Apply player damage
@@ -296,11 +298,12 @@ export function CombatModal({
 
         // Show damage log and apply damage in middle of animation (1250ms)
         setTimeout(() => {
-          const dodgeText = result.isDodged ? ' You dodged!' : '';
           addLogEntry(
             result.turnNumber,
-            `${combat.enemyName} dealt ${result.playerDamageTaken} damage!${dodgeText}`,
-            'damage'
+            result.isDodged
+              ? `${combat.enemyName} attacks, but you dodge!`
+              : `${combat.enemyName} dealt ${result.playerDamageTaken} damage!`,
+            result.isDodged ? 'status' : 'damage'
           );
 
           // Apply player damage
@@ -344,7 +347,14 @@ export function CombatModal({
       }
     } catch (err) {
       console.error('Combat turn failed:', err);
-      addLogEntry(combat.combatTurn, 'Error executing combat turn!', 'status');
+      let message = 'Error executing combat turn!';
+      if (isCommandError(err, 'INSUFFICIENT_MANA')) {
+        message = 'Not enough mana!';
+      } else if (isCommandError(err, 'VALIDATION')) {
+        // Locked or recharging abilities
+        message = `${err.message}!`;
+      }
+      addLogEntry(combat.combatTurn, message, 'status');
       setPhase('ability-select');
     }
   }
@@ -356,12 +366,10 @@ export function CombatModal({
   async function handleFleeAttempt() {
     if (!combat) return;
 
-    // Roll d20 for dexterity check
-    const roll = Math.floor(Math.random() * 20) + 1;
-    setFleeRoll(roll);
+    setEscape(null);
     setPhase('flee-roll');
 
-    addLogEntry(combat.combatTurn, `You attempt to flee! Rolling for dexterity check...`, 'action');
+    addLogEntry(combat.combatTurn, `You attempt to flee! Preparing a dexterity check...`, 'action');
   }
 
   async function handleStartFleeChallenge() {
@@ -384,60 +392,61 @@ export function CombatModal({
   }
 
   async function handleFleeChallengeSubmit() {
-    if (!combat || !challenge || !selectedAnswer || fleeRoll === null) return;
+    if (!combat || !challenge || !selectedAnswer) return;
 
     // Check if answer is correct (supports multiple answers, normalized comparison)
     const challengeSuccess = checkAnswer(selectedAnswer, challenge.correctAnswer || '');
 
-    // Simple modifier - dexterity stat value is the modifier
-    const dexModifier = playerStats.dexterity;
-    const appliedModifier = challengeSuccess ? dexModifier : 0;
-    const total = fleeRoll + appliedModifier;
-
-    // DC is 10 + enemy's damage (representing their speed/ability to catch you)
-    const dc = 10 + Math.floor(combat.enemyDamage / 2);
+    // The backend rolls the d20, adds dexterity on a correct answer and checks it against the enemy
+    let attempt: EscapeAttempt;
+    try {
+      attempt = await attemptEscape(userId, challengeSuccess);
+    } catch (err) {
+      console.error('Escape attempt failed:', err);
+      addLogEntry(combat.combatTurn, 'Error attempting to flee!', 'status');
+      setPhase('ability-select');
+      return;
+    }
+    setEscape(attempt);
 
     addLogEntry(
       combat.combatTurn,
-      `Dexterity check: ${fleeRoll} + ${appliedModifier} = ${total} (DC ${dc})`,
+      `Dexterity check: ${attempt.roll} + ${attempt.modifier} = ${attempt.roll + attempt.modifier} (DC ${attempt.dc})`,
       'action'
     );
 
-    // Enemy gets a free attack (use enemy's damage with defense reduction)
-    const enemyAttack = Math.max(0, combat.enemyDamage - Math.floor(playerStats.defense / 2));
-
-    if (total >= dc) {
-      // Success - dodge the attack!
+    if (attempt.escaped) {
       addLogEntry(combat.combatTurn, `Success! You dodge the enemy's attack and escape!`, 'status');
-      setFleeDamage(0);
-    } else {
-      // Failure - take the attack
-      const damage = Math.max(1, enemyAttack);
-      setFleeDamage(damage);
-      addLogEntry(combat.combatTurn, `Failed! The enemy lands a free attack for ${damage} damage!`, 'damage');
-
-      // Update player health
-      const newHealth = Math.max(0, playerStats.currentHealth - damage);
-      setPlayerStats({
-        ...playerStats,
-        currentHealth: newHealth,
-      });
+      setPhase('flee-result');
 
-      // Check if player died from the flee attack
-      if (newHealth <= 0) {
-        addLogEntry(combat.combatTurn, 'You were struck down while fleeing!', 'status');
-        setPhase('defeat');
-        await endCombatDefeat(userId);
-        setTimeout(() => onDefeat(), 2000);
-        return;
-      }
+      // Show the result for 3 seconds then flee
+      setTimeout(() => {
+        onFlee();
+      }, 3000);
+      return;
     }
 
-    setPhase('flee-result');
+    // Failure - the enemy lands a free attack and the fight goes on
+    addLogEntry(combat.combatTurn, `Failed! The enemy lands a free attack for ${attempt.damage} damage!`, 'damage');
+    setPlayerStats({
+      ...playerStats,
+      currentHealth: attempt.playerCurrentHealth,
+    });
+
+    if (attempt.playerDefeated) {
+      addLogEntry(combat.combatTurn, 'You were struck down while fleeing!', 'status');
+      setPhase('defeat');
+      await endCombatDefeat(userId);
+      setTimeout(() => onDefeat(), 2000);
+      return;
+    }
 
-    // Show the result for 3 seconds then flee
+    setPhase('flee-result');
     setTimeout(() => {
-      onFlee();
+      setPhase('ability-select');
+      setSelectedAbility(null);
+      setChallenge(null);
+      setSelectedAnswer('');
     }, 3000);
   }
 
@@ -682,13 +691,13 @@ export function CombatModal({
             </div>
           )}
 
-          {phase === 'flee-roll' && fleeRoll !== null && (
+          {phase === 'flee-roll' && (
             <div className="text-center py-8">
               <div className="text-6xl mb-4">🎲</div>
               <h3 className="text-3xl font-bold text-blue-400 mb-2">Dexterity Check!</h3>
               <div className="bg-slate-700/50 rounded-lg p-6 mb-4 max-w-md mx-auto">
                 <p className="text-gray-300 mb-4">
-                  You rolled a <span className="text-orange-400 font-bold text-2xl">{fleeRoll}</span> on your d20!
+                  Roll a d20 to slip away from {combat.enemyName}!
                 </p>
                 <p className="text-sm text-gray-400">
                   Answer the coding challenge correctly to add your DEX modifier (+{playerStats.dexterity}) to the roll!
@@ -743,16 +752,20 @@ export function CombatModal({
             </div>
           )}
 
-          {phase === 'flee-result' && fleeRoll !== null && (
+          {phase === 'flee-result' && escape && (
             <div className="text-center py-8">
               <div className="text-6xl mb-4">🏃</div>
               <h3 className="text-3xl font-bold text-blue-400 mb-2">
-                {fleeDamage === 0 ? 'Escaped!' : 'Hit While Fleeing!'}
+                {escape.escaped ? 'Escaped!' : 'Hit While Fleeing!'}
               </h3>
+              <p className="text-gray-300 mb-2">
+                You rolled a <span className="text-orange-400 font-bold text-2xl">{escape.roll}</span>
+                {' '}+ {escape.modifier} against DC {escape.dc}
+              </p>
               <p className="text-gray-300">
-                {fleeDamage === 0
+                {escape.escaped
                   ? 'You successfully dodged the enemy\'s attack and escaped!'
-                  : `You took ${fleeDamage} damage while fleeing!`}
+                  : `You took ${escape.damage} damage and couldn't get away!`}
               </p>
             </div>
           )}
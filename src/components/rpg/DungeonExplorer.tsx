@@ -22,8 +22,6 @@ import {
   // Consumables
   getConsumableInventory,
   useConsumable,
-  // Combat
-  endCombatFlee,
 } from '../../lib/rpg';
 import type {
   DungeonFloor,
@@ -436,15 +434,9 @@ export function DungeonExplorer({ userId, onClose }: DungeonExplorerProps) {
   }
 
   async function handleFlee() {
+    // The escape already ended the fight in the database
     console.log('Fled from combat!');
 
-    // End combat in the database (clear in_combat flag)
-    try {
-      await endCombatFlee(userId);
-    } catch (err) {
-      console.error('Failed to end combat flee in database:', err);
-    }
-
     setCurrentOutcome({
       id: 'combat_flee',
       choiceId: '',
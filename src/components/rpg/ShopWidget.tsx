@@ -1,5 +1,6 @@
 import React, { useEffect, useState } from 'react';
 import { getShopItems, purchaseShopItem, getCharacterStats, getShopRefreshState, forceShopRefresh, type ShopRefreshState } from '../../lib/rpg';
+import { isCommandError } from '../../lib/tauri';
 import type { ShopItemDisplay, CharacterStats, EquipmentItem, ConsumableItem } from '../../types/rpg';
 
 interface ShopWidgetProps {
@@ -105,6 +106,10 @@ export function ShopWidget({ userId, onPurchase }: ShopWidgetProps) {
     } catch (err) {
       console.error('Failed to purchase item:', err);
       setError(err instanceof Error ? err.message : 'Failed to purchase item');
+      if (isCommandError(err, 'INSUFFICIENT_FUNDS')) {
+        // Gold changed elsewhere since the shop loaded; show the real balance
+        setStats(await getCharacterStats(userId));
+      }
     } finally {
       setPurchasing(null);
     }
@@ -0,0 +1,71 @@
+import { invoke } from '@/lib/tauri'
+
+export type ReviewCategory = 'naming' | 'complexity' | 'idioms'
+
+export interface CategoryScore {
+  category: ReviewCategory
+  /** 1 (needs a lot of work) to 5 (excellent) */
+  score: number
+  comment: string
+}
+
+export interface LineComment {
+  /** 1-based line in the reviewed solution */
+  line: number
+  severity: 'praise' | 'suggestion' | 'issue'
+  comment: string
+}
+
+export interface CodeReview {
+  rubricVersion: number
+  model: string
+  summary: string
+  categories: CategoryScore[]
+  lineComments: LineComment[]
+  overallScore: number
+}
+
+export interface StoredCodeReview {
+  review: CodeReview
+  reviewedSolution: string
+  reviewedAt: string
+}
+
+const DEFAULT_MODEL = 'claude-4-sonnet-20250514'
+
+/** Review the accepted solution of a solved puzzle; the review is saved with the puzzle progress */
+export async function reviewPuzzleSolution(
+  userId: number,
+  puzzleId: string,
+  languageId: string,
+  model: string = DEFAULT_MODEL
+): Promise<StoredCodeReview> {
+  return invoke<StoredCodeReview>('review_puzzle_solution', { userId, puzzleId, languageId, model })
+}
+
+export async function getPuzzleCodeReview(
+  userId: number,
+  puzzleId: string,
+  languageId: string
+): Promise<StoredCodeReview | null> {
+  return invoke<StoredCodeReview | null>('get_puzzle_code_review', { userId, puzzleId, languageId })
+}
+
+/** Review a completed lesson solution (not stored by the backend) */
+export async function reviewLessonSolution(
+  language: string,
+  lessonId: number,
+  solution: string,
+  options: { userId?: number | null; model?: string } = {}
+): Promise<CodeReview> {
+  // AI calls are charged to the learner's own budget
+  if (options.userId == null) throw new Error('Select a profile to get a code review')
+
+  return invoke<CodeReview>('review_lesson_solution', {
+    language,
+    lessonId,
+    solution,
+    model: options.model ?? DEFAULT_MODEL,
+    userId: options.userId,
+  })
+}
@@ -1,6 +1,8 @@
 import type { AIProvider, AIProviderType, ChatContext } from '@/types/ai'
 import { OllamaProvider } from './ollama'
 import { ClaudeProvider } from './claude'
+import { invoke } from '@/lib/tauri'
+import { takeLegacyApiKey } from '@/lib/preferences'
 
 /**
  * AI service manager - handles provider selection and messaging
@@ -19,7 +21,7 @@ export class AIService {
    */
   async setProvider(
     type: AIProviderType,
-    config?: { claudeApiKey?: string; ollamaModel?: string }
+    config?: { claudeApiKey?: string; ollamaModel?: string; useMockAI?: boolean }
   ): Promise<void> {
     switch (type) {
       case 'ollama':
@@ -30,10 +32,17 @@ export class AIService {
         break
 
       case 'claude':
-        if (!config?.claudeApiKey) {
-          throw new Error('Claude API key is required')
+        // A newly entered key is handed to the backend, which stores it encrypted
+        if (config?.claudeApiKey) {
+          await this.saveClaudeApiKey(config.claudeApiKey)
         }
-        this.claudeProvider = new ClaudeProvider(config.claudeApiKey)
+        // Route Claude calls to the backend's scripted mock instead of the API
+        if (config?.useMockAI !== undefined) {
+          await invoke('set_llm_backend', {
+            backend: config.useMockAI ? 'mock' : 'claude',
+          })
+        }
+        this.claudeProvider = this.claudeProvider ?? new ClaudeProvider()
         this.currentProvider = this.claudeProvider
         break
 
@@ -43,6 +52,31 @@ export class AIService {
     }
   }
 
+  /**
+   * Store a Claude API key in the backend credential store
+   */
+  async saveClaudeApiKey(apiKey: string, passphrase?: string): Promise<void> {
+    await invoke('set_ai_provider_key', {
+      provider: 'claude',
+      apiKey,
+      passphrase: passphrase || null,
+    })
+  }
+
+  /**
+   * Move an API key saved by older versions out of web storage and into the backend
+   */
+  async migrateLegacyApiKey(): Promise<void> {
+    const legacyKey = takeLegacyApiKey()
+    if (!legacyKey) return
+
+    try {
+      await this.saveClaudeApiKey(legacyKey)
+    } catch (error) {
+      console.error('Failed to migrate stored API key:', error)
+    }
+  }
+
   /**
    * Get current provider
    */
@@ -58,7 +92,7 @@ export class AIService {
       case 'ollama':
         return this.ollamaProvider.isAvailable()
       case 'claude':
-        return this.claudeProvider?.isAvailable() ?? false
+        return (this.claudeProvider ?? new ClaudeProvider()).isAvailable()
       case 'none':
         return false
     }
@@ -115,3 +149,15 @@ export const aiService = new AIService()
 export { OllamaProvider } from './ollama'
 export { ClaudeProvider } from './claude'
 export { SYSTEM_PROMPT, buildChatPrompt, buildHintPrompt } from './prompts'
+export { askTutor } from './tutor'
+export type { TutorContextRef, TutorResponse } from './tutor'
+export {
+  generateMcqDrafts,
+  listMcqDrafts,
+  updateMcqDraft,
+  approveMcqDraft,
+  rejectMcqDraft,
+} from './mcqGeneration'
+export type { McqGenerationRequest, McqGenerationResult, McqDraft, McqDraftStatus } from './mcqGeneration'
+export { reviewPuzzleSolution, getPuzzleCodeReview, reviewLessonSolution } from './codeReview'
+export type { CodeReview, StoredCodeReview, CategoryScore, LineComment } from './codeReview'
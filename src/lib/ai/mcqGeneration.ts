@@ -0,0 +1,82 @@
+import { invoke } from '@/lib/tauri'
+import type { QuestionDifficulty } from '@/types/mcqQuestions'
+
+/** Generate from a lesson (lessonId) or a free-form topic */
+export interface McqGenerationRequest {
+  language: string
+  lessonId?: number
+  topic?: string
+  difficulty: QuestionDifficulty
+  count?: number
+  model?: string
+  userId?: number | null
+}
+
+export type McqDraftStatus = 'pending' | 'approved' | 'rejected'
+
+export interface McqDraft {
+  id: number
+  batchId: string
+  questionText: string
+  explanation: string
+  options: string // JSON array of 4 strings
+  correctAnswerIndex: number
+  difficulty: QuestionDifficulty
+  topic?: string | null
+  language: string
+  tags?: string | null // JSON array
+  sourceKind: 'lesson' | 'topic'
+  sourceRef: string
+  model: string
+  status: McqDraftStatus
+  reviewNotes?: string | null
+  approvedQuestionId?: string | null
+  createdAt: string
+  reviewedAt?: string | null
+}
+
+export interface RejectedQuestion {
+  index: number
+  question?: string | null
+  reason: string
+}
+
+export interface McqGenerationResult {
+  batchId: string
+  drafts: McqDraft[]
+  rejected: RejectedQuestion[]
+}
+
+/**
+ * Ask the model for questions; valid ones land in the draft review queue
+ * and invalid ones come back in `rejected` with the reason
+ */
+export async function generateMcqDrafts(request: McqGenerationRequest): Promise<McqGenerationResult> {
+  // AI calls are charged to the requesting profile's own budget
+  if (request.userId == null) throw new Error('Select a profile to generate questions')
+
+  return invoke<McqGenerationResult>('generate_mcq_drafts', {
+    request: {
+      ...request,
+      model: request.model ?? 'claude-4-sonnet-20250514',
+      userId: request.userId,
+    },
+  })
+}
+
+export async function listMcqDrafts(status?: McqDraftStatus): Promise<McqDraft[]> {
+  return invoke<McqDraft[]>('list_mcq_drafts', { status: status ?? null })
+}
+
+export async function updateMcqDraft(draft: McqDraft): Promise<McqDraft> {
+  return invoke<McqDraft>('update_mcq_draft', { draft })
+}
+
+/** Publish a draft into the question bank, returning the new question id */
+export async function approveMcqDraft(draftId: number, questionId?: string): Promise<string> {
+  return invoke<string>('approve_mcq_draft', { draftId, questionId: questionId ?? null })
+}
+
+export async function rejectMcqDraft(draftId: number, notes?: string): Promise<void> {
+  return invoke<void>('reject_mcq_draft', { draftId, notes: notes ?? null })
+}
@@ -0,0 +1,52 @@
+import { invoke } from '@/lib/tauri'
+import type { ChatMessage } from '@/types/ai'
+
+/**
+ * Reference to what the learner is working on. The backend resolves the
+ * lesson or puzzle itself and builds the tutor prompt from it.
+ */
+export interface TutorContextRef {
+  kind: 'lesson' | 'puzzle' | 'playground'
+  language: string
+  lessonId?: number
+  puzzleId?: string
+  userCode?: string
+  lastResult?: {
+    stdout: string
+    stderr: string
+    exitCode: number
+  } | null
+  hintsUsed?: number
+}
+
+export interface TutorResponse {
+  content: string
+  templateVersion: number
+  solutionRedacted: boolean
+  solutionRevealed: boolean
+}
+
+/**
+ * Ask the Claude tutor through the backend, which builds the prompt and
+ * hides near-complete solutions unless the learner chose to reveal them
+ */
+export async function askTutor(
+  context: TutorContextRef,
+  question: string,
+  history: ChatMessage[],
+  options: { userId?: number | null; revealSolution?: boolean; model?: string } = {}
+): Promise<TutorResponse> {
+  // AI calls are charged to the learner's own budget
+  if (options.userId == null) throw new Error('Select a profile to ask the tutor')
+
+  return invoke<TutorResponse>('ask_tutor', {
+    context,
+    question,
+    history: history
+      .filter((msg) => msg.role !== 'system')
+      .map((msg) => ({ role: msg.role, content: msg.content })),
+    model: options.model ?? 'claude-4-sonnet-20250514',
+    userId: options.userId,
+    revealSolution: options.revealSolution ?? false,
+  })
+}
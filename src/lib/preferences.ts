@@ -4,12 +4,14 @@ import type { LanguageId } from '@/types/language'
 const PREFERENCES_KEY = 'code-tutor-preferences'
 const ONBOARDING_KEY = 'code-tutor-onboarding-complete'
 const STARTING_LANGUAGE_KEY = 'code-tutor-starting-language'
+const LEGACY_API_KEY_KEY = 'claudeApiKey'
 
 // Default settings
 export const DEFAULT_SETTINGS: UserSettings = {
   theme: 'dark',
   aiProvider: 'none',
   claudeApiKey: '',
+  useMockAI: false,
   fontSize: 14,
   editorTabSize: 4,
   autoSave: true,
@@ -22,7 +24,8 @@ export function loadPreferences(): UserSettings {
     const stored = localStorage.getItem(PREFERENCES_KEY)
     if (stored) {
       const parsed = JSON.parse(stored)
-      return { ...DEFAULT_SETTINGS, ...parsed }
+      // API keys live in the backend credential store, never in web storage
+      return { ...DEFAULT_SETTINGS, ...parsed, claudeApiKey: '' }
     }
   } catch (error) {
     console.error('Failed to load preferences:', error)
@@ -33,12 +36,40 @@ export function loadPreferences(): UserSettings {
 // Save preferences to localStorage
 export function savePreferences(settings: UserSettings): void {
   try {
-    localStorage.setItem(PREFERENCES_KEY, JSON.stringify(settings))
+    localStorage.setItem(
+      PREFERENCES_KEY,
+      JSON.stringify({ ...settings, claudeApiKey: '' })
+    )
   } catch (error) {
     console.error('Failed to save preferences:', error)
   }
 }
 
+// Remove an API key saved in web storage by older versions and return it
+export function takeLegacyApiKey(): string | null {
+  try {
+    let apiKey = localStorage.getItem(LEGACY_API_KEY_KEY)
+    localStorage.removeItem(LEGACY_API_KEY_KEY)
+
+    const stored = localStorage.getItem(PREFERENCES_KEY)
+    if (stored) {
+      const parsed = JSON.parse(stored)
+      if (parsed.claudeApiKey) {
+        apiKey = apiKey || parsed.claudeApiKey
+        localStorage.setItem(
+          PREFERENCES_KEY,
+          JSON.stringify({ ...parsed, claudeApiKey: '' })
+        )
+      }
+    }
+
+    return apiKey || null
+  } catch (error) {
+    console.error('Failed to read legacy API key:', error)
+    return null
+  }
+}
+
 // Check if onboarding has been completed
 export function hasCompletedOnboarding(): boolean {
   try {
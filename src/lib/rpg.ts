@@ -57,9 +57,7 @@ import {
   convertAbilityWithUnlockStatus,
   convertDungeonFloor,
   convertEnemyType,
-  convertEnemyTypeToRaw,
   convertBossEnemy,
-  convertBossEnemyToRaw,
   convertDungeonChallenge,
   convertNarrativeLocation,
   convertNarrativeChoice,
@@ -578,16 +576,25 @@ export interface CombatTurnResult {
   turnNumber: number;
 }
 
+export interface EscapeAttempt {
+  roll: number;
+  modifier: number;
+  dc: number;
+  escaped: boolean;
+  damage: number;
+  playerCurrentHealth: number;
+  playerDefeated: boolean;
+}
+
 export interface CombatRewards {
   xpGained: number;
   goldGained: number;
   itemsLooted: string[];
 }
 
-export async function startCombat(userId: number, enemy: EnemyType): Promise<ActiveCombat> {
-  // Convert enemy to raw format for Rust backend
-  const enemyRaw = convertEnemyTypeToRaw(enemy);
-  const result = await invoke<any>('start_combat', { userId, enemy: enemyRaw });
+export async function startCombat(userId: number, enemyId: string): Promise<ActiveCombat> {
+  // The backend loads the enemy's stats itself and holds the fight's state
+  const result = await invoke<any>('start_combat', { userId, enemyId });
   return {
     userId: result.user_id,
     enemyId: result.enemy_id,
@@ -605,10 +612,8 @@ export async function startCombat(userId: number, enemy: EnemyType): Promise<Act
   };
 }
 
-export async function startBossCombat(userId: number, boss: BossEnemy): Promise<ActiveCombat> {
-  // Convert boss to raw format for Rust backend
-  const bossRaw = convertBossEnemyToRaw(boss);
-  const result = await invoke<any>('start_boss_combat', { userId, boss: bossRaw });
+export async function startBossCombat(userId: number, bossId: string): Promise<ActiveCombat> {
+  const result = await invoke<any>('start_boss_combat', { userId, bossId });
   return {
     userId: result.user_id,
     enemyId: result.enemy_id,
@@ -650,22 +655,9 @@ export async function executeCombatTurn(
   };
 }
 
-export async function endCombatVictory(
-  userId: number,
-  enemy: EnemyType,
-  turnsTaken: number,
-  damageDealt: number,
-  damageTaken: number
-): Promise<CombatRewards> {
-  // Convert enemy to raw format for Rust backend
-  const enemyRaw = convertEnemyTypeToRaw(enemy);
-  const result = await invoke<any>('end_combat_victory', {
-    userId,
-    enemy: enemyRaw,
-    turnsTaken,
-    damageDealt,
-    damageTaken,
-  });
+export async function endCombatVictory(userId: number): Promise<CombatRewards> {
+  // Rewards are worked out from the fight the backend recorded
+  const result = await invoke<any>('end_combat_victory', { userId });
   return {
     xpGained: result.xp_gained,
     goldGained: result.gold_gained,
@@ -677,8 +669,18 @@ export async function endCombatDefeat(userId: number): Promise<void> {
   await invoke('end_combat_defeat', { userId });
 }
 
-export async function endCombatFlee(userId: number): Promise<void> {
-  await invoke('end_combat_flee', { userId });
+export async function attemptEscape(userId: number, challengeSuccess: boolean): Promise<EscapeAttempt> {
+  // The backend rolls the d20 and either ends the fight or applies the enemy's free attack
+  const result = await invoke<any>('attempt_escape', { userId, challengeSuccess });
+  return {
+    roll: result.roll,
+    modifier: result.modifier,
+    dc: result.dc,
+    escaped: result.escaped,
+    damage: result.damage,
+    playerCurrentHealth: result.player_current_health,
+    playerDefeated: result.player_defeated,
+  };
 }
 
 // ============================================================================
@@ -546,6 +546,7 @@ export const useAppStore = create<AppState>((set, get) => ({
       if (settings.aiProvider !== 'none') {
         await aiService.setProvider(settings.aiProvider, {
           claudeApiKey: settings.claudeApiKey,
+          useMockAI: settings.useMockAI,
         })
       } else {
         await aiService.setProvider('none')
@@ -554,7 +555,11 @@ export const useAppStore = create<AppState>((set, get) => ({
       console.error('Failed to set AI provider:', error)
     }
 
-    set({ settings, aiProvider: settings.aiProvider })
+    // The key has been handed to the backend; don't keep it in app state
+    set({
+      settings: { ...settings, claudeApiKey: '' },
+      aiProvider: settings.aiProvider,
+    })
   },
 
   // Runtime status tracking
@@ -1,4 +1,5 @@
 import { invoke as tauriInvoke } from '@tauri-apps/api/core'
+import type { AppErrorCode, AppErrorPayload } from '@/types/errors'
 import type { ExecutionResult } from '@/types/execution'
 import type { SupportedLanguage } from '@/types/language'
 
@@ -24,15 +25,52 @@ export function isTauriAvailable(): boolean {
          typeof window.__TAURI_INTERNALS__.invoke === 'function'
 }
 
+/**
+ * Error thrown when a Tauri command fails
+ * Branch on `code`; `message` is safe to show to the user
+ */
+export class CommandError extends Error {
+  readonly code: AppErrorCode
+
+  constructor(payload: AppErrorPayload) {
+    super(payload.message)
+    this.name = 'CommandError'
+    this.code = payload.code
+  }
+
+  // Keep `${err}` rendering the bare message, as it did when commands rejected with strings
+  toString(): string {
+    return this.message
+  }
+}
+
+function isAppErrorPayload(value: unknown): value is AppErrorPayload {
+  return typeof value === 'object' &&
+         value !== null &&
+         typeof (value as AppErrorPayload).code === 'string' &&
+         typeof (value as AppErrorPayload).message === 'string'
+}
+
+/**
+ * Check whether an error came from a command, optionally with a specific code
+ */
+export function isCommandError(error: unknown, code?: AppErrorCode): error is CommandError {
+  return error instanceof CommandError && (code === undefined || error.code === code)
+}
+
 /**
  * Safe invoke wrapper that checks Tauri availability first
- * Throws a helpful error if Tauri is not available
+ * Throws a helpful error if Tauri is not available, and a CommandError if the command fails
  */
 export async function invoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
   if (!isTauriAvailable()) {
     throw new Error(`Tauri runtime not available. Cannot execute command: ${cmd}`)
   }
-  return tauriInvoke<T>(cmd, args)
+  try {
+    return await tauriInvoke<T>(cmd, args)
+  } catch (error) {
+    throw isAppErrorPayload(error) ? new CommandError(error) : error
+  }
 }
 
 /**
@@ -73,17 +111,12 @@ export async function executeCode(
   timeoutMs?: number,
   customExecutablePath?: string
 ): Promise<ExecutionResult> {
-  try {
-    const result = await invoke<ExecutionResult>('execute_code', {
-      language,
-      code,
-      timeoutMs,
-      customExecutablePath,
-    })
-    return result
-  } catch (error) {
-    throw new Error(`${error}`)
-  }
+  return invoke<ExecutionResult>('execute_code', {
+    language,
+    code,
+    timeoutMs,
+    customExecutablePath,
+  })
 }
 
 /**
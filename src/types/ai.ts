@@ -19,6 +19,9 @@ export interface ChatContext {
     exitCode: number
   }
   chatHistory: ChatMessage[]
+  // Recorded with each call for usage reports and budgets
+  userId?: number | null
+  usageContext?: string
 }
 
 /**
@@ -136,6 +139,9 @@ export type PromptType =
 
 export type AIProviderType = 'ollama' | 'claude' | 'none'
 
+/** Where the backend answers Claude calls: the real API or scripted fixtures */
+export type LlmBackend = 'claude' | 'mock'
+
 export interface AIProvider {
   name: string
   type: AIProviderType
@@ -158,3 +164,19 @@ export interface AIConfig {
   ollamaModel?: string
   claudeApiKey?: string
 }
+
+/**
+ * Status of a provider API key held in the backend credential store.
+ * The key itself never leaves the backend.
+ */
+export interface AIKeyStatus {
+  provider: string
+  configured: boolean
+  keySource: 'machine' | 'passphrase' | null
+  locked: boolean
+  keyHint: string | null
+  createdAt: string | null
+  rotatedAt: string | null
+  lastTestedAt: string | null
+  lastTestOk: boolean | null
+}
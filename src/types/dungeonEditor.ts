@@ -240,6 +240,7 @@ export interface LevelMetadata {
   isPublished: boolean;
   version: number;
   tags?: string[];
+  sequenceOrder?: number | null; // campaign position; omitted on save keeps the stored order
 }
 
 export interface DungeonLevel {
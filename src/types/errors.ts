@@ -0,0 +1,15 @@
+// Errors returned by Tauri commands (see src-tauri/src/error.rs)
+export type AppErrorCode =
+  | 'NOT_FOUND'
+  | 'INSUFFICIENT_FUNDS'
+  | 'INSUFFICIENT_MANA'
+  | 'VALIDATION'
+  | 'DATABASE'
+  | 'EXECUTION'
+  | 'AI'
+  | 'INTERNAL'
+
+export interface AppErrorPayload {
+  code: AppErrorCode
+  message: string
+}